@@ -4,7 +4,7 @@ use alloy_primitives::Address;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::{ChainId, DexProtocol};
+use crate::{ChainId, CoreError, DexProtocol, Pool};
 
 /// RPC endpoint configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +33,28 @@ pub struct ChainConfig {
     pub flashbots_relay: Option<String>,
 }
 
+impl ChainConfig {
+    /// Reject DEXes that aren't deployed on this chain
+    pub fn validate(&self) -> Result<(), CoreError> {
+        validate_dexes(self.chain, self.dexes.keys().copied())
+    }
+}
+
+/// Reject any of `dexes` that isn't deployed on `chain`, so a config
+/// enabling one there fails instead of silently finding nothing
+pub fn validate_dexes(chain: ChainId, dexes: impl IntoIterator<Item = DexProtocol>) -> Result<(), CoreError> {
+    for dex in dexes {
+        if !dex.is_available_on(chain) {
+            return Err(CoreError::InvalidConfig(format!(
+                "{} is not available on {}",
+                dex.name(),
+                chain
+            )));
+        }
+    }
+    Ok(())
+}
+
 /// Execution configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionConfig {
@@ -42,6 +64,16 @@ pub struct ExecutionConfig {
     pub deadline_seconds: u64,
     pub use_flashbots: bool,
     pub max_retries: u32,
+    /// Simulations allowed to run at once across RPCs and pre-submit checks
+    #[serde(default = "default_max_concurrent_simulations")]
+    pub max_concurrent_simulations: usize,
+    /// Build, simulate and fetch nonces as usual but never broadcast
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+fn default_max_concurrent_simulations() -> usize {
+    8
 }
 
 impl Default for ExecutionConfig {
@@ -53,6 +85,8 @@ impl Default for ExecutionConfig {
             deadline_seconds: 120,
             use_flashbots: true,
             max_retries: 2,
+            max_concurrent_simulations: default_max_concurrent_simulations(),
+            dry_run: false,
         }
     }
 }
@@ -102,6 +136,108 @@ impl Default for RiskConfig {
     }
 }
 
+/// Flash loan source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlashLoanProvider {
+    AaveV3,
+    Balancer,
+}
+
+impl FlashLoanProvider {
+    /// Fee charged on the borrowed amount, in bps
+    pub fn fee_bps(&self) -> u16 {
+        match self {
+            FlashLoanProvider::AaveV3 => 5,  // 0.05%
+            FlashLoanProvider::Balancer => 0,
+        }
+    }
+}
+
+/// Flash loan configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlashLoanConfig {
+    pub enabled: bool,
+    pub provider: FlashLoanProvider,
+    /// Operator capital available per trade, in USD (None = unconstrained)
+    pub available_capital_usd: Option<f64>,
+}
+
+impl Default for FlashLoanConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: FlashLoanProvider::AaveV3,
+            available_capital_usd: None,
+        }
+    }
+}
+
+/// Swap fee charged by every constant-product pool of one DEX on one chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DexFeeOverride {
+    pub chain: ChainId,
+    pub dex: DexProtocol,
+    pub fee_bps: u16,
+}
+
+/// Per-(chain, DEX) swap fees that replace whatever fee a pool arrived
+/// with, so forks that don't charge 0.3% quote correctly.
+///
+/// Only V2-style pools are overridden. V3, Curve and Solidly pools carry a
+/// fee per pool rather than per DEX.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DexFeeTable {
+    overrides: Vec<DexFeeOverride>,
+}
+
+impl DexFeeTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Charge `fee_bps` on `dex` pools on `chain`, replacing any earlier
+    /// override for the pair
+    pub fn with_fee(mut self, chain: ChainId, dex: DexProtocol, fee_bps: u16) -> Self {
+        self.overrides.retain(|o| (o.chain, o.dex) != (chain, dex));
+        self.overrides.push(DexFeeOverride { chain, dex, fee_bps });
+        self
+    }
+
+    /// Configured fee for `dex` on `chain`, if overridden
+    pub fn fee_bps(&self, chain: ChainId, dex: DexProtocol) -> Option<u16> {
+        self.overrides
+            .iter()
+            .find(|o| o.chain == chain && o.dex == dex)
+            .map(|o| o.fee_bps)
+    }
+
+    pub fn overrides(&self) -> &[DexFeeOverride] {
+        &self.overrides
+    }
+
+    /// Reject overrides for DEXes that aren't deployed on their chain
+    pub fn validate(&self) -> Result<(), CoreError> {
+        self.overrides
+            .iter()
+            .try_for_each(|o| validate_dexes(o.chain, [o.dex]))
+    }
+
+    /// Set the pool's fee from the table. Returns whether it was overridden.
+    pub fn apply(&self, pool: &mut Pool) -> bool {
+        let Pool::UniswapV2(v2) = pool else {
+            return false;
+        };
+        match self.fee_bps(v2.chain, v2.dex) {
+            Some(fee_bps) => {
+                v2.fee_bps = fee_bps;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
 /// Complete bot configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BotConfig {
@@ -109,6 +245,11 @@ pub struct BotConfig {
     pub execution: ExecutionConfig,
     pub detection: DetectionConfig,
     pub risk: RiskConfig,
+    #[serde(default)]
+    pub flash_loan: FlashLoanConfig,
+    /// Swap fees for DEXes that don't charge the 0.3% V2 default
+    #[serde(default)]
+    pub dex_fees: DexFeeTable,
     pub grpc_port: u16,
     pub metrics_port: u16,
 }
@@ -117,4 +258,10 @@ impl BotConfig {
     pub fn get_chain_config(&self, chain: ChainId) -> Option<&ChainConfig> {
         self.chains.iter().find(|c| c.chain == chain)
     }
+
+    /// Check every configured chain/DEX combination exists
+    pub fn validate(&self) -> Result<(), CoreError> {
+        self.chains.iter().try_for_each(ChainConfig::validate)?;
+        self.dex_fees.validate()
+    }
 }
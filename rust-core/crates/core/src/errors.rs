@@ -16,6 +16,27 @@ pub enum CoreError {
     #[error("Pool not found: {0}")]
     PoolNotFound(String),
 
+    #[error("Price not found: {0}")]
+    PriceNotFound(String),
+
+    #[error("Trade not found: {0}")]
+    TradeNotFound(String),
+
+    #[error("Opportunity not found: {0}")]
+    OpportunityNotFound(String),
+
+    #[error("Scanner not running")]
+    ScannerNotRunning,
+
+    #[error("Scanner already running")]
+    ScannerAlreadyRunning,
+
+    #[error("Service is shutting down")]
+    ShuttingDown,
+
+    #[error("Deadline exceeded: {0}")]
+    DeadlineExceeded(String),
+
     #[error("Insufficient liquidity")]
     InsufficientLiquidity,
 
@@ -34,6 +55,18 @@ pub enum CoreError {
     #[error("Invalid configuration: {0}")]
     InvalidConfig(String),
 
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+
+    #[error("Invalid route: {0}")]
+    InvalidRoute(String),
+
+    #[error("Invalid opportunity: {0}")]
+    InvalidOpportunity(String),
+
     #[error("RPC error: {0}")]
     RpcError(String),
 
@@ -81,6 +114,9 @@ pub enum ExecutionError {
     #[error("Transaction not mined in time")]
     NotMined,
 
+    #[error("Relay rejected submission: {0}")]
+    RelayRejected(String),
+
     #[error("Frontrun detected")]
     Frontrun,
 
@@ -94,6 +130,19 @@ pub enum ExecutionError {
     CircuitBreaker(String),
 }
 
+impl ExecutionError {
+    /// Whether resubmitting with a fresh nonce and higher fees can succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ExecutionError::Underpriced
+                | ExecutionError::NotMined
+                | ExecutionError::NonceTooLow
+                | ExecutionError::RelayRejected(_)
+        )
+    }
+}
+
 /// Result type alias
 pub type CoreResult<T> = Result<T, CoreError>;
 pub type PriceFeedResult<T> = Result<T, PriceFeedError>;
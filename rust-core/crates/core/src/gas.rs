@@ -0,0 +1,115 @@
+//! Gas unit estimates per DEX swap
+//!
+//! One table shared by route builders, the quote engine, simulation and
+//! transaction building so every stage prices a route the same way.
+
+use crate::{ArbitrageOpportunity, DexProtocol, SwapRoute};
+
+/// Intrinsic cost of any transaction
+pub const TX_BASE_GAS: u64 = 21_000;
+
+/// Token hand-off between consecutive hops of a multi-hop route
+pub const HOP_TRANSFER_GAS: u64 = 10_000;
+
+/// Extra gas for each initialized tick a V3 swap crosses
+pub const TICK_CROSSING_GAS: u64 = 25_000;
+
+/// Gas for one swap on `dex` that crosses `crossings` initialized ticks.
+/// Crossings only apply to concentrated liquidity pools.
+pub fn gas_units(dex: DexProtocol, crossings: usize) -> u64 {
+    match dex {
+        DexProtocol::UniswapV2
+        | DexProtocol::SushiSwap
+        | DexProtocol::Camelot
+        | DexProtocol::QuickSwap => 90_000,
+        DexProtocol::Aerodrome => 110_000,
+        DexProtocol::UniswapV3 => 120_000 + crossings as u64 * TICK_CROSSING_GAS,
+        DexProtocol::Balancer => 140_000,
+        DexProtocol::Curve => 180_000,
+        DexProtocol::AaveV3 => 200_000,
+    }
+}
+
+/// Gas for a whole route.
+///
+/// Routes built from pool state record crossings in `gas_estimate`; routes
+/// without one fall back to the table with no crossings.
+pub fn route_gas_units(route: &SwapRoute) -> u64 {
+    if route.gas_estimate > 0 {
+        return route.gas_estimate;
+    }
+
+    let swaps: u64 = route.steps.iter().map(|s| gas_units(s.dex, 0)).sum();
+    swaps + hop_overhead(route.steps.len())
+}
+
+/// Gas for executing an opportunity's buy and sell legs in one transaction
+pub fn opportunity_gas_units(opp: &ArbitrageOpportunity) -> u64 {
+    TX_BASE_GAS
+        + route_gas_units(&opp.buy_route)
+        + route_gas_units(&opp.sell_route)
+        + HOP_TRANSFER_GAS
+}
+
+/// Transfer overhead for a route of `hops` swaps
+pub fn hop_overhead(hops: usize) -> u64 {
+    hops.saturating_sub(1) as u64 * HOP_TRANSFER_GAS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChainId, SwapStep};
+    use alloy_primitives::{Address, U256};
+
+    fn route(steps: &[DexProtocol]) -> SwapRoute {
+        SwapRoute {
+            steps: steps
+                .iter()
+                .map(|&dex| SwapStep {
+                    pool: Address::ZERO,
+                    dex,
+                    token_in: Address::ZERO,
+                    token_out: Address::ZERO,
+                    amount_in: U256::ZERO,
+                    amount_out: U256::ZERO,
+                    fee_bps: 30,
+                    min_amount_out: U256::ZERO,
+                })
+                .collect(),
+            chain: ChainId::Ethereum,
+            total_amount_in: U256::ZERO,
+            total_amount_out: U256::ZERO,
+            gas_estimate: 0,
+            price_impact_bps: 0,
+        }
+    }
+
+    #[test]
+    fn test_v3_crossings_cost_more_than_v2() {
+        let v2 = gas_units(DexProtocol::UniswapV2, 0);
+        assert!(gas_units(DexProtocol::UniswapV3, 0) > v2);
+        assert_eq!(
+            gas_units(DexProtocol::UniswapV3, 3),
+            gas_units(DexProtocol::UniswapV3, 0) + 3 * TICK_CROSSING_GAS
+        );
+        // Crossings mean nothing to constant-product pools
+        assert_eq!(gas_units(DexProtocol::UniswapV2, 3), v2);
+    }
+
+    #[test]
+    fn test_route_gas_units() {
+        let single = route(&[DexProtocol::UniswapV2]);
+        assert_eq!(route_gas_units(&single), gas_units(DexProtocol::UniswapV2, 0));
+
+        let two_hop = route(&[DexProtocol::UniswapV2, DexProtocol::Curve]);
+        assert_eq!(
+            route_gas_units(&two_hop),
+            gas_units(DexProtocol::UniswapV2, 0) + gas_units(DexProtocol::Curve, 0) + HOP_TRANSFER_GAS
+        );
+
+        // A recorded estimate wins over the table
+        let recorded = SwapRoute { gas_estimate: 321_000, ..single };
+        assert_eq!(route_gas_units(&recorded), 321_000);
+    }
+}
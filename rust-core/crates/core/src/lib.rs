@@ -13,6 +13,9 @@ pub mod quotes;
 pub mod opportunities;
 pub mod config;
 pub mod errors;
+pub mod gas;
+pub mod math;
+pub mod serde_helpers;
 
 pub use types::*;
 pub use tokens::*;
@@ -21,3 +24,5 @@ pub use quotes::*;
 pub use opportunities::*;
 pub use config::*;
 pub use errors::*;
+pub use gas::*;
+pub use math::*;
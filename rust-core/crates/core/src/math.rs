@@ -0,0 +1,202 @@
+//! Concentrated liquidity price math
+//!
+//! Integer conversions between ticks and `sqrtPriceX96` ported from
+//! Uniswap V3's `TickMath`, so results match the on-chain values exactly.
+
+use alloy_primitives::U256;
+
+/// Lowest tick a V3 pool can reach
+pub const MIN_TICK: i32 = -887_272;
+
+/// Highest tick a V3 pool can reach
+pub const MAX_TICK: i32 = 887_272;
+
+/// `sqrtPriceX96` at `MIN_TICK`
+pub const MIN_SQRT_RATIO: U256 = U256::from_limbs([4_295_128_739, 0, 0, 0]);
+
+/// `sqrtPriceX96` at `MAX_TICK`
+pub const MAX_SQRT_RATIO: U256 = U256::from_limbs([
+    0x5d95_1d52_6398_8d26,
+    0xefd1_fc6a_5064_8849,
+    0xfffd_8963,
+    0,
+]);
+
+/// `2^128 / sqrt(1.0001)^(2^i)` as Q128.128, one per bit of `|tick|`
+const TICK_RATIOS: [u128; 19] = [
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x9aa508b5b7a84e1c677de54f3e99bc9,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe98,
+    0x48a170391f7dc42444e8fa2,
+];
+
+/// `sqrt(1.0001)^1` as Q128.128, for bit 0 of `|tick|`
+const TICK_RATIO_BIT0: u128 = 0xfffcb933bd6fad37aa2d162d1a594001;
+
+/// `2^64 / log2(sqrt(1.0001))` as Q128
+const LOG_SQRT_10001: u128 = 255_738_958_999_603_826_347_141;
+
+/// Error bounds on the tick estimate from the log approximation, Q128.128
+const TICK_LOW_ERROR: u128 = 3_402_992_956_809_132_418_596_140_100_660_247_210;
+const TICK_HIGH_ERROR: u128 = 291_339_464_771_989_622_907_027_621_153_398_088_495;
+
+/// `sqrtPriceX96` at `tick`, rounded up like `TickMath.getSqrtRatioAtTick`.
+/// Returns None outside `MIN_TICK..=MAX_TICK`.
+pub fn tick_to_sqrt_price_x96(tick: i32) -> Option<U256> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return None;
+    }
+
+    let abs_tick = tick.unsigned_abs();
+    let mut ratio = if abs_tick & 1 != 0 {
+        U256::from(TICK_RATIO_BIT0)
+    } else {
+        U256::from(1u8) << 128usize
+    };
+    for (bit, factor) in TICK_RATIOS.iter().enumerate() {
+        if abs_tick & (2 << bit) != 0 {
+            ratio = (ratio * U256::from(*factor)) >> 128usize;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    // Q128.128 to Q64.96, rounding up so the result is never below the tick
+    let rounding = if (ratio % (U256::from(1u8) << 32usize)).is_zero() { 0u8 } else { 1 };
+    Some((ratio >> 32usize) + U256::from(rounding))
+}
+
+/// Greatest tick whose `sqrtPriceX96` is at most `sqrt_price_x96`, like
+/// `TickMath.getTickAtSqrtRatio`. Returns None outside
+/// `MIN_SQRT_RATIO..MAX_SQRT_RATIO`.
+pub fn sqrt_price_x96_to_tick(sqrt_price_x96: U256) -> Option<i32> {
+    if sqrt_price_x96 < MIN_SQRT_RATIO || sqrt_price_x96 >= MAX_SQRT_RATIO {
+        return None;
+    }
+
+    let ratio = sqrt_price_x96 << 32usize;
+    let msb = ratio.bit_len() - 1;
+    let mut r = if msb >= 128 {
+        ratio >> (msb - 127)
+    } else {
+        ratio << (127 - msb)
+    };
+
+    // log2(ratio) as signed Q64.64, integer part from the msb and 14
+    // fractional bits by repeated squaring
+    let mut log2_frac = 0u64;
+    for bit in (50..64).rev() {
+        r = (r * r) >> 127usize;
+        let f: usize = (r >> 128usize).to::<usize>();
+        log2_frac |= (f as u64) << bit;
+        r >>= f;
+    }
+    let log2 = ((msb as i128 - 128) << 64) | log2_frac as i128;
+
+    // Scale to log base sqrt(1.0001) in Q128.128. The bias keeps the
+    // intermediate values unsigned and is removed after the shift.
+    let bias = U256::from(1u8) << 200usize;
+    let scaled = U256::from(log2.unsigned_abs()) * U256::from(LOG_SQRT_10001);
+    let biased = if log2 < 0 { bias - scaled } else { bias + scaled };
+    let unbias = |value: U256| ((value >> 128usize).to::<u128>() as i128 - (1i128 << 72)) as i32;
+
+    let tick_low = unbias(biased - U256::from(TICK_LOW_ERROR));
+    let tick_high = unbias(biased + U256::from(TICK_HIGH_ERROR));
+
+    if tick_low == tick_high {
+        Some(tick_low)
+    } else if tick_to_sqrt_price_x96(tick_high)? <= sqrt_price_x96 {
+        Some(tick_high)
+    } else {
+        Some(tick_low)
+    }
+}
+
+/// Price of token0 in whole token1 units for a `sqrtPriceX96`
+pub fn sqrt_price_x96_to_price(sqrt_price_x96: U256, decimals0: u8, decimals1: u8) -> f64 {
+    // Square in integers while it fits, dropping 64 bits first above 2^128
+    let (squared, shift) = if sqrt_price_x96 <= U256::from(u128::MAX) {
+        (sqrt_price_x96 * sqrt_price_x96, 192)
+    } else {
+        let high = sqrt_price_x96 >> 64usize;
+        (high * high, 64)
+    };
+
+    let raw: f64 = squared.to_string().parse().unwrap_or(0.0);
+    raw / 2f64.powi(shift) * 10f64.powi(decimals0 as i32 - decimals1 as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tick_to_sqrt_price_matches_tick_math() {
+        let q96 = U256::from(1u8) << 96usize;
+        assert_eq!(tick_to_sqrt_price_x96(0), Some(q96));
+        assert_eq!(tick_to_sqrt_price_x96(MIN_TICK), Some(MIN_SQRT_RATIO));
+        assert_eq!(tick_to_sqrt_price_x96(MAX_TICK), Some(MAX_SQRT_RATIO));
+
+        for (tick, expected) in [
+            (1, 79_232_123_823_359_799_118_286_999_568u128),
+            (-1, 79_224_201_403_219_477_170_569_942_574),
+            (50, 79_426_470_787_362_580_746_886_972_461),
+            (-50, 79_030_349_367_926_598_376_800_521_322),
+            (1_000, 83_290_069_058_676_223_003_182_343_270),
+            (200_311, 1_771_577_727_172_025_373_304_338_615_273_325),
+        ] {
+            assert_eq!(tick_to_sqrt_price_x96(tick), Some(U256::from(expected)), "tick {}", tick);
+        }
+
+        assert_eq!(tick_to_sqrt_price_x96(MIN_TICK - 1), None);
+        assert_eq!(tick_to_sqrt_price_x96(MAX_TICK + 1), None);
+    }
+
+    #[test]
+    fn test_sqrt_price_to_tick_inverts() {
+        assert_eq!(sqrt_price_x96_to_tick(MIN_SQRT_RATIO), Some(MIN_TICK));
+        assert_eq!(sqrt_price_x96_to_tick(MAX_SQRT_RATIO - U256::from(1u8)), Some(MAX_TICK - 1));
+        assert_eq!(sqrt_price_x96_to_tick(MIN_SQRT_RATIO - U256::from(1u8)), None);
+        assert_eq!(sqrt_price_x96_to_tick(MAX_SQRT_RATIO), None);
+
+        for tick in [0, 1, -1, 50, -50, 1_000, -1_000, 200_311, -200_311, MAX_TICK - 1, MIN_TICK + 1] {
+            let sqrt = tick_to_sqrt_price_x96(tick).unwrap();
+            assert_eq!(sqrt_price_x96_to_tick(sqrt), Some(tick));
+            // Just below a tick boundary rounds down to the previous tick
+            assert_eq!(sqrt_price_x96_to_tick(sqrt - U256::from(1u8)), Some(tick - 1));
+        }
+    }
+
+    #[test]
+    fn test_sqrt_price_to_human_price() {
+        let q96 = U256::from(1u8) << 96usize;
+        assert_eq!(sqrt_price_x96_to_price(q96, 18, 18), 1.0);
+        assert!((sqrt_price_x96_to_price(q96, 18, 6) - 1e12).abs() < 1e-3);
+
+        // USDC/WETH at tick 200311: ~2000 USDC per WETH, i.e. 1/2000 WETH per USDC
+        let sqrt = tick_to_sqrt_price_x96(200_311).unwrap();
+        let weth_per_usdc = sqrt_price_x96_to_price(sqrt, 6, 18);
+        assert!((1.0 / weth_per_usdc - 2000.0).abs() < 1.0, "{}", weth_per_usdc);
+
+        // Above 2^128 the square no longer fits and takes the shifted path
+        let high = tick_to_sqrt_price_x96(MAX_TICK).unwrap();
+        let expected = 1.0001f64.powi(MAX_TICK);
+        assert!((sqrt_price_x96_to_price(high, 0, 0) / expected - 1.0).abs() < 1e-9);
+    }
+}
@@ -1,10 +1,10 @@
 //! Arbitrage opportunity types
 
-use alloy_primitives::{Address, U256};
+use alloy_primitives::{keccak256, Address, U256};
 use serde::{Deserialize, Serialize};
 use std::time::{Duration, Instant};
 
-use crate::{ChainId, DexProtocol, SwapRoute};
+use crate::{get_decimals, token_label, wrapped_native, ChainId, DetectionConfig, DexProtocol, SwapRoute};
 
 /// Type of arbitrage opportunity
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -17,6 +17,24 @@ pub enum ArbitrageType {
     CrossChain,
     /// Flash loan arbitrage
     FlashLoan,
+    /// Buy on one DEX, return through intermediate tokens on others
+    MultiHop,
+}
+
+/// Share of a block lost to each competing transaction, in bps
+const CONTENTION_PENALTY_BPS: u64 = 1_500;
+/// Contention never cuts an opportunity's lifetime below this share of a block
+const MIN_TTL_BPS: u64 = 2_500;
+
+/// How long an opportunity can realistically be landed on `chain`.
+///
+/// Uncontested opportunities last one block. Each competing transaction
+/// makes it likelier someone else lands first, so the window shrinks by
+/// `CONTENTION_PENALTY_BPS` of a block per competitor, down to a floor.
+pub fn opportunity_ttl_ms(chain: ChainId, competing_txs: u32) -> u64 {
+    let block_ms = chain.block_time_ms();
+    let penalty_bps = (competing_txs as u64 * CONTENTION_PENALTY_BPS).min(10_000 - MIN_TTL_BPS);
+    block_ms - block_ms * penalty_bps / 10_000
 }
 
 /// Detected arbitrage opportunity
@@ -40,9 +58,19 @@ pub struct ArbitrageOpportunity {
     pub output_amount: U256,
     pub gross_profit: U256,
     pub gas_cost_wei: U256,
+    /// Flash loan fee in input token units (zero unless `FlashLoan`)
+    #[serde(default)]
+    pub flash_loan_fee: U256,
     pub net_profit: U256,
     pub profit_bps: i32,
     pub profit_usd: f64,
+    pub input_usd: f64,
+    /// Net profit in whole units of `profit_denomination`
+    #[serde(default)]
+    pub profit_denominated: f64,
+    /// Asset `profit_denominated` is quoted in; None until denominated
+    #[serde(default)]
+    pub profit_denomination: Option<Address>,
 
     // Timing
     pub detected_at_ms: u64,
@@ -89,6 +117,139 @@ impl ArbitrageOpportunity {
         let competition_factor = 1.0 / (1.0 + self.competing_txs as f64 * 0.3);
         self.confidence * competition_factor * 0.8
     }
+
+    /// Every token the opportunity touches, across both routes
+    pub fn tokens(&self) -> Vec<Address> {
+        let mut tokens: Vec<Address> = [self.token_a, self.token_b]
+            .into_iter()
+            .chain(self.buy_route.steps.iter().chain(&self.sell_route.steps).flat_map(|s| [s.token_in, s.token_out]))
+            .filter(|t| !t.is_zero())
+            .collect();
+        tokens.sort();
+        tokens.dedup();
+        tokens
+    }
+
+    /// Every pool the opportunity swaps through, across both routes
+    pub fn pools(&self) -> Vec<Address> {
+        let mut pools: Vec<Address> = self.buy_route.steps
+            .iter()
+            .chain(&self.sell_route.steps)
+            .map(|s| s.pool)
+            .collect();
+        pools.sort();
+        pools.dedup();
+        pools
+    }
+
+    /// Whether both opportunities trade through a common pool, so landing
+    /// one moves the price the other was quoted at
+    pub fn conflicts_with(&self, other: &ArbitrageOpportunity) -> bool {
+        if self.chain != other.chain {
+            return false;
+        }
+        let pools = self.pools();
+        other.pools().iter().any(|pool| pools.binary_search(pool).is_ok())
+    }
+
+    /// Quote net profit in `denomination`, given the price of one input
+    /// token in that asset
+    pub fn denominate_profit(&mut self, denomination: Address, price: f64) {
+        let scale = 10f64.powi(get_decimals(self.chain, self.token_a) as i32);
+        let net: f64 = self.net_profit.to_string().parse().unwrap_or(0.0);
+        self.profit_denominated = net / scale * price;
+        self.profit_denomination = Some(denomination);
+    }
+
+    /// Net profit in whole units of the chain's native token (ETH, or
+    /// MATIC on Polygon). Exact when the input token is the wrapped native
+    /// token; otherwise converted at `price_in_native`, the price of one
+    /// input token in it, and None without one.
+    pub fn profit_eth(&self, price_in_native: Option<f64>) -> Option<f64> {
+        let is_native = wrapped_native(self.chain).is_some_and(|native| native.address == self.token_a);
+        let price = if is_native { 1.0 } else { price_in_native? };
+
+        let scale = 10f64.powi(get_decimals(self.chain, self.token_a) as i32);
+        let net: f64 = self.net_profit.to_string().parse().unwrap_or(0.0);
+        Some(net / scale * price)
+    }
+
+    /// Ranking score under `weights`, as of now
+    pub fn score(&self, weights: &ScoreWeights) -> f64 {
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        self.score_at(weights, now_ms)
+    }
+
+    /// Weighted sum of the normalized profit, success probability and
+    /// share of the opportunity's lifetime still left at `now_ms`. Each
+    /// term lies in 0..=1.
+    pub fn score_at(&self, weights: &ScoreWeights, now_ms: u64) -> f64 {
+        let profit = self.profit_usd.max(0.0);
+        let profit_term = if weights.profit_scale_usd > 0.0 {
+            profit / (profit + weights.profit_scale_usd)
+        } else {
+            0.0
+        };
+
+        let lifetime = self.expires_at_ms.saturating_sub(self.detected_at_ms);
+        let ttl_term = if lifetime > 0 {
+            (self.ttl_ms(now_ms).max(0) as f64 / lifetime as f64).min(1.0)
+        } else {
+            0.0
+        };
+
+        weights.profit * profit_term
+            + weights.probability * self.success_probability()
+            + weights.ttl * ttl_term
+    }
+
+    /// Record competing txs and shorten expiry to match the contention
+    pub fn set_competing_txs(&mut self, competing_txs: u32) {
+        self.competing_txs = competing_txs;
+        let expires_at_ms = self.detected_at_ms + opportunity_ttl_ms(self.chain, competing_txs);
+        self.expires_at_ms = self.expires_at_ms.min(expires_at_ms);
+    }
+}
+
+/// Keep only opportunities that can all be executed together: the most
+/// profitable ones first, dropping any that conflict with one already
+/// kept. Returned most profitable first.
+pub fn collapse_conflicts(mut opportunities: Vec<ArbitrageOpportunity>) -> Vec<ArbitrageOpportunity> {
+    opportunities.sort_by(|a, b| b.profit_usd.total_cmp(&a.profit_usd));
+
+    let mut kept: Vec<ArbitrageOpportunity> = Vec::with_capacity(opportunities.len());
+    for opp in opportunities {
+        if !kept.iter().any(|k| k.conflicts_with(&opp)) {
+            kept.push(opp);
+        }
+    }
+    kept
+}
+
+/// Weights for `ArbitrageOpportunity::score`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoreWeights {
+    pub profit: f64,
+    pub probability: f64,
+    pub ttl: f64,
+    /// Profit, in USD, that earns half the profit term. Profit is
+    /// normalized as `profit / (profit + profit_scale_usd)`.
+    pub profit_scale_usd: f64,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        Self {
+            profit: 0.5,
+            probability: 0.3,
+            ttl: 0.2,
+            profit_scale_usd: 100.0,
+        }
+    }
 }
 
 /// Builder for ArbitrageOpportunity
@@ -103,6 +264,8 @@ pub struct OpportunityBuilder {
     input_amount: Option<U256>,
     gas_cost_wei: Option<U256>,
     block_number: Option<u64>,
+    competing_txs: Option<u32>,
+    ttl_ms: Option<u64>,
 }
 
 impl OpportunityBuilder {
@@ -147,9 +310,25 @@ impl OpportunityBuilder {
         self
     }
 
+    pub fn competing_txs(mut self, count: u32) -> Self {
+        self.competing_txs = Some(count);
+        self
+    }
+
+    /// Override the computed lifetime
+    pub fn ttl_ms(mut self, ttl_ms: u64) -> Self {
+        self.ttl_ms = Some(ttl_ms);
+        self
+    }
+
+    /// Build the opportunity, or `None` if a route is missing or the
+    /// routes don't round-trip back to the starting token
     pub fn build(self) -> Option<ArbitrageOpportunity> {
         let buy_route = self.buy_route?;
         let sell_route = self.sell_route?;
+        if !routes_form_loop(&buy_route, &sell_route, self.token_a) {
+            return None;
+        }
         let input_amount = self.input_amount.unwrap_or(buy_route.total_amount_in);
         let output_amount = sell_route.total_amount_out;
         let gas_cost_wei = self.gas_cost_wei.unwrap_or(U256::ZERO);
@@ -179,31 +358,107 @@ impl OpportunityBuilder {
             .unwrap()
             .as_millis() as u64;
 
+        let chain = self.chain.unwrap_or(ChainId::Ethereum);
+        let competing_txs = self.competing_txs.unwrap_or(0);
+        let ttl_ms = self.ttl_ms.unwrap_or_else(|| opportunity_ttl_ms(chain, competing_txs));
+        let arb_type = self.arb_type.unwrap_or(ArbitrageType::CrossDex);
+        let token_a = self.token_a.unwrap_or(Address::ZERO);
+        let token_b = self.token_b.unwrap_or(Address::ZERO);
+        let block_number = self.block_number.unwrap_or(0);
+        let token_pair = match (self.token_a, self.token_b) {
+            (Some(a), Some(b)) => format!("{}/{}", token_label(chain, a), token_label(chain, b)),
+            _ => String::new(),
+        };
+
         Some(ArbitrageOpportunity {
-            id: format!("{:x}", now_ms),
-            arb_type: self.arb_type.unwrap_or(ArbitrageType::CrossDex),
-            chain: self.chain.unwrap_or(ChainId::Ethereum),
-            token_a: self.token_a.unwrap_or(Address::ZERO),
-            token_b: self.token_b.unwrap_or(Address::ZERO),
-            token_pair: String::new(),
+            id: opportunity_id(arb_type, chain, (token_a, token_b), &buy_route, &sell_route, block_number),
+            arb_type,
+            chain,
+            token_a,
+            token_b,
+            token_pair,
             buy_route,
             sell_route,
             input_amount,
             output_amount,
             gross_profit,
             gas_cost_wei,
+            flash_loan_fee: U256::ZERO,
             net_profit,
             profit_bps,
             profit_usd: 0.0,  // Needs price data
+            input_usd: 0.0,
+            profit_denominated: 0.0,
+            profit_denomination: None,
             detected_at_ms: now_ms,
-            expires_at_ms: now_ms + 12_000,  // 1 block on Ethereum
-            block_number: self.block_number.unwrap_or(0),
+            expires_at_ms: now_ms + ttl_ms,
+            block_number,
             confidence: 0.8,
-            competing_txs: 0,
+            competing_txs,
         })
     }
 }
 
+/// Stable ID for an opportunity: the keccak hash of what makes it the same
+/// trade, namely chain, arb type, token pair, the pools and direction of
+/// every hop, and the block it was seen at, which strategies set to the
+/// newest block among the pools it trades through. Rescanning the same
+/// block yields the same ID, and any difference in those inputs yields a
+/// different one.
+///
+/// Routes without steps have no pools, so their amounts stand in.
+fn opportunity_id(
+    arb_type: ArbitrageType,
+    chain: ChainId,
+    (token_a, token_b): (Address, Address),
+    buy: &SwapRoute,
+    sell: &SwapRoute,
+    block_number: u64,
+) -> String {
+    let mut data = Vec::with_capacity(128);
+    data.extend_from_slice(&chain.chain_id().to_be_bytes());
+    data.push(arb_type as u8);
+    data.extend_from_slice(token_a.as_slice());
+    data.extend_from_slice(token_b.as_slice());
+    data.extend_from_slice(&block_number.to_be_bytes());
+
+    for route in [buy, sell] {
+        // Length prefix keeps hops from shifting between the two routes
+        data.extend_from_slice(&(route.steps.len() as u32).to_be_bytes());
+        if route.steps.is_empty() {
+            data.extend_from_slice(&route.total_amount_in.to_be_bytes::<32>());
+            data.extend_from_slice(&route.total_amount_out.to_be_bytes::<32>());
+        }
+        for step in &route.steps {
+            data.extend_from_slice(step.pool.as_slice());
+            data.extend_from_slice(step.token_in.as_slice());
+            data.extend_from_slice(step.token_out.as_slice());
+        }
+    }
+
+    format!("{:#x}", keccak256(&data))
+}
+
+/// Whether the buy route followed by the sell route is a closed loop: each
+/// hop consumes the previous hop's output and the last hop returns the
+/// starting token, so `output_amount - input_amount` compares like with like.
+///
+/// Routes without steps carry only amounts and are not checked.
+fn routes_form_loop(buy: &SwapRoute, sell: &SwapRoute, token_a: Option<Address>) -> bool {
+    if buy.steps.is_empty() || sell.steps.is_empty() {
+        return true;
+    }
+
+    let steps: Vec<_> = buy.steps.iter().chain(&sell.steps).collect();
+    let start = steps[0].token_in;
+    let end = steps[steps.len() - 1].token_out;
+
+    let chained = steps.windows(2).all(|w| w[0].token_out == w[1].token_in);
+    let starts_at_token_a = token_a.map_or(true, |t| t.is_zero() || t == start);
+
+    chained && start == end && starts_at_token_a
+}
+
 /// Opportunity filter criteria
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpportunityFilter {
@@ -214,6 +469,26 @@ pub struct OpportunityFilter {
     pub allowed_chains: Vec<ChainId>,
     pub max_hops: u8,
     pub min_confidence: f64,
+    /// Tokens no opportunity may touch
+    #[serde(default)]
+    pub token_blocklist: Vec<Address>,
+    /// When set, opportunities may only touch these tokens
+    #[serde(default)]
+    pub token_allowlist: Option<Vec<Address>>,
+    /// Asset net profit is normalized to before `min_profit` applies.
+    /// None keeps profit in each opportunity's input token.
+    #[serde(default)]
+    pub profit_denomination: Option<Address>,
+    /// Minimum net profit in whole units of `profit_denomination`
+    #[serde(default)]
+    pub min_profit: f64,
+    /// Largest combined price impact of the buy and sell routes
+    #[serde(default = "default_max_price_impact_bps")]
+    pub max_price_impact_bps: u16,
+}
+
+fn default_max_price_impact_bps() -> u16 {
+    DetectionConfig::default().max_price_impact_bps
 }
 
 impl Default for OpportunityFilter {
@@ -222,14 +497,15 @@ impl Default for OpportunityFilter {
             min_profit_usd: 1.0,
             min_profit_bps: 10,  // 0.1%
             max_gas_cost_usd: 50.0,
-            allowed_dexes: vec![
-                DexProtocol::UniswapV2,
-                DexProtocol::UniswapV3,
-                DexProtocol::SushiSwap,
-            ],
+            allowed_dexes: DexProtocol::ALL.to_vec(),
             allowed_chains: vec![ChainId::Ethereum, ChainId::Arbitrum],
             max_hops: 3,
             min_confidence: 0.5,
+            token_blocklist: vec![],
+            token_allowlist: None,
+            profit_denomination: None,
+            min_profit: 0.0,
+            max_price_impact_bps: default_max_price_impact_bps(),
         }
     }
 }
@@ -241,5 +517,469 @@ impl OpportunityFilter {
             && opp.confidence >= self.min_confidence
             && self.allowed_chains.contains(&opp.chain)
             && opp.buy_route.hop_count() <= self.max_hops as usize
+            && self.allows_dexes(opp)
+            && self.allows_tokens(opp)
+            && self.allows_profit(opp)
+            && self.allows_price_impact(opp)
+    }
+
+    /// Whether the buy and sell routes only swap on `allowed_dexes`
+    pub fn allows_dexes(&self, opp: &ArbitrageOpportunity) -> bool {
+        opp.buy_route
+            .dexes()
+            .union(&opp.sell_route.dexes())
+            .all(|dex| self.allowed_dexes.contains(dex))
+    }
+
+    /// Whether the buy and sell routes together move prices no more than
+    /// `max_price_impact_bps`
+    pub fn allows_price_impact(&self, opp: &ArbitrageOpportunity) -> bool {
+        let impact = opp.buy_route.price_impact_bps as u32 + opp.sell_route.price_impact_bps as u32;
+        impact <= self.max_price_impact_bps as u32
+    }
+
+    /// Whether profit normalized to the configured denomination clears
+    /// `min_profit`. Opportunities that couldn't be denominated never do.
+    pub fn allows_profit(&self, opp: &ArbitrageOpportunity) -> bool {
+        match self.profit_denomination {
+            Some(denomination) => {
+                opp.profit_denomination == Some(denomination) && opp.profit_denominated >= self.min_profit
+            }
+            None => true,
+        }
+    }
+
+    /// Whether every token on the opportunity passes the block/allow lists
+    pub fn allows_tokens(&self, opp: &ArbitrageOpportunity) -> bool {
+        if self.token_blocklist.is_empty() && self.token_allowlist.is_none() {
+            return true;
+        }
+
+        opp.tokens().iter().all(|token| {
+            !self.token_blocklist.contains(token)
+                && self.token_allowlist.as_ref().map_or(true, |allowed| allowed.contains(token))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_route(chain: ChainId, amount_in: U256, amount_out: U256) -> SwapRoute {
+        SwapRoute {
+            steps: vec![],
+            chain,
+            total_amount_in: amount_in,
+            total_amount_out: amount_out,
+            gas_estimate: 0,
+            price_impact_bps: 0,
+        }
+    }
+
+    fn routed(path: &[u8]) -> ArbitrageOpportunity {
+        let step = |a: u8, b: u8| crate::SwapStep {
+            pool: Address::repeat_byte(0xF0),
+            dex: DexProtocol::UniswapV2,
+            token_in: Address::repeat_byte(a),
+            token_out: Address::repeat_byte(b),
+            amount_in: U256::from(1000u64),
+            amount_out: U256::from(1000u64),
+            fee_bps: 30,
+            min_amount_out: U256::ZERO,
+        };
+        let mut buy = empty_route(ChainId::Ethereum, U256::from(1000u64), U256::from(1000u64));
+        buy.steps = path.windows(2).map(|w| step(w[0], w[1])).collect();
+        let sell = empty_route(ChainId::Ethereum, U256::from(1000u64), U256::from(1100u64));
+
+        let mut opp = OpportunityBuilder::new()
+            .chain(ChainId::Ethereum)
+            .tokens(Address::repeat_byte(path[0]), Address::repeat_byte(path[path.len() - 1]))
+            .routes(buy, sell)
+            .build()
+            .unwrap();
+        opp.profit_usd = 10.0;
+        opp
+    }
+
+    fn route_through(path: &[u8], amount_in: u64, amount_out: u64) -> SwapRoute {
+        let mut route = empty_route(ChainId::Ethereum, U256::from(amount_in), U256::from(amount_out));
+        route.steps = path
+            .windows(2)
+            .map(|w| crate::SwapStep {
+                pool: Address::repeat_byte(0xF0),
+                dex: DexProtocol::UniswapV2,
+                token_in: Address::repeat_byte(w[0]),
+                token_out: Address::repeat_byte(w[1]),
+                amount_in: U256::from(amount_in),
+                amount_out: U256::from(amount_out),
+                fee_bps: 30,
+                min_amount_out: U256::ZERO,
+            })
+            .collect();
+        route
+    }
+
+    #[test]
+    fn test_conflicting_opportunities_collapse_to_most_profitable() {
+        let through = |pool: u8, profit_usd: f64| {
+            let mut opp = routed(&[1, 2]);
+            opp.buy_route.steps[0].pool = Address::repeat_byte(pool);
+            opp.profit_usd = profit_usd;
+            opp
+        };
+        let (small, large, elsewhere) = (through(0xA1, 10.0), through(0xA1, 30.0), through(0xB1, 5.0));
+
+        assert!(small.conflicts_with(&large));
+        assert!(!small.conflicts_with(&elsewhere));
+        let mut other_chain = large.clone();
+        other_chain.chain = ChainId::Base;
+        assert!(!small.conflicts_with(&other_chain));
+
+        let kept = collapse_conflicts(vec![small, large, elsewhere]);
+        let profits: Vec<f64> = kept.iter().map(|o| o.profit_usd).collect();
+        assert_eq!(profits, vec![30.0, 5.0]);
+    }
+
+    #[test]
+    fn test_score_weights_reorder_opportunities() {
+        let opp = |profit_usd: f64, competing_txs: u32, expires_at_ms: u64| {
+            let route = empty_route(ChainId::Ethereum, U256::from(1000u64), U256::from(1100u64));
+            let mut opp = OpportunityBuilder::new().routes(route.clone(), route).build().unwrap();
+            opp.profit_usd = profit_usd;
+            opp.confidence = 1.0;
+            opp.competing_txs = competing_txs;
+            opp.detected_at_ms = 0;
+            opp.expires_at_ms = expires_at_ms;
+            opp
+        };
+        // Large but contested, small and uncontested, mid-sized and nearly expired
+        let opps = [("whale", opp(1000.0, 10, 1000)), ("safe", opp(20.0, 0, 500)), ("stale", opp(200.0, 1, 150))];
+        let ranked = |weights: ScoreWeights| {
+            let mut ranked: Vec<_> = opps.iter().map(|(name, o)| (*name, o.score_at(&weights, 100))).collect();
+            ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+            ranked.into_iter().map(|(name, _)| name).collect::<Vec<_>>()
+        };
+        let only = |profit: f64, probability: f64, ttl: f64| ScoreWeights { profit, probability, ttl, ..ScoreWeights::default() };
+
+        assert_eq!(ranked(only(1.0, 0.0, 0.0)), vec!["whale", "stale", "safe"]);
+        assert_eq!(ranked(only(0.0, 1.0, 0.0)), vec!["safe", "stale", "whale"]);
+        assert_eq!(ranked(only(0.0, 0.0, 1.0)), vec!["whale", "safe", "stale"]);
+
+        // Terms are normalized, so each stays within its weight
+        let (_, whale) = &opps[0];
+        let score = whale.score_at(&only(1.0, 0.0, 0.0), 100);
+        assert!((score - 1000.0 / 1100.0).abs() < 1e-9);
+        assert_eq!(whale.score_at(&only(0.0, 0.0, 1.0), 2_000), 0.0);
+    }
+
+    #[test]
+    fn test_build_accepts_closed_loop() {
+        let opp = OpportunityBuilder::new()
+            .tokens(Address::repeat_byte(1), Address::repeat_byte(2))
+            .routes(route_through(&[1, 2], 1000, 2000), route_through(&[2, 3, 1], 2000, 1010))
+            .build()
+            .unwrap();
+        assert_eq!(opp.gross_profit, U256::from(10u64));
+    }
+
+    #[test]
+    fn test_token_pair_uses_symbols() {
+        let weth = crate::get_token(ChainId::Ethereum, "WETH").unwrap().address;
+        let usdc = crate::get_token(ChainId::Ethereum, "USDC").unwrap().address;
+        let route = empty_route(ChainId::Ethereum, U256::from(1000u64), U256::from(1010u64));
+        let build = |a: Address, b: Address| {
+            OpportunityBuilder::new()
+                .chain(ChainId::Ethereum)
+                .tokens(a, b)
+                .routes(route.clone(), route.clone())
+                .build()
+                .unwrap()
+                .token_pair
+        };
+
+        assert_eq!(build(weth, usdc), "WETH/USDC");
+        assert_eq!(build(weth, Address::repeat_byte(0x12)), "WETH/0x1212..1212");
+    }
+
+    #[test]
+    fn test_id_is_stable_for_the_same_opportunity() {
+        let build = |sell: &[u8], block: u64, amount_out: u64| {
+            OpportunityBuilder::new()
+                .chain(ChainId::Arbitrum)
+                .tokens(Address::repeat_byte(1), Address::repeat_byte(2))
+                .routes(route_through(&[1, 2], 1000, 2000), route_through(sell, 2000, amount_out))
+                .block(block)
+                .build()
+                .unwrap()
+        };
+
+        let first = build(&[2, 1], 100, 1010);
+        // Same trade seen again with different sizing keeps its ID
+        assert_eq!(first.id, build(&[2, 1], 100, 1050).id);
+        assert_eq!(first.id.len(), 66);
+
+        // A later block or a different path is a different opportunity
+        assert_ne!(first.id, build(&[2, 1], 101, 1010).id);
+        assert_ne!(first.id, build(&[2, 3, 1], 100, 1010).id);
+
+        // Step-less routes built in the same instant no longer collide
+        let amounts_only = |out: u64| {
+            OpportunityBuilder::new()
+                .routes(
+                    empty_route(ChainId::Ethereum, U256::from(1000u64), U256::from(1000u64)),
+                    empty_route(ChainId::Ethereum, U256::from(1000u64), U256::from(out)),
+                )
+                .build()
+                .unwrap()
+                .id
+        };
+        assert_ne!(amounts_only(1100), amounts_only(1200));
+    }
+
+    #[test]
+    fn test_build_rejects_broken_loops() {
+        let build = |buy: &[u8], sell: &[u8]| {
+            OpportunityBuilder::new()
+                .routes(route_through(buy, 1000, 2000), route_through(sell, 2000, 1010))
+                .build()
+        };
+
+        assert!(build(&[1, 2], &[2, 1]).is_some());
+        // Sell route ends in a different token than the buy route started with
+        assert!(build(&[1, 2], &[2, 3]).is_none());
+        // Sell route doesn't start where the buy route ended
+        assert!(build(&[1, 2], &[3, 1]).is_none());
+        // Gap inside a route
+        let mut gapped = route_through(&[1, 2], 1000, 2000);
+        gapped.steps.extend(route_through(&[3, 2], 1000, 2000).steps);
+        assert!(OpportunityBuilder::new()
+            .routes(gapped, route_through(&[2, 1], 2000, 1010))
+            .build()
+            .is_none());
+
+        // Declared token_a disagrees with where the loop starts
+        assert!(OpportunityBuilder::new()
+            .tokens(Address::repeat_byte(2), Address::repeat_byte(1))
+            .routes(route_through(&[1, 2], 1000, 2000), route_through(&[2, 1], 2000, 1010))
+            .build()
+            .is_none());
+    }
+
+    #[test]
+    fn test_token_blocklist_drops_routes_through_token() {
+        let filter = OpportunityFilter {
+            token_blocklist: vec![Address::repeat_byte(3)],
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&routed(&[1, 2])));
+        // Blocked token only appears as an intermediate hop
+        assert!(!filter.matches(&routed(&[1, 3, 2])));
+    }
+
+    #[test]
+    fn test_denominated_min_profit() {
+        let usdc = Address::repeat_byte(0xCC);
+        let filter = OpportunityFilter {
+            profit_denomination: Some(usdc),
+            min_profit: 5.0,
+            ..Default::default()
+        };
+
+        // 100 raw units of token 1, which has 18 decimals
+        let mut opp = routed(&[1, 2]);
+        assert!(!filter.matches(&opp), "undenominated profit never passes");
+
+        opp.denominate_profit(usdc, 6e16);
+        assert!((opp.profit_denominated - 6.0).abs() < 1e-9);
+        assert!(filter.matches(&opp));
+
+        opp.denominate_profit(usdc, 4e16);
+        assert!(!filter.matches(&opp));
+
+        // Quoted in some other asset
+        opp.denominate_profit(Address::repeat_byte(0xDD), 6e16);
+        assert!(!filter.matches(&opp));
+    }
+
+    #[test]
+    fn test_profit_eth() {
+        let chain = ChainId::Arbitrum;
+        let weth = crate::get_token(chain, "WETH").unwrap().address;
+        let usdc = crate::get_token(chain, "USDC").unwrap().address;
+        let opportunity = |token: Address, amount_in: u128, amount_out: u128| {
+            let route = empty_route(chain, U256::from(amount_in), U256::from(amount_out));
+            OpportunityBuilder::new()
+                .chain(chain)
+                .tokens(token, usdc)
+                .routes(route.clone(), route)
+                .build()
+                .unwrap()
+        };
+
+        // 0.025 WETH of net profit needs no price
+        let weth_opp = opportunity(weth, 10u128.pow(18), 1_025 * 10u128.pow(15));
+        assert_eq!(weth_opp.net_profit, U256::from(25 * 10u128.pow(15)));
+        assert!((weth_opp.profit_eth(None).unwrap() - 0.025).abs() < 1e-12);
+        assert_eq!(weth_opp.profit_eth(Some(3.0)), weth_opp.profit_eth(None));
+
+        // 40 USDC at 2000 USDC per ETH
+        let usdc_opp = opportunity(usdc, 1_000 * 10u128.pow(6), 1_040 * 10u128.pow(6));
+        assert_eq!(usdc_opp.profit_eth(None), None);
+        assert!((usdc_opp.profit_eth(Some(1.0 / 2000.0)).unwrap() - 0.02).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_price_impact_cap() {
+        let filter = OpportunityFilter::default();
+        assert_eq!(filter.max_price_impact_bps, 100);
+
+        let with_impact = |buy_bps: u16, sell_bps: u16| {
+            let mut opp = routed(&[1, 2]);
+            opp.buy_route.price_impact_bps = buy_bps;
+            opp.sell_route.price_impact_bps = sell_bps;
+            opp
+        };
+
+        // Same profit either way; only the combined impact differs
+        assert!(filter.matches(&with_impact(40, 60)));
+        assert!(!filter.matches(&with_impact(40, 61)));
+        assert!(!filter.matches(&with_impact(u16::MAX, u16::MAX)));
+    }
+
+    #[test]
+    fn test_disallowed_dex_is_filtered_out() {
+        let filter = OpportunityFilter {
+            allowed_dexes: vec![DexProtocol::UniswapV2, DexProtocol::UniswapV3, DexProtocol::SushiSwap],
+            ..Default::default()
+        };
+        let on = |opp: &mut ArbitrageOpportunity, dex: DexProtocol| {
+            let mut step = opp.buy_route.steps[0].clone();
+            std::mem::swap(&mut step.token_in, &mut step.token_out);
+            step.dex = dex;
+            opp.sell_route.steps = vec![step];
+        };
+
+        let mut opp = routed(&[1, 2]);
+        assert_eq!(opp.buy_route.dexes(), [DexProtocol::UniswapV2].into_iter().collect());
+        on(&mut opp, DexProtocol::SushiSwap);
+        assert!(filter.matches(&opp));
+
+        // Only the sell leg touches Curve
+        on(&mut opp, DexProtocol::Curve);
+        assert!(!filter.matches(&opp));
+
+        let with_curve = OpportunityFilter {
+            allowed_dexes: vec![DexProtocol::UniswapV2, DexProtocol::Curve],
+            ..Default::default()
+        };
+        assert!(with_curve.matches(&opp));
+
+        // Disabling a DEX drops routes through it on either leg
+        let without_v2 = OpportunityFilter {
+            allowed_dexes: vec![DexProtocol::Curve],
+            ..Default::default()
+        };
+        assert!(!without_v2.matches(&opp));
+
+        // Every protocol is allowed unless narrowed
+        assert!(OpportunityFilter::default().matches(&opp));
+    }
+
+    #[test]
+    fn test_token_allowlist_only_passes_allowed_routes() {
+        let filter = OpportunityFilter {
+            token_allowlist: Some(vec![Address::repeat_byte(1), Address::repeat_byte(2)]),
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&routed(&[1, 2])));
+        assert!(filter.matches(&routed(&[2, 1, 2])));
+        assert!(!filter.matches(&routed(&[1, 4, 2])));
+    }
+
+    #[test]
+    fn test_expiry_uses_chain_block_time() {
+        let chain = ChainId::Arbitrum;
+        let opp = OpportunityBuilder::new()
+            .chain(chain)
+            .routes(
+                empty_route(chain, U256::from(1000u64), U256::from(1000u64)),
+                empty_route(chain, U256::from(1000u64), U256::from(1010u64)),
+            )
+            .build()
+            .unwrap();
+
+        assert_eq!(opp.expires_at_ms - opp.detected_at_ms, 250);
+        assert!(opp.is_valid(opp.detected_at_ms));
+        assert!(!opp.is_valid(opp.detected_at_ms + 250));
+    }
+
+    #[test]
+    fn test_expiry_defaults_to_ethereum_block() {
+        let opp = OpportunityBuilder::new()
+            .routes(
+                empty_route(ChainId::Ethereum, U256::from(1000u64), U256::from(1000u64)),
+                empty_route(ChainId::Ethereum, U256::from(1000u64), U256::from(1010u64)),
+            )
+            .build()
+            .unwrap();
+
+        assert_eq!(opp.ttl_ms(opp.detected_at_ms), 12_000);
+    }
+
+    fn build(chain: ChainId, competing_txs: u32) -> ArbitrageOpportunity {
+        OpportunityBuilder::new()
+            .chain(chain)
+            .competing_txs(competing_txs)
+            .routes(
+                empty_route(chain, U256::from(1000u64), U256::from(1000u64)),
+                empty_route(chain, U256::from(1000u64), U256::from(1010u64)),
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_expiry_ethereum_vs_arbitrum() {
+        let eth = build(ChainId::Ethereum, 0);
+        let arb = build(ChainId::Arbitrum, 0);
+
+        assert_eq!(eth.ttl_ms(eth.detected_at_ms), 12_000);
+        assert_eq!(arb.ttl_ms(arb.detected_at_ms), 250);
+    }
+
+    #[test]
+    fn test_competition_shortens_expiry() {
+        let calm = build(ChainId::Ethereum, 0);
+        let contested = build(ChainId::Ethereum, 2);
+        let swarmed = build(ChainId::Ethereum, 50);
+
+        assert_eq!(contested.competing_txs, 2);
+        assert_eq!(contested.ttl_ms(contested.detected_at_ms), 8_400);
+        assert!(contested.ttl_ms(contested.detected_at_ms) < calm.ttl_ms(calm.detected_at_ms));
+        // Floored at a quarter block
+        assert_eq!(swarmed.ttl_ms(swarmed.detected_at_ms), 3_000);
+
+        let arb = build(ChainId::Arbitrum, 2);
+        assert_eq!(arb.ttl_ms(arb.detected_at_ms), 175);
+    }
+
+    #[test]
+    fn test_expiry_override_and_late_competition() {
+        let mut opp = OpportunityBuilder::new()
+            .ttl_ms(30_000)
+            .routes(
+                empty_route(ChainId::Ethereum, U256::from(1000u64), U256::from(1000u64)),
+                empty_route(ChainId::Ethereum, U256::from(1000u64), U256::from(1010u64)),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(opp.ttl_ms(opp.detected_at_ms), 30_000);
+
+        opp.set_competing_txs(2);
+        assert_eq!(opp.competing_txs, 2);
+        assert_eq!(opp.ttl_ms(opp.detected_at_ms), 8_400);
     }
 }
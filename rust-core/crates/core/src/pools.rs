@@ -3,9 +3,16 @@
 use alloy_primitives::{Address, U256};
 use serde::{Deserialize, Serialize};
 
-use crate::{ChainId, DexProtocol};
+use crate::{apply_transfer_fee, gas_units, ChainId, DexProtocol};
+use crate::math::sqrt_price_x96_to_price;
+
+fn default_decimals() -> u8 {
+    18
+}
 
 /// Uniswap V2 style pool (constant product)
+///
+/// Also used for V2 forks such as SushiSwap, Camelot and QuickSwap via `dex`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UniswapV2Pool {
     pub address: Address,
@@ -13,6 +20,11 @@ pub struct UniswapV2Pool {
     pub token1: Address,
     pub reserve0: U256,
     pub reserve1: U256,
+    /// Pools serialized before decimals were tracked read as 18
+    #[serde(default = "default_decimals")]
+    pub decimals0: u8,
+    #[serde(default = "default_decimals")]
+    pub decimals1: u8,
     pub fee_bps: u16,  // Usually 30 (0.3%)
     pub chain: ChainId,
     pub dex: DexProtocol,
@@ -22,30 +34,34 @@ pub struct UniswapV2Pool {
 impl UniswapV2Pool {
     /// Calculate output amount using constant product formula
     /// amountOut = (amountIn * fee * reserveOut) / (reserveIn * 10000 + amountIn * fee)
+    ///
+    /// Fee-on-transfer taxes are deducted from the amount reaching the pool
+    /// and from the amount leaving it. Intermediate products never
+    /// overflow; see [`constant_product_out`].
     pub fn get_amount_out(&self, amount_in: U256, token_in: Address) -> U256 {
         if amount_in.is_zero() {
             return U256::ZERO;
         }
 
-        let (reserve_in, reserve_out) = if token_in == self.token0 {
-            (self.reserve0, self.reserve1)
+        let (reserve_in, reserve_out, token_out) = if token_in == self.token0 {
+            (self.reserve0, self.reserve1, self.token1)
         } else {
-            (self.reserve1, self.reserve0)
+            (self.reserve1, self.reserve0, self.token0)
         };
+        let amount_in = apply_transfer_fee(self.chain, token_in, amount_in);
 
         if reserve_in.is_zero() || reserve_out.is_zero() {
             return U256::ZERO;
         }
 
         let fee_multiplier = U256::from(10000 - self.fee_bps);
-        let amount_in_with_fee = amount_in * fee_multiplier;
-        let numerator = amount_in_with_fee * reserve_out;
-        let denominator = reserve_in * U256::from(10000) + amount_in_with_fee;
+        let amount_out = constant_product_out(amount_in, reserve_in, reserve_out, fee_multiplier);
 
-        numerator / denominator
+        apply_transfer_fee(self.chain, token_out, amount_out)
     }
 
-    /// Calculate input amount needed for desired output
+    /// Calculate input amount needed for desired output.
+    /// `U256::MAX` when the output can't be reached or the math overflows.
     pub fn get_amount_in(&self, amount_out: U256, token_out: Address) -> U256 {
         if amount_out.is_zero() {
             return U256::ZERO;
@@ -62,13 +78,27 @@ impl UniswapV2Pool {
         }
 
         let fee_multiplier = U256::from(10000 - self.fee_bps);
-        let numerator = reserve_in * amount_out * U256::from(10000);
-        let denominator = (reserve_out - amount_out) * fee_multiplier;
+        let Some(numerator) = reserve_in
+            .checked_mul(amount_out)
+            .and_then(|n| n.checked_mul(U256::from(10000)))
+        else {
+            return U256::MAX;
+        };
+        let Some(denominator) = (reserve_out - amount_out).checked_mul(fee_multiplier) else {
+            return U256::MAX;
+        };
+
+        (numerator / denominator).saturating_add(U256::from(1))
+    }
 
-        (numerator / denominator) + U256::from(1)
+    /// Whether both reserves hold at least `floor` raw units. A drained
+    /// pool still quotes a spot price from the dust left in it, but no
+    /// trade through it comes close to that price.
+    pub fn has_min_reserves(&self, floor: U256) -> bool {
+        self.reserve0 >= floor && self.reserve1 >= floor
     }
 
-    /// Calculate spot price (token1 per token0)
+    /// Calculate spot price (token1 per token0, raw units)
     pub fn spot_price(&self) -> f64 {
         if self.reserve0.is_zero() {
             return 0.0;
@@ -78,6 +108,11 @@ impl UniswapV2Pool {
         r1 / r0
     }
 
+    /// Spot price in whole tokens (token1 per token0)
+    pub fn human_spot_price(&self) -> f64 {
+        to_human(self.spot_price(), self.decimals0, self.decimals1)
+    }
+
     /// Calculate price impact for a trade
     pub fn price_impact(&self, amount_in: U256, token_in: Address) -> f64 {
         let amount_out = self.get_amount_out(amount_in, token_in);
@@ -104,17 +139,90 @@ impl UniswapV2Pool {
     }
 }
 
+/// `amount_in * fee * reserve_out / (reserve_in * 10000 + amount_in * fee)`
+/// in checked arithmetic.
+///
+/// When an intermediate product would overflow, all three amounts are
+/// scaled down by the same power of two until it fits and the quote is
+/// scaled back up. The formula is homogeneous, so this only costs the
+/// precision of the dropped low bits. Zero if no scale fits.
+fn constant_product_out(amount_in: U256, reserve_in: U256, reserve_out: U256, fee_multiplier: U256) -> U256 {
+    let quote = |amount_in: U256, reserve_in: U256, reserve_out: U256| -> Option<U256> {
+        let amount_in_with_fee = amount_in.checked_mul(fee_multiplier)?;
+        let numerator = amount_in_with_fee.checked_mul(reserve_out)?;
+        let denominator = reserve_in
+            .checked_mul(U256::from(10000))?
+            .checked_add(amount_in_with_fee)?;
+        numerator.checked_div(denominator)
+    };
+
+    for shift in (0..256usize).step_by(8) {
+        let (amount_in, reserve_in, reserve_out) = (amount_in >> shift, reserve_in >> shift, reserve_out >> shift);
+        if amount_in.is_zero() || reserve_in.is_zero() || reserve_out.is_zero() {
+            break;
+        }
+        if let Some(amount_out) = quote(amount_in, reserve_in, reserve_out) {
+            return amount_out.checked_shl(shift).unwrap_or(U256::ZERO);
+        }
+    }
+
+    U256::ZERO
+}
+
+/// Smallest input `amount_out` turns into at least `target`, found by
+/// bisection. The quote must not decrease as its input grows. `U256::MAX`
+/// when no input reaches the target.
+fn invert_amount_out(target: U256, amount_out: impl Fn(U256) -> U256) -> U256 {
+    if target.is_zero() {
+        return U256::ZERO;
+    }
+
+    let mut high = target;
+    while amount_out(high) < target {
+        match high.checked_mul(U256::from(2)) {
+            Some(next) => high = next,
+            None => return U256::MAX,
+        }
+    }
+
+    let mut low = U256::ZERO;
+    while high - low > U256::from(1) {
+        let mid = low + (high - low) / U256::from(2);
+        if amount_out(mid) >= target {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+    high
+}
+
+/// Initialized tick of a V3 pool
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TickInfo {
+    pub index: i32,
+    /// Liquidity added when the tick is crossed left to right
+    pub liquidity_net: i128,
+}
+
 /// Uniswap V3 style pool (concentrated liquidity)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UniswapV3Pool {
     pub address: Address,
     pub token0: Address,
     pub token1: Address,
+    #[serde(default = "default_decimals")]
+    pub decimals0: u8,
+    #[serde(default = "default_decimals")]
+    pub decimals1: u8,
     pub fee: u32,           // Fee in hundredths of a bip (e.g., 3000 = 0.3%)
     pub tick_spacing: i32,
     pub liquidity: u128,
     pub sqrt_price_x96: U256,
     pub tick: i32,
+    /// Initialized ticks around the current price, sorted by index
+    #[serde(default)]
+    pub ticks: Vec<TickInfo>,
     pub chain: ChainId,
     pub block_number: u64,
 }
@@ -126,18 +234,130 @@ impl UniswapV3Pool {
     pub const FEE_MEDIUM: u32 = 3000;   // 0.3%
     pub const FEE_HIGH: u32 = 10000;    // 1%
 
-    /// Calculate current price from sqrtPriceX96
+    /// Calculate current price from sqrtPriceX96 (token1 per token0, raw units)
     pub fn current_price(&self) -> f64 {
-        let sqrt_price: f64 = self.sqrt_price_x96.to_string().parse().unwrap_or(0.0);
-        let q96: f64 = 2f64.powi(96);
-        let price = (sqrt_price / q96).powi(2);
-        price
+        sqrt_price_x96_to_price(self.sqrt_price_x96, 0, 0)
+    }
+
+    /// Current price in whole tokens (token1 per token0)
+    pub fn human_price(&self) -> f64 {
+        sqrt_price_x96_to_price(self.sqrt_price_x96, self.decimals0, self.decimals1)
     }
 
     /// Get fee as percentage
     pub fn fee_percent(&self) -> f64 {
         self.fee as f64 / 1_000_000.0
     }
+
+    /// Swap output, crossing initialized ticks as the price moves.
+    ///
+    /// Without tick data the current liquidity is assumed to extend
+    /// indefinitely, which overestimates output on large trades.
+    pub fn get_amount_out(&self, amount_in: U256, token_in: Address) -> U256 {
+        let (out, _) = self.swap(amount_in, token_in);
+        if !out.is_finite() || out <= 0.0 {
+            return U256::ZERO;
+        }
+        U256::from(out as u128)
+    }
+
+    /// Input needed to receive `amount_out` of `token_out`: the smallest
+    /// amount `get_amount_out` turns into at least that much. `U256::MAX`
+    /// when the output can't be reached.
+    pub fn get_amount_in(&self, amount_out: U256, token_out: Address) -> U256 {
+        let token_in = if token_out == self.token1 { self.token0 } else { self.token1 };
+        invert_amount_out(amount_out, |amount_in| self.get_amount_out(amount_in, token_in))
+    }
+
+    /// Initialized ticks a swap of `amount_in` would cross
+    pub fn ticks_crossed(&self, amount_in: U256, token_in: Address) -> usize {
+        self.swap(amount_in, token_in).1
+    }
+
+    /// Walk the swap through tick ranges, returning the output and the
+    /// number of ticks crossed
+    fn swap(&self, amount_in: U256, token_in: Address) -> (f64, usize) {
+        if amount_in.is_zero() || self.liquidity == 0 || self.sqrt_price_x96.is_zero() {
+            return (0.0, 0);
+        }
+
+        let sqrt_price: f64 = self.sqrt_price_x96.to_string().parse().unwrap_or(0.0);
+        let mut sp = sqrt_price / 2f64.powi(96);
+        let mut liquidity = self.liquidity as f64;
+        let amount: f64 = amount_in.to_string().parse().unwrap_or(0.0);
+        let mut remaining = amount * (1.0 - self.fee_percent());
+        let zero_for_one = token_in == self.token0;
+
+        // token0 in pushes price down through lower ticks, token1 in pushes it up
+        let boundaries: Vec<&TickInfo> = if zero_for_one {
+            self.ticks.iter().rev().filter(|t| t.index <= self.tick).collect()
+        } else {
+            self.ticks.iter().filter(|t| t.index > self.tick).collect()
+        };
+
+        let mut out = 0.0;
+        let mut crossed = 0;
+        for tick in boundaries {
+            let sp_target = tick_sqrt_price(tick.index);
+
+            if liquidity > 0.0 {
+                let to_boundary = if zero_for_one {
+                    liquidity * (1.0 / sp_target - 1.0 / sp)
+                } else {
+                    liquidity * (sp_target - sp)
+                };
+                if remaining < to_boundary {
+                    break;
+                }
+
+                out += if zero_for_one {
+                    liquidity * (sp - sp_target)
+                } else {
+                    liquidity * (1.0 / sp - 1.0 / sp_target)
+                };
+                remaining -= to_boundary;
+            }
+
+            sp = sp_target;
+            liquidity += if zero_for_one { -(tick.liquidity_net as f64) } else { tick.liquidity_net as f64 };
+            crossed += 1;
+        }
+
+        if liquidity > 0.0 && remaining > 0.0 {
+            out += if zero_for_one {
+                let sp_next = liquidity * sp / (liquidity + remaining * sp);
+                liquidity * (sp - sp_next)
+            } else {
+                let sp_next = sp + remaining / liquidity;
+                liquidity * (1.0 / sp - 1.0 / sp_next)
+            };
+        }
+
+        (out, crossed)
+    }
+
+    /// Virtual reserves backing the current tick range: `L / √P` and `L · √P`
+    pub fn virtual_reserves(&self) -> (U256, U256) {
+        let sqrt_price: f64 = self.sqrt_price_x96.to_string().parse().unwrap_or(0.0);
+        let sp = sqrt_price / 2f64.powi(96);
+        if sp <= 0.0 || !sp.is_finite() {
+            return (U256::ZERO, U256::ZERO);
+        }
+
+        let liquidity = self.liquidity as f64;
+        let to_u256 = |v: f64| if v.is_finite() && v > 0.0 { U256::from(v as u128) } else { U256::ZERO };
+        (to_u256(liquidity / sp), to_u256(liquidity * sp))
+    }
+}
+
+/// Convert a raw-unit price of token1 per token0 to whole tokens
+fn to_human(raw: f64, decimals0: u8, decimals1: u8) -> f64 {
+    raw * 10f64.powi(decimals0 as i32 - decimals1 as i32)
+}
+
+/// √P at a tick boundary: `1.0001^(tick / 2)`
+fn tick_sqrt_price(tick: i32) -> f64 {
+    1.0001f64.powf(tick as f64 / 2.0)
 }
 
 /// Curve pool (StableSwap)
@@ -146,6 +366,10 @@ pub struct CurvePool {
     pub address: Address,
     pub tokens: Vec<Address>,
     pub balances: Vec<U256>,
+    /// Decimals of each entry in `tokens`. Empty for pools serialized
+    /// before decimals were tracked, which read as 18.
+    #[serde(default)]
+    pub decimals: Vec<u8>,
     pub a_parameter: U256,  // Amplification coefficient
     pub fee: u64,           // Fee in 1e10 (e.g., 4000000 = 0.04%)
     pub chain: ChainId,
@@ -162,6 +386,300 @@ impl CurvePool {
     pub fn is_stable_pool(&self) -> bool {
         self.a_parameter > U256::from(100)
     }
+
+    /// Newton iteration cap (matches the on-chain implementation)
+    const MAX_ITERATIONS: usize = 255;
+
+    /// Normalized balances at or above 2^120 could overflow the invariant
+    /// math, so such swaps quote nothing
+    fn max_normalized() -> U256 {
+        U256::from(1u64) << 120
+    }
+
+    /// Multiplier bringing raw amounts of coin `index` to 18 decimals
+    fn precision(&self, index: usize) -> U256 {
+        let decimals = self.decimals.get(index).copied().unwrap_or_else(default_decimals);
+        U256::from(10u64).pow(U256::from(18 - decimals.min(18)))
+    }
+
+    /// StableSwap invariant D for normalized balances `xp`
+    fn get_d(&self, xp: &[U256]) -> Option<U256> {
+        let n = U256::from(xp.len());
+        let sum = xp.iter().fold(U256::ZERO, |s, x| s + *x);
+        if sum.is_zero() || xp.iter().any(|x| x.is_zero()) {
+            return None;
+        }
+
+        let ann = self.a_parameter * n;
+        let mut d = sum;
+        for _ in 0..Self::MAX_ITERATIONS {
+            let d_p = xp.iter().fold(d, |d_p, x| d_p * d / (*x * n));
+            let previous = d;
+            d = (ann * sum + d_p * n) * d / ((ann - U256::from(1)) * d + (n + U256::from(1)) * d_p);
+            if d.abs_diff(previous) <= U256::from(1) {
+                return Some(d);
+            }
+        }
+        None
+    }
+
+    /// Normalized balance of coin `j` once coin `i` holds `x`, keeping D
+    fn get_y(&self, i: usize, j: usize, x: U256, xp: &[U256]) -> Option<U256> {
+        let d = self.get_d(xp)?;
+        let n = U256::from(xp.len());
+        let ann = self.a_parameter * n;
+
+        let mut c = d;
+        let mut sum = U256::ZERO;
+        for (k, balance) in xp.iter().enumerate() {
+            let balance = if k == i {
+                x
+            } else if k == j {
+                continue;
+            } else {
+                *balance
+            };
+            sum += balance;
+            c = c * d / (balance * n);
+        }
+        c = c * d / (ann * n);
+        let b = sum + d / ann;
+
+        let mut y = d;
+        for _ in 0..Self::MAX_ITERATIONS {
+            let previous = y;
+            y = (y * y + c) / (U256::from(2) * y + b - d);
+            if y.abs_diff(previous) <= U256::from(1) {
+                return Some(y);
+            }
+        }
+        None
+    }
+
+    /// Output of swapping `amount_in` of `token_in` for `token_out`, fee
+    /// taken, rounded down as `get_dy` does on chain. Zero when either
+    /// token isn't in the pool or the invariant can't be solved.
+    pub fn get_amount_out(&self, amount_in: U256, token_in: Address, token_out: Address) -> U256 {
+        let position = |token: Address| self.tokens.iter().position(|t| *t == token);
+        let (Some(i), Some(j)) = (position(token_in), position(token_out)) else {
+            return U256::ZERO;
+        };
+        if i == j || amount_in.is_zero() || self.a_parameter.is_zero() || self.balances.len() != self.tokens.len() {
+            return U256::ZERO;
+        }
+
+        let limit = Self::max_normalized();
+        let Some(xp) = self.balances
+            .iter()
+            .enumerate()
+            .map(|(k, b)| b.checked_mul(self.precision(k)).filter(|x| *x < limit))
+            .collect::<Option<Vec<U256>>>()
+        else {
+            return U256::ZERO;
+        };
+        let Some(x) = amount_in
+            .checked_mul(self.precision(i))
+            .and_then(|a| a.checked_add(xp[i]))
+            .filter(|x| *x < limit)
+        else {
+            return U256::ZERO;
+        };
+        let Some(y) = self.get_y(i, j, x, &xp) else {
+            return U256::ZERO;
+        };
+        if y + U256::from(1) >= xp[j] {
+            return U256::ZERO;
+        }
+
+        let dy = xp[j] - y - U256::from(1);
+        let fee = dy * U256::from(self.fee) / U256::from(10_000_000_000u64);
+        (dy - fee) / self.precision(j)
+    }
+
+    /// Input of `token_in` needed to receive `amount_out` of `token_out`:
+    /// the smallest amount `get_amount_out` turns into at least that much.
+    /// `U256::MAX` when the output can't be reached.
+    pub fn get_amount_in(&self, amount_out: U256, token_out: Address, token_in: Address) -> U256 {
+        invert_amount_out(amount_out, |amount_in| self.get_amount_out(amount_in, token_in, token_out))
+    }
+
+    /// The other coin of a two-coin pool
+    fn counterpart(&self, token: Address) -> Option<Address> {
+        match self.tokens.as_slice() {
+            [t0, t1] if token == *t0 => Some(*t1),
+            [t0, t1] if token == *t1 => Some(*t0),
+            _ => None,
+        }
+    }
+}
+
+/// Solidly / ve(3,3) style pool (Aerodrome, Velodrome forks)
+///
+/// Volatile pools use constant product; stable pools use `x³y + y³x = k`
+/// on reserves normalized to 18 decimals.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SolidlyPool {
+    pub address: Address,
+    pub token0: Address,
+    pub token1: Address,
+    pub reserve0: U256,
+    pub reserve1: U256,
+    pub decimals0: u8,
+    pub decimals1: u8,
+    pub stable: bool,
+    pub fee_bps: u16,  // Aerodrome: 5 stable, 30 volatile
+    pub chain: ChainId,
+    pub dex: DexProtocol,
+    pub block_number: u64,
+}
+
+impl SolidlyPool {
+    /// Newton iteration cap (matches the on-chain implementation)
+    const MAX_ITERATIONS: usize = 255;
+
+    fn one() -> U256 {
+        U256::from(1_000_000_000_000_000_000u128)
+    }
+
+    fn scale(decimals: u8) -> U256 {
+        U256::from(10u64).pow(U256::from(decimals))
+    }
+
+    /// Stable invariant on normalized reserves: x³y + y³x
+    fn k(x: U256, y: U256) -> U256 {
+        let one = Self::one();
+        let a = x * y / one;
+        let b = x * x / one + y * y / one;
+        a * b / one
+    }
+
+    fn f(x0: U256, y: U256) -> U256 {
+        let one = Self::one();
+        x0 * (y * y / one * y / one) / one + (x0 * x0 / one * x0 / one) * y / one
+    }
+
+    fn d(x0: U256, y: U256) -> U256 {
+        let one = Self::one();
+        U256::from(3) * x0 * (y * y / one) / one + (x0 * x0 / one * x0 / one)
+    }
+
+    /// Solve for y given x0 and the invariant, starting from `y`
+    fn get_y(x0: U256, xy: U256, mut y: U256) -> Option<U256> {
+        let one = Self::one();
+
+        for _ in 0..Self::MAX_ITERATIONS {
+            let k = Self::f(x0, y);
+            let derivative = Self::d(x0, y);
+            if derivative.is_zero() {
+                return None;
+            }
+
+            if k < xy {
+                let mut dy = (xy - k) * one / derivative;
+                if dy.is_zero() {
+                    if k == xy {
+                        return Some(y);
+                    }
+                    if Self::k(x0, y + U256::from(1)) > xy {
+                        return Some(y + U256::from(1));
+                    }
+                    dy = U256::from(1);
+                }
+                y += dy;
+            } else {
+                let mut dy = (k - xy) * one / derivative;
+                if dy.is_zero() {
+                    if k == xy || Self::f(x0, y - U256::from(1)) < xy {
+                        return Some(y);
+                    }
+                    dy = U256::from(1);
+                }
+                y = y.checked_sub(dy)?;
+            }
+        }
+
+        None
+    }
+
+    /// Calculate output amount using the stable or volatile curve,
+    /// net of any fee-on-transfer taxes
+    pub fn get_amount_out(&self, amount_in: U256, token_in: Address) -> U256 {
+        let token_out = if token_in == self.token0 { self.token1 } else { self.token0 };
+        let amount_in = apply_transfer_fee(self.chain, token_in, amount_in);
+        apply_transfer_fee(self.chain, token_out, self.curve_amount_out(amount_in, token_in))
+    }
+
+    /// Input needed to receive `amount_out` of `token_out`: the smallest
+    /// amount `get_amount_out` turns into at least that much. `U256::MAX`
+    /// when the output can't be reached.
+    pub fn get_amount_in(&self, amount_out: U256, token_out: Address) -> U256 {
+        let token_in = if token_out == self.token1 { self.token0 } else { self.token1 };
+        invert_amount_out(amount_out, |amount_in| self.get_amount_out(amount_in, token_in))
+    }
+
+    fn curve_amount_out(&self, amount_in: U256, token_in: Address) -> U256 {
+        if amount_in.is_zero() || self.reserve0.is_zero() || self.reserve1.is_zero() {
+            return U256::ZERO;
+        }
+
+        let zero_for_one = token_in == self.token0;
+        let amount_in = amount_in - amount_in * U256::from(self.fee_bps) / U256::from(10000);
+
+        if !self.stable {
+            let (reserve_in, reserve_out) = if zero_for_one {
+                (self.reserve0, self.reserve1)
+            } else {
+                (self.reserve1, self.reserve0)
+            };
+            return amount_in * reserve_out / (reserve_in + amount_in);
+        }
+
+        let one = Self::one();
+        let (scale0, scale1) = (Self::scale(self.decimals0), Self::scale(self.decimals1));
+        let r0 = self.reserve0 * one / scale0;
+        let r1 = self.reserve1 * one / scale1;
+        let xy = Self::k(r0, r1);
+
+        let (reserve_a, reserve_b, scale_in, scale_out) = if zero_for_one {
+            (r0, r1, scale0, scale1)
+        } else {
+            (r1, r0, scale1, scale0)
+        };
+
+        let normalized_in = amount_in * one / scale_in;
+        let Some(y) = Self::get_y(normalized_in + reserve_a, xy, reserve_b) else {
+            return U256::ZERO;
+        };
+
+        if y >= reserve_b {
+            return U256::ZERO;
+        }
+        (reserve_b - y) * scale_out / one
+    }
+
+    /// Marginal price (token1 per token0, raw units)
+    pub fn spot_price(&self) -> f64 {
+        if self.reserve0.is_zero() {
+            return 0.0;
+        }
+        let r0: f64 = self.reserve0.to_string().parse().unwrap_or(0.0);
+        let r1: f64 = self.reserve1.to_string().parse().unwrap_or(0.0);
+
+        if !self.stable {
+            return r1 / r0;
+        }
+
+        // dy/dx of x³y + y³x on decimal-normalized reserves
+        let x = r0 / 10f64.powi(self.decimals0 as i32);
+        let y = r1 / 10f64.powi(self.decimals1 as i32);
+        let human = (3.0 * x * x * y + y * y * y) / (x * x * x + 3.0 * x * y * y);
+        human * 10f64.powi(self.decimals1 as i32 - self.decimals0 as i32)
+    }
+
+    /// Marginal price in whole tokens (token1 per token0)
+    pub fn human_spot_price(&self) -> f64 {
+        to_human(self.spot_price(), self.decimals0, self.decimals1)
+    }
 }
 
 /// Generic pool enum for unified handling
@@ -170,6 +688,7 @@ pub enum Pool {
     UniswapV2(UniswapV2Pool),
     UniswapV3(UniswapV3Pool),
     Curve(CurvePool),
+    Solidly(SolidlyPool),
 }
 
 impl Pool {
@@ -178,6 +697,7 @@ impl Pool {
             Pool::UniswapV2(p) => p.address,
             Pool::UniswapV3(p) => p.address,
             Pool::Curve(p) => p.address,
+            Pool::Solidly(p) => p.address,
         }
     }
 
@@ -186,6 +706,7 @@ impl Pool {
             Pool::UniswapV2(p) => p.chain,
             Pool::UniswapV3(p) => p.chain,
             Pool::Curve(p) => p.chain,
+            Pool::Solidly(p) => p.chain,
         }
     }
 
@@ -194,8 +715,222 @@ impl Pool {
             Pool::UniswapV2(p) => p.block_number,
             Pool::UniswapV3(p) => p.block_number,
             Pool::Curve(p) => p.block_number,
+            Pool::Solidly(p) => p.block_number,
+        }
+    }
+
+    /// Gas to swap `amount_in` of `token_in` through this pool, including
+    /// any V3 tick crossings
+    pub fn swap_gas(&self, amount_in: U256, token_in: Address) -> u64 {
+        let crossings = match self {
+            Pool::UniswapV3(p) => p.ticks_crossed(amount_in, token_in),
+            _ => 0,
+        };
+        gas_units(self.dex(), crossings)
+    }
+
+    /// Swap fee as a fraction of the input (0.003 for 30 bps)
+    pub fn fee_percent(&self) -> f64 {
+        match self {
+            Pool::UniswapV2(p) => p.fee_bps as f64 / 10_000.0,
+            Pool::UniswapV3(p) => p.fee_percent(),
+            Pool::Curve(p) => p.fee_percent(),
+            Pool::Solidly(p) => p.fee_bps as f64 / 10_000.0,
+        }
+    }
+
+    /// Swap fee in whole bps. Fees finer than a bip (V3's 0.005% tiers,
+    /// Curve's 1e10 units) round up so a route never understates its cost.
+    pub fn fee_bps(&self) -> u16 {
+        match self {
+            Pool::UniswapV2(p) => p.fee_bps,
+            Pool::UniswapV3(p) => p.fee.div_ceil(100).min(u16::MAX as u32) as u16,
+            Pool::Curve(p) => p.fee.div_ceil(1_000_000).min(u16::MAX as u64) as u16,
+            Pool::Solidly(p) => p.fee_bps,
+        }
+    }
+
+    /// DEX the pool belongs to
+    pub fn dex(&self) -> DexProtocol {
+        match self {
+            Pool::UniswapV2(p) => p.dex,
+            Pool::UniswapV3(_) => DexProtocol::UniswapV3,
+            Pool::Curve(_) => DexProtocol::Curve,
+            Pool::Solidly(p) => p.dex,
+        }
+    }
+
+    /// Token balances held by the pool (virtual reserves for V3)
+    pub fn reserves(&self) -> Vec<(Address, U256)> {
+        match self {
+            Pool::UniswapV2(p) => vec![(p.token0, p.reserve0), (p.token1, p.reserve1)],
+            Pool::UniswapV3(p) => {
+                let (r0, r1) = p.virtual_reserves();
+                vec![(p.token0, r0), (p.token1, r1)]
+            }
+            Pool::Solidly(p) => vec![(p.token0, p.reserve0), (p.token1, p.reserve1)],
+            Pool::Curve(p) => p.tokens.iter().copied().zip(p.balances.iter().copied()).collect(),
+        }
+    }
+
+    /// Whether every reserve holds at least `floor` raw units
+    pub fn has_min_reserves(&self, floor: U256) -> bool {
+        match self {
+            Pool::UniswapV2(p) => p.has_min_reserves(floor),
+            _ => self.reserves().iter().all(|(_, reserve)| *reserve >= floor),
+        }
+    }
+
+    /// Token pair for two-token pools
+    pub fn tokens(&self) -> Option<(Address, Address)> {
+        match self {
+            Pool::UniswapV2(p) => Some((p.token0, p.token1)),
+            Pool::UniswapV3(p) => Some((p.token0, p.token1)),
+            Pool::Solidly(p) => Some((p.token0, p.token1)),
+            Pool::Curve(_) => None,
+        }
+    }
+
+    /// Decimals the pool records for one of its tokens
+    pub fn decimals(&self, token: Address) -> Option<u8> {
+        match self {
+            Pool::UniswapV2(p) => pair_decimals(token, (p.token0, p.decimals0), (p.token1, p.decimals1)),
+            Pool::UniswapV3(p) => pair_decimals(token, (p.token0, p.decimals0), (p.token1, p.decimals1)),
+            Pool::Solidly(p) => pair_decimals(token, (p.token0, p.decimals0), (p.token1, p.decimals1)),
+            Pool::Curve(p) => {
+                let index = p.tokens.iter().position(|t| *t == token)?;
+                if p.decimals.is_empty() {
+                    Some(default_decimals())
+                } else {
+                    p.decimals.get(index).copied()
+                }
+            }
+        }
+    }
+
+    /// Spot price of `base` in whole units of `quote` for two-token pools
+    /// holding both
+    pub fn human_price(&self, base: Address, quote: Address) -> Option<f64> {
+        let (token0, token1, price) = match self {
+            Pool::UniswapV2(p) => (p.token0, p.token1, p.human_spot_price()),
+            Pool::UniswapV3(p) => (p.token0, p.token1, p.human_price()),
+            Pool::Solidly(p) => (p.token0, p.token1, p.human_spot_price()),
+            Pool::Curve(_) => return None,
+        };
+
+        if price <= 0.0 || !price.is_finite() {
+            return None;
+        }
+        if base == token0 && quote == token1 {
+            Some(price)
+        } else if base == token1 && quote == token0 {
+            Some(1.0 / price)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the pool trades the given token
+    pub fn contains(&self, token: Address) -> bool {
+        match self {
+            Pool::Curve(p) => p.tokens.contains(&token),
+            _ => self.tokens().is_some_and(|(t0, t1)| t0 == token || t1 == token),
+        }
+    }
+
+    /// The counterpart token for a two-token pool
+    pub fn other_token(&self, token: Address) -> Option<Address> {
+        let (t0, t1) = self.tokens()?;
+        if token == t0 {
+            Some(t1)
+        } else if token == t1 {
+            Some(t0)
+        } else {
+            None
+        }
+    }
+
+    /// Output amount for swapping `amount_in` of `token_in` (zero if unsupported)
+    pub fn get_amount_out(&self, amount_in: U256, token_in: Address) -> U256 {
+        if !self.contains(token_in) {
+            return U256::ZERO;
+        }
+        match self {
+            Pool::UniswapV2(p) => p.get_amount_out(amount_in, token_in),
+            Pool::UniswapV3(p) => p.get_amount_out(amount_in, token_in),
+            Pool::Solidly(p) => p.get_amount_out(amount_in, token_in),
+            // Without an output token only two-coin pools are unambiguous
+            Pool::Curve(p) => p
+                .counterpart(token_in)
+                .map_or(U256::ZERO, |token_out| p.get_amount_out(amount_in, token_in, token_out)),
         }
     }
+
+    /// Input needed to receive `amount_out` of `token_out` (`U256::MAX` if
+    /// unreachable or unsupported)
+    pub fn get_amount_in(&self, amount_out: U256, token_out: Address) -> U256 {
+        if !self.contains(token_out) {
+            return U256::MAX;
+        }
+        match self {
+            Pool::UniswapV2(p) => p.get_amount_in(amount_out, token_out),
+            Pool::UniswapV3(p) => p.get_amount_in(amount_out, token_out),
+            Pool::Solidly(p) => p.get_amount_in(amount_out, token_out),
+            Pool::Curve(p) => p
+                .counterpart(token_out)
+                .map_or(U256::MAX, |token_in| p.get_amount_in(amount_out, token_out, token_in)),
+        }
+    }
+
+    /// Price impact of a trade as a fraction (1.0 when the trade can't be priced)
+    pub fn price_impact(&self, amount_in: U256, token_in: Address) -> f64 {
+        match self {
+            Pool::UniswapV2(p) => p.price_impact(amount_in, token_in),
+            Pool::UniswapV3(p) => {
+                let out = p.get_amount_out(amount_in, token_in);
+                if out.is_zero() {
+                    return 1.0;
+                }
+                let spot = if token_in == p.token0 {
+                    p.current_price()
+                } else {
+                    1.0 / p.current_price()
+                };
+                let in_f64: f64 = amount_in.to_string().parse().unwrap_or(0.0);
+                let out_f64: f64 = out.to_string().parse().unwrap_or(0.0);
+                // Strip the fee so impact reflects only the price move
+                let effective = out_f64 / (in_f64 * (1.0 - p.fee_percent()));
+                (1.0 - effective / spot).max(0.0)
+            }
+            Pool::Solidly(p) => {
+                let out = p.get_amount_out(amount_in, token_in);
+                if out.is_zero() {
+                    return 1.0;
+                }
+                let spot = if token_in == p.token0 {
+                    p.spot_price()
+                } else {
+                    1.0 / p.spot_price()
+                };
+                let in_f64: f64 = amount_in.to_string().parse().unwrap_or(0.0);
+                let out_f64: f64 = out.to_string().parse().unwrap_or(0.0);
+                let fee = p.fee_bps as f64 / 10_000.0;
+                let effective = out_f64 / (in_f64 * (1.0 - fee));
+                (1.0 - effective / spot).max(0.0)
+            }
+            Pool::Curve(_) => 1.0,
+        }
+    }
+}
+
+fn pair_decimals(token: Address, (token0, decimals0): (Address, u8), (token1, decimals1): (Address, u8)) -> Option<u8> {
+    if token == token0 {
+        Some(decimals0)
+    } else if token == token1 {
+        Some(decimals1)
+    } else {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -210,6 +945,8 @@ mod tests {
             token1: Address::repeat_byte(1),
             reserve0: U256::from(1_000_000_000_000u64), // 1M USDC (6 decimals)
             reserve1: U256::from(500_000_000_000_000_000_000u128), // 500 ETH (18 decimals)
+            decimals0: 6,
+            decimals1: 18,
             fee_bps: 30,
             chain: ChainId::Ethereum,
             dex: DexProtocol::UniswapV2,
@@ -225,17 +962,202 @@ mod tests {
         assert!(amount_out < U256::from(1_000_000_000_000_000_000u128)); // Less than 1 ETH
     }
 
+    #[test]
+    fn test_usdc_weth_spot_price_in_human_units() {
+        let (usdc, weth) = (Address::repeat_byte(1), Address::repeat_byte(2));
+
+        // 2M USDC : 1000 WETH
+        let v2 = Pool::UniswapV2(UniswapV2Pool {
+            address: Address::repeat_byte(0xAA),
+            token0: usdc,
+            token1: weth,
+            reserve0: U256::from(2_000_000_000_000u64),
+            reserve1: U256::from(1_000_000_000_000_000_000_000u128),
+            decimals0: 6,
+            decimals1: 18,
+            fee_bps: 30,
+            chain: ChainId::Ethereum,
+            dex: DexProtocol::UniswapV2,
+            block_number: 0,
+        });
+        // Raw units would claim 500M wei per micro-USDC
+        let Pool::UniswapV2(ref pair) = v2 else { unreachable!() };
+        assert!((pair.spot_price() - 5e8).abs() < 1e-3);
+        assert!((pair.human_spot_price() - 0.0005).abs() < 1e-12);
+        assert!((v2.human_price(weth, usdc).unwrap() - 2000.0).abs() < 1e-6);
+        assert_eq!(v2.decimals(usdc), Some(6));
+        assert_eq!(v2.decimals(Address::ZERO), None);
+
+        // Same price from a V3 pool: sqrtPriceX96 = sqrt(raw token1/token0) * 2^96
+        let v3 = Pool::UniswapV3(UniswapV3Pool {
+            address: Address::repeat_byte(0xBB),
+            token0: usdc,
+            token1: weth,
+            decimals0: 6,
+            decimals1: 18,
+            fee: 500,
+            tick_spacing: 10,
+            liquidity: 1_000_000_000_000_000_000,
+            sqrt_price_x96: U256::from((5e8f64.sqrt() * 2f64.powi(96)) as u128),
+            tick: 200_311,
+            ticks: vec![],
+            chain: ChainId::Ethereum,
+            block_number: 0,
+        });
+        let weth_usd = v3.human_price(weth, usdc).unwrap();
+        assert!((weth_usd - 2000.0).abs() / 2000.0 < 1e-9, "{}", weth_usd);
+        assert!(v3.human_price(usdc, Address::ZERO).is_none());
+    }
+
+    #[test]
+    fn test_pools_serialized_without_decimals_read_as_18() {
+        let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        // Pool JSON from before decimals were tracked
+        let strip = |pool: Pool, fields: &[&str]| {
+            let mut json = serde_json::to_value(pool).unwrap();
+            let inner = json.as_object_mut().unwrap().values_mut().next().unwrap();
+            for field in fields {
+                assert!(inner.as_object_mut().unwrap().remove(*field).is_some(), "{}", field);
+            }
+            serde_json::from_value::<Pool>(json).unwrap()
+        };
+
+        let v2 = strip(
+            Pool::UniswapV2(UniswapV2Pool {
+                address: Address::repeat_byte(0xAA),
+                token0: a,
+                token1: b,
+                reserve0: U256::from(1_000u64),
+                reserve1: U256::from(2_000u64),
+                decimals0: 6,
+                decimals1: 8,
+                fee_bps: 30,
+                chain: ChainId::Ethereum,
+                dex: DexProtocol::UniswapV2,
+                block_number: 1,
+            }),
+            &["decimals0", "decimals1"],
+        );
+        assert_eq!((v2.decimals(a), v2.decimals(b)), (Some(18), Some(18)));
+
+        let v3 = strip(
+            Pool::UniswapV3(UniswapV3Pool {
+                address: Address::repeat_byte(0xBB),
+                token0: a,
+                token1: b,
+                decimals0: 6,
+                decimals1: 8,
+                fee: 500,
+                tick_spacing: 10,
+                liquidity: 1_000,
+                sqrt_price_x96: U256::from(1u128 << 96),
+                tick: 0,
+                ticks: vec![],
+                chain: ChainId::Ethereum,
+                block_number: 1,
+            }),
+            &["decimals0", "decimals1", "ticks"],
+        );
+        assert_eq!((v3.decimals(a), v3.decimals(b)), (Some(18), Some(18)));
+
+        let curve = strip(
+            Pool::Curve(CurvePool {
+                address: Address::repeat_byte(0xCC),
+                tokens: vec![a, b],
+                balances: vec![U256::from(1_000u64), U256::from(1_000u64)],
+                decimals: vec![6, 8],
+                a_parameter: U256::from(200),
+                fee: 4_000_000,
+                chain: ChainId::Ethereum,
+                block_number: 1,
+            }),
+            &["decimals"],
+        );
+        assert_eq!((curve.decimals(a), curve.decimals(b)), (Some(18), Some(18)));
+        assert_eq!(curve.decimals(Address::ZERO), None);
+    }
+
+    #[test]
+    fn test_v2_near_max_reserves_do_not_overflow() {
+        let huge = U256::MAX >> 2;
+        let pool = UniswapV2Pool {
+            address: Address::ZERO,
+            token0: Address::ZERO,
+            token1: Address::repeat_byte(1),
+            reserve0: huge,
+            reserve1: huge,
+            decimals0: 18,
+            decimals1: 18,
+            fee_bps: 30,
+            chain: ChainId::Ethereum,
+            dex: DexProtocol::UniswapV2,
+            block_number: 0,
+        };
+
+        // 1e18 * 9970 * reserve overflows; the scaled quote is still ~0.997
+        let one = U256::from(1_000_000_000_000_000_000u128);
+        let out = pool.get_amount_out(one, Address::ZERO);
+        let ratio = out.to::<u128>() as f64 / 1e18;
+        assert!((ratio - 0.997).abs() < 1e-4, "{}", ratio);
+
+        // An input as large as the pool takes at most half of it
+        let out = pool.get_amount_out(huge, Address::ZERO);
+        assert!(out > U256::ZERO && out < huge / U256::from(2));
+
+        // Even a max input is quoted below the reserve rather than panicking
+        let out = pool.get_amount_out(U256::MAX, Address::ZERO);
+        assert!(out > U256::ZERO && out < huge);
+        assert_eq!(pool.get_amount_in(huge - U256::from(1), Address::repeat_byte(1)), U256::MAX);
+    }
+
+    #[test]
+    fn test_v2_fee_on_transfer_output_is_lower() {
+        let paxg = crate::get_token(ChainId::Ethereum, "PAXG").unwrap().address;
+        let weth = crate::get_token(ChainId::Ethereum, "WETH").unwrap().address;
+        let reserve = U256::from(1_000_000_000_000_000_000_000u128);
+
+        let pool = |token0: Address| UniswapV2Pool {
+            address: Address::ZERO,
+            token0,
+            token1: weth,
+            reserve0: reserve,
+            reserve1: reserve,
+            decimals0: 18,
+            decimals1: 18,
+            fee_bps: 30,
+            chain: ChainId::Ethereum,
+            dex: DexProtocol::UniswapV2,
+            block_number: 0,
+        };
+        let taxed = pool(paxg);
+        let naive = pool(Address::repeat_byte(0x77));
+
+        let amount_in = U256::from(1_000_000_000_000_000_000u128);
+        let taxed_sell = taxed.get_amount_out(amount_in, paxg);
+        let naive_sell = naive.get_amount_out(amount_in, Address::repeat_byte(0x77));
+        assert!(taxed_sell < naive_sell);
+
+        // Buying the token is taxed on the way out
+        let taxed_buy = taxed.get_amount_out(amount_in, weth);
+        let naive_buy = naive.get_amount_out(amount_in, weth);
+        assert_eq!(taxed_buy, apply_transfer_fee(ChainId::Ethereum, paxg, naive_buy));
+        assert!(taxed_buy < naive_buy);
+    }
+
     #[test]
     fn test_v3_price_calculation() {
         let pool = UniswapV3Pool {
             address: Address::ZERO,
             token0: Address::ZERO,
             token1: Address::repeat_byte(1),
+            decimals0: 18,
+            decimals1: 18,
             fee: 3000,
             tick_spacing: 60,
             liquidity: 1_000_000_000_000,
             sqrt_price_x96: U256::from(1u128 << 96), // Price = 1
             tick: 0,
+            ticks: vec![],
             chain: ChainId::Ethereum,
             block_number: 0,
         };
@@ -243,4 +1165,314 @@ mod tests {
         let price = pool.current_price();
         assert!((price - 1.0).abs() < 0.01);
     }
+
+    #[test]
+    fn test_fee_bps_rounds_sub_bip_tiers_up() {
+        let v3 = |fee: u32| Pool::UniswapV3(UniswapV3Pool {
+            address: Address::ZERO,
+            token0: Address::ZERO,
+            token1: Address::repeat_byte(1),
+            decimals0: 18,
+            decimals1: 18,
+            fee,
+            tick_spacing: 10,
+            liquidity: 0,
+            sqrt_price_x96: U256::ZERO,
+            tick: 0,
+            ticks: vec![],
+            chain: ChainId::Ethereum,
+            block_number: 0,
+        });
+
+        assert_eq!(v3(UniswapV3Pool::FEE_LOWEST).fee_bps(), 1);
+        assert_eq!(v3(UniswapV3Pool::FEE_LOW).fee_bps(), 5);
+        assert_eq!(v3(UniswapV3Pool::FEE_MEDIUM).fee_bps(), 30);
+        assert_eq!(v3(UniswapV3Pool::FEE_HIGH).fee_bps(), 100);
+        // 0.005% and 0.025% tiers would truncate to 0 and 2
+        assert_eq!(v3(50).fee_bps(), 1);
+        assert_eq!(v3(250).fee_bps(), 3);
+        assert!((v3(250).fee_percent() - 0.00025).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_v3_amount_out_within_tick() {
+        let pool = UniswapV3Pool {
+            address: Address::ZERO,
+            token0: Address::ZERO,
+            token1: Address::repeat_byte(1),
+            decimals0: 18,
+            decimals1: 18,
+            fee: 3000,
+            tick_spacing: 60,
+            liquidity: 1_000_000_000_000_000_000,
+            sqrt_price_x96: U256::from(1u128 << 96), // Price = 1
+            tick: 0,
+            ticks: vec![],
+            chain: ChainId::Ethereum,
+            block_number: 0,
+        };
+
+        // Small trade at price 1 returns ~amount minus the 0.3% fee
+        let amount_in = U256::from(1_000_000u64);
+        let out0 = pool.get_amount_out(amount_in, pool.token0);
+        let out1 = pool.get_amount_out(amount_in, pool.token1);
+
+        assert!(out0 > U256::from(996_000u64) && out0 < U256::from(997_000u64));
+        assert!(out1 > U256::from(996_000u64) && out1 < U256::from(997_000u64));
+
+        let wrapped = Pool::UniswapV3(pool);
+        assert!(wrapped.get_amount_out(amount_in, Address::repeat_byte(9)).is_zero());
+
+        // At price 1 both virtual reserves equal liquidity
+        let reserves = wrapped.reserves();
+        assert_eq!(reserves[0], (Address::ZERO, U256::from(1_000_000_000_000_000_000u128)));
+        assert_eq!(reserves[1].1, U256::from(1_000_000_000_000_000_000u128));
+    }
+
+    /// Price-1 pool with liquidity that thins out past ±60 and ±120 ticks
+    fn ticked_v3_pool() -> UniswapV3Pool {
+        const UNIT: i128 = 1_000_000_000_000_000_000;
+
+        // Positions over [-60, 60], [-120, 120] and a wide [-6000, 6000]
+        let positions = [(60, UNIT), (120, UNIT / 2), (6000, UNIT / 10)];
+        let mut ticks: Vec<TickInfo> = positions
+            .iter()
+            .flat_map(|&(width, l)| [
+                TickInfo { index: -width, liquidity_net: l },
+                TickInfo { index: width, liquidity_net: -l },
+            ])
+            .collect();
+        ticks.sort_by_key(|t| t.index);
+
+        UniswapV3Pool {
+            address: Address::ZERO,
+            token0: Address::ZERO,
+            token1: Address::repeat_byte(1),
+            decimals0: 18,
+            decimals1: 18,
+            fee: 3000,
+            tick_spacing: 60,
+            liquidity: 1_600_000_000_000_000_000,
+            sqrt_price_x96: U256::from(1u128 << 96), // Price = 1
+            tick: 0,
+            ticks,
+            chain: ChainId::Ethereum,
+            block_number: 0,
+        }
+    }
+
+    #[test]
+    fn test_v3_amount_out_crosses_ticks() {
+        let pool = ticked_v3_pool();
+        let single_tick = UniswapV3Pool { ticks: vec![], ..pool.clone() };
+
+        // Small trades stay in range and match the single-tick estimate
+        let small = U256::from(1_000_000u64);
+        assert_eq!(pool.get_amount_out(small, pool.token0), single_tick.get_amount_out(small, pool.token0));
+        assert_eq!(pool.swap(small, pool.token0).1, 0);
+
+        // 0.01 units exhausts the two narrow positions on either side
+        let amount_in = U256::from(10_000_000_000_000_000u128);
+        for token_in in [pool.token0, pool.token1] {
+            let (_, crossed) = pool.swap(amount_in, token_in);
+            assert_eq!(crossed, 2);
+
+            let multi: f64 = pool.get_amount_out(amount_in, token_in).to::<u128>() as f64;
+            let single: f64 = single_tick.get_amount_out(amount_in, token_in).to::<u128>() as f64;
+            assert!(multi > 0.0);
+            // ~1.2% less once liquidity thins out past the narrow ranges
+            assert!(multi < single * 0.995, "multi {} single {}", multi, single);
+            assert!(multi > single * 0.98, "multi {} single {}", multi, single);
+        }
+    }
+
+    #[test]
+    fn test_v3_amount_in_inverts_amount_out() {
+        let pool = ticked_v3_pool();
+        let amount_in = U256::from(10_000_000_000_000_000u128);
+
+        for token_in in [pool.token0, pool.token1] {
+            let token_out = if token_in == pool.token0 { pool.token1 } else { pool.token0 };
+            let target = pool.get_amount_out(amount_in, token_in);
+
+            // The smallest input reaching the target, across tick crossings
+            let required = pool.get_amount_in(target, token_out);
+            assert!(required <= amount_in);
+            assert!(pool.get_amount_out(required, token_in) >= target);
+            assert!(pool.get_amount_out(required - U256::from(1), token_in) < target);
+        }
+
+        let empty = UniswapV3Pool { liquidity: 0, ..pool.clone() };
+        assert_eq!(empty.get_amount_in(U256::from(1_000u64), pool.token1), U256::MAX);
+    }
+
+    #[test]
+    fn test_v3_crossings_add_swap_gas() {
+        let v2 = Pool::UniswapV2(UniswapV2Pool {
+            address: Address::ZERO,
+            token0: Address::ZERO,
+            token1: Address::repeat_byte(1),
+            reserve0: U256::from(1_000_000_000_000_000_000u128),
+            reserve1: U256::from(1_000_000_000_000_000_000u128),
+            decimals0: 18,
+            decimals1: 18,
+            fee_bps: 30,
+            chain: ChainId::Ethereum,
+            dex: DexProtocol::UniswapV2,
+            block_number: 0,
+        });
+        let v3 = Pool::UniswapV3(ticked_v3_pool());
+        let (small, large) = (U256::from(1_000_000u64), U256::from(10_000_000_000_000_000u128));
+
+        assert_eq!(v2.swap_gas(large, Address::ZERO), gas_units(DexProtocol::UniswapV2, 0));
+        assert_eq!(v3.swap_gas(small, Address::ZERO), gas_units(DexProtocol::UniswapV3, 0));
+        assert_eq!(v3.swap_gas(large, Address::ZERO), gas_units(DexProtocol::UniswapV3, 2));
+        assert!(v3.swap_gas(large, Address::ZERO) > v2.swap_gas(large, Address::ZERO));
+    }
+
+    fn aerodrome_pool(stable: bool) -> SolidlyPool {
+        SolidlyPool {
+            address: Address::ZERO,
+            token0: Address::ZERO,
+            token1: Address::repeat_byte(1),
+            reserve0: U256::from(1_000_000_000_000u64), // 1M USDC (6 decimals)
+            reserve1: U256::from(1_000_000_000_000u64), // 1M USDbC (6 decimals)
+            decimals0: 6,
+            decimals1: 6,
+            stable,
+            fee_bps: if stable { 5 } else { 30 },
+            chain: ChainId::Base,
+            dex: DexProtocol::Aerodrome,
+            block_number: 0,
+        }
+    }
+
+    #[test]
+    fn test_aerodrome_stable_vs_volatile() {
+        let stable = aerodrome_pool(true);
+        let volatile = aerodrome_pool(false);
+
+        // Swap 100k of a 1M/1M pool
+        let amount_in = U256::from(100_000_000_000u64);
+        let stable_out = stable.get_amount_out(amount_in, Address::ZERO);
+        let volatile_out = volatile.get_amount_out(amount_in, Address::ZERO);
+
+        // Constant product loses ~9% to curvature; the stable curve stays near 1:1
+        assert!(volatile_out < U256::from(91_000_000_000u64));
+        assert!(stable_out > U256::from(99_000_000_000u64));
+        assert!(stable_out < amount_in);
+
+        // Balanced stable pool is priced at parity
+        assert!((stable.spot_price() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_aerodrome_outputs_match_pool_contract() {
+        // Expected values from Aerodrome's `Pool.getAmountOut`, integer
+        // arithmetic and Newton iteration included
+        let usdc = |amount: u64| U256::from(amount);
+        let balanced = aerodrome_pool(true);
+        assert_eq!(balanced.get_amount_out(usdc(100_000_000_000), Address::ZERO), usdc(99_900_151_543));
+        assert_eq!(
+            aerodrome_pool(false).get_amount_out(usdc(100_000_000_000), Address::ZERO),
+            usdc(90_661_089_388)
+        );
+
+        let imbalanced = SolidlyPool {
+            reserve0: usdc(5_000_000_000_000),
+            reserve1: usdc(4_000_000_000_000),
+            ..aerodrome_pool(true)
+        };
+        assert_eq!(imbalanced.get_amount_out(usdc(250_000_000_000), Address::ZERO), usdc(248_489_702_740));
+
+        // 6 and 18 decimals, both directions
+        let mixed = SolidlyPool {
+            reserve1: U256::from(1_000_000_000_000_000_000_000_000u128),
+            decimals1: 18,
+            ..aerodrome_pool(true)
+        };
+        assert_eq!(
+            mixed.get_amount_out(usdc(1_000_000_000), Address::ZERO),
+            U256::from(999_499_999_500_999_250_748u128)
+        );
+        assert_eq!(
+            mixed.get_amount_out(U256::from(1_000_000_000_000_000_000_000u128), mixed.token1),
+            usdc(999_499_999)
+        );
+    }
+
+    #[test]
+    fn test_solidly_stable_mixed_decimals() {
+        let pool = SolidlyPool {
+            reserve0: U256::from(1_000_000_000_000u64),                  // 1M (6 decimals)
+            reserve1: U256::from(1_000_000_000_000_000_000_000_000u128), // 1M (18 decimals)
+            decimals1: 18,
+            ..aerodrome_pool(true)
+        };
+
+        let out = pool.get_amount_out(U256::from(1_000_000_000u64), Address::ZERO); // 1000
+        let out_human: f64 = out.to_string().parse::<f64>().unwrap() / 1e18;
+        assert!(out_human > 999.0 && out_human < 1000.0);
+    }
+
+    fn curve_pool(tokens: Vec<Address>, balances: Vec<U256>, decimals: Vec<u8>) -> CurvePool {
+        CurvePool {
+            address: Address::repeat_byte(0xCC),
+            tokens,
+            balances,
+            decimals,
+            a_parameter: U256::from(200),
+            fee: 4_000_000, // 0.04%
+            chain: ChainId::Ethereum,
+            block_number: 1,
+        }
+    }
+
+    #[test]
+    fn test_curve_stable_swap_near_parity() {
+        let (usdc, dai) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        // 1M USDC (6 decimals) against 1M DAI (18 decimals)
+        let pool = curve_pool(
+            vec![usdc, dai],
+            vec![U256::from(1_000_000_000_000u64), U256::from(1_000_000_000_000_000_000_000_000u128)],
+            vec![6, 18],
+        );
+
+        let out = pool.get_amount_out(U256::from(1_000_000_000u64), usdc, dai); // 1000 USDC
+        let out_human: f64 = out.to_string().parse::<f64>().unwrap() / 1e18;
+        assert!(out_human > 999.0 && out_human < 999.6, "{}", out_human);
+        assert_eq!(Pool::Curve(pool.clone()).get_amount_out(U256::from(1_000_000_000u64), usdc), out);
+
+        // Unknown tokens and empty swaps quote nothing
+        assert!(pool.get_amount_out(U256::from(1_000u64), usdc, Address::ZERO).is_zero());
+        assert!(pool.get_amount_out(U256::ZERO, usdc, dai).is_zero());
+    }
+
+    #[test]
+    fn test_curve_amount_in_inverts_amount_out() {
+        let (usdc, dai) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let pool = Pool::Curve(curve_pool(
+            vec![usdc, dai],
+            vec![U256::from(1_000_000_000_000u64), U256::from(1_000_000_000_000_000_000_000_000u128)],
+            vec![6, 18],
+        ));
+
+        let want = U256::from(999_000_000_000_000_000_000u128); // 999 DAI
+        let amount_in = pool.get_amount_in(want, dai);
+        assert!(amount_in < U256::MAX);
+        assert!(pool.get_amount_out(amount_in, usdc) >= want);
+        assert!(pool.get_amount_out(amount_in - U256::from(1), usdc) < want);
+
+        // More than the pool holds can't be bought
+        assert_eq!(pool.get_amount_in(U256::from(2_000_000u64) * U256::from(10u64).pow(U256::from(18)), dai), U256::MAX);
+
+        // With three coins the input token is ambiguous
+        let tri = Pool::Curve(curve_pool(
+            vec![usdc, dai, Address::repeat_byte(3)],
+            vec![U256::from(1_000_000u64); 3],
+            vec![18; 3],
+        ));
+        assert_eq!(tri.get_amount_in(U256::from(1_000u64), dai), U256::MAX);
+        assert!(tri.get_amount_out(U256::from(1_000u64), usdc).is_zero());
+    }
 }
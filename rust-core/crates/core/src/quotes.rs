@@ -2,8 +2,9 @@
 
 use alloy_primitives::{Address, U256};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
-use crate::{ChainId, DexProtocol, Pool};
+use crate::{hop_overhead, ChainId, CoreError, DexProtocol, Pool};
 
 /// A single swap step in a route
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +16,24 @@ pub struct SwapStep {
     pub amount_in: U256,
     pub amount_out: U256,
     pub fee_bps: u16,
+    /// Least output the hop may return before the swap reverts. Zero
+    /// leaves the hop unprotected.
+    #[serde(default)]
+    pub min_amount_out: U256,
+}
+
+impl SwapStep {
+    /// Protect the hop against `amount_out` falling by more than
+    /// `slippage_bps`
+    pub fn set_slippage(&mut self, slippage_bps: u16) {
+        self.min_amount_out = apply_slippage(self.amount_out, slippage_bps);
+    }
+}
+
+/// `amount` reduced by `slippage_bps`, rounded down
+pub fn apply_slippage(amount: U256, slippage_bps: u16) -> U256 {
+    let keep_bps = 10_000 - slippage_bps.min(10_000) as u64;
+    amount * U256::from(keep_bps) / U256::from(10_000u64)
 }
 
 /// A complete swap route (may be multi-hop)
@@ -47,6 +66,20 @@ impl SwapRoute {
         out_f64 / in_f64
     }
 
+    /// Set every step's minimum output to its quoted output less
+    /// `slippage_bps`
+    pub fn with_slippage(mut self, slippage_bps: u16) -> Self {
+        for step in &mut self.steps {
+            step.set_slippage(slippage_bps);
+        }
+        self
+    }
+
+    /// Every DEX the route swaps on
+    pub fn dexes(&self) -> HashSet<DexProtocol> {
+        self.steps.iter().map(|step| step.dex).collect()
+    }
+
     /// Get the token path
     pub fn token_path(&self) -> Vec<Address> {
         if self.steps.is_empty() {
@@ -60,10 +93,93 @@ impl SwapRoute {
         path
     }
 
+    /// The same token path walked backwards from `amount_in`, swapping
+    /// through `pools[i]` for step `i` of the reversed route.
+    ///
+    /// Every step is requoted against its pool's current state, since a
+    /// pool prices the two directions differently. Step minimums start at
+    /// zero. None unless there is one pool per step and each trades its
+    /// step's tokens.
+    pub fn reverse(&self, amount_in: U256, pools: &[&Pool]) -> Option<SwapRoute> {
+        if pools.len() != self.steps.len() {
+            return None;
+        }
+
+        let mut steps = Vec::with_capacity(self.steps.len());
+        let mut amount = amount_in;
+        let mut gas = 0;
+        let mut impact = 0.0;
+
+        for (step, pool) in self.steps.iter().rev().zip(pools) {
+            let (token_in, token_out) = (step.token_out, step.token_in);
+            if !pool.contains(token_in) || !pool.contains(token_out) {
+                return None;
+            }
+
+            let amount_out = pool.get_amount_out(amount, token_in);
+            gas += pool.swap_gas(amount, token_in);
+            impact += pool.price_impact(amount, token_in);
+            steps.push(SwapStep {
+                pool: pool.address(),
+                dex: pool.dex(),
+                token_in,
+                token_out,
+                amount_in: amount,
+                amount_out,
+                fee_bps: pool.fee_bps(),
+                min_amount_out: U256::ZERO,
+            });
+            amount = amount_out;
+        }
+
+        Some(SwapRoute {
+            gas_estimate: gas + hop_overhead(steps.len()),
+            steps,
+            chain: self.chain,
+            total_amount_in: amount_in,
+            total_amount_out: amount,
+            price_impact_bps: (impact * 10_000.0).min(u16::MAX as f64) as u16,
+        })
+    }
+
     /// Calculate total fees in bps
     pub fn total_fees_bps(&self) -> u32 {
         self.steps.iter().map(|s| s.fee_bps as u32).sum()
     }
+
+    /// Check that amounts flow through the steps: each step consumes the
+    /// previous step's output, and the totals match the first input and the
+    /// last output. Routes without steps carry only totals and always pass.
+    pub fn validate(&self) -> Result<(), CoreError> {
+        let (Some(first), Some(last)) = (self.steps.first(), self.steps.last()) else {
+            return Ok(());
+        };
+
+        if self.total_amount_in != first.amount_in {
+            return Err(CoreError::InvalidRoute(format!(
+                "total_amount_in {} != first step amount_in {}",
+                self.total_amount_in, first.amount_in
+            )));
+        }
+
+        for (i, pair) in self.steps.windows(2).enumerate() {
+            if pair[0].amount_out != pair[1].amount_in {
+                return Err(CoreError::InvalidRoute(format!(
+                    "step {} amount_out {} != step {} amount_in {}",
+                    i, pair[0].amount_out, i + 1, pair[1].amount_in
+                )));
+            }
+        }
+
+        if self.total_amount_out != last.amount_out {
+            return Err(CoreError::InvalidRoute(format!(
+                "total_amount_out {} != last step amount_out {}",
+                self.total_amount_out, last.amount_out
+            )));
+        }
+
+        Ok(())
+    }
 }
 
 /// Quote from a DEX
@@ -85,16 +201,28 @@ impl Quote {
     }
 }
 
+/// Which side of a quote is fixed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuoteKind {
+    /// Sell exactly this much `token_in`
+    ExactIn(U256),
+    /// Receive at least this much `token_out` for the smallest input
+    ExactOut(U256),
+}
+
 /// Quote request parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuoteRequest {
     pub chain: ChainId,
     pub token_in: Address,
     pub token_out: Address,
-    pub amount_in: U256,
+    pub kind: QuoteKind,
     pub slippage_bps: u16,
     pub max_hops: u8,
     pub deadline_ms: u64,
+    /// Pools every returned route must trade through
+    #[serde(default)]
+    pub must_use_pools: Vec<Address>,
 }
 
 impl QuoteRequest {
@@ -108,10 +236,24 @@ impl QuoteRequest {
             chain,
             token_in,
             token_out,
-            amount_in,
+            kind: QuoteKind::ExactIn(amount_in),
             slippage_bps: 50,  // 0.5% default
             max_hops: 3,
             deadline_ms: 30_000,  // 30 seconds
+            must_use_pools: Vec::new(),
+        }
+    }
+
+    /// Request the input needed to receive `amount_out` of `token_out`
+    pub fn exact_out(
+        chain: ChainId,
+        token_in: Address,
+        token_out: Address,
+        amount_out: U256,
+    ) -> Self {
+        Self {
+            kind: QuoteKind::ExactOut(amount_out),
+            ..Self::new(chain, token_in, token_out, U256::ZERO)
         }
     }
 
@@ -124,6 +266,12 @@ impl QuoteRequest {
         self.max_hops = hops;
         self
     }
+
+    /// Only return routes that trade through every one of `pools`
+    pub fn with_must_use_pools(mut self, pools: Vec<Address>) -> Self {
+        self.must_use_pools = pools;
+        self
+    }
 }
 
 /// Aggregated quotes from multiple DEXes
@@ -133,9 +281,23 @@ pub struct AggregatedQuotes {
     pub quotes: Vec<Quote>,
     pub best_quote_index: Option<usize>,
     pub timestamp_ms: u64,
+    /// Why no quotes were returned, when the request ruled every route out
+    #[serde(default)]
+    pub reason: Option<String>,
 }
 
 impl AggregatedQuotes {
+    /// No quotes for `request`, for the given reason
+    pub fn empty(request: QuoteRequest, timestamp_ms: u64, reason: impl Into<String>) -> Self {
+        Self {
+            request,
+            quotes: Vec::new(),
+            best_quote_index: None,
+            timestamp_ms,
+            reason: Some(reason.into()),
+        }
+    }
+
     pub fn best_quote(&self) -> Option<&Quote> {
         self.best_quote_index.and_then(|i| self.quotes.get(i))
     }
@@ -171,3 +333,122 @@ impl AggregatedQuotes {
         Some((spread * 10_000.0) as u16)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(amounts: &[u64]) -> SwapRoute {
+        SwapRoute {
+            steps: amounts
+                .windows(2)
+                .map(|w| SwapStep {
+                    pool: Address::ZERO,
+                    dex: DexProtocol::UniswapV2,
+                    token_in: Address::ZERO,
+                    token_out: Address::ZERO,
+                    amount_in: U256::from(w[0]),
+                    amount_out: U256::from(w[1]),
+                    fee_bps: 30,
+                    min_amount_out: U256::ZERO,
+                })
+                .collect(),
+            chain: ChainId::Ethereum,
+            total_amount_in: U256::from(amounts[0]),
+            total_amount_out: U256::from(amounts[amounts.len() - 1]),
+            gas_estimate: 0,
+            price_impact_bps: 0,
+        }
+    }
+
+    fn v2(address: u8, token0: u8, token1: u8, reserve0: u128, reserve1: u128) -> Pool {
+        Pool::UniswapV2(crate::UniswapV2Pool {
+            address: Address::repeat_byte(address),
+            token0: Address::repeat_byte(token0),
+            token1: Address::repeat_byte(token1),
+            reserve0: U256::from(reserve0),
+            reserve1: U256::from(reserve1),
+            decimals0: 18,
+            decimals1: 18,
+            fee_bps: 30,
+            chain: ChainId::Ethereum,
+            dex: DexProtocol::UniswapV2,
+            block_number: 1,
+        })
+    }
+
+    #[test]
+    fn test_reverse_requotes_opposite_path() {
+        let e18 = 1_000_000_000_000_000_000u128;
+        let (a, b, c) = (Address::repeat_byte(1), Address::repeat_byte(2), Address::repeat_byte(3));
+        let ab = v2(0xAB, 1, 2, 1_000 * e18, 2_000 * e18);
+        let bc = v2(0xBC, 2, 3, 5_000 * e18, 1_000 * e18);
+
+        // A -> B -> C, built by reversing an unquoted C -> B -> A path
+        let mut stub = route(&[0, 0, 0]);
+        (stub.steps[0].token_in, stub.steps[0].token_out) = (c, b);
+        (stub.steps[1].token_in, stub.steps[1].token_out) = (b, a);
+        let forward = stub.reverse(U256::from(10 * e18), &[&ab, &bc]).unwrap();
+        assert_eq!(forward.token_path(), vec![a, b, c]);
+        assert!(forward.validate().is_ok());
+        assert_eq!(forward.steps[0].amount_out, ab.get_amount_out(U256::from(10 * e18), a));
+        // Each hop's impact at the amount it trades
+        let impact = ab.price_impact(U256::from(10 * e18), a) + bc.price_impact(forward.steps[0].amount_out, b);
+        assert_eq!(forward.price_impact_bps, (impact * 10_000.0) as u16);
+        assert!(forward.price_impact_bps > 0);
+
+        let back = forward.reverse(forward.total_amount_out, &[&bc, &ab]).unwrap();
+        assert_eq!(back.token_path(), vec![c, b, a]);
+        assert!(back.validate().is_ok());
+        assert_eq!(back.steps[0].pool, bc.address());
+        // Requoted against the pool, not the forward step's amounts with labels swapped
+        assert_eq!(back.steps[0].amount_out, bc.get_amount_out(forward.total_amount_out, c));
+        assert_ne!(back.steps[0].amount_out, forward.steps[1].amount_in);
+        // The round trip pays fees twice
+        assert!(back.total_amount_out < forward.total_amount_in);
+
+        // Reversing again restores the forward path and its quotes
+        let again = back.reverse(U256::from(10 * e18), &[&ab, &bc]).unwrap();
+        assert_eq!(again.token_path(), forward.token_path());
+        assert_eq!(again.total_amount_out, forward.total_amount_out);
+
+        // One pool per step, each trading its step's tokens
+        assert!(forward.reverse(forward.total_amount_out, &[&bc]).is_none());
+        assert!(forward.reverse(forward.total_amount_out, &[&ab, &bc]).is_none());
+    }
+
+    #[test]
+    fn test_slippage_sets_each_step_minimum() {
+        let protected = route(&[1_000, 990, 2_000]).with_slippage(50);
+        let minimums: Vec<U256> = protected.steps.iter().map(|s| s.min_amount_out).collect();
+        // 990 * 0.995 = 985.05 rounds down
+        assert_eq!(minimums, vec![U256::from(985u64), U256::from(1_990u64)]);
+
+        assert_eq!(apply_slippage(U256::from(2_000u64), 0), U256::from(2_000u64));
+        assert_eq!(apply_slippage(U256::from(2_000u64), 20_000), U256::ZERO);
+    }
+
+    #[test]
+    fn test_validate_accepts_chained_amounts() {
+        assert!(route(&[1_000, 990, 2_000]).validate().is_ok());
+        assert!(route(&[1_000]).validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_broken_chain() {
+        // Step 2 claims more input than step 1 produced
+        let mut broken = route(&[1_000, 990, 2_000]);
+        broken.steps[1].amount_in = U256::from(1_500u64);
+        let err = broken.validate().unwrap_err();
+        assert!(matches!(err, CoreError::InvalidRoute(_)));
+        assert!(err.to_string().contains("step 0 amount_out 990 != step 1 amount_in 1500"));
+
+        let mut bad_total_in = route(&[1_000, 990]);
+        bad_total_in.total_amount_in = U256::from(500u64);
+        assert!(bad_total_in.validate().is_err());
+
+        let mut bad_total_out = route(&[1_000, 990]);
+        bad_total_out.total_amount_out = U256::from(5_000u64);
+        assert!(bad_total_out.validate().is_err());
+    }
+}
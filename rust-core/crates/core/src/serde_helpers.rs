@@ -0,0 +1,64 @@
+//! Serde helpers for types whose default encoding isn't JSON-friendly
+//!
+//! Use with `#[serde(with = "defi_core::serde_helpers::u256_decimal")]`.
+
+/// U256 as a decimal string, so JS clients don't lose precision
+pub mod u256_decimal {
+    use alloy_primitives::U256;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &U256, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<U256, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        U256::from_str_radix(&s, 10).map_err(D::Error::custom)
+    }
+}
+
+/// Duration as whole milliseconds
+pub mod duration_ms {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(value.as_millis() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        Ok(Duration::from_millis(u64::deserialize(deserializer)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::U256;
+    use serde::{Deserialize, Serialize};
+    use std::time::Duration;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Sample {
+        #[serde(with = "super::u256_decimal")]
+        amount: U256,
+        #[serde(with = "super::duration_ms")]
+        age: Duration,
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let sample = Sample {
+            amount: U256::from(1_000_000_000_000_000_000u128),
+            age: Duration::from_millis(1500),
+        };
+
+        let json = serde_json::to_string(&sample).unwrap();
+        assert_eq!(json, r#"{"amount":"1000000000000000000","age":1500}"#);
+        assert_eq!(serde_json::from_str::<Sample>(&json).unwrap(), sample);
+    }
+
+    #[test]
+    fn test_rejects_non_decimal_u256() {
+        assert!(serde_json::from_str::<Sample>(r#"{"amount":"0xff","age":0}"#).is_err());
+    }
+}
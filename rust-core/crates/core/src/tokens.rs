@@ -5,9 +5,10 @@
 //! - WBTC: 8 decimals
 //! - Most others: 18 decimals
 
-use alloy_primitives::Address;
+use alloy_primitives::{Address, U256};
+use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::LazyLock;
 
 use crate::ChainId;
@@ -60,6 +61,10 @@ pub static TOKENS: LazyLock<HashMap<ChainId, HashMap<&'static str, Token>>> = La
         "0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599".parse().unwrap(),
         "WBTC", "Wrapped Bitcoin", 8, ChainId::Ethereum  // ⚠️ 8 decimals!
     ));
+    eth_tokens.insert("PAXG", Token::new(
+        "0x45804880De22913dAFE09f4980848ECE6EcbAf78".parse().unwrap(),
+        "PAXG", "Paxos Gold", 18, ChainId::Ethereum  // ⚠️ fee on transfer!
+    ));
     chains.insert(ChainId::Ethereum, eth_tokens);
 
     // Arbitrum
@@ -113,6 +118,40 @@ pub static TOKENS: LazyLock<HashMap<ChainId, HashMap<&'static str, Token>>> = La
     chains
 });
 
+/// Known fee-on-transfer tokens: transfer tax in bps keyed by chain and address
+pub static FEE_ON_TRANSFER: LazyLock<HashMap<(ChainId, Address), u16>> = LazyLock::new(|| {
+    let mut fees = HashMap::new();
+
+    // PAXG charges 0.02% on every transfer
+    fees.insert(
+        (ChainId::Ethereum, "0x45804880De22913dAFE09f4980848ECE6EcbAf78".parse().unwrap()),
+        2,
+    );
+
+    fees
+});
+
+/// Stablecoin addresses per chain: the well-known ones from `TOKENS`, plus
+/// anything added with `register_stablecoin`
+static STABLECOINS: LazyLock<RwLock<HashMap<ChainId, HashSet<Address>>>> = LazyLock::new(|| {
+    let stables = TOKENS
+        .iter()
+        .map(|(chain, tokens)| {
+            let addresses = tokens
+                .values()
+                .filter(|t| is_stablecoin(&t.symbol))
+                .map(|t| t.address)
+                .collect();
+            (*chain, addresses)
+        })
+        .collect();
+    RwLock::new(stables)
+});
+
+/// Decimals of registered tokens that aren't in `TOKENS`
+static REGISTERED_DECIMALS: LazyLock<RwLock<HashMap<(ChainId, Address), u8>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
 /// Get token by symbol for a chain
 pub fn get_token(chain: ChainId, symbol: &str) -> Option<&'static Token> {
     TOKENS.get(&chain)?.get(symbol)
@@ -127,9 +166,85 @@ pub fn get_decimals(chain: ChainId, address: Address) -> u8 {
             }
         }
     }
+    if let Some(decimals) = REGISTERED_DECIMALS.read().get(&(chain, address)) {
+        return *decimals;
+    }
     18 // Default, but should log a warning in production
 }
 
+/// Get a well-known token by address
+pub fn get_token_by_address(chain: ChainId, address: Address) -> Option<&'static Token> {
+    TOKENS.get(&chain)?.values().find(|t| t.address == address)
+}
+
+/// Symbol of a well-known token, or its address shortened to `0x1234..abcd`
+pub fn token_label(chain: ChainId, address: Address) -> String {
+    match get_token_by_address(chain, address) {
+        Some(token) => token.symbol.clone(),
+        None => {
+            let hex = format!("{:x}", address);
+            format!("0x{}..{}", &hex[..4], &hex[hex.len() - 4..])
+        }
+    }
+}
+
+/// Wrapped form of the chain's native gas token (WETH, or WMATIC on Polygon)
+pub fn wrapped_native(chain: ChainId) -> Option<&'static Token> {
+    match chain {
+        ChainId::Polygon => get_token(chain, "WMATIC"),
+        _ => get_token(chain, "WETH"),
+    }
+}
+
+/// Well-known stablecoins on a chain
+pub fn stablecoins(chain: ChainId) -> Vec<&'static Token> {
+    TOKENS
+        .get(&chain)
+        .map(|tokens| tokens.values().filter(|t| is_stablecoin(&t.symbol)).collect())
+        .unwrap_or_default()
+}
+
+/// Treat `address` as a USD stablecoin on `chain` from now on, e.g. bridged
+/// or chain-specific stables like USDbC on Base.
+///
+/// `decimals` is recorded for tokens that aren't already well known so
+/// prices quoted against them come out in human units.
+pub fn register_stablecoin(chain: ChainId, address: Address, decimals: u8) {
+    if get_token_by_address(chain, address).is_none() {
+        REGISTERED_DECIMALS.write().insert((chain, address), decimals);
+    }
+    STABLECOINS.write().entry(chain).or_default().insert(address);
+}
+
+/// Whether `address` is a well-known or registered stablecoin on `chain`
+pub fn is_stablecoin_address(chain: ChainId, address: Address) -> bool {
+    STABLECOINS.read().get(&chain).is_some_and(|s| s.contains(&address))
+}
+
+/// Well-known and registered stablecoin addresses on a chain, in address order
+pub fn stablecoin_addresses(chain: ChainId) -> Vec<Address> {
+    let mut addresses: Vec<Address> = STABLECOINS
+        .read()
+        .get(&chain)
+        .map(|s| s.iter().copied().collect())
+        .unwrap_or_default();
+    addresses.sort();
+    addresses
+}
+
+/// Transfer tax charged by a token, in bps (0 for standard ERC20s)
+pub fn transfer_fee_bps(chain: ChainId, address: Address) -> u16 {
+    FEE_ON_TRANSFER.get(&(chain, address)).copied().unwrap_or(0)
+}
+
+/// Amount actually received after a transfer of `amount`
+pub fn apply_transfer_fee(chain: ChainId, address: Address, amount: U256) -> U256 {
+    match transfer_fee_bps(chain, address) {
+        0 => amount,
+        fee_bps => amount - amount * U256::from(fee_bps) / U256::from(10000),
+    }
+}
+
 /// Check if token is a stablecoin
 pub fn is_stablecoin(symbol: &str) -> bool {
     matches!(symbol.to_uppercase().as_str(), "USDC" | "USDT" | "DAI" | "FRAX" | "LUSD")
@@ -151,6 +266,63 @@ mod tests {
         assert_eq!(wbtc.decimals, 8, "WBTC must have 8 decimals!");
     }
 
+    #[test]
+    fn test_wrapped_native() {
+        assert_eq!(wrapped_native(ChainId::Arbitrum).unwrap().symbol, "WETH");
+        assert_eq!(wrapped_native(ChainId::Polygon).unwrap().symbol, "WMATIC");
+    }
+
+    #[test]
+    fn test_token_by_address() {
+        let usdc = get_token(ChainId::Arbitrum, "USDC").unwrap();
+        let found = get_token_by_address(ChainId::Arbitrum, usdc.address).unwrap();
+        assert_eq!(found.symbol, "USDC");
+        assert!(get_token_by_address(ChainId::Ethereum, usdc.address).is_none());
+    }
+
+    #[test]
+    fn test_token_label() {
+        let weth = get_token(ChainId::Ethereum, "WETH").unwrap().address;
+        assert_eq!(token_label(ChainId::Ethereum, weth), "WETH");
+        assert_eq!(token_label(ChainId::Ethereum, Address::repeat_byte(0xab)), "0xabab..abab");
+    }
+
+    #[test]
+    fn test_stablecoins_per_chain() {
+        let symbols: Vec<&str> = stablecoins(ChainId::Base).iter().map(|t| t.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["USDC"]);
+    }
+
+    #[test]
+    fn test_fee_on_transfer_tokens() {
+        let paxg = get_token(ChainId::Ethereum, "PAXG").unwrap();
+        assert_eq!(transfer_fee_bps(ChainId::Ethereum, paxg.address), 2);
+
+        let amount = U256::from(1_000_000u64);
+        assert_eq!(apply_transfer_fee(ChainId::Ethereum, paxg.address, amount), U256::from(999_800u64));
+
+        let weth = get_token(ChainId::Ethereum, "WETH").unwrap();
+        assert_eq!(apply_transfer_fee(ChainId::Ethereum, weth.address, amount), amount);
+    }
+
+    #[test]
+    fn test_registered_stablecoins() {
+        let usdc = get_token(ChainId::Base, "USDC").unwrap().address;
+        assert!(is_stablecoin_address(ChainId::Base, usdc));
+        assert!(!is_stablecoin_address(ChainId::Base, wrapped_native(ChainId::Base).unwrap().address));
+
+        let usdbc: Address = "0xd9aAEc86B65D86f6A7B5B1b0c42FFA531710b6CA".parse().unwrap();
+        assert!(!is_stablecoin_address(ChainId::Base, usdbc));
+        assert_eq!(get_decimals(ChainId::Base, usdbc), 18);
+
+        register_stablecoin(ChainId::Base, usdbc, 6);
+        assert!(is_stablecoin_address(ChainId::Base, usdbc));
+        assert!(!is_stablecoin_address(ChainId::Ethereum, usdbc));
+        assert_eq!(get_decimals(ChainId::Base, usdbc), 6);
+        assert!(stablecoin_addresses(ChainId::Base).contains(&usdbc));
+        assert!(stablecoin_addresses(ChainId::Base).contains(&usdc));
+    }
+
     #[test]
     fn test_stablecoin_detection() {
         assert!(is_stablecoin("USDC"));
@@ -41,6 +41,12 @@ impl ChainId {
             ChainId::Polygon => 2000,
         }
     }
+
+    /// Whether the priority fee influences inclusion. Arbitrum's sequencer
+    /// orders transactions first-come first-served and ignores tips.
+    pub fn uses_priority_fee(&self) -> bool {
+        !matches!(self, ChainId::Arbitrum)
+    }
 }
 
 impl fmt::Display for ChainId {
@@ -64,6 +70,19 @@ pub enum DexProtocol {
 }
 
 impl DexProtocol {
+    /// Every supported protocol
+    pub const ALL: [DexProtocol; 9] = [
+        DexProtocol::UniswapV2,
+        DexProtocol::UniswapV3,
+        DexProtocol::SushiSwap,
+        DexProtocol::Curve,
+        DexProtocol::Balancer,
+        DexProtocol::AaveV3,
+        DexProtocol::Camelot,
+        DexProtocol::Aerodrome,
+        DexProtocol::QuickSwap,
+    ];
+
     pub fn name(&self) -> &'static str {
         match self {
             DexProtocol::UniswapV2 => "uniswap-v2",
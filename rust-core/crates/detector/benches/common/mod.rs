@@ -0,0 +1,64 @@
+//! Synthetic pools and pair walks shared by the benchmark targets
+
+use std::time::Instant;
+
+use alloy_primitives::{Address, U256};
+
+use defi_core::{ChainId, DexProtocol, Pool, UniswapV2Pool};
+use defi_detector::ChainSnapshot;
+use defi_price_feed::PoolEntry;
+
+/// Pools per token pair in the scaling benchmarks
+pub const POOLS_PER_PAIR: usize = 4;
+
+/// Synthetic V2 pools spread over `pairs` token pairs
+pub fn synthetic_pools(count: usize, pairs: usize) -> Vec<PoolEntry> {
+    (0..count)
+        .map(|i| {
+            let pair = (i % pairs) as u64;
+            let token0 = Address::from_word(U256::from(pair * 2 + 1).into());
+            let token1 = Address::from_word(U256::from(pair * 2 + 2).into());
+            let skew = 1_000 + (i % 7) as u128;
+
+            PoolEntry {
+                pool: Pool::UniswapV2(UniswapV2Pool {
+                    address: Address::from_word(U256::from(1_000_000 + i as u64).into()),
+                    token0,
+                    token1,
+                    reserve0: U256::from(1_000_000_000_000_000_000_000u128),
+                    reserve1: U256::from(skew * 1_000_000_000_000_000_000u128),
+                    decimals0: 18,
+                    decimals1: 18,
+                    fee_bps: 30,
+                    chain: ChainId::Ethereum,
+                    dex: DexProtocol::UniswapV2,
+                    block_number: 1,
+                }),
+                updated_at: Instant::now(),
+            }
+        })
+        .collect()
+}
+
+/// Snapshot of 10,000 pools, the size the pair walk groups run at
+pub fn pair_walk_snapshot() -> ChainSnapshot {
+    ChainSnapshot::new(ChainId::Ethereum, synthetic_pools(10_000, 10_000 / POOLS_PER_PAIR))
+}
+
+/// Address keyed: copy every pair out, then hash it and collect its pools
+pub fn address_pair_walk(snapshot: &ChainSnapshot) -> usize {
+    snapshot
+        .pairs()
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|(a, b)| snapshot.pair_pools(a, b).len())
+        .sum()
+}
+
+/// Interned: walk pair ids and borrow their pool indices
+pub fn interned_pair_walk(snapshot: &ChainSnapshot) -> usize {
+    snapshot
+        .pair_ids()
+        .map(|id| snapshot.pair_pool_indices(id).len())
+        .sum()
+}
@@ -0,0 +1,120 @@
+//! Detection benchmarks
+//!
+//! Run with: cargo bench -p defi-detector. See README.md alongside.
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use alloy_primitives::Address;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use defi_core::ChainId;
+use defi_detector::snapshot::normalize_pair;
+use defi_detector::{ArbitrageScanner, ChainSnapshot, CrossDexStrategy, ScannerConfig, Strategy};
+use defi_price_feed::{PoolEntry, PriceState};
+
+use common::{address_pair_walk, interned_pair_walk, pair_walk_snapshot, synthetic_pools, POOLS_PER_PAIR};
+
+/// Pool counts every scaling benchmark runs at
+const POOL_COUNTS: [usize; 3] = [100, 1_000, 10_000];
+
+/// Previous approach: derive pairs, then linearly re-filter every pool per pair
+fn naive_pair_lookup(pools: &[PoolEntry]) -> usize {
+    let mut pairs: Vec<(Address, Address)> = Vec::new();
+    for entry in pools {
+        if let Some((t0, t1)) = entry.pool.tokens() {
+            let pair = normalize_pair(t0, t1);
+            if !pairs.contains(&pair) {
+                pairs.push(pair);
+            }
+        }
+    }
+
+    pairs
+        .iter()
+        .map(|pair| {
+            pools
+                .iter()
+                .filter(|p| p.pool.tokens().map(|(a, b)| normalize_pair(a, b)) == Some(*pair))
+                .count()
+        })
+        .sum()
+}
+
+/// Snapshot approach: index once, then O(1) lookups per pair
+fn snapshot_pair_lookup(pools: &[PoolEntry]) -> usize {
+    let snapshot = ChainSnapshot::new(ChainId::Ethereum, pools.to_vec());
+    snapshot
+        .pairs()
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|(a, b)| snapshot.pair_pools(a, b).len())
+        .sum()
+}
+
+fn bench_pair_indexing(c: &mut Criterion) {
+    let pools = synthetic_pools(1_000, 250);
+
+    let mut group = c.benchmark_group("pair_index_1000_pools");
+    group.bench_function("naive_rescan", |b| b.iter(|| naive_pair_lookup(black_box(&pools))));
+    group.bench_function("chain_snapshot", |b| b.iter(|| snapshot_pair_lookup(black_box(&pools))));
+    group.finish();
+}
+
+fn bench_pair_interning(c: &mut Criterion) {
+    let snapshot = pair_walk_snapshot();
+    assert_eq!(address_pair_walk(&snapshot), interned_pair_walk(&snapshot));
+
+    let mut group = c.benchmark_group("pair_walk_10000_pools");
+    group.bench_function("address_keyed", |b| b.iter(|| address_pair_walk(black_box(&snapshot))));
+    group.bench_function("interned", |b| b.iter(|| interned_pair_walk(black_box(&snapshot))));
+    group.finish();
+}
+
+/// Price state holding `count` pools, as the scanner would see it live
+fn synthetic_state(count: usize) -> Arc<PriceState> {
+    let state = Arc::new(PriceState::new());
+    for entry in synthetic_pools(count, count / POOLS_PER_PAIR) {
+        state.update_pool(entry.pool);
+    }
+    state
+}
+
+fn bench_cross_dex(c: &mut Criterion) {
+    let state = Arc::new(PriceState::new());
+    let strategy = CrossDexStrategy::new();
+
+    let mut group = c.benchmark_group("cross_dex_find_opportunities");
+    for count in POOL_COUNTS {
+        let snapshot = ChainSnapshot::new(ChainId::Ethereum, synthetic_pools(count, count / POOLS_PER_PAIR));
+        group.bench_with_input(BenchmarkId::from_parameter(count), &snapshot, |b, snapshot| {
+            b.iter(|| strategy.find_opportunities(black_box(snapshot), &state))
+        });
+    }
+    group.finish();
+}
+
+fn bench_scan_once(c: &mut Criterion) {
+    // Pools are only written once per state, so keep them fresh for the
+    // whole run or every iteration after the first scans nothing
+    let config = ScannerConfig {
+        enabled_chains: vec![ChainId::Ethereum],
+        max_price_age: Duration::from_secs(3600),
+        ..Default::default()
+    };
+
+    let mut group = c.benchmark_group("scanner_scan_once");
+    group.sample_size(20);
+    for count in POOL_COUNTS {
+        let scanner = ArbitrageScanner::new(config.clone(), synthetic_state(count));
+        group.bench_with_input(BenchmarkId::from_parameter(count), &scanner, |b, scanner| {
+            b.iter(|| scanner.scan_once())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_pair_indexing, bench_pair_interning, bench_cross_dex, bench_scan_once);
+criterion_main!(benches);
@@ -7,9 +7,17 @@
 //! - Sub-millisecond detection latency
 
 pub mod scanner;
+pub mod snapshot;
 pub mod strategies;
 pub mod optimizer;
+pub mod quotes;
+pub mod queue;
+pub mod sink;
 
-pub use scanner::ArbitrageScanner;
-pub use strategies::{CrossDexStrategy, TriangularStrategy, Strategy};
-pub use optimizer::RouteOptimizer;
+pub use scanner::{ArbitrageScanner, ScannerConfig, ScannerStats};
+pub use strategies::{CrossDexStrategy, MultiHopStrategy, TriangularStrategy, Strategy};
+pub use optimizer::{ConfidenceModel, RouteOptimizer};
+pub use quotes::{QuoteEngine, QuoteEngineConfig};
+pub use queue::OpportunityQueue;
+pub use sink::{OpportunitySink, OpportunitySinkConfig};
+pub use snapshot::{ChainSnapshot, PairId};
@@ -1,48 +1,149 @@
 //! Route optimization for arbitrage opportunities
 
 use alloy_primitives::U256;
-use defi_core::{ArbitrageOpportunity, GasPrice};
+use std::time::Duration;
+use defi_core::{
+    get_decimals, opportunity_gas_units, wrapped_native, ArbitrageOpportunity, ArbitrageType, CoreError,
+    FlashLoanConfig, GasPrice, RiskConfig, SwapRoute,
+};
+use defi_price_feed::PriceState;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Weights for the execution confidence score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfidenceModel {
+    /// Starting confidence before any penalty
+    pub base: f64,
+    /// Each competing tx divides confidence by `1 + competition_weight * n`
+    pub competition_weight: f64,
+    /// Multiplier per hop beyond `free_hops`
+    pub hop_decay: f64,
+    pub free_hops: usize,
+    /// Profits under this many bps are scaled by `small_profit_factor`
+    pub small_profit_bps: i32,
+    pub small_profit_factor: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Default for ConfidenceModel {
+    fn default() -> Self {
+        Self {
+            base: 0.9,
+            competition_weight: 0.2,
+            hop_decay: 0.9,
+            free_hops: 2,
+            small_profit_bps: 20,
+            small_profit_factor: 0.8,
+            min: 0.1,
+            max: 0.99,
+        }
+    }
+}
+
+/// Oldest USD reference price opportunities may be sized against
+pub(crate) const DEFAULT_MAX_REF_PRICE_AGE: Duration = Duration::from_secs(10);
 
 /// Route optimizer - refines opportunities for execution
+#[derive(Clone)]
 pub struct RouteOptimizer {
     min_profit_after_gas: U256,
+    /// Net profit must be at least this multiple of gas cost; zero disables
+    min_profit_gas_multiple: f64,
     gas_price: Option<GasPrice>,
+    max_position_usd: Option<f64>,
+    flash_loan: FlashLoanConfig,
+    confidence: ConfidenceModel,
+    max_ref_price_age: Duration,
 }
 
 impl RouteOptimizer {
     pub fn new() -> Self {
         Self {
             min_profit_after_gas: U256::from(1_000_000_000_000_000u128), // 0.001 ETH
+            min_profit_gas_multiple: 0.0,
             gas_price: None,
+            max_position_usd: None,
+            flash_loan: FlashLoanConfig::default(),
+            confidence: ConfidenceModel::default(),
+            max_ref_price_age: DEFAULT_MAX_REF_PRICE_AGE,
         }
     }
 
+    /// Reject opportunities whose USD reference price is older than `max_age`
+    pub fn with_max_ref_price_age(mut self, max_age: Duration) -> Self {
+        self.max_ref_price_age = max_age;
+        self
+    }
+
+    pub fn with_confidence_model(mut self, model: ConfidenceModel) -> Self {
+        self.confidence = model;
+        self
+    }
+
     pub fn with_min_profit(mut self, min: U256) -> Self {
         self.min_profit_after_gas = min;
         self
     }
 
+    /// Reject opportunities whose net profit is under `multiple` times
+    /// their gas cost, leaving headroom for gas to rise before inclusion.
+    /// Gas is converted into the input token through the native token's USD
+    /// price; opportunities whose gas can't be converted are rejected.
+    pub fn with_min_profit_gas_multiple(mut self, multiple: f64) -> Self {
+        self.min_profit_gas_multiple = multiple.max(0.0);
+        self
+    }
+
+    /// Cap input size at the risk config's maximum position
+    pub fn with_risk_config(self, risk: &RiskConfig) -> Self {
+        self.with_max_position_usd(risk.max_position_usd)
+    }
+
+    pub fn with_max_position_usd(mut self, max_usd: f64) -> Self {
+        self.max_position_usd = Some(max_usd);
+        self
+    }
+
+    /// Operator capital and flash loan provider. Without flash loans, sizes
+    /// are capped at the available capital; with them, larger sizes are
+    /// borrowed and the provider fee is charged against profit.
+    pub fn with_flash_loan(mut self, config: FlashLoanConfig) -> Self {
+        self.flash_loan = config;
+        self
+    }
+
     pub fn update_gas_price(&mut self, gas_price: GasPrice) {
         self.gas_price = Some(gas_price);
     }
 
-    /// Optimize an opportunity for execution
-    pub fn optimize(&self, mut opp: ArbitrageOpportunity) -> Option<ArbitrageOpportunity> {
+    /// Optimize an opportunity for execution, pricing its gas against the
+    /// minimum gas multiple from `state`
+    pub fn optimize(&self, mut opp: ArbitrageOpportunity, state: &PriceState) -> Option<ArbitrageOpportunity> {
+        // Profit computed from routes whose amounts don't chain is meaningless
+        if let Err(e) = opp.buy_route.validate().and_then(|_| opp.sell_route.validate()) {
+            debug!("Dropping opportunity {}: {}", opp.id, e);
+            return None;
+        }
+
         // Calculate actual gas cost
         if let Some(gas_price) = &self.gas_price {
-            let gas_units = opp.buy_route.gas_estimate + opp.sell_route.gas_estimate;
-            opp.gas_cost_wei = gas_price.estimate_cost(gas_units);
+            opp.gas_cost_wei = gas_price.estimate_cost(opportunity_gas_units(&opp));
         }
 
         // Recalculate net profit
-        if opp.gross_profit > opp.gas_cost_wei {
-            opp.net_profit = opp.gross_profit - opp.gas_cost_wei;
-        } else {
-            opp.net_profit = U256::ZERO;
-        }
+        opp.net_profit = opp.gross_profit
+            .saturating_sub(opp.gas_cost_wei)
+            .saturating_sub(opp.flash_loan_fee);
 
         // Filter unprofitable opportunities
-        if opp.net_profit < self.min_profit_after_gas {
+        let Some(min_profit_for_gas) = self.min_profit_for_gas(&opp, state) else {
+            debug!("Dropping opportunity {}: gas can't be priced in {}", opp.id, opp.token_a);
+            return None;
+        };
+        if opp.net_profit < self.min_profit_after_gas || opp.net_profit < min_profit_for_gas {
             return None;
         }
 
@@ -59,40 +160,200 @@ impl RouteOptimizer {
         Some(opp)
     }
 
+    /// Net profit, in raw units of the input token, required to cover the
+    /// opportunity's gas by the configured multiple. None when the multiple
+    /// is set but gas can't be priced in the input token.
+    fn min_profit_for_gas(&self, opp: &ArbitrageOpportunity, state: &PriceState) -> Option<U256> {
+        if self.min_profit_gas_multiple == 0.0 {
+            return Some(U256::ZERO);
+        }
+        let multiple_bps = (self.min_profit_gas_multiple * 10_000.0).round() as u64;
+        let gas_cost = gas_cost_in_input(opp, state)?;
+        Some(gas_cost.saturating_mul(U256::from(multiple_bps)) / U256::from(10_000u64))
+    }
+
     /// Calculate confidence score for an opportunity
     fn calculate_confidence(&self, opp: &ArbitrageOpportunity) -> f64 {
-        let mut confidence = 0.9;
+        let model = &self.confidence;
+        let mut confidence = model.base;
 
         // Reduce confidence if there's competition
         if opp.competing_txs > 0 {
-            confidence *= 1.0 / (1.0 + opp.competing_txs as f64 * 0.2);
+            confidence *= 1.0 / (1.0 + opp.competing_txs as f64 * model.competition_weight);
         }
 
         // Reduce confidence for multi-hop routes
         let total_hops = opp.buy_route.hop_count() + opp.sell_route.hop_count();
-        if total_hops > 2 {
-            confidence *= 0.9f64.powi((total_hops - 2) as i32);
+        if total_hops > model.free_hops {
+            confidence *= model.hop_decay.powi((total_hops - model.free_hops) as i32);
         }
 
         // Reduce confidence for small profits (more susceptible to slippage)
-        if opp.profit_bps < 20 {
-            confidence *= 0.8;
+        if opp.profit_bps < model.small_profit_bps {
+            confidence *= model.small_profit_factor;
         }
 
-        confidence.max(0.1).min(0.99)
+        confidence.max(model.min).min(model.max)
     }
 
-    /// Find optimal input amount for maximum profit
-    pub fn optimize_size(&self, opp: &ArbitrageOpportunity) -> U256 {
+    /// Find optimal input amount for maximum profit, capped at the
+    /// maximum position size
+    pub fn optimize_size(&self, opp: &ArbitrageOpportunity, state: &PriceState) -> U256 {
         // Binary search for optimal size
         // This is a simplified version - production would simulate at multiple sizes
 
         let min_size = opp.input_amount / U256::from(10);
         let max_size = opp.input_amount * U256::from(10);
 
-        // For now, return the current size
-        opp.input_amount
+        // For now, the current size is the optimum
+        let optimal = opp.input_amount;
+
+        match self.max_position_amount(opp, state) {
+            Some(cap) => optimal.min(cap),
+            None => optimal,
+        }
+    }
+
+    /// Largest input amount whose USD value stays within the position cap,
+    /// and within operator capital when it can't be topped up by a flash loan.
+    /// None when there is no cap or the input token can't be priced.
+    fn max_position_amount(&self, opp: &ArbitrageOpportunity, state: &PriceState) -> Option<U256> {
+        let capital_usd = self.flash_loan.available_capital_usd.filter(|_| !self.flash_loan.enabled);
+        let max_usd = match (self.max_position_usd, capital_usd) {
+            (Some(position), Some(capital)) => position.min(capital),
+            (position, capital) => position.or(capital)?,
+        };
+        let price = self.reference_price(opp, state).ok().flatten()?;
+        if price <= 0.0 {
+            return None;
+        }
+
+        let decimals = get_decimals(opp.chain, opp.token_a);
+        let max_amount = max_usd / price * 10f64.powi(decimals as i32);
+        Some(U256::from(max_amount as u128))
+    }
+
+    /// USD price of the input token used for sizing and profit reporting.
+    ///
+    /// `Ok(None)` when the token can't be priced. Prices derived from pools
+    /// older than the configured maximum are `CoreError::StalePrice`: sizing
+    /// against them risks trading far more than the cap allows.
+    pub fn reference_price(
+        &self,
+        opp: &ArbitrageOpportunity,
+        state: &PriceState,
+    ) -> Result<Option<f64>, CoreError> {
+        let Some((price, age)) = state.get_usd_price_with_age(opp.chain, opp.token_a) else {
+            return Ok(None);
+        };
+
+        if age > self.max_ref_price_age {
+            return Err(CoreError::StalePrice {
+                age_ms: age.as_millis() as u64,
+                max_ms: self.max_ref_price_age.as_millis() as u64,
+            });
+        }
+
+        Ok(Some(price))
+    }
+
+    /// Clamp an opportunity to the position cap.
+    ///
+    /// When the size is reduced, both routes are re-quoted against current
+    /// pool state and profits are recomputed at the capped size. Sizes
+    /// above operator capital with flash loans enabled are marked
+    /// `FlashLoan` and charged the provider fee. Returns None if a route can
+    /// no longer be quoted or the USD reference price is stale.
+    pub fn apply_position_cap(
+        &self,
+        mut opp: ArbitrageOpportunity,
+        state: &PriceState,
+    ) -> Option<ArbitrageOpportunity> {
+        let price = match self.reference_price(&opp, state) {
+            Ok(price) => price,
+            Err(e) => {
+                debug!("Dropping opportunity {}: {}", opp.id, e);
+                return None;
+            }
+        };
+
+        let size = self.optimize_size(&opp, state);
+        let capped = size < opp.input_amount;
+
+        if capped {
+            opp.buy_route = requote_route(&opp.buy_route, state, size)?;
+            opp.sell_route = requote_route(&opp.sell_route, state, opp.buy_route.total_amount_out)?;
+            opp.input_amount = size;
+            opp.output_amount = opp.sell_route.total_amount_out;
+            opp.gross_profit = opp.output_amount.saturating_sub(opp.input_amount);
+        }
+
+        let scale = 10f64.powi(get_decimals(opp.chain, opp.token_a) as i32);
+        let to_usd = |amount: U256, price: f64| amount.to_string().parse::<f64>().unwrap_or(0.0) / scale * price;
+
+        // Sizes beyond operator capital are borrowed
+        let capital_usd = self.flash_loan.available_capital_usd.filter(|_| self.flash_loan.enabled);
+        if let (Some(capital), Some(price)) = (capital_usd, price) {
+            if to_usd(opp.input_amount, price) > capital {
+                opp.arb_type = ArbitrageType::FlashLoan;
+                opp.flash_loan_fee = opp.input_amount
+                    * U256::from(self.flash_loan.provider.fee_bps())
+                    / U256::from(10_000);
+            }
+        }
+
+        if capped || !opp.flash_loan_fee.is_zero() {
+            opp.net_profit = opp.gross_profit
+                .saturating_sub(opp.gas_cost_wei)
+                .saturating_sub(opp.flash_loan_fee);
+
+            let input_f: f64 = opp.input_amount.to_string().parse().unwrap_or(1.0);
+            let profit_f: f64 = opp.net_profit.to_string().parse().unwrap_or(0.0);
+            opp.profit_bps = ((profit_f / input_f) * 10_000.0) as i32;
+        }
+
+        if let Some(price) = price {
+            opp.input_usd = to_usd(opp.input_amount, price);
+            opp.profit_usd = to_usd(opp.net_profit, price);
+        }
+
+        Some(opp)
+    }
+}
+
+/// An opportunity's gas cost in raw units of its input token: as is when
+/// the input is the wrapped native token, otherwise through both tokens'
+/// USD prices
+fn gas_cost_in_input(opp: &ArbitrageOpportunity, state: &PriceState) -> Option<U256> {
+    if wrapped_native(opp.chain).map(|token| token.address) == Some(opp.token_a) {
+        return Some(opp.gas_cost_wei);
+    }
+    let gas_usd = state.gas_cost_usd(opp.chain, opp.gas_cost_wei)?;
+    let price = state.get_usd_price(opp.chain, opp.token_a).filter(|price| *price > 0.0)?;
+    let scale = 10f64.powi(get_decimals(opp.chain, opp.token_a) as i32);
+    Some(U256::from((gas_usd / price * scale) as u128))
+}
+
+/// Re-run a route's steps at a new input amount. Step minimums keep their
+/// ratio to the quoted output.
+fn requote_route(route: &SwapRoute, state: &PriceState, amount_in: U256) -> Option<SwapRoute> {
+    let mut requoted = route.clone();
+    let mut amount = amount_in;
+
+    for step in &mut requoted.steps {
+        let entry = state.get_pool(route.chain, step.pool)?;
+        let amount_out = entry.pool.get_amount_out(amount, step.token_in);
+        if !step.amount_out.is_zero() {
+            step.min_amount_out = amount_out * step.min_amount_out / step.amount_out;
+        }
+        step.amount_in = amount;
+        step.amount_out = amount_out;
+        amount = amount_out;
     }
+
+    requoted.total_amount_in = amount_in;
+    requoted.total_amount_out = amount;
+    Some(requoted)
 }
 
 impl Default for RouteOptimizer {
@@ -105,9 +366,334 @@ impl Default for RouteOptimizer {
 mod tests {
     use super::*;
 
+    use alloy_primitives::Address;
+    use defi_core::{get_token, ChainId, DexProtocol, OpportunityBuilder, Pool, SwapStep, UniswapV2Pool};
+
+    const WETH_UNIT: u128 = 1_000_000_000_000_000_000;
+    const USDC_UNIT: u128 = 1_000_000;
+
+    fn seed_pool(state: &PriceState, address: Address, usdc_per_weth: u128) {
+        let weth = get_token(ChainId::Ethereum, "WETH").unwrap().address;
+        let usdc = get_token(ChainId::Ethereum, "USDC").unwrap().address;
+
+        state.update_pool(Pool::UniswapV2(UniswapV2Pool {
+            address,
+            token0: weth,
+            token1: usdc,
+            reserve0: U256::from(10_000 * WETH_UNIT),
+            reserve1: U256::from(10_000 * usdc_per_weth * USDC_UNIT),
+            decimals0: 18,
+            decimals1: 6,
+            fee_bps: 30,
+            chain: ChainId::Ethereum,
+            dex: DexProtocol::UniswapV2,
+            block_number: 1,
+        }));
+    }
+
+    fn route(state: &PriceState, pool: Address, token_in: Address, token_out: Address, amount_in: U256) -> SwapRoute {
+        let amount_out = state.get_pool(ChainId::Ethereum, pool).unwrap().pool.get_amount_out(amount_in, token_in);
+        SwapRoute {
+            steps: vec![SwapStep {
+                pool,
+                dex: DexProtocol::UniswapV2,
+                token_in,
+                token_out,
+                amount_in,
+                amount_out,
+                fee_bps: 30,
+                min_amount_out: U256::ZERO,
+            }],
+            chain: ChainId::Ethereum,
+            total_amount_in: amount_in,
+            total_amount_out: amount_out,
+            gas_estimate: 150_000,
+            price_impact_bps: 0,
+        }
+    }
+
+    /// Sell WETH where it's expensive, buy it back where it's cheap
+    fn weth_opportunity(state: &PriceState, input: U256) -> ArbitrageOpportunity {
+        let weth = get_token(ChainId::Ethereum, "WETH").unwrap().address;
+        let usdc = get_token(ChainId::Ethereum, "USDC").unwrap().address;
+        let (cheap, rich) = (Address::repeat_byte(0xA1), Address::repeat_byte(0xB2));
+
+        let sell = route(state, rich, weth, usdc, input);
+        let buy_back = route(state, cheap, usdc, weth, sell.total_amount_out);
+
+        OpportunityBuilder::new()
+            .chain(ChainId::Ethereum)
+            .tokens(weth, usdc)
+            .routes(sell, buy_back)
+            .input(input)
+            .build()
+            .unwrap()
+    }
+
+    fn contested(competing_txs: u32) -> ArbitrageOpportunity {
+        let route = SwapRoute {
+            steps: vec![],
+            chain: ChainId::Ethereum,
+            total_amount_in: U256::from(1_000u64),
+            total_amount_out: U256::from(1_000u64),
+            gas_estimate: 0,
+            price_impact_bps: 0,
+        };
+        let mut opp = OpportunityBuilder::new()
+            .routes(route.clone(), route)
+            .competing_txs(competing_txs)
+            .build()
+            .unwrap();
+        opp.profit_bps = 50;
+        opp
+    }
+
+    #[test]
+    fn test_default_confidence_model_matches_legacy_weights() {
+        let optimizer = RouteOptimizer::new();
+        assert!((optimizer.calculate_confidence(&contested(0)) - 0.9).abs() < 1e-12);
+        assert!((optimizer.calculate_confidence(&contested(1)) - 0.9 / 1.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_competition_weight_shifts_confidence() {
+        let opp = contested(2);
+        let default = RouteOptimizer::new().calculate_confidence(&opp);
+
+        let harsh = RouteOptimizer::new().with_confidence_model(ConfidenceModel {
+            competition_weight: 0.5,
+            ..Default::default()
+        });
+        let lenient = RouteOptimizer::new().with_confidence_model(ConfidenceModel {
+            competition_weight: 0.0,
+            ..Default::default()
+        });
+
+        // 0.9 / (1 + 2 * 0.5)
+        assert!((harsh.calculate_confidence(&opp) - 0.45).abs() < 1e-12);
+        assert!(harsh.calculate_confidence(&opp) < default);
+        // No competition penalty at all
+        assert!((lenient.calculate_confidence(&opp) - 0.9).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_gas_multiple_rejects_thin_margins() {
+        // 1 gwei over bare two-leg routes
+        let gas_price = GasPrice {
+            base_fee: U256::from(1_000_000_000u64),
+            priority_fee: U256::ZERO,
+            max_fee: U256::from(1_000_000_000u64),
+        };
+        let gas_cost = gas_price.estimate_cost(opportunity_gas_units(&contested(0)));
+
+        // Net profit of 1.1x gas once gas is paid
+        let input = U256::from(WETH_UNIT);
+        let route = |amount_out: U256| SwapRoute {
+            steps: vec![],
+            chain: ChainId::Ethereum,
+            total_amount_in: input,
+            total_amount_out: amount_out,
+            gas_estimate: 0,
+            price_impact_bps: 0,
+        };
+        let opportunity = |token: Address, gross: U256| {
+            OpportunityBuilder::new()
+                .chain(ChainId::Ethereum)
+                .tokens(token, Address::repeat_byte(0xEE))
+                .routes(route(input), route(input + gross))
+                .build()
+                .unwrap()
+        };
+
+        let optimizer = |multiple: f64| {
+            let mut optimizer = RouteOptimizer::new()
+                .with_min_profit(U256::ZERO)
+                .with_min_profit_gas_multiple(multiple);
+            optimizer.update_gas_price(gas_price);
+            optimizer
+        };
+
+        // WETH profits are counted in the gas token itself
+        let weth = get_token(ChainId::Ethereum, "WETH").unwrap().address;
+        let gross = gas_cost * U256::from(21) / U256::from(10);
+        let opp = opportunity(weth, gross);
+        let unpriced = PriceState::new();
+        let accepted = optimizer(1.0).optimize(opp.clone(), &unpriced).unwrap();
+        assert_eq!(accepted.gas_cost_wei, gas_cost);
+        assert_eq!(accepted.net_profit, gross - gas_cost);
+        assert!(optimizer(2.0).optimize(opp.clone(), &unpriced).is_none());
+        // Disabled by default
+        assert!(optimizer(0.0).optimize(opp, &unpriced).is_some());
+
+        // DAI profits are held to gas priced in DAI: at $2000 ETH the net
+        // profit is 1.5x the gas cost, clearing 1x but not 2x
+        let dai = get_token(ChainId::Ethereum, "DAI").unwrap().address;
+        let gas_in_dai = gas_cost * U256::from(2_000);
+        let opp = opportunity(dai, gas_in_dai * U256::from(3) / U256::from(2) + gas_cost);
+        let state = PriceState::new();
+        seed_pool(&state, Address::repeat_byte(0xA1), 2000);
+        assert!(optimizer(1.0).optimize(opp.clone(), &state).is_some());
+        assert!(optimizer(2.0).optimize(opp.clone(), &state).is_none());
+        // Without a native price the margin can't be checked
+        assert!(optimizer(1.0).optimize(opp.clone(), &unpriced).is_none());
+        assert!(optimizer(0.0).optimize(opp, &unpriced).is_some());
+    }
+
     #[test]
     fn test_optimizer_creation() {
         let optimizer = RouteOptimizer::new();
         assert!(optimizer.gas_price.is_none());
     }
+
+    #[test]
+    fn test_optimize_drops_routes_that_dont_chain() {
+        let state = PriceState::new();
+        seed_pool(&state, Address::repeat_byte(0xA1), 2000);
+        seed_pool(&state, Address::repeat_byte(0xB2), 2100);
+
+        let opp = weth_opportunity(&state, U256::from(WETH_UNIT));
+        assert!(RouteOptimizer::new().optimize(opp.clone(), &state).is_some());
+
+        // Sell leg claims more output than its step produced
+        let mut inflated = opp;
+        inflated.sell_route.total_amount_out *= U256::from(2);
+        assert!(RouteOptimizer::new().optimize(inflated, &state).is_none());
+    }
+
+    #[test]
+    fn test_position_cap_clamps_size_and_recomputes_profit() {
+        let state = PriceState::new();
+        seed_pool(&state, Address::repeat_byte(0xA1), 2000);
+        seed_pool(&state, Address::repeat_byte(0xB2), 2100);
+
+        let weth = get_token(ChainId::Ethereum, "WETH").unwrap().address;
+        let price = state.get_usd_price(ChainId::Ethereum, weth).unwrap();
+
+        // Unconstrained optimum worth about $50k
+        let uncapped_input = U256::from((50_000.0 / price * WETH_UNIT as f64) as u128);
+        let opp = weth_opportunity(&state, uncapped_input);
+        assert!(opp.net_profit > U256::ZERO);
+
+        let optimizer = RouteOptimizer::new().with_risk_config(&RiskConfig {
+            max_position_usd: 10_000.0,
+            ..Default::default()
+        });
+
+        let capped_size = optimizer.optimize_size(&opp, &state);
+        assert_eq!(capped_size, U256::from((10_000.0 / price * WETH_UNIT as f64) as u128));
+
+        let capped = optimizer.apply_position_cap(opp.clone(), &state).unwrap();
+        assert_eq!(capped.input_amount, capped_size);
+        assert!((capped.input_usd - 10_000.0).abs() < 0.01);
+
+        // Profit matches a fresh quote at the capped size
+        let expected = weth_opportunity(&state, capped_size);
+        assert_eq!(capped.net_profit, expected.net_profit);
+        assert!(capped.net_profit < opp.net_profit);
+        assert!(capped.profit_usd > 0.0);
+    }
+
+    #[test]
+    fn test_flash_loan_makes_capital_constrained_arb_profitable() {
+        use defi_core::{FlashLoanConfig, FlashLoanProvider};
+
+        let state = PriceState::new();
+        seed_pool(&state, Address::repeat_byte(0xA1), 2000);
+        seed_pool(&state, Address::repeat_byte(0xB2), 2100);
+
+        let weth = get_token(ChainId::Ethereum, "WETH").unwrap().address;
+        let price = state.get_usd_price(ChainId::Ethereum, weth).unwrap();
+        let opp = weth_opportunity(&state, U256::from((20_000.0 / price * WETH_UNIT as f64) as u128));
+
+        // Only $1 of our own capital: the trade shrinks to dust and isn't worth it
+        let own_capital = RouteOptimizer::new()
+            .with_max_position_usd(100_000.0)
+            .with_flash_loan(FlashLoanConfig {
+                enabled: false,
+                available_capital_usd: Some(1.0),
+                ..Default::default()
+            });
+        let capped = own_capital.apply_position_cap(opp.clone(), &state).unwrap();
+        assert!(capped.input_usd <= 1.0 + 1e-6);
+        assert!(own_capital.optimize(capped, &state).is_none());
+
+        // Borrowing the difference keeps the full size, minus Aave's 0.05%
+        let borrowed = RouteOptimizer::new()
+            .with_max_position_usd(100_000.0)
+            .with_flash_loan(FlashLoanConfig {
+                enabled: true,
+                provider: FlashLoanProvider::AaveV3,
+                available_capital_usd: Some(1.0),
+            });
+        let flash = borrowed.apply_position_cap(opp.clone(), &state).unwrap();
+        assert_eq!(flash.arb_type, ArbitrageType::FlashLoan);
+        assert_eq!(flash.input_amount, opp.input_amount);
+        assert_eq!(flash.flash_loan_fee, opp.input_amount * U256::from(5) / U256::from(10_000));
+        assert_eq!(flash.net_profit, opp.gross_profit - flash.flash_loan_fee);
+
+        let optimized = borrowed.optimize(flash, &state).unwrap();
+        assert_eq!(optimized.arb_type, ArbitrageType::FlashLoan);
+        assert_eq!(optimized.net_profit, opp.gross_profit - optimized.flash_loan_fee);
+    }
+
+    #[test]
+    fn test_balancer_flash_loan_is_free() {
+        use defi_core::{FlashLoanConfig, FlashLoanProvider};
+
+        let state = PriceState::new();
+        seed_pool(&state, Address::repeat_byte(0xA1), 2000);
+        seed_pool(&state, Address::repeat_byte(0xB2), 2100);
+
+        let opp = weth_opportunity(&state, U256::from(WETH_UNIT));
+        let optimizer = RouteOptimizer::new().with_flash_loan(FlashLoanConfig {
+            enabled: true,
+            provider: FlashLoanProvider::Balancer,
+            available_capital_usd: Some(100.0),
+        });
+
+        let flash = optimizer.apply_position_cap(opp.clone(), &state).unwrap();
+        assert_eq!(flash.arb_type, ArbitrageType::FlashLoan);
+        assert!(flash.flash_loan_fee.is_zero());
+        assert_eq!(flash.net_profit, opp.net_profit);
+    }
+
+    #[test]
+    fn test_position_under_cap_is_unchanged() {
+        let state = PriceState::new();
+        seed_pool(&state, Address::repeat_byte(0xA1), 2000);
+        seed_pool(&state, Address::repeat_byte(0xB2), 2100);
+
+        let opp = weth_opportunity(&state, U256::from(WETH_UNIT));
+        let optimizer = RouteOptimizer::new().with_max_position_usd(10_000.0);
+
+        let capped = optimizer.apply_position_cap(opp.clone(), &state).unwrap();
+        assert_eq!(capped.input_amount, opp.input_amount);
+        assert_eq!(capped.net_profit, opp.net_profit);
+    }
+
+    #[test]
+    fn test_stale_reference_price_is_rejected() {
+        let fresh = PriceState::new();
+        seed_pool(&fresh, Address::repeat_byte(0xA1), 2000);
+        seed_pool(&fresh, Address::repeat_byte(0xB2), 2100);
+
+        // Same pools, last updated a minute ago
+        let mut snapshot = fresh.snapshot();
+        for entry in &mut snapshot.pools {
+            entry.age = Duration::from_secs(60);
+        }
+        let stale = PriceState::from_snapshot(snapshot);
+
+        let optimizer = RouteOptimizer::new()
+            .with_max_position_usd(10_000.0)
+            .with_max_ref_price_age(Duration::from_secs(30));
+        let opp = weth_opportunity(&fresh, U256::from(WETH_UNIT));
+
+        assert!(optimizer.reference_price(&opp, &fresh).unwrap().is_some());
+        assert!(optimizer.apply_position_cap(opp.clone(), &fresh).is_some());
+
+        let err = optimizer.reference_price(&opp, &stale).unwrap_err();
+        assert!(matches!(err, CoreError::StalePrice { max_ms: 30_000, .. }), "{:?}", err);
+        assert!(optimizer.apply_position_cap(opp, &stale).is_none());
+    }
 }
@@ -0,0 +1,256 @@
+//! Bounded priority queue between the scanner and the executor
+//!
+//! Opportunities are ranked by expected value (`success_probability() *
+//! profit_usd`). When full, the lowest-ranked entry is evicted, and entries
+//! past `expires_at_ms` are never handed out.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use parking_lot::Mutex;
+
+use defi_core::ArbitrageOpportunity;
+
+/// Ordering key: expected value, then insertion order (older first on ties)
+#[derive(Debug, Clone, Copy)]
+struct QueueKey {
+    score: f64,
+    seq: u64,
+}
+
+impl PartialEq for QueueKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for QueueKey {}
+
+impl PartialOrd for QueueKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueueKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+#[derive(Debug, Default)]
+struct QueueInner {
+    entries: BTreeMap<QueueKey, ArbitrageOpportunity>,
+    next_seq: u64,
+}
+
+impl QueueInner {
+    fn prune_expired(&mut self, now_ms: u64) {
+        self.entries.retain(|_, opp| opp.expires_at_ms > now_ms);
+    }
+}
+
+/// Opportunities waiting for execution, highest expected value first
+#[derive(Debug)]
+pub struct OpportunityQueue {
+    capacity: usize,
+    inner: Mutex<QueueInner>,
+}
+
+impl OpportunityQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            inner: Mutex::new(QueueInner::default()),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.lock().entries.is_empty()
+    }
+
+    /// Queue an opportunity, replacing any queued under the same id.
+    /// Returns false if it was expired or ranked below everything in a
+    /// full queue.
+    pub fn push(&self, opp: ArbitrageOpportunity) -> bool {
+        self.push_at(opp, now_ms())
+    }
+
+    pub fn push_at(&self, opp: ArbitrageOpportunity, now_ms: u64) -> bool {
+        if opp.expires_at_ms <= now_ms {
+            return false;
+        }
+
+        let mut inner = self.inner.lock();
+        // A re-found opportunity supersedes the one queued by an earlier scan
+        inner.entries.retain(|_, queued| queued.id != opp.id);
+        let key = QueueKey {
+            score: opp.success_probability() * opp.profit_usd,
+            seq: inner.next_seq,
+        };
+        inner.next_seq += 1;
+
+        if inner.entries.len() >= self.capacity {
+            inner.prune_expired(now_ms);
+        }
+        if inner.entries.len() >= self.capacity {
+            match inner.entries.keys().next() {
+                Some(lowest) if *lowest < key => {
+                    let lowest = *lowest;
+                    inner.entries.remove(&lowest);
+                }
+                _ => return false,
+            }
+        }
+
+        inner.entries.insert(key, opp);
+        true
+    }
+
+    /// Take the highest-value opportunity that hasn't expired
+    pub fn pop(&self) -> Option<ArbitrageOpportunity> {
+        self.pop_at(now_ms())
+    }
+
+    pub fn pop_at(&self, now_ms: u64) -> Option<ArbitrageOpportunity> {
+        let mut inner = self.inner.lock();
+        inner.prune_expired(now_ms);
+        inner.entries.pop_last().map(|(_, opp)| opp)
+    }
+
+    /// Queued opportunity with the given id, left in the queue
+    pub fn get(&self, id: &str) -> Option<ArbitrageOpportunity> {
+        self.inner.lock().entries.values().find(|opp| opp.id == id).cloned()
+    }
+
+    /// Drop every queued opportunity
+    pub fn clear(&self) {
+        self.inner.lock().entries.clear();
+    }
+}
+
+impl Default for OpportunityQueue {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+    use defi_core::{ChainId, OpportunityBuilder, SwapRoute};
+
+    fn opportunity(profit_usd: f64, expires_at_ms: u64) -> ArbitrageOpportunity {
+        let route = SwapRoute {
+            steps: vec![],
+            chain: ChainId::Arbitrum,
+            total_amount_in: U256::from(1_000u64),
+            total_amount_out: U256::from(1_010u64),
+            gas_estimate: 0,
+            price_impact_bps: 0,
+        };
+        let mut opp = OpportunityBuilder::new()
+            .chain(ChainId::Arbitrum)
+            .routes(route.clone(), route)
+            .build()
+            .unwrap();
+        // Same routes, so told apart by id
+        opp.id = format!("{}-{}", profit_usd, expires_at_ms);
+        opp.profit_usd = profit_usd;
+        opp.expires_at_ms = expires_at_ms;
+        opp
+    }
+
+    #[test]
+    fn test_pops_highest_value_first() {
+        let queue = OpportunityQueue::new(8);
+        for profit in [20.0, 50.0, 10.0, 35.0] {
+            assert!(queue.push_at(opportunity(profit, 2_000), 1_000));
+        }
+
+        let popped: Vec<f64> = std::iter::from_fn(|| queue.pop_at(1_000))
+            .map(|o| o.profit_usd)
+            .collect();
+        assert_eq!(popped, vec![50.0, 35.0, 20.0, 10.0]);
+    }
+
+    #[test]
+    fn test_get_by_id_leaves_entry_queued() {
+        let queue = OpportunityQueue::new(8);
+        let opp = opportunity(20.0, u64::MAX);
+        let id = opp.id.clone();
+        assert!(queue.push(opp));
+
+        assert_eq!(queue.get(&id).unwrap().profit_usd, 20.0);
+        assert!(queue.get("missing").is_none());
+        assert_eq!(queue.len(), 1);
+    }
+
+    #[test]
+    fn test_same_id_replaces_queued_entry() {
+        let queue = OpportunityQueue::new(8);
+        let opp = opportunity(20.0, 2_000);
+        assert!(queue.push_at(opp.clone(), 1_000));
+
+        let mut refound = opp;
+        refound.profit_usd = 30.0;
+        assert!(queue.push_at(refound, 1_000));
+
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.pop_at(1_000).unwrap().profit_usd, 30.0);
+        assert!(queue.pop_at(1_000).is_none());
+    }
+
+    #[test]
+    fn test_full_queue_drops_lowest() {
+        let queue = OpportunityQueue::new(2);
+        assert!(queue.push_at(opportunity(20.0, 2_000), 1_000));
+        assert!(queue.push_at(opportunity(40.0, 2_000), 1_000));
+
+        // Worse than everything queued
+        assert!(!queue.push_at(opportunity(5.0, 2_000), 1_000));
+        // Better: evicts the 20
+        assert!(queue.push_at(opportunity(30.0, 2_000), 1_000));
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pop_at(1_000).unwrap().profit_usd, 40.0);
+        assert_eq!(queue.pop_at(1_000).unwrap().profit_usd, 30.0);
+        assert!(queue.pop_at(1_000).is_none());
+    }
+
+    #[test]
+    fn test_expired_never_returned() {
+        let queue = OpportunityQueue::new(2);
+        assert!(!queue.push_at(opportunity(100.0, 1_000), 1_000));
+
+        assert!(queue.push_at(opportunity(90.0, 1_500), 1_000));
+        assert!(queue.push_at(opportunity(10.0, 3_000), 1_000));
+
+        // The 90 lapses before anyone pops it
+        assert_eq!(queue.pop_at(2_000).unwrap().profit_usd, 10.0);
+        assert!(queue.pop_at(2_000).is_none());
+
+        // Expired entries make room before live ones are evicted
+        let queue = OpportunityQueue::new(1);
+        assert!(queue.push_at(opportunity(90.0, 1_500), 1_000));
+        assert!(queue.push_at(opportunity(1.0, 3_000), 2_000));
+        assert_eq!(queue.pop_at(2_000).unwrap().profit_usd, 1.0);
+    }
+}
@@ -0,0 +1,404 @@
+//! Multi-DEX quote aggregation
+//!
+//! Quotes a swap across every tracked pool path for the pair, up to
+//! `max_hops`, and ranks them by output, or by input for exact-output
+//! requests. Requests pinning `must_use_pools` only see paths through all
+//! of them.
+
+use alloy_primitives::{Address, U256};
+use std::time::Duration;
+
+use defi_core::{apply_slippage, hop_overhead, AggregatedQuotes, Quote, QuoteKind, QuoteRequest, SwapRoute, SwapStep};
+use defi_price_feed::{PoolEntry, PriceState};
+
+/// Quote engine configuration
+#[derive(Debug, Clone)]
+pub struct QuoteEngineConfig {
+    /// Pools older than this are not quoted
+    pub max_pool_age: Duration,
+    /// Upper bound on paths explored per request
+    pub max_routes: usize,
+}
+
+impl Default for QuoteEngineConfig {
+    fn default() -> Self {
+        Self {
+            max_pool_age: Duration::from_secs(30),
+            max_routes: 64,
+        }
+    }
+}
+
+/// Produces aggregated quotes from tracked pool state
+pub struct QuoteEngine {
+    config: QuoteEngineConfig,
+}
+
+impl QuoteEngine {
+    pub fn new() -> Self {
+        Self::with_config(QuoteEngineConfig::default())
+    }
+
+    pub fn with_config(config: QuoteEngineConfig) -> Self {
+        Self { config }
+    }
+
+    /// Quote every pool path from `token_in` to `token_out`.
+    ///
+    /// Routes whose price impact exceeds `slippage_bps` are dropped, and every
+    /// quote is valid for one block on the request's chain. Exact-output
+    /// routes are quoted forward from the smallest input that reaches the
+    /// requested amount, so their output may exceed it by rounding.
+    ///
+    /// When no route can use every pool in `must_use_pools` the result is
+    /// empty and `reason` says why.
+    pub async fn quote(&self, req: QuoteRequest, state: &PriceState) -> AggregatedQuotes {
+        let now = now_ms();
+        let pools = state.get_chain_pools(req.chain, self.config.max_pool_age);
+
+        let mut required = Vec::with_capacity(req.must_use_pools.len());
+        for address in &req.must_use_pools {
+            match pools.iter().position(|entry| entry.pool.address() == *address) {
+                Some(i) if !required.contains(&i) => required.push(i),
+                Some(_) => {}
+                None => {
+                    let reason = format!("Required pool {} has no fresh state on {}", address, req.chain);
+                    return AggregatedQuotes::empty(req, now, reason);
+                }
+            }
+        }
+
+        let mut paths = Vec::new();
+        let mut path = Vec::new();
+        let mut visited = vec![req.token_in];
+        self.find_paths(&req, &pools, &required, req.token_in, &mut path, &mut visited, &mut paths);
+
+        let valid_until_ms = now + req.chain.block_time_ms().min(req.deadline_ms);
+
+        let quotes: Vec<Quote> = paths
+            .iter()
+            .filter_map(|path| {
+                let amount_in = match req.kind {
+                    QuoteKind::ExactIn(amount_in) => amount_in,
+                    QuoteKind::ExactOut(amount_out) => path_amount_in(&req, &pools, path, amount_out)?,
+                };
+                quote_path(&req, &pools, path, amount_in)
+            })
+            .filter(|route| route.price_impact_bps <= req.slippage_bps)
+            .map(|route| Quote {
+                source: route
+                    .steps
+                    .iter()
+                    .map(|s| s.dex.name())
+                    .collect::<Vec<_>>()
+                    .join("+"),
+                route,
+                timestamp_ms: now,
+                valid_until_ms,
+            })
+            .collect();
+
+        let ranked = quotes.iter().enumerate();
+        let best_quote_index = match req.kind {
+            QuoteKind::ExactIn(_) => ranked.max_by_key(|(_, q)| q.route.total_amount_out),
+            QuoteKind::ExactOut(_) => ranked.min_by_key(|(_, q)| q.route.total_amount_in),
+        }
+        .map(|(i, _)| i);
+
+        let reason = (quotes.is_empty() && !required.is_empty())
+            .then(|| format!("No route within {} hops and slippage uses every required pool", req.max_hops));
+
+        AggregatedQuotes {
+            request: req,
+            quotes,
+            best_quote_index,
+            timestamp_ms: now,
+            reason,
+        }
+    }
+
+    /// Depth-first search for pool paths through every `required` pool,
+    /// never revisiting a token
+    #[allow(clippy::too_many_arguments)]
+    fn find_paths(
+        &self,
+        req: &QuoteRequest,
+        pools: &[PoolEntry],
+        required: &[usize],
+        token: Address,
+        path: &mut Vec<usize>,
+        visited: &mut Vec<Address>,
+        paths: &mut Vec<Vec<usize>>,
+    ) {
+        let hops_left = (req.max_hops as usize).saturating_sub(path.len());
+        let missing = required.iter().filter(|i| !path.contains(i)).count();
+        if hops_left == 0 || missing > hops_left {
+            return;
+        }
+
+        for (i, entry) in pools.iter().enumerate() {
+            if paths.len() >= self.config.max_routes {
+                return;
+            }
+
+            let Some(next) = entry.pool.other_token(token) else {
+                continue;
+            };
+
+            if next == req.token_out {
+                path.push(i);
+                if required.iter().all(|r| path.contains(r)) {
+                    paths.push(path.clone());
+                }
+                path.pop();
+            } else if !visited.contains(&next) {
+                path.push(i);
+                visited.push(next);
+                self.find_paths(req, pools, required, next, path, visited, paths);
+                visited.pop();
+                path.pop();
+            }
+        }
+    }
+}
+
+impl Default for QuoteEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Input of `req.token_in` needed for `amount_out` of `req.token_out`,
+/// walking a path of pools backwards
+fn path_amount_in(req: &QuoteRequest, pools: &[PoolEntry], path: &[usize], amount_out: U256) -> Option<U256> {
+    let mut token_out = req.token_out;
+    let mut amount = amount_out;
+
+    for &i in path.iter().rev() {
+        let pool = &pools[i].pool;
+        let token_in = pool.other_token(token_out)?;
+        amount = pool.get_amount_in(amount, token_out);
+        if amount == U256::MAX {
+            return None;
+        }
+        token_out = token_in;
+    }
+
+    Some(amount)
+}
+
+/// Run `amount_in` through a path of pools
+fn quote_path(req: &QuoteRequest, pools: &[PoolEntry], path: &[usize], amount_in: U256) -> Option<SwapRoute> {
+    let mut steps = Vec::with_capacity(path.len());
+    let mut token_in = req.token_in;
+    let mut amount = amount_in;
+    let mut impact = 0.0;
+    let mut gas = 0;
+
+    for &i in path {
+        let pool = &pools[i].pool;
+        let token_out = pool.other_token(token_in)?;
+        let amount_out = pool.get_amount_out(amount, token_in);
+        if amount_out.is_zero() {
+            return None;
+        }
+
+        impact += pool.price_impact(amount, token_in);
+        gas += pool.swap_gas(amount, token_in);
+        steps.push(SwapStep {
+            pool: pool.address(),
+            dex: pool.dex(),
+            token_in,
+            token_out,
+            amount_in: amount,
+            amount_out,
+            fee_bps: pool.fee_bps(),
+            min_amount_out: apply_slippage(amount_out, req.slippage_bps),
+        });
+
+        token_in = token_out;
+        amount = amount_out;
+    }
+
+    Some(SwapRoute {
+        gas_estimate: gas + hop_overhead(steps.len()),
+        steps,
+        chain: req.chain,
+        total_amount_in: amount_in,
+        total_amount_out: amount,
+        price_impact_bps: (impact * 10_000.0).min(u16::MAX as f64) as u16,
+    })
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use defi_core::{ChainId, DexProtocol, Pool, UniswapV2Pool};
+
+    const UNIT: u128 = 1_000_000_000_000_000_000;
+
+    fn seed(state: &PriceState, address: u8, token0: u8, token1: u8, r0: u128, r1: u128, dex: DexProtocol) {
+        state.update_pool(Pool::UniswapV2(UniswapV2Pool {
+            address: Address::repeat_byte(address),
+            token0: Address::repeat_byte(token0),
+            token1: Address::repeat_byte(token1),
+            reserve0: U256::from(r0 * UNIT),
+            reserve1: U256::from(r1 * UNIT),
+            decimals0: 18,
+            decimals1: 18,
+            fee_bps: 30,
+            chain: ChainId::Arbitrum,
+            dex,
+            block_number: 1,
+        }));
+    }
+
+    fn request(max_hops: u8) -> QuoteRequest {
+        QuoteRequest::new(ChainId::Arbitrum, Address::repeat_byte(1), Address::repeat_byte(2), U256::from(UNIT))
+            .with_max_hops(max_hops)
+            .with_slippage(500)
+    }
+
+    #[tokio::test]
+    async fn test_best_quote_selection() {
+        let state = PriceState::new();
+        seed(&state, 0xA0, 1, 2, 10_000, 20_000, DexProtocol::UniswapV2);
+        seed(&state, 0xB0, 1, 2, 10_000, 20_400, DexProtocol::Camelot);
+
+        let quotes = QuoteEngine::new().quote(request(1), &state).await;
+
+        assert_eq!(quotes.quote_count(), 2);
+        let best = quotes.best_quote().unwrap();
+        assert_eq!(best.route.steps[0].pool, Address::repeat_byte(0xB0));
+        assert_eq!(best.source, "camelot");
+        assert_eq!(best.valid_until_ms, best.timestamp_ms + ChainId::Arbitrum.block_time_ms());
+
+        // ~2% apart at spot, minus a little slippage
+        let spread = quotes.price_spread_bps().unwrap();
+        assert!((195..=205).contains(&spread), "spread {}", spread);
+    }
+
+    #[tokio::test]
+    async fn test_max_hops_limits_paths() {
+        let state = PriceState::new();
+        seed(&state, 0xA0, 1, 2, 10_000, 20_000, DexProtocol::UniswapV2);
+        // 1 -> 3 -> 2 at a better rate
+        seed(&state, 0xC0, 1, 3, 10_000, 10_000, DexProtocol::UniswapV2);
+        seed(&state, 0xD0, 3, 2, 10_000, 21_000, DexProtocol::SushiSwap);
+
+        let direct = QuoteEngine::new().quote(request(1), &state).await;
+        assert_eq!(direct.quote_count(), 1);
+
+        let multi = QuoteEngine::new().quote(request(2), &state).await;
+        assert_eq!(multi.quote_count(), 2);
+        let best = multi.best_quote().unwrap();
+        assert_eq!(best.route.hop_count(), 2);
+        assert_eq!(best.source, "uniswap-v2+sushiswap");
+        assert_eq!(best.route.token_path(), vec![
+            Address::repeat_byte(1),
+            Address::repeat_byte(3),
+            Address::repeat_byte(2),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_pinned_pools_exclude_other_routes() {
+        let state = PriceState::new();
+        seed(&state, 0xA0, 1, 2, 10_000, 20_000, DexProtocol::UniswapV2);
+        seed(&state, 0xB0, 1, 2, 10_000, 20_400, DexProtocol::Camelot);
+        seed(&state, 0xC0, 1, 3, 10_000, 10_000, DexProtocol::UniswapV2);
+        seed(&state, 0xD0, 3, 2, 10_000, 21_000, DexProtocol::SushiSwap);
+        let engine = QuoteEngine::new();
+        let pinned = |pools: &[u8]| request(2).with_must_use_pools(pools.iter().map(|p| Address::repeat_byte(*p)).collect());
+
+        assert_eq!(engine.quote(request(2), &state).await.quote_count(), 3);
+
+        // The worse direct pool is the only quote, despite better routes
+        let quotes = engine.quote(pinned(&[0xA0]), &state).await;
+        assert_eq!(quotes.quote_count(), 1);
+        assert_eq!(quotes.best_quote().unwrap().route.steps[0].pool, Address::repeat_byte(0xA0));
+        assert!(quotes.reason.is_none());
+
+        // Pinning one leg of the two-hop route keeps only that route
+        let quotes = engine.quote(pinned(&[0xD0]), &state).await;
+        assert_eq!(quotes.quote_count(), 1);
+        let route = &quotes.best_quote().unwrap().route;
+        assert_eq!(route.steps.iter().map(|s| s.pool).collect::<Vec<_>>(), vec![
+            Address::repeat_byte(0xC0),
+            Address::repeat_byte(0xD0),
+        ]);
+
+        // Two direct pools can't both be on one route
+        let quotes = engine.quote(pinned(&[0xA0, 0xB0]), &state).await;
+        assert_eq!(quotes.quote_count(), 0);
+        assert!(quotes.best_quote().is_none());
+        assert!(quotes.reason.unwrap().contains("every required pool"));
+
+        // Nor can a pool that isn't tracked
+        let quotes = engine.quote(pinned(&[0xEE]), &state).await;
+        assert_eq!(quotes.quote_count(), 0);
+        assert!(quotes.reason.unwrap().contains(&Address::repeat_byte(0xEE).to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_exact_out_consistent_with_exact_in() {
+        let state = PriceState::new();
+        seed(&state, 0xA0, 1, 2, 10_000, 20_000, DexProtocol::UniswapV2);
+        // 1 -> 3 -> 2 at a better rate
+        seed(&state, 0xC0, 1, 3, 10_000, 10_000, DexProtocol::UniswapV2);
+        seed(&state, 0xD0, 3, 2, 10_000, 21_000, DexProtocol::SushiSwap);
+
+        let engine = QuoteEngine::new();
+        let exact_out = |amount_out: U256| {
+            QuoteRequest::exact_out(ChainId::Arbitrum, Address::repeat_byte(1), Address::repeat_byte(2), amount_out)
+                .with_max_hops(2)
+                .with_slippage(500)
+        };
+
+        let exact_in = engine.quote(request(2), &state).await;
+        assert_eq!(exact_in.quote_count(), 2);
+
+        // Asking for what one unit buys on a path costs one unit on that path,
+        // give or take rounding
+        for quote in &exact_in.quotes {
+            let quotes = engine.quote(exact_out(quote.route.total_amount_out), &state).await;
+            let inverse = quotes.quotes.iter().find(|q| q.source == quote.source).unwrap();
+
+            assert!(inverse.route.total_amount_out >= quote.route.total_amount_out);
+            let input = inverse.route.total_amount_in;
+            assert!(input >= U256::from(UNIT - 2) && input <= U256::from(UNIT + 2), "{}", input);
+            inverse.route.validate().unwrap();
+        }
+
+        // The cheapest input wins for exact output
+        let best_out = exact_in.best_quote().unwrap().route.total_amount_out;
+        let quotes = engine.quote(exact_out(best_out), &state).await;
+        assert_eq!(quotes.best_quote().unwrap().route.hop_count(), 2);
+
+        // More than either path can deliver
+        let quotes = engine.quote(exact_out(U256::from(20_000 * UNIT)), &state).await;
+        assert_eq!(quotes.quote_count(), 0);
+        assert!(quotes.best_quote().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_slippage_excludes_high_impact_routes() {
+        let state = PriceState::new();
+        // 1 unit into a 10-unit pool moves the price ~10%
+        seed(&state, 0xA0, 1, 2, 10, 20, DexProtocol::UniswapV2);
+        seed(&state, 0xB0, 1, 2, 10_000, 20_000, DexProtocol::Camelot);
+
+        let quotes = QuoteEngine::new().quote(request(1).with_slippage(100), &state).await;
+
+        assert_eq!(quotes.quote_count(), 1);
+        assert_eq!(quotes.best_quote().unwrap().route.steps[0].pool, Address::repeat_byte(0xB0));
+        assert!(quotes.price_spread_bps().is_none());
+    }
+}
@@ -1,19 +1,38 @@
 //! Main arbitrage scanner
 
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use alloy_primitives::{Address, U256};
+use futures::Stream;
+use parking_lot::Mutex;
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
 
 use defi_core::{
-    ArbitrageOpportunity, ChainId, DetectionConfig, OpportunityFilter,
-    Pool, UniswapV2Pool,
+    collapse_conflicts, ArbitrageOpportunity, ChainId, CoreError, DetectionConfig, DexProtocol,
+    FlashLoanConfig, GasPrice, OpportunityFilter, RiskConfig,
 };
-use defi_price_feed::PriceState;
+use defi_price_feed::{MempoolMonitor, PoolEntry, PoolLiquidity, PriceState};
 
-use crate::strategies::{CrossDexStrategy, TriangularStrategy, Strategy};
-use crate::optimizer::RouteOptimizer;
+use crate::strategies::{CrossDexStrategy, MultiHopStrategy, TriangularStrategy, Strategy};
+use crate::optimizer::{ConfidenceModel, RouteOptimizer, DEFAULT_MAX_REF_PRICE_AGE};
+use crate::queue::OpportunityQueue;
+use crate::sink::OpportunitySink;
+use crate::snapshot::{normalize_pair, ChainSnapshot, TokenPair};
+
+/// Opportunities `scan_stream` buffers ahead of a slow consumer
+const STREAM_BUFFER: usize = 64;
+
+/// Default `min_reserve`: Uniswap V2's `MINIMUM_LIQUIDITY`, which stays
+/// locked in a pool after every LP has withdrawn
+const MIN_RESERVE: u64 = 1_000;
+
+/// Cancellation flag for scans that can't be cancelled
+static NEVER_CANCELLED: AtomicBool = AtomicBool::new(false);
 
 /// Scanner configuration
 #[derive(Debug, Clone)]
@@ -24,6 +43,46 @@ pub struct ScannerConfig {
     pub max_gas_gwei: f64,
     pub enabled_chains: Vec<ChainId>,
     pub parallel_chains: bool,
+    /// Fresh pools each enabled chain needs before the scanner is ready
+    pub min_pools_per_chain: usize,
+    /// Largest input, in USD, any opportunity may be sized to
+    pub max_position_usd: f64,
+    /// Pools worth less than this, in USD, are not scanned
+    pub min_liquidity_usd: f64,
+    /// Pools holding fewer raw units than this of either token are not
+    /// scanned, priced or not
+    pub min_reserve: U256,
+    /// Pools holding less than this share, in bps, of the deepest pool for
+    /// the same pair in either token are not scanned. Zero disables.
+    pub min_relative_reserve_bps: u32,
+    /// Opportunities whose buy and sell routes together move prices more
+    /// than this are dropped
+    pub max_price_impact_bps: u16,
+    /// Operator capital and flash loan provider used to size opportunities
+    pub flash_loan: FlashLoanConfig,
+    /// Weights used to score execution confidence
+    pub confidence: ConfidenceModel,
+    /// Opportunities whose USD reference price is older than this are dropped
+    pub max_ref_price_age: Duration,
+    /// Opportunities with less than this left to live when a scan finishes
+    /// are dropped, since a transaction couldn't land before they expire
+    pub min_ttl_ms: u64,
+    /// Keep only the most profitable of opportunities sharing a pool, since
+    /// executing one moves the price the others were found at
+    pub collapse_conflicts: bool,
+    /// Have the built-in cross-DEX strategy read spot prices the feeds
+    /// wrote to the price state, no older than `max_price_age`, instead of
+    /// recomputing them from reserves
+    pub state_prices: bool,
+    /// Longest route, in pools, the built-in multi-hop strategy searches.
+    /// Below 3, as by default, leaves it out, since two-pool arbs are the
+    /// cross-DEX strategy's.
+    pub multi_hop_max_hops: usize,
+    /// Have `run` re-scan only pairs whose pools changed since its last
+    /// tick, and their neighbouring pairs, instead of every pool
+    pub incremental: bool,
+    /// DEXes opportunities may route through
+    pub allowed_dexes: Vec<DexProtocol>,
 }
 
 impl Default for ScannerConfig {
@@ -35,7 +94,38 @@ impl Default for ScannerConfig {
             max_gas_gwei: 50.0,
             enabled_chains: vec![ChainId::Ethereum, ChainId::Arbitrum],
             parallel_chains: true,
+            min_pools_per_chain: 1,
+            max_position_usd: RiskConfig::default().max_position_usd,
+            min_liquidity_usd: DetectionConfig::default().min_liquidity_usd,
+            min_reserve: U256::from(MIN_RESERVE),
+            min_relative_reserve_bps: 10,
+            max_price_impact_bps: DetectionConfig::default().max_price_impact_bps,
+            flash_loan: FlashLoanConfig::default(),
+            confidence: ConfidenceModel::default(),
+            max_ref_price_age: DEFAULT_MAX_REF_PRICE_AGE,
+            min_ttl_ms: 0,
+            collapse_conflicts: true,
+            state_prices: false,
+            multi_hop_max_hops: 0,
+            incremental: false,
+            allowed_dexes: DexProtocol::ALL.to_vec(),
+        }
+    }
+}
+
+impl ScannerConfig {
+    /// Reject configs that would silently scan nothing: no enabled chains,
+    /// or an enabled chain none of `allowed_dexes` is deployed on
+    pub fn validate(&self) -> Result<(), CoreError> {
+        if self.enabled_chains.is_empty() {
+            return Err(CoreError::InvalidConfig("no chains enabled".to_string()));
+        }
+        for &chain in &self.enabled_chains {
+            if !self.allowed_dexes.iter().any(|dex| dex.is_available_on(chain)) {
+                return Err(CoreError::InvalidConfig(format!("no allowed DEX is available on {}", chain)));
+            }
         }
+        Ok(())
     }
 }
 
@@ -46,21 +136,66 @@ pub struct ArbitrageScanner {
     strategies: Vec<Box<dyn Strategy + Send + Sync>>,
     filter: OpportunityFilter,
     optimizer: RouteOptimizer,
+    /// Wall-clock time of the last completed scan (0 = never)
+    last_scan_ms: AtomicU64,
+    /// Latched once every enabled chain has warmed up
+    ready: AtomicBool,
+    /// Optional pending-tx tracker used to estimate competition
+    mempool: Option<Arc<MempoolMonitor>>,
+    /// Pair -> pool addresses per chain, maintained by `scan_incremental`
+    pair_index: Mutex<HashMap<ChainId, HashMap<TokenPair, Vec<Address>>>>,
+    /// Optional queue every scan's opportunities are pushed into
+    queue: Option<Arc<OpportunityQueue>>,
+    /// Optional NDJSON dump of every scan's opportunities
+    sink: Option<Arc<OpportunitySink>>,
 }
 
 impl ArbitrageScanner {
     pub fn new(config: ScannerConfig, state: Arc<PriceState>) -> Self {
-        let strategies: Vec<Box<dyn Strategy + Send + Sync>> = vec![
-            Box::new(CrossDexStrategy::new()),
+        let mut cross_dex = CrossDexStrategy::new();
+        if config.state_prices {
+            cross_dex = cross_dex.with_state_prices(config.max_price_age);
+        }
+        let mut strategies: Vec<Box<dyn Strategy + Send + Sync>> = vec![
+            Box::new(cross_dex),
             Box::new(TriangularStrategy::new()),
         ];
+        if config.multi_hop_max_hops >= 3 {
+            strategies.push(Box::new(MultiHopStrategy::new().with_max_hops(config.multi_hop_max_hops)));
+        }
+
+        Self::with_strategies(config, state, strategies)
+    }
+
+    /// Create a scanner with a custom set of strategies (no built-ins)
+    pub fn with_strategies(
+        config: ScannerConfig,
+        state: Arc<PriceState>,
+        strategies: Vec<Box<dyn Strategy + Send + Sync>>,
+    ) -> Self {
+        let optimizer = RouteOptimizer::new()
+            .with_max_position_usd(config.max_position_usd)
+            .with_flash_loan(config.flash_loan.clone())
+            .with_confidence_model(config.confidence.clone())
+            .with_max_ref_price_age(config.max_ref_price_age);
+        let filter = OpportunityFilter {
+            max_price_impact_bps: config.max_price_impact_bps,
+            allowed_dexes: config.allowed_dexes.clone(),
+            ..Default::default()
+        };
 
         Self {
             config,
             state,
             strategies,
-            filter: OpportunityFilter::default(),
-            optimizer: RouteOptimizer::new(),
+            filter,
+            optimizer,
+            last_scan_ms: AtomicU64::new(0),
+            ready: AtomicBool::new(false),
+            mempool: None,
+            pair_index: Mutex::new(HashMap::new()),
+            queue: None,
+            sink: None,
         }
     }
 
@@ -74,7 +209,11 @@ impl ArbitrageScanner {
             tokio::select! {
                 _ = interval.tick() => {
                     let start = Instant::now();
-                    let opportunities = self.scan_all_chains().await;
+                    let opportunities = if self.config.incremental {
+                        self.scan_incremental()
+                    } else {
+                        self.scan_all_chains().await
+                    };
                     let duration = start.elapsed();
 
                     if !opportunities.is_empty() {
@@ -107,52 +246,216 @@ impl ArbitrageScanner {
 
     /// Scan all enabled chains
     async fn scan_all_chains(&self) -> Vec<ArbitrageOpportunity> {
-        if self.config.parallel_chains {
+        let opportunities: Vec<ArbitrageOpportunity> = if self.config.parallel_chains {
             // Parallel scanning using rayon
             self.config.enabled_chains
                 .par_iter()
-                .flat_map(|chain| self.scan_chain(*chain))
+                .flat_map(|chain| self.scan_chain(*chain, &NEVER_CANCELLED))
                 .collect()
         } else {
             // Sequential scanning
             self.config.enabled_chains
                 .iter()
-                .flat_map(|chain| self.scan_chain(*chain))
+                .flat_map(|chain| self.scan_chain(*chain, &NEVER_CANCELLED))
                 .collect()
-        }
+        };
+
+        self.record_scan();
+        self.enqueue(&opportunities);
+        opportunities
     }
 
     /// Scan a single chain for opportunities
-    fn scan_chain(&self, chain: ChainId) -> Vec<ArbitrageOpportunity> {
+    fn scan_chain(&self, chain: ChainId, cancelled: &AtomicBool) -> Vec<ArbitrageOpportunity> {
+        let start = Instant::now();
+        match self.chain_snapshot(chain) {
+            Some(snapshot) => self.evaluate(&snapshot, &self.strategies, start, cancelled),
+            None => vec![],
+        }
+    }
+
+    /// Scan a single chain, sending each strategy's opportunities as soon
+    /// as that strategy finishes. Stops early once `tx` is closed.
+    fn scan_chain_streaming(&self, chain: ChainId, tx: &mpsc::Sender<ArbitrageOpportunity>) {
         let start = Instant::now();
+        let Some(snapshot) = self.chain_snapshot(chain) else {
+            return;
+        };
+        // Opportunities already sent, which later ones sharing a pool with
+        // them give way to
+        let sent: Mutex<Vec<ArbitrageOpportunity>> = Mutex::new(Vec::new());
 
+        self.strategies.par_iter().for_each(|strategy| {
+            if tx.is_closed() {
+                return;
+            }
+            let opportunities = self.evaluate(&snapshot, std::slice::from_ref(strategy), start, &NEVER_CANCELLED);
+            let opportunities = if self.config.collapse_conflicts {
+                let mut sent = sent.lock();
+                let unclaimed: Vec<ArbitrageOpportunity> = opportunities
+                    .into_iter()
+                    .filter(|opp| !sent.iter().any(|other| other.conflicts_with(opp)))
+                    .collect();
+                sent.extend(unclaimed.iter().cloned());
+                unclaimed
+            } else {
+                opportunities
+            };
+            self.enqueue(&opportunities);
+            for opp in opportunities {
+                if tx.blocking_send(opp).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    /// Fresh, liquid pools of a chain indexed for the strategies. None
+    /// while the chain has no pools or the scanner is warming up.
+    fn chain_snapshot(&self, chain: ChainId) -> Option<ChainSnapshot> {
         // Get fresh pool data
         let pools = self.state.get_chain_pools(chain, self.config.max_price_age);
 
         if pools.is_empty() {
             debug!("No pools available for {}", chain);
+            return None;
+        }
+
+        if !self.is_ready() {
+            debug!(
+                "Scanner warming up: {} has {}/{} pools",
+                chain,
+                pools.len(),
+                self.config.min_pools_per_chain
+            );
+            return None;
+        }
+
+        // Index pools once and share the snapshot across strategies
+        Some(ChainSnapshot::new(chain, self.liquid_pools(self.funded_pools(pools))))
+    }
+
+    /// Re-scan only the pairs touched by pools updated since the last
+    /// incremental scan, plus every pair sharing a token with one, so
+    /// triangles through a changed pool are found again. Reuses the
+    /// persistent pair index.
+    fn scan_chain_incremental(&self, chain: ChainId) -> Vec<ArbitrageOpportunity> {
+        let start = Instant::now();
+
+        // Leave updates queued while warming up so the first real scan sees them
+        if !self.is_ready() {
+            return vec![];
+        }
+
+        let dirty = self.state.get_dirty_pools(chain);
+        if dirty.is_empty() {
             return vec![];
         }
 
+        let pools = {
+            let mut index = self.pair_index.lock();
+            let pairs = index.entry(chain).or_default();
+
+            let mut touched: HashSet<TokenPair> = HashSet::new();
+            for address in dirty {
+                let Some((t0, t1)) = self.state.get_pool(chain, address).and_then(|e| e.pool.tokens()) else {
+                    continue;
+                };
+                let pair = normalize_pair(t0, t1);
+                let members = pairs.entry(pair).or_default();
+                if !members.contains(&address) {
+                    members.push(address);
+                }
+                touched.insert(pair);
+            }
+
+            let tokens: HashSet<Address> = touched.iter().flat_map(|&(t0, t1)| [t0, t1]).collect();
+            let neighbours: Vec<TokenPair> = pairs
+                .keys()
+                .filter(|(t0, t1)| tokens.contains(t0) || tokens.contains(t1))
+                .copied()
+                .collect();
+
+            let mut pools = Vec::new();
+            for pair in neighbours {
+                let Some(members) = pairs.get_mut(&pair) else {
+                    continue;
+                };
+                // Forget pools the state has since cleaned up
+                members.retain(|address| match self.state.get_pool(chain, *address) {
+                    Some(entry) => {
+                        let fresh = entry.updated_at.elapsed() < self.config.max_price_age;
+                        if fresh && !self.state.is_quarantined(chain, *address) {
+                            pools.push(entry);
+                        }
+                        true
+                    }
+                    None => false,
+                });
+            }
+            pools
+        };
+
+        if pools.is_empty() {
+            return vec![];
+        }
+
+        let snapshot = ChainSnapshot::new(chain, self.liquid_pools(self.funded_pools(pools)));
+        self.evaluate(&snapshot, &self.strategies, start, &NEVER_CANCELLED)
+    }
+
+    /// Run strategies over a snapshot, then annotate, optimize and filter.
+    /// Strategies not yet started when `cancelled` is set are skipped.
+    fn evaluate(
+        &self,
+        snapshot: &ChainSnapshot,
+        strategies: &[Box<dyn Strategy + Send + Sync>],
+        start: Instant,
+        cancelled: &AtomicBool,
+    ) -> Vec<ArbitrageOpportunity> {
+        let chain = snapshot.chain;
+
         // Run all strategies in parallel
-        let opportunities: Vec<ArbitrageOpportunity> = self.strategies
+        let opportunities: Vec<ArbitrageOpportunity> = strategies
             .par_iter()
+            .filter(|_| !cancelled.load(Ordering::Relaxed))
             .flat_map(|strategy| {
-                strategy.find_opportunities(chain, &pools, &self.state)
+                strategy.find_opportunities(snapshot, &self.state)
             })
-            .filter(|opp| self.filter.matches(opp))
             .collect();
 
-        // Optimize routes for valid opportunities
+        // Estimate competition before confidence is scored
+        let opportunities = match &self.mempool {
+            Some(mempool) => opportunities
+                .into_iter()
+                .map(|mut opp| {
+                    mempool.annotate(&mut opp);
+                    opp
+                })
+                .collect(),
+            None => opportunities,
+        };
+
+        // Optimize routes, then hold what's left after gas and fees to the filter
+        let now_ms = now_ms();
         let optimized: Vec<ArbitrageOpportunity> = opportunities
             .into_iter()
-            .filter_map(|opp| self.optimizer.optimize(opp))
+            .filter_map(|opp| self.optimizer.apply_position_cap(opp, &self.state))
+            .filter_map(|opp| self.optimizer.optimize(opp, &self.state))
+            .map(|opp| self.denominate(opp))
+            .filter(|opp| self.filter.matches(opp))
+            .filter(|opp| opp.ttl_ms(now_ms) >= self.config.min_ttl_ms as i64)
             .collect();
+        let optimized = if self.config.collapse_conflicts {
+            collapse_conflicts(optimized)
+        } else {
+            optimized
+        };
 
         debug!(
             "Scanned {} with {} pools, found {} opportunities in {:?}",
             chain,
-            pools.len(),
+            snapshot.len(),
             optimized.len(),
             start.elapsed()
         );
@@ -160,12 +463,216 @@ impl ArbitrageScanner {
         optimized
     }
 
+    /// Quote net profit in the filter's denomination so `min_profit`
+    /// compares like with like across pairs. Left undenominated when the
+    /// input token can't be priced in that asset.
+    fn denominate(&self, mut opp: ArbitrageOpportunity) -> ArbitrageOpportunity {
+        if let Some(denomination) = self.filter.profit_denomination {
+            if let Some(price) = self.state.get_price_in(opp.chain, opp.token_a, denomination) {
+                opp.denominate_profit(denomination, price);
+            }
+        }
+        opp
+    }
+
+    /// Drop drained pools: those below `min_reserve` in either token, and
+    /// those below `min_relative_reserve_bps` of the deepest pool for their
+    /// pair. Their dust reserves quote prices that would show up as
+    /// arbitrage against every healthy pool.
+    fn funded_pools(&self, pools: Vec<PoolEntry>) -> Vec<PoolEntry> {
+        let pools: Vec<PoolEntry> = pools
+            .into_iter()
+            .filter(|entry| entry.pool.has_min_reserves(self.config.min_reserve))
+            .collect();
+        if self.config.min_relative_reserve_bps == 0 {
+            return pools;
+        }
+
+        let pair_of = |entry: &PoolEntry| entry.pool.tokens().map(|(t0, t1)| normalize_pair(t0, t1));
+        let mut deepest: HashMap<(TokenPair, Address), U256> = HashMap::new();
+        for entry in &pools {
+            let Some(pair) = pair_of(entry) else {
+                continue;
+            };
+            for (token, reserve) in entry.pool.reserves() {
+                let max = deepest.entry((pair, token)).or_default();
+                *max = (*max).max(reserve);
+            }
+        }
+
+        let bps = U256::from(self.config.min_relative_reserve_bps);
+        pools
+            .into_iter()
+            .filter(|entry| {
+                let Some(pair) = pair_of(entry) else {
+                    return true;
+                };
+                entry.pool.reserves().iter().all(|(token, reserve)| {
+                    let max = deepest.get(&(pair, *token)).copied().unwrap_or_default();
+                    reserve.saturating_mul(U256::from(10_000u16)) >= max.saturating_mul(bps)
+                })
+            })
+            .collect()
+    }
+
+    /// Drop pools below the liquidity threshold. Pools whose tokens can't
+    /// be priced yet are kept rather than guessed at.
+    fn liquid_pools(&self, pools: Vec<PoolEntry>) -> Vec<PoolEntry> {
+        pools
+            .into_iter()
+            .filter(|entry| {
+                entry.pool
+                    .liquidity_usd(&self.state)
+                    .map_or(true, |usd| usd >= self.config.min_liquidity_usd)
+            })
+            .collect()
+    }
+
+    /// Scan every enabled chain, re-evaluating only pairs whose pools
+    /// changed since the previous incremental scan
+    pub fn scan_incremental(&self) -> Vec<ArbitrageOpportunity> {
+        let opportunities: Vec<ArbitrageOpportunity> = self.config.enabled_chains
+            .iter()
+            .flat_map(|chain| self.scan_chain_incremental(*chain))
+            .collect();
+
+        self.record_scan();
+        self.enqueue(&opportunities);
+        opportunities
+    }
+
     /// Single scan (for testing)
     pub fn scan_once(&self) -> Vec<ArbitrageOpportunity> {
-        self.config.enabled_chains
-            .iter()
-            .flat_map(|chain| self.scan_chain(*chain))
-            .collect()
+        self.scan_once_cancellable(&NEVER_CANCELLED).unwrap_or_default()
+    }
+
+    /// `scan_once` that stops starting chains and strategies once
+    /// `cancelled` is set. A cancelled scan returns None and queues and
+    /// records nothing, since its results would be partial.
+    pub fn scan_once_cancellable(&self, cancelled: &AtomicBool) -> Option<Vec<ArbitrageOpportunity>> {
+        let mut opportunities = Vec::new();
+        for chain in &self.config.enabled_chains {
+            if cancelled.load(Ordering::Relaxed) {
+                return None;
+            }
+            opportunities.extend(self.scan_chain(*chain, cancelled));
+        }
+        if cancelled.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        self.record_scan();
+        self.enqueue(&opportunities);
+        Some(opportunities)
+    }
+
+    /// Scan every enabled chain on the blocking pool, yielding each
+    /// strategy's opportunities as soon as it finishes instead of waiting
+    /// for the whole scan. Yields opportunities in completion order, and
+    /// queues and records them the way `scan_once` does. Conflicts are
+    /// collapsed across strategies as they finish: an opportunity sharing
+    /// a pool with one already yielded is dropped even when it's the more
+    /// profitable, so the set can differ from `scan_once`'s when strategies
+    /// conflict.
+    ///
+    /// Dropping the stream stops the scan after the strategies already
+    /// running. Must be called from within a tokio runtime.
+    pub fn scan_stream(self: &Arc<Self>) -> impl Stream<Item = ArbitrageOpportunity> + Send + 'static {
+        let (tx, mut rx) = mpsc::channel(STREAM_BUFFER);
+        let scanner = Arc::clone(self);
+
+        tokio::task::spawn_blocking(move || {
+            if scanner.config.parallel_chains {
+                scanner.config.enabled_chains
+                    .par_iter()
+                    .for_each(|chain| scanner.scan_chain_streaming(*chain, &tx));
+            } else {
+                for chain in &scanner.config.enabled_chains {
+                    scanner.scan_chain_streaming(*chain, &tx);
+                }
+            }
+            scanner.record_scan();
+        });
+
+        futures::stream::poll_fn(move |cx| rx.poll_recv(cx))
+    }
+
+    fn enqueue(&self, opportunities: &[ArbitrageOpportunity]) {
+        if let Some(queue) = &self.queue {
+            for opp in opportunities {
+                queue.push(opp.clone());
+            }
+        }
+        if let Some(sink) = &self.sink {
+            for opp in opportunities {
+                sink.record(opp);
+            }
+        }
+    }
+
+    fn record_scan(&self) {
+        self.last_scan_ms.store(now_ms(), Ordering::Relaxed);
+    }
+
+    /// Wall-clock time (ms) of the last completed scan, if any
+    pub fn last_scan_ms(&self) -> Option<u64> {
+        match self.last_scan_ms.load(Ordering::Relaxed) {
+            0 => None,
+            ms => Some(ms),
+        }
+    }
+
+    /// Whether every enabled chain has at least `min_pools_per_chain` fresh pools.
+    /// Once reached, readiness is latched so a brief lull doesn't flap it.
+    pub fn is_ready(&self) -> bool {
+        if self.ready.load(Ordering::Relaxed) {
+            return true;
+        }
+
+        let warm = self.config.enabled_chains.iter().all(|chain| {
+            self.state.fresh_pool_count(*chain, self.config.max_price_age)
+                >= self.config.min_pools_per_chain
+        });
+
+        if warm {
+            info!("Scanner warmup complete");
+            self.ready.store(true, Ordering::Relaxed);
+        }
+
+        warm
+    }
+
+    /// Chains this scanner covers
+    pub fn enabled_chains(&self) -> &[ChainId] {
+        &self.config.enabled_chains
+    }
+
+    /// Attach a mempool monitor so opportunities carry `competing_txs`
+    pub fn with_mempool(mut self, mempool: Arc<MempoolMonitor>) -> Self {
+        self.mempool = Some(mempool);
+        self
+    }
+
+    /// Push every opportunity found into `queue` for the executor to drain
+    pub fn with_queue(mut self, queue: Arc<OpportunityQueue>) -> Self {
+        self.queue = Some(queue);
+        self
+    }
+
+    /// Write every opportunity found to `sink` for offline analysis
+    pub fn with_sink(mut self, sink: Arc<OpportunitySink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// Register an additional strategy
+    pub fn add_strategy(&mut self, strategy: Box<dyn Strategy + Send + Sync>) {
+        self.strategies.push(strategy);
+    }
+
+    /// Names of all registered strategies
+    pub fn strategy_names(&self) -> Vec<&'static str> {
+        self.strategies.iter().map(|s| s.name()).collect()
     }
 
     /// Update filter
@@ -173,6 +680,21 @@ impl ArbitrageScanner {
         self.filter = filter;
     }
 
+    /// Gas price the optimizer charges against net profit
+    pub fn update_gas_price(&mut self, gas_price: GasPrice) {
+        self.optimizer.update_gas_price(gas_price);
+    }
+
+    /// Optimizer opportunities are sized and charged gas by
+    pub fn optimizer(&self) -> &RouteOptimizer {
+        &self.optimizer
+    }
+
+    /// Operator criteria every opportunity must meet
+    pub fn filter(&self) -> &OpportunityFilter {
+        &self.filter
+    }
+
     /// Get current stats
     pub fn stats(&self) -> ScannerStats {
         let state_stats = self.state.stats();
@@ -187,7 +709,7 @@ impl ArbitrageScanner {
 }
 
 /// Scanner statistics
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ScannerStats {
     pub enabled_chains: usize,
     pub strategy_count: usize,
@@ -195,9 +717,83 @@ pub struct ScannerStats {
     pub price_count: usize,
 }
 
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloy_primitives::{Address, U256};
+    use defi_core::{ArbitrageType, OpportunityBuilder, Pool, SwapRoute, UniswapV2Pool};
+    use defi_price_feed::test_support::seed_v2_pool;
+
+    #[test]
+    fn test_stats_round_trip() {
+        let stats = ScannerStats {
+            enabled_chains: 4,
+            strategy_count: 2,
+            pool_count: 120,
+            price_count: 30,
+        };
+
+        let json = serde_json::to_string(&stats).unwrap();
+        assert_eq!(serde_json::from_str::<ScannerStats>(&json).unwrap(), stats);
+    }
+
+    /// Emits a single fixed opportunity whenever it sees any pool
+    struct FixedStrategy;
+
+    impl Strategy for FixedStrategy {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        fn find_opportunities(
+            &self,
+            snapshot: &ChainSnapshot,
+            _state: &Arc<PriceState>,
+        ) -> Vec<ArbitrageOpportunity> {
+            let chain = snapshot.chain;
+            let one_eth = U256::from(1_000_000_000_000_000_000u128);
+            let route = |amount_in: U256, amount_out: U256| SwapRoute {
+                steps: vec![],
+                chain,
+                total_amount_in: amount_in,
+                total_amount_out: amount_out,
+                gas_estimate: 0,
+                price_impact_bps: 0,
+            };
+
+            let mut opp = OpportunityBuilder::new()
+                .arb_type(ArbitrageType::CrossDex)
+                .chain(chain)
+                .routes(route(one_eth, one_eth), route(one_eth, one_eth * U256::from(102) / U256::from(100)))
+                .build()
+                .unwrap();
+            opp.profit_usd = 50.0;
+            vec![opp]
+        }
+    }
+
+    fn seed_pool(state: &PriceState, chain: ChainId) {
+        state.update_pool(Pool::UniswapV2(UniswapV2Pool {
+            address: Address::repeat_byte(0xAA),
+            token0: Address::repeat_byte(1),
+            token1: Address::repeat_byte(2),
+            reserve0: U256::from(1_000_000u64),
+            reserve1: U256::from(1_000_000u64),
+            decimals0: 18,
+            decimals1: 18,
+            fee_bps: 30,
+            chain,
+            dex: DexProtocol::UniswapV2,
+            block_number: 1,
+        }));
+    }
 
     #[test]
     fn test_scanner_creation() {
@@ -208,6 +804,39 @@ mod tests {
         let stats = scanner.stats();
         assert_eq!(stats.strategy_count, 2);
         assert_eq!(stats.enabled_chains, 2);
+
+        // Multi-hop is opt-in, and needs room for a route longer than two pools
+        let with_hops = |multi_hop_max_hops| {
+            let config = ScannerConfig {
+                multi_hop_max_hops,
+                ..Default::default()
+            };
+            ArbitrageScanner::new(config, Arc::new(PriceState::new()))
+        };
+        assert_eq!(with_hops(2).stats().strategy_count, 2);
+        let scanner = with_hops(3);
+        assert_eq!(scanner.stats().strategy_count, 3);
+        let names: Vec<&str> = scanner.strategies.iter().map(|s| s.name()).collect();
+        assert!(names.contains(&"multi_hop"));
+    }
+
+    #[test]
+    fn test_validate_rejects_chains_without_allowed_dex() {
+        let config = |chain| ScannerConfig {
+            enabled_chains: vec![chain],
+            allowed_dexes: vec![DexProtocol::Aerodrome],
+            ..Default::default()
+        };
+
+        assert!(matches!(config(ChainId::Ethereum).validate(), Err(CoreError::InvalidConfig(_))));
+        assert!(config(ChainId::Base).validate().is_ok());
+        assert!(ScannerConfig::default().validate().is_ok());
+
+        let no_chains = ScannerConfig {
+            enabled_chains: vec![],
+            ..Default::default()
+        };
+        assert!(matches!(no_chains.validate(), Err(CoreError::InvalidConfig(_))));
     }
 
     #[test]
@@ -216,7 +845,876 @@ mod tests {
         let state = Arc::new(PriceState::new());
         let scanner = ArbitrageScanner::new(config, state);
 
+        assert!(scanner.last_scan_ms().is_none());
+
         let opportunities = scanner.scan_once();
         assert!(opportunities.is_empty());
+        assert!(scanner.last_scan_ms().is_some());
+    }
+
+    #[test]
+    fn test_custom_strategy_flows_through_scanner() {
+        let config = ScannerConfig {
+            enabled_chains: vec![ChainId::Ethereum],
+            ..Default::default()
+        };
+        let state = Arc::new(PriceState::new());
+        seed_pool(&state, ChainId::Ethereum);
+
+        let mut scanner = ArbitrageScanner::with_strategies(config, state, vec![]);
+        scanner.add_strategy(Box::new(FixedStrategy));
+
+        assert_eq!(scanner.strategy_names(), vec!["fixed"]);
+
+        let opportunities = scanner.scan_once();
+        assert_eq!(opportunities.len(), 1);
+        assert_eq!(opportunities[0].chain, ChainId::Ethereum);
+    }
+
+    #[test]
+    fn test_scan_feeds_queue() {
+        let config = ScannerConfig {
+            enabled_chains: vec![ChainId::Ethereum],
+            ..Default::default()
+        };
+        let state = Arc::new(PriceState::new());
+        seed_pool(&state, ChainId::Ethereum);
+
+        let queue = Arc::new(OpportunityQueue::new(8));
+        let scanner = ArbitrageScanner::with_strategies(config, state, vec![Box::new(FixedStrategy)])
+            .with_queue(Arc::clone(&queue));
+
+        let found = scanner.scan_once();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.pop().unwrap().id, found[0].id);
+    }
+
+    #[tokio::test]
+    async fn test_scan_writes_ndjson_to_sink() {
+        let path = std::env::temp_dir().join(format!("defi-scan-sink-{}.ndjson", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let config = ScannerConfig {
+            enabled_chains: vec![ChainId::Ethereum],
+            ..Default::default()
+        };
+        let state = Arc::new(PriceState::new());
+        seed_pool(&state, ChainId::Ethereum);
+
+        let sink = Arc::new(OpportunitySink::open(crate::sink::OpportunitySinkConfig::new(&path)).unwrap());
+        let scanner = ArbitrageScanner::with_strategies(config, state, vec![Box::new(FixedStrategy)])
+            .with_sink(Arc::clone(&sink));
+
+        let mut found = scanner.scan_once();
+        found.extend(scanner.scan_once());
+        sink.flush().await;
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+        for (line, opp) in lines.iter().zip(&found) {
+            let raw: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert!(raw["net_profit"].is_string());
+            assert!(raw["buy_route"]["total_amount_in"].is_string());
+
+            let parsed: ArbitrageOpportunity = serde_json::from_str(line).unwrap();
+            assert_eq!(parsed.id, opp.id);
+            assert_eq!(parsed.net_profit, opp.net_profit);
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// `FixedStrategy`'s opportunity once per listed buy route impact
+    struct ImpactStrategy(Vec<u16>);
+
+    impl Strategy for ImpactStrategy {
+        fn name(&self) -> &'static str {
+            "impact"
+        }
+
+        fn find_opportunities(
+            &self,
+            snapshot: &ChainSnapshot,
+            state: &Arc<PriceState>,
+        ) -> Vec<ArbitrageOpportunity> {
+            self.0
+                .iter()
+                .flat_map(|&bps| {
+                    FixedStrategy.find_opportunities(snapshot, state).into_iter().map(move |mut opp| {
+                        opp.buy_route.price_impact_bps = bps;
+                        opp
+                    })
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_price_impact_cap_from_config() {
+        let state = Arc::new(PriceState::new());
+        seed_pool(&state, ChainId::Ethereum);
+
+        let scan = |max_price_impact_bps: u16| {
+            let config = ScannerConfig {
+                enabled_chains: vec![ChainId::Ethereum],
+                max_price_impact_bps,
+                ..Default::default()
+            };
+            let strategy = ImpactStrategy(vec![20, 500]);
+            let scanner = ArbitrageScanner::with_strategies(config, Arc::clone(&state), vec![Box::new(strategy)]);
+            let mut impacts: Vec<u16> = scanner.scan_once().iter().map(|o| o.buy_route.price_impact_bps).collect();
+            impacts.sort();
+            impacts
+        };
+
+        // Same profit, only the deep-impact one is dropped at the default cap
+        assert_eq!(scan(ScannerConfig::default().max_price_impact_bps), vec![20]);
+        assert_eq!(scan(1_000), vec![20, 500]);
+    }
+
+    #[test]
+    fn test_custom_strategy_respects_filter() {
+        let config = ScannerConfig {
+            enabled_chains: vec![ChainId::Ethereum],
+            ..Default::default()
+        };
+        let state = Arc::new(PriceState::new());
+        seed_pool(&state, ChainId::Ethereum);
+
+        let mut scanner = ArbitrageScanner::new(config, state);
+        scanner.add_strategy(Box::new(FixedStrategy));
+        scanner.set_filter(OpportunityFilter {
+            min_profit_usd: 100.0,
+            ..Default::default()
+        });
+
+        assert_eq!(scanner.stats().strategy_count, 3);
+        assert!(scanner.scan_once().is_empty());
+    }
+
+    /// Emits one opportunity per pool, sized off its reserves
+    struct PoolEchoStrategy;
+
+    impl Strategy for PoolEchoStrategy {
+        fn name(&self) -> &'static str {
+            "pool_echo"
+        }
+
+        fn find_opportunities(
+            &self,
+            snapshot: &ChainSnapshot,
+            _state: &Arc<PriceState>,
+        ) -> Vec<ArbitrageOpportunity> {
+            snapshot
+                .pools()
+                .iter()
+                .filter_map(|entry| {
+                    let pool = &entry.pool;
+                    let (token0, token1) = pool.tokens()?;
+                    let amount_in = U256::from(1_000_000_000_000_000_000u128);
+                    let amount_out = pool.get_amount_out(amount_in, token0);
+                    let route = SwapRoute {
+                        steps: vec![],
+                        chain: snapshot.chain,
+                        total_amount_in: amount_in,
+                        total_amount_out: amount_out,
+                        gas_estimate: 0,
+                        price_impact_bps: 0,
+                    };
+                    let mut opp = OpportunityBuilder::new()
+                        .chain(snapshot.chain)
+                        .tokens(token0, token1)
+                        .routes(route.clone(), route)
+                        .build()
+                        .unwrap();
+                    opp.profit_usd = 50.0;
+                    Some(opp)
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_restored_snapshot_replays_identically() {
+        let config = ScannerConfig {
+            enabled_chains: vec![ChainId::Ethereum],
+            max_price_age: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let state = Arc::new(PriceState::new());
+        // Priced above parity so every echoed quote clears the optimizer's
+        // minimum profit
+        let one_eth = U256::from(1_000_000_000_000_000_000u128);
+        for (address, reserve1) in [(0xA1, 1_010_000u64), (0xA2, 1_050_000)] {
+            state.update_pool(Pool::UniswapV2(UniswapV2Pool {
+                address: Address::repeat_byte(address),
+                token0: Address::repeat_byte(1),
+                token1: Address::repeat_byte(2),
+                reserve0: U256::from(1_000_000u64) * one_eth,
+                reserve1: U256::from(reserve1) * one_eth,
+                decimals0: 18,
+                decimals1: 18,
+                fee_bps: 30,
+                chain: ChainId::Ethereum,
+                dex: DexProtocol::UniswapV2,
+                block_number: 1,
+            }));
+        }
+
+        let json = serde_json::to_string(&state.snapshot()).unwrap();
+        let restored = Arc::new(PriceState::from_snapshot(serde_json::from_str(&json).unwrap()));
+
+        let scan = |state: Arc<PriceState>| {
+            let scanner = ArbitrageScanner::with_strategies(
+                config.clone(),
+                state,
+                vec![Box::new(PoolEchoStrategy)],
+            );
+            let mut found: Vec<_> = scanner
+                .scan_once()
+                .into_iter()
+                .map(|o| (o.token_a, o.token_b, o.input_amount, o.output_amount, o.net_profit, o.profit_bps))
+                .collect();
+            found.sort_by_key(|o| o.3);
+            found
+        };
+
+        let original = scan(state);
+        assert_eq!(original.len(), 2);
+        assert_eq!(original, scan(restored));
+    }
+
+    #[test]
+    fn test_min_liquidity_filter() {
+        let chain = ChainId::Ethereum;
+        let weth = defi_core::get_token(chain, "WETH").unwrap().address;
+        let usdc = defi_core::get_token(chain, "USDC").unwrap().address;
+        let (e18, e6) = (10u128.pow(18), 10u128.pow(6));
+        let v2 = |address: u8, token0: Address, r0: u128, r1: u128| Pool::UniswapV2(UniswapV2Pool {
+            address: Address::repeat_byte(address),
+            token0,
+            token1: usdc,
+            reserve0: U256::from(r0),
+            reserve1: U256::from(r1),
+            decimals0: 18,
+            decimals1: 6,
+            fee_bps: 30,
+            chain,
+            dex: DexProtocol::UniswapV2,
+            block_number: 1,
+        });
+
+        let state = Arc::new(PriceState::new());
+        // $1M: 250 WETH at $2000 + 500k USDC
+        state.update_pool(v2(0xB1, weth, 250 * e18, 500_000 * e6));
+        // $500: 0.125 WETH + 250 USDC
+        state.update_pool(v2(0xB2, weth, e18 / 8, 250 * e6));
+
+        let config = ScannerConfig {
+            enabled_chains: vec![chain],
+            max_price_age: Duration::from_secs(60),
+            ..Default::default()
+        };
+        assert_eq!(config.min_liquidity_usd, 10_000.0);
+        let scanner = ArbitrageScanner::with_strategies(config.clone(), Arc::clone(&state), vec![]);
+
+        let kept = scanner.liquid_pools(state.get_chain_pools(chain, config.max_price_age));
+        let kept: Vec<Address> = kept.iter().map(|e| e.pool.address()).collect();
+        assert_eq!(kept, vec![Address::repeat_byte(0xB1)]);
+    }
+
+    #[test]
+    fn test_drained_pools_excluded() {
+        let chain = ChainId::Ethereum;
+        let v2 = |address: u8, token1: u8, reserve0: u128, reserve1: u128| Pool::UniswapV2(UniswapV2Pool {
+            address: Address::repeat_byte(address),
+            token0: Address::repeat_byte(1),
+            token1: Address::repeat_byte(token1),
+            reserve0: U256::from(reserve0),
+            reserve1: U256::from(reserve1),
+            decimals0: 18,
+            decimals1: 18,
+            fee_bps: 30,
+            chain,
+            dex: DexProtocol::UniswapV2,
+            block_number: 1,
+        });
+        let e18 = 10u128.pow(18);
+
+        let state = Arc::new(PriceState::new());
+        state.update_pool(v2(0xD1, 2, 1_000 * e18, 1_000 * e18));
+        // Same pair, drained to a billionth of the deep pool at twice its price
+        state.update_pool(v2(0xD2, 2, 1_000_000_000, 2_000_000_000));
+        // Alone in its pair, but down to the locked minimum on one side
+        state.update_pool(v2(0xD3, 3, 1_000 * e18, 999));
+        // Small but healthy
+        state.update_pool(v2(0xD4, 4, 1_000_000, 1_000_000));
+
+        let config = ScannerConfig {
+            enabled_chains: vec![chain],
+            max_price_age: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let kept = |config: ScannerConfig| {
+            let scanner = ArbitrageScanner::with_strategies(config.clone(), Arc::clone(&state), vec![]);
+            let mut kept: Vec<Address> = scanner
+                .funded_pools(state.get_chain_pools(chain, config.max_price_age))
+                .iter()
+                .map(|e| e.pool.address())
+                .collect();
+            kept.sort();
+            kept
+        };
+
+        assert_eq!(kept(config.clone()), vec![Address::repeat_byte(0xD1), Address::repeat_byte(0xD4)]);
+
+        let relative_off = ScannerConfig { min_relative_reserve_bps: 0, ..config.clone() };
+        assert_eq!(
+            kept(relative_off),
+            vec![Address::repeat_byte(0xD1), Address::repeat_byte(0xD2), Address::repeat_byte(0xD4)]
+        );
+
+        let higher_floor = ScannerConfig { min_reserve: U256::from(10_000_000u64), ..config };
+        assert_eq!(kept(higher_floor), vec![Address::repeat_byte(0xD1)]);
+    }
+
+    /// Emits one opportunity per `(token_a, token_b, amount_in, amount_out)`
+    struct LoopStrategy(Vec<(Address, Address, U256, U256)>);
+
+    impl Strategy for LoopStrategy {
+        fn name(&self) -> &'static str {
+            "loop"
+        }
+
+        fn find_opportunities(
+            &self,
+            snapshot: &ChainSnapshot,
+            _state: &Arc<PriceState>,
+        ) -> Vec<ArbitrageOpportunity> {
+            let route = |amount_in: U256, amount_out: U256| SwapRoute {
+                steps: vec![],
+                chain: snapshot.chain,
+                total_amount_in: amount_in,
+                total_amount_out: amount_out,
+                gas_estimate: 0,
+                price_impact_bps: 0,
+            };
+
+            self.0
+                .iter()
+                .map(|&(token_a, token_b, amount_in, amount_out)| {
+                    let mut opp = OpportunityBuilder::new()
+                        .chain(snapshot.chain)
+                        .tokens(token_a, token_b)
+                        .routes(route(amount_in, amount_in), route(amount_in, amount_out))
+                        .build()
+                        .unwrap();
+                    opp.profit_usd = 50.0;
+                    opp
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_profit_normalized_to_denomination_for_filtering() {
+        let chain = ChainId::Arbitrum;
+        let weth = defi_core::get_token(chain, "WETH").unwrap().address;
+        let arb = defi_core::get_token(chain, "ARB").unwrap().address;
+        let usdc = defi_core::get_token(chain, "USDC").unwrap().address;
+        let (e18, e6) = (10u128.pow(18), 10u128.pow(6));
+        let v2 = |address: u8, token0: Address, r0: u128, r1: u128| Pool::UniswapV2(UniswapV2Pool {
+            address: Address::repeat_byte(address),
+            token0,
+            token1: usdc,
+            reserve0: U256::from(r0),
+            reserve1: U256::from(r1),
+            decimals0: 18,
+            decimals1: 6,
+            fee_bps: 30,
+            chain,
+            dex: DexProtocol::UniswapV2,
+            block_number: 1,
+        });
+
+        let state = Arc::new(PriceState::new());
+        // 1 WETH = 2000 USDC, 1 ARB = 1 USDC
+        state.update_pool(v2(0xC1, weth, 1_000 * e18, 2_000_000 * e6));
+        state.update_pool(v2(0xC2, arb, 1_000_000 * e18, 1_000_000 * e6));
+
+        // 0.01 WETH (20 USDC) and 15 ARB (15 USDC) of net profit
+        let strategy = LoopStrategy(vec![
+            (weth, usdc, U256::from(e18), U256::from(101 * e18 / 100)),
+            (arb, usdc, U256::from(1_000 * e18), U256::from(1_015 * e18)),
+        ]);
+        let config = ScannerConfig {
+            enabled_chains: vec![chain],
+            ..Default::default()
+        };
+        let mut scanner = ArbitrageScanner::with_strategies(config, state, vec![Box::new(strategy)]);
+
+        let scan = |scanner: &mut ArbitrageScanner, min_profit: f64| {
+            scanner.set_filter(OpportunityFilter {
+                profit_denomination: Some(usdc),
+                min_profit,
+                ..Default::default()
+            });
+            let mut found: Vec<(Address, f64)> = scanner
+                .scan_once()
+                .iter()
+                .map(|o| {
+                    assert_eq!(o.profit_denomination, Some(usdc));
+                    (o.token_a, o.profit_denominated)
+                })
+                .collect();
+            found.sort_by(|a, b| a.1.total_cmp(&b.1));
+            found
+        };
+
+        let both = scan(&mut scanner, 10.0);
+        assert_eq!(both.len(), 2);
+        assert_eq!(both[0].0, arb);
+        assert!((both[0].1 - 15.0).abs() < 1e-6);
+        assert_eq!(both[1].0, weth);
+        assert!((both[1].1 - 20.0).abs() < 1e-6);
+
+        // 18 USDC separates them even though 15 ARB > 0.01 WETH in raw units
+        let weth_only = scan(&mut scanner, 18.0);
+        assert_eq!(weth_only.len(), 1);
+        assert_eq!(weth_only[0].0, weth);
+    }
+
+    #[test]
+    fn test_gas_is_paid_before_profit_is_filtered() {
+        let chain = ChainId::Arbitrum;
+        let weth = defi_core::get_token(chain, "WETH").unwrap().address;
+        let usdc = defi_core::get_token(chain, "USDC").unwrap().address;
+        let (e18, e6) = (10u128.pow(18), 10u128.pow(6));
+
+        let state = Arc::new(PriceState::new());
+        // 1 WETH = 2000 USDC
+        state.update_pool(Pool::UniswapV2(UniswapV2Pool {
+            address: Address::repeat_byte(0xC1),
+            token0: weth,
+            token1: usdc,
+            reserve0: U256::from(1_000 * e18),
+            reserve1: U256::from(2_000_000 * e6),
+            decimals0: 18,
+            decimals1: 6,
+            fee_bps: 30,
+            chain,
+            dex: DexProtocol::UniswapV2,
+            block_number: 1,
+        }));
+
+        // 0.01 WETH (20 USDC) before gas
+        let strategy = LoopStrategy(vec![(weth, usdc, U256::from(e18), U256::from(101 * e18 / 100))]);
+        let config = ScannerConfig {
+            enabled_chains: vec![chain],
+            ..Default::default()
+        };
+        let mut scanner = ArbitrageScanner::with_strategies(config, state, vec![Box::new(strategy)]);
+        scanner.set_filter(OpportunityFilter {
+            profit_denomination: Some(usdc),
+            min_profit: 15.0,
+            ..Default::default()
+        });
+
+        let found = scanner.scan_once();
+        assert_eq!(found.len(), 1);
+
+        // 0.005 WETH of gas leaves 10 USDC, under the 15 USDC minimum
+        let units = defi_core::opportunity_gas_units(&found[0]);
+        let gas_price = U256::from(5 * e18 / 1_000) / U256::from(units);
+        scanner.update_gas_price(GasPrice {
+            base_fee: gas_price,
+            priority_fee: U256::ZERO,
+            max_fee: gas_price,
+        });
+        assert!(scanner.scan_once().is_empty());
+
+        // A lower bar lets it through, denominated after gas
+        scanner.set_filter(OpportunityFilter {
+            profit_denomination: Some(usdc),
+            min_profit: 5.0,
+            ..Default::default()
+        });
+        let found = scanner.scan_once();
+        assert_eq!(found.len(), 1);
+        assert!((found[0].profit_denominated - 10.0).abs() < 1e-3);
+    }
+
+    /// Emits a WETH loop with 1% profit per TTL, each expiring that many ms
+    /// after detection
+    struct TtlStrategy(Vec<u64>);
+
+    impl Strategy for TtlStrategy {
+        fn name(&self) -> &'static str {
+            "ttl"
+        }
+
+        fn find_opportunities(
+            &self,
+            snapshot: &ChainSnapshot,
+            _state: &Arc<PriceState>,
+        ) -> Vec<ArbitrageOpportunity> {
+            let weth = defi_core::get_token(snapshot.chain, "WETH").unwrap().address;
+            let usdc = defi_core::get_token(snapshot.chain, "USDC").unwrap().address;
+            let one = U256::from(10u128.pow(18));
+            let route = |amount_out: U256| SwapRoute {
+                steps: vec![],
+                chain: snapshot.chain,
+                total_amount_in: one,
+                total_amount_out: amount_out,
+                gas_estimate: 0,
+                price_impact_bps: 0,
+            };
+
+            self.0
+                .iter()
+                .map(|&ttl_ms| {
+                    let mut opp = OpportunityBuilder::new()
+                        .chain(snapshot.chain)
+                        .tokens(weth, usdc)
+                        .routes(route(one), route(one * U256::from(101) / U256::from(100)))
+                        .ttl_ms(ttl_ms)
+                        .build()
+                        .unwrap();
+                    opp.profit_usd = 50.0;
+                    opp
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_short_lived_opportunities_dropped() {
+        let chain = ChainId::Arbitrum;
+        let weth = defi_core::get_token(chain, "WETH").unwrap().address;
+        let usdc = defi_core::get_token(chain, "USDC").unwrap().address;
+
+        let state = Arc::new(PriceState::new());
+        // 1 WETH = 2000 USDC
+        state.update_pool(Pool::UniswapV2(UniswapV2Pool {
+            address: Address::repeat_byte(0xD1),
+            token0: weth,
+            token1: usdc,
+            reserve0: U256::from(1_000 * 10u128.pow(18)),
+            reserve1: U256::from(2_000_000 * 10u128.pow(6)),
+            decimals0: 18,
+            decimals1: 6,
+            fee_bps: 30,
+            chain,
+            dex: DexProtocol::UniswapV2,
+            block_number: 1,
+        }));
+
+        let scan = |min_ttl_ms: u64| {
+            let config = ScannerConfig {
+                enabled_chains: vec![chain],
+                min_ttl_ms,
+                ..Default::default()
+            };
+            let strategy = TtlStrategy(vec![50, 5_000]);
+            let scanner = ArbitrageScanner::with_strategies(config, Arc::clone(&state), vec![Box::new(strategy)]);
+            let now = now_ms();
+            let mut ttls: Vec<i64> = scanner.scan_once().iter().map(|o| o.ttl_ms(now)).collect();
+            ttls.sort();
+            ttls
+        };
+
+        // Without a minimum both come back
+        assert_eq!(scan(0).len(), 2);
+
+        // 50ms can't survive a 200ms minimum
+        let kept = scan(200);
+        assert_eq!(kept.len(), 1);
+        assert!(kept[0] > 200, "{:?}", kept);
+    }
+
+    /// Sorted pairs and pool count of one snapshot
+    type SnapshotRecord = (Vec<TokenPair>, usize);
+
+    /// Records the pairs and pool count of every snapshot it's handed
+    struct PairRecorder(Arc<Mutex<Vec<SnapshotRecord>>>);
+
+    impl Strategy for PairRecorder {
+        fn name(&self) -> &'static str {
+            "pair_recorder"
+        }
+
+        fn find_opportunities(
+            &self,
+            snapshot: &ChainSnapshot,
+            _state: &Arc<PriceState>,
+        ) -> Vec<ArbitrageOpportunity> {
+            let mut pairs: Vec<TokenPair> = snapshot.pairs().collect();
+            pairs.sort();
+            self.0.lock().push((pairs, snapshot.len()));
+            vec![]
+        }
+    }
+
+    #[test]
+    fn test_incremental_scan_only_revisits_changed_pairs() {
+        let chain = ChainId::Ethereum;
+        let v2 = |address: u8, token0: u8, token1: u8, reserve1: u64| Pool::UniswapV2(UniswapV2Pool {
+            address: Address::repeat_byte(address),
+            token0: Address::repeat_byte(token0),
+            token1: Address::repeat_byte(token1),
+            reserve0: U256::from(1_000_000u64),
+            reserve1: U256::from(reserve1),
+            decimals0: 18,
+            decimals1: 18,
+            fee_bps: 30,
+            chain,
+            dex: DexProtocol::UniswapV2,
+            block_number: 1,
+        });
+
+        let state = Arc::new(PriceState::new());
+        state.update_pool(v2(0xA1, 1, 2, 1_000_000));
+        state.update_pool(v2(0xA2, 1, 2, 1_010_000));
+        state.update_pool(v2(0xB1, 3, 4, 1_000_000));
+        state.update_pool(v2(0xB2, 3, 4, 1_010_000));
+        state.update_pool(v2(0xC1, 2, 3, 1_000_000));
+
+        let config = ScannerConfig {
+            enabled_chains: vec![chain],
+            max_price_age: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let scanner = ArbitrageScanner::with_strategies(
+            config,
+            Arc::clone(&state),
+            vec![Box::new(PairRecorder(Arc::clone(&seen)))],
+        );
+        let pair_12 = normalize_pair(Address::repeat_byte(1), Address::repeat_byte(2));
+        let pair_23 = normalize_pair(Address::repeat_byte(2), Address::repeat_byte(3));
+        let pair_34 = normalize_pair(Address::repeat_byte(3), Address::repeat_byte(4));
+
+        // First pass sees everything
+        scanner.scan_incremental();
+        assert_eq!(seen.lock().pop(), Some((vec![pair_12, pair_23, pair_34], 5)));
+
+        // One updated pool brings back its pair, with both of its pools,
+        // and the pairs sharing a token with it, but not pairs further out
+        state.update_pool(v2(0xA2, 1, 2, 1_020_000));
+        scanner.scan_incremental();
+        assert_eq!(seen.lock().pop(), Some((vec![pair_12, pair_23], 3)));
+
+        state.update_pool(v2(0xB1, 3, 4, 1_020_000));
+        scanner.scan_incremental();
+        assert_eq!(seen.lock().pop(), Some((vec![pair_23, pair_34], 3)));
+
+        // Nothing changed, nothing scanned
+        scanner.scan_incremental();
+        assert!(seen.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_incremental_run_skips_unchanged_ticks() {
+        let config = ScannerConfig {
+            enabled_chains: vec![ChainId::Ethereum],
+            scan_interval: Duration::from_millis(10),
+            max_price_age: Duration::from_secs(60),
+            incremental: true,
+            ..Default::default()
+        };
+        let state = Arc::new(PriceState::new());
+        seed_pool(&state, ChainId::Ethereum);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let scanner = Arc::new(ArbitrageScanner::with_strategies(
+            config,
+            Arc::clone(&state),
+            vec![Box::new(PairRecorder(Arc::clone(&seen)))],
+        ));
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+        let running = tokio::spawn({
+            let scanner = Arc::clone(&scanner);
+            async move { scanner.run(shutdown_rx).await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        shutdown_tx.send(()).unwrap();
+        running.await.unwrap();
+
+        // Only the first tick had a changed pool to scan
+        assert_eq!(seen.lock().len(), 1);
+        assert!(scanner.last_scan_ms().is_some());
+    }
+
+    #[test]
+    fn test_incremental_scan_keeps_updates_queued_until_warm() {
+        let config = ScannerConfig {
+            enabled_chains: vec![ChainId::Ethereum, ChainId::Arbitrum],
+            max_price_age: Duration::from_secs(60),
+            min_pools_per_chain: 1,
+            ..Default::default()
+        };
+        let state = Arc::new(PriceState::new());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let scanner = ArbitrageScanner::with_strategies(
+            config,
+            Arc::clone(&state),
+            vec![Box::new(PairRecorder(Arc::clone(&seen)))],
+        );
+        let pair = normalize_pair(Address::repeat_byte(1), Address::repeat_byte(2));
+
+        // Ethereum updates arrive while Arbitrum is still cold
+        seed_pool(&state, ChainId::Ethereum);
+        scanner.scan_incremental();
+        assert!(seen.lock().is_empty());
+
+        // Once warm, the Ethereum update is still there to scan
+        seed_pool(&state, ChainId::Arbitrum);
+        scanner.scan_incremental();
+        assert_eq!(*seen.lock(), vec![(vec![pair], 1), (vec![pair], 1)]);
+    }
+
+    #[test]
+    fn test_warmup_until_pools_arrive() {
+        let config = ScannerConfig {
+            enabled_chains: vec![ChainId::Ethereum, ChainId::Arbitrum],
+            max_price_age: Duration::from_secs(60),
+            min_pools_per_chain: 1,
+            ..Default::default()
+        };
+        let state = Arc::new(PriceState::new());
+        let mut scanner = ArbitrageScanner::with_strategies(config, Arc::clone(&state), vec![]);
+        scanner.add_strategy(Box::new(FixedStrategy));
+
+        assert!(!scanner.is_ready());
+
+        // One chain warm is not enough
+        seed_pool(&state, ChainId::Ethereum);
+        assert!(!scanner.is_ready());
+        assert!(scanner.scan_once().is_empty());
+
+        seed_pool(&state, ChainId::Arbitrum);
+        assert!(scanner.is_ready());
+        assert_eq!(scanner.scan_once().len(), 2);
+    }
+
+    #[test]
+    fn test_mispriced_v2_pools_yield_cross_dex_opportunity() {
+        let chain = ChainId::Ethereum;
+        let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let e18 = 10u128.pow(18);
+
+        // 2.0 B per A on one DEX, 2.1 on the other
+        let state = Arc::new(PriceState::new());
+        let uni = seed_v2_pool(&state, chain, DexProtocol::UniswapV2, a, b, 1_000 * e18, 2_000 * e18);
+        let sushi = seed_v2_pool(&state, chain, DexProtocol::SushiSwap, a, b, 1_000 * e18, 2_100 * e18);
+
+        // Each leg moves its pool over 1%, past the default impact cap
+        let config = ScannerConfig {
+            enabled_chains: vec![chain],
+            max_price_age: Duration::from_secs(60),
+            max_price_impact_bps: 1_000,
+            ..Default::default()
+        };
+        let mut scanner = ArbitrageScanner::new(config, state);
+        // Synthetic tokens have no USD price, so don't require one
+        let filter = OpportunityFilter {
+            min_profit_usd: 0.0,
+            ..scanner.filter().clone()
+        };
+        scanner.set_filter(filter);
+
+        let found = scanner.scan_once();
+        assert_eq!(found.len(), 1);
+        let opp = &found[0];
+        assert_eq!(opp.arb_type, ArbitrageType::CrossDex);
+        assert_eq!((opp.token_a, opp.token_b), (a, b));
+
+        // Sell A where it's dear, buy it back where it's cheap
+        assert_eq!(opp.buy_route.steps[0].pool, sushi);
+        assert_eq!(opp.sell_route.steps[0].pool, uni);
+        assert!(opp.output_amount > opp.input_amount);
+        assert!(opp.profit_bps > 100, "{}", opp.profit_bps);
+    }
+
+    #[test]
+    fn test_allowed_dexes_from_config() {
+        let chain = ChainId::Arbitrum;
+        let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let e18 = 10u128.pow(18);
+        let state = Arc::new(PriceState::new());
+        seed_v2_pool(&state, chain, DexProtocol::UniswapV2, a, b, 1_000 * e18, 2_000 * e18);
+        seed_v2_pool(&state, chain, DexProtocol::Camelot, a, b, 1_000 * e18, 2_100 * e18);
+
+        let scan = |allowed_dexes: Vec<DexProtocol>| {
+            let config = ScannerConfig {
+                enabled_chains: vec![chain],
+                max_price_age: Duration::from_secs(60),
+                // Legs move their pools past the default impact cap
+                max_price_impact_bps: 1_000,
+                allowed_dexes,
+                ..Default::default()
+            };
+            let mut scanner = ArbitrageScanner::new(config, Arc::clone(&state));
+            let filter = OpportunityFilter {
+                min_profit_usd: 0.0,
+                ..scanner.filter.clone()
+            };
+            scanner.set_filter(filter);
+            scanner.scan_once().len()
+        };
+
+        // Camelot routes pass by default and drop once it's left out
+        assert_eq!(scan(ScannerConfig::default().allowed_dexes), 1);
+        assert_eq!(scan(vec![DexProtocol::UniswapV2, DexProtocol::SushiSwap]), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_scan_stream_yields_same_set_as_scan_once() {
+        use futures::StreamExt;
+
+        let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let e18 = 10u128.pow(18);
+        let state = Arc::new(PriceState::new());
+        for chain in [ChainId::Ethereum, ChainId::Arbitrum] {
+            seed_v2_pool(&state, chain, DexProtocol::UniswapV2, a, b, 1_000 * e18, 2_000 * e18);
+            seed_v2_pool(&state, chain, DexProtocol::SushiSwap, a, b, 1_000 * e18, 2_100 * e18);
+        }
+
+        let config = ScannerConfig {
+            max_price_age: Duration::from_secs(60),
+            // Cross-DEX legs move their pools past the default impact cap
+            max_price_impact_bps: 1_000,
+            ..Default::default()
+        };
+        // Both cross-DEX strategies find the same opportunity, which
+        // conflicts with itself across strategies
+        let strategies: Vec<Box<dyn Strategy + Send + Sync>> = vec![
+            Box::new(CrossDexStrategy::new()),
+            Box::new(CrossDexStrategy::new()),
+            Box::new(FixedStrategy),
+        ];
+        let mut scanner = ArbitrageScanner::with_strategies(config, state, strategies);
+        let filter = OpportunityFilter {
+            min_profit_usd: 0.0,
+            ..scanner.filter().clone()
+        };
+        scanner.set_filter(filter);
+        let scanner = Arc::new(scanner);
+
+        // Arrival order differs, so compare sorted
+        let keys = |opps: Vec<ArbitrageOpportunity>| {
+            let mut keys: Vec<_> = opps
+                .into_iter()
+                .map(|o| (o.chain.chain_id(), format!("{:?}", o.arb_type), o.token_a, o.output_amount))
+                .collect();
+            keys.sort();
+            keys
+        };
+
+        let once = keys(scanner.scan_once());
+        let streamed = keys(scanner.scan_stream().collect().await);
+        // One cross-DEX and one fixed opportunity per chain
+        assert_eq!(once.len(), 4);
+        assert_eq!(streamed, once);
     }
 }
@@ -0,0 +1,251 @@
+//! NDJSON dump of detected opportunities for offline analysis
+//!
+//! `OpportunitySink::record` only hands the opportunity to a bounded
+//! channel. A writer task on the blocking pool serializes it as one JSON
+//! object per line, every field included and `U256` amounts as strings, so
+//! a slow disk never holds up a scan. Opportunities arriving while the
+//! buffer is full are dropped, counted and warned about.
+//!
+//! Once the file passes `max_file_bytes` it is rotated: `path` moves to
+//! `path.1`, `path.1` to `path.2` and so on, keeping `max_files` old files.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use tracing::warn;
+
+use defi_core::ArbitrageOpportunity;
+use defi_price_feed::{BackgroundWriter, RecordWriter};
+
+/// Where and how an `OpportunitySink` writes
+#[derive(Debug, Clone)]
+pub struct OpportunitySinkConfig {
+    pub path: PathBuf,
+    /// Rotate once the current file would grow past this. Zero never rotates.
+    pub max_file_bytes: u64,
+    /// Rotated files kept alongside the current one
+    pub max_files: usize,
+    /// Opportunities buffered for the writer before new ones are dropped
+    pub buffer: usize,
+}
+
+impl OpportunitySinkConfig {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_file_bytes: 100 * 1024 * 1024,
+            max_files: 5,
+            buffer: 10_000,
+        }
+    }
+}
+
+/// Non-blocking NDJSON writer for detected opportunities
+pub struct OpportunitySink {
+    writer: BackgroundWriter<Box<ArbitrageOpportunity>>,
+    written: Arc<AtomicU64>,
+}
+
+impl OpportunitySink {
+    /// Open the configured file for appending and start the writer task.
+    /// Must be called from within a tokio runtime.
+    pub fn open(config: OpportunitySinkConfig) -> std::io::Result<Self> {
+        let file = RotatingWriter::open(config.path, config.max_file_bytes, config.max_files)?;
+        let written = Arc::new(AtomicU64::new(0));
+        let writer = BackgroundWriter::spawn(
+            "Opportunity sink",
+            config.buffer,
+            SinkWriter { file, written: Arc::clone(&written) },
+        );
+
+        Ok(Self { writer, written })
+    }
+
+    /// Queue an opportunity for writing without waiting
+    pub fn record(&self, opp: &ArbitrageOpportunity) {
+        self.writer.send(Box::new(opp.clone()));
+    }
+
+    /// Wait until everything recorded so far has been written out
+    pub async fn flush(&self) {
+        self.writer.flush().await;
+    }
+
+    /// Opportunities written to disk
+    pub fn written(&self) -> u64 {
+        self.written.load(Ordering::Relaxed)
+    }
+
+    /// Opportunities dropped because the buffer was full
+    pub fn dropped(&self) -> u64 {
+        self.writer.dropped()
+    }
+}
+
+/// Rotating file behind an `OpportunitySink`, counting what it writes
+struct SinkWriter {
+    file: RotatingWriter,
+    written: Arc<AtomicU64>,
+}
+
+impl RecordWriter for SinkWriter {
+    type Record = Box<ArbitrageOpportunity>;
+
+    fn write(&mut self, opp: Box<ArbitrageOpportunity>) {
+        match self.file.write_record(&opp) {
+            Ok(()) => {
+                self.written.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => warn!("Failed to write opportunity {}: {}", opp.id, e),
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Err(e) = self.file.flush() {
+            warn!("Failed to flush opportunity sink: {}", e);
+        }
+    }
+}
+
+/// Append-only file that rotates by size
+struct RotatingWriter {
+    path: PathBuf,
+    max_file_bytes: u64,
+    max_files: usize,
+    file: BufWriter<File>,
+    size: u64,
+}
+
+impl RotatingWriter {
+    fn open(path: PathBuf, max_file_bytes: u64, max_files: usize) -> std::io::Result<Self> {
+        let file = open_append(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_file_bytes,
+            max_files,
+            file: BufWriter::new(file),
+            size,
+        })
+    }
+
+    fn write_record(&mut self, opp: &ArbitrageOpportunity) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(opp)?;
+        line.push(b'\n');
+
+        let len = line.len() as u64;
+        if self.max_file_bytes > 0 && self.size > 0 && self.size + len > self.max_file_bytes {
+            self.rotate()?;
+        }
+
+        self.file.write_all(&line)?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+
+    /// Shift `path.N` to `path.N+1`, dropping the oldest, and start a new file
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+
+        for index in (1..self.max_files).rev() {
+            rename_if_exists(&self.rotated(index), &self.rotated(index + 1))?;
+        }
+        if self.max_files > 0 {
+            rename_if_exists(&self.path, &self.rotated(1))?;
+        } else {
+            std::fs::remove_file(&self.path)?;
+        }
+
+        self.file = BufWriter::new(open_append(&self.path)?);
+        self.size = 0;
+        Ok(())
+    }
+
+    fn rotated(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        name.into()
+    }
+}
+
+fn open_append(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn rename_if_exists(from: &Path, to: &Path) -> std::io::Result<()> {
+    match std::fs::rename(from, to) {
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+    use defi_core::{ChainId, OpportunityBuilder, SwapRoute};
+
+    fn opportunity(amount_out: u64) -> ArbitrageOpportunity {
+        let route = SwapRoute {
+            steps: vec![],
+            chain: ChainId::Base,
+            total_amount_in: U256::from(1_000u64),
+            total_amount_out: U256::from(amount_out),
+            gas_estimate: 0,
+            price_impact_bps: 0,
+        };
+        OpportunityBuilder::new()
+            .chain(ChainId::Base)
+            .routes(route.clone(), route)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rotates_after_max_size() {
+        let path = std::env::temp_dir().join(format!("defi-opps-rotate-{}.ndjson", std::process::id()));
+        let rotated = |index: usize| PathBuf::from(format!("{}.{}", path.display(), index));
+        for file in [path.clone(), rotated(1), rotated(2), rotated(3)] {
+            let _ = std::fs::remove_file(file);
+        }
+
+        // Every record is bigger than the limit, so each lands in its own file
+        let sink = OpportunitySink::open(OpportunitySinkConfig {
+            max_file_bytes: 1,
+            max_files: 2,
+            ..OpportunitySinkConfig::new(&path)
+        })
+        .unwrap();
+        let opps: Vec<ArbitrageOpportunity> = (0..5).map(|i| opportunity(1_010 + i)).collect();
+        for opp in &opps {
+            sink.record(opp);
+        }
+        sink.flush().await;
+        assert_eq!(sink.written(), 5);
+        assert_eq!(sink.dropped(), 0);
+
+        let ids = |file: &Path| -> Vec<String> {
+            std::fs::read_to_string(file)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str::<ArbitrageOpportunity>(line).unwrap().id)
+                .collect()
+        };
+        // Newest in the live file, the two before it kept, the rest gone
+        assert_eq!(ids(&path), vec![opps[4].id.clone()]);
+        assert_eq!(ids(&rotated(1)), vec![opps[3].id.clone()]);
+        assert_eq!(ids(&rotated(2)), vec![opps[2].id.clone()]);
+        assert!(!rotated(3).exists());
+
+        for file in [path.clone(), rotated(1), rotated(2)] {
+            std::fs::remove_file(file).unwrap();
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Per-scan chain snapshot shared by all strategies
+//!
+//! Built once per chain per scan so strategies don't each re-derive token
+//! pairs or re-filter the full pool list.
+//!
+//! Each pair is interned as a [`PairId`] so the hot path can walk pairs
+//! and their pools by index instead of hashing and copying addresses.
+
+use std::collections::HashMap;
+use alloy_primitives::Address;
+use rayon::prelude::*;
+
+use defi_core::ChainId;
+use defi_price_feed::PoolEntry;
+
+/// Normalized (lower, higher) token pair
+pub type TokenPair = (Address, Address);
+
+/// Normalize a pair so (A,B) and (B,A) map to the same key
+pub fn normalize_pair(a: Address, b: Address) -> TokenPair {
+    if a < b { (a, b) } else { (b, a) }
+}
+
+/// Dense id of a token pair, only meaningful for the snapshot that issued it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PairId(u32);
+
+impl PairId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Pools for one chain plus a pair -> pools index
+#[derive(Debug, Clone)]
+pub struct ChainSnapshot {
+    pub chain: ChainId,
+    pools: Vec<PoolEntry>,
+    /// Pairs in first-seen order with indices into `pools`
+    pairs: Vec<(TokenPair, Vec<usize>)>,
+    pair_index: HashMap<TokenPair, usize>,
+}
+
+impl ChainSnapshot {
+    pub fn new(chain: ChainId, pools: Vec<PoolEntry>) -> Self {
+        let mut pairs: Vec<(TokenPair, Vec<usize>)> = Vec::new();
+        let mut pair_index: HashMap<TokenPair, usize> = HashMap::new();
+
+        for (i, entry) in pools.iter().enumerate() {
+            let Some((t0, t1)) = entry.pool.tokens() else {
+                continue;
+            };
+
+            let pair = normalize_pair(t0, t1);
+            match pair_index.get(&pair) {
+                Some(&slot) => pairs[slot].1.push(i),
+                None => {
+                    pair_index.insert(pair, pairs.len());
+                    pairs.push((pair, vec![i]));
+                }
+            }
+        }
+
+        Self {
+            chain,
+            pools,
+            pairs,
+            pair_index,
+        }
+    }
+
+    /// All pools in the snapshot
+    pub fn pools(&self) -> &[PoolEntry] {
+        &self.pools
+    }
+
+    pub fn len(&self) -> usize {
+        self.pools.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pools.is_empty()
+    }
+
+    /// Unique token pairs in first-seen order
+    pub fn pairs(&self) -> impl Iterator<Item = TokenPair> + '_ {
+        self.pairs.iter().map(|(pair, _)| *pair)
+    }
+
+    pub fn pair_count(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Ids of every pair, in the same order as `pairs`
+    pub fn pair_ids(&self) -> impl Iterator<Item = PairId> {
+        (0..self.pairs.len() as u32).map(PairId)
+    }
+
+    /// `pair_ids` split across the rayon pool
+    pub fn par_pair_ids(&self) -> impl IndexedParallelIterator<Item = PairId> {
+        (0..self.pairs.len() as u32).into_par_iter().map(PairId)
+    }
+
+    /// Id of the given pair (either order), if any pool trades it
+    pub fn pair_id(&self, a: Address, b: Address) -> Option<PairId> {
+        self.pair_index.get(&normalize_pair(a, b)).map(|&slot| PairId(slot as u32))
+    }
+
+    /// Normalized addresses behind an id
+    pub fn pair(&self, id: PairId) -> TokenPair {
+        self.pairs[id.index()].0
+    }
+
+    /// Indices into `pools` of the pools trading a pair
+    pub fn pair_pool_indices(&self, id: PairId) -> &[usize] {
+        &self.pairs[id.index()].1
+    }
+
+    /// Pools trading the given pair (either order)
+    pub fn pair_pools(&self, a: Address, b: Address) -> Vec<&PoolEntry> {
+        self.pair_id(a, b)
+            .map(|id| self.pair_pool_indices(id).iter().map(|&i| &self.pools[i]).collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+    use defi_core::{DexProtocol, Pool, UniswapV2Pool};
+    use std::time::Instant;
+
+    fn entry(address: u8, token0: u8, token1: u8) -> PoolEntry {
+        PoolEntry {
+            pool: Pool::UniswapV2(UniswapV2Pool {
+                address: Address::repeat_byte(address),
+                token0: Address::repeat_byte(token0),
+                token1: Address::repeat_byte(token1),
+                reserve0: U256::from(1_000u64),
+                reserve1: U256::from(1_000u64),
+                decimals0: 18,
+                decimals1: 18,
+                fee_bps: 30,
+                chain: ChainId::Ethereum,
+                dex: DexProtocol::UniswapV2,
+                block_number: 1,
+            }),
+            updated_at: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn test_pair_index_matches_linear_filter() {
+        let pools = vec![entry(10, 1, 2), entry(11, 2, 1), entry(12, 2, 3), entry(13, 1, 3)];
+        let snapshot = ChainSnapshot::new(ChainId::Ethereum, pools.clone());
+
+        assert_eq!(snapshot.len(), 4);
+        assert_eq!(snapshot.pair_count(), 3);
+
+        for (a, b) in snapshot.pairs().collect::<Vec<_>>() {
+            let indexed: Vec<Address> = snapshot.pair_pools(a, b).iter().map(|p| p.pool.address()).collect();
+            let linear: Vec<Address> = pools
+                .iter()
+                .filter(|p| p.pool.tokens().map(|(t0, t1)| normalize_pair(t0, t1)) == Some((a, b)))
+                .map(|p| p.pool.address())
+                .collect();
+            assert_eq!(indexed, linear);
+        }
+
+        // Lookup is order-insensitive
+        assert_eq!(snapshot.pair_pools(Address::repeat_byte(2), Address::repeat_byte(1)).len(), 2);
+        assert!(snapshot.pair_pools(Address::repeat_byte(4), Address::repeat_byte(1)).is_empty());
+    }
+
+    #[test]
+    fn test_pair_ids_resolve_to_addresses() {
+        let pools = vec![entry(10, 1, 2), entry(11, 2, 3), entry(12, 2, 1)];
+        let snapshot = ChainSnapshot::new(ChainId::Ethereum, pools);
+
+        let ids: Vec<PairId> = snapshot.pair_ids().collect();
+        assert_eq!(ids.len(), snapshot.pair_count());
+        assert_eq!(snapshot.par_pair_ids().collect::<Vec<_>>(), ids);
+
+        for (id, (a, b)) in ids.iter().zip(snapshot.pairs()) {
+            assert_eq!(snapshot.pair(*id), (a, b));
+            assert_eq!(snapshot.pair_id(b, a), Some(*id));
+
+            let by_id: Vec<Address> = snapshot
+                .pair_pool_indices(*id)
+                .iter()
+                .map(|&i| snapshot.pools()[i].pool.address())
+                .collect();
+            let by_address: Vec<Address> = snapshot.pair_pools(a, b).iter().map(|p| p.pool.address()).collect();
+            assert_eq!(by_id, by_address);
+        }
+
+        assert_eq!(snapshot.pair_id(Address::repeat_byte(1), Address::repeat_byte(3)), None);
+    }
+}
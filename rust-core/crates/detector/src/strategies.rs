@@ -1,22 +1,29 @@
 //! Arbitrage detection strategies
 
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use alloy_primitives::{Address, U256};
 use rayon::prelude::*;
 
 use defi_core::{
-    ArbitrageOpportunity, ArbitrageType, ChainId, DexProtocol,
-    OpportunityBuilder, Pool, SwapRoute, SwapStep, UniswapV2Pool,
+    ArbitrageOpportunity, ArbitrageType, ChainId, DexProtocol, ExecutionConfig,
+    OpportunityBuilder, Pool, SwapRoute, SwapStep,
+    apply_slippage, hop_overhead, transfer_fee_bps,
 };
-use defi_price_feed::{PriceState, PoolEntry};
+use defi_price_feed::PriceState;
+
+use crate::snapshot::{ChainSnapshot, PairId};
 
 /// Strategy trait for different arbitrage types
+///
+/// Strategies receive a [`ChainSnapshot`] built once per chain per scan,
+/// so pair lookups are shared rather than recomputed by every strategy.
 pub trait Strategy: Send + Sync {
     fn name(&self) -> &'static str;
     fn find_opportunities(
         &self,
-        chain: ChainId,
-        pools: &[PoolEntry],
+        snapshot: &ChainSnapshot,
         state: &Arc<PriceState>,
     ) -> Vec<ArbitrageOpportunity>;
 }
@@ -24,53 +31,89 @@ pub trait Strategy: Send + Sync {
 /// Cross-DEX arbitrage: Buy on DEX A, sell on DEX B
 pub struct CrossDexStrategy {
     min_price_diff_bps: u32,
+    validate_direction: bool,
+    slippage_bps: u16,
+    /// Spot prices come from `PriceState` entries this fresh instead of
+    /// from reserves. None prices everything from reserves.
+    state_price_max_age: Option<Duration>,
 }
 
 impl CrossDexStrategy {
     pub fn new() -> Self {
         Self {
             min_price_diff_bps: 10,  // 0.1% minimum
+            validate_direction: true,
+            slippage_bps: ExecutionConfig::default().slippage_bps,
+            state_price_max_age: None,
         }
     }
 
+    /// Find spreads from the prices feeds already wrote to `PriceState`,
+    /// accepting entries up to `max_age` old, rather than recomputing them
+    /// from reserves every scan. Reserves still size and route each trade.
+    ///
+    /// A DEX price can't tell one of a DEX's pools from another, so a DEX
+    /// with several pools for the pair, or no fresh price, is priced from
+    /// reserves as before.
+    pub fn with_state_prices(mut self, max_age: Duration) -> Self {
+        self.state_price_max_age = Some(max_age);
+        self
+    }
+
+    /// Slippage each hop tolerates: every step's `min_amount_out` is its
+    /// quoted output less this
+    pub fn with_slippage_bps(mut self, slippage_bps: u16) -> Self {
+        self.slippage_bps = slippage_bps;
+        self
+    }
+
+    /// Simulate both directions of every pool pair at size and keep the
+    /// one that actually returns a profit (on by default). When off, the
+    /// spot prices alone pick the direction.
+    pub fn with_direction_validation(mut self, enabled: bool) -> Self {
+        self.validate_direction = enabled;
+        self
+    }
+
+    /// Minimum spread for a pair before pool fees. A cross-DEX round trip
+    /// moves each token twice, so fee-on-transfer taxes are charged twice
+    /// per token.
+    fn min_spread_bps(&self, chain: ChainId, token0: Address, token1: Address) -> u32 {
+        let tax_bps = transfer_fee_bps(chain, token0) as u32 + transfer_fee_bps(chain, token1) as u32;
+        self.min_price_diff_bps + 2 * tax_bps
+    }
+
+    /// Works on the interned pair: pools are reached through their snapshot
+    /// indices and the addresses are only resolved once per pair
     fn find_pair_opportunities(
         &self,
-        chain: ChainId,
-        token0: Address,
-        token1: Address,
-        pools: &[PoolEntry],
+        snapshot: &ChainSnapshot,
+        pair: PairId,
+        state: &PriceState,
     ) -> Vec<ArbitrageOpportunity> {
         let mut opportunities = Vec::new();
 
-        // Get all pools for this pair
-        let pair_pools: Vec<&PoolEntry> = pools
-            .iter()
-            .filter(|p| match &p.pool {
-                Pool::UniswapV2(v2) => {
-                    (v2.token0 == token0 && v2.token1 == token1) ||
-                    (v2.token0 == token1 && v2.token1 == token0)
-                }
-                Pool::UniswapV3(v3) => {
-                    (v3.token0 == token0 && v3.token1 == token1) ||
-                    (v3.token0 == token1 && v3.token1 == token0)
-                }
-                _ => false,
-            })
-            .collect();
-
-        if pair_pools.len() < 2 {
+        let indices = snapshot.pair_pool_indices(pair);
+        if indices.len() < 2 {
             return opportunities;
         }
 
+        let (token0, token1) = snapshot.pair(pair);
+        let pools = snapshot.pools();
+        let prices = self.venue_prices(snapshot, indices, token0, token1, state);
+
         // Compare all pairs of pools
-        for i in 0..pair_pools.len() {
-            for j in (i + 1)..pair_pools.len() {
-                if let Some(opp) = self.compare_pools(
-                    chain,
+        for (n, (&i, price_i)) in indices.iter().zip(&prices).enumerate() {
+            for (&j, price_j) in indices[n + 1..].iter().zip(&prices[n + 1..]) {
+                let (Some(price_i), Some(price_j)) = (*price_i, *price_j) else {
+                    continue;
+                };
+                if let Some(opp) = self.compare_priced(
+                    snapshot.chain,
                     token0,
                     token1,
-                    &pair_pools[i].pool,
-                    &pair_pools[j].pool,
+                    (&pools[i].pool, price_i),
+                    (&pools[j].pool, price_j),
                 ) {
                     opportunities.push(opp);
                 }
@@ -80,37 +123,81 @@ impl CrossDexStrategy {
         opportunities
     }
 
-    fn compare_pools(
+    /// Spot price of token0 in token1 for each of the pair's pools, from
+    /// reserves or, with `with_state_prices`, from the feeds' prices
+    fn venue_prices(
         &self,
-        chain: ChainId,
+        snapshot: &ChainSnapshot,
+        indices: &[usize],
         token0: Address,
         token1: Address,
-        pool_a: &Pool,
-        pool_b: &Pool,
-    ) -> Option<ArbitrageOpportunity> {
-        // Get prices from both pools
-        let (price_a, dex_a) = self.get_pool_price(pool_a, token0)?;
-        let (price_b, dex_b) = self.get_pool_price(pool_b, token0)?;
-
-        // Calculate price difference in bps
-        let (buy_pool, sell_pool, buy_price, sell_price) = if price_a < price_b {
-            (pool_a, pool_b, price_a, price_b)
-        } else {
-            (pool_b, pool_a, price_b, price_a)
+        state: &PriceState,
+    ) -> Vec<Option<f64>> {
+        let pools = snapshot.pools();
+        let Some(max_age) = self.state_price_max_age else {
+            return indices
+                .iter()
+                .map(|&i| self.get_pool_price(&pools[i].pool, token0).map(|(price, _)| price))
+                .collect();
         };
 
-        let price_diff_bps = ((sell_price - buy_price) / buy_price * 10000.0) as u32;
+        let mut pools_per_dex: HashMap<DexProtocol, usize> = HashMap::new();
+        for &i in indices {
+            *pools_per_dex.entry(pools[i].pool.dex()).or_default() += 1;
+        }
 
-        if price_diff_bps < self.min_price_diff_bps {
+        // Feed prices are in whole units, so fallbacks are too
+        indices
+            .iter()
+            .map(|&i| {
+                let pool = &pools[i].pool;
+                let dex = pool.dex();
+                let fed = if pools_per_dex[&dex] == 1 {
+                    state.get_dex_price(snapshot.chain, token0, token1, dex, max_age)
+                } else {
+                    None
+                };
+                fed.or_else(|| pool.human_price(token0, token1))
+            })
+            .collect()
+    }
+
+    /// Look for a cross-DEX arb between two pools of the same pair, given
+    /// each pool's spot price (token1 per token0)
+    fn compare_priced(
+        &self,
+        chain: ChainId,
+        token0: Address,
+        token1: Address,
+        (pool_a, price_a): (&Pool, f64),
+        (pool_b, price_b): (&Pool, f64),
+    ) -> Option<ArbitrageOpportunity> {
+        // Each fee tier is its own venue: the spread has to pay for the
+        // swap fee of both pools on top of the minimum
+        let (low, high) = if price_a < price_b { (price_a, price_b) } else { (price_b, price_a) };
+        let price_diff_bps = (high - low) / low * 10_000.0;
+        let fees_bps = (pool_a.fee_percent() + pool_b.fee_percent()) * 10_000.0;
+
+        if price_diff_bps < self.min_spread_bps(chain, token0, token1) as f64 + fees_bps {
             return None;
         }
 
-        // Calculate optimal trade size and profit
-        let input_amount = self.calculate_optimal_size(buy_pool, sell_pool)?;
+        // Calculate optimal trade size
+        let input_amount = self.calculate_optimal_size(&[pool_a, pool_b])?;
 
-        // Build routes
-        let buy_route = self.build_route(chain, buy_pool, token0, token1, input_amount)?;
-        let sell_route = self.build_route(chain, sell_pool, token1, token0, buy_route.total_amount_out)?;
+        let (buy_route, sell_route) = if self.validate_direction {
+            // Slippage at size can leave the spot direction unprofitable,
+            // so price both round trips and keep the one that pays
+            [(pool_a, pool_b), (pool_b, pool_a)]
+                .into_iter()
+                .filter_map(|(first, second)| self.round_trip(chain, first, second, token0, token1, input_amount))
+                .filter(|(_, sell)| sell.total_amount_out > input_amount)
+                .max_by_key(|(_, sell)| sell.total_amount_out)?
+        } else {
+            // Sell token0 where it's dearer, buy it back where it's cheaper
+            let (first, second) = if price_a > price_b { (pool_a, pool_b) } else { (pool_b, pool_a) };
+            self.round_trip(chain, first, second, token0, token1, input_amount)?
+        };
 
         OpportunityBuilder::new()
             .arb_type(ArbitrageType::CrossDex)
@@ -118,9 +205,28 @@ impl CrossDexStrategy {
             .tokens(token0, token1)
             .routes(buy_route, sell_route)
             .input(input_amount)
+            .block(pool_a.block_number().max(pool_b.block_number()))
             .build()
     }
 
+    /// token0 -> token1 on `first`, then back to token0 on `second`
+    fn round_trip(
+        &self,
+        chain: ChainId,
+        first: &Pool,
+        second: &Pool,
+        token0: Address,
+        token1: Address,
+        input_amount: U256,
+    ) -> Option<(SwapRoute, SwapRoute)> {
+        let buy_route = self.build_route(chain, first, token0, token1, input_amount)?;
+        let sell_route = buy_route
+            .reverse(buy_route.total_amount_out, &[second])?
+            .with_slippage(self.slippage_bps);
+        sell_route.validate().ok()?;
+        Some((buy_route, sell_route))
+    }
+
     fn get_pool_price(&self, pool: &Pool, base_token: Address) -> Option<(f64, DexProtocol)> {
         match pool {
             Pool::UniswapV2(v2) => {
@@ -141,20 +247,32 @@ impl CrossDexStrategy {
                 };
                 Some((adjusted, DexProtocol::UniswapV3))
             }
+            Pool::Solidly(sp) => {
+                let price = sp.spot_price();
+                let adjusted = if sp.token0 == base_token {
+                    price
+                } else {
+                    1.0 / price
+                };
+                Some((adjusted, sp.dex))
+            }
             _ => None,
         }
     }
 
-    fn calculate_optimal_size(&self, buy_pool: &Pool, sell_pool: &Pool) -> Option<U256> {
-        // Simplified: use a percentage of the smaller pool's liquidity
-        match (buy_pool, sell_pool) {
-            (Pool::UniswapV2(a), Pool::UniswapV2(b)) => {
-                let min_reserve = a.reserve0.min(a.reserve1).min(b.reserve0).min(b.reserve1);
-                // Trade 1% of liquidity
-                Some(min_reserve / U256::from(100))
-            }
-            _ => Some(U256::from(1_000_000_000_000_000_000u128))  // 1 ETH default
+    /// Size a trade through every pool it touches
+    fn calculate_optimal_size(&self, pools: &[&Pool]) -> Option<U256> {
+        // Simplified: use a percentage of the smallest pool's liquidity
+        let mut min_reserve: Option<U256> = None;
+        for pool in pools {
+            let Pool::UniswapV2(v2) = pool else {
+                return Some(U256::from(1_000_000_000_000_000_000u128));  // 1 ETH default
+            };
+            let reserve = v2.reserve0.min(v2.reserve1);
+            min_reserve = Some(min_reserve.map_or(reserve, |min| min.min(reserve)));
         }
+        // Trade 1% of liquidity
+        min_reserve.map(|reserve| reserve / U256::from(100))
     }
 
     fn build_route(
@@ -165,36 +283,34 @@ impl CrossDexStrategy {
         token_out: Address,
         amount_in: U256,
     ) -> Option<SwapRoute> {
-        let (amount_out, pool_address, dex, fee_bps) = match pool {
-            Pool::UniswapV2(v2) => {
-                let out = v2.get_amount_out(amount_in, token_in);
-                (out, v2.address, v2.dex, v2.fee_bps)
-            }
-            Pool::UniswapV3(v3) => {
-                // Simplified V3 output calculation
-                (amount_in, v3.address, DexProtocol::UniswapV3, (v3.fee / 100) as u16)
-            }
+        let amount_out = match pool {
+            Pool::UniswapV2(v2) => v2.get_amount_out(amount_in, token_in),
+            Pool::UniswapV3(v3) => v3.get_amount_out(amount_in, token_in),
+            Pool::Solidly(sp) => sp.get_amount_out(amount_in, token_in),
             _ => return None,
         };
 
         let step = SwapStep {
-            pool: pool_address,
-            dex,
+            pool: pool.address(),
+            dex: pool.dex(),
             token_in,
             token_out,
             amount_in,
             amount_out,
-            fee_bps,
+            fee_bps: pool.fee_bps(),
+            min_amount_out: apply_slippage(amount_out, self.slippage_bps),
         };
 
-        Some(SwapRoute {
+        let route = SwapRoute {
             steps: vec![step],
             chain,
             total_amount_in: amount_in,
             total_amount_out: amount_out,
-            gas_estimate: 150_000,
-            price_impact_bps: 0,
-        })
+            gas_estimate: pool.swap_gas(amount_in, token_in),
+            price_impact_bps: (pool.price_impact(amount_in, token_in) * 10_000.0).min(u16::MAX as f64) as u16,
+        };
+        route.validate().ok()?;
+        Some(route)
     }
 }
 
@@ -211,31 +327,224 @@ impl Strategy for CrossDexStrategy {
 
     fn find_opportunities(
         &self,
-        chain: ChainId,
-        pools: &[PoolEntry],
+        snapshot: &ChainSnapshot,
+        state: &Arc<PriceState>,
+    ) -> Vec<ArbitrageOpportunity> {
+        // Scan pairs in parallel
+        snapshot
+            .par_pair_ids()
+            .flat_map(|pair| self.find_pair_opportunities(snapshot, pair, state))
+            .collect()
+    }
+}
+
+/// Cross-DEX arbitrage through intermediate tokens: buy A -> B on one pool,
+/// then return B -> ... -> A along the best path, `max_hops` pools in total
+pub struct MultiHopStrategy {
+    max_hops: usize,
+    min_profit_bps: u32,
+    /// Route building and sizing shared with the direct strategy
+    direct: CrossDexStrategy,
+}
+
+impl MultiHopStrategy {
+    pub fn new() -> Self {
+        Self {
+            max_hops: 3,
+            min_profit_bps: 10,
+            direct: CrossDexStrategy::new(),
+        }
+    }
+
+    /// Longest arb, in pools, counting the buy leg
+    pub fn with_max_hops(mut self, max_hops: usize) -> Self {
+        self.max_hops = max_hops;
+        self
+    }
+
+    /// Best return route from `token` back to `target`, compounding output
+    /// hop by hop, as the pools of the whole arb and the amount of `target`
+    /// it returns. Only paths of two or more pools are considered; direct
+    /// returns are `CrossDexStrategy`'s job.
+    #[allow(clippy::too_many_arguments)]
+    fn best_return(
+        &self,
+        snapshot: &ChainSnapshot,
+        by_token: &HashMap<Address, Vec<usize>>,
+        token: Address,
+        target: Address,
+        amount: U256,
+        used: &mut Vec<usize>,
+        visited: &mut Vec<Address>,
+        hops: &mut Vec<SwapRoute>,
+        best: &mut Option<(Vec<usize>, U256)>,
+    ) {
+        // One hop of the budget goes to the buy leg
+        if hops.len() + 1 >= self.max_hops {
+            return;
+        }
+
+        let pools = snapshot.pools();
+        for &i in by_token.get(&token).map(Vec::as_slice).unwrap_or_default() {
+            if used.contains(&i) {
+                continue;
+            }
+            let pool = &pools[i].pool;
+            let Some(next) = pool.other_token(token) else {
+                continue;
+            };
+            if next != target && visited.contains(&next) {
+                continue;
+            }
+            let Some(hop) = self.direct.build_route(snapshot.chain, pool, token, next, amount) else {
+                continue;
+            };
+            if hop.total_amount_out.is_zero() {
+                continue;
+            }
+
+            let out = hop.total_amount_out;
+            hops.push(hop);
+            used.push(i);
+
+            if next == target {
+                let beats_best = best.as_ref().map_or(true, |(_, best_out)| out > *best_out);
+                if hops.len() >= 2 && beats_best {
+                    *best = Some((used.clone(), out));
+                }
+            } else {
+                visited.push(next);
+                self.best_return(snapshot, by_token, next, target, out, used, visited, hops, best);
+                visited.pop();
+            }
+
+            used.pop();
+            hops.pop();
+        }
+    }
+
+    /// Swap `amount` of `token` through `path` in order, one route per pool
+    fn swap_along(&self, chain: ChainId, path: &[&Pool], mut token: Address, mut amount: U256) -> Option<Vec<SwapRoute>> {
+        let mut hops = Vec::with_capacity(path.len());
+        for pool in path {
+            let next = pool.other_token(token)?;
+            let hop = self.direct.build_route(chain, pool, token, next, amount)?;
+            token = next;
+            amount = hop.total_amount_out;
+            hops.push(hop);
+        }
+        Some(hops)
+    }
+}
+
+impl Default for MultiHopStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Join consecutive single-hop routes into one route
+fn join_routes(chain: ChainId, hops: Vec<SwapRoute>) -> SwapRoute {
+    let total_amount_in = hops[0].total_amount_in;
+    let total_amount_out = hops[hops.len() - 1].total_amount_out;
+    let gas: u64 = hops.iter().map(|h| h.gas_estimate).sum();
+    let price_impact_bps = hops.iter().map(|h| h.price_impact_bps).fold(0u16, u16::saturating_add);
+    let steps: Vec<SwapStep> = hops.into_iter().flat_map(|h| h.steps).collect();
+
+    SwapRoute {
+        gas_estimate: gas + hop_overhead(steps.len()),
+        steps,
+        chain,
+        total_amount_in,
+        total_amount_out,
+        price_impact_bps,
+    }
+}
+
+impl Strategy for MultiHopStrategy {
+    fn name(&self) -> &'static str {
+        "multi_hop"
+    }
+
+    fn find_opportunities(
+        &self,
+        snapshot: &ChainSnapshot,
         _state: &Arc<PriceState>,
     ) -> Vec<ArbitrageOpportunity> {
-        // Extract unique token pairs
-        let mut pairs: Vec<(Address, Address)> = Vec::new();
-
-        for entry in pools {
-            let (t0, t1) = match &entry.pool {
-                Pool::UniswapV2(v2) => (v2.token0, v2.token1),
-                Pool::UniswapV3(v3) => (v3.token0, v3.token1),
-                _ => continue,
+        let chain = snapshot.chain;
+        let pools = snapshot.pools();
+
+        let mut by_token: HashMap<Address, Vec<usize>> = HashMap::new();
+        for (i, entry) in pools.iter().enumerate() {
+            if let Some((t0, t1)) = entry.pool.tokens() {
+                by_token.entry(t0).or_default().push(i);
+                by_token.entry(t1).or_default().push(i);
+            }
+        }
+
+        let mut opportunities = Vec::new();
+        for (i, entry) in pools.iter().enumerate() {
+            let Some((t0, t1)) = entry.pool.tokens() else {
+                continue;
             };
+            // Paths are searched at what the buy pool alone can take, then
+            // the trade is sized across every pool of the path found
+            let Some(probe_amount) = self.direct.calculate_optimal_size(&[&entry.pool]) else {
+                continue;
+            };
+
+            for (token_a, token_b) in [(t0, t1), (t1, t0)] {
+                let Some(probe_route) = self.direct.build_route(chain, &entry.pool, token_a, token_b, probe_amount) else {
+                    continue;
+                };
+
+                let mut best = None;
+                self.best_return(
+                    snapshot,
+                    &by_token,
+                    token_b,
+                    token_a,
+                    probe_route.total_amount_out,
+                    &mut vec![i],
+                    &mut vec![token_a, token_b],
+                    &mut Vec::new(),
+                    &mut best,
+                );
+                let Some((path, _)) = best else {
+                    continue;
+                };
+
+                let path: Vec<&Pool> = path.iter().map(|&k| &pools[k].pool).collect();
+                let Some(input_amount) = self.direct.calculate_optimal_size(&path) else {
+                    continue;
+                };
+                let Some(mut hops) = self.swap_along(chain, &path, token_a, input_amount) else {
+                    continue;
+                };
+                let block = path.iter().map(|pool| pool.block_number()).max().unwrap_or(0);
+                let buy_route = hops.remove(0);
+                let sell_route = join_routes(chain, hops);
+                let min_out = input_amount
+                    + input_amount * U256::from(self.min_profit_bps) / U256::from(10_000u64);
+                if sell_route.total_amount_out <= min_out {
+                    continue;
+                }
 
-            let pair = if t0 < t1 { (t0, t1) } else { (t1, t0) };
-            if !pairs.contains(&pair) {
-                pairs.push(pair);
+                if let Some(opp) = OpportunityBuilder::new()
+                    .arb_type(ArbitrageType::MultiHop)
+                    .chain(chain)
+                    .tokens(token_a, token_b)
+                    .routes(buy_route, sell_route)
+                    .input(input_amount)
+                    .block(block)
+                    .build()
+                {
+                    opportunities.push(opp);
+                }
             }
         }
 
-        // Scan pairs in parallel
-        pairs
-            .par_iter()
-            .flat_map(|(t0, t1)| self.find_pair_opportunities(chain, *t0, *t1, pools))
-            .collect()
+        opportunities
     }
 }
 
@@ -265,8 +574,7 @@ impl Strategy for TriangularStrategy {
 
     fn find_opportunities(
         &self,
-        chain: ChainId,
-        pools: &[PoolEntry],
+        _snapshot: &ChainSnapshot,
         _state: &Arc<PriceState>,
     ) -> Vec<ArbitrageOpportunity> {
         // Triangular arbitrage detection is more complex
@@ -283,6 +591,22 @@ impl Strategy for TriangularStrategy {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use defi_core::{Price, SolidlyPool, UniswapV2Pool};
+    use defi_price_feed::PoolEntry;
+
+    /// Compare two pools at their own spot prices
+    fn compare_pools(
+        strategy: &CrossDexStrategy,
+        chain: ChainId,
+        token0: Address,
+        token1: Address,
+        pool_a: &Pool,
+        pool_b: &Pool,
+    ) -> Option<ArbitrageOpportunity> {
+        let (price_a, _) = strategy.get_pool_price(pool_a, token0)?;
+        let (price_b, _) = strategy.get_pool_price(pool_b, token0)?;
+        strategy.compare_priced(chain, token0, token1, (pool_a, price_a), (pool_b, price_b))
+    }
 
     #[test]
     fn test_cross_dex_strategy() {
@@ -290,6 +614,439 @@ mod tests {
         assert_eq!(strategy.name(), "cross_dex");
     }
 
+    #[test]
+    fn test_fee_on_transfer_widens_min_spread() {
+        let strategy = CrossDexStrategy::new();
+        let paxg = defi_core::get_token(ChainId::Ethereum, "PAXG").unwrap().address;
+        let weth = defi_core::get_token(ChainId::Ethereum, "WETH").unwrap().address;
+        let usdc = defi_core::get_token(ChainId::Ethereum, "USDC").unwrap().address;
+
+        assert_eq!(strategy.min_spread_bps(ChainId::Ethereum, weth, usdc), 10);
+        assert_eq!(strategy.min_spread_bps(ChainId::Ethereum, paxg, weth), 14);
+    }
+
+    fn solidly_entry(address: Address, stable: bool, reserve1: u64) -> PoolEntry {
+        PoolEntry {
+            pool: Pool::Solidly(SolidlyPool {
+                address,
+                token0: Address::repeat_byte(1),
+                token1: Address::repeat_byte(2),
+                reserve0: U256::from(1_000_000_000_000u64),
+                reserve1: U256::from(reserve1),
+                decimals0: 6,
+                decimals1: 6,
+                stable,
+                fee_bps: if stable { 5 } else { 30 },
+                chain: ChainId::Base,
+                dex: DexProtocol::Aerodrome,
+                block_number: 1,
+            }),
+            updated_at: std::time::Instant::now(),
+        }
+    }
+
+    #[test]
+    fn test_cross_dex_prices_aerodrome_pools() {
+        let strategy = CrossDexStrategy::new();
+        let stable = solidly_entry(Address::repeat_byte(0xA1), true, 1_000_000_000_000);
+        let volatile = solidly_entry(Address::repeat_byte(0xA2), false, 1_050_000_000_000);
+
+        let (stable_price, dex) = strategy.get_pool_price(&stable.pool, Address::repeat_byte(1)).unwrap();
+        assert_eq!(dex, DexProtocol::Aerodrome);
+        assert!((stable_price - 1.0).abs() < 1e-9);
+
+        let (volatile_price, _) = strategy.get_pool_price(&volatile.pool, Address::repeat_byte(1)).unwrap();
+        assert!((volatile_price - 1.05).abs() < 1e-9);
+
+        let route = strategy
+            .build_route(ChainId::Base, &stable.pool, Address::repeat_byte(1), Address::repeat_byte(2), U256::from(1_000_000u64))
+            .unwrap();
+        assert_eq!(route.steps[0].dex, DexProtocol::Aerodrome);
+        assert!(route.total_amount_out > U256::ZERO);
+    }
+
+    fn v2_entry(address: u8, token1: u8, reserve0: u128, reserve1: u128) -> PoolEntry {
+        PoolEntry {
+            pool: Pool::UniswapV2(UniswapV2Pool {
+                address: Address::repeat_byte(address),
+                token0: Address::repeat_byte(1),
+                token1: Address::repeat_byte(token1),
+                reserve0: U256::from(reserve0),
+                reserve1: U256::from(reserve1),
+                decimals0: 18,
+                decimals1: 18,
+                fee_bps: 30,
+                chain: ChainId::Ethereum,
+                dex: DexProtocol::UniswapV2,
+                block_number: 1,
+            }),
+            updated_at: std::time::Instant::now(),
+        }
+    }
+
+    #[test]
+    fn test_cross_dex_snapshot_matches_pairwise_compare() {
+        let strategy = CrossDexStrategy::new();
+        let e18 = 1_000_000_000_000_000_000u128;
+        let cheap = v2_entry(0xA1, 2, 1_000 * e18, 1_000 * e18);
+        let dear = v2_entry(0xA2, 2, 1_000 * e18, 1_100 * e18);
+        let unrelated = v2_entry(0xA3, 3, e18, e18);
+
+        let snapshot = ChainSnapshot::new(ChainId::Ethereum, vec![cheap.clone(), unrelated, dear.clone()]);
+        let state = Arc::new(PriceState::new());
+        let found = strategy.find_opportunities(&snapshot, &state);
+
+        let direct = compare_pools(
+            &strategy,
+            ChainId::Ethereum,
+            Address::repeat_byte(1),
+            Address::repeat_byte(2),
+            &cheap.pool,
+            &dear.pool,
+        );
+
+        assert_eq!(found.len(), 1);
+        let direct = direct.unwrap();
+        assert_eq!(found[0].input_amount, direct.input_amount);
+        assert_eq!(found[0].output_amount, direct.output_amount);
+    }
+
+    #[test]
+    fn test_state_prices_find_the_reserve_spread() {
+        let e18 = 1_000_000_000_000_000_000u128;
+        let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let cheap = v2_entry(0xA1, 2, 1_000 * e18, 1_000 * e18);
+        let mut dear = v2_entry(0xA2, 2, 1_000 * e18, 1_100 * e18);
+        if let Pool::UniswapV2(ref mut pool) = dear.pool {
+            pool.dex = DexProtocol::SushiSwap;
+        }
+        let snapshot = ChainSnapshot::new(ChainId::Ethereum, vec![cheap, dear]);
+
+        let fed = |sushi_price: Price| {
+            let state = Arc::new(PriceState::new());
+            state.update_price(Price {
+                value: 1.0,
+                token: a,
+                quote_token: b,
+                dex: DexProtocol::UniswapV2,
+                chain: ChainId::Ethereum,
+                block_number: 1,
+                timestamp_ms: 0,
+            });
+            state.update_price(sushi_price);
+            state
+        };
+        // Sushi's feed quotes the pair the other way round
+        let sushi = |b_in_a: f64| Price {
+            value: b_in_a,
+            token: b,
+            quote_token: a,
+            dex: DexProtocol::SushiSwap,
+            chain: ChainId::Ethereum,
+            block_number: 1,
+            timestamp_ms: 0,
+        };
+
+        let from_reserves = CrossDexStrategy::new().find_opportunities(&snapshot, &Arc::new(PriceState::new()));
+        let from_state = CrossDexStrategy::new()
+            .with_state_prices(Duration::from_secs(60))
+            .find_opportunities(&snapshot, &fed(sushi(1.0 / 1.1)));
+
+        assert_eq!(from_reserves.len(), 1);
+        assert_eq!(from_state.len(), 1);
+        assert_eq!(from_state[0].input_amount, from_reserves[0].input_amount);
+        assert_eq!(from_state[0].output_amount, from_reserves[0].output_amount);
+        assert_eq!(from_state[0].buy_route.steps[0].pool, from_reserves[0].buy_route.steps[0].pool);
+
+        // The spread comes from the feeds: with them agreeing there's none,
+        // whatever the reserves say
+        let flat = CrossDexStrategy::new()
+            .with_state_prices(Duration::from_secs(60))
+            .find_opportunities(&snapshot, &fed(sushi(1.0)));
+        assert!(flat.is_empty());
+
+        // Stale feed prices fall back to reserves
+        let stale = CrossDexStrategy::new()
+            .with_state_prices(Duration::ZERO)
+            .find_opportunities(&snapshot, &fed(sushi(1.0)));
+        assert_eq!(stale.len(), 1);
+    }
+
+    #[test]
+    fn test_route_steps_carry_slippage_minimums() {
+        let e18 = 1_000_000_000_000_000_000u128;
+        let cheap = v2_entry(0xA1, 2, 1_000 * e18, 1_000 * e18);
+        let dear = v2_entry(0xA2, 2, 1_000 * e18, 1_100 * e18);
+        let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+
+        for slippage_bps in [50u16, 200] {
+            let strategy = CrossDexStrategy::new().with_slippage_bps(slippage_bps);
+            let opp = compare_pools(&strategy, ChainId::Ethereum, a, b, &cheap.pool, &dear.pool).unwrap();
+
+            let steps: Vec<&SwapStep> = opp.buy_route.steps.iter().chain(&opp.sell_route.steps).collect();
+            assert_eq!(steps.len(), 2);
+            for step in steps {
+                let expected = step.amount_out * U256::from(10_000 - slippage_bps) / U256::from(10_000u64);
+                assert_eq!(step.min_amount_out, expected);
+                assert!(step.min_amount_out < step.amount_out);
+            }
+        }
+    }
+
+    #[test]
+    fn test_routes_carry_price_impact() {
+        let e18 = 1_000_000_000_000_000_000u128;
+        let cheap = v2_entry(0xA1, 2, 1_000 * e18, 1_000 * e18);
+        let dear = v2_entry(0xA2, 2, 1_000 * e18, 1_100 * e18);
+        let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+
+        let opp = compare_pools(&CrossDexStrategy::new(), ChainId::Ethereum, a, b, &cheap.pool, &dear.pool).unwrap();
+        for route in [&opp.buy_route, &opp.sell_route] {
+            let step = &route.steps[0];
+            let pool = if step.pool == cheap.pool.address() { &cheap.pool } else { &dear.pool };
+            let impact = pool.price_impact(step.amount_in, step.token_in);
+            assert_eq!(route.price_impact_bps, (impact * 10_000.0) as u16);
+            assert!(route.price_impact_bps > 0);
+        }
+    }
+
+    #[test]
+    fn test_opportunities_carry_their_pools_block() {
+        let e18 = 1_000_000_000_000_000_000u128;
+        let cheap = v2_entry(0xA1, 2, 1_000 * e18, 1_000 * e18);
+        let mut dear = v2_entry(0xA2, 2, 1_000 * e18, 1_100 * e18);
+        let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let strategy = CrossDexStrategy::new();
+
+        let at_one = compare_pools(&strategy, ChainId::Ethereum, a, b, &cheap.pool, &dear.pool).unwrap();
+        assert_eq!(at_one.block_number, 1);
+
+        // The same trade seen after one of its pools moved on is a new one
+        if let Pool::UniswapV2(pool) = &mut dear.pool {
+            pool.block_number = 5;
+        }
+        let at_five = compare_pools(&strategy, ChainId::Ethereum, a, b, &cheap.pool, &dear.pool).unwrap();
+        assert_eq!(at_five.block_number, 5);
+        assert_ne!(at_five.id, at_one.id);
+    }
+
+    #[test]
+    fn test_interned_pairs_match_address_lookup() {
+        let strategy = CrossDexStrategy::new();
+        let e18 = 1_000_000_000_000_000_000u128;
+        let pools = vec![
+            v2_entry(0xA1, 2, 1_000 * e18, 1_000 * e18),
+            v2_entry(0xB1, 3, 1_000 * e18, 2_000 * e18),
+            v2_entry(0xA2, 2, 1_000 * e18, 1_100 * e18),
+            v2_entry(0xB2, 3, 1_000 * e18, 2_100 * e18),
+            v2_entry(0xA3, 2, 1_000 * e18, 1_050 * e18),
+            v2_entry(0xC1, 4, e18, e18),
+        ];
+        let snapshot = ChainSnapshot::new(ChainId::Ethereum, pools);
+
+        // Address keyed: hash every pair and collect its pools
+        let mut naive: Vec<String> = snapshot
+            .pairs()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .flat_map(|(t0, t1)| {
+                let pair_pools = snapshot.pair_pools(t0, t1);
+                let mut found = Vec::new();
+                for i in 0..pair_pools.len() {
+                    for j in (i + 1)..pair_pools.len() {
+                        let (a, b) = (&pair_pools[i].pool, &pair_pools[j].pool);
+                        found.extend(compare_pools(&strategy, ChainId::Ethereum, t0, t1, a, b));
+                    }
+                }
+                found
+            })
+            .map(|o| o.id)
+            .collect();
+
+        let found = strategy.find_opportunities(&snapshot, &Arc::new(PriceState::new()));
+        let mut interned: Vec<String> = found.iter().map(|o| o.id.clone()).collect();
+
+        naive.sort();
+        interned.sort();
+        assert_eq!(naive.len(), 4);
+        assert_eq!(interned, naive);
+
+        // Opportunities still carry the real token addresses
+        assert!(found.iter().all(|o| o.token_a == Address::repeat_byte(1)));
+    }
+
+    fn v3_entry(address: u8, fee: u32, price: f64) -> PoolEntry {
+        let sqrt_price_x96 = (2f64.powi(96) * price.sqrt()) as u128;
+        PoolEntry {
+            pool: Pool::UniswapV3(defi_core::UniswapV3Pool {
+                address: Address::repeat_byte(address),
+                token0: Address::repeat_byte(1),
+                token1: Address::repeat_byte(2),
+                decimals0: 18,
+                decimals1: 18,
+                fee,
+                tick_spacing: 10,
+                liquidity: 1_000_000_000_000_000_000_000_000,
+                sqrt_price_x96: U256::from(sqrt_price_x96),
+                tick: (price.ln() / 1.0001f64.ln()) as i32,
+                ticks: vec![],
+                chain: ChainId::Ethereum,
+                block_number: 1,
+            }),
+            updated_at: std::time::Instant::now(),
+        }
+    }
+
+    #[test]
+    fn test_v3_fee_tiers_are_separate_venues() {
+        let strategy = CrossDexStrategy::new();
+        let low = v3_entry(0xB1, defi_core::UniswapV3Pool::FEE_LOW, 1.0);
+        let medium = v3_entry(0xB2, defi_core::UniswapV3Pool::FEE_MEDIUM, 1.01);
+
+        let snapshot = ChainSnapshot::new(ChainId::Ethereum, vec![low.clone(), medium.clone()]);
+        let found = strategy.find_opportunities(&snapshot, &Arc::new(PriceState::new()));
+        assert_eq!(found.len(), 1);
+
+        // token0 is sold where it's dearer and bought back where it's cheaper
+        let buy = &found[0].buy_route.steps[0];
+        let sell = &found[0].sell_route.steps[0];
+        assert_eq!((buy.pool, buy.fee_bps), (medium.pool.address(), 30));
+        assert_eq!((sell.pool, sell.fee_bps), (low.pool.address(), 5));
+        assert_eq!(buy.dex, sell.dex);
+
+        // Output is priced through the pool, net of the 0.05% fee
+        assert_eq!(sell.amount_out, low.pool.get_amount_out(sell.amount_in, sell.token_in));
+        let ratio = sell.amount_out.to::<u128>() as f64 / sell.amount_in.to::<u128>() as f64;
+        assert!(ratio > 0.9994 && ratio < 0.9995, "{}", ratio);
+    }
+
+    #[test]
+    fn test_spread_must_cover_both_fee_tiers() {
+        let strategy = CrossDexStrategy::new();
+        let (t0, t1) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let low = v3_entry(0xB1, defi_core::UniswapV3Pool::FEE_LOW, 1.0);
+
+        // 30 bps clears the 10 bps minimum but not 5 + 30 bps of fees
+        let medium = v3_entry(0xB2, defi_core::UniswapV3Pool::FEE_MEDIUM, 1.003);
+        assert!(compare_pools(&strategy, ChainId::Ethereum, t0, t1, &low.pool, &medium.pool).is_none());
+
+        // The same spread against another 0.05% pool is wide enough
+        let other_low = v3_entry(0xB3, defi_core::UniswapV3Pool::FEE_LOW, 1.003);
+        assert!(compare_pools(&strategy, ChainId::Ethereum, t0, t1, &low.pool, &other_low.pool).is_some());
+    }
+
+    #[test]
+    fn test_direction_follows_simulated_profit() {
+        let strategy = CrossDexStrategy::new();
+        let (t0, t1) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let e18 = 1_000_000_000_000_000_000u128;
+        let cheap = v2_entry(0xA1, 2, 1_000 * e18, 1_000 * e18);
+        let dear = v2_entry(0xA2, 2, 1_000 * e18, 1_100 * e18);
+
+        // Argument order doesn't decide the direction
+        for (a, b) in [(&cheap, &dear), (&dear, &cheap)] {
+            let opp = compare_pools(&strategy, ChainId::Ethereum, t0, t1, &a.pool, &b.pool).unwrap();
+            assert_eq!(opp.buy_route.steps[0].pool, dear.pool.address());
+            assert_eq!(opp.sell_route.steps[0].pool, cheap.pool.address());
+            assert!(opp.gross_profit > U256::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_slippage_can_rule_out_both_directions() {
+        let (t0, t1) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let e18 = 1_000_000_000_000_000_000u128;
+        // 150 bps of spot spread clears 10 bps plus 60 bps of fees, but
+        // selling 1% of the thin pool's reserves into it costs more than that
+        let thin = v2_entry(0xA1, 2, 100 * e18, 1_015 * e18 / 10);
+        let deep = v2_entry(0xA2, 2, 1_000_000 * e18, 1_000_000 * e18);
+
+        let validated = CrossDexStrategy::new();
+        assert!(compare_pools(&validated, ChainId::Ethereum, t0, t1, &thin.pool, &deep.pool).is_none());
+        assert!(compare_pools(&validated, ChainId::Ethereum, t0, t1, &deep.pool, &thin.pool).is_none());
+
+        // Trusting spot prices trades the losing direction
+        let spot_only = CrossDexStrategy::new().with_direction_validation(false);
+        let opp = compare_pools(&spot_only, ChainId::Ethereum, t0, t1, &deep.pool, &thin.pool).unwrap();
+        assert_eq!(opp.buy_route.steps[0].pool, thin.pool.address());
+        assert!(opp.output_amount < opp.input_amount);
+        assert_eq!(opp.gross_profit, U256::ZERO);
+    }
+
+    fn pair_entry(address: u8, token0: u8, token1: u8, reserve0: u128, reserve1: u128) -> PoolEntry {
+        let mut entry = v2_entry(address, token1, reserve0, reserve1);
+        if let Pool::UniswapV2(v2) = &mut entry.pool {
+            v2.token0 = Address::repeat_byte(token0);
+        }
+        entry
+    }
+
+    #[test]
+    fn test_multi_hop_beats_direct_comparison() {
+        let e18 = 1_000_000_000_000_000_000u128;
+        // A/B spread is too thin to trade directly, but B -> C -> A returns more A
+        let snapshot = ChainSnapshot::new(
+            ChainId::Ethereum,
+            vec![
+                pair_entry(0xA1, 1, 2, 1_000 * e18, 1_000 * e18),
+                pair_entry(0xA2, 1, 2, 1_000 * e18, 1_002 * e18),
+                pair_entry(0xB1, 2, 3, 1_000 * e18, 1_100 * e18),
+                pair_entry(0xC1, 3, 1, 1_000 * e18, 1_000 * e18),
+            ],
+        );
+        let state = Arc::new(PriceState::new());
+
+        let direct_best = CrossDexStrategy::new()
+            .find_opportunities(&snapshot, &state)
+            .into_iter()
+            .map(|o| o.net_profit)
+            .max()
+            .unwrap_or(U256::ZERO);
+
+        let strategy = MultiHopStrategy::new();
+        assert_eq!(strategy.name(), "multi_hop");
+        let best = strategy
+            .find_opportunities(&snapshot, &state)
+            .into_iter()
+            .max_by_key(|o| o.net_profit)
+            .unwrap();
+
+        assert_eq!(best.sell_route.hop_count(), 2);
+        assert_eq!(best.sell_route.steps[0].token_in, best.buy_route.steps[0].token_out);
+        assert_eq!(best.sell_route.steps[1].token_out, best.buy_route.steps[0].token_in);
+        assert!(best.net_profit > direct_best);
+
+        assert_eq!(best.arb_type, ArbitrageType::MultiHop);
+
+        // Two pools only fit a direct return, which is CrossDexStrategy's job
+        let capped = MultiHopStrategy::new().with_max_hops(2);
+        assert!(capped.find_opportunities(&snapshot, &state).is_empty());
+    }
+
+    #[test]
+    fn test_multi_hop_sizes_across_every_leg() {
+        let e18 = 1_000_000_000_000_000_000u128;
+        // The C -> A return pool is a tenth the depth of the others
+        let snapshot = ChainSnapshot::new(
+            ChainId::Ethereum,
+            vec![
+                pair_entry(0xA2, 1, 2, 1_000 * e18, 1_002 * e18),
+                pair_entry(0xB1, 2, 3, 1_000 * e18, 1_100 * e18),
+                pair_entry(0xC1, 3, 1, 100 * e18, 100 * e18),
+            ],
+        );
+
+        let opportunities = MultiHopStrategy::new().find_opportunities(&snapshot, &Arc::new(PriceState::new()));
+        let opp = opportunities
+            .iter()
+            .find(|o| o.buy_route.steps[0].pool == Address::repeat_byte(0xA2))
+            .unwrap();
+        // 1% of the thinnest pool on the path, not of the buy pool
+        assert_eq!(opp.input_amount, U256::from(e18));
+        assert_eq!(opp.sell_route.steps[0].amount_in, opp.buy_route.total_amount_out);
+        assert!(opp.output_amount > opp.input_amount);
+    }
+
     #[test]
     fn test_triangular_strategy() {
         let strategy = TriangularStrategy::new();
@@ -1,7 +1,15 @@
 //! Transaction builder for arbitrage execution
 
 use alloy_primitives::{Address, Bytes, U256};
-use defi_core::{ArbitrageOpportunity, ChainId, SwapRoute};
+use defi_core::{
+    opportunity_gas_units, wrapped_native, ArbitrageOpportunity, ArbitrageType, ChainId,
+    CoreError, ExecutionConfig, GasPrice, RiskConfig,
+};
+
+/// `WETH.deposit()` selector
+const DEPOSIT_SELECTOR: [u8; 4] = [0xd0, 0xe3, 0x0d, 0xb0];
+/// `WETH.withdraw(uint256)` selector
+const WITHDRAW_SELECTOR: [u8; 4] = [0x2e, 0x1a, 0x7d, 0x4d];
 
 /// Built transaction ready for submission
 #[derive(Debug, Clone)]
@@ -16,11 +24,22 @@ pub struct BuiltTransaction {
     pub nonce: Option<u64>,
 }
 
+/// EIP-1559 fee fields for a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Eip1559Fees {
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee: U256,
+}
+
 /// Transaction builder
 pub struct TransactionBuilder {
     chain: ChainId,
     router_address: Address,
     deadline_seconds: u64,
+    max_gas_price_gwei: f64,
+    max_position_usd: f64,
+    native_in: bool,
+    native_out: bool,
 }
 
 impl TransactionBuilder {
@@ -29,6 +48,98 @@ impl TransactionBuilder {
             chain,
             router_address,
             deadline_seconds: 120,
+            max_gas_price_gwei: ExecutionConfig::default().max_gas_price_gwei,
+            max_position_usd: RiskConfig::default().max_position_usd,
+            native_in: false,
+            native_out: false,
+        }
+    }
+
+    /// Pay for the route in native ETH, wrapping it before the first swap
+    pub fn with_native_in(mut self, native_in: bool) -> Self {
+        self.native_in = native_in;
+        self
+    }
+
+    /// Receive native ETH, unwrapping the route output after the last swap
+    pub fn with_native_out(mut self, native_out: bool) -> Self {
+        self.native_out = native_out;
+        self
+    }
+
+    /// Apply the position cap from risk config
+    pub fn with_risk_config(mut self, risk: &RiskConfig) -> Self {
+        self.max_position_usd = risk.max_position_usd;
+        self
+    }
+
+    /// Reject opportunities sized above the position cap. Sizes that were
+    /// never priced (`input_usd == 0`) are left to the optimizer.
+    fn check_position(&self, opp: &ArbitrageOpportunity) -> anyhow::Result<()> {
+        if opp.input_usd > self.max_position_usd {
+            anyhow::bail!(
+                "Position ${:.2} exceeds max position ${:.2}",
+                opp.input_usd,
+                self.max_position_usd
+            );
+        }
+        Ok(())
+    }
+
+    /// Apply deadline and gas cap from execution config
+    pub fn with_execution_config(mut self, config: &ExecutionConfig) -> Self {
+        self.deadline_seconds = config.deadline_seconds;
+        self.max_gas_price_gwei = config.max_gas_price_gwei;
+        self
+    }
+
+    /// Select EIP-1559 fees for the current network conditions.
+    ///
+    /// `max_fee_per_gas = base_fee * 2 + priority_fee`, which leaves room for
+    /// the base fee to double before inclusion, capped at the configured
+    /// maximum. On chains where tips don't buy priority the tip is dropped.
+    pub fn select_fees(&self, gas_price: &GasPrice) -> anyhow::Result<Eip1559Fees> {
+        let cap = gwei_to_wei(self.max_gas_price_gwei);
+
+        if gas_price.base_fee > cap {
+            return Err(CoreError::GasPriceTooHigh {
+                price_gwei: wei_to_gwei(gas_price.base_fee),
+                max_gwei: self.max_gas_price_gwei,
+            }
+            .into());
+        }
+
+        let priority_fee = if self.chain.uses_priority_fee() {
+            gas_price.priority_fee
+        } else {
+            U256::ZERO
+        };
+
+        let max_fee_per_gas = (gas_price.base_fee * U256::from(2) + priority_fee).min(cap);
+
+        Ok(Eip1559Fees {
+            max_fee_per_gas,
+            max_priority_fee: priority_fee.min(max_fee_per_gas),
+        })
+    }
+
+    /// Build the transaction the opportunity calls for: `FlashLoan`
+    /// opportunities borrow their full input, everything else is a plain
+    /// multicall. Either way the position cap applies.
+    pub fn build_tx(
+        &self,
+        opp: &ArbitrageOpportunity,
+        from: Address,
+        nonce: u64,
+        gas_price: &GasPrice,
+    ) -> anyhow::Result<BuiltTransaction> {
+        self.check_position(opp)?;
+
+        match opp.arb_type {
+            ArbitrageType::FlashLoan => {
+                self.build_flash_loan_tx(opp, opp.input_amount, from, nonce, gas_price)
+            }
+            _ => self.build_arbitrage_tx(opp, from, nonce, gas_price),
         }
     }
 
@@ -38,20 +149,31 @@ impl TransactionBuilder {
         opp: &ArbitrageOpportunity,
         from: Address,
         nonce: u64,
+        gas_price: &GasPrice,
     ) -> anyhow::Result<BuiltTransaction> {
+        self.check_position(opp)?;
+
         // Build multicall data for atomic execution
         let calldata = self.encode_multicall(opp)?;
 
         let gas_limit = self.estimate_gas(opp);
+        let fees = self.select_fees(gas_price)?;
+
+        // Wrapped ETH comes from the transaction value
+        let value = if self.native_in {
+            opp.input_amount
+        } else {
+            U256::ZERO
+        };
 
         Ok(BuiltTransaction {
             chain: self.chain,
             to: self.router_address,
-            value: U256::ZERO,
+            value,
             data: calldata,
             gas_limit,
-            max_fee_per_gas: U256::from(50_000_000_000u64), // 50 gwei
-            max_priority_fee: U256::from(2_000_000_000u64),  // 2 gwei
+            max_fee_per_gas: fees.max_fee_per_gas,
+            max_priority_fee: fees.max_priority_fee,
             nonce: Some(nonce),
         })
     }
@@ -63,11 +185,13 @@ impl TransactionBuilder {
         flash_loan_amount: U256,
         from: Address,
         nonce: u64,
+        gas_price: &GasPrice,
     ) -> anyhow::Result<BuiltTransaction> {
         // Build flash loan callback data
         let calldata = self.encode_flash_loan(opp, flash_loan_amount)?;
 
         let gas_limit = self.estimate_gas(opp) + 100_000; // Extra for flash loan
+        let fees = self.select_fees(gas_price)?;
 
         Ok(BuiltTransaction {
             chain: self.chain,
@@ -75,8 +199,8 @@ impl TransactionBuilder {
             value: U256::ZERO,
             data: calldata,
             gas_limit,
-            max_fee_per_gas: U256::from(50_000_000_000u64),
-            max_priority_fee: U256::from(2_000_000_000u64),
+            max_fee_per_gas: fees.max_fee_per_gas,
+            max_priority_fee: fees.max_priority_fee,
             nonce: Some(nonce),
         })
     }
@@ -84,6 +208,15 @@ impl TransactionBuilder {
     fn encode_multicall(&self, opp: &ArbitrageOpportunity) -> anyhow::Result<Bytes> {
         let mut calls = Vec::new();
 
+        let first = opp.buy_route.steps.first();
+        let last = opp.sell_route.steps.last();
+
+        if self.native_in {
+            let step = first.ok_or_else(|| anyhow::anyhow!("Native input on an empty route"))?;
+            let weth = self.wrapped_native_for(step.token_in)?;
+            calls.push(encode_wrap(weth));
+        }
+
         // Encode buy route swaps
         for step in &opp.buy_route.steps {
             calls.push(self.encode_swap(step)?);
@@ -94,26 +227,47 @@ impl TransactionBuilder {
             calls.push(self.encode_swap(step)?);
         }
 
+        if self.native_out {
+            let step = last.ok_or_else(|| anyhow::anyhow!("Native output on an empty route"))?;
+            let weth = self.wrapped_native_for(step.token_out)?;
+            // Only the minimum is certain to have arrived
+            calls.push(encode_unwrap(weth, step.min_amount_out));
+        }
+
         // Encode multicall
         // In production, use proper ABI encoding
         let mut data = Vec::new();
         data.extend_from_slice(&[0xac, 0x96, 0x50, 0xd8]); // multicall selector
 
+        // Calls differ in size, so each is prefixed with its length
         for call in &calls {
+            data.extend_from_slice(&U256::from(call.len()).to_be_bytes::<32>());
             data.extend_from_slice(call);
         }
 
         Ok(Bytes::from(data))
     }
 
+    /// The chain's wrapped native token, provided the route starts or ends in it
+    fn wrapped_native_for(&self, token: Address) -> anyhow::Result<Address> {
+        let weth = wrapped_native(self.chain)
+            .ok_or_else(|| anyhow::anyhow!("No wrapped native token on {}", self.chain.name()))?;
+
+        if token != weth.address {
+            anyhow::bail!("Native ETH requires the route to use {} at its ends", weth.symbol);
+        }
+        Ok(weth.address)
+    }
+
     fn encode_swap(&self, step: &defi_core::SwapStep) -> anyhow::Result<Vec<u8>> {
         // Encode swap call
         // In production, use alloy-sol-types
 
         let mut data = Vec::new();
-        // Placeholder encoding
+        // Placeholder encoding: pool, amount in, minimum out for this hop
         data.extend_from_slice(&step.pool.as_slice());
         data.extend_from_slice(&step.amount_in.to_be_bytes::<32>());
+        data.extend_from_slice(&step.min_amount_out.to_be_bytes::<32>());
 
         Ok(data)
     }
@@ -136,11 +290,252 @@ impl TransactionBuilder {
     }
 
     fn estimate_gas(&self, opp: &ArbitrageOpportunity) -> u64 {
-        let base = 50_000u64;
-        let per_swap = 150_000u64;
+        opportunity_gas_units(opp)
+    }
+}
+
+fn encode_wrap(weth: Address) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(weth.as_slice());
+    data.extend_from_slice(&DEPOSIT_SELECTOR);
+    data
+}
+
+fn encode_unwrap(weth: Address, amount: U256) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(weth.as_slice());
+    data.extend_from_slice(&WITHDRAW_SELECTOR);
+    data.extend_from_slice(&amount.to_be_bytes::<32>());
+    data
+}
+
+fn gwei_to_wei(gwei: f64) -> U256 {
+    U256::from((gwei * 1e9) as u128)
+}
+
+fn wei_to_gwei(wei: U256) -> f64 {
+    wei.to_string().parse::<f64>().unwrap_or(f64::MAX) / 1e9
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use defi_core::SwapRoute;
+
+    fn gas(base_gwei: f64, priority_gwei: f64) -> GasPrice {
+        let base_fee = gwei_to_wei(base_gwei);
+        let priority_fee = gwei_to_wei(priority_gwei);
+        GasPrice {
+            base_fee,
+            priority_fee,
+            max_fee: base_fee + priority_fee,
+        }
+    }
+
+    fn weth_round_trip() -> ArbitrageOpportunity {
+        let weth = wrapped_native(ChainId::Arbitrum).unwrap().address;
+        let usdc = defi_core::get_token(ChainId::Arbitrum, "USDC").unwrap().address;
+        let step = |token_in, token_out, amount_in: u64, amount_out: u64| defi_core::SwapStep {
+            pool: Address::repeat_byte(0xAA),
+            dex: defi_core::DexProtocol::UniswapV2,
+            token_in,
+            token_out,
+            amount_in: U256::from(amount_in),
+            amount_out: U256::from(amount_out),
+            fee_bps: 30,
+            min_amount_out: U256::ZERO,
+        };
+        let route = |step: defi_core::SwapStep| SwapRoute {
+            chain: ChainId::Arbitrum,
+            total_amount_in: step.amount_in,
+            total_amount_out: step.amount_out,
+            steps: vec![step],
+            gas_estimate: 0,
+            price_impact_bps: 0,
+        };
+
+        defi_core::OpportunityBuilder::new()
+            .routes(
+                route(step(weth, usdc, 1_000, 2_000)),
+                route(step(usdc, weth, 2_000, 1_010)),
+            )
+            .build()
+            .unwrap()
+    }
+
+    /// Split multicall calldata back into its length-prefixed calls
+    fn calls(data: &[u8]) -> Vec<&[u8]> {
+        let mut rest = &data[4..];
+        let mut calls = Vec::new();
+        while !rest.is_empty() {
+            let len = U256::from_be_slice(&rest[..32]).to::<usize>();
+            calls.push(&rest[32..32 + len]);
+            rest = &rest[32 + len..];
+        }
+        calls
+    }
+
+    #[test]
+    fn test_native_in_leads_with_deposit() {
+        let weth = wrapped_native(ChainId::Arbitrum).unwrap().address;
+        let opp = weth_round_trip();
+        let builder = TransactionBuilder::new(ChainId::Arbitrum, Address::ZERO).with_native_in(true);
+
+        let tx = builder.build_arbitrage_tx(&opp, Address::ZERO, 0, &gas(0.01, 0.0)).unwrap();
+
+        assert_eq!(tx.value, U256::from(1_000));
+        let calls = calls(&tx.data);
+        assert_eq!(calls.len(), 3);
+        assert_eq!(&calls[0][..20], weth.as_slice());
+        assert_eq!(&calls[0][20..], &DEPOSIT_SELECTOR);
+    }
+
+    #[test]
+    fn test_native_out_ends_with_withdraw() {
+        let weth = wrapped_native(ChainId::Arbitrum).unwrap().address;
+        let mut opp = weth_round_trip();
+        opp.sell_route = opp.sell_route.with_slippage(100);
+
+        let plain = TransactionBuilder::new(ChainId::Arbitrum, Address::ZERO)
+            .build_arbitrage_tx(&opp, Address::ZERO, 0, &gas(0.01, 0.0))
+            .unwrap();
+        let native = TransactionBuilder::new(ChainId::Arbitrum, Address::ZERO)
+            .with_native_out(true)
+            .build_arbitrage_tx(&opp, Address::ZERO, 0, &gas(0.01, 0.0))
+            .unwrap();
+
+        assert_eq!(plain.value, U256::ZERO);
+        assert_eq!(&native.data[..plain.data.len()], &plain.data[..]);
+        let calls = calls(&native.data);
+        assert_eq!(calls.len(), 3);
+        let unwrap = calls[2];
+        assert_eq!(&unwrap[..20], weth.as_slice());
+        assert_eq!(&unwrap[20..24], &WITHDRAW_SELECTOR);
+        // The sell leg's guaranteed output, not its quote of 1010
+        assert_eq!(&unwrap[24..], &U256::from(999).to_be_bytes::<32>());
+    }
+
+    #[test]
+    fn test_each_swap_encodes_its_step_minimum() {
+        let mut opp = weth_round_trip();
+        let usdc = opp.sell_route.steps[0].token_in;
+        let weth = opp.sell_route.steps[0].token_out;
+        let dai = Address::repeat_byte(0xDA);
+
+        // Two-hop sell leg, each hop protected at 1%
+        let hop = |token_in, token_out, amount_in: u64, amount_out: u64| defi_core::SwapStep {
+            token_in,
+            token_out,
+            amount_in: U256::from(amount_in),
+            amount_out: U256::from(amount_out),
+            ..opp.sell_route.steps[0].clone()
+        };
+        opp.sell_route.steps = vec![hop(usdc, dai, 2_000, 1_990), hop(dai, weth, 1_990, 1_010)];
+        opp.buy_route = opp.buy_route.with_slippage(100);
+        opp.sell_route = opp.sell_route.with_slippage(100);
+
+        let tx = TransactionBuilder::new(ChainId::Arbitrum, Address::ZERO)
+            .build_arbitrage_tx(&opp, Address::ZERO, 0, &gas(0.01, 0.0))
+            .unwrap();
+
+        let swaps = calls(&tx.data);
+        let steps: Vec<_> = opp.buy_route.steps.iter().chain(&opp.sell_route.steps).collect();
+        assert_eq!(swaps.len(), steps.len());
+        for (swap, step) in swaps.iter().zip(steps) {
+            let min = U256::from_be_slice(&swap[52..84]);
+            assert_eq!(&swap[..20], step.pool.as_slice());
+            assert_eq!(min, step.amount_out * U256::from(99u64) / U256::from(100u64));
+        }
+    }
+
+    #[test]
+    fn test_native_requires_weth_route() {
+        let mut opp = weth_round_trip();
+        opp.buy_route.steps[0].token_in = Address::repeat_byte(0x11);
+
+        let builder = TransactionBuilder::new(ChainId::Arbitrum, Address::ZERO).with_native_in(true);
+        assert!(builder.build_arbitrage_tx(&opp, Address::ZERO, 0, &gas(0.01, 0.0)).is_err());
+    }
+
+    #[test]
+    fn test_flash_loan_opportunity_routes_through_flash_loan_tx() {
+        let builder = TransactionBuilder::new(ChainId::Arbitrum, Address::ZERO);
+        let mut opp = weth_round_trip();
+
+        let plain = builder.build_tx(&opp, Address::ZERO, 0, &gas(0.01, 0.0)).unwrap();
+        assert_eq!(&plain.data[..4], &[0xac, 0x96, 0x50, 0xd8]);
+
+        opp.arb_type = ArbitrageType::FlashLoan;
+        let flash = builder.build_tx(&opp, Address::ZERO, 0, &gas(0.01, 0.0)).unwrap();
+        assert_eq!(&flash.data[..4], &[0xab, 0x9c, 0x4b, 0x5d]);
+        assert_eq!(&flash.data[4..36], &opp.input_amount.to_be_bytes::<32>());
+        assert_eq!(flash.gas_limit, plain.gas_limit + 100_000);
+    }
+
+    #[test]
+    fn test_rejects_position_over_cap() {
+        let builder = TransactionBuilder::new(ChainId::Ethereum, Address::ZERO)
+            .with_risk_config(&RiskConfig {
+                max_position_usd: 10_000.0,
+                ..Default::default()
+            });
+        let route = SwapRoute {
+            steps: vec![],
+            chain: ChainId::Ethereum,
+            total_amount_in: U256::ZERO,
+            total_amount_out: U256::ZERO,
+            gas_estimate: 0,
+            price_impact_bps: 0,
+        };
+        let mut opp = defi_core::OpportunityBuilder::new()
+            .routes(route.clone(), route)
+            .build()
+            .unwrap();
+
+        opp.input_usd = 10_000.0;
+        assert!(builder.build_arbitrage_tx(&opp, Address::ZERO, 0, &gas(20.0, 1.0)).is_ok());
+
+        opp.input_usd = 50_000.0;
+        assert!(builder.build_arbitrage_tx(&opp, Address::ZERO, 0, &gas(20.0, 1.0)).is_err());
+
+        // Borrowing the input doesn't lift the cap
+        opp.arb_type = ArbitrageType::FlashLoan;
+        assert!(builder.build_tx(&opp, Address::ZERO, 0, &gas(20.0, 1.0)).is_err());
+    }
+
+    #[test]
+    fn test_ethereum_fees() {
+        let builder = TransactionBuilder::new(ChainId::Ethereum, Address::ZERO);
+
+        let fees = builder.select_fees(&gas(30.0, 2.0)).unwrap();
+        assert_eq!(fees.max_fee_per_gas, gwei_to_wei(62.0));
+        assert_eq!(fees.max_priority_fee, gwei_to_wei(2.0));
+    }
+
+    #[test]
+    fn test_ethereum_high_base_fee_is_capped() {
+        let config = ExecutionConfig {
+            max_gas_price_gwei: 150.0,
+            ..Default::default()
+        };
+        let builder = TransactionBuilder::new(ChainId::Ethereum, Address::ZERO)
+            .with_execution_config(&config);
+
+        // 2 * 90 + 3 = 183 gwei, above the 150 gwei cap
+        let fees = builder.select_fees(&gas(90.0, 3.0)).unwrap();
+        assert_eq!(fees.max_fee_per_gas, gwei_to_wei(150.0));
+        assert_eq!(fees.max_priority_fee, gwei_to_wei(3.0));
+
+        // Base fee alone over the cap can't be included
+        assert!(builder.select_fees(&gas(160.0, 2.0)).is_err());
+    }
 
-        let swaps = opp.buy_route.steps.len() + opp.sell_route.steps.len();
+    #[test]
+    fn test_arbitrum_low_fees_drop_tip() {
+        let builder = TransactionBuilder::new(ChainId::Arbitrum, Address::ZERO);
 
-        base + (swaps as u64 * per_swap)
+        let fees = builder.select_fees(&gas(0.01, 1.5)).unwrap();
+        assert_eq!(fees.max_fee_per_gas, gwei_to_wei(0.02));
+        assert_eq!(fees.max_priority_fee, U256::ZERO);
     }
 }
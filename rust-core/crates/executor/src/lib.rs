@@ -9,7 +9,11 @@
 pub mod simulator;
 pub mod builder;
 pub mod submitter;
+pub mod store;
+pub mod rpc;
 
-pub use simulator::{EvmSimulator, SimulationResult};
-pub use builder::{TransactionBuilder, BuiltTransaction};
-pub use submitter::{TransactionSubmitter, SubmitterConfig};
+pub use simulator::{BlockHeader, BlockSource, EvmSimulator, ForkBlock, SimulationPool, SimulationResult, LOCAL_FORK_BLOCK};
+pub use builder::{TransactionBuilder, BuiltTransaction, Eip1559Fees};
+pub use submitter::{InclusionStatus, RepriceConfig, TransactionSubmitter, SubmitterConfig, TxSigner};
+pub use store::{TradeReceipt, TradeRecord, TradeStatus, TradeStore};
+pub use rpc::{JsonRpcClient, RpcBlockSource};
@@ -0,0 +1,209 @@
+//! Minimal JSON-RPC access to an execution node
+
+use parking_lot::RwLock;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::simulator::{BlockHeader, BlockSource};
+
+/// JSON-RPC client for a single HTTP endpoint
+#[derive(Debug, Clone)]
+pub struct JsonRpcClient {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl JsonRpcClient {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Call `method` and return its `result`, which is `Null` for methods
+    /// that answer "not found" that way
+    pub async fn call(&self, method: &str, params: serde_json::Value) -> anyhow::Result<serde_json::Value> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let mut response: serde_json::Value = self.client
+            .post(&self.url)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        if let Some(error) = response.get("error") {
+            anyhow::bail!("{} failed: {}", method, error);
+        }
+        response
+            .get_mut("result")
+            .map(serde_json::Value::take)
+            .ok_or_else(|| anyhow::anyhow!("{} returned no result", method))
+    }
+
+    /// Number and timestamp of the latest block
+    pub async fn latest_header(&self) -> anyhow::Result<BlockHeader> {
+        let block = self.call("eth_getBlockByNumber", serde_json::json!(["latest", false])).await?;
+        Ok(BlockHeader {
+            number: quantity(&block["number"])?,
+            timestamp: quantity(&block["timestamp"])?,
+        })
+    }
+}
+
+/// Parse a hex-encoded JSON-RPC quantity
+pub(crate) fn quantity(value: &serde_json::Value) -> anyhow::Result<u64> {
+    let hex = value
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Expected a hex quantity, got {}", value))?;
+    Ok(u64::from_str_radix(hex.trim_start_matches("0x"), 16)?)
+}
+
+/// Chain head polled over JSON-RPC in the background.
+///
+/// `BlockSource` is read from synchronous simulation code, so the head is
+/// fetched ahead of time and served from memory. Polling stops when the
+/// source is dropped.
+pub struct RpcBlockSource {
+    rpc: JsonRpcClient,
+    head: Arc<RwLock<Option<BlockHeader>>>,
+    poller: JoinHandle<()>,
+}
+
+impl RpcBlockSource {
+    /// Start polling `rpc_url` every `interval`. Must be called from within
+    /// a tokio runtime.
+    pub fn spawn(rpc_url: impl Into<String>, interval: Duration) -> Self {
+        let rpc = JsonRpcClient::new(rpc_url);
+        let head = Arc::new(RwLock::new(None));
+
+        let poller = {
+            let rpc = rpc.clone();
+            let head = Arc::clone(&head);
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(interval);
+                loop {
+                    ticker.tick().await;
+                    match rpc.latest_header().await {
+                        Ok(header) => *head.write() = Some(header),
+                        Err(e) => warn!("Chain head poll of {} failed: {}", rpc.url(), e),
+                    }
+                }
+            })
+        };
+
+        Self { rpc, head, poller }
+    }
+}
+
+impl BlockSource for RpcBlockSource {
+    fn latest_block(&self) -> anyhow::Result<u64> {
+        self.latest_header().map(|header| header.number)
+    }
+
+    fn latest_header(&self) -> anyhow::Result<BlockHeader> {
+        (*self.head.read()).ok_or_else(|| anyhow::anyhow!("No block fetched from {} yet", self.rpc.url()))
+    }
+}
+
+impl Drop for RpcBlockSource {
+    fn drop(&mut self) {
+        self.poller.abort();
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Local JSON-RPC endpoint answering every call with `reply(method, params)`
+    pub(crate) async fn mock_rpc_server<F>(reply: F) -> String
+    where
+        F: Fn(&str, &serde_json::Value) -> serde_json::Value + Send + Sync + 'static,
+    {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let reply = Arc::new(reply);
+
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let reply = reply.clone();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    let body = loop {
+                        let n = socket.read(&mut buf).await.unwrap();
+                        assert!(n > 0, "client closed mid-request");
+                        request.extend_from_slice(&buf[..n]);
+                        let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") else {
+                            continue;
+                        };
+                        let headers = String::from_utf8_lossy(&request[..end]).to_ascii_lowercase();
+                        let length: usize = headers
+                            .lines()
+                            .find_map(|l| l.strip_prefix("content-length:"))
+                            .map(|v| v.trim().parse().unwrap())
+                            .unwrap_or(0);
+                        if request.len() >= end + 4 + length {
+                            break request[end + 4..end + 4 + length].to_vec();
+                        }
+                    };
+
+                    let call: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                    let result = reply(call["method"].as_str().unwrap(), &call["params"]);
+                    let body = serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": call["id"],
+                        "result": result,
+                    })
+                    .to_string();
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    socket.write_all(response.as_bytes()).await.unwrap();
+                });
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_block_source_serves_polled_head() {
+        let url = mock_rpc_server(|method, params| {
+            assert_eq!(method, "eth_getBlockByNumber");
+            assert_eq!(params[0], "latest");
+            serde_json::json!({ "number": "0x1234", "timestamp": "0x65f00000" })
+        })
+        .await;
+
+        let source = RpcBlockSource::spawn(url, Duration::from_millis(10));
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while source.latest_block().is_err() {
+            assert!(tokio::time::Instant::now() < deadline, "head never fetched");
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        assert_eq!(
+            source.latest_header().unwrap(),
+            BlockHeader { number: 0x1234, timestamp: 0x65f0_0000 }
+        );
+        assert_eq!(source.latest_block().unwrap(), 0x1234);
+    }
+}
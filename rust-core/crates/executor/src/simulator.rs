@@ -2,43 +2,266 @@
 
 use alloy_primitives::{Address, Bytes, U256};
 use revm::{
-    primitives::{ExecutionResult, Output, TransactTo, TxEnv},
-    Evm, InMemoryDB,
+    primitives::{BlockEnv, ExecutionResult, Output, ResultAndState, TransactTo, TxEnv},
+    Database, DatabaseCommit, Evm, InMemoryDB,
 };
-use std::collections::HashMap;
-use tracing::{debug, warn};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::debug;
 
-use defi_core::{ArbitrageOpportunity, ChainId, ExecutionResult as TradeResult};
+use defi_core::{opportunity_gas_units, ArbitrageOpportunity, ChainId, ExecutionConfig, GasPrice};
+
+use crate::builder::{BuiltTransaction, TransactionBuilder};
 
 /// Simulation result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SimulationResult {
     pub success: bool,
     pub gas_used: u64,
     pub output: Vec<u8>,
+    #[serde(with = "defi_core::serde_helpers::u256_decimal")]
     pub profit: U256,
     pub error: Option<String>,
 }
 
+impl SimulationResult {
+    fn failed(gas_used: u64, error: String) -> Self {
+        Self {
+            success: false,
+            gas_used,
+            output: vec![],
+            profit: U256::ZERO,
+            error: Some(error),
+        }
+    }
+}
+
+/// Block to fork simulation state from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForkBlock {
+    Latest,
+    Pending,
+    Number(u64),
+}
+
+impl From<u64> for ForkBlock {
+    fn from(block: u64) -> Self {
+        ForkBlock::Number(block)
+    }
+}
+
+/// Number and timestamp of a block, as simulations see it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub number: u64,
+    /// Seconds since the UNIX epoch
+    pub timestamp: u64,
+}
+
+impl BlockHeader {
+    /// `number` stamped with the current time, for blocks whose own
+    /// timestamp isn't known
+    pub fn at_now(number: u64) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self { number, timestamp }
+    }
+
+    fn apply(&self, env: &mut BlockEnv) {
+        env.number = U256::from(self.number);
+        env.timestamp = U256::from(self.timestamp);
+    }
+}
+
+/// Source of the chain head used to resolve `Latest` and `Pending`
+pub trait BlockSource: Send + Sync {
+    fn latest_block(&self) -> anyhow::Result<u64>;
+
+    /// Latest block with its timestamp. Sources that only track the
+    /// number report the current time.
+    fn latest_header(&self) -> anyhow::Result<BlockHeader> {
+        self.latest_block().map(BlockHeader::at_now)
+    }
+}
+
+/// Caps how many simulations run at once so bursts of opportunities
+/// don't exhaust node rate limits or CPU
+#[derive(Debug, Clone)]
+pub struct SimulationPool {
+    permits: Arc<Semaphore>,
+    max_concurrent: usize,
+}
+
+impl SimulationPool {
+    pub fn new(max_concurrent: usize) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        Self {
+            permits: Arc::new(Semaphore::new(max_concurrent)),
+            max_concurrent,
+        }
+    }
+
+    pub fn from_config(config: &ExecutionConfig) -> Self {
+        Self::new(config.max_concurrent_simulations)
+    }
+
+    pub fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+
+    /// Permits not currently held by a running simulation
+    pub fn available(&self) -> usize {
+        self.permits.available_permits()
+    }
+
+    /// Wait for a slot; the simulation holds it until the permit drops
+    pub async fn acquire(&self) -> OwnedSemaphorePermit {
+        Arc::clone(&self.permits)
+            .acquire_owned()
+            .await
+            .expect("simulation semaphore is never closed")
+    }
+
+    /// Run `simulation` once a slot is free
+    pub async fn run<F: std::future::Future>(&self, simulation: F) -> F::Output {
+        let _permit = self.acquire().await;
+        simulation.await
+    }
+}
+
+impl Default for SimulationPool {
+    fn default() -> Self {
+        Self::from_config(&ExecutionConfig::default())
+    }
+}
+
+/// Block a simulator without a block source forks from: the local state it
+/// was seeded with
+pub const LOCAL_FORK_BLOCK: u64 = 0;
+
 /// EVM simulator for local trade validation
 pub struct EvmSimulator {
     chain: ChainId,
-    fork_block: u64,
+    router: Address,
+    /// None forks from the chain head when a block source is set, and from
+    /// the fixed `LOCAL_FORK_BLOCK` otherwise
+    fork_block: Option<ForkBlock>,
+    block_source: Option<Arc<dyn BlockSource>>,
+    /// Current scan, advanced by `begin_scan`
+    scan: AtomicU64,
+    /// Head resolved during the scan it is keyed by
+    cached_head: Mutex<Option<(u64, BlockHeader)>>,
+    /// Account state each `simulate_opportunity` call starts from
+    base_state: InMemoryDB,
 }
 
 impl EvmSimulator {
     pub fn new(chain: ChainId) -> Self {
         Self {
             chain,
-            fork_block: 0,
+            router: Address::ZERO,
+            fork_block: None,
+            block_source: None,
+            scan: AtomicU64::new(0),
+            cached_head: Mutex::new(None),
+            base_state: InMemoryDB::default(),
         }
     }
 
-    pub fn with_fork_block(mut self, block: u64) -> Self {
-        self.fork_block = block;
+    pub fn chain(&self) -> ChainId {
+        self.chain
+    }
+
+    pub fn with_fork_block(mut self, block: impl Into<ForkBlock>) -> Self {
+        self.fork_block = Some(block.into());
+        self
+    }
+
+    pub fn with_block_source(mut self, source: Arc<dyn BlockSource>) -> Self {
+        self.block_source = Some(source);
+        self
+    }
+
+    /// Router that executes the multicall
+    pub fn with_router(mut self, router: Address) -> Self {
+        self.router = router;
+        self
+    }
+
+    pub fn router(&self) -> Address {
+        self.router
+    }
+
+    /// Seed the state `simulate_opportunity` runs against. Each simulation
+    /// works on its own copy, so one run never affects the next.
+    pub fn with_state(mut self, db: InMemoryDB) -> Self {
+        self.base_state = db;
         self
     }
 
+    /// Start a new scan: the next simulation re-reads the chain head, and
+    /// every simulation after it forks from that same block until the next
+    /// call. Call once per batch of opportunities.
+    pub fn begin_scan(&self) {
+        self.scan.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Resolve the configured fork block to a concrete number. Unless one
+    /// was set, that is the chain head with a block source and
+    /// `LOCAL_FORK_BLOCK` without.
+    pub fn resolve_fork_block(&self) -> anyhow::Result<u64> {
+        self.resolve_fork().map(|block| block.number)
+    }
+
+    /// The block simulations run in: the fork block's number, with the
+    /// chain head's timestamp when it was resolved from one.
+    ///
+    /// The chain head is fetched at most once per scan, so every
+    /// simulation within a scan forks from the same block.
+    pub fn resolve_fork(&self) -> anyhow::Result<BlockHeader> {
+        let fork_block = match self.fork_block {
+            Some(fork_block) => fork_block,
+            None if self.block_source.is_some() => ForkBlock::Latest,
+            None => return Ok(BlockHeader::at_now(LOCAL_FORK_BLOCK)),
+        };
+
+        match fork_block {
+            ForkBlock::Number(block) => Ok(BlockHeader::at_now(block)),
+            ForkBlock::Latest => self.chain_head(fork_block),
+            ForkBlock::Pending => {
+                let head = self.chain_head(fork_block)?;
+                Ok(BlockHeader {
+                    number: head.number + 1,
+                    timestamp: head.timestamp + self.chain.block_time_ms().div_ceil(1000),
+                })
+            }
+        }
+    }
+
+    fn chain_head(&self, fork_block: ForkBlock) -> anyhow::Result<BlockHeader> {
+        let scan = self.scan.load(Ordering::Relaxed);
+        let mut cached = self.cached_head.lock();
+
+        if let Some((cached_scan, head)) = *cached {
+            if cached_scan == scan {
+                return Ok(head);
+            }
+        }
+
+        let source = self.block_source
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No block source configured to resolve {:?}", fork_block))?;
+        let head = source.latest_header()?;
+        *cached = Some((scan, head));
+
+        Ok(head)
+    }
+
     /// Simulate a complete arbitrage opportunity
     pub fn simulate_opportunity(
         &self,
@@ -46,116 +269,115 @@ impl EvmSimulator {
         from: Address,
         value: U256,
     ) -> SimulationResult {
-        // Create in-memory database
-        let mut db = InMemoryDB::default();
+        // In production, this would fork from an actual node
+        let mut db = self.base_state.clone();
+        self.simulate_with_db(&mut db, opp, from, value)
+    }
+
+    /// Simulate an opportunity as the single multicall transaction that
+    /// would be submitted, so both legs succeed or revert together.
+    ///
+    /// Profit is the change in `from`'s balance of `opp.token_a` (native
+    /// balance when `token_a` is zero), not the detector's estimate.
+    pub fn simulate_with_db(
+        &self,
+        db: &mut InMemoryDB,
+        opp: &ArbitrageOpportunity,
+        from: Address,
+        value: U256,
+    ) -> SimulationResult {
+        let block = match self.resolve_fork() {
+            Ok(block) => block,
+            Err(e) => return SimulationResult::failed(0, format!("Fork block resolution failed: {}", e)),
+        };
+        debug!("Simulating on {} at block {}", self.chain, block.number);
 
         // Set up initial state
-        // In production, this would fork from an actual node
-        self.setup_initial_state(&mut db, from, value);
-
-        // Build and simulate each step
-        let mut total_gas = 0u64;
-
-        // Simulate buy route
-        for step in &opp.buy_route.steps {
-            match self.simulate_swap(&mut db, from, step.pool, step.amount_in) {
-                Ok(result) => {
-                    if !result.success {
-                        return SimulationResult {
-                            success: false,
-                            gas_used: total_gas,
-                            output: vec![],
-                            profit: U256::ZERO,
-                            error: Some(format!("Buy step failed: {:?}", result.error)),
-                        };
-                    }
-                    total_gas += result.gas_used;
-                }
-                Err(e) => {
-                    return SimulationResult {
-                        success: false,
-                        gas_used: total_gas,
-                        output: vec![],
-                        profit: U256::ZERO,
-                        error: Some(format!("Simulation error: {}", e)),
-                    };
-                }
-            }
+        self.setup_initial_state(db, from, value);
+
+        let gas_price = GasPrice {
+            base_fee: U256::ZERO,
+            priority_fee: U256::ZERO,
+            max_fee: U256::ZERO,
+        };
+        let tx = match TransactionBuilder::new(self.chain, self.router)
+            .build_tx(opp, from, 0, &gas_price)
+        {
+            Ok(tx) => tx,
+            Err(e) => return SimulationResult::failed(0, format!("Failed to build transaction: {}", e)),
+        };
+
+        let before = match self.balance_of(db, &block, opp.token_a, from) {
+            Ok(balance) => balance,
+            Err(e) => return SimulationResult::failed(0, format!("Simulation error: {}", e)),
+        };
+
+        let result = match self.execute(db, &block, from, &tx) {
+            Ok(result) => result,
+            Err(e) => return SimulationResult::failed(0, format!("Simulation error: {}", e)),
+        };
+        if !result.success {
+            return result;
         }
 
-        // Simulate sell route
-        for step in &opp.sell_route.steps {
-            match self.simulate_swap(&mut db, from, step.pool, step.amount_in) {
-                Ok(result) => {
-                    if !result.success {
-                        return SimulationResult {
-                            success: false,
-                            gas_used: total_gas,
-                            output: vec![],
-                            profit: U256::ZERO,
-                            error: Some(format!("Sell step failed: {:?}", result.error)),
-                        };
-                    }
-                    total_gas += result.gas_used;
-                }
-                Err(e) => {
-                    return SimulationResult {
-                        success: false,
-                        gas_used: total_gas,
-                        output: vec![],
-                        profit: U256::ZERO,
-                        error: Some(format!("Simulation error: {}", e)),
-                    };
-                }
-            }
+        let after = match self.balance_of(db, &block, opp.token_a, from) {
+            Ok(balance) => balance,
+            Err(e) => return SimulationResult::failed(result.gas_used, format!("Simulation error: {}", e)),
+        };
+
+        if after <= before {
+            return SimulationResult::failed(
+                result.gas_used,
+                format!("Net balance change not positive: {} -> {}", before, after),
+            );
         }
 
         SimulationResult {
-            success: true,
-            gas_used: total_gas,
-            output: vec![],
-            profit: opp.net_profit,
-            error: None,
+            profit: after - before,
+            ..result
         }
     }
 
     fn setup_initial_state(&self, db: &mut InMemoryDB, account: Address, balance: U256) {
-        // Set up account with balance
         // In production, this would copy state from a forked node
         debug!("Setting up simulation state for {:?}", account);
+
+        let mut info = db.basic(account).ok().flatten().unwrap_or_default();
+        info.balance = info.balance.max(balance);
+        db.insert_account_info(account, info);
     }
 
-    fn simulate_swap(
+    /// Run the built transaction and commit its state changes
+    fn execute(
         &self,
         db: &mut InMemoryDB,
+        block: &BlockHeader,
         from: Address,
-        pool: Address,
-        amount: U256,
+        built: &BuiltTransaction,
     ) -> anyhow::Result<SimulationResult> {
-        // Build swap transaction
-        let calldata = self.encode_swap_call(pool, amount)?;
-
-        // Configure transaction
         let tx = TxEnv {
             caller: from,
-            transact_to: TransactTo::Call(pool),
-            value: U256::ZERO,
-            data: calldata.clone(),
-            gas_limit: 500_000,
-            gas_price: U256::from(20_000_000_000u64), // 20 gwei
+            transact_to: TransactTo::Call(built.to),
+            value: built.value,
+            data: built.data.clone(),
+            gas_limit: built.gas_limit,
+            gas_price: U256::ZERO,
             ..Default::default()
         };
 
-        // Execute in EVM
-        let mut evm = Evm::builder()
-            .with_db(db)
-            .with_tx_env(tx)
-            .build();
-
-        let result = evm.transact()?;
+        let ResultAndState { result, state } = {
+            let mut evm = Evm::builder()
+                .with_db(&mut *db)
+                .modify_block_env(|env| block.apply(env))
+                .with_tx_env(tx)
+                .build();
+            evm.transact()?
+        };
 
-        match result.result {
+        match result {
             ExecutionResult::Success { gas_used, output, .. } => {
+                db.commit(state);
+
                 let output_bytes = match output {
                     Output::Call(bytes) => bytes.to_vec(),
                     Output::Create(bytes, _) => bytes.to_vec(),
@@ -179,41 +401,58 @@ impl EvmSimulator {
                 })
             }
             ExecutionResult::Halt { reason, gas_used } => {
-                Ok(SimulationResult {
-                    success: false,
-                    gas_used,
-                    output: vec![],
-                    profit: U256::ZERO,
-                    error: Some(format!("Execution halted: {:?}", reason)),
-                })
+                Ok(SimulationResult::failed(gas_used, format!("Execution halted: {:?}", reason)))
             }
         }
     }
 
-    fn encode_swap_call(&self, pool: Address, amount: U256) -> anyhow::Result<Bytes> {
-        // Encode swap function call
-        // In production, use alloy-sol-types for proper encoding
-
-        // Uniswap V2 swap: swap(uint256,uint256,address,bytes)
-        // Function selector: 0x022c0d9f
+    /// Balance of `token` held by `account`; `Address::ZERO` is the native token
+    fn balance_of(
+        &self,
+        db: &mut InMemoryDB,
+        block: &BlockHeader,
+        token: Address,
+        account: Address,
+    ) -> anyhow::Result<U256> {
+        if token == Address::ZERO {
+            return Ok(db.basic(account)?.map(|info| info.balance).unwrap_or_default());
+        }
 
-        let mut data = Vec::with_capacity(132);
-        data.extend_from_slice(&[0x02, 0x2c, 0x0d, 0x9f]); // selector
+        // balanceOf(address)
+        let mut data = Vec::with_capacity(36);
+        data.extend_from_slice(&[0x70, 0xa0, 0x82, 0x31]);
+        data.extend_from_slice(&[0u8; 12]);
+        data.extend_from_slice(account.as_slice());
 
-        // Simplified encoding - production would use proper ABI encoding
-        data.extend_from_slice(&[0u8; 128]);
+        let tx = TxEnv {
+            caller: account,
+            transact_to: TransactTo::Call(token),
+            data: Bytes::from(data),
+            gas_limit: 100_000,
+            gas_price: U256::ZERO,
+            ..Default::default()
+        };
 
-        Ok(Bytes::from(data))
+        let mut evm = Evm::builder()
+            .with_db(&mut *db)
+            .modify_block_env(|env| block.apply(env))
+            .with_tx_env(tx)
+            .build();
+        match evm.transact()?.result {
+            ExecutionResult::Success { output, .. } => {
+                let bytes = output.into_data();
+                if bytes.len() < 32 {
+                    anyhow::bail!("Malformed balanceOf response from {}", token);
+                }
+                Ok(U256::from_be_slice(&bytes[..32]))
+            }
+            other => anyhow::bail!("balanceOf failed on {}: {:?}", token, other),
+        }
     }
 
     /// Estimate gas for an opportunity
     pub fn estimate_gas(&self, opp: &ArbitrageOpportunity) -> u64 {
-        let base_gas = 21_000u64;
-        let swap_gas = 150_000u64;
-
-        let total_swaps = opp.buy_route.steps.len() + opp.sell_route.steps.len();
-
-        base_gas + (total_swaps as u64 * swap_gas)
+        opportunity_gas_units(opp)
     }
 
     /// Validate slippage bounds
@@ -231,17 +470,327 @@ impl EvmSimulator {
 mod tests {
     use super::*;
 
+    struct MockBlockSource {
+        head: AtomicU64,
+        calls: AtomicU64,
+    }
+
+    impl BlockSource for MockBlockSource {
+        fn latest_block(&self) -> anyhow::Result<u64> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            Ok(self.head.load(Ordering::Relaxed))
+        }
+    }
+
+    fn mock_source(head: u64) -> Arc<MockBlockSource> {
+        Arc::new(MockBlockSource {
+            head: AtomicU64::new(head),
+            calls: AtomicU64::new(0),
+        })
+    }
+
+    #[test]
+    fn test_simulation_result_round_trip() {
+        let result = SimulationResult {
+            success: true,
+            gas_used: 321_000,
+            output: vec![0xde, 0xad],
+            profit: U256::from(12_345_678_901_234_567_890u128),
+            error: None,
+        };
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["profit"], "12345678901234567890");
+        assert_eq!(serde_json::from_value::<SimulationResult>(json).unwrap(), result);
+    }
+
     #[test]
     fn test_simulator_creation() {
         let simulator = EvmSimulator::new(ChainId::Ethereum);
-        assert_eq!(simulator.fork_block, 0);
+        assert_eq!(simulator.fork_block, None);
+        assert_eq!(simulator.resolve_fork_block().unwrap(), LOCAL_FORK_BLOCK);
+    }
+
+    #[test]
+    fn test_latest_resolves_to_provider_head() {
+        let source = mock_source(19_000_000);
+        let simulator = EvmSimulator::new(ChainId::Ethereum).with_block_source(source.clone());
+
+        assert_eq!(simulator.resolve_fork_block().unwrap(), 19_000_000);
+
+        // Cached within the scan
+        source.head.store(19_000_001, Ordering::Relaxed);
+        assert_eq!(simulator.resolve_fork_block().unwrap(), 19_000_000);
+        assert_eq!(source.calls.load(Ordering::Relaxed), 1);
+
+        // and re-read once the next one begins
+        simulator.begin_scan();
+        assert_eq!(simulator.resolve_fork_block().unwrap(), 19_000_001);
+        assert_eq!(source.calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_fork_block_variants() {
+        let source = mock_source(500);
+
+        let pending = EvmSimulator::new(ChainId::Arbitrum)
+            .with_block_source(source.clone())
+            .with_fork_block(ForkBlock::Pending);
+        assert_eq!(pending.resolve_fork_block().unwrap(), 501);
+        let head = source.latest_header().unwrap();
+        assert_eq!(
+            pending.resolve_fork().unwrap().timestamp,
+            head.timestamp + ChainId::Arbitrum.block_time_ms().div_ceil(1000)
+        );
+
+        let pinned = EvmSimulator::new(ChainId::Arbitrum).with_fork_block(42);
+        assert_eq!(pinned.resolve_fork_block().unwrap(), 42);
+
+        // An explicit Latest without a source can't be resolved
+        let latest = EvmSimulator::new(ChainId::Arbitrum).with_fork_block(ForkBlock::Latest);
+        assert!(latest.resolve_fork_block().is_err());
+    }
+
+    use revm::primitives::{AccountInfo, Bytecode};
+
+    const PAYOUT_WEI: u64 = 1_000;
+
+    /// Router for one buy and one sell leg: calls each leg's pool, reverts
+    /// the whole transaction if either call fails, then pays the caller.
+    fn router_code() -> Vec<u8> {
+        // CALL(gas, pool, 0, 0, 0, 0, 0) with the pool read from calldata at `offset`
+        let leg = |offset: u8| -> Vec<u8> {
+            vec![
+                0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00,
+                0x60, offset, 0x35, 0x60, 0x60, 0x1c, // pool = calldata[offset..] >> 96
+                0x5a, 0xf1,                          // GAS CALL
+                0x15, 0x60, 0x3c, 0x57,              // ISZERO, jump to revert
+            ]
+        };
+
+        let (buy_offset, sell_offset) = leg_offsets();
+        let mut code = leg(buy_offset);
+        code.extend(leg(sell_offset));
+        code.extend([
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00,
+            0x61, (PAYOUT_WEI >> 8) as u8, PAYOUT_WEI as u8,
+            0x33, 0x5a, 0xf1, 0x50, 0x00,            // pay CALLER, STOP
+        ]);
+        assert_eq!(code.len(), 0x3c);
+        code.extend([0x5b, 0x60, 0x00, 0x60, 0x00, 0xfd]); // JUMPDEST, REVERT
+        code
+    }
+
+    /// Calldata offsets of the buy and sell pools in the multicall the
+    /// simulator builds for `two_leg_opportunity`
+    fn leg_offsets() -> (u8, u8) {
+        let (buy_pool, sell_pool) = (Address::repeat_byte(0xA1), Address::repeat_byte(0xA2));
+        let gas_price = GasPrice {
+            base_fee: U256::ZERO,
+            priority_fee: U256::ZERO,
+            max_fee: U256::ZERO,
+        };
+        let tx = TransactionBuilder::new(ChainId::Ethereum, Address::ZERO)
+            .build_tx(&two_leg_opportunity(buy_pool, sell_pool), Address::ZERO, 0, &gas_price)
+            .unwrap();
+
+        let offset = |pool: Address| {
+            let position = tx.data.windows(20).position(|window| window == pool.as_slice()).unwrap();
+            u8::try_from(position).unwrap()
+        };
+        (offset(buy_pool), offset(sell_pool))
+    }
+
+    fn install(db: &mut InMemoryDB, address: Address, balance: U256, code: Vec<u8>) {
+        let bytecode = Bytecode::new_raw(Bytes::from(code));
+        db.insert_account_info(address, AccountInfo::new(balance, 1, bytecode.hash_slow(), bytecode));
+    }
+
+    #[test]
+    fn test_fork_block_reaches_block_env() {
+        let block = BlockHeader { number: 19_000_000, timestamp: 1_710_000_000 };
+        let simulator = EvmSimulator::new(ChainId::Ethereum);
+        let mut db = InMemoryDB::default();
+
+        // "Tokens" whose balanceOf returns NUMBER and TIMESTAMP respectively
+        let number = Address::repeat_byte(0xB1);
+        let timestamp = Address::repeat_byte(0xB2);
+        install(&mut db, number, U256::ZERO, vec![0x43, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3]);
+        install(&mut db, timestamp, U256::ZERO, vec![0x42, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3]);
+
+        let account = Address::repeat_byte(0x42);
+        assert_eq!(simulator.balance_of(&mut db, &block, number, account).unwrap(), U256::from(block.number));
+        assert_eq!(simulator.balance_of(&mut db, &block, timestamp, account).unwrap(), U256::from(block.timestamp));
+    }
+
+    fn two_leg_opportunity(buy_pool: Address, sell_pool: Address) -> ArbitrageOpportunity {
+        let route = |pool: Address, token_in: u8, token_out: u8| defi_core::SwapRoute {
+            steps: vec![defi_core::SwapStep {
+                pool,
+                dex: defi_core::DexProtocol::UniswapV2,
+                token_in: Address::repeat_byte(token_in),
+                token_out: Address::repeat_byte(token_out),
+                amount_in: U256::from(1_000u64),
+                amount_out: U256::from(1_000u64),
+                fee_bps: 30,
+                min_amount_out: U256::ZERO,
+            }],
+            chain: ChainId::Ethereum,
+            total_amount_in: U256::from(1_000u64),
+            total_amount_out: U256::from(1_000u64),
+            gas_estimate: 0,
+            price_impact_bps: 0,
+        };
+
+        let mut opp = defi_core::OpportunityBuilder::new()
+            .routes(route(buy_pool, 1, 2), route(sell_pool, 2, 1))
+            .build()
+            .unwrap();
+        // Detector estimate that the simulator must not echo back
+        opp.net_profit = U256::from(999_999u64);
+        opp
+    }
+
+    fn setup(sell_reverts: bool) -> (EvmSimulator, InMemoryDB, ArbitrageOpportunity) {
+        let router = Address::repeat_byte(0xEE);
+        let (buy_pool, sell_pool) = (Address::repeat_byte(0xA1), Address::repeat_byte(0xA2));
+
+        let mut db = InMemoryDB::default();
+        install(&mut db, router, U256::from(1_000_000u64), router_code());
+        install(&mut db, buy_pool, U256::ZERO, vec![0x00]);
+        let sell_code = if sell_reverts { vec![0x60, 0x00, 0x60, 0x00, 0xfd] } else { vec![0x00] };
+        install(&mut db, sell_pool, U256::ZERO, sell_code);
+
+        let simulator = EvmSimulator::new(ChainId::Ethereum).with_fork_block(1).with_router(router);
+        (simulator, db, two_leg_opportunity(buy_pool, sell_pool))
+    }
+
+    #[test]
+    fn test_profit_comes_from_balance_diff() {
+        let (simulator, mut db, opp) = setup(false);
+        let from = Address::repeat_byte(0x42);
+
+        let result = simulator.simulate_with_db(&mut db, &opp, from, U256::ZERO);
+
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.profit, U256::from(PAYOUT_WEI));
+        assert!(result.gas_used > 0);
+    }
+
+    #[test]
+    fn test_reverting_leg_fails_whole_simulation() {
+        let (simulator, mut db, opp) = setup(true);
+        let from = Address::repeat_byte(0x42);
+
+        let result = simulator.simulate_with_db(&mut db, &opp, from, U256::ZERO);
+
+        assert!(!result.success);
+        assert_eq!(result.profit, U256::ZERO);
+        assert_eq!(result.error.as_deref(), Some("Transaction reverted"));
+        // The buy leg's effects were rolled back with the rest
+        assert_eq!(db.basic(from).unwrap().unwrap_or_default().balance, U256::ZERO);
+    }
+
+    #[test]
+    fn test_seeded_state_is_copied_per_simulation() {
+        let (simulator, db, opp) = setup(false);
+        let simulator = simulator.with_state(db);
+        let from = Address::repeat_byte(0x42);
+
+        // The first payout doesn't carry over into the second run
+        for _ in 0..2 {
+            let result = simulator.simulate_opportunity(&opp, from, U256::ZERO);
+            assert!(result.success, "{:?}", result.error);
+            assert_eq!(result.profit, U256::from(PAYOUT_WEI));
+        }
+        assert!(!simulator.base_state.accounts.contains_key(&from));
+    }
+
+    #[test]
+    fn test_default_simulator_simulates_local_state() {
+        let (buy_pool, sell_pool) = (Address::repeat_byte(0xA1), Address::repeat_byte(0xA2));
+        let mut db = InMemoryDB::default();
+        // The default router is the zero address
+        install(&mut db, Address::ZERO, U256::from(1_000_000u64), router_code());
+        install(&mut db, buy_pool, U256::ZERO, vec![0x00]);
+        install(&mut db, sell_pool, U256::ZERO, vec![0x00]);
+
+        let simulator = EvmSimulator::new(ChainId::Ethereum);
+        let opp = two_leg_opportunity(buy_pool, sell_pool);
+        let result = simulator.simulate_with_db(&mut db, &opp, Address::repeat_byte(0x42), U256::ZERO);
+
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.profit, U256::from(PAYOUT_WEI));
+    }
+
+    #[test]
+    fn test_unprofitable_transaction_fails() {
+        let router = Address::repeat_byte(0xEE);
+        let mut db = InMemoryDB::default();
+        // Router that accepts the call and pays nothing
+        install(&mut db, router, U256::ZERO, vec![0x00]);
+
+        let simulator = EvmSimulator::new(ChainId::Ethereum).with_fork_block(1).with_router(router);
+        let opp = two_leg_opportunity(Address::repeat_byte(0xA1), Address::repeat_byte(0xA2));
+        let result = simulator.simulate_with_db(&mut db, &opp, Address::repeat_byte(0x42), U256::ZERO);
+
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("not positive"));
+    }
+
+    #[tokio::test]
+    async fn test_simulation_pool_serializes_excess_requests() {
+        use std::sync::atomic::AtomicUsize;
+
+        let pool = SimulationPool::new(2);
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let pool = pool.clone();
+                let running = Arc::clone(&running);
+                let peak = Arc::clone(&peak);
+                tokio::spawn(async move {
+                    pool.run(async {
+                        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now, Ordering::SeqCst);
+                        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                        running.fetch_sub(1, Ordering::SeqCst);
+                    })
+                    .await
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(pool.available(), 2);
+    }
+
+    #[test]
+    fn test_simulation_pool_has_at_least_one_permit() {
+        assert_eq!(SimulationPool::new(0).max_concurrent(), 1);
+        assert_eq!(SimulationPool::default().max_concurrent(), 8);
     }
 
     #[test]
     fn test_gas_estimation() {
+        use defi_core::{gas_units, DexProtocol, HOP_TRANSFER_GAS, TX_BASE_GAS};
+
         let simulator = EvmSimulator::new(ChainId::Ethereum);
 
-        // Create mock opportunity with 2 swaps (1 buy, 1 sell)
-        // Gas should be: 21000 + 2 * 150000 = 321000
+        // Two V2 swaps (1 buy, 1 sell) priced from the table
+        let mut opp = two_leg_opportunity(Address::repeat_byte(0xA1), Address::repeat_byte(0xA2));
+        let v2 = gas_units(DexProtocol::UniswapV2, 0);
+        assert_eq!(simulator.estimate_gas(&opp), TX_BASE_GAS + 2 * v2 + HOP_TRANSFER_GAS);
+
+        // A sell leg through V3 crossing two ticks costs more
+        opp.sell_route.steps[0].dex = DexProtocol::UniswapV3;
+        opp.sell_route.gas_estimate = gas_units(DexProtocol::UniswapV3, 2);
+        assert!(simulator.estimate_gas(&opp) > TX_BASE_GAS + 2 * v2 + HOP_TRANSFER_GAS);
     }
 }
@@ -0,0 +1,438 @@
+//! In-memory trade store backing trade status lookups
+
+use alloy_primitives::{Address, I256, U256};
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use std::future::Future;
+use std::time::Duration;
+use tracing::{debug, info};
+
+use defi_core::ChainId;
+
+/// Trade lifecycle status
+///
+/// Valid transitions: `Pending -> Submitted -> Confirmed | Failed | Reverted`,
+/// and `Pending -> Failed` when submission itself fails. Dry runs are
+/// stored as `Simulated` and never move on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeStatus {
+    Pending,
+    Submitted,
+    Confirmed,
+    Failed,
+    Reverted,
+    /// Simulated and built in dry-run mode, never broadcast
+    Simulated,
+}
+
+impl TradeStatus {
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            TradeStatus::Confirmed | TradeStatus::Failed | TradeStatus::Reverted | TradeStatus::Simulated
+        )
+    }
+
+    pub fn can_transition_to(&self, next: TradeStatus) -> bool {
+        matches!(
+            (self, next),
+            (TradeStatus::Pending, TradeStatus::Submitted)
+                | (TradeStatus::Pending, TradeStatus::Failed)
+                | (TradeStatus::Submitted, TradeStatus::Confirmed)
+                | (TradeStatus::Submitted, TradeStatus::Failed)
+                | (TradeStatus::Submitted, TradeStatus::Reverted)
+        )
+    }
+}
+
+/// Stored state of a single trade
+#[derive(Debug, Clone)]
+pub struct TradeRecord {
+    pub trade_id: String,
+    pub chain: ChainId,
+    pub delegation_id: String,
+    pub opportunity_id: Option<String>,
+    /// Client-supplied key; retries carrying it get this trade back
+    pub idempotency_key: Option<String>,
+    /// Token the trade starts and ends in, whose balance change is the profit
+    pub profit_token: Option<Address>,
+    pub status: TradeStatus,
+    pub tx_hash: Option<String>,
+    pub block_number: Option<u64>,
+    pub gas_used: Option<u64>,
+    pub actual_output: Option<U256>,
+    pub actual_profit_usd: f64,
+    pub error: Option<String>,
+    /// Run in dry-run mode: `tx_hash` was never broadcast and the trade
+    /// is `Simulated`
+    pub dry_run: bool,
+    /// Pre-flight simulation's profit, in the profit token
+    pub simulated_profit: Option<U256>,
+    pub created_at_ms: u64,
+    pub updated_at_ms: u64,
+}
+
+impl TradeRecord {
+    pub fn new(trade_id: impl Into<String>, chain: ChainId, delegation_id: impl Into<String>) -> Self {
+        let now = now_ms();
+        Self {
+            trade_id: trade_id.into(),
+            chain,
+            delegation_id: delegation_id.into(),
+            opportunity_id: None,
+            idempotency_key: None,
+            profit_token: None,
+            status: TradeStatus::Pending,
+            tx_hash: None,
+            block_number: None,
+            gas_used: None,
+            actual_output: None,
+            actual_profit_usd: 0.0,
+            error: None,
+            dry_run: false,
+            simulated_profit: None,
+            created_at_ms: now,
+            updated_at_ms: now,
+        }
+    }
+}
+
+/// On-chain outcome of a submitted transaction
+#[derive(Debug, Clone)]
+pub struct TradeReceipt {
+    pub success: bool,
+    pub block_number: u64,
+    pub gas_used: u64,
+    /// Wei paid per unit of gas
+    pub effective_gas_price: U256,
+    pub actual_output: Option<U256>,
+    /// Signed change in the account's balance of the trade's profit token
+    pub balance_delta: I256,
+}
+
+/// How often `drain` rechecks for unsettled trades
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+/// Concurrent in-memory trade store keyed by trade ID
+#[derive(Debug, Default)]
+pub struct TradeStore {
+    trades: DashMap<String, TradeRecord>,
+    /// (delegation_id, idempotency_key) -> trade_id
+    idempotency_keys: DashMap<(String, String), String>,
+}
+
+impl TradeStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&self, record: TradeRecord) {
+        self.trades.insert(record.trade_id.clone(), record);
+    }
+
+    /// Insert `record` unless its delegation already has a trade under the
+    /// same idempotency key, in which case that trade is returned and
+    /// nothing is stored. Records without a key are always inserted.
+    pub fn insert_idempotent(&self, record: TradeRecord) -> Result<(), Box<TradeRecord>> {
+        let Some(key) = record.idempotency_key.clone() else {
+            self.insert(record);
+            return Ok(());
+        };
+
+        match self.idempotency_keys.entry((record.delegation_id.clone(), key)) {
+            Entry::Occupied(mut existing) => match self.get(existing.get()) {
+                Some(trade) => Err(Box::new(trade)),
+                None => {
+                    // The indexed trade is gone; let this one take the key
+                    existing.insert(record.trade_id.clone());
+                    self.insert(record);
+                    Ok(())
+                }
+            },
+            Entry::Vacant(slot) => {
+                slot.insert(record.trade_id.clone());
+                self.insert(record);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn get(&self, trade_id: &str) -> Option<TradeRecord> {
+        self.trades.get(trade_id).map(|r| r.value().clone())
+    }
+
+    /// Trade a delegation stored under `idempotency_key`
+    pub fn get_by_idempotency_key(&self, delegation_id: &str, idempotency_key: &str) -> Option<TradeRecord> {
+        let trade_id = self.idempotency_keys
+            .get(&(delegation_id.to_string(), idempotency_key.to_string()))?
+            .clone();
+        self.get(&trade_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.trades.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trades.is_empty()
+    }
+
+    /// Trades that haven't reached a terminal state
+    pub fn in_flight(&self) -> usize {
+        self.trades.iter().filter(|r| !r.status.is_terminal()).count()
+    }
+
+    /// Wait until every trade is terminal or `timeout` passes.
+    /// Returns the number of trades still in flight.
+    pub async fn drain(&self, timeout: Duration) -> usize {
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        loop {
+            let in_flight = self.in_flight();
+            let now = tokio::time::Instant::now();
+            if in_flight == 0 || now >= deadline {
+                return in_flight;
+            }
+            tokio::time::sleep(DRAIN_POLL_INTERVAL.min(deadline - now)).await;
+        }
+    }
+
+    /// Apply a status change if the transition is valid.
+    /// Returns `false` for unknown trades or disallowed transitions.
+    fn transition(&self, trade_id: &str, next: TradeStatus, update: impl FnOnce(&mut TradeRecord)) -> bool {
+        let Some(mut record) = self.trades.get_mut(trade_id) else {
+            return false;
+        };
+
+        if !record.status.can_transition_to(next) {
+            debug!("Ignoring {:?} -> {:?} for trade {}", record.status, next, trade_id);
+            return false;
+        }
+
+        record.status = next;
+        update(&mut record);
+        record.updated_at_ms = now_ms();
+        true
+    }
+
+    pub fn mark_submitted(&self, trade_id: &str, tx_hash: impl Into<String>) -> bool {
+        let tx_hash = tx_hash.into();
+        self.transition(trade_id, TradeStatus::Submitted, |r| r.tx_hash = Some(tx_hash))
+    }
+
+    pub fn mark_failed(&self, trade_id: &str, error: impl Into<String>) -> bool {
+        let error = error.into();
+        self.transition(trade_id, TradeStatus::Failed, |r| r.error = Some(error))
+    }
+
+    /// Record a mined receipt and the trade's realized profit, moving the
+    /// trade to Confirmed or Reverted
+    pub fn apply_receipt(&self, trade_id: &str, receipt: TradeReceipt, profit_usd: f64) -> bool {
+        let next = if receipt.success {
+            TradeStatus::Confirmed
+        } else {
+            TradeStatus::Reverted
+        };
+
+        self.transition(trade_id, next, |r| {
+            r.block_number = Some(receipt.block_number);
+            r.gas_used = Some(receipt.gas_used);
+            r.actual_output = receipt.actual_output;
+            r.actual_profit_usd = profit_usd;
+        })
+    }
+
+    /// Submitted trades awaiting a receipt, as (trade_id, tx_hash)
+    pub fn awaiting_receipt(&self) -> Vec<(String, String)> {
+        self.trades
+            .iter()
+            .filter(|r| r.status == TradeStatus::Submitted)
+            .filter_map(|r| Some((r.trade_id.clone(), r.tx_hash.clone()?)))
+            .collect()
+    }
+
+    /// Single confirmation pass: look up receipts for submitted trades and
+    /// value each one's balance change with `profit_usd`.
+    /// Returns the trades that reached a terminal state.
+    pub async fn poll_confirmations<F, Fut, V>(&self, mut fetch_receipt: F, profit_usd: V) -> Vec<TradeRecord>
+    where
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = Option<TradeReceipt>>,
+        V: Fn(&TradeRecord, &TradeReceipt) -> f64,
+    {
+        let mut settled = Vec::new();
+
+        for (trade_id, tx_hash) in self.awaiting_receipt() {
+            let Some(receipt) = fetch_receipt(tx_hash).await else {
+                continue;
+            };
+            let Some(record) = self.get(&trade_id) else {
+                continue;
+            };
+
+            let profit = profit_usd(&record, &receipt);
+            if self.apply_receipt(&trade_id, receipt, profit) {
+                info!("Trade {} settled with {:.2} USD profit", trade_id, profit);
+                settled.extend(self.get(&trade_id));
+            }
+        }
+
+        settled
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_transitions() {
+        let store = TradeStore::new();
+        store.insert(TradeRecord::new("t1", ChainId::Ethereum, "d1"));
+        assert_eq!(store.get("t1").unwrap().status, TradeStatus::Pending);
+
+        // Can't confirm before submission
+        assert!(!store.apply_receipt("t1", TradeReceipt {
+            success: true,
+            block_number: 1,
+            gas_used: 1,
+            effective_gas_price: U256::ZERO,
+            actual_output: None,
+            balance_delta: I256::ZERO,
+        }, 0.0));
+
+        assert!(store.mark_submitted("t1", "0xabc"));
+        let record = store.get("t1").unwrap();
+        assert_eq!(record.status, TradeStatus::Submitted);
+        assert_eq!(record.tx_hash.as_deref(), Some("0xabc"));
+
+        assert!(store.apply_receipt("t1", TradeReceipt {
+            success: true,
+            block_number: 19_000_000,
+            gas_used: 210_000,
+            effective_gas_price: U256::from(20_000_000_000u64),
+            actual_output: Some(U256::from(42u64)),
+            balance_delta: I256::try_from(42i64).unwrap(),
+        }, 12.5));
+        let record = store.get("t1").unwrap();
+        assert_eq!(record.status, TradeStatus::Confirmed);
+        assert_eq!(record.block_number, Some(19_000_000));
+        assert_eq!(record.actual_output, Some(U256::from(42u64)));
+        assert_eq!(record.actual_profit_usd, 12.5);
+
+        // Terminal states don't move
+        assert!(!store.mark_failed("t1", "late failure"));
+        assert!(record.status.is_terminal());
+    }
+
+    #[test]
+    fn test_failed_before_submission() {
+        let store = TradeStore::new();
+        store.insert(TradeRecord::new("t2", ChainId::Arbitrum, "d1"));
+
+        assert!(store.mark_failed("t2", "simulation reverted"));
+        let record = store.get("t2").unwrap();
+        assert_eq!(record.status, TradeStatus::Failed);
+        assert_eq!(record.error.as_deref(), Some("simulation reverted"));
+        assert!(!store.mark_failed("missing", "x"));
+    }
+
+    #[test]
+    fn test_idempotency_key_keeps_first_trade() {
+        let store = TradeStore::new();
+        let keyed = |trade_id: &str, delegation_id: &str| {
+            let mut record = TradeRecord::new(trade_id, ChainId::Base, delegation_id);
+            record.idempotency_key = Some("retry-1".to_string());
+            record
+        };
+
+        assert!(store.insert_idempotent(keyed("t1", "d1")).is_ok());
+        let existing = store.insert_idempotent(keyed("t2", "d1")).unwrap_err();
+        assert_eq!(existing.trade_id, "t1");
+        assert!(store.get("t2").is_none());
+
+        // Keys are scoped to the delegation
+        assert!(store.insert_idempotent(keyed("t3", "d2")).is_ok());
+        assert_eq!(store.get_by_idempotency_key("d2", "retry-1").unwrap().trade_id, "t3");
+
+        // Unkeyed records are never deduplicated
+        assert!(store.insert_idempotent(TradeRecord::new("t4", ChainId::Base, "d1")).is_ok());
+        assert!(store.insert_idempotent(TradeRecord::new("t5", ChainId::Base, "d1")).is_ok());
+        assert_eq!(store.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_drain_counts_unsettled_trades() {
+        let store = TradeStore::new();
+        assert_eq!(store.drain(Duration::from_secs(5)).await, 0);
+
+        store.insert(TradeRecord::new("done", ChainId::Ethereum, "d1"));
+        store.insert(TradeRecord::new("stuck", ChainId::Ethereum, "d1"));
+        store.mark_failed("done", "reverted in simulation");
+        store.mark_submitted("stuck", "0x01");
+
+        assert_eq!(store.in_flight(), 1);
+        assert_eq!(store.drain(Duration::from_millis(50)).await, 1);
+
+        // Dry runs never settle, so they don't hold up a drain
+        let mut dry_run = TradeRecord::new("dry", ChainId::Ethereum, "d1");
+        dry_run.dry_run = true;
+        dry_run.status = TradeStatus::Simulated;
+        store.insert(dry_run);
+        assert_eq!(store.in_flight(), 1);
+        assert!(!TradeStatus::Simulated.can_transition_to(TradeStatus::Submitted));
+    }
+
+    #[tokio::test]
+    async fn test_poll_confirmations() {
+        let store = TradeStore::new();
+        store.insert(TradeRecord::new("ok", ChainId::Ethereum, "d1"));
+        store.insert(TradeRecord::new("bad", ChainId::Ethereum, "d1"));
+        store.insert(TradeRecord::new("waiting", ChainId::Ethereum, "d1"));
+        store.mark_submitted("ok", "0x01");
+        store.mark_submitted("bad", "0x02");
+        store.mark_submitted("waiting", "0x03");
+
+        let settled = store
+            .poll_confirmations(
+                |hash| async move {
+                    match hash.as_str() {
+                        "0x01" | "0x02" => Some(TradeReceipt {
+                            success: hash == "0x01",
+                            block_number: 10,
+                            gas_used: 100_000,
+                            effective_gas_price: U256::ZERO,
+                            actual_output: None,
+                            balance_delta: if hash == "0x01" {
+                                I256::try_from(3_000i64).unwrap()
+                            } else {
+                                I256::try_from(-500i64).unwrap()
+                            },
+                        }),
+                        _ => None,
+                    }
+                },
+                // One USD per thousand units
+                |_, receipt| receipt.balance_delta.to_string().parse::<f64>().unwrap() / 1_000.0,
+            )
+            .await;
+
+        assert_eq!(settled.len(), 2);
+        assert_eq!(store.get("ok").unwrap().status, TradeStatus::Confirmed);
+        assert_eq!(store.get("bad").unwrap().status, TradeStatus::Reverted);
+        assert_eq!(store.get("waiting").unwrap().status, TradeStatus::Submitted);
+
+        // Losing trades book negative profit
+        assert_eq!(store.get("ok").unwrap().actual_profit_usd, 3.0);
+        assert_eq!(store.get("bad").unwrap().actual_profit_usd, -0.5);
+        let total: f64 = settled.iter().map(|r| r.actual_profit_usd).sum();
+        assert_eq!(total, 2.5);
+    }
+}
@@ -1,11 +1,17 @@
 //! Transaction submission with Flashbots support
 
-use alloy_primitives::{Address, Bytes, U256};
-use std::time::Duration;
+use alloy_primitives::{b256, keccak256, Address, Bytes, B256, I256, U256};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 
-use defi_core::{ChainId, ExecutionResult};
-use crate::builder::BuiltTransaction;
+use defi_core::types::{ExecutionResult, GasPrice};
+use defi_core::{ChainId, ExecutionConfig, ExecutionError, TX_BASE_GAS};
+use crate::builder::{BuiltTransaction, Eip1559Fees};
+use crate::rpc::{quantity, JsonRpcClient};
+use crate::simulator::BlockSource;
+use crate::store::TradeReceipt;
 
 /// Submission configuration
 #[derive(Debug, Clone)]
@@ -14,8 +20,63 @@ pub struct SubmitterConfig {
     pub rpc_url: String,
     pub flashbots_relay: Option<String>,
     pub use_flashbots: bool,
+    /// Account transactions are sent from
+    pub from: Address,
     pub max_retries: u32,
+    /// Wait before the first retry; doubles on each further attempt
     pub retry_delay: Duration,
+    /// Multiplier applied to both fee fields on every retry
+    pub fee_bump_factor: f64,
+    /// Escalate fees on public-mempool transactions that sit unincluded.
+    /// None submits once and leaves the transaction to the mempool.
+    pub reprice: Option<RepriceConfig>,
+    /// Go through every step up to the broadcast but never send anything,
+    /// reporting the hash the transaction would have had
+    pub dry_run: bool,
+}
+
+impl SubmitterConfig {
+    /// Apply the route, retry budget and dry-run flag from execution config
+    pub fn with_execution_config(mut self, config: &ExecutionConfig) -> Self {
+        self.use_flashbots = config.use_flashbots;
+        self.max_retries = config.max_retries;
+        self.dry_run = config.dry_run;
+        self
+    }
+}
+
+/// Fee escalation for a pending public-mempool transaction.
+///
+/// Every `blocks_per_step` blocks without inclusion, the transaction is
+/// resent at the same nonce with both fee fields at the next multiplier of
+/// `fee_schedule`, taken over the original fees. Multipliers are raised as
+/// needed to clear the nodes' replacement minimum over the previous send.
+/// Once the schedule runs out the last fee is kept until expiry.
+#[derive(Debug, Clone)]
+pub struct RepriceConfig {
+    pub blocks_per_step: u64,
+    pub fee_schedule: Vec<f64>,
+    /// How often the chain is checked for inclusion
+    pub poll_interval: Duration,
+}
+
+impl Default for RepriceConfig {
+    fn default() -> Self {
+        Self {
+            blocks_per_step: 1,
+            fee_schedule: vec![1.25, 1.5, 2.0, 3.0],
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// What the chain says about a transaction being repriced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InclusionStatus {
+    /// Current head block
+    pub head: u64,
+    /// Hash of whichever sent version was included, if any has been
+    pub included: Option<String>,
 }
 
 impl Default for SubmitterConfig {
@@ -25,28 +86,285 @@ impl Default for SubmitterConfig {
             rpc_url: String::new(),
             flashbots_relay: Some("https://relay.flashbots.net".to_string()),
             use_flashbots: true,
+            from: Address::ZERO,
             max_retries: 2,
             retry_delay: Duration::from_millis(500),
+            // Nodes require at least a 10% bump to replace a pending transaction
+            fee_bump_factor: 1.125,
+            reprice: None,
+            dry_run: false,
         }
     }
 }
 
+/// Smallest fee increase nodes accept for replacing a pending transaction
+const MIN_REPLACEMENT_BUMP: f64 = 1.125;
+
+/// Signs transactions for the account they are sent from
+pub trait TxSigner: Send + Sync {
+    fn address(&self) -> Address;
+
+    /// Raw signed transaction, ready for `eth_sendRawTransaction`
+    fn sign(&self, tx: &BuiltTransaction) -> anyhow::Result<Bytes>;
+}
+
 /// Transaction submitter
 pub struct TransactionSubmitter {
     config: SubmitterConfig,
+    /// Chain RPC at `config.rpc_url`, if one is set
+    rpc: Option<JsonRpcClient>,
     pending_nonce: u64,
+    /// Chain head repricing counts blocks against. None estimates it from
+    /// the chain's block time.
+    block_source: Option<Arc<dyn BlockSource>>,
 }
 
 impl TransactionSubmitter {
     pub fn new(config: SubmitterConfig) -> Self {
+        let rpc = (!config.rpc_url.is_empty()).then(|| JsonRpcClient::new(config.rpc_url.clone()));
         Self {
             config,
+            rpc,
             pending_nonce: 0,
+            block_source: None,
         }
     }
 
-    /// Submit a transaction
-    pub async fn submit(&mut self, tx: BuiltTransaction) -> anyhow::Result<ExecutionResult> {
+    pub fn with_block_source(mut self, source: Arc<dyn BlockSource>) -> Self {
+        self.block_source = Some(source);
+        self
+    }
+
+    /// Account transactions are sent from
+    pub fn sender(&self) -> Address {
+        self.config.from
+    }
+
+    /// Whether submissions stop short of broadcasting
+    pub fn is_dry_run(&self) -> bool {
+        self.config.dry_run
+    }
+
+    /// Submit a transaction, retrying transient failures
+    pub async fn submit(&self, tx: BuiltTransaction) -> anyhow::Result<ExecutionResult> {
+        self.submit_with(tx, |tx| self.send_once(tx)).await
+    }
+
+    /// Submission loop over an arbitrary send function.
+    ///
+    /// Each attempt re-fetches the nonce. Retryable errors are resubmitted
+    /// up to `max_retries` times with both fee fields multiplied by
+    /// `fee_bump_factor` and an exponentially growing delay; anything else
+    /// fails immediately. Latency covers every attempt. In dry-run mode
+    /// `send` is never called.
+    pub async fn submit_with<F, Fut>(&self, mut tx: BuiltTransaction, mut send: F) -> anyhow::Result<ExecutionResult>
+    where
+        F: FnMut(BuiltTransaction) -> Fut,
+        Fut: Future<Output = Result<String, ExecutionError>>,
+    {
+        if self.config.dry_run {
+            return self.dry_run(tx).await;
+        }
+
+        let start = Instant::now();
+        let mut attempt = 0;
+
+        loop {
+            tx.nonce = Some(self.get_nonce(self.config.from).await?);
+
+            let error = match send(tx.clone()).await {
+                Ok(tx_hash) => {
+                    return Ok(ExecutionResult {
+                        success: true,
+                        tx_hash: Some(tx_hash),
+                        gas_used: Some(tx.gas_limit),
+                        profit_wei: None,
+                        error: None,
+                        latency_us: start.elapsed().as_micros() as u64,
+                    });
+                }
+                Err(e) => e,
+            };
+
+            if !error.is_retryable() || attempt >= self.config.max_retries {
+                error!("Submission failed after {} attempts: {}", attempt + 1, error);
+                return Ok(ExecutionResult {
+                    success: false,
+                    tx_hash: None,
+                    gas_used: None,
+                    profit_wei: None,
+                    error: Some(error.to_string()),
+                    latency_us: start.elapsed().as_micros() as u64,
+                });
+            }
+
+            let delay = self.config.retry_delay.saturating_mul(2u32.saturating_pow(attempt));
+            attempt += 1;
+            tx.max_fee_per_gas = bump_fee(tx.max_fee_per_gas, self.config.fee_bump_factor);
+            tx.max_priority_fee = bump_fee(tx.max_priority_fee, self.config.fee_bump_factor);
+            warn!(
+                "Submission attempt {} failed ({}), retrying in {:?} at priority fee {}",
+                attempt, error, delay, tx.max_priority_fee
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Submit a transaction for an opportunity expiring at `expires_at_ms`.
+    /// Public-mempool submissions are repriced until included when
+    /// `reprice` is configured; everything else is a plain `submit`.
+    pub async fn submit_until(&self, tx: BuiltTransaction, expires_at_ms: u64) -> anyhow::Result<ExecutionResult> {
+        let public = !(self.config.use_flashbots && self.config.flashbots_relay.is_some());
+        let Some(reprice) = self.config.reprice.clone().filter(|_| public) else {
+            return self.submit(tx).await;
+        };
+
+        self.reprice_with(
+            tx,
+            expires_at_ms,
+            &reprice,
+            |tx| self.submit_public(tx),
+            |hashes| self.inclusion_status(hashes),
+        )
+        .await
+    }
+
+    /// Repricing loop over arbitrary send and watch functions.
+    ///
+    /// The nonce is fetched once and every resend reuses it. `watch` gets
+    /// every hash sent so far, since a replaced version can still be the
+    /// one that lands. Gives up with `NotMined` once `expires_at_ms`
+    /// passes, and fails immediately if a send is rejected. Latency covers
+    /// the whole wait. In dry-run mode nothing is sent or watched.
+    pub async fn reprice_with<F, FutS, W, FutW>(
+        &self,
+        mut tx: BuiltTransaction,
+        expires_at_ms: u64,
+        reprice: &RepriceConfig,
+        mut send: F,
+        mut watch: W,
+    ) -> anyhow::Result<ExecutionResult>
+    where
+        F: FnMut(BuiltTransaction) -> FutS,
+        FutS: Future<Output = Result<String, ExecutionError>>,
+        W: FnMut(Vec<String>) -> FutW,
+        FutW: Future<Output = anyhow::Result<InclusionStatus>>,
+    {
+        if self.config.dry_run {
+            return self.dry_run(tx).await;
+        }
+
+        let start = Instant::now();
+        let original = Eip1559Fees {
+            max_fee_per_gas: tx.max_fee_per_gas,
+            max_priority_fee: tx.max_priority_fee,
+        };
+        tx.nonce = Some(self.get_nonce(self.config.from).await?);
+
+        let mut hashes = Vec::new();
+        let mut step = 0;
+        let mut step_started_at: Option<u64> = None;
+        let failed = |error: ExecutionError| ExecutionResult {
+            success: false,
+            tx_hash: None,
+            gas_used: None,
+            profit_wei: None,
+            error: Some(error.to_string()),
+            latency_us: start.elapsed().as_micros() as u64,
+        };
+
+        loop {
+            match send(tx.clone()).await {
+                Ok(tx_hash) => hashes.push(tx_hash),
+                Err(e) => {
+                    error!("Send at nonce {:?} failed: {}", tx.nonce, e);
+                    return Ok(failed(e));
+                }
+            }
+
+            // Wait for inclusion or the end of this step
+            loop {
+                tokio::time::sleep(reprice.poll_interval).await;
+                if now_ms() >= expires_at_ms {
+                    warn!("Opportunity expired after {} sends at nonce {:?}", hashes.len(), tx.nonce);
+                    return Ok(failed(ExecutionError::NotMined));
+                }
+
+                let status = watch(hashes.clone()).await?;
+                if let Some(tx_hash) = status.included {
+                    return Ok(ExecutionResult {
+                        success: true,
+                        tx_hash: Some(tx_hash),
+                        gas_used: Some(tx.gas_limit),
+                        profit_wei: None,
+                        error: None,
+                        latency_us: start.elapsed().as_micros() as u64,
+                    });
+                }
+
+                let started = *step_started_at.get_or_insert(status.head);
+                if step < reprice.fee_schedule.len() && status.head >= started + reprice.blocks_per_step {
+                    step_started_at = Some(status.head);
+                    break;
+                }
+            }
+
+            let factor = reprice.fee_schedule[step];
+            step += 1;
+            tx.max_fee_per_gas = bump_fee(original.max_fee_per_gas, factor)
+                .max(bump_fee(tx.max_fee_per_gas, MIN_REPLACEMENT_BUMP));
+            tx.max_priority_fee = bump_fee(original.max_priority_fee, factor)
+                .max(bump_fee(tx.max_priority_fee, MIN_REPLACEMENT_BUMP));
+            info!(
+                "Not included, resending nonce {:?} at priority fee {}",
+                tx.nonce, tx.max_priority_fee
+            );
+        }
+    }
+
+    /// Everything a submission does short of sending: fetch the nonce and
+    /// report the transaction as sent under the hash it would have had
+    pub async fn dry_run(&self, mut tx: BuiltTransaction) -> anyhow::Result<ExecutionResult> {
+        let start = Instant::now();
+        tx.nonce = Some(self.get_nonce(self.config.from).await?);
+
+        let tx_hash = dry_run_hash(&tx);
+        info!("Dry run: not broadcasting {} at nonce {:?}", tx_hash, tx.nonce);
+
+        Ok(ExecutionResult {
+            success: true,
+            tx_hash: Some(tx_hash),
+            gas_used: Some(tx.gas_limit),
+            profit_wei: None,
+            error: None,
+            latency_us: start.elapsed().as_micros() as u64,
+        })
+    }
+
+    /// Chain head and whether any of `tx_hashes` has been included
+    async fn inclusion_status(&self, tx_hashes: Vec<String>) -> anyhow::Result<InclusionStatus> {
+        let head = self.head_block()?;
+
+        for tx_hash in tx_hashes {
+            if self.fetch_receipt(&tx_hash).await?.is_some() {
+                return Ok(InclusionStatus { head, included: Some(tx_hash) });
+            }
+        }
+        Ok(InclusionStatus { head, included: None })
+    }
+
+    /// Latest block from the block source, or the number of block times
+    /// elapsed since the epoch without one. The estimate is only good for
+    /// counting blocks, which is all repricing needs.
+    fn head_block(&self) -> anyhow::Result<u64> {
+        match self.block_source {
+            Some(ref source) => source.latest_block(),
+            None => Ok(now_ms() / self.config.chain.block_time_ms().max(1)),
+        }
+    }
+
+    /// One submission attempt over the configured route
+    async fn send_once(&self, tx: BuiltTransaction) -> Result<String, ExecutionError> {
         if self.config.use_flashbots && self.config.flashbots_relay.is_some() {
             self.submit_flashbots(tx).await
         } else {
@@ -55,58 +373,81 @@ impl TransactionSubmitter {
     }
 
     /// Submit via Flashbots relay
-    async fn submit_flashbots(&self, tx: BuiltTransaction) -> anyhow::Result<ExecutionResult> {
+    async fn submit_flashbots(&self, tx: BuiltTransaction) -> Result<String, ExecutionError> {
         let relay = self.config.flashbots_relay.as_ref()
-            .ok_or_else(|| anyhow::anyhow!("Flashbots relay not configured"))?;
+            .ok_or_else(|| ExecutionError::RelayRejected("Flashbots relay not configured".to_string()))?;
 
         info!("Submitting to Flashbots relay: {}", relay);
 
         // Build bundle
-        let bundle = self.build_flashbots_bundle(&tx)?;
+        let bundle = self.build_flashbots_bundle(&tx);
+        debug!("Bundle of {} transactions at nonce {:?}", bundle.transactions.len(), tx.nonce);
 
         // In production:
         // 1. Sign the bundle with Flashbots auth key
         // 2. Send to relay via eth_sendBundle
         // 3. Monitor for inclusion
 
-        // Placeholder result
-        Ok(ExecutionResult {
-            success: true,
-            tx_hash: Some("0x...".to_string()),
-            gas_used: Some(tx.gas_limit),
-            profit_wei: None,
-            error: None,
-            latency_us: 0,
-        })
+        // Placeholder hash
+        Ok("0x...".to_string())
     }
 
     /// Submit to public mempool
-    async fn submit_public(&self, tx: BuiltTransaction) -> anyhow::Result<ExecutionResult> {
+    async fn submit_public(&self, tx: BuiltTransaction) -> Result<String, ExecutionError> {
         info!("Submitting to public mempool");
+        debug!("Sending {} bytes to {} at nonce {:?}", tx.data.len(), tx.to, tx.nonce);
 
         // In production:
         // 1. Sign the transaction
         // 2. Send via eth_sendRawTransaction
         // 3. Wait for confirmation
 
-        // Placeholder result
-        Ok(ExecutionResult {
-            success: true,
-            tx_hash: Some("0x...".to_string()),
-            gas_used: Some(tx.gas_limit),
-            profit_wei: None,
-            error: None,
-            latency_us: 0,
-        })
+        // Placeholder hash
+        Ok("0x...".to_string())
     }
 
-    fn build_flashbots_bundle(&self, tx: &BuiltTransaction) -> anyhow::Result<FlashbotsBundle> {
-        Ok(FlashbotsBundle {
+    fn build_flashbots_bundle(&self, tx: &BuiltTransaction) -> FlashbotsBundle {
+        FlashbotsBundle {
             transactions: vec![tx.data.clone()],
             block_number: 0,  // Would be current + 1
             min_timestamp: None,
             max_timestamp: None,
-        })
+        }
+    }
+
+    /// Look up the receipt for a submitted transaction with
+    /// `eth_getTransactionReceipt`. None while it is pending, and always
+    /// without an RPC configured.
+    pub async fn fetch_receipt(&self, tx_hash: &str) -> anyhow::Result<Option<TradeReceipt>> {
+        let Some(ref rpc) = self.rpc else {
+            debug!("No RPC configured to check receipt for {}", tx_hash);
+            return Ok(None);
+        };
+
+        let receipt = rpc.call("eth_getTransactionReceipt", serde_json::json!([tx_hash])).await?;
+        if receipt.is_null() {
+            return Ok(None);
+        }
+        decode_receipt(&receipt, self.config.from).map(Some)
+    }
+
+    /// Current fees on the chain: the latest block's base fee and the
+    /// node's suggested priority fee. None without an RPC configured.
+    pub async fn fetch_gas_price(&self) -> anyhow::Result<Option<GasPrice>> {
+        let Some(ref rpc) = self.rpc else {
+            debug!("No RPC configured to fetch gas price");
+            return Ok(None);
+        };
+
+        let block = rpc.call("eth_getBlockByNumber", serde_json::json!(["latest", false])).await?;
+        let base_fee = U256::from(quantity(&block["baseFeePerGas"])?);
+        let tip = rpc.call("eth_maxPriorityFeePerGas", serde_json::json!([])).await?;
+        let priority_fee = U256::from(quantity(&tip)?);
+        Ok(Some(GasPrice {
+            base_fee,
+            priority_fee,
+            max_fee: base_fee * U256::from(2) + priority_fee,
+        }))
     }
 
     /// Get current nonce
@@ -120,16 +461,51 @@ impl TransactionSubmitter {
         self.pending_nonce += 1;
     }
 
-    /// Cancel a pending transaction
-    pub async fn cancel(&self, nonce: u64) -> anyhow::Result<()> {
+    /// Zero-value self-transfer that replaces the pending transaction at
+    /// `nonce`. Both fee fields are raised by at least 12.5% over `pending`.
+    pub fn build_replacement(&self, from: Address, nonce: u64, pending: &Eip1559Fees) -> BuiltTransaction {
+        let factor = self.config.fee_bump_factor.max(MIN_REPLACEMENT_BUMP);
+
+        BuiltTransaction {
+            chain: self.config.chain,
+            to: from,
+            value: U256::ZERO,
+            data: Bytes::new(),
+            gas_limit: TX_BASE_GAS,
+            max_fee_per_gas: bump_fee(pending.max_fee_per_gas, factor),
+            max_priority_fee: bump_fee(pending.max_priority_fee, factor),
+            nonce: Some(nonce),
+        }
+    }
+
+    /// Cancel a pending transaction by replacing it with a zero-value
+    /// self-transfer at the same nonce. Returns the replacement's hash.
+    pub async fn cancel(
+        &self,
+        signer: &dyn TxSigner,
+        nonce: u64,
+        pending: &Eip1559Fees,
+    ) -> anyhow::Result<String> {
         info!("Cancelling transaction with nonce {}", nonce);
 
-        // In production:
-        // 1. Build a zero-value self-transfer
-        // 2. Use higher gas price
-        // 3. Submit to replace the pending tx
+        let replacement = self.build_replacement(signer.address(), nonce, pending);
+        let raw = signer.sign(&replacement)?;
 
-        Ok(())
+        // Replacements go to the public mempool, where the pending tx lives
+        self.send_raw(&raw).await.map_err(anyhow::Error::from)
+    }
+
+    /// Broadcast a signed transaction, returning its hash
+    async fn send_raw(&self, raw: &Bytes) -> Result<String, ExecutionError> {
+        if self.config.dry_run {
+            info!("Dry run: not broadcasting {} byte transaction", raw.len());
+        } else {
+            // In production, send via eth_sendRawTransaction to the chain RPC
+            debug!("Broadcasting {} byte transaction", raw.len());
+        }
+
+        // A transaction's hash is the keccak of its signed encoding
+        Ok(format!("{:#x}", keccak256(raw)))
     }
 }
 
@@ -141,3 +517,485 @@ struct FlashbotsBundle {
     min_timestamp: Option<u64>,
     max_timestamp: Option<u64>,
 }
+
+/// `Transfer(address,address,uint256)` event topic
+const TRANSFER_TOPIC: B256 = b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
+/// Decode a JSON-RPC transaction receipt for a trade sent by `account`.
+///
+/// A trade starts and ends in the same token, so the first token `account`
+/// transfers out is taken as the profit token: what comes back of it is
+/// the output, and the difference the balance change.
+fn decode_receipt(receipt: &serde_json::Value, account: Address) -> anyhow::Result<TradeReceipt> {
+    let mut transfers = Vec::new();
+    for log in receipt["logs"].as_array().into_iter().flatten() {
+        let topics: Vec<B256> = serde_json::from_value(log["topics"].clone())?;
+        if topics.len() != 3 || topics[0] != TRANSFER_TOPIC {
+            continue;
+        }
+        let token: Address = serde_json::from_value(log["address"].clone())?;
+        let data: Bytes = serde_json::from_value(log["data"].clone())?;
+        let amount = U256::try_from_be_slice(&data)
+            .ok_or_else(|| anyhow::anyhow!("Transfer amount of {} bytes", data.len()))?;
+        let (from, to) = (Address::from_word(topics[1]), Address::from_word(topics[2]));
+        transfers.push((token, from, to, amount));
+    }
+
+    let profit_token = transfers.iter().find(|(_, from, _, _)| *from == account).map(|t| t.0);
+    let (mut sent, mut received) = (U256::ZERO, U256::ZERO);
+    for &(token, from, to, amount) in &transfers {
+        if Some(token) != profit_token {
+            continue;
+        }
+        if from == account {
+            sent += amount;
+        }
+        if to == account {
+            received += amount;
+        }
+    }
+
+    Ok(TradeReceipt {
+        success: quantity(&receipt["status"])? == 1,
+        block_number: quantity(&receipt["blockNumber"])?,
+        gas_used: quantity(&receipt["gasUsed"])?,
+        effective_gas_price: U256::from(quantity(&receipt["effectiveGasPrice"])?),
+        actual_output: profit_token.map(|_| received),
+        balance_delta: I256::try_from(received)? - I256::try_from(sent)?,
+    })
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Stand-in for the hash of a transaction that is never signed: keccak
+/// over the chain, nonce and every field a signature would commit to
+fn dry_run_hash(tx: &BuiltTransaction) -> String {
+    let mut data = Vec::with_capacity(140 + tx.data.len());
+    data.extend_from_slice(&tx.chain.chain_id().to_be_bytes());
+    data.extend_from_slice(&tx.nonce.unwrap_or(0).to_be_bytes());
+    data.extend_from_slice(tx.to.as_slice());
+    data.extend_from_slice(&tx.value.to_be_bytes::<32>());
+    data.extend_from_slice(&tx.gas_limit.to_be_bytes());
+    data.extend_from_slice(&tx.max_fee_per_gas.to_be_bytes::<32>());
+    data.extend_from_slice(&tx.max_priority_fee.to_be_bytes::<32>());
+    data.extend_from_slice(&tx.data);
+    format!("{:#x}", keccak256(&data))
+}
+
+/// Scale a fee by `factor`, rounding up so even tiny fees move
+fn bump_fee(fee: U256, factor: f64) -> U256 {
+    const SCALE: u64 = 1_000_000;
+    let multiplier = U256::from((factor.max(1.0) * SCALE as f64) as u64);
+    (fee * multiplier).div_ceil(U256::from(SCALE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+
+    fn tx() -> BuiltTransaction {
+        BuiltTransaction {
+            chain: ChainId::Ethereum,
+            to: Address::repeat_byte(0xEE),
+            value: U256::ZERO,
+            data: Bytes::new(),
+            gas_limit: 300_000,
+            max_fee_per_gas: U256::from(40_000_000_000u64),
+            max_priority_fee: U256::from(2_000_000_000u64),
+            nonce: None,
+        }
+    }
+
+    fn submitter(max_retries: u32) -> TransactionSubmitter {
+        TransactionSubmitter::new(SubmitterConfig {
+            max_retries,
+            retry_delay: Duration::from_millis(5),
+            fee_bump_factor: 1.5,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_errors_with_bumped_fees() {
+        let submitter = submitter(3);
+        let sent = Mutex::new(Vec::new());
+
+        let result = submitter
+            .submit_with(tx(), |tx| {
+                let mut sent = sent.lock();
+                sent.push((tx.max_priority_fee, tx.max_fee_per_gas, tx.nonce));
+                let attempt = sent.len();
+                async move {
+                    match attempt {
+                        1 => Err(ExecutionError::Underpriced),
+                        2 => Err(ExecutionError::RelayRejected("bundle simulation failed".to_string())),
+                        _ => Ok("0xfeed".to_string()),
+                    }
+                }
+            })
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.tx_hash.as_deref(), Some("0xfeed"));
+        // Two delays of 5ms and 10ms
+        assert!(result.latency_us >= 15_000, "{}", result.latency_us);
+
+        let sent = sent.into_inner();
+        let gwei = |n: u64| U256::from(n) * U256::from(1_000_000_000u64);
+        assert_eq!(sent.len(), 3);
+        assert_eq!(sent[0].0, gwei(2));
+        assert_eq!(sent[1].0, gwei(3));
+        assert_eq!(sent[2].0, U256::from(4_500_000_000u64));
+        assert_eq!(sent[2].1, gwei(90));
+        assert!(sent.iter().all(|(_, _, nonce)| nonce.is_some()));
+    }
+
+    /// Records what it signs and returns a fixed encoding
+    struct MockSigner {
+        signed: Mutex<Vec<BuiltTransaction>>,
+    }
+
+    impl TxSigner for MockSigner {
+        fn address(&self) -> Address {
+            Address::repeat_byte(0x42)
+        }
+
+        fn sign(&self, tx: &BuiltTransaction) -> anyhow::Result<Bytes> {
+            self.signed.lock().push(tx.clone());
+            Ok(Bytes::from_static(&[0x02, 0xf8, 0x65]))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_replaces_at_same_nonce_with_higher_fees() {
+        let submitter = TransactionSubmitter::new(SubmitterConfig::default());
+        let signer = MockSigner { signed: Mutex::new(Vec::new()) };
+        let pending = Eip1559Fees {
+            max_fee_per_gas: U256::from(40_000_000_000u64),
+            max_priority_fee: U256::from(2_000_000_000u64),
+        };
+
+        let tx_hash = submitter.cancel(&signer, 17, &pending).await.unwrap();
+        assert_eq!(tx_hash, format!("{:#x}", keccak256([0x02, 0xf8, 0x65])));
+
+        let from = signer.address();
+        let signed = signer.signed.into_inner();
+        assert_eq!(signed.len(), 1);
+        let replacement = &signed[0];
+        assert_eq!(replacement.nonce, Some(17));
+        assert_eq!(replacement.to, from);
+        assert_eq!(replacement.value, U256::ZERO);
+        assert!(replacement.data.is_empty());
+        assert_eq!(replacement.gas_limit, TX_BASE_GAS);
+
+        // At least 12.5% over the pending fees
+        let min = |fee: U256| fee * U256::from(1125) / U256::from(1000);
+        assert!(replacement.max_fee_per_gas >= min(pending.max_fee_per_gas));
+        assert!(replacement.max_priority_fee >= min(pending.max_priority_fee));
+    }
+
+    #[test]
+    fn test_replacement_bump_never_below_node_minimum() {
+        let submitter = TransactionSubmitter::new(SubmitterConfig {
+            fee_bump_factor: 1.01,
+            ..Default::default()
+        });
+        let pending = Eip1559Fees {
+            max_fee_per_gas: U256::from(1_000u64),
+            max_priority_fee: U256::from(1u64),
+        };
+
+        let replacement = submitter.build_replacement(Address::ZERO, 3, &pending);
+        assert_eq!(replacement.max_fee_per_gas, U256::from(1_125u64));
+        // Rounded up so a 1 wei tip still increases
+        assert_eq!(replacement.max_priority_fee, U256::from(2u64));
+    }
+
+    #[tokio::test]
+    async fn test_revert_is_not_retried() {
+        let submitter = submitter(3);
+        let mut attempts = 0;
+
+        let result = submitter
+            .submit_with(tx(), |_| {
+                attempts += 1;
+                async { Err::<String, _>(ExecutionError::Reverted("STF".to_string())) }
+            })
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert_eq!(attempts, 1);
+        assert_eq!(result.error.as_deref(), Some("Transaction reverted: STF"));
+    }
+
+    #[tokio::test]
+    async fn test_reprices_until_expiry_when_never_included() {
+        let submitter = submitter(0);
+        let reprice = RepriceConfig {
+            blocks_per_step: 2,
+            fee_schedule: vec![1.25, 1.3, 2.0],
+            poll_interval: Duration::from_millis(2),
+        };
+        let sent = Mutex::new(Vec::new());
+        let head = Mutex::new(100u64);
+        let expires_at_ms = now_ms() + 150;
+
+        let result = submitter
+            .reprice_with(
+                tx(),
+                expires_at_ms,
+                &reprice,
+                |tx| {
+                    let mut sent = sent.lock();
+                    sent.push(tx);
+                    let tx_hash = format!("0x{:02x}", sent.len());
+                    async move { Ok(tx_hash) }
+                },
+                |_| {
+                    // A block per poll, and the transaction never lands
+                    let mut head = head.lock();
+                    *head += 1;
+                    let status = InclusionStatus { head: *head, included: None };
+                    async move { Ok(status) }
+                },
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("Transaction not mined in time"));
+        assert!(now_ms() >= expires_at_ms);
+
+        // The original plus one resend per schedule step, all at one nonce
+        let sent = sent.into_inner();
+        assert_eq!(sent.len(), 4);
+        assert!(sent.iter().all(|tx| tx.nonce == sent[0].nonce && tx.nonce.is_some()));
+
+        let gwei = |n: u64| U256::from(n) * U256::from(1_000_000_000u64);
+        let fees: Vec<U256> = sent.iter().map(|tx| tx.max_priority_fee).collect();
+        // 1.3x is under the 12.5% replacement minimum over 1.25x, so it's raised
+        assert_eq!(fees, vec![
+            gwei(2),
+            U256::from(2_500_000_000u64),
+            U256::from(2_812_500_000u64),
+            gwei(4),
+        ]);
+        assert_eq!(sent[3].max_fee_per_gas, gwei(80));
+    }
+
+    #[tokio::test]
+    async fn test_reprice_stops_once_any_version_lands() {
+        let submitter = submitter(0);
+        let reprice = RepriceConfig {
+            blocks_per_step: 1,
+            poll_interval: Duration::from_millis(1),
+            ..Default::default()
+        };
+        let mut sends = 0;
+        let mut polls = 0;
+
+        let result = submitter
+            .reprice_with(
+                tx(),
+                now_ms() + 60_000,
+                &reprice,
+                |_| {
+                    sends += 1;
+                    let tx_hash = format!("0x{:02x}", sends);
+                    async move { Ok(tx_hash) }
+                },
+                |hashes| {
+                    polls += 1;
+                    // The first version lands after one replacement was sent
+                    let included = (hashes.len() == 2).then(|| hashes[0].clone());
+                    let status = InclusionStatus { head: polls, included };
+                    async move { Ok(status) }
+                },
+            )
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.tx_hash.as_deref(), Some("0x01"));
+        assert_eq!(sends, 2);
+    }
+
+    #[tokio::test]
+    async fn test_inclusion_status_reports_chain_head() {
+        struct FixedHead(u64);
+        impl BlockSource for FixedHead {
+            fn latest_block(&self) -> anyhow::Result<u64> {
+                Ok(self.0)
+            }
+        }
+
+        // Without a block source the head advances once per block time
+        let estimated = submitter(0).inclusion_status(vec!["0xfeed".to_string()]).await.unwrap();
+        let expected = now_ms() / ChainId::Ethereum.block_time_ms();
+        assert!(estimated.head > 0 && estimated.head.abs_diff(expected) <= 1, "{}", estimated.head);
+        assert_eq!(estimated.included, None);
+
+        let submitter = submitter(0).with_block_source(Arc::new(FixedHead(19_000_000)));
+        let status = submitter.inclusion_status(vec!["0xfeed".to_string()]).await.unwrap();
+        assert_eq!(status, InclusionStatus { head: 19_000_000, included: None });
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_never_sends() {
+        let submitter = TransactionSubmitter::new(SubmitterConfig {
+            use_flashbots: false,
+            dry_run: true,
+            ..Default::default()
+        });
+        let mut sends = 0;
+
+        let result = submitter
+            .submit_with(tx(), |_| {
+                sends += 1;
+                async { Ok("0xfeed".to_string()) }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(sends, 0);
+        assert!(result.success);
+        assert_eq!(result.gas_used, Some(300_000));
+        // The hash of the transaction at the fetched nonce
+        let mut at_nonce = tx();
+        at_nonce.nonce = Some(submitter.get_nonce(Address::ZERO).await.unwrap());
+        assert_eq!(result.tx_hash, Some(dry_run_hash(&at_nonce)));
+
+        // Repricing neither sends nor waits on inclusion
+        let result = submitter
+            .reprice_with(
+                tx(),
+                now_ms() + 60_000,
+                &RepriceConfig::default(),
+                |_| {
+                    sends += 1;
+                    async { Ok("0xfeed".to_string()) }
+                },
+                |_| async { Ok(InclusionStatus { head: 0, included: None }) },
+            )
+            .await
+            .unwrap();
+        assert_eq!(sends, 0);
+        assert!(result.success);
+        assert_eq!(result.tx_hash, Some(dry_run_hash(&at_nonce)));
+    }
+
+    #[test]
+    fn test_execution_config_sets_dry_run() {
+        let config = SubmitterConfig::default().with_execution_config(&ExecutionConfig {
+            use_flashbots: false,
+            max_retries: 5,
+            dry_run: true,
+            ..Default::default()
+        });
+        assert!(!config.use_flashbots);
+        assert_eq!(config.max_retries, 5);
+        assert!(TransactionSubmitter::new(config).is_dry_run());
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries() {
+        let submitter = submitter(2);
+        let mut attempts = 0;
+
+        let result = submitter
+            .submit_with(tx(), |_| {
+                attempts += 1;
+                async { Err::<String, _>(ExecutionError::NotMined) }
+            })
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert_eq!(attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_receipt_decodes_profit_token_transfers() {
+        let account = Address::repeat_byte(0x42);
+        let (weth, usdc, pool) = (Address::repeat_byte(0xE1), Address::repeat_byte(0xE2), Address::repeat_byte(0xA1));
+        let transfer = |token: Address, from: Address, to: Address, amount: u64| {
+            serde_json::json!({
+                "address": token,
+                "topics": [TRANSFER_TOPIC, from.into_word(), to.into_word()],
+                "data": B256::from(U256::from(amount)),
+            })
+        };
+        let logs = vec![
+            transfer(weth, account, pool, 1_000),
+            transfer(usdc, pool, pool, 2_000_000),
+            // Not a transfer
+            serde_json::json!({ "address": pool, "topics": [B256::repeat_byte(0x01)], "data": "0x" }),
+            transfer(weth, pool, account, 1_010),
+        ];
+
+        let url = crate::rpc::tests::mock_rpc_server(move |method, params| {
+            assert_eq!(method, "eth_getTransactionReceipt");
+            match params[0].as_str().unwrap() {
+                "0xpending" => serde_json::Value::Null,
+                _ => serde_json::json!({
+                    "status": "0x1",
+                    "blockNumber": "0x10",
+                    "gasUsed": "0x249f0",
+                    "effectiveGasPrice": "0x2540be400",
+                    "logs": logs,
+                }),
+            }
+        })
+        .await;
+        let submitter = TransactionSubmitter::new(SubmitterConfig {
+            rpc_url: url,
+            from: account,
+            ..Default::default()
+        });
+
+        assert!(submitter.fetch_receipt("0xpending").await.unwrap().is_none());
+
+        let receipt = submitter.fetch_receipt("0xmined").await.unwrap().unwrap();
+        assert!(receipt.success);
+        assert_eq!(receipt.block_number, 16);
+        assert_eq!(receipt.gas_used, 150_000);
+        assert_eq!(receipt.effective_gas_price, U256::from(10_000_000_000u64));
+        assert_eq!(receipt.actual_output, Some(U256::from(1_010u64)));
+        assert_eq!(receipt.balance_delta, I256::try_from(10i64).unwrap());
+
+        // Nothing to ask without an RPC
+        let offline = TransactionSubmitter::new(SubmitterConfig::default());
+        assert!(offline.fetch_receipt("0xmined").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_gas_price_reads_base_fee_and_tip() {
+        let url = crate::rpc::tests::mock_rpc_server(|method, _| match method {
+            "eth_getBlockByNumber" => serde_json::json!({ "number": "0x10", "baseFeePerGas": "0x6fc23ac00" }),
+            "eth_maxPriorityFeePerGas" => serde_json::json!("0x77359400"),
+            other => panic!("unexpected {}", other),
+        })
+        .await;
+        let submitter = TransactionSubmitter::new(SubmitterConfig {
+            rpc_url: url,
+            ..Default::default()
+        });
+
+        // 30 gwei base fee, 2 gwei tip
+        let gas_price = submitter.fetch_gas_price().await.unwrap().unwrap();
+        assert_eq!(gas_price.base_fee, U256::from(30_000_000_000u64));
+        assert_eq!(gas_price.priority_fee, U256::from(2_000_000_000u64));
+        assert_eq!(gas_price.max_fee, U256::from(62_000_000_000u64));
+
+        let offline = TransactionSubmitter::new(SubmitterConfig::default());
+        assert!(offline.fetch_gas_price().await.unwrap().is_none());
+    }
+}
@@ -2,6 +2,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     tonic_build::configure()
         .build_server(true)
         .build_client(true)
+        .type_attribute(".", "#[derive(serde::Serialize)]")
         .out_dir("src/generated")
         .compile(
             &["../../proto/defi.proto"],
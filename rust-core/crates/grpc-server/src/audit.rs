@@ -0,0 +1,269 @@
+//! Durable audit trail
+//!
+//! Audited actions are written as one JSON record per line to every
+//! configured sink, independently of the tracing subscriber, and mirrored
+//! to `tracing` under the `audit` target for operational logs.
+//!
+//! File sinks are written by a task on the blocking pool, so logging never
+//! waits on the disk. Records arriving while a file's buffer is full are
+//! dropped with a warning and show up as gaps in `seq`.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use defi_price_feed::{BackgroundWriter, RecordWriter};
+
+/// Records buffered for each audit file before new ones are dropped
+const AUDIT_BUFFER: usize = 10_000;
+
+/// Audited action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AuditEvent {
+    TradeExecuteRequest,
+    TradeExecuteResult,
+    ConfigUpdate,
+    ScannerStart,
+    ScannerStop,
+}
+
+impl AuditEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditEvent::TradeExecuteRequest => "TRADE_EXECUTE_REQUEST",
+            AuditEvent::TradeExecuteResult => "TRADE_EXECUTE_RESULT",
+            AuditEvent::ConfigUpdate => "CONFIG_UPDATE",
+            AuditEvent::ScannerStart => "SCANNER_START",
+            AuditEvent::ScannerStop => "SCANNER_STOP",
+        }
+    }
+}
+
+/// How an audited action ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOutcome {
+    /// Accepted, outcome recorded by a later event
+    Requested,
+    Success,
+    Failure,
+}
+
+/// One line of the audit trail.
+///
+/// `seq` increases by one per record, carrying on from the last record
+/// already in the logger's files, so gaps in a file point at lost or
+/// removed records. `details` holds the event-specific fields.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub seq: u64,
+    pub timestamp_ms: u64,
+    pub event: AuditEvent,
+    pub outcome: AuditOutcome,
+    pub message: String,
+    pub details: serde_json::Map<String, serde_json::Value>,
+}
+
+enum AuditSink {
+    /// Append-only JSONL file, fed to its writer task
+    File(BackgroundWriter<Box<AuditRecord>>),
+    Channel(mpsc::UnboundedSender<AuditRecord>),
+}
+
+/// Writes audit records to a file and/or channel. With no sinks the
+/// records only reach `tracing`.
+pub struct AuditLogger {
+    next_seq: AtomicU64,
+    sinks: Vec<AuditSink>,
+}
+
+impl AuditLogger {
+    pub fn new() -> Self {
+        Self {
+            next_seq: AtomicU64::new(0),
+            sinks: Vec::new(),
+        }
+    }
+
+    /// Also append records to `path`, creating it if needed, numbering
+    /// them on from the last record already there. Must be called from
+    /// within a tokio runtime.
+    pub fn with_file(mut self, path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+
+        if let Some(last) = last_seq(&file)? {
+            self.next_seq.fetch_max(last + 1, Ordering::Relaxed);
+        }
+
+        let writer = BackgroundWriter::spawn("Audit file", AUDIT_BUFFER, AuditFileWriter(BufWriter::new(file)));
+        self.sinks.push(AuditSink::File(writer));
+        Ok(self)
+    }
+
+    /// Also send records to `sender`
+    pub fn with_channel(mut self, sender: mpsc::UnboundedSender<AuditRecord>) -> Self {
+        self.sinks.push(AuditSink::Channel(sender));
+        self
+    }
+
+    /// Record an audited action. `details` should be a JSON object; any
+    /// other value is stored under a `value` key.
+    ///
+    /// Sink failures are logged rather than returned: a broken audit file
+    /// must not change the outcome of the action being audited.
+    pub fn log(
+        &self,
+        event: AuditEvent,
+        outcome: AuditOutcome,
+        message: &str,
+        details: serde_json::Value,
+    ) -> AuditRecord {
+        let details = match details {
+            serde_json::Value::Object(map) => map,
+            serde_json::Value::Null => serde_json::Map::new(),
+            other => serde_json::Map::from_iter([("value".to_string(), other)]),
+        };
+        let record = AuditRecord {
+            seq: self.next_seq.fetch_add(1, Ordering::Relaxed),
+            timestamp_ms: chrono::Utc::now().timestamp_millis() as u64,
+            event,
+            outcome,
+            message: message.to_string(),
+            details,
+        };
+
+        info!(
+            target: "audit",
+            event = event.as_str(),
+            outcome = ?outcome,
+            details = %serde_json::Value::Object(record.details.clone()),
+            "{}",
+            message
+        );
+
+        for sink in &self.sinks {
+            match sink {
+                AuditSink::File(writer) => {
+                    writer.send(Box::new(record.clone()));
+                }
+                AuditSink::Channel(sender) => {
+                    let _ = sender.send(record.clone());
+                }
+            }
+        }
+
+        record
+    }
+
+    /// Wait until every record logged so far is written to the files
+    pub async fn flush(&self) {
+        for sink in &self.sinks {
+            if let AuditSink::File(writer) = sink {
+                writer.flush().await;
+            }
+        }
+    }
+}
+
+/// `seq` of the last readable record in an audit file
+fn last_seq(file: &File) -> std::io::Result<Option<u64>> {
+    let mut last = None;
+    for line in BufReader::new(file).lines() {
+        if let Ok(record) = serde_json::from_str::<AuditRecord>(&line?) {
+            last = Some(record.seq);
+        }
+    }
+    Ok(last)
+}
+
+/// Audit file behind a `BackgroundWriter`
+struct AuditFileWriter(BufWriter<File>);
+
+impl RecordWriter for AuditFileWriter {
+    type Record = Box<AuditRecord>;
+
+    fn write(&mut self, record: Box<AuditRecord>) {
+        let written = serde_json::to_string(&record)
+            .map_err(std::io::Error::from)
+            .and_then(|line| writeln!(self.0, "{}", line));
+        if let Err(e) = written {
+            warn!("Failed to write audit record {}: {}", record.seq, e);
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Err(e) = self.0.flush() {
+            warn!("Failed to flush audit file: {}", e);
+        }
+    }
+}
+
+impl Default for AuditLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_sink_appends_json_lines() {
+        let path = std::env::temp_dir().join(format!("defi-audit-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let logger = AuditLogger::new().with_file(&path).unwrap();
+        logger.log(AuditEvent::ScannerStart, AuditOutcome::Success, "started", serde_json::json!({ "chains_count": 2 }));
+        logger.log(AuditEvent::ScannerStop, AuditOutcome::Success, "stopped", serde_json::Value::Null);
+        logger.flush().await;
+
+        // Reopening appends rather than truncating, and numbers on from the file
+        let reopened = AuditLogger::new().with_file(&path).unwrap();
+        let logged = reopened.log(AuditEvent::ConfigUpdate, AuditOutcome::Failure, "rejected", serde_json::json!(7));
+        assert_eq!(logged.seq, 2);
+        reopened.flush().await;
+
+        let records: Vec<AuditRecord> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records.iter().map(|r| r.seq).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(records[0].details["chains_count"], 2);
+        assert!(records[1].details.is_empty());
+        assert_eq!(records[2].details["value"], 7);
+
+        let raw: serde_json::Value = serde_json::from_str(
+            std::fs::read_to_string(&path).unwrap().lines().next().unwrap(),
+        ).unwrap();
+        assert_eq!(raw["event"], "SCANNER_START");
+        assert_eq!(raw["outcome"], "success");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_channel_sink_receives_records() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let logger = AuditLogger::new().with_channel(tx);
+
+        let logged = logger.log(AuditEvent::TradeExecuteRequest, AuditOutcome::Requested, "requested", serde_json::json!({ "trade_id": "t-1" }));
+        assert_eq!(rx.try_recv().unwrap(), logged);
+
+        // A dropped receiver doesn't stop logging
+        drop(rx);
+        logger.log(AuditEvent::TradeExecuteResult, AuditOutcome::Success, "submitted", serde_json::Value::Null);
+    }
+}
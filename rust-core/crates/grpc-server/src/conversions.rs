@@ -1,8 +1,11 @@
 //! Type conversions between internal types and proto types
 
-use defi_core::{ChainId, DexProtocol as CoreDexProtocol};
+use alloy_primitives::Address;
+use defi_core::{get_decimals, get_token_by_address, wrapped_native, ChainId, DexProtocol as CoreDexProtocol};
+use defi_executor::{TradeRecord, TradeStatus};
+use defi_price_feed::PriceState;
 
-use crate::proto::{Chain, DexProtocol};
+use crate::proto::{Chain, DexProtocol, ExecutionStatus, GetTradeStatusResponse, ScoreWeights};
 
 impl From<Chain> for ChainId {
     fn from(chain: Chain) -> Self {
@@ -27,104 +30,184 @@ impl From<ChainId> for Chain {
     }
 }
 
-impl From<i32> for ChainId {
-    fn from(value: i32) -> Self {
-        Chain::try_from(value)
-            .unwrap_or(Chain::Ethereum)
-            .into()
-    }
+/// Chain from a proto `Chain` value, Ethereum when unknown
+pub fn chain_from_proto(value: i32) -> ChainId {
+    Chain::try_from(value).unwrap_or(Chain::Ethereum).into()
 }
 
 impl From<DexProtocol> for CoreDexProtocol {
     fn from(dex: DexProtocol) -> Self {
         match dex {
-            DexProtocol::UniswapV2 => CoreDexProtocol::UniswapV2,
-            DexProtocol::UniswapV3 => CoreDexProtocol::UniswapV3,
-            DexProtocol::Sushiswap => CoreDexProtocol::SushiSwap,
-            DexProtocol::Curve => CoreDexProtocol::Curve,
-            DexProtocol::Balancer => CoreDexProtocol::Balancer,
-            DexProtocol::AaveV3 => CoreDexProtocol::AaveV3,
-            DexProtocol::Unknown => CoreDexProtocol::UniswapV2,
+            DexProtocol::DexUniswapV2 => CoreDexProtocol::UniswapV2,
+            DexProtocol::DexUniswapV3 => CoreDexProtocol::UniswapV3,
+            DexProtocol::DexSushiswap => CoreDexProtocol::SushiSwap,
+            DexProtocol::DexCurve => CoreDexProtocol::Curve,
+            DexProtocol::DexBalancer => CoreDexProtocol::Balancer,
+            DexProtocol::DexAaveV3 => CoreDexProtocol::AaveV3,
+            DexProtocol::DexCamelot => CoreDexProtocol::Camelot,
+            DexProtocol::DexAerodrome => CoreDexProtocol::Aerodrome,
+            DexProtocol::DexQuickswap => CoreDexProtocol::QuickSwap,
+            DexProtocol::DexUnknown => CoreDexProtocol::UniswapV2,
         }
     }
 }
 
+/// DEX from a proto `DexProtocol` value, Uniswap V2 when unknown
+pub fn dex_from_proto(value: i32) -> CoreDexProtocol {
+    DexProtocol::try_from(value).unwrap_or(DexProtocol::DexUniswapV2).into()
+}
+
 impl From<CoreDexProtocol> for DexProtocol {
     fn from(dex: CoreDexProtocol) -> Self {
         match dex {
-            CoreDexProtocol::UniswapV2 => DexProtocol::UniswapV2,
-            CoreDexProtocol::UniswapV3 => DexProtocol::UniswapV3,
-            CoreDexProtocol::SushiSwap => DexProtocol::Sushiswap,
-            CoreDexProtocol::Curve => DexProtocol::Curve,
-            CoreDexProtocol::Balancer => DexProtocol::Balancer,
-            CoreDexProtocol::AaveV3 => DexProtocol::AaveV3,
+            CoreDexProtocol::UniswapV2 => DexProtocol::DexUniswapV2,
+            CoreDexProtocol::UniswapV3 => DexProtocol::DexUniswapV3,
+            CoreDexProtocol::SushiSwap => DexProtocol::DexSushiswap,
+            CoreDexProtocol::Curve => DexProtocol::DexCurve,
+            CoreDexProtocol::Balancer => DexProtocol::DexBalancer,
+            CoreDexProtocol::AaveV3 => DexProtocol::DexAaveV3,
+            CoreDexProtocol::Camelot => DexProtocol::DexCamelot,
+            CoreDexProtocol::Aerodrome => DexProtocol::DexAerodrome,
+            CoreDexProtocol::QuickSwap => DexProtocol::DexQuickswap,
+        }
+    }
+}
+
+impl From<TradeStatus> for ExecutionStatus {
+    fn from(status: TradeStatus) -> Self {
+        match status {
+            TradeStatus::Pending => ExecutionStatus::ExecutionPending,
+            TradeStatus::Submitted => ExecutionStatus::ExecutionSubmitted,
+            TradeStatus::Confirmed => ExecutionStatus::ExecutionConfirmed,
+            TradeStatus::Failed => ExecutionStatus::ExecutionFailed,
+            TradeStatus::Reverted => ExecutionStatus::ExecutionReverted,
+            TradeStatus::Simulated => ExecutionStatus::ExecutionSimulated,
         }
     }
 }
 
-impl From<i32> for CoreDexProtocol {
-    fn from(value: i32) -> Self {
-        DexProtocol::try_from(value)
-            .unwrap_or(DexProtocol::UniswapV2)
-            .into()
+impl From<ScoreWeights> for defi_core::ScoreWeights {
+    fn from(weights: ScoreWeights) -> Self {
+        let profit_scale_usd = if weights.profit_scale_usd > 0.0 {
+            weights.profit_scale_usd
+        } else {
+            defi_core::ScoreWeights::default().profit_scale_usd
+        };
+        Self {
+            profit: weights.profit,
+            probability: weights.probability,
+            ttl: weights.ttl,
+            profit_scale_usd,
+        }
     }
 }
 
-/// Convert core opportunity to proto format
+/// Convert a stored trade record to a status response
+pub fn trade_record_to_proto(record: &TradeRecord) -> GetTradeStatusResponse {
+    GetTradeStatusResponse {
+        success: true,
+        trade_id: record.trade_id.clone(),
+        status: ExecutionStatus::from(record.status) as i32,
+        tx_hash: record.tx_hash.clone().unwrap_or_default(),
+        block_number: record.block_number.unwrap_or(0),
+        gas_used: record.gas_used.unwrap_or(0),
+        actual_output: record.actual_output.map(|o| o.to_string()).unwrap_or_default(),
+        actual_profit_usd: record.actual_profit_usd,
+        error: record.error.clone().unwrap_or_default(),
+    }
+}
+
+/// Price of an opportunity's input token in its chain's native token
+fn price_in_native(price_state: &PriceState, opp: &defi_core::ArbitrageOpportunity) -> Option<f64> {
+    let native = wrapped_native(opp.chain)?;
+    price_state.get_price_in(opp.chain, opp.token_a, native.address)
+}
+
+/// Convert core opportunity to proto format, pricing gas and `profit_eth`
+/// from `price_state`. Values that can't be priced are zero.
 pub fn opportunity_to_proto(
     opp: &defi_core::ArbitrageOpportunity,
+    price_state: &PriceState,
 ) -> crate::proto::ArbitrageOpportunity {
-    use chrono::Utc;
+    let route = opp.buy_route.steps
+        .iter()
+        .chain(&opp.sell_route.steps)
+        .map(|step| step_to_proto(opp.chain, step))
+        .collect();
 
     crate::proto::ArbitrageOpportunity {
         id: opp.id.clone(),
         chain: Chain::from(opp.chain) as i32,
         token_pair: opp.token_pair.clone(),
-        route: opp.route.iter().map(step_to_proto).collect(),
-        input_amount: Some(token_amount_to_proto(&opp.input)),
-        output_amount: Some(token_amount_to_proto(&opp.output)),
+        route,
+        input_amount: Some(crate::proto::TokenAmount {
+            token: Some(token_to_proto(opp.chain, opp.token_a)),
+            amount: opp.input_amount.to_string(),
+            amount_usd: opp.input_usd,
+        }),
+        output_amount: Some(crate::proto::TokenAmount {
+            token: Some(token_to_proto(opp.chain, opp.token_a)),
+            amount: opp.output_amount.to_string(),
+            amount_usd: 0.0,
+        }),
         profit_usd: opp.profit_usd,
         profit_bps: opp.profit_bps as f64,
         confidence: opp.confidence,
-        gas_estimate: opp.gas_estimate,
-        gas_cost_usd: opp.gas_cost_usd,
-        expires_at_ms: opp.expires_at.timestamp_millis() as u64,
-        detected_at_ms: Utc::now().timestamp_millis() as u64,
+        gas_estimate: opp.buy_route.gas_estimate + opp.sell_route.gas_estimate,
+        gas_cost_usd: price_state.gas_cost_usd(opp.chain, opp.gas_cost_wei).unwrap_or(0.0),
+        expires_at_ms: opp.expires_at_ms,
+        detected_at_ms: opp.detected_at_ms,
+        simulated: false,
+        simulated_gas_used: 0,
+        simulated_profit: String::new(),
+        profit: opp.profit_denomination.map(|token| {
+            let scale = 10f64.powi(get_decimals(opp.chain, token) as i32);
+            crate::proto::TokenAmount {
+                token: Some(token_to_proto(opp.chain, token)),
+                amount: format!("{:.0}", opp.profit_denominated * scale),
+                amount_usd: opp.profit_usd,
+            }
+        }),
+        profit_eth: opp.profit_eth(price_in_native(price_state, opp)).unwrap_or(0.0),
     }
 }
 
-fn step_to_proto(step: &defi_core::SwapStep) -> crate::proto::SwapStep {
+/// Convert a core quote to proto format
+pub fn quote_to_proto(quote: &defi_core::Quote) -> crate::proto::Quote {
+    let chain = quote.route.chain;
+
+    crate::proto::Quote {
+        route: quote.route.steps.iter().map(|step| step_to_proto(chain, step)).collect(),
+        amount_out: quote.route.total_amount_out.to_string(),
+        price_impact_bps: quote.route.price_impact_bps as f64,
+        gas_estimate: quote.route.gas_estimate,
+        valid_until_ms: quote.valid_until_ms,
+        source: quote.source.clone(),
+    }
+}
+
+fn step_to_proto(chain: ChainId, step: &defi_core::SwapStep) -> crate::proto::SwapStep {
     crate::proto::SwapStep {
         dex: DexProtocol::from(step.dex) as i32,
-        pool_address: step.pool_address.to_string(),
-        token_in: Some(crate::proto::Token {
-            address: step.token_in.address.to_string(),
-            symbol: step.token_in.symbol.clone(),
-            decimals: step.token_in.decimals as u32,
-            chain: Chain::from(step.token_in.chain) as i32,
-        }),
-        token_out: Some(crate::proto::Token {
-            address: step.token_out.address.to_string(),
-            symbol: step.token_out.symbol.clone(),
-            decimals: step.token_out.decimals as u32,
-            chain: Chain::from(step.token_out.chain) as i32,
-        }),
+        pool_address: step.pool.to_string(),
+        token_in: Some(token_to_proto(chain, step.token_in)),
+        token_out: Some(token_to_proto(chain, step.token_out)),
         amount_in: step.amount_in.to_string(),
         amount_out: step.amount_out.to_string(),
-        price_impact_bps: step.price_impact_bps as f64,
+        price_impact_bps: 0.0,
+        min_amount_out: step.min_amount_out.to_string(),
     }
 }
 
-fn token_amount_to_proto(amount: &defi_core::TokenAmount) -> crate::proto::TokenAmount {
-    crate::proto::TokenAmount {
-        token: Some(crate::proto::Token {
-            address: amount.token.address.to_string(),
-            symbol: amount.token.symbol.clone(),
-            decimals: amount.token.decimals as u32,
-            chain: Chain::from(amount.token.chain) as i32,
-        }),
-        amount: amount.amount.to_string(),
-        amount_usd: amount.value_usd,
+/// Proto token for an address, filling symbol and decimals for known tokens
+fn token_to_proto(chain: ChainId, address: Address) -> crate::proto::Token {
+    crate::proto::Token {
+        address: address.to_string(),
+        symbol: get_token_by_address(chain, address)
+            .map(|t| t.symbol.clone())
+            .unwrap_or_default(),
+        decimals: get_decimals(chain, address) as u32,
+        chain: Chain::from(chain) as i32,
     }
 }
 
@@ -136,3 +219,74 @@ pub fn now_ms() -> u64 {
         .map(|d| d.as_millis() as u64)
         .unwrap_or(0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+    use defi_core::{get_token, OpportunityBuilder, SwapRoute, SwapStep};
+    use defi_price_feed::test_support::seed_v2_pool;
+
+    const ETH: u128 = 1_000_000_000_000_000_000;
+    const USDC: u128 = 1_000_000;
+
+    fn route(pool: Address, token_in: Address, token_out: Address, amount_in: u128, amount_out: u128) -> SwapRoute {
+        SwapRoute {
+            steps: vec![SwapStep {
+                pool,
+                dex: CoreDexProtocol::UniswapV2,
+                token_in,
+                token_out,
+                amount_in: U256::from(amount_in),
+                amount_out: U256::from(amount_out),
+                fee_bps: 30,
+                min_amount_out: U256::ZERO,
+            }],
+            chain: ChainId::Ethereum,
+            total_amount_in: U256::from(amount_in),
+            total_amount_out: U256::from(amount_out),
+            gas_estimate: 100_000,
+            price_impact_bps: 0,
+        }
+    }
+
+    #[test]
+    fn test_opportunity_gas_is_priced_from_state() {
+        let chain = ChainId::Ethereum;
+        let weth = get_token(chain, "WETH").unwrap().address;
+        let usdc = get_token(chain, "USDC").unwrap().address;
+
+        // WETH at 2000 USDC
+        let state = PriceState::new();
+        let pool = seed_v2_pool(&state, chain, CoreDexProtocol::UniswapV2, weth, usdc, 1_000 * ETH, 2_000_000 * USDC);
+
+        let opp = OpportunityBuilder::new()
+            .chain(chain)
+            .tokens(weth, usdc)
+            .routes(
+                route(pool, weth, usdc, ETH, 2_000 * USDC),
+                route(pool, usdc, weth, 2_000 * USDC, ETH + ETH / 100),
+            )
+            .gas_cost(U256::from(ETH / 1_000))
+            .build()
+            .unwrap();
+        let proto = opportunity_to_proto(&opp, &state);
+
+        assert!((proto.gas_cost_usd - 2.0).abs() < 1e-6, "{}", proto.gas_cost_usd);
+    }
+
+    #[test]
+    fn test_unpriced_gas_is_zero() {
+        let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let pool = Address::repeat_byte(0xAA);
+        let opp = OpportunityBuilder::new()
+            .chain(ChainId::Ethereum)
+            .routes(route(pool, a, b, 1_000, 1_000), route(pool, b, a, 1_000, 1_100))
+            .gas_cost(U256::from(ETH))
+            .build()
+            .unwrap();
+
+        let proto = opportunity_to_proto(&opp, &PriceState::new());
+        assert_eq!(proto.gas_cost_usd, 0.0);
+    }
+}
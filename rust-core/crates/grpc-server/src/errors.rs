@@ -0,0 +1,146 @@
+//! Mapping from internal errors to gRPC status codes
+//!
+//! Each status carries the human-readable error message plus a JSON
+//! [`ErrorDetail`] in the status details, so clients can branch on the
+//! error class without parsing messages.
+
+use serde::{Deserialize, Serialize};
+use tonic::{Code, Status};
+
+use defi_core::{CoreError, ExecutionError, PriceFeedError};
+
+/// Machine-readable error classification attached to a status
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorDetail {
+    /// Error family: "core", "price_feed" or "execution"
+    pub domain: String,
+    /// Stable identifier for the variant, e.g. "PRICE_NOT_FOUND"
+    pub reason: String,
+}
+
+/// Convert an internal error into a gRPC status
+pub trait ToStatus {
+    fn to_status(&self) -> Status;
+}
+
+fn status(code: Code, domain: &str, reason: &str, message: String) -> Status {
+    let detail = ErrorDetail {
+        domain: domain.to_string(),
+        reason: reason.to_string(),
+    };
+    let details = serde_json::to_vec(&detail).unwrap_or_default();
+
+    Status::with_details(code, message, details.into())
+}
+
+/// Decode the structured detail from a status produced by [`ToStatus`]
+pub fn error_detail(status: &Status) -> Option<ErrorDetail> {
+    serde_json::from_slice(status.details()).ok()
+}
+
+impl ToStatus for CoreError {
+    fn to_status(&self) -> Status {
+        let (code, reason) = match self {
+            CoreError::ChainNotConfigured(_) => (Code::FailedPrecondition, "CHAIN_NOT_CONFIGURED"),
+            CoreError::TokenNotFound(_) => (Code::NotFound, "TOKEN_NOT_FOUND"),
+            CoreError::PoolNotFound(_) => (Code::NotFound, "POOL_NOT_FOUND"),
+            CoreError::PriceNotFound(_) => (Code::NotFound, "PRICE_NOT_FOUND"),
+            CoreError::TradeNotFound(_) => (Code::NotFound, "TRADE_NOT_FOUND"),
+            CoreError::OpportunityNotFound(_) => (Code::NotFound, "OPPORTUNITY_NOT_FOUND"),
+            CoreError::ScannerNotRunning => (Code::FailedPrecondition, "SCANNER_NOT_RUNNING"),
+            CoreError::ScannerAlreadyRunning => (Code::FailedPrecondition, "SCANNER_ALREADY_RUNNING"),
+            CoreError::ShuttingDown => (Code::Unavailable, "SHUTTING_DOWN"),
+            CoreError::DeadlineExceeded(_) => (Code::DeadlineExceeded, "DEADLINE_EXCEEDED"),
+            CoreError::InsufficientLiquidity => (Code::FailedPrecondition, "INSUFFICIENT_LIQUIDITY"),
+            CoreError::StalePrice { .. } => (Code::Unavailable, "STALE_PRICE"),
+            CoreError::PriceImpactTooHigh { .. } => (Code::FailedPrecondition, "PRICE_IMPACT_TOO_HIGH"),
+            CoreError::SlippageExceeded { .. } => (Code::FailedPrecondition, "SLIPPAGE_EXCEEDED"),
+            CoreError::GasPriceTooHigh { .. } => (Code::FailedPrecondition, "GAS_PRICE_TOO_HIGH"),
+            CoreError::InvalidConfig(_) => (Code::InvalidArgument, "INVALID_CONFIG"),
+            CoreError::InvalidAmount(_) => (Code::InvalidArgument, "INVALID_AMOUNT"),
+            CoreError::InvalidAddress(_) => (Code::InvalidArgument, "INVALID_ADDRESS"),
+            CoreError::InvalidRoute(_) => (Code::InvalidArgument, "INVALID_ROUTE"),
+            CoreError::InvalidOpportunity(_) => (Code::InvalidArgument, "INVALID_OPPORTUNITY"),
+            CoreError::RpcError(_) => (Code::Unavailable, "RPC_ERROR"),
+            CoreError::SerializationError(_) => (Code::Internal, "SERIALIZATION_ERROR"),
+        };
+
+        status(code, "core", reason, self.to_string())
+    }
+}
+
+impl ToStatus for PriceFeedError {
+    fn to_status(&self) -> Status {
+        let (code, reason) = match self {
+            PriceFeedError::ConnectionFailed(_) => (Code::Unavailable, "CONNECTION_FAILED"),
+            PriceFeedError::SubscriptionFailed(_) => (Code::Unavailable, "SUBSCRIPTION_FAILED"),
+            PriceFeedError::Disconnected => (Code::Unavailable, "DISCONNECTED"),
+            PriceFeedError::InvalidMessage(_) => (Code::Internal, "INVALID_MESSAGE"),
+            PriceFeedError::RateLimited => (Code::ResourceExhausted, "RATE_LIMITED"),
+            PriceFeedError::Timeout => (Code::DeadlineExceeded, "TIMEOUT"),
+        };
+
+        status(code, "price_feed", reason, self.to_string())
+    }
+}
+
+impl ToStatus for ExecutionError {
+    fn to_status(&self) -> Status {
+        let (code, reason) = match self {
+            ExecutionError::SimulationFailed(_) => (Code::Aborted, "SIMULATION_FAILED"),
+            ExecutionError::Reverted(_) => (Code::Aborted, "REVERTED"),
+            ExecutionError::NonceTooLow => (Code::Aborted, "NONCE_TOO_LOW"),
+            ExecutionError::Underpriced => (Code::FailedPrecondition, "UNDERPRICED"),
+            ExecutionError::NotMined => (Code::DeadlineExceeded, "NOT_MINED"),
+            ExecutionError::RelayRejected(_) => (Code::Unavailable, "RELAY_REJECTED"),
+            ExecutionError::Frontrun => (Code::Aborted, "FRONTRUN"),
+            ExecutionError::InsufficientBalance => (Code::FailedPrecondition, "INSUFFICIENT_BALANCE"),
+            ExecutionError::DelegationInvalid(_) => (Code::PermissionDenied, "DELEGATION_INVALID"),
+            ExecutionError::CircuitBreaker(_) => (Code::Unavailable, "CIRCUIT_BREAKER"),
+        };
+
+        status(code, "execution", reason, self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_status(status: Status, code: Code, domain: &str, reason: &str) {
+        assert_eq!(status.code(), code);
+        let detail = error_detail(&status).unwrap();
+        assert_eq!(detail.domain, domain);
+        assert_eq!(detail.reason, reason);
+    }
+
+    #[test]
+    fn test_core_error_codes() {
+        let status = CoreError::PriceNotFound("WETH".to_string()).to_status();
+        assert_eq!(status.message(), "Price not found: WETH");
+        assert_status(status, Code::NotFound, "core", "PRICE_NOT_FOUND");
+
+        assert_status(CoreError::ScannerNotRunning.to_status(), Code::FailedPrecondition, "core", "SCANNER_NOT_RUNNING");
+        assert_status(CoreError::InvalidAmount("abc".to_string()).to_status(), Code::InvalidArgument, "core", "INVALID_AMOUNT");
+        assert_status(CoreError::RpcError("timeout".to_string()).to_status(), Code::Unavailable, "core", "RPC_ERROR");
+    }
+
+    #[test]
+    fn test_price_feed_error_codes() {
+        assert_status(PriceFeedError::RateLimited.to_status(), Code::ResourceExhausted, "price_feed", "RATE_LIMITED");
+        assert_status(PriceFeedError::Timeout.to_status(), Code::DeadlineExceeded, "price_feed", "TIMEOUT");
+        assert_status(PriceFeedError::Disconnected.to_status(), Code::Unavailable, "price_feed", "DISCONNECTED");
+    }
+
+    #[test]
+    fn test_execution_error_codes() {
+        assert_status(
+            ExecutionError::DelegationInvalid("expired".to_string()).to_status(),
+            Code::PermissionDenied,
+            "execution",
+            "DELEGATION_INVALID",
+        );
+        assert_status(ExecutionError::Reverted("STF".to_string()).to_status(), Code::Aborted, "execution", "REVERTED");
+        assert_status(ExecutionError::NotMined.to_status(), Code::DeadlineExceeded, "execution", "NOT_MINED");
+    }
+}
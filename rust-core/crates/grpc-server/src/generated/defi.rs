@@ -1,82 +1,41 @@
-// This file is auto-generated by tonic-build at compile time.
-// For development, we include manual definitions that match the proto.
-// In production builds, tonic-build will generate this file.
-
-#![allow(clippy::all)]
-#![allow(unused_imports)]
-
-use prost::Message;
-use tonic::{Request, Response, Status, Streaming};
-
-// Chain enum
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
-#[repr(i32)]
-pub enum Chain {
-    Unknown = 0,
-    Ethereum = 1,
-    Arbitrum = 2,
-    Base = 3,
-    Polygon = 4,
-}
-
-// DexProtocol enum
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
-#[repr(i32)]
-pub enum DexProtocol {
-    Unknown = 0,
-    UniswapV2 = 1,
-    UniswapV3 = 2,
-    Sushiswap = 3,
-    Curve = 4,
-    Balancer = 5,
-    AaveV3 = 6,
-}
-
-// ExecutionStatus enum
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
-#[repr(i32)]
-pub enum ExecutionStatus {
-    Unknown = 0,
-    Pending = 1,
-    Submitted = 2,
-    Confirmed = 3,
-    Failed = 4,
-    Reverted = 5,
-}
-
-// Token message
+// This file is @generated by prost-build.
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Token {
     #[prost(string, tag = "1")]
-    pub address: String,
+    pub address: ::prost::alloc::string::String,
     #[prost(string, tag = "2")]
-    pub symbol: String,
+    pub symbol: ::prost::alloc::string::String,
     #[prost(uint32, tag = "3")]
     pub decimals: u32,
     #[prost(enumeration = "Chain", tag = "4")]
     pub chain: i32,
 }
-
-// TokenAmount message
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TokenAmount {
     #[prost(message, optional, tag = "1")]
-    pub token: Option<Token>,
+    pub token: ::core::option::Option<Token>,
+    /// Wei string to avoid precision loss
     #[prost(string, tag = "2")]
-    pub amount: String,
+    pub amount: ::prost::alloc::string::String,
     #[prost(double, tag = "3")]
     pub amount_usd: f64,
 }
-
-// Price operations
+/// Price operations
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetPriceRequest {
     #[prost(string, tag = "1")]
-    pub token_address: String,
+    pub token_address: ::prost::alloc::string::String,
     #[prost(enumeration = "Chain", tag = "2")]
     pub chain: i32,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetPriceResponse {
     #[prost(bool, tag = "1")]
@@ -86,23 +45,25 @@ pub struct GetPriceResponse {
     #[prost(uint64, tag = "3")]
     pub timestamp_ms: u64,
     #[prost(string, tag = "4")]
-    pub source: String,
+    pub source: ::prost::alloc::string::String,
     #[prost(string, tag = "5")]
-    pub error: String,
+    pub error: ::prost::alloc::string::String,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StreamPricesRequest {
     #[prost(string, repeated, tag = "1")]
-    pub token_addresses: Vec<String>,
+    pub token_addresses: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
     #[prost(enumeration = "Chain", tag = "2")]
     pub chain: i32,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PriceUpdate {
     #[prost(string, tag = "1")]
-    pub token_address: String,
+    pub token_address: ::prost::alloc::string::String,
     #[prost(enumeration = "Chain", tag = "2")]
     pub chain: i32,
     #[prost(double, tag = "3")]
@@ -110,58 +71,147 @@ pub struct PriceUpdate {
     #[prost(uint64, tag = "4")]
     pub timestamp_ms: u64,
     #[prost(string, tag = "5")]
-    pub source: String,
+    pub source: ::prost::alloc::string::String,
+}
+/// Quote operations
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetQuoteRequest {
+    #[prost(enumeration = "Chain", tag = "1")]
+    pub chain: i32,
+    #[prost(string, tag = "2")]
+    pub token_in: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub token_out: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub amount_in: ::prost::alloc::string::String,
+    /// 0 = default (50)
+    #[prost(uint32, tag = "5")]
+    pub slippage_bps: u32,
+    /// 0 = default (3)
+    #[prost(uint32, tag = "6")]
+    pub max_hops: u32,
+    /// Pool addresses every returned route must trade through. When no route
+    /// can, the response has no quotes and error says why.
+    #[prost(string, repeated, tag = "7")]
+    pub must_use_pools: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Quote {
+    #[prost(message, repeated, tag = "1")]
+    pub route: ::prost::alloc::vec::Vec<SwapStep>,
+    #[prost(string, tag = "2")]
+    pub amount_out: ::prost::alloc::string::String,
+    #[prost(double, tag = "3")]
+    pub price_impact_bps: f64,
+    #[prost(uint64, tag = "4")]
+    pub gas_estimate: u64,
+    #[prost(uint64, tag = "5")]
+    pub valid_until_ms: u64,
+    #[prost(string, tag = "6")]
+    pub source: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetQuoteResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(message, repeated, tag = "2")]
+    pub quotes: ::prost::alloc::vec::Vec<Quote>,
+    /// -1 when no route was found
+    #[prost(int32, tag = "3")]
+    pub best_quote_index: i32,
+    #[prost(uint32, tag = "4")]
+    pub price_spread_bps: u32,
+    #[prost(string, tag = "5")]
+    pub error: ::prost::alloc::string::String,
 }
-
-// Opportunity operations
+/// Weights for SORT_SCORE. Each term is normalized to 0..1 before weighting.
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScoreWeights {
+    #[prost(double, tag = "1")]
+    pub profit: f64,
+    #[prost(double, tag = "2")]
+    pub probability: f64,
+    /// Share of the opportunity's lifetime left
+    #[prost(double, tag = "3")]
+    pub ttl: f64,
+    /// Profit scoring half the profit term; 0 = server default
+    #[prost(double, tag = "4")]
+    pub profit_scale_usd: f64,
+}
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetOpportunitiesRequest {
     #[prost(enumeration = "Chain", repeated, tag = "1")]
-    pub chains: Vec<i32>,
+    pub chains: ::prost::alloc::vec::Vec<i32>,
     #[prost(double, tag = "2")]
     pub min_profit_usd: f64,
     #[prost(double, tag = "3")]
     pub min_confidence: f64,
+    /// <= 0 returns up to the maximum of 100
     #[prost(int32, tag = "4")]
     pub limit: i32,
+    /// Applied before limit, highest first
+    #[prost(enumeration = "OpportunitySort", tag = "5")]
+    pub sort_by: i32,
+    /// Drop opportunities that don't simulate profitably; chains without a simulator come back unsimulated
+    #[prost(bool, tag = "6")]
+    pub simulate: bool,
+    /// For SORT_SCORE; unset uses the server's weights
+    #[prost(message, optional, tag = "7")]
+    pub score_weights: ::core::option::Option<ScoreWeights>,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetOpportunitiesResponse {
     #[prost(bool, tag = "1")]
     pub success: bool,
     #[prost(message, repeated, tag = "2")]
-    pub opportunities: Vec<ArbitrageOpportunity>,
+    pub opportunities: ::prost::alloc::vec::Vec<ArbitrageOpportunity>,
     #[prost(uint64, tag = "3")]
     pub scan_duration_us: u64,
     #[prost(string, tag = "4")]
-    pub error: String,
+    pub error: ::prost::alloc::string::String,
+    /// Served from the previous scan; scan_duration_us is 0
+    #[prost(bool, tag = "5")]
+    pub cache_hit: bool,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StreamOpportunitiesRequest {
     #[prost(enumeration = "Chain", repeated, tag = "1")]
-    pub chains: Vec<i32>,
+    pub chains: ::prost::alloc::vec::Vec<i32>,
     #[prost(double, tag = "2")]
     pub min_profit_usd: f64,
     #[prost(double, tag = "3")]
     pub min_confidence: f64,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ArbitrageOpportunity {
     #[prost(string, tag = "1")]
-    pub id: String,
+    pub id: ::prost::alloc::string::String,
     #[prost(enumeration = "Chain", tag = "2")]
     pub chain: i32,
     #[prost(string, tag = "3")]
-    pub token_pair: String,
+    pub token_pair: ::prost::alloc::string::String,
     #[prost(message, repeated, tag = "4")]
-    pub route: Vec<SwapStep>,
+    pub route: ::prost::alloc::vec::Vec<SwapStep>,
     #[prost(message, optional, tag = "5")]
-    pub input_amount: Option<TokenAmount>,
+    pub input_amount: ::core::option::Option<TokenAmount>,
     #[prost(message, optional, tag = "6")]
-    pub output_amount: Option<TokenAmount>,
+    pub output_amount: ::core::option::Option<TokenAmount>,
     #[prost(double, tag = "7")]
     pub profit_usd: f64,
     #[prost(double, tag = "8")]
@@ -176,47 +226,113 @@ pub struct ArbitrageOpportunity {
     pub expires_at_ms: u64,
     #[prost(uint64, tag = "13")]
     pub detected_at_ms: u64,
+    /// Set when the fields below come from simulation
+    #[prost(bool, tag = "14")]
+    pub simulated: bool,
+    #[prost(uint64, tag = "15")]
+    pub simulated_gas_used: u64,
+    /// Wei of the input token
+    #[prost(string, tag = "16")]
+    pub simulated_profit: ::prost::alloc::string::String,
+    /// Net profit in the configured profit denomination, if any
+    #[prost(message, optional, tag = "17")]
+    pub profit: ::core::option::Option<TokenAmount>,
+    /// Net profit in the chain's native token, 0 if the input token can't be priced in it
+    #[prost(double, tag = "18")]
+    pub profit_eth: f64,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SwapStep {
     #[prost(enumeration = "DexProtocol", tag = "1")]
     pub dex: i32,
     #[prost(string, tag = "2")]
-    pub pool_address: String,
+    pub pool_address: ::prost::alloc::string::String,
     #[prost(message, optional, tag = "3")]
-    pub token_in: Option<Token>,
+    pub token_in: ::core::option::Option<Token>,
     #[prost(message, optional, tag = "4")]
-    pub token_out: Option<Token>,
+    pub token_out: ::core::option::Option<Token>,
     #[prost(string, tag = "5")]
-    pub amount_in: String,
+    pub amount_in: ::prost::alloc::string::String,
     #[prost(string, tag = "6")]
-    pub amount_out: String,
+    pub amount_out: ::prost::alloc::string::String,
     #[prost(double, tag = "7")]
     pub price_impact_bps: f64,
+    /// Least output before the hop reverts, 0 if unprotected
+    #[prost(string, tag = "8")]
+    pub min_amount_out: ::prost::alloc::string::String,
 }
-
-// Simulation operations
+/// Re-run a past opportunity through the optimizer under what-if conditions
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReplayOpportunityRequest {
+    /// Core ArbitrageOpportunity as JSON, e.g. a line of the opportunity sink
+    #[prost(string, tag = "1")]
+    pub opportunity_json: ::prost::alloc::string::String,
+    /// Empty keeps the recorded gas cost
+    #[prost(string, tag = "2")]
+    pub gas_price_wei: ::prost::alloc::string::String,
+    /// Empty uses the optimizer default
+    #[prost(string, tag = "3")]
+    pub min_profit_wei: ::prost::alloc::string::String,
+    /// Also simulate on the chain's EVM simulator
+    #[prost(bool, tag = "4")]
+    pub simulate: bool,
+}
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReplayOpportunityResponse {
+    #[prost(bool, tag = "1")]
+    pub would_execute: bool,
+    /// Empty when it would execute
+    #[prost(string, tag = "2")]
+    pub rejection_reason: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub gas_cost_wei: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub net_profit_wei: ::prost::alloc::string::String,
+    #[prost(int32, tag = "5")]
+    pub profit_bps: i32,
+    #[prost(double, tag = "6")]
+    pub profit_usd: f64,
+    #[prost(double, tag = "7")]
+    pub confidence: f64,
+    #[prost(bool, tag = "8")]
+    pub simulated: bool,
+    #[prost(bool, tag = "9")]
+    pub simulation_success: bool,
+    #[prost(string, tag = "10")]
+    pub simulated_profit: ::prost::alloc::string::String,
+    #[prost(string, tag = "11")]
+    pub simulation_error: ::prost::alloc::string::String,
+}
+/// Simulation operations
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SimulateTradeRequest {
     #[prost(enumeration = "Chain", tag = "1")]
     pub chain: i32,
     #[prost(string, tag = "2")]
-    pub delegation_id: String,
+    pub delegation_id: ::prost::alloc::string::String,
     #[prost(enumeration = "DexProtocol", tag = "3")]
     pub protocol: i32,
     #[prost(string, tag = "4")]
-    pub token_in: String,
+    pub token_in: ::prost::alloc::string::String,
     #[prost(string, tag = "5")]
-    pub token_out: String,
+    pub token_out: ::prost::alloc::string::String,
     #[prost(string, tag = "6")]
-    pub amount_in: String,
+    pub amount_in: ::prost::alloc::string::String,
     #[prost(string, tag = "7")]
-    pub min_amount_out: String,
+    pub min_amount_out: ::prost::alloc::string::String,
     #[prost(uint32, tag = "8")]
     pub slippage_bps: u32,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SimulateTradeResponse {
     #[prost(bool, tag = "1")]
@@ -224,7 +340,7 @@ pub struct SimulateTradeResponse {
     #[prost(bool, tag = "2")]
     pub would_succeed: bool,
     #[prost(string, tag = "3")]
-    pub expected_output: String,
+    pub expected_output: ::prost::alloc::string::String,
     #[prost(double, tag = "4")]
     pub expected_output_usd: f64,
     #[prost(double, tag = "5")]
@@ -234,21 +350,23 @@ pub struct SimulateTradeResponse {
     #[prost(double, tag = "7")]
     pub gas_cost_usd: f64,
     #[prost(string, tag = "8")]
-    pub error: String,
+    pub error: ::prost::alloc::string::String,
     #[prost(string, tag = "9")]
-    pub revert_reason: String,
+    pub revert_reason: ::prost::alloc::string::String,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SimulateRouteRequest {
     #[prost(enumeration = "Chain", tag = "1")]
     pub chain: i32,
     #[prost(message, repeated, tag = "2")]
-    pub route: Vec<SwapStep>,
+    pub route: ::prost::alloc::vec::Vec<SwapStep>,
     #[prost(string, tag = "3")]
-    pub input_amount: String,
+    pub input_amount: ::prost::alloc::string::String,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SimulateRouteResponse {
     #[prost(bool, tag = "1")]
@@ -256,17 +374,18 @@ pub struct SimulateRouteResponse {
     #[prost(bool, tag = "2")]
     pub would_succeed: bool,
     #[prost(string, tag = "3")]
-    pub final_output: String,
+    pub final_output: ::prost::alloc::string::String,
     #[prost(double, tag = "4")]
     pub total_price_impact_bps: f64,
     #[prost(uint64, tag = "5")]
     pub total_gas_estimate: u64,
     #[prost(message, repeated, tag = "6")]
-    pub step_results: Vec<StepResult>,
+    pub step_results: ::prost::alloc::vec::Vec<StepResult>,
     #[prost(string, tag = "7")]
-    pub error: String,
+    pub error: ::prost::alloc::string::String,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StepResult {
     #[prost(uint32, tag = "1")]
@@ -274,86 +393,103 @@ pub struct StepResult {
     #[prost(bool, tag = "2")]
     pub success: bool,
     #[prost(string, tag = "3")]
-    pub output_amount: String,
+    pub output_amount: ::prost::alloc::string::String,
     #[prost(uint64, tag = "4")]
     pub gas_used: u64,
     #[prost(string, tag = "5")]
-    pub error: String,
+    pub error: ::prost::alloc::string::String,
 }
-
-// Execution operations
+/// Execution operations
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ExecuteTradeRequest {
     #[prost(enumeration = "Chain", tag = "1")]
     pub chain: i32,
     #[prost(string, tag = "2")]
-    pub delegation_id: String,
+    pub delegation_id: ::prost::alloc::string::String,
+    /// Optional: use detected opportunity
     #[prost(string, tag = "3")]
-    pub opportunity_id: String,
+    pub opportunity_id: ::prost::alloc::string::String,
     #[prost(enumeration = "DexProtocol", tag = "4")]
     pub protocol: i32,
     #[prost(string, tag = "5")]
-    pub token_in: String,
+    pub token_in: ::prost::alloc::string::String,
     #[prost(string, tag = "6")]
-    pub token_out: String,
+    pub token_out: ::prost::alloc::string::String,
     #[prost(string, tag = "7")]
-    pub amount_in: String,
+    pub amount_in: ::prost::alloc::string::String,
     #[prost(string, tag = "8")]
-    pub min_amount_out: String,
+    pub min_amount_out: ::prost::alloc::string::String,
     #[prost(uint32, tag = "9")]
     pub slippage_bps: u32,
     #[prost(uint64, tag = "10")]
     pub deadline_ms: u64,
     #[prost(bool, tag = "11")]
     pub use_flashbots: bool,
+    /// Optional: retries with the same key and delegation return the
+    /// original trade instead of submitting again
+    #[prost(string, tag = "12")]
+    pub idempotency_key: ::prost::alloc::string::String,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ExecuteTradeResponse {
     #[prost(bool, tag = "1")]
     pub success: bool,
     #[prost(string, tag = "2")]
-    pub tx_hash: String,
+    pub tx_hash: ::prost::alloc::string::String,
     #[prost(string, tag = "3")]
-    pub trade_id: String,
+    pub trade_id: ::prost::alloc::string::String,
     #[prost(enumeration = "ExecutionStatus", tag = "4")]
     pub status: i32,
     #[prost(string, tag = "5")]
-    pub error: String,
+    pub error: ::prost::alloc::string::String,
+    /// Nothing was broadcast; tx_hash is the hash the transaction would have had
+    #[prost(bool, tag = "6")]
+    pub dry_run: bool,
+    /// Pre-flight simulation's profit in wei, when one ran
+    #[prost(string, tag = "7")]
+    pub simulated_profit: ::prost::alloc::string::String,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetTradeStatusRequest {
     #[prost(string, tag = "1")]
-    pub trade_id: String,
+    pub trade_id: ::prost::alloc::string::String,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetTradeStatusResponse {
     #[prost(bool, tag = "1")]
     pub success: bool,
     #[prost(string, tag = "2")]
-    pub trade_id: String,
+    pub trade_id: ::prost::alloc::string::String,
     #[prost(enumeration = "ExecutionStatus", tag = "3")]
     pub status: i32,
     #[prost(string, tag = "4")]
-    pub tx_hash: String,
+    pub tx_hash: ::prost::alloc::string::String,
     #[prost(uint64, tag = "5")]
     pub block_number: u64,
     #[prost(uint64, tag = "6")]
     pub gas_used: u64,
     #[prost(string, tag = "7")]
-    pub actual_output: String,
+    pub actual_output: ::prost::alloc::string::String,
     #[prost(double, tag = "8")]
     pub actual_profit_usd: f64,
     #[prost(string, tag = "9")]
-    pub error: String,
+    pub error: ::prost::alloc::string::String,
 }
-
-// System management
+/// System management
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetSystemStatusRequest {}
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetSystemStatusResponse {
     #[prost(bool, tag = "1")]
@@ -377,9 +513,13 @@ pub struct GetSystemStatusResponse {
     #[prost(uint64, tag = "10")]
     pub last_scan_duration_us: u64,
     #[prost(message, repeated, tag = "11")]
-    pub chain_statuses: Vec<ChainStatus>,
+    pub chain_statuses: ::prost::alloc::vec::Vec<ChainStatus>,
+    /// Scanner started but waiting for pool data
+    #[prost(bool, tag = "12")]
+    pub warming_up: bool,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChainStatus {
     #[prost(enumeration = "Chain", tag = "1")]
@@ -393,126 +533,1679 @@ pub struct ChainStatus {
     #[prost(uint64, tag = "5")]
     pub last_update_ms: u64,
 }
-
+/// Lightweight liveness/readiness probe
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HealthRequest {
+    /// 0 = server default
+    #[prost(uint64, tag = "1")]
+    pub max_scan_age_ms: u64,
+}
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HealthResponse {
+    #[prost(bool, tag = "1")]
+    pub live: bool,
+    #[prost(bool, tag = "2")]
+    pub ready: bool,
+    #[prost(bool, tag = "3")]
+    pub scanner_ticking: bool,
+    #[prost(uint64, tag = "4")]
+    pub last_scan_age_ms: u64,
+    #[prost(message, repeated, tag = "5")]
+    pub feeds: ::prost::alloc::vec::Vec<FeedHealth>,
+    #[prost(message, repeated, tag = "6")]
+    pub chains: ::prost::alloc::vec::Vec<ChainHealth>,
+}
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FeedHealth {
+    #[prost(enumeration = "Chain", tag = "1")]
+    pub chain: i32,
+    #[prost(enumeration = "DexProtocol", tag = "2")]
+    pub dex: i32,
+    #[prost(bool, tag = "3")]
+    pub connected: bool,
+}
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChainHealth {
+    #[prost(enumeration = "Chain", tag = "1")]
+    pub chain: i32,
+    #[prost(bool, tag = "2")]
+    pub has_data: bool,
+    #[prost(uint64, tag = "3")]
+    pub last_update_age_ms: u64,
+}
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateConfigRequest {
     #[prost(uint64, optional, tag = "1")]
-    pub scan_interval_ms: Option<u64>,
+    pub scan_interval_ms: ::core::option::Option<u64>,
     #[prost(double, optional, tag = "2")]
-    pub min_profit_usd: Option<f64>,
+    pub min_profit_usd: ::core::option::Option<f64>,
     #[prost(double, optional, tag = "3")]
-    pub min_confidence: Option<f64>,
+    pub min_confidence: ::core::option::Option<f64>,
     #[prost(double, optional, tag = "4")]
-    pub max_gas_gwei: Option<f64>,
+    pub max_gas_gwei: ::core::option::Option<f64>,
     #[prost(enumeration = "Chain", repeated, tag = "5")]
-    pub enabled_chains: Vec<i32>,
+    pub enabled_chains: ::prost::alloc::vec::Vec<i32>,
     #[prost(enumeration = "DexProtocol", repeated, tag = "6")]
-    pub enabled_dexes: Vec<i32>,
+    pub enabled_dexes: ::prost::alloc::vec::Vec<i32>,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateConfigResponse {
     #[prost(bool, tag = "1")]
     pub success: bool,
     #[prost(string, tag = "2")]
-    pub error: String,
+    pub error: ::prost::alloc::string::String,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StartScannerRequest {
     #[prost(enumeration = "Chain", repeated, tag = "1")]
-    pub chains: Vec<i32>,
+    pub chains: ::prost::alloc::vec::Vec<i32>,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StartScannerResponse {
     #[prost(bool, tag = "1")]
     pub success: bool,
     #[prost(string, tag = "2")]
-    pub error: String,
+    pub error: ::prost::alloc::string::String,
 }
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StopScannerRequest {}
-
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StopScannerResponse {
     #[prost(bool, tag = "1")]
     pub success: bool,
     #[prost(string, tag = "2")]
-    pub error: String,
-}
-
-// Service trait definition
-#[tonic::async_trait]
-pub trait DefiService: Send + Sync + 'static {
-    async fn get_price(&self, request: Request<GetPriceRequest>) -> Result<Response<GetPriceResponse>, Status>;
-
-    type StreamPricesStream: futures::Stream<Item = Result<PriceUpdate, Status>> + Send + 'static;
-    async fn stream_prices(&self, request: Request<StreamPricesRequest>) -> Result<Response<Self::StreamPricesStream>, Status>;
-
-    async fn get_opportunities(&self, request: Request<GetOpportunitiesRequest>) -> Result<Response<GetOpportunitiesResponse>, Status>;
-
-    type StreamOpportunitiesStream: futures::Stream<Item = Result<ArbitrageOpportunity, Status>> + Send + 'static;
-    async fn stream_opportunities(&self, request: Request<StreamOpportunitiesRequest>) -> Result<Response<Self::StreamOpportunitiesStream>, Status>;
-
-    async fn simulate_trade(&self, request: Request<SimulateTradeRequest>) -> Result<Response<SimulateTradeResponse>, Status>;
-    async fn simulate_route(&self, request: Request<SimulateRouteRequest>) -> Result<Response<SimulateRouteResponse>, Status>;
-
-    async fn execute_trade(&self, request: Request<ExecuteTradeRequest>) -> Result<Response<ExecuteTradeResponse>, Status>;
-    async fn get_trade_status(&self, request: Request<GetTradeStatusRequest>) -> Result<Response<GetTradeStatusResponse>, Status>;
-
-    async fn get_system_status(&self, request: Request<GetSystemStatusRequest>) -> Result<Response<GetSystemStatusResponse>, Status>;
-    async fn update_config(&self, request: Request<UpdateConfigRequest>) -> Result<Response<UpdateConfigResponse>, Status>;
-    async fn start_scanner(&self, request: Request<StartScannerRequest>) -> Result<Response<StartScannerResponse>, Status>;
-    async fn stop_scanner(&self, request: Request<StopScannerRequest>) -> Result<Response<StopScannerResponse>, Status>;
-}
-
-// Server builder
-pub struct DefiServiceServer<T: DefiService> {
-    inner: std::sync::Arc<T>,
-}
-
-impl<T: DefiService> DefiServiceServer<T> {
-    pub fn new(inner: T) -> Self {
-        Self {
-            inner: std::sync::Arc::new(inner),
+    pub error: ::prost::alloc::string::String,
+}
+/// Common types
+#[derive(serde::Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Chain {
+    Unknown = 0,
+    Ethereum = 1,
+    Arbitrum = 2,
+    Base = 3,
+    Polygon = 4,
+}
+impl Chain {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Chain::Unknown => "CHAIN_UNKNOWN",
+            Chain::Ethereum => "CHAIN_ETHEREUM",
+            Chain::Arbitrum => "CHAIN_ARBITRUM",
+            Chain::Base => "CHAIN_BASE",
+            Chain::Polygon => "CHAIN_POLYGON",
         }
     }
-
-    pub fn from_arc(inner: std::sync::Arc<T>) -> Self {
-        Self { inner }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "CHAIN_UNKNOWN" => Some(Self::Unknown),
+            "CHAIN_ETHEREUM" => Some(Self::Ethereum),
+            "CHAIN_ARBITRUM" => Some(Self::Arbitrum),
+            "CHAIN_BASE" => Some(Self::Base),
+            "CHAIN_POLYGON" => Some(Self::Polygon),
+            _ => None,
+        }
     }
 }
-
-impl<T: DefiService> tonic::codegen::Service<http::Request<tonic::body::BoxBody>> for DefiServiceServer<T> {
-    type Response = http::Response<tonic::body::BoxBody>;
-    type Error = std::convert::Infallible;
-    type Future = std::pin::Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
-
-    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
-        std::task::Poll::Ready(Ok(()))
+#[derive(serde::Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum DexProtocol {
+    DexUnknown = 0,
+    DexUniswapV2 = 1,
+    DexUniswapV3 = 2,
+    DexSushiswap = 3,
+    DexCurve = 4,
+    DexBalancer = 5,
+    DexAaveV3 = 6,
+    DexCamelot = 7,
+    DexAerodrome = 8,
+    DexQuickswap = 9,
+}
+impl DexProtocol {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            DexProtocol::DexUnknown => "DEX_UNKNOWN",
+            DexProtocol::DexUniswapV2 => "DEX_UNISWAP_V2",
+            DexProtocol::DexUniswapV3 => "DEX_UNISWAP_V3",
+            DexProtocol::DexSushiswap => "DEX_SUSHISWAP",
+            DexProtocol::DexCurve => "DEX_CURVE",
+            DexProtocol::DexBalancer => "DEX_BALANCER",
+            DexProtocol::DexAaveV3 => "DEX_AAVE_V3",
+            DexProtocol::DexCamelot => "DEX_CAMELOT",
+            DexProtocol::DexAerodrome => "DEX_AERODROME",
+            DexProtocol::DexQuickswap => "DEX_QUICKSWAP",
+        }
     }
-
-    fn call(&mut self, _req: http::Request<tonic::body::BoxBody>) -> Self::Future {
-        // Placeholder - tonic-build generates the proper routing
-        Box::pin(async move {
-            Ok(http::Response::builder()
-                .status(200)
-                .body(tonic::body::BoxBody::default())
-                .unwrap())
-        })
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "DEX_UNKNOWN" => Some(Self::DexUnknown),
+            "DEX_UNISWAP_V2" => Some(Self::DexUniswapV2),
+            "DEX_UNISWAP_V3" => Some(Self::DexUniswapV3),
+            "DEX_SUSHISWAP" => Some(Self::DexSushiswap),
+            "DEX_CURVE" => Some(Self::DexCurve),
+            "DEX_BALANCER" => Some(Self::DexBalancer),
+            "DEX_AAVE_V3" => Some(Self::DexAaveV3),
+            "DEX_CAMELOT" => Some(Self::DexCamelot),
+            "DEX_AERODROME" => Some(Self::DexAerodrome),
+            "DEX_QUICKSWAP" => Some(Self::DexQuickswap),
+            _ => None,
+        }
     }
 }
-
-impl<T: DefiService> Clone for DefiServiceServer<T> {
-    fn clone(&self) -> Self {
-        Self {
-            inner: self.inner.clone(),
+/// Opportunity operations
+#[derive(serde::Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum OpportunitySort {
+    /// success_probability * profit_usd
+    SortExpectedValue = 0,
+    SortProfitUsd = 1,
+    SortProfitBps = 2,
+    SortSuccessProbability = 3,
+    /// Weighted score, see ScoreWeights
+    SortScore = 4,
+}
+impl OpportunitySort {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            OpportunitySort::SortExpectedValue => "SORT_EXPECTED_VALUE",
+            OpportunitySort::SortProfitUsd => "SORT_PROFIT_USD",
+            OpportunitySort::SortProfitBps => "SORT_PROFIT_BPS",
+            OpportunitySort::SortSuccessProbability => "SORT_SUCCESS_PROBABILITY",
+            OpportunitySort::SortScore => "SORT_SCORE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "SORT_EXPECTED_VALUE" => Some(Self::SortExpectedValue),
+            "SORT_PROFIT_USD" => Some(Self::SortProfitUsd),
+            "SORT_PROFIT_BPS" => Some(Self::SortProfitBps),
+            "SORT_SUCCESS_PROBABILITY" => Some(Self::SortSuccessProbability),
+            "SORT_SCORE" => Some(Self::SortScore),
+            _ => None,
+        }
+    }
+}
+#[derive(serde::Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ExecutionStatus {
+    ExecutionUnknown = 0,
+    ExecutionPending = 1,
+    ExecutionSubmitted = 2,
+    ExecutionConfirmed = 3,
+    ExecutionFailed = 4,
+    ExecutionReverted = 5,
+    /// Dry run: simulated and built but never broadcast
+    ExecutionSimulated = 6,
+}
+impl ExecutionStatus {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ExecutionStatus::ExecutionUnknown => "EXECUTION_UNKNOWN",
+            ExecutionStatus::ExecutionPending => "EXECUTION_PENDING",
+            ExecutionStatus::ExecutionSubmitted => "EXECUTION_SUBMITTED",
+            ExecutionStatus::ExecutionConfirmed => "EXECUTION_CONFIRMED",
+            ExecutionStatus::ExecutionFailed => "EXECUTION_FAILED",
+            ExecutionStatus::ExecutionReverted => "EXECUTION_REVERTED",
+            ExecutionStatus::ExecutionSimulated => "EXECUTION_SIMULATED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "EXECUTION_UNKNOWN" => Some(Self::ExecutionUnknown),
+            "EXECUTION_PENDING" => Some(Self::ExecutionPending),
+            "EXECUTION_SUBMITTED" => Some(Self::ExecutionSubmitted),
+            "EXECUTION_CONFIRMED" => Some(Self::ExecutionConfirmed),
+            "EXECUTION_FAILED" => Some(Self::ExecutionFailed),
+            "EXECUTION_REVERTED" => Some(Self::ExecutionReverted),
+            "EXECUTION_SIMULATED" => Some(Self::ExecutionSimulated),
+            _ => None,
         }
     }
 }
-
-impl<T: DefiService> tonic::transport::NamedService for DefiServiceServer<T> {
-    const NAME: &'static str = "defi.DefiService";
+/// Generated client implementations.
+pub mod defi_service_client {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
+    #[derive(Debug, Clone)]
+    pub struct DefiServiceClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl DefiServiceClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> DefiServiceClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> DefiServiceClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+            >>::Error: Into<StdError> + Send + Sync,
+        {
+            DefiServiceClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_decoding_message_size(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_encoding_message_size(limit);
+            self
+        }
+        /// Price feed operations
+        pub async fn get_price(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetPriceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetPriceResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/defi.DefiService/GetPrice",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("defi.DefiService", "GetPrice"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn stream_prices(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StreamPricesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::PriceUpdate>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/defi.DefiService/StreamPrices",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("defi.DefiService", "StreamPrices"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        /// Quotes
+        pub async fn get_quote(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetQuoteRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetQuoteResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/defi.DefiService/GetQuote",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("defi.DefiService", "GetQuote"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Arbitrage detection
+        pub async fn get_opportunities(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetOpportunitiesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetOpportunitiesResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/defi.DefiService/GetOpportunities",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("defi.DefiService", "GetOpportunities"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn stream_opportunities(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StreamOpportunitiesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::ArbitrageOpportunity>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/defi.DefiService/StreamOpportunities",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("defi.DefiService", "StreamOpportunities"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        pub async fn replay_opportunity(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReplayOpportunityRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ReplayOpportunityResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/defi.DefiService/ReplayOpportunity",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("defi.DefiService", "ReplayOpportunity"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Trade simulation
+        pub async fn simulate_trade(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SimulateTradeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SimulateTradeResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/defi.DefiService/SimulateTrade",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("defi.DefiService", "SimulateTrade"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn simulate_route(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SimulateRouteRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SimulateRouteResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/defi.DefiService/SimulateRoute",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("defi.DefiService", "SimulateRoute"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Trade execution
+        pub async fn execute_trade(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ExecuteTradeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ExecuteTradeResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/defi.DefiService/ExecuteTrade",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("defi.DefiService", "ExecuteTrade"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_trade_status(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetTradeStatusRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetTradeStatusResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/defi.DefiService/GetTradeStatus",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("defi.DefiService", "GetTradeStatus"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// System management
+        pub async fn get_system_status(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetSystemStatusRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetSystemStatusResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/defi.DefiService/GetSystemStatus",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("defi.DefiService", "GetSystemStatus"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn health(
+            &mut self,
+            request: impl tonic::IntoRequest<super::HealthRequest>,
+        ) -> std::result::Result<tonic::Response<super::HealthResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/defi.DefiService/Health");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("defi.DefiService", "Health"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn update_config(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateConfigRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::UpdateConfigResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/defi.DefiService/UpdateConfig",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("defi.DefiService", "UpdateConfig"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn start_scanner(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StartScannerRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::StartScannerResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/defi.DefiService/StartScanner",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("defi.DefiService", "StartScanner"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn stop_scanner(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StopScannerRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::StopScannerResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/defi.DefiService/StopScanner",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("defi.DefiService", "StopScanner"));
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod defi_service_server {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with DefiServiceServer.
+    #[async_trait]
+    pub trait DefiService: Send + Sync + 'static {
+        /// Price feed operations
+        async fn get_price(
+            &self,
+            request: tonic::Request<super::GetPriceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetPriceResponse>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the StreamPrices method.
+        type StreamPricesStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::PriceUpdate, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        async fn stream_prices(
+            &self,
+            request: tonic::Request<super::StreamPricesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::StreamPricesStream>,
+            tonic::Status,
+        >;
+        /// Quotes
+        async fn get_quote(
+            &self,
+            request: tonic::Request<super::GetQuoteRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetQuoteResponse>,
+            tonic::Status,
+        >;
+        /// Arbitrage detection
+        async fn get_opportunities(
+            &self,
+            request: tonic::Request<super::GetOpportunitiesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetOpportunitiesResponse>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the StreamOpportunities method.
+        type StreamOpportunitiesStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::ArbitrageOpportunity, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        async fn stream_opportunities(
+            &self,
+            request: tonic::Request<super::StreamOpportunitiesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::StreamOpportunitiesStream>,
+            tonic::Status,
+        >;
+        async fn replay_opportunity(
+            &self,
+            request: tonic::Request<super::ReplayOpportunityRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ReplayOpportunityResponse>,
+            tonic::Status,
+        >;
+        /// Trade simulation
+        async fn simulate_trade(
+            &self,
+            request: tonic::Request<super::SimulateTradeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SimulateTradeResponse>,
+            tonic::Status,
+        >;
+        async fn simulate_route(
+            &self,
+            request: tonic::Request<super::SimulateRouteRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SimulateRouteResponse>,
+            tonic::Status,
+        >;
+        /// Trade execution
+        async fn execute_trade(
+            &self,
+            request: tonic::Request<super::ExecuteTradeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ExecuteTradeResponse>,
+            tonic::Status,
+        >;
+        async fn get_trade_status(
+            &self,
+            request: tonic::Request<super::GetTradeStatusRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetTradeStatusResponse>,
+            tonic::Status,
+        >;
+        /// System management
+        async fn get_system_status(
+            &self,
+            request: tonic::Request<super::GetSystemStatusRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetSystemStatusResponse>,
+            tonic::Status,
+        >;
+        async fn health(
+            &self,
+            request: tonic::Request<super::HealthRequest>,
+        ) -> std::result::Result<tonic::Response<super::HealthResponse>, tonic::Status>;
+        async fn update_config(
+            &self,
+            request: tonic::Request<super::UpdateConfigRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::UpdateConfigResponse>,
+            tonic::Status,
+        >;
+        async fn start_scanner(
+            &self,
+            request: tonic::Request<super::StartScannerRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::StartScannerResponse>,
+            tonic::Status,
+        >;
+        async fn stop_scanner(
+            &self,
+            request: tonic::Request<super::StopScannerRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::StopScannerResponse>,
+            tonic::Status,
+        >;
+    }
+    #[derive(Debug)]
+    pub struct DefiServiceServer<T: DefiService> {
+        inner: _Inner<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    struct _Inner<T>(Arc<T>);
+    impl<T: DefiService> DefiServiceServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            let inner = _Inner(inner);
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+                max_decoding_message_size: None,
+                max_encoding_message_size: None,
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.max_decoding_message_size = Some(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.max_encoding_message_size = Some(limit);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for DefiServiceServer<T>
+    where
+        T: DefiService,
+        B: Body + Send + 'static,
+        B::Error: Into<StdError> + Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            let inner = self.inner.clone();
+            match req.uri().path() {
+                "/defi.DefiService/GetPrice" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetPriceSvc<T: DefiService>(pub Arc<T>);
+                    impl<
+                        T: DefiService,
+                    > tonic::server::UnaryService<super::GetPriceRequest>
+                    for GetPriceSvc<T> {
+                        type Response = super::GetPriceResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetPriceRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DefiService>::get_price(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetPriceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/defi.DefiService/StreamPrices" => {
+                    #[allow(non_camel_case_types)]
+                    struct StreamPricesSvc<T: DefiService>(pub Arc<T>);
+                    impl<
+                        T: DefiService,
+                    > tonic::server::ServerStreamingService<super::StreamPricesRequest>
+                    for StreamPricesSvc<T> {
+                        type Response = super::PriceUpdate;
+                        type ResponseStream = T::StreamPricesStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::StreamPricesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DefiService>::stream_prices(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = StreamPricesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/defi.DefiService/GetQuote" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetQuoteSvc<T: DefiService>(pub Arc<T>);
+                    impl<
+                        T: DefiService,
+                    > tonic::server::UnaryService<super::GetQuoteRequest>
+                    for GetQuoteSvc<T> {
+                        type Response = super::GetQuoteResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetQuoteRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DefiService>::get_quote(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetQuoteSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/defi.DefiService/GetOpportunities" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetOpportunitiesSvc<T: DefiService>(pub Arc<T>);
+                    impl<
+                        T: DefiService,
+                    > tonic::server::UnaryService<super::GetOpportunitiesRequest>
+                    for GetOpportunitiesSvc<T> {
+                        type Response = super::GetOpportunitiesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetOpportunitiesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DefiService>::get_opportunities(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetOpportunitiesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/defi.DefiService/StreamOpportunities" => {
+                    #[allow(non_camel_case_types)]
+                    struct StreamOpportunitiesSvc<T: DefiService>(pub Arc<T>);
+                    impl<
+                        T: DefiService,
+                    > tonic::server::ServerStreamingService<
+                        super::StreamOpportunitiesRequest,
+                    > for StreamOpportunitiesSvc<T> {
+                        type Response = super::ArbitrageOpportunity;
+                        type ResponseStream = T::StreamOpportunitiesStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::StreamOpportunitiesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DefiService>::stream_opportunities(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = StreamOpportunitiesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/defi.DefiService/ReplayOpportunity" => {
+                    #[allow(non_camel_case_types)]
+                    struct ReplayOpportunitySvc<T: DefiService>(pub Arc<T>);
+                    impl<
+                        T: DefiService,
+                    > tonic::server::UnaryService<super::ReplayOpportunityRequest>
+                    for ReplayOpportunitySvc<T> {
+                        type Response = super::ReplayOpportunityResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReplayOpportunityRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DefiService>::replay_opportunity(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ReplayOpportunitySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/defi.DefiService/SimulateTrade" => {
+                    #[allow(non_camel_case_types)]
+                    struct SimulateTradeSvc<T: DefiService>(pub Arc<T>);
+                    impl<
+                        T: DefiService,
+                    > tonic::server::UnaryService<super::SimulateTradeRequest>
+                    for SimulateTradeSvc<T> {
+                        type Response = super::SimulateTradeResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SimulateTradeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DefiService>::simulate_trade(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SimulateTradeSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/defi.DefiService/SimulateRoute" => {
+                    #[allow(non_camel_case_types)]
+                    struct SimulateRouteSvc<T: DefiService>(pub Arc<T>);
+                    impl<
+                        T: DefiService,
+                    > tonic::server::UnaryService<super::SimulateRouteRequest>
+                    for SimulateRouteSvc<T> {
+                        type Response = super::SimulateRouteResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SimulateRouteRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DefiService>::simulate_route(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SimulateRouteSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/defi.DefiService/ExecuteTrade" => {
+                    #[allow(non_camel_case_types)]
+                    struct ExecuteTradeSvc<T: DefiService>(pub Arc<T>);
+                    impl<
+                        T: DefiService,
+                    > tonic::server::UnaryService<super::ExecuteTradeRequest>
+                    for ExecuteTradeSvc<T> {
+                        type Response = super::ExecuteTradeResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ExecuteTradeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DefiService>::execute_trade(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ExecuteTradeSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/defi.DefiService/GetTradeStatus" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetTradeStatusSvc<T: DefiService>(pub Arc<T>);
+                    impl<
+                        T: DefiService,
+                    > tonic::server::UnaryService<super::GetTradeStatusRequest>
+                    for GetTradeStatusSvc<T> {
+                        type Response = super::GetTradeStatusResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetTradeStatusRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DefiService>::get_trade_status(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetTradeStatusSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/defi.DefiService/GetSystemStatus" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetSystemStatusSvc<T: DefiService>(pub Arc<T>);
+                    impl<
+                        T: DefiService,
+                    > tonic::server::UnaryService<super::GetSystemStatusRequest>
+                    for GetSystemStatusSvc<T> {
+                        type Response = super::GetSystemStatusResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetSystemStatusRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DefiService>::get_system_status(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetSystemStatusSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/defi.DefiService/Health" => {
+                    #[allow(non_camel_case_types)]
+                    struct HealthSvc<T: DefiService>(pub Arc<T>);
+                    impl<
+                        T: DefiService,
+                    > tonic::server::UnaryService<super::HealthRequest>
+                    for HealthSvc<T> {
+                        type Response = super::HealthResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::HealthRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DefiService>::health(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = HealthSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/defi.DefiService/UpdateConfig" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpdateConfigSvc<T: DefiService>(pub Arc<T>);
+                    impl<
+                        T: DefiService,
+                    > tonic::server::UnaryService<super::UpdateConfigRequest>
+                    for UpdateConfigSvc<T> {
+                        type Response = super::UpdateConfigResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateConfigRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DefiService>::update_config(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = UpdateConfigSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/defi.DefiService/StartScanner" => {
+                    #[allow(non_camel_case_types)]
+                    struct StartScannerSvc<T: DefiService>(pub Arc<T>);
+                    impl<
+                        T: DefiService,
+                    > tonic::server::UnaryService<super::StartScannerRequest>
+                    for StartScannerSvc<T> {
+                        type Response = super::StartScannerResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::StartScannerRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DefiService>::start_scanner(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = StartScannerSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/defi.DefiService/StopScanner" => {
+                    #[allow(non_camel_case_types)]
+                    struct StopScannerSvc<T: DefiService>(pub Arc<T>);
+                    impl<
+                        T: DefiService,
+                    > tonic::server::UnaryService<super::StopScannerRequest>
+                    for StopScannerSvc<T> {
+                        type Response = super::StopScannerResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::StopScannerRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as DefiService>::stop_scanner(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = StopScannerSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => {
+                    Box::pin(async move {
+                        Ok(
+                            http::Response::builder()
+                                .status(200)
+                                .header("grpc-status", "12")
+                                .header("content-type", "application/grpc")
+                                .body(empty_body())
+                                .unwrap(),
+                        )
+                    })
+                }
+            }
+        }
+    }
+    impl<T: DefiService> Clone for DefiServiceServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self {
+                inner,
+                accept_compression_encodings: self.accept_compression_encodings,
+                send_compression_encodings: self.send_compression_encodings,
+                max_decoding_message_size: self.max_decoding_message_size,
+                max_encoding_message_size: self.max_encoding_message_size,
+            }
+        }
+    }
+    impl<T: DefiService> Clone for _Inner<T> {
+        fn clone(&self) -> Self {
+            Self(Arc::clone(&self.0))
+        }
+    }
+    impl<T: std::fmt::Debug> std::fmt::Debug for _Inner<T> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+    impl<T: DefiService> tonic::server::NamedService for DefiServiceServer<T> {
+        const NAME: &'static str = "defi.DefiService";
+    }
 }
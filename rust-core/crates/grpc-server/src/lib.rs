@@ -5,11 +5,18 @@
 pub mod server;
 pub mod service;
 pub mod conversions;
+pub mod errors;
+pub mod rest;
+pub mod audit;
+pub mod runtime;
 
 // Re-export proto types
 pub mod proto {
     include!("generated/defi.rs");
 }
 
+pub use audit::{AuditEvent, AuditLogger, AuditOutcome, AuditRecord};
+pub use rest::{RestGateway, RestGatewayConfig};
+pub use runtime::{build_runtime, RuntimeConfig};
 pub use server::{GrpcServer, GrpcServerConfig};
 pub use service::DefiServiceImpl;
@@ -3,22 +3,31 @@
 //! Main entry point for the gRPC server
 
 use std::env;
+use std::sync::Arc;
 use std::time::Duration;
 
 use tokio::signal;
-use tracing::{error, info, Level};
+use tracing::{error, info, warn, Level};
 use tracing_subscriber::{fmt, EnvFilter};
 
 use defi_grpc_server::{
-    GrpcServer, GrpcServerConfig, DefiServiceImpl,
+    build_runtime, AuditLogger, GrpcServer, GrpcServerConfig, DefiServiceImpl, RestGateway,
+    RestGatewayConfig, RuntimeConfig,
 };
-use defi_price_feed::AggregatorConfig;
+use alloy_primitives::Address;
+use defi_core::{ChainId, ExecutionConfig};
+use defi_executor::{EvmSimulator, RepriceConfig, RpcBlockSource, SubmitterConfig};
+use defi_price_feed::{AggregatorConfig, MempoolConfig};
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    // Load .env file
+fn main() -> anyhow::Result<()> {
+    // Load .env file before anything reads the environment
     dotenvy::dotenv().ok();
 
+    let runtime_config = RuntimeConfig::from_env()?;
+    build_runtime(&runtime_config)?.block_on(run(runtime_config))
+}
+
+async fn run(runtime_config: RuntimeConfig) -> anyhow::Result<()> {
     // Initialize logging
     let filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("info"));
@@ -32,6 +41,10 @@ async fn main() -> anyhow::Result<()> {
         .init();
 
     info!("Starting DeFi Bot Rust Core v{}", env!("CARGO_PKG_VERSION"));
+    match runtime_config.worker_threads {
+        Some(threads) => info!("Async runtime running {} worker threads", threads),
+        None => info!("Async runtime running one worker thread per core"),
+    }
 
     // Load configuration
     let host = env::var("GRPC_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
@@ -39,15 +52,136 @@ async fn main() -> anyhow::Result<()> {
         .unwrap_or_else(|_| "50051".to_string())
         .parse()
         .unwrap_or(50051);
+    let rest_port: u16 = env::var("REST_PORT")
+        .unwrap_or_else(|_| "8080".to_string())
+        .parse()
+        .unwrap_or(8080);
+    let max_message_bytes: usize = env::var("GRPC_MAX_MESSAGE_BYTES")
+        .unwrap_or_else(|_| "4194304".to_string())
+        .parse()
+        .unwrap_or(4 * 1024 * 1024);
+
+    // Pending transactions feed `competing_txs` when a node is given
+    let mempool = env::var("MEMPOOL_WS_URL").ok().map(|ws_url| {
+        info!("Monitoring mempool at {}", ws_url);
+        MempoolConfig {
+            ws_url,
+            ..Default::default()
+        }
+    });
 
     // Create service with aggregator
     let aggregator_config = AggregatorConfig {
         cleanup_interval: Duration::from_secs(60),
         max_price_age: Duration::from_secs(30),
+        mempool,
         ..Default::default()
     };
+    // Fail fast on DEXes configured for chains they aren't deployed on
+    aggregator_config.validate()?;
 
-    let service = DefiServiceImpl::with_config(aggregator_config);
+    let execution = ExecutionConfig {
+        max_concurrent_simulations: env::var("MAX_CONCURRENT_SIMULATIONS")
+            .unwrap_or_else(|_| "8".to_string())
+            .parse()
+            .unwrap_or(8),
+        // Simulate and build trades as usual but never broadcast them
+        dry_run: env::var("DRY_RUN")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false),
+        ..Default::default()
+    };
+    if execution.dry_run {
+        info!("Dry-run mode: trades will not be broadcast");
+    }
+
+    let drain_timeout_secs: u64 = env::var("DRAIN_TIMEOUT_SECS")
+        .unwrap_or_else(|_| "30".to_string())
+        .parse()
+        .unwrap_or(30);
+
+    // Audit records go to their own append-only file when configured
+    let audit = match env::var("AUDIT_LOG_PATH") {
+        Ok(path) => {
+            info!("Writing audit log to {}", path);
+            AuditLogger::new().with_file(path)?
+        }
+        Err(_) => AuditLogger::new(),
+    };
+
+    // Trades are pre-flighted on every chain with a node to fork from, and
+    // refused on the others unless pre-flight is switched off
+    let preflight_required = env::var("PREFLIGHT_SIMULATION")
+        .map(|v| !(v == "0" || v.eq_ignore_ascii_case("false")))
+        .unwrap_or(true);
+    let router: Address = match env::var("ROUTER_ADDRESS") {
+        Ok(address) => address.parse()?,
+        Err(_) => Address::ZERO,
+    };
+    if !preflight_required {
+        if router == Address::ZERO {
+            warn!("Pre-flight simulation disabled, but without ROUTER_ADDRESS trades on chains without a simulator are still refused");
+        } else {
+            warn!("Pre-flight simulation disabled: trades on chains without a simulator are sent to {} unchecked", router);
+        }
+    }
+
+    // Trades are sent on Ethereum, priced at its node's current fees and
+    // tracked to their receipts through it. Public-mempool sends are
+    // repriced every REPRICE_BLOCKS_PER_STEP blocks until included when set.
+    let submitter_config = SubmitterConfig {
+        rpc_url: env::var("SUBMIT_RPC_URL")
+            .or_else(|_| env::var("ETH_RPC_URL"))
+            .unwrap_or_default(),
+        from: match env::var("SENDER_ADDRESS") {
+            Ok(address) => address.parse()?,
+            Err(_) => Address::ZERO,
+        },
+        reprice: env::var("REPRICE_BLOCKS_PER_STEP")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(|blocks_per_step| RepriceConfig {
+                blocks_per_step,
+                ..Default::default()
+            }),
+        ..Default::default()
+    }
+    .with_execution_config(&execution);
+    if submitter_config.rpc_url.is_empty() {
+        warn!("No SUBMIT_RPC_URL or ETH_RPC_URL: trades are priced at detection-time gas and receipts are never fetched");
+    }
+
+    let mut service = DefiServiceImpl::with_config(aggregator_config)
+        .with_max_concurrent_simulations(execution.max_concurrent_simulations)
+        .with_preflight_required(preflight_required)
+        .with_router(router)
+        .with_drain_timeout(Duration::from_secs(drain_timeout_secs))
+        .with_submitter_config(submitter_config)
+        .with_audit_logger(audit);
+
+    // Execute what the scanner queues without waiting for execute_trade
+    if let Ok(delegation_id) = env::var("AUTO_EXECUTE_DELEGATION") {
+        info!("Auto-executing queued opportunities under delegation {}", delegation_id);
+        service = service.with_auto_execute(delegation_id);
+    }
+
+    for (chain, rpc_var) in [
+        (ChainId::Ethereum, "ETH_RPC_URL"),
+        (ChainId::Arbitrum, "ARBITRUM_RPC_URL"),
+        (ChainId::Base, "BASE_RPC_URL"),
+        (ChainId::Polygon, "POLYGON_RPC_URL"),
+    ] {
+        let Ok(rpc_url) = env::var(rpc_var) else {
+            continue;
+        };
+        info!("Simulating {} trades against {}", chain, rpc_url);
+        let head = RpcBlockSource::spawn(rpc_url, Duration::from_millis(chain.block_time_ms()));
+        service = service.with_simulator(
+            EvmSimulator::new(chain)
+                .with_router(router)
+                .with_block_source(Arc::new(head)),
+        );
+    }
 
     // Start background services
     service.start().await?;
@@ -61,12 +195,23 @@ async fn main() -> anyhow::Result<()> {
         keep_alive_interval: Duration::from_secs(60),
         keep_alive_timeout: Duration::from_secs(20),
         accept_http1: true,
+        max_decoding_message_size: max_message_bytes,
+        ..Default::default()
     };
 
-    let server = GrpcServer::with_service(server_config, service);
+    let rest_gateway = RestGateway::new(
+        RestGatewayConfig {
+            host: server_config.host.clone(),
+            port: rest_port,
+        },
+        service.clone(),
+    );
 
-    // Setup shutdown channel
+    let server = GrpcServer::with_service(server_config, service.clone());
+
+    // Setup shutdown channels
     let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+    let (rest_shutdown_tx, rest_shutdown_rx) = tokio::sync::oneshot::channel();
 
     // Spawn shutdown signal handler
     tokio::spawn(async move {
@@ -97,6 +242,15 @@ async fn main() -> anyhow::Result<()> {
         }
 
         let _ = shutdown_tx.send(());
+        let _ = rest_shutdown_tx.send(());
+    });
+
+    // Start REST gateway
+    info!("REST gateway listening on {}", rest_gateway.address());
+    tokio::spawn(async move {
+        if let Err(e) = rest_gateway.start_with_shutdown(rest_shutdown_rx).await {
+            error!("REST gateway error: {}", e);
+        }
     });
 
     // Start server
@@ -108,6 +262,9 @@ async fn main() -> anyhow::Result<()> {
         return Err(e);
     }
 
+    // Let in-flight trades settle before feeds go away
+    service.stop().await;
+
     info!("Server shutdown complete");
     Ok(())
 }
@@ -0,0 +1,264 @@
+//! JSON REST gateway for clients that can't speak gRPC
+//!
+//! Each route calls the same `DefiServiceImpl` method as its gRPC
+//! counterpart and returns the proto response serialized as JSON.
+
+use std::net::SocketAddr;
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response as HttpResponse};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Deserialize;
+use tonic::Request;
+use tracing::info;
+
+use defi_core::ChainId;
+
+use crate::proto::*;
+use crate::proto::defi_service_server::DefiService;
+use crate::service::DefiServiceImpl;
+
+/// REST gateway configuration
+#[derive(Debug, Clone)]
+pub struct RestGatewayConfig {
+    pub host: String,
+    pub port: u16,
+}
+
+impl Default for RestGatewayConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_string(),
+            port: 8080,
+        }
+    }
+}
+
+/// Query parameters for `GET /price`
+#[derive(Debug, Deserialize)]
+pub struct PriceQuery {
+    pub token: String,
+    pub chain: Option<String>,
+}
+
+/// Query parameters for `GET /opportunities`
+#[derive(Debug, Deserialize)]
+pub struct OpportunitiesQuery {
+    /// Comma separated chain names or ids
+    pub chains: Option<String>,
+    pub min_profit_usd: Option<f64>,
+    pub min_confidence: Option<f64>,
+    pub limit: Option<i32>,
+    /// expected_value (default), profit_usd, profit_bps, success_probability
+    /// or score, which ranks with the server's score weights
+    pub sort: Option<String>,
+    /// Only return opportunities that simulate profitably
+    pub simulate: Option<bool>,
+}
+
+/// Gateway error rendered as `{"error": "..."}`
+pub struct RestError {
+    status: StatusCode,
+    message: String,
+}
+
+impl RestError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<tonic::Status> for RestError {
+    fn from(status: tonic::Status) -> Self {
+        use tonic::Code;
+
+        let http_status = match status.code() {
+            Code::InvalidArgument | Code::FailedPrecondition => StatusCode::BAD_REQUEST,
+            Code::NotFound => StatusCode::NOT_FOUND,
+            Code::PermissionDenied => StatusCode::FORBIDDEN,
+            Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+            Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+            Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        Self {
+            status: http_status,
+            message: status.message().to_string(),
+        }
+    }
+}
+
+impl IntoResponse for RestError {
+    fn into_response(self) -> HttpResponse {
+        (self.status, Json(serde_json::json!({ "error": self.message }))).into_response()
+    }
+}
+
+/// Parse a chain given by name ("arbitrum") or proto id ("2")
+fn parse_chain(value: &str) -> Result<Chain, RestError> {
+    let value = value.trim();
+
+    if let Ok(id) = value.parse::<i32>() {
+        return match Chain::try_from(id) {
+            Ok(Chain::Unknown) | Err(_) => Err(RestError::bad_request(format!("Unknown chain: {}", value))),
+            Ok(chain) => Ok(chain),
+        };
+    }
+
+    [ChainId::Ethereum, ChainId::Arbitrum, ChainId::Base, ChainId::Polygon]
+        .into_iter()
+        .find(|c| c.name().eq_ignore_ascii_case(value))
+        .map(Chain::from)
+        .ok_or_else(|| RestError::bad_request(format!("Unknown chain: {}", value)))
+}
+
+fn parse_sort(value: &str) -> Result<OpportunitySort, RestError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "expected_value" => Ok(OpportunitySort::SortExpectedValue),
+        "profit_usd" => Ok(OpportunitySort::SortProfitUsd),
+        "profit_bps" => Ok(OpportunitySort::SortProfitBps),
+        "success_probability" => Ok(OpportunitySort::SortSuccessProbability),
+        "score" => Ok(OpportunitySort::SortScore),
+        _ => Err(RestError::bad_request(format!("Unknown sort: {}", value))),
+    }
+}
+
+async fn get_price(
+    State(service): State<DefiServiceImpl>,
+    Query(query): Query<PriceQuery>,
+) -> Result<Json<GetPriceResponse>, RestError> {
+    let chain = match query.chain.as_deref() {
+        Some(chain) => parse_chain(chain)?,
+        None => Chain::Ethereum,
+    };
+
+    let response = service
+        .get_price(Request::new(GetPriceRequest {
+            token_address: query.token,
+            chain: chain as i32,
+        }))
+        .await?;
+
+    Ok(Json(response.into_inner()))
+}
+
+async fn get_opportunities(
+    State(service): State<DefiServiceImpl>,
+    Query(query): Query<OpportunitiesQuery>,
+) -> Result<Json<GetOpportunitiesResponse>, RestError> {
+    let chains = query.chains
+        .as_deref()
+        .map(|chains| {
+            chains
+                .split(',')
+                .filter(|c| !c.trim().is_empty())
+                .map(|c| parse_chain(c).map(|c| c as i32))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    let sort = query.sort
+        .as_deref()
+        .map(parse_sort)
+        .transpose()?
+        .unwrap_or(OpportunitySort::SortExpectedValue);
+
+    let response = service
+        .get_opportunities(Request::new(GetOpportunitiesRequest {
+            chains,
+            min_profit_usd: query.min_profit_usd.unwrap_or(0.0),
+            min_confidence: query.min_confidence.unwrap_or(0.0),
+            limit: query.limit.unwrap_or(0),
+            sort_by: sort as i32,
+            simulate: query.simulate.unwrap_or(false),
+            score_weights: None,
+        }))
+        .await?;
+
+    Ok(Json(response.into_inner()))
+}
+
+async fn get_status(
+    State(service): State<DefiServiceImpl>,
+) -> Result<Json<GetSystemStatusResponse>, RestError> {
+    let response = service
+        .get_system_status(Request::new(GetSystemStatusRequest {}))
+        .await?;
+
+    Ok(Json(response.into_inner()))
+}
+
+/// Build the gateway routes over a service handle
+pub fn router(service: DefiServiceImpl) -> Router {
+    Router::new()
+        .route("/price", get(get_price))
+        .route("/opportunities", get(get_opportunities))
+        .route("/status", get(get_status))
+        .with_state(service)
+}
+
+/// REST gateway running alongside the gRPC server
+pub struct RestGateway {
+    config: RestGatewayConfig,
+    service: DefiServiceImpl,
+}
+
+impl RestGateway {
+    pub fn new(config: RestGatewayConfig, service: DefiServiceImpl) -> Self {
+        Self { config, service }
+    }
+
+    /// Get gateway address
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.config.host, self.config.port)
+    }
+
+    /// Serve until the shutdown signal fires
+    pub async fn start_with_shutdown(
+        &self,
+        shutdown: tokio::sync::oneshot::Receiver<()>,
+    ) -> anyhow::Result<()> {
+        let addr: SocketAddr = self.address().parse()?;
+
+        info!("Starting REST gateway on {}", addr);
+
+        axum::Server::bind(&addr)
+            .serve(router(self.service.clone()).into_make_service())
+            .with_graceful_shutdown(async {
+                shutdown.await.ok();
+            })
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chain() {
+        assert_eq!(parse_chain("arbitrum").ok(), Some(Chain::Arbitrum));
+        assert_eq!(parse_chain("Base").ok(), Some(Chain::Base));
+        assert_eq!(parse_chain("4").ok(), Some(Chain::Polygon));
+        assert!(parse_chain("0").is_err());
+        assert!(parse_chain("solana").is_err());
+    }
+
+    #[test]
+    fn test_parse_sort() {
+        assert_eq!(parse_sort("profit_bps").ok(), Some(OpportunitySort::SortProfitBps));
+        assert_eq!(parse_sort("Expected_Value").ok(), Some(OpportunitySort::SortExpectedValue));
+        assert_eq!(parse_sort("score").ok(), Some(OpportunitySort::SortScore));
+        assert!(parse_sort("newest").is_err());
+    }
+}
@@ -0,0 +1,89 @@
+//! Tokio runtime for the server process
+//!
+//! The runtime is built explicitly rather than through `#[tokio::main]` so
+//! operators can split cores between async workers and the rayon pool the
+//! scanner evaluates strategies on, which sizes itself from
+//! `RAYON_NUM_THREADS`.
+
+use std::env;
+
+use anyhow::{bail, Context};
+use tokio::runtime::{Builder, Runtime};
+
+/// Environment variable holding the async worker count
+pub const WORKER_THREADS_ENV: &str = "WORKER_THREADS";
+
+/// How the server's runtime is built
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuntimeConfig {
+    /// Async worker threads. None starts one per core.
+    pub worker_threads: Option<usize>,
+}
+
+impl RuntimeConfig {
+    /// Read `WORKER_THREADS`, unset keeping one worker per core
+    pub fn from_env() -> anyhow::Result<Self> {
+        let worker_threads = match env::var(WORKER_THREADS_ENV) {
+            Ok(value) => parse_worker_threads(&value)
+                .with_context(|| format!("Invalid {}", WORKER_THREADS_ENV))?,
+            Err(_) => None,
+        };
+        Ok(Self { worker_threads })
+    }
+}
+
+/// Worker count from its configured value. Empty or `auto` means one per
+/// core; zero is rejected since the runtime needs at least one worker.
+pub fn parse_worker_threads(value: &str) -> anyhow::Result<Option<usize>> {
+    let value = value.trim();
+    if value.is_empty() || value.eq_ignore_ascii_case("auto") {
+        return Ok(None);
+    }
+
+    let threads: usize = value
+        .parse()
+        .with_context(|| format!("{:?} is not a thread count", value))?;
+    if threads == 0 {
+        bail!("worker thread count must be at least 1");
+    }
+    Ok(Some(threads))
+}
+
+/// Multi-thread runtime with IO and timers enabled
+pub fn build_runtime(config: &RuntimeConfig) -> std::io::Result<Runtime> {
+    let mut builder = Builder::new_multi_thread();
+    builder.enable_all().thread_name("defi-bot-worker");
+    if let Some(threads) = config.worker_threads {
+        builder.worker_threads(threads);
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_worker_threads() {
+        assert_eq!(parse_worker_threads("4").unwrap(), Some(4));
+        assert_eq!(parse_worker_threads(" 12 ").unwrap(), Some(12));
+        assert_eq!(parse_worker_threads("").unwrap(), None);
+        assert_eq!(parse_worker_threads("AUTO").unwrap(), None);
+
+        assert!(parse_worker_threads("0").is_err());
+        assert!(parse_worker_threads("-2").is_err());
+        assert!(parse_worker_threads("four").is_err());
+    }
+
+    #[test]
+    fn test_build_runtime_with_fixed_workers() {
+        let runtime = build_runtime(&RuntimeConfig { worker_threads: Some(2) }).unwrap();
+
+        let name = runtime.block_on(async {
+            tokio::spawn(async { std::thread::current().name().map(str::to_string) })
+                .await
+                .unwrap()
+        });
+        assert_eq!(name.as_deref(), Some("defi-bot-worker"));
+    }
+}
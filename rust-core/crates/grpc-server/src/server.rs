@@ -7,7 +7,7 @@ use std::time::Duration;
 use tonic::transport::Server;
 use tracing::{error, info};
 
-use crate::proto::DefiServiceServer;
+use crate::proto::defi_service_server::DefiServiceServer;
 use crate::service::DefiServiceImpl;
 
 /// Server configuration
@@ -19,6 +19,10 @@ pub struct GrpcServerConfig {
     pub keep_alive_interval: Duration,
     pub keep_alive_timeout: Duration,
     pub accept_http1: bool,
+    /// Largest request message, in bytes, the server will decode
+    pub max_decoding_message_size: usize,
+    /// Largest response message, in bytes, the server will encode
+    pub max_encoding_message_size: usize,
 }
 
 impl Default for GrpcServerConfig {
@@ -30,6 +34,9 @@ impl Default for GrpcServerConfig {
             keep_alive_interval: Duration::from_secs(60),
             keep_alive_timeout: Duration::from_secs(20),
             accept_http1: true, // For grpc-web compatibility
+            max_decoding_message_size: 4 * 1024 * 1024,
+            // Leaves room for price and opportunity lists across every chain
+            max_encoding_message_size: 64 * 1024 * 1024,
         }
     }
 }
@@ -60,6 +67,13 @@ impl GrpcServer {
         &self.service
     }
 
+    /// Service wrapped for tonic, with the configured message size limits
+    fn service_server(&self) -> DefiServiceServer<DefiServiceImpl> {
+        DefiServiceServer::new((*self.service).clone())
+            .max_decoding_message_size(self.config.max_decoding_message_size)
+            .max_encoding_message_size(self.config.max_encoding_message_size)
+    }
+
     /// Start the server
     pub async fn start(&self) -> anyhow::Result<()> {
         let addr: SocketAddr = format!("{}:{}", self.config.host, self.config.port)
@@ -68,7 +82,7 @@ impl GrpcServer {
         info!("Starting gRPC server on {}", addr);
 
         // Create the service
-        let service = DefiServiceServer::new((*self.service).clone());
+        let service = self.service_server();
 
         // Build and run the server
         Server::builder()
@@ -93,7 +107,7 @@ impl GrpcServer {
 
         info!("Starting gRPC server on {} (with graceful shutdown)", addr);
 
-        let service = DefiServiceServer::new((*self.service).clone());
+        let service = self.service_server();
 
         Server::builder()
             .concurrency_limit_per_connection(256)
@@ -160,6 +174,16 @@ impl GrpcServerBuilder {
         self
     }
 
+    pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+        self.config.max_decoding_message_size = limit;
+        self
+    }
+
+    pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+        self.config.max_encoding_message_size = limit;
+        self
+    }
+
     pub fn service(mut self, service: DefiServiceImpl) -> Self {
         self.service = Some(service);
         self
@@ -200,4 +224,83 @@ mod tests {
 
         assert_eq!(server.address(), "0.0.0.0:9000");
     }
+
+    /// Serve `server` on a free local port and connect to it
+    async fn serve_locally(server: GrpcServer) -> tonic::transport::Channel {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = futures::stream::unfold(listener, |listener| async move {
+            let accepted = listener.accept().await.map(|(stream, _)| stream);
+            Some((accepted, listener))
+        });
+        tokio::spawn(
+            Server::builder()
+                .add_service(server.service_server())
+                .serve_with_incoming(incoming),
+        );
+
+        tonic::transport::Endpoint::from_shared(format!("http://{}", addr))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap()
+    }
+
+    async fn unary<Req, Resp>(
+        channel: &tonic::transport::Channel,
+        path: &'static str,
+        request: Req,
+    ) -> Result<Resp, tonic::Status>
+    where
+        Req: prost::Message + Send + Sync + 'static,
+        Resp: prost::Message + Default + Send + Sync + 'static,
+    {
+        let mut grpc = tonic::client::Grpc::new(channel.clone());
+        grpc.ready().await.map_err(|e| tonic::Status::unavailable(e.to_string()))?;
+        let codec = tonic::codec::ProstCodec::<Req, Resp>::default();
+        grpc.unary(tonic::Request::new(request), http::uri::PathAndQuery::from_static(path), codec)
+            .await
+            .map(tonic::Response::into_inner)
+    }
+
+    #[tokio::test]
+    async fn test_max_decoding_message_size() {
+        use crate::proto::{GetPriceRequest, GetPriceResponse};
+
+        let request = GetPriceRequest {
+            token_address: format!("0x{}", "ab".repeat(64)),
+            chain: crate::proto::Chain::Ethereum as i32,
+        };
+        let get_price = |channel: tonic::transport::Channel, request: GetPriceRequest| async move {
+            unary::<_, GetPriceResponse>(&channel, "/defi.DefiService/GetPrice", request).await
+        };
+
+        // Decoded and answered: there's just no price for the token
+        let channel = serve_locally(GrpcServerBuilder::new().build()).await;
+        let status = get_price(channel, request.clone()).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+
+        let channel = serve_locally(GrpcServerBuilder::new().max_decoding_message_size(64).build()).await;
+        let status = get_price(channel, request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::OutOfRange, "{}", status.message());
+    }
+
+    #[tokio::test]
+    async fn test_max_encoding_message_size() {
+        use crate::proto::{GetSystemStatusRequest, GetSystemStatusResponse};
+
+        let get_status = |channel: tonic::transport::Channel| async move {
+            unary::<_, GetSystemStatusResponse>(&channel, "/defi.DefiService/GetSystemStatus", GetSystemStatusRequest {})
+                .await
+        };
+
+        let channel = serve_locally(GrpcServerBuilder::new().build()).await;
+        // A fresh service's status carries little more than `success`
+        let response = get_status(channel).await.unwrap();
+        assert!(prost::Message::encoded_len(&response) > 1);
+
+        let channel = serve_locally(GrpcServerBuilder::new().max_encoding_message_size(1).build()).await;
+        let status = get_status(channel).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::OutOfRange, "{}", status.message());
+    }
 }
@@ -1,40 +1,494 @@
 //! gRPC service implementation
 
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use futures::Stream;
 use parking_lot::RwLock;
 use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Status};
 use tracing::{debug, error, info, warn};
 
-use defi_core::ChainId;
-use defi_detector::{ArbitrageScanner, ScannerConfig};
-use defi_executor::{TransactionSubmitter, SubmitterConfig};
-use defi_price_feed::{PriceAggregator, AggregatorConfig, PriceState};
-
+use alloy_primitives::{Address, U256};
+use defi_core::{
+    get_decimals, get_token_by_address, hop_overhead, opportunity_gas_units, ChainId, CoreError,
+    ExecutionError, GasPrice, QuoteRequest,
+};
+use defi_detector::{ArbitrageScanner, OpportunityQueue, QuoteEngine, RouteOptimizer, ScannerConfig};
+use defi_executor::{
+    EvmSimulator, SimulationPool, SimulationResult, TradeReceipt, TradeRecord, TradeStatus, TradeStore,
+    TransactionBuilder, TransactionSubmitter, SubmitterConfig,
+};
+use defi_price_feed::{PriceAggregator, AggregatorConfig, FeedStatus, PriceState};
+
+use crate::audit::{AuditEvent, AuditLogger, AuditOutcome};
 use crate::conversions::{self, opportunity_to_proto, now_ms};
+use crate::errors::ToStatus;
 use crate::proto::*;
+use crate::proto::defi_service_server::DefiService;
 
 /// Service state
 pub struct ServiceState {
     pub price_state: Arc<PriceState>,
     pub aggregator: Option<PriceAggregator>,
-    pub scanner: Option<ArbitrageScanner>,
-    pub submitter: TransactionSubmitter,
+    pub scanner: Option<Arc<ArbitrageScanner>>,
+    /// Scanner output awaiting execution, best expected value first
+    pub opportunity_queue: Arc<OpportunityQueue>,
+    pub submitter: Arc<TransactionSubmitter>,
+    pub trades: Arc<TradeStore>,
+    pub simulations: SimulationPool,
+    /// Simulators used by `get_opportunities` when asked to simulate, and
+    /// for the pre-flight check in `execute_trade`
+    pub simulators: HashMap<ChainId, Arc<EvmSimulator>>,
+    /// How far below the opportunity's expected net profit a pre-flight
+    /// simulation may come in before the trade is refused, in bps
+    pub max_profit_decay_bps: u32,
+    /// Whether `execute_trade` refuses opportunities on chains without a
+    /// simulator rather than submitting them unchecked
+    pub require_preflight: bool,
+    /// Router unchecked trades are sent to on chains without a simulator;
+    /// the zero address refuses them instead
+    pub router: Address,
     pub start_time: Instant,
     pub opportunities_found: u64,
     pub trades_executed: u64,
     pub total_profit_usd: f64,
     pub scanner_shutdown: Option<oneshot::Sender<()>>,
+    pub trade_tracker: Option<JoinHandle<()>>,
+    /// Delegation queued opportunities are executed under as the scanner
+    /// finds them; None leaves them to `execute_trade`
+    pub auto_execute: Option<String>,
+    pub queue_consumer: Option<JoinHandle<()>>,
+    /// Set by `stop`; new trades are rejected while in-flight ones settle
+    pub draining: bool,
+    /// How long `stop` waits for in-flight trades
+    pub drain_timeout: Duration,
+    /// Last `get_opportunities` scan, reused by calls in the same block
+    scan_cache: Option<CachedScan>,
+    /// How long a cached scan is served for; zero disables caching
+    pub scan_cache_ttl: Duration,
+    /// Weights for `OpportunitySort::SortScore` when a request brings none
+    pub score_weights: defi_core::ScoreWeights,
+}
+
+/// Default window within which the scanner must have completed a scan to be
+/// considered ticking by the health probe
+const DEFAULT_MAX_SCAN_AGE_MS: u64 = 5_000;
+
+/// How often submitted trades are checked for receipts
+const TRADE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the queue consumer checks for new opportunities
+const QUEUE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Default time `stop` waits for in-flight trades to settle
+const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default pre-flight profit decay tolerance: 10%
+const DEFAULT_MAX_PROFIT_DECAY_BPS: u32 = 1_000;
+
+/// Default lifetime of a cached `get_opportunities` scan
+const DEFAULT_SCAN_CACHE_TTL: Duration = Duration::from_millis(50);
+
+/// Scanner output kept for polling clients. Valid while it is younger than
+/// the TTL, came from the current scanner and no scanned chain has moved to
+/// a new block.
+struct CachedScan {
+    scanner: Arc<ArbitrageScanner>,
+    blocks: Vec<Option<u64>>,
+    scanned_at: Instant,
+    opportunities: Vec<defi_core::ArbitrageOpportunity>,
+}
+
+impl CachedScan {
+    fn is_fresh(&self, scanner: &Arc<ArbitrageScanner>, blocks: &[Option<u64>], ttl: Duration) -> bool {
+        Arc::ptr_eq(&self.scanner, scanner)
+            && self.blocks == blocks
+            && self.scanned_at.elapsed() < ttl
+    }
+}
+
+/// Latest block seen for each chain the scanner covers
+fn scanned_blocks(price_state: &PriceState, scanner: &ArbitrageScanner) -> Vec<Option<u64>> {
+    scanner.enabled_chains()
+        .iter()
+        .map(|&chain| price_state.get_block(chain))
+        .collect()
+}
+
+/// Run a single route step against the tracked pool state.
+/// Returns the output amount, the step's price impact as a fraction and
+/// its gas estimate.
+fn simulate_step(
+    price_state: &PriceState,
+    chain: ChainId,
+    step: &SwapStep,
+    amount_in: U256,
+) -> Result<(U256, f64, u64), String> {
+    let pool_address: Address = step.pool_address
+        .parse()
+        .map_err(|_| format!("Invalid pool address: {}", step.pool_address))?;
+    let token_in: Address = step.token_in
+        .as_ref()
+        .ok_or_else(|| "Missing token_in".to_string())?
+        .address
+        .parse()
+        .map_err(|_| "Invalid token_in address".to_string())?;
+
+    let entry = price_state
+        .get_pool(chain, pool_address)
+        .ok_or_else(|| format!("Pool not found: {}", step.pool_address))?;
+
+    let amount_out = entry.pool.get_amount_out(amount_in, token_in);
+    if amount_out.is_zero() {
+        return Err("Step produced zero output".to_string());
+    }
+
+    Ok((
+        amount_out,
+        entry.pool.price_impact(amount_in, token_in),
+        entry.pool.swap_gas(amount_in, token_in),
+    ))
+}
+
+/// USD value of a settled trade's balance change in its profit token,
+/// less the gas its receipt paid. A balance change or gas cost that can't
+/// be priced books nothing.
+fn realized_profit_usd(price_state: &PriceState, record: &TradeRecord, receipt: &TradeReceipt) -> f64 {
+    let gas_cost_wei = U256::from(receipt.gas_used).saturating_mul(receipt.effective_gas_price);
+    let gas_usd = price_state.gas_cost_usd(record.chain, gas_cost_wei).unwrap_or_else(|| {
+        warn!("No native USD price on {}, booking trade {} without gas", record.chain, record.trade_id);
+        0.0
+    });
+    balance_delta_usd(price_state, record, receipt) - gas_usd
+}
+
+/// USD value of a settled trade's balance change in its profit token.
+/// Trades without a profit token or a USD price book nothing.
+fn balance_delta_usd(price_state: &PriceState, record: &TradeRecord, receipt: &TradeReceipt) -> f64 {
+    let Some(token) = record.profit_token else {
+        return 0.0;
+    };
+    let Some(price) = price_state.get_usd_price(record.chain, token) else {
+        warn!("No USD price for {} on {}, booking trade {} at zero", token, record.chain, record.trade_id);
+        return 0.0;
+    };
+
+    let decimals = get_token_by_address(record.chain, token).map(|t| t.decimals).unwrap_or(18);
+    let delta: f64 = receipt.balance_delta.to_string().parse().unwrap_or(0.0);
+    delta / 10f64.powi(decimals as i32) * price
+}
+
+/// Where `get_price` and `stream_prices` say prices come from
+const PRICE_SOURCE: &str = "pools";
+
+/// USD price of a hex-encoded token address, with the wall-clock time of
+/// the oldest pool it was derived from. None for unparseable addresses.
+fn token_usd_price(price_state: &PriceState, chain: ChainId, token: &str) -> Option<(f64, u64)> {
+    let token: Address = token.parse().ok()?;
+    let (price, age) = price_state.get_usd_price_with_age(chain, token)?;
+    Some((price, now_ms().saturating_sub(age.as_millis() as u64)))
+}
+
+/// When the client stops waiting, from the `grpc-timeout` header
+fn request_deadline<T>(request: &Request<T>) -> Option<Instant> {
+    let value = request.metadata().get("grpc-timeout")?.to_str().ok()?;
+    let (amount, unit) = value.split_at(value.len().checked_sub(1)?);
+    let amount: u64 = amount.parse().ok()?;
+    let timeout = match unit {
+        "H" => Duration::from_secs(amount.saturating_mul(3600)),
+        "M" => Duration::from_secs(amount.saturating_mul(60)),
+        "S" => Duration::from_secs(amount),
+        "m" => Duration::from_millis(amount),
+        "u" => Duration::from_micros(amount),
+        "n" => Duration::from_nanos(amount),
+        _ => return None,
+    };
+    Instant::now().checked_add(timeout)
+}
+
+/// Fail once `deadline` has passed, naming the work that was cut short
+fn check_deadline(deadline: Option<Instant>, work: &str) -> Result<(), CoreError> {
+    match deadline {
+        Some(deadline) if Instant::now() >= deadline => {
+            Err(CoreError::DeadlineExceeded(work.to_string()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Scan on the blocking pool, cancelling the scan once `deadline` passes
+/// so no further strategies run for a client that has given up
+async fn scan_before(
+    scanner: &Arc<ArbitrageScanner>,
+    deadline: Option<Instant>,
+) -> Result<Vec<defi_core::ArbitrageOpportunity>, Status> {
+    let Some(deadline) = deadline else {
+        return Ok(scanner.scan_once());
+    };
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let scan = tokio::task::spawn_blocking({
+        let scanner = Arc::clone(scanner);
+        let cancelled = Arc::clone(&cancelled);
+        move || scanner.scan_once_cancellable(&cancelled)
+    });
+
+    match tokio::time::timeout_at(deadline.into(), scan).await {
+        Ok(Ok(Some(opportunities))) => Ok(opportunities),
+        Ok(Err(e)) => Err(Status::internal(format!("Scan failed: {}", e))),
+        Ok(Ok(None)) | Err(_) => {
+            cancelled.store(true, Ordering::Relaxed);
+            Err(CoreError::DeadlineExceeded("scan".to_string()).to_status())
+        }
+    }
+}
+
+/// Reject a config update enabling a DEX deployed on none of the enabled
+/// chains, which would otherwise silently do nothing
+fn validate_enabled_dexes(req: &UpdateConfigRequest) -> Result<(), CoreError> {
+    if req.enabled_chains.is_empty() {
+        return Ok(());
+    }
+    let chains: Vec<ChainId> = req.enabled_chains.iter().map(|&c| conversions::chain_from_proto(c)).collect();
+
+    for &dex in &req.enabled_dexes {
+        let dex = conversions::dex_from_proto(dex);
+        if !chains.iter().any(|&chain| dex.is_available_on(chain)) {
+            return Err(CoreError::InvalidConfig(format!(
+                "{} is not available on any enabled chain",
+                dex.name()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// One confirmation pass over submitted trades. Realized profit of every
+/// settled trade, losses included, is added to `total_profit_usd`.
+/// Returns the number of trades settled.
+async fn settle_trades<F, Fut>(state: &RwLock<ServiceState>, fetch_receipt: F) -> usize
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Option<TradeReceipt>>,
+{
+    let (trades, price_state) = {
+        let state = state.read();
+        (Arc::clone(&state.trades), Arc::clone(&state.price_state))
+    };
+
+    let settled = trades
+        .poll_confirmations(fetch_receipt, |record, receipt| {
+            realized_profit_usd(&price_state, record, receipt)
+        })
+        .await;
+
+    if !settled.is_empty() {
+        let realized: f64 = settled.iter().map(|r| r.actual_profit_usd).sum();
+        state.write().total_profit_usd += realized;
+    }
+    settled.len()
+}
+
+/// Feed connection states from the aggregator, if one is configured
+fn feed_statuses(state: &ServiceState) -> Vec<FeedStatus> {
+    state.aggregator
+        .as_ref()
+        .map(|a| a.feed_statuses())
+        .unwrap_or_default()
+}
+
+/// Most opportunities a single `get_opportunities` call returns
+const MAX_OPPORTUNITIES: usize = 100;
+
+/// Ranking score for an opportunity under the requested sort. `weights`
+/// only apply to `OpportunitySort::SortScore`.
+fn opportunity_score(
+    opp: &defi_core::ArbitrageOpportunity,
+    sort: OpportunitySort,
+    weights: &defi_core::ScoreWeights,
+    now_ms: u64,
+) -> f64 {
+    match sort {
+        OpportunitySort::SortExpectedValue => opp.success_probability() * opp.profit_usd,
+        OpportunitySort::SortProfitUsd => opp.profit_usd,
+        OpportunitySort::SortProfitBps => opp.profit_bps as f64,
+        OpportunitySort::SortSuccessProbability => opp.success_probability(),
+        OpportunitySort::SortScore => opp.score_at(weights, now_ms),
+    }
+}
+
+/// Sort highest score first and keep the top `limit` (non-positive = maximum)
+fn rank_opportunities<T: Borrow<defi_core::ArbitrageOpportunity>>(
+    mut opportunities: Vec<T>,
+    sort: OpportunitySort,
+    weights: &defi_core::ScoreWeights,
+    limit: i32,
+) -> Vec<T> {
+    let now = now_ms();
+    opportunities.sort_by(|a, b| {
+        opportunity_score(b.borrow(), sort, weights, now)
+            .partial_cmp(&opportunity_score(a.borrow(), sort, weights, now))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let limit = if limit > 0 { (limit as usize).min(MAX_OPPORTUNITIES) } else { MAX_OPPORTUNITIES };
+    opportunities.truncate(limit);
+    opportunities
+}
+
+/// An opportunity with its simulation outcome, when one was requested
+struct Candidate {
+    opp: defi_core::ArbitrageOpportunity,
+    simulation: Option<SimulationResult>,
+}
+
+impl Borrow<defi_core::ArbitrageOpportunity> for Candidate {
+    fn borrow(&self) -> &defi_core::ArbitrageOpportunity {
+        &self.opp
+    }
+}
+
+impl Candidate {
+    fn to_proto(&self, price_state: &PriceState) -> ArbitrageOpportunity {
+        let mut proto = opportunity_to_proto(&self.opp, price_state);
+        if let Some(ref simulation) = self.simulation {
+            proto.simulated = true;
+            proto.simulated_gas_used = simulation.gas_used;
+            proto.simulated_profit = simulation.profit.to_string();
+        }
+        proto
+    }
+}
+
+/// Parse a wei amount where an empty string means unset
+fn parse_optional_wei(value: &str) -> Result<Option<U256>, CoreError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    value
+        .parse()
+        .map(Some)
+        .map_err(|_| CoreError::InvalidAmount(value.to_string()))
+}
+
+/// Run a past opportunity through a copy of `base` at the given gas price
+/// and minimum profit, keeping its sizing and confidence settings. Returns the recomputed opportunity and, if it
+/// would have been dropped, why.
+///
+/// A rejected opportunity is optimized again without a minimum so the
+/// response still shows what its profit came to.
+fn replay_through_optimizer(
+    base: &RouteOptimizer,
+    price_state: &PriceState,
+    opp: &defi_core::ArbitrageOpportunity,
+    gas_price: Option<U256>,
+    min_profit: Option<U256>,
+) -> (defi_core::ArbitrageOpportunity, Option<String>) {
+    let optimizer = |min_profit: Option<U256>| {
+        let mut optimizer = base.clone();
+        if let Some(min_profit) = min_profit {
+            optimizer = optimizer.with_min_profit(min_profit);
+        }
+        if let Some(gas_price) = gas_price {
+            optimizer.update_gas_price(GasPrice {
+                base_fee: gas_price,
+                priority_fee: U256::ZERO,
+                max_fee: gas_price,
+            });
+        }
+        optimizer
+    };
+
+    if let Some(optimized) = optimizer(min_profit).optimize(opp.clone(), price_state) {
+        return (optimized, None);
+    }
+
+    match optimizer(Some(U256::ZERO)).optimize(opp.clone(), price_state) {
+        Some(recomputed) => {
+            let reason = format!("Net profit {} wei is below the minimum", recomputed.net_profit);
+            (recomputed, Some(reason))
+        }
+        None => (opp.clone(), Some("Routes don't chain".to_string())),
+    }
+}
+
+/// USD value of a replayed opportunity's net profit, from the input token's
+/// tracked price or, when it can't be priced, the recorded USD profit
+/// scaled to the new net profit
+fn replayed_profit_usd(
+    price_state: &PriceState,
+    original: &defi_core::ArbitrageOpportunity,
+    replayed: &defi_core::ArbitrageOpportunity,
+) -> f64 {
+    let net: f64 = replayed.net_profit.to_string().parse().unwrap_or(0.0);
+
+    if let Some(price) = price_state.get_usd_price(replayed.chain, replayed.token_a) {
+        let scale = 10f64.powi(get_decimals(replayed.chain, replayed.token_a) as i32);
+        return net / scale * price;
+    }
+
+    let recorded: f64 = original.net_profit.to_string().parse().unwrap_or(0.0);
+    if recorded > 0.0 {
+        original.profit_usd * net / recorded
+    } else {
+        0.0
+    }
+}
+
+/// Chains known to the aggregator, the scanner, or the price state
+fn tracked_chains(state: &ServiceState) -> Vec<ChainId> {
+    let mut chains: Vec<ChainId> = Vec::new();
+
+    let configured = state.aggregator.as_ref().map(|a| a.chains()).unwrap_or_default();
+    let scanned = state.scanner
+        .as_ref()
+        .map(|s| s.enabled_chains().to_vec())
+        .unwrap_or_default();
+
+    for chain in configured.into_iter().chain(scanned).chain(state.price_state.chains()) {
+        if !chains.contains(&chain) {
+            chains.push(chain);
+        }
+    }
+
+    chains
+}
+
+/// Tracks the last price pushed per token so unchanged prices aren't resent
+#[derive(Debug, Default)]
+pub struct PriceCoalescer {
+    last_sent: HashMap<String, f64>,
+}
+
+impl PriceCoalescer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `price` differs from the last value sent for `token`
+    pub fn should_send(&self, token: &str, price: f64) -> bool {
+        self.last_sent.get(token) != Some(&price)
+    }
+
+    /// Record that `price` was delivered for `token`
+    pub fn mark_sent(&mut self, token: &str, price: f64) {
+        self.last_sent.insert(token.to_string(), price);
+    }
 }
 
 /// gRPC service implementation
 pub struct DefiServiceImpl {
     state: Arc<RwLock<ServiceState>>,
+    audit: Arc<AuditLogger>,
 }
 
 impl DefiServiceImpl {
@@ -45,16 +499,32 @@ impl DefiServiceImpl {
             price_state: Arc::clone(&price_state),
             aggregator: None,
             scanner: None,
-            submitter: TransactionSubmitter::new(SubmitterConfig::default()),
+            opportunity_queue: Arc::new(OpportunityQueue::default()),
+            submitter: Arc::new(TransactionSubmitter::new(SubmitterConfig::default())),
+            trades: Arc::new(TradeStore::new()),
+            simulations: SimulationPool::default(),
+            simulators: HashMap::new(),
+            max_profit_decay_bps: DEFAULT_MAX_PROFIT_DECAY_BPS,
+            require_preflight: true,
+            router: Address::ZERO,
             start_time: Instant::now(),
             opportunities_found: 0,
             trades_executed: 0,
             total_profit_usd: 0.0,
             scanner_shutdown: None,
+            trade_tracker: None,
+            auto_execute: None,
+            queue_consumer: None,
+            draining: false,
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            scan_cache: None,
+            scan_cache_ttl: DEFAULT_SCAN_CACHE_TTL,
+            score_weights: defi_core::ScoreWeights::default(),
         };
 
         Self {
             state: Arc::new(RwLock::new(state)),
+            audit: Arc::new(AuditLogger::new()),
         }
     }
 
@@ -67,19 +537,111 @@ impl DefiServiceImpl {
             price_state: Arc::clone(&price_state),
             aggregator: Some(aggregator),
             scanner: None,
-            submitter: TransactionSubmitter::new(SubmitterConfig::default()),
+            opportunity_queue: Arc::new(OpportunityQueue::default()),
+            submitter: Arc::new(TransactionSubmitter::new(SubmitterConfig::default())),
+            trades: Arc::new(TradeStore::new()),
+            simulations: SimulationPool::default(),
+            simulators: HashMap::new(),
+            max_profit_decay_bps: DEFAULT_MAX_PROFIT_DECAY_BPS,
+            require_preflight: true,
+            router: Address::ZERO,
             start_time: Instant::now(),
             opportunities_found: 0,
             trades_executed: 0,
             total_profit_usd: 0.0,
             scanner_shutdown: None,
+            trade_tracker: None,
+            auto_execute: None,
+            queue_consumer: None,
+            draining: false,
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            scan_cache: None,
+            scan_cache_ttl: DEFAULT_SCAN_CACHE_TTL,
+            score_weights: defi_core::ScoreWeights::default(),
         };
 
         Self {
             state: Arc::new(RwLock::new(state)),
+            audit: Arc::new(AuditLogger::new()),
         }
     }
 
+    /// Limit how many simulations run at once
+    pub fn with_max_concurrent_simulations(self, max_concurrent: usize) -> Self {
+        self.state.write().simulations = SimulationPool::new(max_concurrent);
+        self
+    }
+
+    /// How long `stop` waits for in-flight trades before giving up on them
+    pub fn with_drain_timeout(self, drain_timeout: Duration) -> Self {
+        self.state.write().drain_timeout = drain_timeout;
+        self
+    }
+
+    /// How long `get_opportunities` reuses a scan within the same block.
+    /// Zero scans on every call.
+    pub fn with_scan_cache_ttl(self, ttl: Duration) -> Self {
+        self.state.write().scan_cache_ttl = ttl;
+        self
+    }
+
+    /// Weights `get_opportunities` scores with under `SORT_SCORE` when the
+    /// request doesn't set its own
+    pub fn with_score_weights(self, weights: defi_core::ScoreWeights) -> Self {
+        self.state.write().score_weights = weights;
+        self
+    }
+
+    /// Where audit records are written besides `tracing`
+    pub fn with_audit_logger(mut self, audit: AuditLogger) -> Self {
+        self.audit = Arc::new(audit);
+        self
+    }
+
+    /// How far, in bps, a pre-flight simulation may fall short of an
+    /// opportunity's expected net profit before `execute_trade` refuses it
+    pub fn with_max_profit_decay_bps(self, max_decay_bps: u32) -> Self {
+        self.state.write().max_profit_decay_bps = max_decay_bps;
+        self
+    }
+
+    /// Whether `execute_trade` needs a simulator for the opportunity's
+    /// chain. Disabling it submits opportunities on chains without one
+    /// unchecked, through the router set with `with_router`; dry runs
+    /// always need one.
+    pub fn with_preflight_required(self, required: bool) -> Self {
+        self.state.write().require_preflight = required;
+        self
+    }
+
+    /// Router for trades submitted without a simulator, whose own router
+    /// is used otherwise
+    pub fn with_router(self, router: Address) -> Self {
+        self.state.write().router = router;
+        self
+    }
+
+    /// Simulator for the simulator's chain, used by `get_opportunities`
+    /// requests with `simulate` set and by `execute_trade`
+    pub fn with_simulator(self, simulator: EvmSimulator) -> Self {
+        self.state.write().simulators.insert(simulator.chain(), Arc::new(simulator));
+        self
+    }
+
+    /// Execute queued opportunities under `delegation_id` from `start` on,
+    /// instead of waiting for `execute_trade` to name them
+    pub fn with_auto_execute(self, delegation_id: impl Into<String>) -> Self {
+        self.state.write().auto_execute = Some(delegation_id.into());
+        self
+    }
+
+    /// Submitter settings, including dry-run mode. Call before `start`,
+    /// which hands the submitter to the trade tracker.
+    pub fn with_submitter_config(self, config: SubmitterConfig) -> Self {
+        self.state.write().submitter = Arc::new(TransactionSubmitter::new(config));
+        self
+    }
+
     /// Start all background services
     pub async fn start(&self) -> anyhow::Result<()> {
         let mut state = self.state.write();
@@ -90,31 +652,404 @@ impl DefiServiceImpl {
             info!("Price aggregator started");
         }
 
+        // Track confirmations for submitted trades
+        if state.trade_tracker.is_none() {
+            let service_state = Arc::clone(&self.state);
+            let submitter = Arc::clone(&state.submitter);
+
+            state.trade_tracker = Some(tokio::spawn(async move {
+                let mut interval = tokio::time::interval(TRADE_POLL_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    settle_trades(&service_state, |tx_hash| {
+                        let submitter = Arc::clone(&submitter);
+                        async move {
+                            match submitter.fetch_receipt(&tx_hash).await {
+                                Ok(receipt) => receipt,
+                                Err(e) => {
+                                    warn!("Receipt lookup failed for {}: {}", tx_hash, e);
+                                    None
+                                }
+                            }
+                        }
+                    })
+                    .await;
+                }
+            }));
+        }
+
+        // Execute what the scanner queues
+        if state.queue_consumer.is_none() {
+            if let Some(delegation_id) = state.auto_execute.clone() {
+                let service = self.clone();
+                state.queue_consumer = Some(tokio::spawn(async move {
+                    service.consume_queue(delegation_id).await;
+                }));
+            }
+        }
+
         Ok(())
     }
 
     /// Stop all services
+    ///
+    /// New trades are rejected from the start. Trades already in flight get
+    /// up to the drain timeout to settle, with the trade tracker still
+    /// polling receipts, before feeds are stopped.
     pub async fn stop(&self) {
+        let (trades, drain_timeout) = {
+            let mut state = self.state.write();
+            state.draining = true;
+
+            // Stop scanner
+            if let Some(shutdown) = state.scanner_shutdown.take() {
+                let _ = shutdown.send(());
+            }
+
+            (Arc::clone(&state.trades), state.drain_timeout)
+        };
+
+        let in_flight = trades.drain(drain_timeout).await;
+        if in_flight > 0 {
+            warn!("Stopping with {} trades still in flight after {:?}", in_flight, drain_timeout);
+        }
+
         let mut state = self.state.write();
 
-        // Stop scanner
-        if let Some(shutdown) = state.scanner_shutdown.take() {
-            let _ = shutdown.send(());
+        // Stop trade tracker
+        if let Some(tracker) = state.trade_tracker.take() {
+            tracker.abort();
+        }
+        if let Some(consumer) = state.queue_consumer.take() {
+            consumer.abort();
         }
 
         // Stop aggregator
         if let Some(ref mut aggregator) = state.aggregator {
             aggregator.stop().await;
         }
+        drop(state);
 
+        self.audit.flush().await;
         info!("All services stopped");
     }
+
+    /// Answer a retried `execute_trade` with the trade its idempotency key
+    /// already names
+    fn replayed_trade(&self, record: &TradeRecord) -> ExecuteTradeResponse {
+        let success = !matches!(record.status, TradeStatus::Failed | TradeStatus::Reverted);
+        self.audit.log(
+            AuditEvent::TradeExecuteResult,
+            if success { AuditOutcome::Success } else { AuditOutcome::Failure },
+            "Trade execution replayed for idempotency key",
+            serde_json::json!({
+                "trade_id": record.trade_id,
+                "delegation_id": record.delegation_id,
+                "idempotency_key": record.idempotency_key,
+            }),
+        );
+
+        ExecuteTradeResponse {
+            success,
+            tx_hash: record.tx_hash.clone().unwrap_or_default(),
+            trade_id: record.trade_id.clone(),
+            status: ExecutionStatus::from(record.status) as i32,
+            error: record.error.clone().unwrap_or_default(),
+            dry_run: record.dry_run,
+            simulated_profit: record.simulated_profit.map(|p| p.to_string()).unwrap_or_default(),
+        }
+    }
+
+    /// Detected opportunity `execute_trade` names, from the queue or the
+    /// last cached scan
+    fn queued_opportunity(&self, opportunity_id: &str) -> Result<defi_core::ArbitrageOpportunity, CoreError> {
+        let state = self.state.read();
+        state.opportunity_queue
+            .get(opportunity_id)
+            .or_else(|| {
+                state.scan_cache
+                    .as_ref()
+                    .and_then(|cache| cache.opportunities.iter().find(|o| o.id == opportunity_id).cloned())
+            })
+            .ok_or_else(|| CoreError::OpportunityNotFound(opportunity_id.to_string()))
+    }
+
+    /// Take queued opportunities, best first, and execute each under
+    /// `delegation_id` as `execute_trade` would, one at a time. Stops
+    /// taking them once the service is draining.
+    async fn consume_queue(&self, delegation_id: String) {
+        let mut interval = tokio::time::interval(QUEUE_POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            loop {
+                let (queue, trades, dry_run) = {
+                    let state = self.state.read();
+                    if state.draining {
+                        return;
+                    }
+                    (Arc::clone(&state.opportunity_queue), Arc::clone(&state.trades), state.submitter.is_dry_run())
+                };
+                let Some(opp) = queue.pop() else {
+                    break;
+                };
+
+                let mut record = TradeRecord::new(uuid::Uuid::new_v4().to_string(), opp.chain, delegation_id.clone());
+                record.opportunity_id = Some(opp.id.clone());
+                record.profit_token = Some(opp.token_a);
+                trades.insert(record.clone());
+
+                self.audit.log(
+                    AuditEvent::TradeExecuteRequest,
+                    AuditOutcome::Requested,
+                    "Queued opportunity taken for execution",
+                    serde_json::json!({
+                        "trade_id": record.trade_id,
+                        "delegation_id": delegation_id,
+                        "chain": opp.chain.name(),
+                        "opportunity_id": opp.id,
+                    }),
+                );
+                // Failures are recorded on the trade and audited
+                let _ = self.run_trade(record, Ok(opp), dry_run).await;
+            }
+        }
+    }
+
+    /// Re-simulate a detected opportunity just before submission.
+    ///
+    /// Fails with `SimulationFailed` when the simulation reverts or its
+    /// profit has decayed more than `max_profit_decay_bps` below the
+    /// opportunity's expected net profit since detection, and when the
+    /// chain has no simulator. The check is only skipped, with a warning,
+    /// when pre-flight was disabled with `with_preflight_required` and
+    /// isn't `required` anyway, as a dry run has nothing to report without
+    /// a simulation; the trade then goes to the configured router, and is
+    /// refused with `ChainNotConfigured` when there is none.
+    async fn preflight_simulation(&self, opp: defi_core::ArbitrageOpportunity, required: bool) -> Result<Preflight, Status> {
+        let (opp, simulator, simulations, max_decay_bps, sender) = {
+            let state = self.state.read();
+            let Some(simulator) = state.simulators.get(&opp.chain).map(Arc::clone) else {
+                if required || state.require_preflight {
+                    return Err(
+                        ExecutionError::SimulationFailed(format!("No simulator for {}", opp.chain)).to_status()
+                    );
+                }
+                if state.router == Address::ZERO {
+                    return Err(CoreError::ChainNotConfigured(opp.chain).to_status());
+                }
+                warn!("No simulator for {}, executing opportunity {} without pre-flight", opp.chain, opp.id);
+                return Ok(Preflight {
+                    router: state.router,
+                    opportunity: opp,
+                    simulation: None,
+                });
+            };
+            (opp, simulator, state.simulations.clone(), state.max_profit_decay_bps, state.submitter.sender())
+        };
+
+        // Pre-flight against the current head, not the one the opportunity
+        // was detected at
+        simulator.begin_scan();
+        // From the account the transaction will be sent from
+        let result = simulations
+            .run(async { simulator.simulate_opportunity(&opp, sender, U256::ZERO) })
+            .await;
+        if !result.success {
+            let error = result.error.unwrap_or_else(|| "reverted".to_string());
+            return Err(ExecutionError::SimulationFailed(error).to_status());
+        }
+
+        let floor = opp.net_profit.saturating_mul(U256::from(10_000 - max_decay_bps.min(10_000)))
+            / U256::from(10_000);
+        if result.profit < floor {
+            return Err(ExecutionError::SimulationFailed(format!(
+                "Simulated profit {} is more than {}bps below the expected {}",
+                result.profit, max_decay_bps, opp.net_profit
+            ))
+            .to_status());
+        }
+
+        Ok(Preflight {
+            opportunity: opp,
+            router: simulator.router(),
+            simulation: Some(result),
+        })
+    }
+
+    /// Build the transaction a cleared opportunity calls for and take
+    /// it through the submitter. A dry run stops short of broadcasting; a
+    /// live submission is repriced until the opportunity expires when the
+    /// submitter is configured to.
+    async fn submit_preflighted(&self, preflight: &Preflight, dry_run: bool) -> Result<defi_core::types::ExecutionResult, Status> {
+        let submitter = Arc::clone(&self.state.read().submitter);
+        let opp = &preflight.opportunity;
+
+        // Price gas at the chain's current fees, or at whatever the
+        // opportunity was costed at when there is no RPC to ask
+        let gas_price = submitter
+            .fetch_gas_price()
+            .await
+            .map_err(|e| Status::internal(format!("Failed to fetch gas price: {}", e)))?
+            .unwrap_or_else(|| {
+                let base_fee = opp.gas_cost_wei / U256::from(opportunity_gas_units(opp).max(1));
+                GasPrice {
+                    base_fee,
+                    priority_fee: U256::ZERO,
+                    max_fee: base_fee,
+                }
+            });
+
+        let nonce = submitter
+            .get_nonce(submitter.sender())
+            .await
+            .map_err(|e| Status::internal(format!("Failed to fetch nonce: {}", e)))?;
+        let tx = TransactionBuilder::new(opp.chain, preflight.router)
+            .build_tx(opp, submitter.sender(), nonce, &gas_price)
+            .map_err(|e| Status::internal(format!("Failed to build transaction: {}", e)))?;
+        let submitted = if dry_run {
+            submitter.dry_run(tx).await
+        } else {
+            submitter.submit_until(tx, opp.expires_at_ms).await
+        };
+        submitted.map_err(|e| Status::internal(format!("Submission failed: {}", e)))
+    }
+
+    /// Pre-flight, submit and record a trade already reserved in the trade
+    /// store, for the opportunity its `opportunity_id` resolved to
+    async fn run_trade(
+        &self,
+        mut record: TradeRecord,
+        opportunity: Result<defi_core::ArbitrageOpportunity, Status>,
+        dry_run: bool,
+    ) -> Result<ExecuteTradeResponse, Status> {
+        let trade_id = record.trade_id.clone();
+        let trades = Arc::clone(&self.state.read().trades);
+
+        let preflight = match opportunity {
+            Ok(opp) => self.preflight_simulation(opp, dry_run).await,
+            Err(status) => Err(status),
+        };
+        let preflight = match preflight {
+            Ok(preflight) => preflight,
+            Err(status) => {
+                trades.mark_failed(&trade_id, status.message());
+                self.audit.log(
+                    AuditEvent::TradeExecuteResult,
+                    AuditOutcome::Failure,
+                    "Trade rejected by pre-flight simulation",
+                    serde_json::json!({
+                        "trade_id": trade_id,
+                        "delegation_id": record.delegation_id,
+                        "opportunity_id": record.opportunity_id,
+                        "error": status.message(),
+                    }),
+                );
+                return Err(status);
+            }
+        };
+
+        let result = match self.submit_preflighted(&preflight, dry_run).await {
+            Ok(result) => result,
+            Err(status) => {
+                trades.mark_failed(&trade_id, status.message());
+                self.audit.log(
+                    AuditEvent::TradeExecuteResult,
+                    AuditOutcome::Failure,
+                    "Trade submission failed",
+                    serde_json::json!({
+                        "trade_id": trade_id,
+                        "delegation_id": record.delegation_id,
+                        "opportunity_id": record.opportunity_id,
+                        "error": status.message(),
+                    }),
+                );
+                return Err(status);
+            }
+        };
+        record.dry_run = dry_run;
+        record.tx_hash = result.tx_hash;
+        record.simulated_profit = preflight.simulation.as_ref().map(|s| s.profit);
+        if dry_run {
+            record.status = TradeStatus::Simulated;
+            record.gas_used = preflight.simulation.as_ref().map(|s| s.gas_used);
+        } else if result.success {
+            // Broadcast; the receipt tracker takes it from here
+            record.status = TradeStatus::Submitted;
+        }
+        if !result.success {
+            record.status = TradeStatus::Failed;
+            record.error = result.error;
+        }
+
+        // Replace the reservation with the outcome
+        trades.insert(record.clone());
+        if !dry_run {
+            self.state.write().trades_executed += 1;
+        }
+
+        if dry_run {
+            self.audit.log(
+                AuditEvent::TradeExecuteResult,
+                AuditOutcome::Success,
+                "Trade execution dry run",
+                serde_json::json!({
+                    "trade_id": trade_id,
+                    "delegation_id": record.delegation_id,
+                    "tx_hash": record.tx_hash,
+                    "simulated_profit": record.simulated_profit.map(|p| p.to_string()),
+                }),
+            );
+            return Ok(ExecuteTradeResponse {
+                success: true,
+                tx_hash: record.tx_hash.unwrap_or_default(),
+                trade_id,
+                status: ExecutionStatus::from(record.status) as i32,
+                error: String::new(),
+                dry_run: true,
+                simulated_profit: record.simulated_profit.map(|p| p.to_string()).unwrap_or_default(),
+            });
+        }
+
+        let success = record.status != TradeStatus::Failed;
+        self.audit.log(
+            AuditEvent::TradeExecuteResult,
+            if success { AuditOutcome::Success } else { AuditOutcome::Failure },
+            if success { "Trade execution submitted" } else { "Trade submission failed" },
+            serde_json::json!({
+                "trade_id": trade_id,
+                "delegation_id": record.delegation_id,
+                "tx_hash": record.tx_hash,
+                "status": format!("{:?}", record.status).to_lowercase(),
+                "error": record.error,
+            }),
+        );
+
+        Ok(ExecuteTradeResponse {
+            success,
+            tx_hash: record.tx_hash.unwrap_or_default(),
+            trade_id,
+            status: ExecutionStatus::from(record.status) as i32,
+            error: record.error.unwrap_or_default(),
+            dry_run: false,
+            simulated_profit: record.simulated_profit.map(|p| p.to_string()).unwrap_or_default(),
+        })
+    }
+}
+
+/// A detected opportunity cleared for submission
+struct Preflight {
+    opportunity: defi_core::ArbitrageOpportunity,
+    /// Router the transaction is sent to, and the simulation ran against
+    router: Address,
+    /// `None` when pre-flight was skipped on a chain without a simulator
+    simulation: Option<SimulationResult>,
 }
 
 impl Clone for DefiServiceImpl {
     fn clone(&self) -> Self {
         Self {
             state: Arc::clone(&self.state),
+            audit: Arc::clone(&self.audit),
         }
     }
 }
@@ -126,27 +1061,21 @@ impl DefiService for DefiServiceImpl {
         request: Request<GetPriceRequest>,
     ) -> Result<Response<GetPriceResponse>, Status> {
         let req = request.into_inner();
-        let chain: ChainId = req.chain.into();
+        let chain = conversions::chain_from_proto(req.chain);
 
         let state = self.state.read();
 
         // Try to get price from state
-        if let Some(price) = state.price_state.get_price(&req.token_address, chain) {
+        if let Some((price_usd, timestamp_ms)) = token_usd_price(&state.price_state, chain, &req.token_address) {
             Ok(Response::new(GetPriceResponse {
                 success: true,
-                price_usd: price.price_usd,
-                timestamp_ms: price.timestamp.timestamp_millis() as u64,
-                source: price.source.clone(),
+                price_usd,
+                timestamp_ms,
+                source: PRICE_SOURCE.to_string(),
                 error: String::new(),
             }))
         } else {
-            Ok(Response::new(GetPriceResponse {
-                success: false,
-                price_usd: 0.0,
-                timestamp_ms: 0,
-                source: String::new(),
-                error: format!("Price not found for {} on {}", req.token_address, chain),
-            }))
+            Err(CoreError::PriceNotFound(format!("{} on {}", req.token_address, chain)).to_status())
         }
     }
 
@@ -157,7 +1086,7 @@ impl DefiService for DefiServiceImpl {
         request: Request<StreamPricesRequest>,
     ) -> Result<Response<Self::StreamPricesStream>, Status> {
         let req = request.into_inner();
-        let chain: ChainId = req.chain.into();
+        let chain = conversions::chain_from_proto(req.chain);
         let tokens = req.token_addresses;
 
         let (tx, rx) = mpsc::channel(100);
@@ -166,6 +1095,7 @@ impl DefiService for DefiServiceImpl {
         // Spawn background task to push updates
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_millis(100));
+            let mut coalescer = PriceCoalescer::new();
 
             loop {
                 interval.tick().await;
@@ -173,17 +1103,30 @@ impl DefiService for DefiServiceImpl {
                 let state = state.read();
 
                 for token in &tokens {
-                    if let Some(price) = state.price_state.get_price(token, chain) {
+                    if let Some((price_usd, timestamp_ms)) = token_usd_price(&state.price_state, chain, token) {
+                        // Skip tokens whose price hasn't moved since the last push
+                        if !coalescer.should_send(token, price_usd) {
+                            continue;
+                        }
+
                         let update = PriceUpdate {
                             token_address: token.clone(),
                             chain: Chain::from(chain) as i32,
-                            price_usd: price.price_usd,
-                            timestamp_ms: price.timestamp.timestamp_millis() as u64,
-                            source: price.source.clone(),
+                            price_usd,
+                            timestamp_ms,
+                            source: PRICE_SOURCE.to_string(),
                         };
 
-                        if tx.send(Ok(update)).await.is_err() {
-                            return; // Client disconnected
+                        // Never block the interval on a slow client: drop the update
+                        // and let the next tick resend the latest value instead
+                        match tx.try_send(Ok(update)) {
+                            Ok(()) => coalescer.mark_sent(token, price_usd),
+                            Err(mpsc::error::TrySendError::Full(_)) => {
+                                debug!("Price stream backpressure, dropping update for {}", token);
+                            }
+                            Err(mpsc::error::TrySendError::Closed(_)) => {
+                                return; // Client disconnected
+                            }
                         }
                     }
                 }
@@ -193,44 +1136,156 @@ impl DefiService for DefiServiceImpl {
         Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
     }
 
+    async fn get_quote(
+        &self,
+        request: Request<GetQuoteRequest>,
+    ) -> Result<Response<GetQuoteResponse>, Status> {
+        let req = request.into_inner();
+        let chain = conversions::chain_from_proto(req.chain);
+
+        let parse_address = |value: &str| -> Result<Address, CoreError> {
+            value.parse().map_err(|_| CoreError::InvalidAddress(value.to_string()))
+        };
+        let token_in = parse_address(&req.token_in).map_err(|e| e.to_status())?;
+        let token_out = parse_address(&req.token_out).map_err(|e| e.to_status())?;
+        let amount_in: U256 = req.amount_in
+            .parse()
+            .map_err(|_| CoreError::InvalidAmount(req.amount_in.clone()).to_status())?;
+        let must_use_pools = req.must_use_pools
+            .iter()
+            .map(|pool| parse_address(pool))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_status())?;
+
+        let mut quote_request = QuoteRequest::new(chain, token_in, token_out, amount_in)
+            .with_must_use_pools(must_use_pools);
+        if req.slippage_bps > 0 {
+            quote_request = quote_request.with_slippage(req.slippage_bps.min(u16::MAX as u32) as u16);
+        }
+        if req.max_hops > 0 {
+            quote_request = quote_request.with_max_hops(req.max_hops.min(u8::MAX as u32) as u8);
+        }
+
+        let price_state = Arc::clone(&self.state.read().price_state);
+        let quotes = QuoteEngine::new().quote(quote_request, &price_state).await;
+
+        Ok(Response::new(GetQuoteResponse {
+            success: true,
+            quotes: quotes.quotes.iter().map(conversions::quote_to_proto).collect(),
+            best_quote_index: quotes.best_quote_index.map(|i| i as i32).unwrap_or(-1),
+            price_spread_bps: quotes.price_spread_bps().unwrap_or(0) as u32,
+            error: quotes.reason.clone().unwrap_or_default(),
+        }))
+    }
+
     async fn get_opportunities(
         &self,
         request: Request<GetOpportunitiesRequest>,
     ) -> Result<Response<GetOpportunitiesResponse>, Status> {
+        let deadline = request_deadline(&request);
         let req = request.into_inner();
         let start = Instant::now();
 
-        let state = self.state.read();
+        let (scanner, price_state, simulators, simulations, blocks, cache_ttl, cached) = {
+            let state = self.state.read();
+            let scanner = state.scanner
+                .as_ref()
+                .map(Arc::clone)
+                .ok_or_else(|| CoreError::ScannerNotRunning.to_status())?;
+            let blocks = scanned_blocks(&state.price_state, &scanner);
+            let cached = state.scan_cache
+                .as_ref()
+                .filter(|cache| cache.is_fresh(&scanner, &blocks, state.scan_cache_ttl))
+                .map(|cache| cache.opportunities.clone());
+            (
+                scanner,
+                Arc::clone(&state.price_state),
+                state.simulators.clone(),
+                state.simulations.clone(),
+                blocks,
+                state.scan_cache_ttl,
+                cached,
+            )
+        };
 
-        if let Some(ref scanner) = state.scanner {
-            let opportunities = scanner.scan_once();
-            let duration_us = start.elapsed().as_micros() as u64;
+        let cache_hit = cached.is_some();
+        let opportunities = match cached {
+            Some(opportunities) => opportunities,
+            None => {
+                let opportunities = scan_before(&scanner, deadline).await?;
+                // A fresh scan is simulated against a fresh head, a cached
+                // one against the head it was first simulated at
+                for simulator in simulators.values() {
+                    simulator.begin_scan();
+                }
+                if !cache_ttl.is_zero() {
+                    self.state.write().scan_cache = Some(CachedScan {
+                        scanner: Arc::clone(&scanner),
+                        blocks,
+                        scanned_at: Instant::now(),
+                        opportunities: opportunities.clone(),
+                    });
+                }
+                opportunities
+            }
+        };
+        let duration_us = if cache_hit { 0 } else { start.elapsed().as_micros() as u64 };
+        let now = now_ms();
+
+        // Filter by request parameters, dropping anything already expired
+        let filtered: Vec<_> = opportunities
+            .into_iter()
+            .filter(|opp| {
+                opp.is_valid(now)
+                    && opp.profit_usd >= req.min_profit_usd
+                    && opp.confidence >= req.min_confidence
+            })
+            .collect();
 
-            // Filter by request parameters
-            let filtered: Vec<_> = opportunities
-                .into_iter()
-                .filter(|opp| {
-                    opp.profit_usd >= req.min_profit_usd
-                        && opp.confidence >= req.min_confidence
-                })
-                .take(req.limit.min(100) as usize)
-                .map(|opp| opportunity_to_proto(&opp))
-                .collect();
+        let mut candidates = Vec::with_capacity(filtered.len());
+        for opp in filtered {
+            if !req.simulate {
+                candidates.push(Candidate { opp, simulation: None });
+                continue;
+            }
 
-            Ok(Response::new(GetOpportunitiesResponse {
-                success: true,
-                opportunities: filtered,
-                scan_duration_us: duration_us,
-                error: String::new(),
-            }))
-        } else {
-            Ok(Response::new(GetOpportunitiesResponse {
-                success: false,
-                opportunities: vec![],
-                scan_duration_us: 0,
-                error: "Scanner not initialized".to_string(),
-            }))
+            // Returned with `simulated` unset, for the caller to check
+            // another way
+            let Some(simulator) = simulators.get(&opp.chain) else {
+                debug!("No simulator for {}, returning opportunity {} unsimulated", opp.chain, opp.id);
+                candidates.push(Candidate { opp, simulation: None });
+                continue;
+            };
+            check_deadline(deadline, "simulation").map_err(|e| e.to_status())?;
+            // The service holds no wallet, so simulate from the zero address
+            let result = simulations
+                .run(async { simulator.simulate_opportunity(&opp, Address::ZERO, U256::ZERO) })
+                .await;
+
+            if result.success && !result.profit.is_zero() {
+                candidates.push(Candidate { opp, simulation: Some(result) });
+            } else {
+                debug!("Opportunity {} failed simulation: {:?}", opp.id, result.error);
+            }
         }
+
+        let sort = OpportunitySort::try_from(req.sort_by).unwrap_or(OpportunitySort::SortExpectedValue);
+        let weights = match req.score_weights {
+            Some(weights) => weights.into(),
+            None => self.state.read().score_weights,
+        };
+        let opportunities: Vec<_> = rank_opportunities(candidates, sort, &weights, req.limit)
+            .iter()
+            .map(|candidate| candidate.to_proto(&price_state))
+            .collect();
+
+        Ok(Response::new(GetOpportunitiesResponse {
+            success: true,
+            opportunities,
+            scan_duration_us: duration_us,
+            error: String::new(),
+            cache_hit,
+        }))
     }
 
     type StreamOpportunitiesStream = Pin<Box<dyn Stream<Item = Result<ArbitrageOpportunity, Status>> + Send>>;
@@ -250,36 +1305,107 @@ impl DefiService for DefiServiceImpl {
             loop {
                 interval.tick().await;
 
-                let state_guard = state.read();
+                // Don't hold the state lock across sends to the client
+                let (scanner, price_state) = {
+                    let state = state.read();
+                    (state.scanner.clone(), Arc::clone(&state.price_state))
+                };
 
-                if let Some(ref scanner) = state_guard.scanner {
+                if let Some(scanner) = scanner {
                     let opportunities = scanner.scan_once();
+                    let now = now_ms();
 
                     for opp in opportunities {
-                        if opp.profit_usd >= req.min_profit_usd
+                        // The scanner filters before optimizing, which can
+                        // lower confidence, so its floor is checked again.
+                        // Clients can only tighten it.
+                        if opp.is_valid(now)
+                            && scanner.filter().matches(&opp)
+                            && opp.profit_usd >= req.min_profit_usd
                             && opp.confidence >= req.min_confidence
                         {
-                            let proto_opp = opportunity_to_proto(&opp);
+                            let proto_opp = opportunity_to_proto(&opp, &price_state);
                             if tx.send(Ok(proto_opp)).await.is_err() {
                                 return;
                             }
                         }
                     }
                 }
-
-                drop(state_guard);
             }
         });
 
         Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
     }
 
+    /// Recompute the execution decision for a past opportunity at a
+    /// hypothetical gas price and minimum profit. Expiry is ignored: a
+    /// replayed opportunity has always expired.
+    async fn replay_opportunity(
+        &self,
+        request: Request<ReplayOpportunityRequest>,
+    ) -> Result<Response<ReplayOpportunityResponse>, Status> {
+        let req = request.into_inner();
+
+        let opp: defi_core::ArbitrageOpportunity = serde_json::from_str(&req.opportunity_json)
+            .map_err(|e| CoreError::InvalidOpportunity(e.to_string()).to_status())?;
+        let gas_price = parse_optional_wei(&req.gas_price_wei).map_err(|e| e.to_status())?;
+        let min_profit = parse_optional_wei(&req.min_profit_wei).map_err(|e| e.to_status())?;
+
+        let (base, price_state, simulator, simulations) = {
+            let state = self.state.read();
+            (
+                // The running scanner's optimizer, so replays size and score
+                // opportunities the way it did
+                state.scanner.as_ref().map_or_else(RouteOptimizer::new, |s| s.optimizer().clone()),
+                Arc::clone(&state.price_state),
+                state.simulators.get(&opp.chain).map(Arc::clone),
+                state.simulations.clone(),
+            )
+        };
+
+        let (replayed, mut rejection) = replay_through_optimizer(&base, &price_state, &opp, gas_price, min_profit);
+        let mut response = ReplayOpportunityResponse {
+            gas_cost_wei: replayed.gas_cost_wei.to_string(),
+            net_profit_wei: replayed.net_profit.to_string(),
+            profit_bps: replayed.profit_bps,
+            profit_usd: replayed_profit_usd(&price_state, &opp, &replayed),
+            confidence: replayed.confidence,
+            ..Default::default()
+        };
+
+        if req.simulate {
+            let simulator = simulator.ok_or_else(|| {
+                ExecutionError::SimulationFailed(format!("No simulator for {}", opp.chain)).to_status()
+            })?;
+            simulator.begin_scan();
+            // The service holds no wallet, so simulate from the zero address
+            let result = simulations
+                .run(async { simulator.simulate_opportunity(&replayed, Address::ZERO, U256::ZERO) })
+                .await;
+
+            response.simulated = true;
+            response.simulation_success = result.success && !result.profit.is_zero();
+            response.simulated_profit = result.profit.to_string();
+            response.simulation_error = result.error.unwrap_or_default();
+            if rejection.is_none() && !response.simulation_success {
+                rejection = Some("Simulation reverted or returned no profit".to_string());
+            }
+        }
+
+        response.would_execute = rejection.is_none();
+        response.rejection_reason = rejection.unwrap_or_default();
+        Ok(Response::new(response))
+    }
+
     async fn simulate_trade(
         &self,
         request: Request<SimulateTradeRequest>,
     ) -> Result<Response<SimulateTradeResponse>, Status> {
         let req = request.into_inner();
-        let _chain: ChainId = req.chain.into();
+        let _chain = conversions::chain_from_proto(req.chain);
+
+        let simulations = self.state.read().simulations.clone();
+        let _permit = simulations.acquire().await;
 
         // In production:
         // 1. Create EVM simulator for the chain
@@ -305,29 +1431,69 @@ impl DefiService for DefiServiceImpl {
         &self,
         request: Request<SimulateRouteRequest>,
     ) -> Result<Response<SimulateRouteResponse>, Status> {
+        let deadline = request_deadline(&request);
         let req = request.into_inner();
+        let chain = conversions::chain_from_proto(req.chain);
+
+        let input_amount: U256 = match req.input_amount.parse() {
+            Ok(amount) => amount,
+            Err(_) => return Err(CoreError::InvalidAmount(req.input_amount).to_status()),
+        };
+
+        let (price_state, simulations) = {
+            let state = self.state.read();
+            (Arc::clone(&state.price_state), state.simulations.clone())
+        };
+        let _permit = match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline.into(), simulations.acquire())
+                .await
+                .map_err(|_| CoreError::DeadlineExceeded("waiting for a simulation slot".to_string()).to_status())?,
+            None => simulations.acquire().await,
+        };
 
-        // Simulate each step
+        // Simulate each step, feeding its output into the next step
         let mut step_results = Vec::new();
         let mut total_gas = 0u64;
-
-        for (i, _step) in req.route.iter().enumerate() {
-            step_results.push(StepResult {
-                step_index: i as u32,
-                success: true,
-                output_amount: "0".to_string(),
-                gas_used: 100000, // Placeholder
-                error: String::new(),
-            });
-            total_gas += 100000;
+        let mut total_impact_bps = 0.0;
+        let mut amount = input_amount;
+        let mut would_succeed = !req.route.is_empty();
+
+        for (i, step) in req.route.iter().enumerate() {
+            check_deadline(deadline, "route simulation").map_err(|e| e.to_status())?;
+            match simulate_step(&price_state, chain, step, amount) {
+                Ok((amount_out, impact, gas)) => {
+                    step_results.push(StepResult {
+                        step_index: i as u32,
+                        success: true,
+                        output_amount: amount_out.to_string(),
+                        gas_used: gas,
+                        error: String::new(),
+                    });
+                    total_gas += gas;
+                    total_impact_bps += impact * 10_000.0;
+                    amount = amount_out;
+                }
+                Err(e) => {
+                    step_results.push(StepResult {
+                        step_index: i as u32,
+                        success: false,
+                        output_amount: "0".to_string(),
+                        gas_used: 0,
+                        error: e,
+                    });
+                    would_succeed = false;
+                    amount = U256::ZERO;
+                    break;
+                }
+            }
         }
 
         Ok(Response::new(SimulateRouteResponse {
             success: true,
-            would_succeed: true,
-            final_output: "0".to_string(),
-            total_price_impact_bps: 0.0,
-            total_gas_estimate: total_gas,
+            would_succeed,
+            final_output: amount.to_string(),
+            total_price_impact_bps: total_impact_bps,
+            total_gas_estimate: total_gas + hop_overhead(step_results.iter().filter(|r| r.success).count()),
             step_results,
             error: String::new(),
         }))
@@ -337,52 +1503,57 @@ impl DefiService for DefiServiceImpl {
         &self,
         request: Request<ExecuteTradeRequest>,
     ) -> Result<Response<ExecuteTradeResponse>, Status> {
+        if self.state.read().draining {
+            return Err(CoreError::ShuttingDown.to_status());
+        }
+
         let req = request.into_inner();
-        let chain: ChainId = req.chain.into();
+        let chain = conversions::chain_from_proto(req.chain);
+        let idempotency_key = (!req.idempotency_key.is_empty()).then(|| req.idempotency_key.clone());
+
+        // The transaction is built from a detected opportunity, which a
+        // dry run also needs to simulate
+        let dry_run = self.state.read().submitter.is_dry_run();
+        if req.opportunity_id.is_empty() {
+            return Err(CoreError::InvalidOpportunity(
+                "Execution needs an opportunity_id to build the transaction from".to_string(),
+            )
+            .to_status());
+        }
+
         let trade_id = uuid::Uuid::new_v4().to_string();
 
-        // Audit log: trade execution request
-        info!(
-            target: "audit",
-            event = "TRADE_EXECUTE_REQUEST",
-            trade_id = %trade_id,
-            delegation_id = %req.delegation_id,
-            chain = ?chain,
-            dex = req.dex,
-            amount_in = %req.amount_in,
-            "Trade execution requested"
-        );
+        // In production, verify the delegation is valid
 
-        // In production:
-        // 1. Verify delegation is valid
-        // 2. Build transaction
-        // 3. Simulate
-        // 4. Submit via mempool or Flashbots
+        let mut record = TradeRecord::new(trade_id.clone(), chain, req.delegation_id.clone());
+        record.opportunity_id = Some(req.opportunity_id.clone());
+        // Arbitrage round trips end in the token they start with
+        record.profit_token = req.token_in.parse().ok();
+        record.idempotency_key = idempotency_key.clone();
 
-        // Update stats
-        {
-            let mut state = self.state.write();
-            state.trades_executed += 1;
-        }
-
-        // Audit log: trade execution outcome
-        info!(
-            target: "audit",
-            event = "TRADE_EXECUTE_RESULT",
-            trade_id = %trade_id,
-            delegation_id = %req.delegation_id,
-            outcome = "success",
-            status = "pending",
-            "Trade execution submitted"
+        // Reserve the key before anything is simulated or sent, so a
+        // concurrent retry replays this trade instead of submitting again
+        let trades = Arc::clone(&self.state.read().trades);
+        if let Err(existing) = trades.insert_idempotent(record.clone()) {
+            return Ok(Response::new(self.replayed_trade(&existing)));
+        }
+
+        self.audit.log(
+            AuditEvent::TradeExecuteRequest,
+            AuditOutcome::Requested,
+            "Trade execution requested",
+            serde_json::json!({
+                "trade_id": trade_id,
+                "delegation_id": req.delegation_id,
+                "chain": chain.name(),
+                "dex": conversions::dex_from_proto(req.protocol).name(),
+                "amount_in": req.amount_in,
+                "idempotency_key": idempotency_key,
+            }),
         );
 
-        Ok(Response::new(ExecuteTradeResponse {
-            success: true,
-            tx_hash: String::new(), // Would be actual tx hash
-            trade_id,
-            status: ExecutionStatus::Pending as i32,
-            error: String::new(),
-        }))
+        let opportunity = self.queued_opportunity(&req.opportunity_id).map_err(|e| e.to_status());
+        self.run_trade(record, opportunity, dry_run).await.map(Response::new)
     }
 
     async fn get_trade_status(
@@ -390,19 +1561,12 @@ impl DefiService for DefiServiceImpl {
         request: Request<GetTradeStatusRequest>,
     ) -> Result<Response<GetTradeStatusResponse>, Status> {
         let req = request.into_inner();
+        let record = self.state.read().trades.get(&req.trade_id);
 
-        // In production, look up from database/cache
-        Ok(Response::new(GetTradeStatusResponse {
-            success: true,
-            trade_id: req.trade_id,
-            status: ExecutionStatus::Pending as i32,
-            tx_hash: String::new(),
-            block_number: 0,
-            gas_used: 0,
-            actual_output: String::new(),
-            actual_profit_usd: 0.0,
-            error: String::new(),
-        }))
+        match record {
+            Some(record) => Ok(Response::new(conversions::trade_record_to_proto(&record))),
+            None => Err(CoreError::TradeNotFound(req.trade_id).to_status()),
+        }
     }
 
     async fn get_system_status(
@@ -414,24 +1578,37 @@ impl DefiService for DefiServiceImpl {
         let uptime = state.start_time.elapsed().as_secs();
         let price_stats = state.price_state.stats();
 
-        let scanner_running = state.scanner.is_some();
-
-        // Build chain statuses
-        let chain_statuses: Vec<ChainStatus> = vec![
-            ChainStatus {
-                chain: Chain::Ethereum as i32,
-                connected: true,
-                last_block: 0,
-                pool_count: price_stats.pool_count as u32,
-                last_update_ms: now_ms(),
-            },
-        ];
+        // A started scanner only counts as running once its pools have warmed up
+        let warming_up = state.scanner.as_ref().is_some_and(|s| !s.is_ready());
+        let scanner_running = state.scanner.is_some() && !warming_up;
+        let feeds = feed_statuses(&state);
+        let now = now_ms();
+
+        // Build chain statuses from real per-chain data
+        let chain_statuses: Vec<ChainStatus> = tracked_chains(&state)
+            .into_iter()
+            .map(|chain| {
+                let age = state.price_state.chain_last_update_age(chain);
+                ChainStatus {
+                    chain: Chain::from(chain) as i32,
+                    connected: feeds.iter().any(|f| f.chain == chain && f.connected),
+                    last_block: state.price_state.get_block(chain).unwrap_or(0),
+                    pool_count: state.price_state.chain_pool_count(chain) as u32,
+                    last_update_ms: age
+                        .map(|a| now.saturating_sub(a.as_millis() as u64))
+                        .unwrap_or(0),
+                }
+            })
+            .collect();
 
         Ok(Response::new(GetSystemStatusResponse {
             success: true,
             scanner_running,
             uptime_seconds: uptime,
-            active_feeds: 0,
+            active_feeds: state.aggregator
+                .as_ref()
+                .map(|a| a.stats().feed_count as u32)
+                .unwrap_or(0),
             tracked_pools: price_stats.pool_count as u32,
             tracked_tokens: price_stats.price_count as u32,
             opportunities_found: state.opportunities_found,
@@ -439,6 +1616,65 @@ impl DefiService for DefiServiceImpl {
             total_profit_usd: state.total_profit_usd,
             last_scan_duration_us: 0,
             chain_statuses,
+            warming_up,
+        }))
+    }
+
+    async fn health(
+        &self,
+        request: Request<HealthRequest>,
+    ) -> Result<Response<HealthResponse>, Status> {
+        let req = request.into_inner();
+        let max_scan_age_ms = if req.max_scan_age_ms == 0 {
+            DEFAULT_MAX_SCAN_AGE_MS
+        } else {
+            req.max_scan_age_ms
+        };
+
+        let state = self.state.read();
+        let now = now_ms();
+
+        // The scanner counts as ticking only if its loop completed a scan recently
+        let last_scan_age_ms = state.scanner
+            .as_ref()
+            .and_then(|s| s.last_scan_ms())
+            .map(|t| now.saturating_sub(t));
+        let scanner_ticking = last_scan_age_ms.is_some_and(|age| age <= max_scan_age_ms);
+
+        let feeds: Vec<FeedHealth> = feed_statuses(&state)
+            .into_iter()
+            .map(|f| FeedHealth {
+                chain: Chain::from(f.chain) as i32,
+                dex: DexProtocol::from(f.dex) as i32,
+                connected: f.connected,
+            })
+            .collect();
+
+        let chains: Vec<ChainHealth> = tracked_chains(&state)
+            .into_iter()
+            .map(|chain| {
+                let age = state.price_state.chain_last_update_age(chain);
+                ChainHealth {
+                    chain: Chain::from(chain) as i32,
+                    has_data: age.is_some(),
+                    last_update_age_ms: age.map(|a| a.as_millis() as u64).unwrap_or(0),
+                }
+            })
+            .collect();
+
+        let scanner_warm = state.scanner.as_ref().is_some_and(|s| s.is_ready());
+        let ready = scanner_ticking
+            && scanner_warm
+            && feeds.iter().all(|f| f.connected)
+            && chains.iter().all(|c| c.has_data);
+
+        Ok(Response::new(HealthResponse {
+            live: true,
+            ready,
+            scanner_ticking,
+            last_scan_age_ms: last_scan_age_ms.unwrap_or(0),
+            feeds,
+            chains,
         }))
     }
 
@@ -448,15 +1684,25 @@ impl DefiService for DefiServiceImpl {
     ) -> Result<Response<UpdateConfigResponse>, Status> {
         let req = request.into_inner();
 
-        // Audit log: config update with outcome
-        info!(
-            target: "audit",
-            event = "CONFIG_UPDATE",
-            scan_interval_ms = ?req.scan_interval_ms,
-            min_profit_usd = ?req.min_profit_usd,
-            chains_count = req.enabled_chains.len(),
-            outcome = "success",
-            "Configuration updated"
+        if let Err(e) = validate_enabled_dexes(&req) {
+            self.audit.log(
+                AuditEvent::ConfigUpdate,
+                AuditOutcome::Failure,
+                "Configuration rejected",
+                serde_json::json!({ "error": e.to_string() }),
+            );
+            return Err(e.to_status());
+        }
+
+        self.audit.log(
+            AuditEvent::ConfigUpdate,
+            AuditOutcome::Success,
+            "Configuration updated",
+            serde_json::json!({
+                "scan_interval_ms": req.scan_interval_ms,
+                "min_profit_usd": req.min_profit_usd,
+                "chains_count": req.enabled_chains.len(),
+            }),
         );
 
         // In production, apply config changes to scanner/aggregator
@@ -475,16 +1721,13 @@ impl DefiService for DefiServiceImpl {
         let mut state = self.state.write();
 
         if state.scanner.is_some() {
-            return Ok(Response::new(StartScannerResponse {
-                success: false,
-                error: "Scanner already running".to_string(),
-            }));
+            return Err(CoreError::ScannerAlreadyRunning.to_status());
         }
 
         // Create scanner with configured chains
         let chains: Vec<ChainId> = req.chains
             .iter()
-            .map(|&c| c.into())
+            .map(|&c| conversions::chain_from_proto(c))
             .collect();
 
         let scanner_config = ScannerConfig {
@@ -495,21 +1738,30 @@ impl DefiService for DefiServiceImpl {
             },
             ..Default::default()
         };
+        scanner_config.validate().map_err(|e| e.to_status())?;
 
-        let scanner = ArbitrageScanner::new(scanner_config, Arc::clone(&state.price_state));
-        state.scanner = Some(scanner);
+        let chains_count = scanner_config.enabled_chains.len();
+        let mut scanner = ArbitrageScanner::new(scanner_config, Arc::clone(&state.price_state))
+            .with_queue(Arc::clone(&state.opportunity_queue));
+        if let Some(mempool) = state.aggregator.as_ref().and_then(|a| a.mempool()) {
+            scanner = scanner.with_mempool(mempool);
+        }
+        let scanner = Arc::new(scanner);
+        state.scanner = Some(Arc::clone(&scanner));
 
-        // Create shutdown channel
-        let (shutdown_tx, _shutdown_rx) = oneshot::channel();
+        // Create shutdown channel and drive the scan loop in the background
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
         state.scanner_shutdown = Some(shutdown_tx);
 
-        // Audit log: scanner started
-        info!(
-            target: "audit",
-            event = "SCANNER_START",
-            chains_count = scanner_config.enabled_chains.len(),
-            outcome = "success",
-            "Arbitrage scanner started"
+        tokio::spawn(async move {
+            scanner.run(shutdown_rx).await;
+        });
+
+        self.audit.log(
+            AuditEvent::ScannerStart,
+            AuditOutcome::Success,
+            "Arbitrage scanner started",
+            serde_json::json!({ "chains_count": chains_count }),
         );
 
         Ok(Response::new(StartScannerResponse {
@@ -532,13 +1784,11 @@ impl DefiService for DefiServiceImpl {
 
         state.scanner = None;
 
-        // Audit log: scanner stopped
-        info!(
-            target: "audit",
-            event = "SCANNER_STOP",
-            was_running = was_running,
-            outcome = "success",
-            "Arbitrage scanner stopped"
+        self.audit.log(
+            AuditEvent::ScannerStop,
+            AuditOutcome::Success,
+            "Arbitrage scanner stopped",
+            serde_json::json!({ "was_running": was_running }),
         );
 
         Ok(Response::new(StopScannerResponse {
@@ -553,3 +1803,1707 @@ impl Default for DefiServiceImpl {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seed_v2_pool(service: &DefiServiceImpl, address: Address, token0: Address, token1: Address) {
+        let reserve = U256::from(1_000_000_000_000_000_000_000u128);
+        service.state.read().price_state.update_pool(defi_core::Pool::UniswapV2(defi_core::UniswapV2Pool {
+            address,
+            token0,
+            token1,
+            reserve0: reserve,
+            reserve1: reserve,
+            decimals0: 18,
+            decimals1: 18,
+            fee_bps: 30,
+            chain: ChainId::Ethereum,
+            dex: defi_core::DexProtocol::UniswapV2,
+            block_number: 1,
+        }));
+    }
+
+    fn proto_step(pool: Address, token_in: Address, token_out: Address) -> SwapStep {
+        let token = |address: Address| Token {
+            address: address.to_string(),
+            symbol: String::new(),
+            decimals: 18,
+            chain: Chain::Ethereum as i32,
+        };
+        SwapStep {
+            dex: DexProtocol::DexUniswapV2 as i32,
+            pool_address: pool.to_string(),
+            token_in: Some(token(token_in)),
+            token_out: Some(token(token_out)),
+            amount_in: String::new(),
+            amount_out: String::new(),
+            price_impact_bps: 0.0,
+            min_amount_out: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_simulate_route_chains_step_outputs() {
+        let service = DefiServiceImpl::new();
+        let (a, b, c) = (Address::repeat_byte(1), Address::repeat_byte(2), Address::repeat_byte(3));
+        let (pool_ab, pool_bc) = (Address::repeat_byte(0xAB), Address::repeat_byte(0xBC));
+        seed_v2_pool(&service, pool_ab, a, b);
+        seed_v2_pool(&service, pool_bc, b, c);
+
+        let response = service
+            .simulate_route(Request::new(SimulateRouteRequest {
+                chain: Chain::Ethereum as i32,
+                route: vec![proto_step(pool_ab, a, b), proto_step(pool_bc, b, c)],
+                input_amount: "1000000000000000000".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert!(response.would_succeed);
+        assert_eq!(response.step_results.len(), 2);
+        let swap_gas = defi_core::gas_units(defi_core::DexProtocol::UniswapV2, 0);
+        assert_eq!(response.step_results[0].gas_used, swap_gas);
+        assert_eq!(response.total_gas_estimate, 2 * swap_gas + defi_core::HOP_TRANSFER_GAS);
+
+        // Step 2 consumes step 1's output
+        let step1_out: U256 = response.step_results[0].output_amount.parse().unwrap();
+        let final_out: U256 = response.final_output.parse().unwrap();
+        assert_eq!(response.step_results[1].output_amount, response.final_output);
+        assert!(final_out > U256::ZERO && final_out < step1_out);
+        assert!(response.total_price_impact_bps > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_route_stops_on_missing_pool() {
+        let service = DefiServiceImpl::new();
+        let (a, b, c) = (Address::repeat_byte(1), Address::repeat_byte(2), Address::repeat_byte(3));
+        let pool_ab = Address::repeat_byte(0xAB);
+        seed_v2_pool(&service, pool_ab, a, b);
+
+        let response = service
+            .simulate_route(Request::new(SimulateRouteRequest {
+                chain: Chain::Ethereum as i32,
+                route: vec![proto_step(pool_ab, a, b), proto_step(Address::repeat_byte(0xBC), b, c)],
+                input_amount: "1000000000000000000".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert!(!response.would_succeed);
+        assert!(!response.step_results[1].success);
+        assert_eq!(response.final_output, "0");
+    }
+
+    #[tokio::test]
+    async fn test_simulations_beyond_limit_queue() {
+        let service = DefiServiceImpl::new().with_max_concurrent_simulations(1);
+        let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let pool_ab = Address::repeat_byte(0xAB);
+        seed_v2_pool(&service, pool_ab, a, b);
+
+        let handles: Vec<_> = (0..6)
+            .map(|i| {
+                let service = service.clone();
+                tokio::spawn(async move {
+                    if i % 2 == 0 {
+                        let response = service
+                            .simulate_route(Request::new(SimulateRouteRequest {
+                                chain: Chain::Ethereum as i32,
+                                route: vec![proto_step(pool_ab, a, b)],
+                                input_amount: "1000000000000000000".to_string(),
+                            }))
+                            .await
+                            .unwrap();
+                        response.into_inner().would_succeed
+                    } else {
+                        let response = service
+                            .simulate_trade(Request::new(SimulateTradeRequest {
+                                chain: Chain::Ethereum as i32,
+                                amount_in: "1000".to_string(),
+                                ..Default::default()
+                            }))
+                            .await
+                            .unwrap();
+                        response.into_inner().would_succeed
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert!(handle.await.unwrap());
+        }
+        assert_eq!(service.state.read().simulations.available(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_system_status_reports_per_chain_blocks() {
+        let service = DefiServiceImpl::new();
+        {
+            let state = service.state.read();
+            state.price_state.update_block(ChainId::Ethereum, 19_000_000);
+            state.price_state.update_block(ChainId::Arbitrum, 200_000_000);
+        }
+
+        let status = service
+            .get_system_status(Request::new(GetSystemStatusRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(status.active_feeds, 0);
+        assert_eq!(status.chain_statuses.len(), 2);
+
+        let block_for = |chain: Chain| {
+            status.chain_statuses
+                .iter()
+                .find(|c| c.chain == chain as i32)
+                .map(|c| c.last_block)
+        };
+        assert_eq!(block_for(Chain::Ethereum), Some(19_000_000));
+        assert_eq!(block_for(Chain::Arbitrum), Some(200_000_000));
+    }
+
+    #[tokio::test]
+    async fn test_health_reflects_stopped_scanner() {
+        let service = DefiServiceImpl::new();
+
+        let health = service
+            .health(Request::new(HealthRequest::default()))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert!(health.live);
+        assert!(!health.scanner_ticking);
+        assert!(!health.ready);
+        assert!(health.feeds.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_health_reflects_running_scanner() {
+        let service = DefiServiceImpl::new();
+        seed_v2_pool(&service, Address::repeat_byte(0xAB), Address::repeat_byte(1), Address::repeat_byte(2));
+        let health = || async {
+            service
+                .health(Request::new(HealthRequest::default()))
+                .await
+                .unwrap()
+                .into_inner()
+        };
+
+        service
+            .start_scanner(Request::new(StartScannerRequest {
+                chains: vec![Chain::Ethereum as i32],
+            }))
+            .await
+            .unwrap();
+
+        // Ticking once the loop completes its first scan
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let running = loop {
+            let running = health().await;
+            if running.scanner_ticking {
+                break running;
+            }
+            assert!(Instant::now() < deadline, "scanner never ticked");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        };
+        assert!(running.live);
+        assert!(running.ready);
+        assert!(running.last_scan_age_ms <= DEFAULT_MAX_SCAN_AGE_MS);
+        assert_eq!(running.chains.len(), 1);
+        assert!(running.chains[0].has_data);
+
+        service.stop_scanner(Request::new(StopScannerRequest {})).await.unwrap();
+        let stopped = health().await;
+        assert!(!stopped.scanner_ticking);
+        assert!(!stopped.ready);
+    }
+
+    #[tokio::test]
+    async fn test_health_reports_chain_update_age() {
+        let service = DefiServiceImpl::new();
+        service.state.read().price_state.update_block(ChainId::Arbitrum, 1_000);
+
+        let health = service
+            .health(Request::new(HealthRequest::default()))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(health.chains.len(), 1);
+        assert_eq!(health.chains[0].chain, Chain::Arbitrum as i32);
+        assert!(health.chains[0].has_data);
+    }
+
+    /// Let `service` send trades without pre-flight and queue an
+    /// opportunity for them, returning its id
+    fn queue_unchecked_opportunity(service: &DefiServiceImpl) -> String {
+        let opp = legs_opportunity(ChainId::Ethereum, Address::repeat_byte(0xA1), Address::repeat_byte(0xA2), 20.0);
+        {
+            let mut state = service.state.write();
+            state.require_preflight = false;
+            state.router = Address::repeat_byte(0xEE);
+        }
+        service.state.read().opportunity_queue.push(opp.clone());
+        opp.id
+    }
+
+    #[tokio::test]
+    async fn test_auto_execute_drains_queue() {
+        let service = DefiServiceImpl::new()
+            .with_auto_execute("delegation-auto")
+            .with_drain_timeout(Duration::from_millis(10));
+        let opportunity_id = queue_unchecked_opportunity(&service);
+        let (queue, trades) = {
+            let state = service.state.read();
+            (Arc::clone(&state.opportunity_queue), Arc::clone(&state.trades))
+        };
+
+        service.start().await.unwrap();
+        for _ in 0..50 {
+            if !trades.awaiting_receipt().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        service.stop().await;
+
+        assert!(queue.is_empty());
+        assert_eq!(trades.len(), 1);
+        let (trade_id, _) = trades.awaiting_receipt().pop().unwrap();
+        let record = trades.get(&trade_id).unwrap();
+        assert_eq!(record.status, TradeStatus::Submitted);
+        assert_eq!(record.delegation_id, "delegation-auto");
+        assert_eq!(record.opportunity_id.as_deref(), Some(opportunity_id.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_idempotency_key_returns_original_trade() {
+        let service = DefiServiceImpl::new();
+        let opportunity_id = queue_unchecked_opportunity(&service);
+        let execute = |idempotency_key: &str| {
+            let service = service.clone();
+            let request = ExecuteTradeRequest {
+                chain: Chain::Ethereum as i32,
+                delegation_id: "delegation-1".to_string(),
+                opportunity_id: opportunity_id.clone(),
+                amount_in: "1000".to_string(),
+                idempotency_key: idempotency_key.to_string(),
+                ..Default::default()
+            };
+            async move { service.execute_trade(Request::new(request)).await.unwrap().into_inner() }
+        };
+
+        let first = execute("order-7").await;
+        let retry = execute("order-7").await;
+        assert_eq!(retry.trade_id, first.trade_id);
+        assert_eq!(service.state.read().trades.len(), 1);
+        assert_eq!(service.state.read().trades_executed, 1);
+
+        // A retry after submission reports where the trade got to
+        assert_eq!(retry.status, ExecutionStatus::ExecutionSubmitted as i32);
+        assert_eq!(retry.tx_hash, first.tx_hash);
+
+        // Another key, or none, is a new trade
+        assert_ne!(execute("order-8").await.trade_id, first.trade_id);
+        assert_ne!(execute("").await.trade_id, execute("").await.trade_id);
+        assert_eq!(service.state.read().trades.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_retry_of_failed_trade_reports_failure() {
+        let (audit_tx, mut audit_rx) = mpsc::unbounded_channel();
+        let service = DefiServiceImpl::new().with_audit_logger(AuditLogger::new().with_channel(audit_tx));
+        let opportunity_id = queue_unchecked_opportunity(&service);
+        let execute = || {
+            let service = service.clone();
+            let request = ExecuteTradeRequest {
+                chain: Chain::Ethereum as i32,
+                delegation_id: "delegation-1".to_string(),
+                opportunity_id: opportunity_id.clone(),
+                amount_in: "1000".to_string(),
+                idempotency_key: "order-7".to_string(),
+                ..Default::default()
+            };
+            async move { service.execute_trade(Request::new(request)).await.unwrap().into_inner() }
+        };
+
+        let first = execute().await;
+        assert!(first.success);
+        service.state.read().trades.mark_failed(&first.trade_id, "nonce too low");
+        while audit_rx.try_recv().is_ok() {}
+
+        let retry = execute().await;
+        assert_eq!(retry.trade_id, first.trade_id);
+        assert!(!retry.success);
+        assert_eq!(retry.status, ExecutionStatus::ExecutionFailed as i32);
+        assert_eq!(retry.error, "nonce too low");
+
+        let record = audit_rx.try_recv().unwrap();
+        assert_eq!(record.event, AuditEvent::TradeExecuteResult);
+        assert_eq!(record.outcome, AuditOutcome::Failure);
+    }
+
+    #[tokio::test]
+    async fn test_trade_status_follows_store() {
+        let service = DefiServiceImpl::new();
+        let opportunity_id = queue_unchecked_opportunity(&service);
+
+        let executed = service
+            .execute_trade(Request::new(ExecuteTradeRequest {
+                chain: Chain::Ethereum as i32,
+                delegation_id: "delegation-1".to_string(),
+                opportunity_id,
+                amount_in: "1000".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let status_of = |trade_id: String| {
+            let service = service.clone();
+            async move {
+                service
+                    .get_trade_status(Request::new(GetTradeStatusRequest { trade_id }))
+                    .await
+                    .unwrap()
+                    .into_inner()
+            }
+        };
+
+        let status = status_of(executed.trade_id.clone()).await;
+        assert!(status.success);
+        assert_eq!(status.status, ExecutionStatus::ExecutionSubmitted as i32);
+        assert_eq!(status.tx_hash, executed.tx_hash);
+
+        let trades = Arc::clone(&service.state.read().trades);
+        trades.apply_receipt(&executed.trade_id, defi_executor::TradeReceipt {
+            success: true,
+            block_number: 19_000_001,
+            gas_used: 180_000,
+            effective_gas_price: U256::from(20_000_000_000u64),
+            actual_output: Some(U256::from(995u64)),
+            balance_delta: alloy_primitives::I256::ZERO,
+        }, 3.25);
+
+        let status = status_of(executed.trade_id.clone()).await;
+        assert_eq!(status.status, ExecutionStatus::ExecutionConfirmed as i32);
+        assert_eq!(status.tx_hash, executed.tx_hash);
+        assert_eq!(status.block_number, 19_000_001);
+        assert_eq!(status.actual_output, "995");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_trade_status() {
+        let service = DefiServiceImpl::new();
+
+        let status = service
+            .get_trade_status(Request::new(GetTradeStatusRequest { trade_id: "missing".to_string() }))
+            .await
+            .unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::NotFound);
+        assert_eq!(status.message(), "Trade not found: missing");
+    }
+
+    #[tokio::test]
+    async fn test_get_quote_picks_best_pool() {
+        let service = DefiServiceImpl::new();
+        let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        seed_v2_pool(&service, Address::repeat_byte(0xAB), a, b);
+
+        let response = service
+            .get_quote(Request::new(GetQuoteRequest {
+                chain: Chain::Ethereum as i32,
+                token_in: a.to_string(),
+                token_out: b.to_string(),
+                amount_in: "1000000000000000000".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(response.quotes.len(), 1);
+        assert_eq!(response.best_quote_index, 0);
+        let quote = &response.quotes[0];
+        assert_eq!(quote.route[0].pool_address, Address::repeat_byte(0xAB).to_string());
+        assert!(quote.amount_out.parse::<U256>().unwrap() > U256::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_get_quote_without_route() {
+        let service = DefiServiceImpl::new();
+
+        let response = service
+            .get_quote(Request::new(GetQuoteRequest {
+                chain: Chain::Ethereum as i32,
+                token_in: Address::repeat_byte(1).to_string(),
+                token_out: Address::repeat_byte(2).to_string(),
+                amount_in: "1000".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert!(response.quotes.is_empty());
+        assert_eq!(response.best_quote_index, -1);
+
+        let status = service
+            .get_quote(Request::new(GetQuoteRequest {
+                token_in: "not-an-address".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_missing_price_is_not_found() {
+        let service = DefiServiceImpl::new();
+
+        let status = service
+            .get_price(Request::new(GetPriceRequest {
+                token_address: "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".to_string(),
+                chain: Chain::Ethereum as i32,
+            }))
+            .await
+            .unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::NotFound);
+        assert_eq!(crate::errors::error_detail(&status).unwrap().reason, "PRICE_NOT_FOUND");
+    }
+
+    /// Emits one opportunity per configured USD profit, in that order
+    struct ProfitsStrategy(Vec<f64>);
+
+    impl defi_detector::Strategy for ProfitsStrategy {
+        fn name(&self) -> &'static str {
+            "profits"
+        }
+
+        fn find_opportunities(
+            &self,
+            snapshot: &defi_detector::ChainSnapshot,
+            _state: &Arc<PriceState>,
+        ) -> Vec<defi_core::ArbitrageOpportunity> {
+            let one_eth = U256::from(1_000_000_000_000_000_000u128);
+            let route = |amount_out: U256| defi_core::SwapRoute {
+                steps: vec![],
+                chain: snapshot.chain,
+                total_amount_in: one_eth,
+                total_amount_out: amount_out,
+                gas_estimate: 0,
+                price_impact_bps: 0,
+            };
+
+            self.0
+                .iter()
+                .map(|profit_usd| {
+                    let mut opp = defi_core::OpportunityBuilder::new()
+                        .chain(snapshot.chain)
+                        .routes(route(one_eth), route(one_eth * U256::from(102) / U256::from(100)))
+                        .build()
+                        .unwrap();
+                    opp.profit_usd = *profit_usd;
+                    opp
+                })
+                .collect()
+        }
+    }
+
+    fn service_with_profits(profits: Vec<f64>) -> DefiServiceImpl {
+        let service = DefiServiceImpl::new();
+        seed_v2_pool(&service, Address::repeat_byte(0xAB), Address::repeat_byte(1), Address::repeat_byte(2));
+
+        let config = ScannerConfig {
+            enabled_chains: vec![ChainId::Ethereum],
+            ..Default::default()
+        };
+        let mut state = service.state.write();
+        let scanner = ArbitrageScanner::with_strategies(
+            config,
+            Arc::clone(&state.price_state),
+            vec![Box::new(ProfitsStrategy(profits))],
+        );
+        state.scanner = Some(Arc::new(scanner));
+        drop(state);
+
+        service
+    }
+
+    #[tokio::test]
+    async fn test_limit_returns_highest_scoring_opportunity() {
+        let service = service_with_profits(vec![20.0, 80.0, 50.0]);
+
+        let response = service
+            .get_opportunities(Request::new(GetOpportunitiesRequest {
+                limit: 1,
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(response.opportunities.len(), 1);
+        assert_eq!(response.opportunities[0].profit_usd, 80.0);
+
+        // No limit returns everything, best first
+        let all = service
+            .get_opportunities(Request::new(GetOpportunitiesRequest::default()))
+            .await
+            .unwrap()
+            .into_inner();
+        let profits: Vec<f64> = all.opportunities.iter().map(|o| o.profit_usd).collect();
+        assert_eq!(profits, vec![80.0, 50.0, 20.0]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_enforces_server_confidence_floor() {
+        use futures::StreamExt;
+
+        let stream_with_base_confidence = |base: f64| async move {
+            let service = DefiServiceImpl::new();
+            seed_v2_pool(&service, Address::repeat_byte(0xAB), Address::repeat_byte(1), Address::repeat_byte(2));
+
+            let config = ScannerConfig {
+                enabled_chains: vec![ChainId::Ethereum],
+                confidence: defi_detector::ConfidenceModel { base, ..Default::default() },
+                ..Default::default()
+            };
+            let price_state = Arc::clone(&service.state.read().price_state);
+            let scanner = ArbitrageScanner::with_strategies(
+                config,
+                price_state,
+                vec![Box::new(ProfitsStrategy(vec![20.0]))],
+            );
+            assert_eq!(scanner.filter().min_confidence, 0.5);
+            service.state.write().scanner = Some(Arc::new(scanner));
+
+            // The client asks for everything
+            let mut stream = service
+                .stream_opportunities(Request::new(StreamOpportunitiesRequest {
+                    min_confidence: 0.0,
+                    ..Default::default()
+                }))
+                .await
+                .unwrap()
+                .into_inner();
+            tokio::time::timeout(Duration::from_millis(500), stream.next())
+                .await
+                .ok()
+                .flatten()
+        };
+
+        assert!(stream_with_base_confidence(0.3).await.is_none());
+        let streamed = stream_with_base_confidence(0.9).await.unwrap().unwrap();
+        assert_eq!(streamed.confidence, 0.9);
+    }
+
+    /// Counts its runs, each taking the given time
+    struct SlowStrategy(Duration, Arc<std::sync::atomic::AtomicUsize>);
+
+    impl defi_detector::Strategy for SlowStrategy {
+        fn name(&self) -> &'static str {
+            "slow"
+        }
+
+        fn find_opportunities(
+            &self,
+            _snapshot: &defi_detector::ChainSnapshot,
+            _state: &Arc<PriceState>,
+        ) -> Vec<defi_core::ArbitrageOpportunity> {
+            self.1.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            std::thread::sleep(self.0);
+            vec![]
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_scan_aborts_at_client_deadline() {
+        let service = DefiServiceImpl::new();
+        seed_v2_pool(&service, Address::repeat_byte(0xAB), Address::repeat_byte(1), Address::repeat_byte(2));
+        let arbitrum_pool = defi_core::Pool::UniswapV2(defi_core::UniswapV2Pool {
+            address: Address::repeat_byte(0xAC),
+            token0: Address::repeat_byte(1),
+            token1: Address::repeat_byte(2),
+            reserve0: U256::from(1_000_000u64),
+            reserve1: U256::from(1_000_000u64),
+            decimals0: 18,
+            decimals1: 18,
+            fee_bps: 30,
+            chain: ChainId::Arbitrum,
+            dex: defi_core::DexProtocol::UniswapV2,
+            block_number: 1,
+        });
+        service.state.read().price_state.update_pool(arbitrum_pool);
+
+        let runs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let config = ScannerConfig {
+            enabled_chains: vec![ChainId::Ethereum, ChainId::Arbitrum],
+            max_price_age: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let price_state = Arc::clone(&service.state.read().price_state);
+        let scanner = ArbitrageScanner::with_strategies(
+            config,
+            price_state,
+            vec![Box::new(SlowStrategy(Duration::from_millis(300), Arc::clone(&runs)))],
+        );
+        service.state.write().scanner = Some(Arc::new(scanner));
+
+        let mut request = Request::new(GetOpportunitiesRequest::default());
+        request.set_timeout(Duration::from_millis(50));
+        let start = Instant::now();
+        let status = service.get_opportunities(request).await.unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+        assert_eq!(crate::errors::error_detail(&status).unwrap().reason, "DEADLINE_EXCEEDED");
+        assert!(start.elapsed() < Duration::from_millis(250), "{:?}", start.elapsed());
+
+        // The chain already being scanned finishes, the next never starts
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(service.state.read().scanner.as_ref().unwrap().last_scan_ms().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_route_simulation_respects_deadline() {
+        let service = DefiServiceImpl::new();
+        let (pool, a, b) = (Address::repeat_byte(0xAB), Address::repeat_byte(1), Address::repeat_byte(2));
+        seed_v2_pool(&service, pool, a, b);
+
+        let route_request = || SimulateRouteRequest {
+            chain: Chain::Ethereum as i32,
+            input_amount: "1000000".to_string(),
+            route: vec![proto_step(pool, a, b)],
+        };
+
+        let mut expired = Request::new(route_request());
+        expired.set_timeout(Duration::ZERO);
+        let status = service.simulate_route(expired).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+
+        let mut generous = Request::new(route_request());
+        generous.set_timeout(Duration::from_secs(30));
+        assert!(service.simulate_route(generous).await.unwrap().into_inner().would_succeed);
+    }
+
+    /// Counts how often the scanner runs it
+    struct CountingStrategy(Arc<std::sync::atomic::AtomicUsize>);
+
+    impl defi_detector::Strategy for CountingStrategy {
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+
+        fn find_opportunities(
+            &self,
+            snapshot: &defi_detector::ChainSnapshot,
+            state: &Arc<PriceState>,
+        ) -> Vec<defi_core::ArbitrageOpportunity> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            ProfitsStrategy(vec![10.0]).find_opportunities(snapshot, state)
+        }
+    }
+
+    fn counting_service(ttl: Duration) -> (DefiServiceImpl, Arc<std::sync::atomic::AtomicUsize>) {
+        let service = DefiServiceImpl::new().with_scan_cache_ttl(ttl);
+        seed_v2_pool(&service, Address::repeat_byte(0xAB), Address::repeat_byte(1), Address::repeat_byte(2));
+
+        let scans = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let config = ScannerConfig {
+            enabled_chains: vec![ChainId::Ethereum],
+            ..Default::default()
+        };
+        let mut state = service.state.write();
+        let scanner = ArbitrageScanner::with_strategies(
+            config,
+            Arc::clone(&state.price_state),
+            vec![Box::new(CountingStrategy(Arc::clone(&scans)))],
+        );
+        state.scanner = Some(Arc::new(scanner));
+        drop(state);
+
+        (service, scans)
+    }
+
+    #[tokio::test]
+    async fn test_repeated_calls_in_a_block_scan_once() {
+        let (service, scans) = counting_service(Duration::from_secs(60));
+        let scans = move || scans.load(std::sync::atomic::Ordering::SeqCst);
+        let get = || async {
+            service
+                .get_opportunities(Request::new(GetOpportunitiesRequest::default()))
+                .await
+                .unwrap()
+                .into_inner()
+        };
+        service.state.read().price_state.update_block(ChainId::Ethereum, 100);
+
+        let first = get().await;
+        assert!(!first.cache_hit);
+        assert_eq!(first.opportunities.len(), 1);
+        let scanned = scans();
+        assert!(scanned > 0);
+
+        let second = get().await;
+        assert!(second.cache_hit);
+        assert_eq!(second.scan_duration_us, 0);
+        assert_eq!(second.opportunities, first.opportunities);
+        assert_eq!(scans(), scanned);
+
+        // A new block invalidates the cached scan
+        service.state.read().price_state.update_block(ChainId::Ethereum, 101);
+        let third = get().await;
+        assert!(!third.cache_hit);
+        assert!(scans() > scanned);
+    }
+
+    #[tokio::test]
+    async fn test_zero_ttl_scans_every_call() {
+        let (service, scans) = counting_service(Duration::ZERO);
+
+        for _ in 0..2 {
+            let response = service
+                .get_opportunities(Request::new(GetOpportunitiesRequest::default()))
+                .await
+                .unwrap()
+                .into_inner();
+            assert!(!response.cache_hit);
+        }
+        let per_scan = scans.load(std::sync::atomic::Ordering::SeqCst) / 2;
+        assert!(per_scan > 0);
+        assert_eq!(scans.load(std::sync::atomic::Ordering::SeqCst), 2 * per_scan);
+    }
+
+    /// Emits one two-leg opportunity per (sell pool, USD profit), all
+    /// buying through the same pool
+    struct LegsStrategy {
+        buy_pool: Address,
+        sells: Vec<(Address, f64)>,
+    }
+
+    /// Two-leg opportunity buying through `buy_pool` and selling through
+    /// `sell_pool` for a 2% gross profit
+    fn legs_opportunity(chain: ChainId, buy_pool: Address, sell_pool: Address, profit_usd: f64) -> defi_core::ArbitrageOpportunity {
+        let one_eth = U256::from(1_000_000_000_000_000_000u128);
+        let route = |pool: Address, token_in: u8, token_out: u8, amount_out: U256| {
+            defi_core::SwapRoute {
+                steps: vec![defi_core::SwapStep {
+                    pool,
+                    dex: defi_core::DexProtocol::UniswapV2,
+                    token_in: Address::repeat_byte(token_in),
+                    token_out: Address::repeat_byte(token_out),
+                    amount_in: one_eth,
+                    amount_out,
+                    fee_bps: 30,
+                    min_amount_out: U256::ZERO,
+                }],
+                chain,
+                total_amount_in: one_eth,
+                total_amount_out: amount_out,
+                gas_estimate: 0,
+                price_impact_bps: 0,
+            }
+        };
+
+        let mut opp = defi_core::OpportunityBuilder::new()
+            .chain(chain)
+            .routes(
+                route(buy_pool, 1, 2, one_eth),
+                route(sell_pool, 2, 1, one_eth * U256::from(102) / U256::from(100)),
+            )
+            .build()
+            .unwrap();
+        opp.profit_usd = profit_usd;
+        opp
+    }
+
+    impl defi_detector::Strategy for LegsStrategy {
+        fn name(&self) -> &'static str {
+            "legs"
+        }
+
+        fn find_opportunities(
+            &self,
+            snapshot: &defi_detector::ChainSnapshot,
+            _state: &Arc<PriceState>,
+        ) -> Vec<defi_core::ArbitrageOpportunity> {
+            self.sells
+                .iter()
+                .map(|&(sell_pool, profit_usd)| legs_opportunity(snapshot.chain, self.buy_pool, sell_pool, profit_usd))
+                .collect()
+        }
+    }
+
+    /// Router that calls the buy and sell legs' pools, reverts if either
+    /// call fails and otherwise pays the caller 1000 wei
+    fn router_code() -> Vec<u8> {
+        let leg = |offset: u8| -> Vec<u8> {
+            vec![
+                0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00,
+                0x60, offset, 0x35, 0x60, 0x60, 0x1c, // pool = calldata[offset..] >> 96
+                0x5a, 0xf1,                          // GAS CALL
+                0x15, 0x60, 0x3c, 0x57,              // ISZERO, jump to revert
+            ]
+        };
+
+        let (buy_offset, sell_offset) = leg_offsets();
+        let mut code = leg(buy_offset);
+        code.extend(leg(sell_offset));
+        code.extend([
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00,
+            0x61, 0x03, 0xe8,                        // 1000 wei
+            0x33, 0x5a, 0xf1, 0x50, 0x00,            // pay CALLER, STOP
+        ]);
+        code.extend([0x5b, 0x60, 0x00, 0x60, 0x00, 0xfd]); // JUMPDEST, REVERT
+        code
+    }
+
+    /// Calldata offsets of the buy and sell pools in the multicall built
+    /// for a `legs_opportunity`
+    fn leg_offsets() -> (u8, u8) {
+        let (buy_pool, sell_pool) = (Address::repeat_byte(0xA1), Address::repeat_byte(0xA2));
+        let gas_price = GasPrice {
+            base_fee: U256::ZERO,
+            priority_fee: U256::ZERO,
+            max_fee: U256::ZERO,
+        };
+        let tx = TransactionBuilder::new(ChainId::Ethereum, Address::ZERO)
+            .build_tx(&legs_opportunity(ChainId::Ethereum, buy_pool, sell_pool, 0.0), Address::ZERO, 0, &gas_price)
+            .unwrap();
+
+        let offset = |pool: Address| {
+            let position = tx.data.windows(20).position(|window| window == pool.as_slice()).unwrap();
+            u8::try_from(position).unwrap()
+        };
+        (offset(buy_pool), offset(sell_pool))
+    }
+
+    /// Ethereum simulator running `router_code` against pools with the
+    /// given code
+    fn router_simulator(pools: &[(Address, Vec<u8>)]) -> EvmSimulator {
+        use revm::primitives::{AccountInfo, Bytecode};
+        use revm::InMemoryDB;
+
+        let router = Address::repeat_byte(0xEE);
+        let mut db = InMemoryDB::default();
+        let mut install = |address: Address, balance: u64, code: Vec<u8>| {
+            let bytecode = Bytecode::new_raw(code.into());
+            db.insert_account_info(address, AccountInfo::new(U256::from(balance), 1, bytecode.hash_slow(), bytecode));
+        };
+        install(router, 1_000_000, router_code());
+        for (pool, code) in pools {
+            install(*pool, 0, code.clone());
+        }
+
+        EvmSimulator::new(ChainId::Ethereum)
+            .with_fork_block(1)
+            .with_router(router)
+            .with_state(db)
+    }
+
+    #[tokio::test]
+    async fn test_simulate_drops_reverting_opportunities() {
+        let buy_pool = Address::repeat_byte(0xA1);
+        let (good_pool, reverting_pool) = (Address::repeat_byte(0xA2), Address::repeat_byte(0xA3));
+
+        let simulator = router_simulator(&[
+            (buy_pool, vec![0x00]),
+            (good_pool, vec![0x00]),
+            (reverting_pool, vec![0x60, 0x00, 0x60, 0x00, 0xfd]),
+        ]);
+        let service = DefiServiceImpl::new().with_simulator(simulator);
+        seed_v2_pool(&service, Address::repeat_byte(0xAB), Address::repeat_byte(1), Address::repeat_byte(2));
+
+        // Both legs share the buy pool, so keep them from collapsing
+        let config = ScannerConfig {
+            enabled_chains: vec![ChainId::Ethereum],
+            collapse_conflicts: false,
+            ..Default::default()
+        };
+        let strategy = LegsStrategy {
+            buy_pool,
+            sells: vec![(good_pool, 20.0), (reverting_pool, 80.0)],
+        };
+        {
+            let mut state = service.state.write();
+            let scanner = ArbitrageScanner::with_strategies(
+                config,
+                Arc::clone(&state.price_state),
+                vec![Box::new(strategy)],
+            );
+            state.scanner = Some(Arc::new(scanner));
+        }
+
+        // Without simulation both come back, unannotated
+        let unsimulated = service
+            .get_opportunities(Request::new(GetOpportunitiesRequest::default()))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(unsimulated.opportunities.len(), 2);
+        assert!(unsimulated.opportunities.iter().all(|o| !o.simulated));
+
+        let simulated = service
+            .get_opportunities(Request::new(GetOpportunitiesRequest {
+                simulate: true,
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(simulated.opportunities.len(), 1);
+        let opp = &simulated.opportunities[0];
+        assert_eq!(opp.profit_usd, 20.0);
+        assert!(opp.simulated);
+        assert!(opp.simulated_gas_used > 0);
+        assert_eq!(opp.simulated_profit, "1000");
+
+        // A chain without a simulator comes back unsimulated, not dropped
+        service.state.write().simulators.clear();
+        let unchecked = service
+            .get_opportunities(Request::new(GetOpportunitiesRequest {
+                simulate: true,
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(unchecked.opportunities.len(), 2);
+        assert!(unchecked.opportunities.iter().all(|o| !o.simulated));
+    }
+
+    async fn replay(
+        service: &DefiServiceImpl,
+        opp: &defi_core::ArbitrageOpportunity,
+        gas_price_gwei: u64,
+        min_profit_wei: &str,
+        simulate: bool,
+    ) -> Result<ReplayOpportunityResponse, Status> {
+        service
+            .replay_opportunity(Request::new(ReplayOpportunityRequest {
+                opportunity_json: serde_json::to_string(opp).unwrap(),
+                gas_price_wei: (gas_price_gwei * 1_000_000_000).to_string(),
+                min_profit_wei: min_profit_wei.to_string(),
+                simulate,
+            }))
+            .await
+            .map(Response::into_inner)
+    }
+
+    #[tokio::test]
+    async fn test_replay_decision_follows_gas_price() {
+        let service = DefiServiceImpl::new();
+        // 0.02 ETH gross over 211k gas, worth $40 when detected
+        let opp = legs_opportunity(ChainId::Ethereum, Address::repeat_byte(0xA1), Address::repeat_byte(0xA2), 40.0);
+
+        let cheap = replay(&service, &opp, 10, "", false).await.unwrap();
+        assert!(cheap.would_execute);
+        assert!(cheap.rejection_reason.is_empty());
+        assert_eq!(cheap.gas_cost_wei, "2110000000000000");
+        assert_eq!(cheap.net_profit_wei, "17890000000000000");
+        assert_eq!(cheap.profit_bps, 178);
+        assert!((cheap.profit_usd - 35.78).abs() < 1e-9, "{}", cheap.profit_usd);
+        assert!((cheap.confidence - 0.9).abs() < 1e-9);
+        assert!(!cheap.simulated);
+
+        // Gas eats the whole spread
+        let expensive = replay(&service, &opp, 100, "", false).await.unwrap();
+        assert!(!expensive.would_execute);
+        assert!(!expensive.rejection_reason.is_empty());
+        assert_eq!(expensive.gas_cost_wei, "21100000000000000");
+        assert_eq!(expensive.net_profit_wei, "0");
+        assert_eq!(expensive.profit_usd, 0.0);
+
+        // Profitable at 50 gwei, but not by the 0.01 ETH asked for
+        let default_min = replay(&service, &opp, 50, "", false).await.unwrap();
+        assert!(default_min.would_execute);
+        let strict = replay(&service, &opp, 50, "10000000000000000", false).await.unwrap();
+        assert!(!strict.would_execute);
+        assert_eq!(strict.net_profit_wei, default_min.net_profit_wei);
+    }
+
+    #[tokio::test]
+    async fn test_replay_uses_scanner_optimizer() {
+        let service = DefiServiceImpl::new();
+        let opp = legs_opportunity(ChainId::Ethereum, Address::repeat_byte(0xA1), Address::repeat_byte(0xA2), 40.0);
+        assert!((replay(&service, &opp, 10, "", false).await.unwrap().confidence - 0.9).abs() < 1e-9);
+
+        let config = ScannerConfig {
+            enabled_chains: vec![ChainId::Ethereum],
+            confidence: defi_detector::ConfidenceModel {
+                base: 0.5,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        {
+            let mut state = service.state.write();
+            let scanner = ArbitrageScanner::new(config, Arc::clone(&state.price_state));
+            state.scanner = Some(Arc::new(scanner));
+        }
+
+        let replayed = replay(&service, &opp, 10, "", false).await.unwrap();
+        assert!((replayed.confidence - 0.5).abs() < 1e-9, "{}", replayed.confidence);
+    }
+
+    #[tokio::test]
+    async fn test_replay_simulates_when_asked() {
+        let buy_pool = Address::repeat_byte(0xA1);
+        let (good_pool, reverting_pool) = (Address::repeat_byte(0xA2), Address::repeat_byte(0xA3));
+        let good = legs_opportunity(ChainId::Ethereum, buy_pool, good_pool, 20.0);
+        let reverting = legs_opportunity(ChainId::Ethereum, buy_pool, reverting_pool, 20.0);
+
+        // No simulator for the chain
+        let status = replay(&DefiServiceImpl::new(), &good, 10, "", true).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Aborted);
+
+        let service = DefiServiceImpl::new().with_simulator(router_simulator(&[
+            (buy_pool, vec![0x00]),
+            (good_pool, vec![0x00]),
+            (reverting_pool, vec![0x60, 0x00, 0x60, 0x00, 0xfd]),
+        ]));
+
+        let passed = replay(&service, &good, 10, "", true).await.unwrap();
+        assert!(passed.simulated && passed.simulation_success);
+        assert_eq!(passed.simulated_profit, "1000");
+        assert!(passed.would_execute);
+
+        // Profitable on paper, but the simulation reverts
+        let failed = replay(&service, &reverting, 10, "", true).await.unwrap();
+        assert!(failed.simulated && !failed.simulation_success);
+        assert!(!failed.would_execute);
+        assert!(!failed.rejection_reason.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_replay_rejects_malformed_input() {
+        let service = DefiServiceImpl::new();
+        let status = service
+            .replay_opportunity(Request::new(ReplayOpportunityRequest {
+                opportunity_json: "{\"id\": 1}".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let opp = legs_opportunity(ChainId::Ethereum, Address::repeat_byte(0xA1), Address::repeat_byte(0xA2), 40.0);
+        let status = service
+            .replay_opportunity(Request::new(ReplayOpportunityRequest {
+                opportunity_json: serde_json::to_string(&opp).unwrap(),
+                gas_price_wei: "ten gwei".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn test_rank_by_success_probability() {
+        let route = defi_core::SwapRoute {
+            steps: vec![],
+            chain: ChainId::Ethereum,
+            total_amount_in: U256::from(1000u64),
+            total_amount_out: U256::from(1100u64),
+            gas_estimate: 0,
+            price_impact_bps: 0,
+        };
+        let opp = |profit_usd: f64, competing_txs: u32| {
+            let mut opp = defi_core::OpportunityBuilder::new()
+                .routes(route.clone(), route.clone())
+                .build()
+                .unwrap();
+            opp.profit_usd = profit_usd;
+            opp.competing_txs = competing_txs;
+            opp
+        };
+
+        // Big but heavily contested vs small and uncontested
+        let contested = opp(100.0, 10);
+        let quiet = opp(30.0, 0);
+
+        let weights = defi_core::ScoreWeights::default();
+        let by_profit = rank_opportunities(vec![quiet.clone(), contested.clone()], OpportunitySort::SortProfitUsd, &weights, 1);
+        assert_eq!(by_profit[0].profit_usd, 100.0);
+
+        let by_probability = rank_opportunities(vec![contested.clone(), quiet.clone()], OpportunitySort::SortSuccessProbability, &weights, 1);
+        assert_eq!(by_probability[0].profit_usd, 30.0);
+
+        // The weighted score follows whichever term is weighted
+        let profit_heavy = defi_core::ScoreWeights { profit: 1.0, probability: 0.0, ttl: 0.0, ..weights };
+        let by_score = rank_opportunities(vec![quiet.clone(), contested.clone()], OpportunitySort::SortScore, &profit_heavy, 1);
+        assert_eq!(by_score[0].profit_usd, 100.0);
+
+        let probability_heavy = defi_core::ScoreWeights { profit: 0.1, probability: 1.0, ..profit_heavy };
+        let by_score = rank_opportunities(vec![contested, quiet], OpportunitySort::SortScore, &probability_heavy, 1);
+        assert_eq!(by_score[0].profit_usd, 30.0);
+    }
+
+    #[tokio::test]
+    async fn test_opportunities_without_scanner_is_failed_precondition() {
+        let service = DefiServiceImpl::new();
+
+        let status = service
+            .get_opportunities(Request::new(GetOpportunitiesRequest::default()))
+            .await
+            .unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_route_amount_is_invalid_argument() {
+        let service = DefiServiceImpl::new();
+
+        let status = service
+            .simulate_route(Request::new(SimulateRouteRequest {
+                chain: Chain::Ethereum as i32,
+                route: vec![],
+                input_amount: "lots".to_string(),
+            }))
+            .await
+            .unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn test_coalescer_skips_unchanged_price() {
+        let mut coalescer = PriceCoalescer::new();
+        let token = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+
+        assert!(coalescer.should_send(token, 2000.0));
+        coalescer.mark_sent(token, 2000.0);
+
+        // Constant price produces no repeated updates
+        for _ in 0..10 {
+            assert!(!coalescer.should_send(token, 2000.0));
+        }
+
+        assert!(coalescer.should_send(token, 2001.5));
+    }
+
+    #[test]
+    fn test_coalescer_resends_after_dropped_update() {
+        let mut coalescer = PriceCoalescer::new();
+        let token = "0xaf88d065e77c8cC2239327C5EDb3A432268e5831";
+
+        // A dropped update is never marked, so it is retried on the next tick
+        assert!(coalescer.should_send(token, 1.0));
+        assert!(coalescer.should_send(token, 1.0));
+
+        coalescer.mark_sent(token, 1.0);
+        assert!(!coalescer.should_send(token, 1.0));
+    }
+
+    #[tokio::test]
+    async fn test_execute_trade_rejects_decayed_opportunities() {
+        let (buy_pool, sell_pool) = (Address::repeat_byte(0xA1), Address::repeat_byte(0xA2));
+        let service = DefiServiceImpl::new()
+            .with_simulator(router_simulator(&[(buy_pool, vec![0x00]), (sell_pool, vec![0x00])]))
+            .with_max_profit_decay_bps(1_000);
+        let execute = |opportunity_id: String| {
+            let service = service.clone();
+            async move {
+                service
+                    .execute_trade(Request::new(ExecuteTradeRequest {
+                        chain: Chain::Ethereum as i32,
+                        delegation_id: "delegation-1".to_string(),
+                        opportunity_id,
+                        ..Default::default()
+                    }))
+                    .await
+            }
+        };
+
+        // The router pays out 1000 wei, within 10% of an expected 1050
+        let mut stable = legs_opportunity(ChainId::Ethereum, buy_pool, sell_pool, 20.0);
+        stable.net_profit = U256::from(1_050u64);
+        let mut decayed = legs_opportunity(ChainId::Ethereum, buy_pool, sell_pool, 40.0);
+        decayed.id = "decayed".to_string();
+        decayed.net_profit = U256::from(2_000u64);
+        {
+            let state = service.state.read();
+            assert!(state.opportunity_queue.push(stable.clone()));
+            assert!(state.opportunity_queue.push(decayed));
+        }
+
+        let status = execute("decayed".to_string()).await.unwrap_err();
+        let detail = crate::errors::error_detail(&status).unwrap();
+        assert_eq!(detail.reason, "SIMULATION_FAILED");
+        assert!(status.message().contains("below the expected 2000"), "{}", status.message());
+        assert_eq!(service.state.read().trades_executed, 0);
+
+        let status = execute("missing".to_string()).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+
+        let response = execute(stable.id.clone()).await.unwrap().into_inner();
+        assert!(response.success);
+        assert_eq!(service.state.read().trades_executed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_trade_without_simulator_fails_closed() {
+        let opp = legs_opportunity(ChainId::Ethereum, Address::repeat_byte(0xA1), Address::repeat_byte(0xA2), 20.0);
+        let request = || {
+            Request::new(ExecuteTradeRequest {
+                chain: Chain::Ethereum as i32,
+                delegation_id: "delegation-1".to_string(),
+                opportunity_id: opp.id.clone(),
+                ..Default::default()
+            })
+        };
+
+        let service = DefiServiceImpl::new();
+        assert!(service.state.read().opportunity_queue.push(opp.clone()));
+        let status = service.execute_trade(request()).await.unwrap_err();
+        assert_eq!(crate::errors::error_detail(&status).unwrap().reason, "SIMULATION_FAILED");
+        assert!(status.message().contains("No simulator for ethereum"), "{}", status.message());
+        assert_eq!(service.state.read().trades_executed, 0);
+
+        // Disabling it still needs a router to send the trade to
+        let unrouted = DefiServiceImpl::new().with_preflight_required(false);
+        assert!(unrouted.state.read().opportunity_queue.push(opp.clone()));
+        let status = unrouted.execute_trade(request()).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+        assert_eq!(crate::errors::error_detail(&status).unwrap().reason, "CHAIN_NOT_CONFIGURED");
+        assert_eq!(unrouted.state.read().trades_executed, 0);
+
+        // Skipped only once explicitly disabled, and then actually broadcast
+        let unchecked = DefiServiceImpl::new()
+            .with_preflight_required(false)
+            .with_router(Address::repeat_byte(0xEE));
+        assert!(unchecked.state.read().opportunity_queue.push(opp.clone()));
+        let response = unchecked.execute_trade(request()).await.unwrap().into_inner();
+        assert!(response.success);
+        assert!(!response.tx_hash.is_empty());
+        assert_eq!(response.status, ExecutionStatus::ExecutionSubmitted as i32);
+        assert!(response.simulated_profit.is_empty());
+        assert_eq!(unchecked.state.read().trades_executed, 1);
+
+        // A dry run still needs the simulation it reports
+        let dry_run = DefiServiceImpl::new()
+            .with_preflight_required(false)
+            .with_submitter_config(SubmitterConfig {
+                dry_run: true,
+                ..Default::default()
+            });
+        assert!(dry_run.state.read().opportunity_queue.push(opp.clone()));
+        let status = dry_run.execute_trade(request()).await.unwrap_err();
+        assert_eq!(crate::errors::error_detail(&status).unwrap().reason, "SIMULATION_FAILED");
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_reports_simulation_without_submitting() {
+        let (buy_pool, sell_pool) = (Address::repeat_byte(0xA1), Address::repeat_byte(0xA2));
+        let service = DefiServiceImpl::new()
+            .with_simulator(router_simulator(&[(buy_pool, vec![0x00]), (sell_pool, vec![0x00])]))
+            .with_submitter_config(SubmitterConfig {
+                dry_run: true,
+                ..Default::default()
+            });
+        // The router pays out exactly the expected 1000 wei
+        let mut opp = legs_opportunity(ChainId::Ethereum, buy_pool, sell_pool, 20.0);
+        opp.net_profit = U256::from(1_000u64);
+        assert!(service.state.read().opportunity_queue.push(opp.clone()));
+
+        let request = |opportunity_id: String| {
+            Request::new(ExecuteTradeRequest {
+                chain: Chain::Ethereum as i32,
+                delegation_id: "delegation-1".to_string(),
+                opportunity_id,
+                ..Default::default()
+            })
+        };
+        let response = service.execute_trade(request(opp.id.clone())).await.unwrap().into_inner();
+
+        assert!(response.success);
+        assert!(response.dry_run);
+        assert!(response.tx_hash.starts_with("0x") && response.tx_hash.len() == 66, "{}", response.tx_hash);
+        assert_eq!(response.simulated_profit, "1000");
+
+        // Stored as never sent, so the trade tracker has nothing to poll
+        // and shutdown nothing to wait for
+        assert_eq!(response.status, ExecutionStatus::ExecutionSimulated as i32);
+        {
+            let state = service.state.read();
+            let record = state.trades.get(&response.trade_id).unwrap();
+            assert!(record.dry_run);
+            assert_eq!(record.status, defi_executor::TradeStatus::Simulated);
+            assert_eq!(record.tx_hash.as_deref(), Some(response.tx_hash.as_str()));
+            assert!(state.trades.awaiting_receipt().is_empty());
+            assert_eq!(state.trades.in_flight(), 0);
+            assert_eq!(state.trades_executed, 0);
+        }
+
+        // Without an opportunity there is nothing to simulate
+        let status = service.execute_trade(request(String::new())).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_live_execution_goes_through_submitter() {
+        let (buy_pool, sell_pool) = (Address::repeat_byte(0xA1), Address::repeat_byte(0xA2));
+        let simulator = || router_simulator(&[(buy_pool, vec![0x00]), (sell_pool, vec![0x00])]);
+        let mut opp = legs_opportunity(ChainId::Ethereum, buy_pool, sell_pool, 20.0);
+        opp.net_profit = U256::from(1_000u64);
+        let request = || {
+            Request::new(ExecuteTradeRequest {
+                chain: Chain::Ethereum as i32,
+                delegation_id: "delegation-1".to_string(),
+                opportunity_id: opp.id.clone(),
+                ..Default::default()
+            })
+        };
+
+        // Sent once through the relay
+        let service = DefiServiceImpl::new().with_simulator(simulator());
+        assert!(service.state.read().opportunity_queue.push(opp.clone()));
+        let response = service.execute_trade(request()).await.unwrap().into_inner();
+        assert!(response.success);
+        assert!(!response.tx_hash.is_empty());
+        let record = service.state.read().trades.get(&response.trade_id).unwrap();
+        assert_eq!(record.tx_hash.as_deref(), Some(response.tx_hash.as_str()));
+        // Handed to the receipt tracker
+        assert_eq!(response.status, ExecutionStatus::ExecutionSubmitted as i32);
+        assert_eq!(record.status, TradeStatus::Submitted);
+        assert_eq!(
+            service.state.read().trades.awaiting_receipt(),
+            vec![(response.trade_id.clone(), response.tx_hash.clone())]
+        );
+
+        // Repriced in the public mempool until the opportunity expires
+        let service = DefiServiceImpl::new()
+            .with_simulator(simulator())
+            .with_submitter_config(SubmitterConfig {
+                use_flashbots: false,
+                reprice: Some(defi_executor::RepriceConfig {
+                    poll_interval: Duration::from_millis(10),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            });
+        opp.expires_at_ms = now_ms() + 300;
+        assert!(service.state.read().opportunity_queue.push(opp.clone()));
+        let response = service.execute_trade(request()).await.unwrap().into_inner();
+        assert!(!response.success);
+        assert_eq!(response.status, ExecutionStatus::ExecutionFailed as i32);
+        assert_eq!(response.error, ExecutionError::NotMined.to_string());
+        let record = service.state.read().trades.get(&response.trade_id).unwrap();
+        assert_eq!(record.status, TradeStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_retries_submit_once() {
+        let (buy_pool, sell_pool) = (Address::repeat_byte(0xA1), Address::repeat_byte(0xA2));
+        let service = DefiServiceImpl::new()
+            .with_simulator(router_simulator(&[(buy_pool, vec![0x00]), (sell_pool, vec![0x00])]));
+        let mut opp = legs_opportunity(ChainId::Ethereum, buy_pool, sell_pool, 20.0);
+        opp.net_profit = U256::from(1_000u64);
+        assert!(service.state.read().opportunity_queue.push(opp.clone()));
+
+        let execute = || {
+            let service = service.clone();
+            let request = ExecuteTradeRequest {
+                chain: Chain::Ethereum as i32,
+                delegation_id: "delegation-1".to_string(),
+                opportunity_id: opp.id.clone(),
+                idempotency_key: "order-7".to_string(),
+                ..Default::default()
+            };
+            tokio::spawn(async move { service.execute_trade(Request::new(request)).await.unwrap().into_inner() })
+        };
+
+        let (first, second) = tokio::join!(execute(), execute());
+        let (first, second) = (first.unwrap(), second.unwrap());
+        assert_eq!(first.trade_id, second.trade_id);
+        assert_eq!(service.state.read().trades.len(), 1);
+        assert_eq!(service.state.read().trades_executed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_submit_error_is_recorded_as_failed_trade() {
+        let (buy_pool, sell_pool) = (Address::repeat_byte(0xA1), Address::repeat_byte(0xA2));
+        let service = DefiServiceImpl::new()
+            .with_simulator(router_simulator(&[(buy_pool, vec![0x00]), (sell_pool, vec![0x00])]));
+        let mut opp = legs_opportunity(ChainId::Ethereum, buy_pool, sell_pool, 20.0);
+        opp.net_profit = U256::from(1_000u64);
+        // Costed at a gas price above the cap, so the transaction can't be built
+        opp.gas_cost_wei = U256::from(10u64).pow(U256::from(30u64));
+        assert!(service.state.read().opportunity_queue.push(opp.clone()));
+
+        let status = service
+            .execute_trade(Request::new(ExecuteTradeRequest {
+                chain: Chain::Ethereum as i32,
+                delegation_id: "delegation-1".to_string(),
+                opportunity_id: opp.id.clone(),
+                idempotency_key: "order-7".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Internal);
+
+        let record = service
+            .state
+            .read()
+            .trades
+            .get_by_idempotency_key("delegation-1", "order-7")
+            .unwrap();
+        assert_eq!(record.status, TradeStatus::Failed);
+        assert_eq!(record.error.as_deref(), Some(status.message()));
+        assert_eq!(service.state.read().trades.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_live_execution_prices_gas_at_current_fees() {
+        let (buy_pool, sell_pool) = (Address::repeat_byte(0xA1), Address::repeat_byte(0xA2));
+        // A node that refuses connections, so fees can't be fetched
+        let service = DefiServiceImpl::new()
+            .with_simulator(router_simulator(&[(buy_pool, vec![0x00]), (sell_pool, vec![0x00])]))
+            .with_submitter_config(SubmitterConfig {
+                rpc_url: "http://127.0.0.1:1".to_string(),
+                ..Default::default()
+            });
+        let mut opp = legs_opportunity(ChainId::Ethereum, buy_pool, sell_pool, 20.0);
+        opp.net_profit = U256::from(1_000u64);
+        assert!(service.state.read().opportunity_queue.push(opp.clone()));
+
+        let status = service
+            .execute_trade(Request::new(ExecuteTradeRequest {
+                chain: Chain::Ethereum as i32,
+                delegation_id: "delegation-1".to_string(),
+                opportunity_id: opp.id.clone(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert!(status.message().starts_with("Failed to fetch gas price"), "{}", status.message());
+        assert_eq!(service.state.read().trades_executed, 0);
+    }
+
+    async fn submit_trade(service: &DefiServiceImpl) -> Result<String, Status> {
+        let opportunity_id = queue_unchecked_opportunity(service);
+        service
+            .execute_trade(Request::new(ExecuteTradeRequest {
+                chain: Chain::Ethereum as i32,
+                delegation_id: "delegation-1".to_string(),
+                opportunity_id,
+                amount_in: "1000".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .map(|r| r.into_inner().trade_id)
+    }
+
+    #[tokio::test]
+    async fn test_settled_trades_net_realized_profit() {
+        let service = DefiServiceImpl::new();
+        let usdc = defi_core::get_token(ChainId::Ethereum, "USDC").unwrap().address;
+        let opportunity_id = queue_unchecked_opportunity(&service);
+
+        let mut trade_ids = Vec::new();
+        for _ in 0..2 {
+            let executed = service
+                .execute_trade(Request::new(ExecuteTradeRequest {
+                    chain: Chain::Ethereum as i32,
+                    delegation_id: "delegation-1".to_string(),
+                    opportunity_id: opportunity_id.clone(),
+                    token_in: usdc.to_string(),
+                    amount_in: "1000000000".to_string(),
+                    ..Default::default()
+                }))
+                .await
+                .unwrap()
+                .into_inner();
+            trade_ids.push(executed.trade_id);
+        }
+        // Tell the two broadcasts apart by hash
+        let trades = Arc::clone(&service.state.read().trades);
+        for (trade_id, tx_hash) in trade_ids.iter().zip(["0xwin", "0xloss"]) {
+            let mut record = trades.get(trade_id).unwrap();
+            record.tx_hash = Some(tx_hash.to_string());
+            trades.insert(record);
+        }
+
+        // WETH at 2000 USDC, so 150k gas at 10 gwei costs $3
+        let weth = defi_core::get_token(ChainId::Ethereum, "WETH").unwrap().address;
+        service.state.read().price_state.update_pool(defi_core::Pool::UniswapV2(defi_core::UniswapV2Pool {
+            address: Address::repeat_byte(0xA1),
+            token0: weth,
+            token1: usdc,
+            reserve0: U256::from(1_000_000_000_000_000_000_000u128),
+            reserve1: U256::from(2_000_000_000_000u64),
+            decimals0: 18,
+            decimals1: 6,
+            fee_bps: 30,
+            chain: ChainId::Ethereum,
+            dex: defi_core::DexProtocol::UniswapV2,
+            block_number: 1,
+        }));
+
+        // +25 USDC and -10.5 USDC, in 6-decimal units, each less $3 of gas
+        let settled = settle_trades(&service.state, |tx_hash| async move {
+            let delta: i64 = if tx_hash == "0xwin" { 25_000_000 } else { -10_500_000 };
+            Some(defi_executor::TradeReceipt {
+                success: true,
+                block_number: 1,
+                gas_used: 150_000,
+                effective_gas_price: U256::from(10_000_000_000u64),
+                actual_output: None,
+                balance_delta: alloy_primitives::I256::try_from(delta).unwrap(),
+            })
+        })
+        .await;
+        assert_eq!(settled, 2);
+
+        let status = service
+            .get_system_status(Request::new(GetSystemStatusRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!((status.total_profit_usd - 8.5).abs() < 1e-6, "{}", status.total_profit_usd);
+        assert!((trades.get(&trade_ids[1]).unwrap().actual_profit_usd + 13.5).abs() < 1e-6);
+
+        // Already settled trades aren't booked twice
+        assert_eq!(settle_trades(&service.state, |_| async { None }).await, 0);
+        assert!((service.state.read().total_profit_usd - 8.5).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_stop_waits_for_in_flight_trade() {
+        let service = DefiServiceImpl::new().with_drain_timeout(Duration::from_secs(5));
+        let trade_id = submit_trade(&service).await.unwrap();
+        let trades = Arc::clone(&service.state.read().trades);
+
+        // Settles 200ms into shutdown
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            trades.mark_submitted(&trade_id, "0xabc");
+            trades.apply_receipt(&trade_id, defi_executor::TradeReceipt {
+                success: true,
+                block_number: 1,
+                gas_used: 150_000,
+                effective_gas_price: U256::ZERO,
+                actual_output: None,
+                balance_delta: alloy_primitives::I256::ZERO,
+            }, 1.0);
+        });
+
+        let start = Instant::now();
+        service.stop().await;
+        let elapsed = start.elapsed();
+
+        assert!(elapsed >= Duration::from_millis(200), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_secs(5), "{:?}", elapsed);
+        assert_eq!(service.state.read().trades.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_stop_gives_up_after_drain_timeout() {
+        let service = DefiServiceImpl::new().with_drain_timeout(Duration::from_millis(150));
+        let trade_id = submit_trade(&service).await.unwrap();
+
+        let start = Instant::now();
+        service.stop().await;
+        let elapsed = start.elapsed();
+
+        assert!(elapsed >= Duration::from_millis(150), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_secs(2), "{:?}", elapsed);
+        assert_eq!(
+            service.state.read().trades.get(&trade_id).unwrap().status,
+            defi_executor::TradeStatus::Submitted
+        );
+
+        // No new trades once draining has begun
+        let status = submit_trade(&service).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+        assert_eq!(crate::errors::error_detail(&status).unwrap().reason, "SHUTTING_DOWN");
+    }
+
+    #[tokio::test]
+    async fn test_update_config_rejects_dex_missing_from_chains() {
+        let service = DefiServiceImpl::new();
+        let update = |chain: Chain| UpdateConfigRequest {
+            enabled_chains: vec![chain as i32],
+            enabled_dexes: vec![DexProtocol::DexAerodrome as i32],
+            ..Default::default()
+        };
+
+        let status = service.update_config(Request::new(update(Chain::Ethereum))).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert_eq!(crate::errors::error_detail(&status).unwrap().reason, "INVALID_CONFIG");
+
+        let response = service.update_config(Request::new(update(Chain::Base))).await.unwrap();
+        assert!(response.into_inner().success);
+    }
+
+    #[tokio::test]
+    async fn test_audited_actions_write_json_records() {
+        let path = std::env::temp_dir().join(format!("defi-service-audit-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let service = DefiServiceImpl::new()
+            .with_audit_logger(AuditLogger::new().with_file(&path).unwrap());
+
+        let trade_id = submit_trade(&service).await.unwrap();
+        service
+            .update_config(Request::new(UpdateConfigRequest {
+                min_profit_usd: Some(5.0),
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+        service
+            .start_scanner(Request::new(StartScannerRequest { chains: vec![Chain::Arbitrum as i32] }))
+            .await
+            .unwrap();
+        service.stop_scanner(Request::new(StopScannerRequest {})).await.unwrap();
+        service.audit.flush().await;
+
+        let records: Vec<serde_json::Value> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        std::fs::remove_file(&path).unwrap();
+
+        let events: Vec<&str> = records.iter().map(|r| r["event"].as_str().unwrap()).collect();
+        assert_eq!(events, vec![
+            "TRADE_EXECUTE_REQUEST",
+            "TRADE_EXECUTE_RESULT",
+            "CONFIG_UPDATE",
+            "SCANNER_START",
+            "SCANNER_STOP",
+        ]);
+        for (i, record) in records.iter().enumerate() {
+            assert_eq!(record["seq"], i as u64);
+            assert!(record["timestamp_ms"].as_u64().unwrap() > 0);
+            assert!(record["outcome"].is_string());
+            assert!(record["message"].is_string());
+            assert!(record["details"].is_object());
+        }
+
+        assert_eq!(records[0]["outcome"], "requested");
+        assert_eq!(records[0]["details"]["trade_id"], trade_id.as_str());
+        assert_eq!(records[0]["details"]["chain"], "ethereum");
+        assert_eq!(records[1]["details"]["trade_id"], trade_id.as_str());
+        assert_eq!(records[1]["outcome"], "success");
+        assert_eq!(records[2]["details"]["min_profit_usd"], 5.0);
+        assert_eq!(records[3]["details"]["chains_count"], 1);
+        assert_eq!(records[4]["details"]["was_running"], true);
+    }
+}
@@ -0,0 +1,59 @@
+//! REST gateway integration tests
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use serde_json::Value;
+use tower::ServiceExt;
+
+use defi_grpc_server::{rest, DefiServiceImpl};
+
+async fn get_json(uri: &str) -> (StatusCode, Value) {
+    let app = rest::router(DefiServiceImpl::new());
+
+    let response = app
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_status_endpoint() {
+    let (status, json) = get_json("/status").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["success"], true);
+    assert!(json["uptime_seconds"].is_u64());
+    assert!(json["chain_statuses"].is_array());
+    assert!(json["warming_up"].is_boolean());
+}
+
+#[tokio::test]
+async fn test_opportunities_endpoint_requires_scanner() {
+    let (status, json) = get_json("/opportunities?chains=ethereum,arbitrum&limit=5").await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"], "Scanner not running");
+}
+
+#[tokio::test]
+async fn test_price_endpoint_reports_missing_price() {
+    let (status, json) = get_json(
+        "/price?token=0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2&chain=ethereum",
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(json["error"].as_str().unwrap().starts_with("Price not found"));
+}
+
+#[tokio::test]
+async fn test_unknown_chain_is_bad_request() {
+    let (status, json) = get_json("/opportunities?chains=solana").await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"], "Unknown chain: solana");
+}
@@ -1,22 +1,56 @@
 //! Price feed aggregator - coordinates multiple feeds
 
+use alloy_primitives::Address;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, RwLock};
 use tokio::task::JoinHandle;
 use tracing::{error, info, warn};
 
-use defi_core::{ChainId, DexProtocol, RpcConfig};
-use crate::feeds::{FeedConfig, PriceUpdate, UniswapV3Feed};
-use crate::state::PriceState;
+use defi_core::{validate_dexes, ChainId, CoreError, DexFeeTable, DexProtocol, RpcConfig};
+use crate::feeds::{
+    FeedConfig, FeedKind, LogFeed, PoolFetcher, PriceUpdate, DEFAULT_MAX_FRAME_SIZE, DEFAULT_MAX_MESSAGE_SIZE,
+    DEFAULT_SUBSCRIBE_TIMEOUT, DEFAULT_UPDATE_BUFFER,
+};
+use crate::mempool::{MempoolConfig, MempoolMonitor};
+use crate::ratelimit::RateLimiters;
+use crate::state::{PriceDeviationConfig, PriceState};
+
+/// Wait before reconnecting a mempool subscription that closed or failed
+const MEMPOOL_RECONNECT_DELAY: Duration = Duration::from_secs(5);
 
 /// Aggregator configuration
 #[derive(Debug, Clone)]
 pub struct AggregatorConfig {
     pub chains: Vec<ChainConfig>,
     pub cleanup_interval: Duration,
+    /// Prices older than this are dropped by cleanup
     pub max_price_age: Duration,
+    /// Pools not updated for this long are dropped by cleanup
+    pub max_pool_age: Duration,
+    /// Restart a feed whose task has exited, or that has written no price
+    /// or pool for this long since it was last (re)started. Zero disables
+    /// restarts.
+    pub feed_timeout: Duration,
+    /// Swap fees for DEXes that don't charge the pool's reported fee
+    pub dex_fees: DexFeeTable,
+    /// Quarantine pools priced too far from the other DEXes. None trusts
+    /// every feed.
+    pub price_deviation: Option<PriceDeviationConfig>,
+    /// Track pending transactions for competition estimates. None leaves
+    /// `competing_txs` at zero.
+    pub mempool: Option<MempoolConfig>,
+    /// Re-read V3 tick bitmaps over each chain's `rpc_http` this often.
+    /// Zero disables refreshes, leaving V3 quotes on in-range liquidity.
+    pub tick_refresh_interval: Duration,
+    /// Bitmap words read either side of a pool's current tick
+    pub tick_word_range: i16,
+    /// Request budget for each chain's `rpc_http`, shared by every caller
+    /// that takes its limiter from `PriceAggregator::rate_limiters`
+    pub rpc_requests_per_second: u32,
 }
 
 #[derive(Debug, Clone)]
@@ -25,6 +59,20 @@ pub struct ChainConfig {
     pub rpc_http: String,
     pub rpc_ws: String,
     pub enabled_dexes: Vec<DexProtocol>,
+    /// Wrapped native / stablecoin pool that prices gas on this chain
+    pub native_usd_pool: Option<Address>,
+}
+
+impl ChainConfig {
+    /// Endpoint config for this chain's RPC under a shared request budget
+    fn rpc_config(&self, requests_per_second: u32) -> RpcConfig {
+        RpcConfig {
+            http_url: self.rpc_http.clone(),
+            ws_url: Some(self.rpc_ws.clone()).filter(|ws| !ws.is_empty()),
+            chain: self.chain,
+            requests_per_second,
+        }
+    }
 }
 
 impl Default for AggregatorConfig {
@@ -33,7 +81,26 @@ impl Default for AggregatorConfig {
             chains: vec![],
             cleanup_interval: Duration::from_secs(60),
             max_price_age: Duration::from_secs(30),
+            max_pool_age: Duration::from_secs(600),
+            feed_timeout: Duration::from_secs(120),
+            dex_fees: DexFeeTable::new(),
+            price_deviation: Some(PriceDeviationConfig::default()),
+            mempool: None,
+            tick_refresh_interval: Duration::from_secs(60),
+            tick_word_range: 2,
+            rpc_requests_per_second: 25,
+        }
+    }
+}
+
+impl AggregatorConfig {
+    /// Reject DEXes enabled on, or given fees for, a chain they aren't
+    /// deployed on
+    pub fn validate(&self) -> Result<(), CoreError> {
+        for chain_config in &self.chains {
+            validate_dexes(chain_config.chain, chain_config.enabled_dexes.iter().copied())?;
         }
+        self.dex_fees.validate()
     }
 }
 
@@ -44,19 +111,164 @@ pub struct PriceAggregator {
     update_rx: Option<mpsc::Receiver<PriceUpdate>>,
     update_tx: mpsc::Sender<PriceUpdate>,
     handles: Vec<JoinHandle<()>>,
+    feeds: Vec<FeedHandle>,
+    mempool: Option<Arc<MempoolMonitor>>,
+    rate_limiters: Arc<RateLimiters>,
     running: Arc<RwLock<bool>>,
 }
 
+/// Connection tracking for a spawned feed task
+#[derive(Debug, Clone)]
+struct FeedHandle {
+    chain: ChainId,
+    dex: DexProtocol,
+    connected: Arc<AtomicBool>,
+    restarts: Arc<AtomicU64>,
+}
+
+/// Point-in-time connection state of a feed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedStatus {
+    pub chain: ChainId,
+    pub dex: DexProtocol,
+    pub connected: bool,
+    /// Times the supervisor has restarted the feed
+    pub restarts: u64,
+}
+
+/// A feed task owned by the supervisor. Dropping it aborts the task.
+struct SupervisedFeed {
+    config: FeedConfig,
+    connected: Arc<AtomicBool>,
+    restarts: Arc<AtomicU64>,
+    task: JoinHandle<()>,
+    started_at: Instant,
+}
+
+impl SupervisedFeed {
+    /// Whether the task has exited or the feed has been silent for longer
+    /// than `timeout`. A freshly (re)started feed gets a full `timeout`.
+    fn is_stalled(&self, state: &PriceState, timeout: Duration) -> bool {
+        if self.task.is_finished() {
+            return true;
+        }
+        let since_start = self.started_at.elapsed();
+        let silent_for = state
+            .feed_last_update_age(self.config.chain, self.config.dex)
+            .map_or(since_start, |age| age.min(since_start));
+        silent_for > timeout
+    }
+
+    fn restart(&mut self, state: &Arc<PriceState>, tx: &mpsc::Sender<PriceUpdate>) {
+        self.task.abort();
+        self.connected.store(false, Ordering::Relaxed);
+        self.task = spawn_feed(self.config.clone(), Arc::clone(state), tx.clone(), Arc::clone(&self.connected));
+        self.started_at = Instant::now();
+        self.restarts.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Drop for SupervisedFeed {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn spawn_feed(
+    config: FeedConfig,
+    state: Arc<PriceState>,
+    tx: mpsc::Sender<PriceUpdate>,
+    connected: Arc<AtomicBool>,
+) -> JoinHandle<()> {
+    let mut feed = LogFeed::new(config, state).with_connection_flag(connected);
+    tokio::spawn(async move {
+        feed.run(tx).await;
+    })
+}
+
+/// Restart stalled feeds until the aggregator stops. Owns the feed tasks,
+/// so aborting the supervisor aborts them too.
+async fn supervise(
+    mut feeds: Vec<SupervisedFeed>,
+    state: Arc<PriceState>,
+    tx: mpsc::Sender<PriceUpdate>,
+    timeout: Duration,
+    running: Arc<RwLock<bool>>,
+) {
+    if timeout.is_zero() {
+        // Nothing to check, but the feeds live as long as this task
+        std::future::pending::<()>().await;
+    }
+
+    let mut interval = tokio::time::interval((timeout / 2).max(Duration::from_millis(1)));
+    loop {
+        interval.tick().await;
+
+        if !*running.read().await {
+            break;
+        }
+
+        for feed in &mut feeds {
+            if feed.is_stalled(&state, timeout) {
+                warn!(
+                    "Restarting {} feed for {}: no updates within {:?}",
+                    feed.config.dex.name(),
+                    feed.config.chain,
+                    timeout
+                );
+                feed.restart(&state, &tx);
+            }
+        }
+    }
+}
+
+/// Re-fetch the ticks of every tracked V3 pool until the aggregator stops
+async fn refresh_ticks(
+    fetcher: PoolFetcher,
+    state: Arc<PriceState>,
+    every: Duration,
+    word_range: i16,
+    running: Arc<RwLock<bool>>,
+) {
+    let mut interval = tokio::time::interval(every);
+    loop {
+        interval.tick().await;
+
+        if !*running.read().await {
+            break;
+        }
+
+        let refreshed = fetcher.refresh_v3_ticks(&state, word_range).await;
+        if refreshed > 0 {
+            info!("Refreshed ticks for {} V3 pools", refreshed);
+        }
+    }
+}
+
 impl PriceAggregator {
     pub fn new(config: AggregatorConfig) -> Self {
         let (update_tx, update_rx) = mpsc::channel(10_000);
 
+        let state = Arc::new(PriceState::new());
+        state.set_dex_fees(config.dex_fees.clone());
+        state.set_price_deviation(config.price_deviation);
+        for chain_config in &config.chains {
+            if let Some(pool) = chain_config.native_usd_pool {
+                state.set_native_usd_pool(chain_config.chain, pool);
+            }
+        }
+
+        let mempool = config.mempool.clone().map(|c| Arc::new(MempoolMonitor::new(c)));
+
         Self {
             config,
-            state: Arc::new(PriceState::new()),
+            state,
             update_rx: Some(update_rx),
             update_tx,
             handles: vec![],
+            feeds: vec![],
+            mempool,
+            rate_limiters: Arc::new(RateLimiters::new()),
             running: Arc::new(RwLock::new(false)),
         }
     }
@@ -66,6 +278,16 @@ impl PriceAggregator {
         Arc::clone(&self.state)
     }
 
+    /// Mempool monitor, when one is configured. Started by `start`.
+    pub fn mempool(&self) -> Option<Arc<MempoolMonitor>> {
+        self.mempool.clone()
+    }
+
+    /// Limiters shared by every caller of the configured RPC endpoints
+    pub fn rate_limiters(&self) -> Arc<RateLimiters> {
+        Arc::clone(&self.rate_limiters)
+    }
+
     /// Get update receiver (can only be taken once)
     pub fn take_update_receiver(&mut self) -> Option<mpsc::Receiver<PriceUpdate>> {
         self.update_rx.take()
@@ -73,45 +295,74 @@ impl PriceAggregator {
 
     /// Start all feeds
     pub async fn start(&mut self) -> anyhow::Result<()> {
+        self.config.validate()?;
         info!("Starting price aggregator");
         *self.running.write().await = true;
 
+        let mut supervised = Vec::new();
+
         for chain_config in &self.config.chains {
             for dex in &chain_config.enabled_dexes {
+                let Some(kind) = FeedKind::for_dex(*dex) else {
+                    warn!("Feed for {} not implemented yet", dex.name());
+                    continue;
+                };
+
                 let feed_config = FeedConfig {
                     chain: chain_config.chain,
                     dex: *dex,
                     ws_url: chain_config.rpc_ws.clone(),
                     reconnect_delay: Duration::from_secs(5),
+                    max_reconnect_delay: Duration::from_secs(120),
+                    stable_connection_threshold: Duration::from_secs(60),
                     max_reconnects: 10,
+                    record_path: None,
+                    topics: kind.topics(),
+                    addresses: vec![],
+                    max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+                    max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+                    update_buffer: DEFAULT_UPDATE_BUFFER,
+                    subscribe_timeout: DEFAULT_SUBSCRIBE_TIMEOUT,
                 };
 
-                match dex {
-                    DexProtocol::UniswapV3 => {
-                        let mut feed = UniswapV3Feed::new(
-                            feed_config,
-                            Arc::clone(&self.state),
-                        );
-                        let tx = self.update_tx.clone();
-
-                        let handle = tokio::spawn(async move {
-                            feed.run(tx).await;
-                        });
+                let connected = Arc::new(AtomicBool::new(false));
+                let restarts = Arc::new(AtomicU64::new(0));
+                self.feeds.push(FeedHandle {
+                    chain: chain_config.chain,
+                    dex: *dex,
+                    connected: Arc::clone(&connected),
+                    restarts: Arc::clone(&restarts),
+                });
 
-                        self.handles.push(handle);
-                        info!("Started {} feed for {}", dex.name(), chain_config.chain);
-                    }
-                    _ => {
-                        // Add other DEX feed implementations
-                        warn!("Feed for {} not implemented yet", dex.name());
-                    }
-                }
+                let task = spawn_feed(
+                    feed_config.clone(),
+                    Arc::clone(&self.state),
+                    self.update_tx.clone(),
+                    Arc::clone(&connected),
+                );
+                supervised.push(SupervisedFeed {
+                    config: feed_config,
+                    connected,
+                    restarts,
+                    task,
+                    started_at: Instant::now(),
+                });
+                info!("Started {} feed for {}", dex.name(), chain_config.chain);
             }
         }
 
+        self.handles.push(tokio::spawn(supervise(
+            supervised,
+            Arc::clone(&self.state),
+            self.update_tx.clone(),
+            self.config.feed_timeout,
+            Arc::clone(&self.running),
+        )));
+
         // Start cleanup task
         let state = Arc::clone(&self.state);
-        let max_age = self.config.max_price_age;
+        let max_price_age = self.config.max_price_age;
+        let max_pool_age = self.config.max_pool_age;
         let cleanup_interval = self.config.cleanup_interval;
         let running = Arc::clone(&self.running);
 
@@ -125,7 +376,8 @@ impl PriceAggregator {
                     break;
                 }
 
-                state.cleanup(max_age);
+                state.cleanup(max_price_age, max_pool_age);
+                state.compact();
                 let stats = state.stats();
                 info!(
                     "Price state: {} prices, {} pools, {} updates",
@@ -136,6 +388,37 @@ impl PriceAggregator {
 
         self.handles.push(cleanup_handle);
 
+        // Feeds only carry slot0, so V3 ticks come from periodic RPC reads
+        if !self.config.tick_refresh_interval.is_zero() {
+            for chain_config in self.config.chains.iter().filter(|c| !c.rpc_http.is_empty()) {
+                let rpc = chain_config.rpc_config(self.config.rpc_requests_per_second);
+                let fetcher = PoolFetcher::new(chain_config.chain, rpc.http_url.clone(), self.rate_limiters.get(&rpc));
+                self.handles.push(tokio::spawn(refresh_ticks(
+                    fetcher,
+                    Arc::clone(&self.state),
+                    self.config.tick_refresh_interval,
+                    self.config.tick_word_range,
+                    Arc::clone(&self.running),
+                )));
+                info!("Refreshing V3 ticks for {} every {:?}", chain_config.chain, self.config.tick_refresh_interval);
+            }
+        }
+
+        // Keep the mempool subscription open for as long as we run
+        if let Some(mempool) = &self.mempool {
+            let mempool = Arc::clone(mempool);
+            let running = Arc::clone(&self.running);
+            self.handles.push(tokio::spawn(async move {
+                while *running.read().await {
+                    if let Err(e) = mempool.run().await {
+                        warn!("Mempool monitor error: {}", e);
+                    }
+                    tokio::time::sleep(MEMPOOL_RECONNECT_DELAY).await;
+                }
+            }));
+            info!("Started mempool monitor");
+        }
+
         Ok(())
     }
 
@@ -147,6 +430,28 @@ impl PriceAggregator {
         for handle in self.handles.drain(..) {
             handle.abort();
         }
+
+        for feed in self.feeds.drain(..) {
+            feed.connected.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Connection state of every started feed
+    pub fn feed_statuses(&self) -> Vec<FeedStatus> {
+        self.feeds
+            .iter()
+            .map(|f| FeedStatus {
+                chain: f.chain,
+                dex: f.dex,
+                connected: f.connected.load(Ordering::Relaxed),
+                restarts: f.restarts.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// Chains this aggregator is configured for
+    pub fn chains(&self) -> Vec<ChainId> {
+        self.config.chains.iter().map(|c| c.chain).collect()
     }
 
     /// Check if running
@@ -159,7 +464,8 @@ impl PriceAggregator {
         let state_stats = self.state.stats();
 
         AggregatorStats {
-            feed_count: self.handles.len(),
+            feed_count: self.feeds.len(),
+            feed_restarts: self.feeds.iter().map(|f| f.restarts.load(Ordering::Relaxed)).sum(),
             price_count: state_stats.price_count,
             pool_count: state_stats.pool_count,
             update_count: state_stats.update_count,
@@ -169,12 +475,16 @@ impl PriceAggregator {
 }
 
 /// Aggregator statistics
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AggregatorStats {
     pub feed_count: usize,
+    /// Restarts across all feeds since the aggregator started
+    #[serde(default)]
+    pub feed_restarts: u64,
     pub price_count: usize,
     pub pool_count: usize,
     pub update_count: u64,
+    #[serde(rename = "last_update_age_ms", with = "defi_core::serde_helpers::duration_ms")]
     pub last_update_age: Duration,
 }
 
@@ -182,6 +492,46 @@ pub struct AggregatorStats {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_stats_round_trip() {
+        let stats = AggregatorStats {
+            feed_count: 3,
+            feed_restarts: 1,
+            price_count: 10,
+            pool_count: 6,
+            update_count: 42,
+            last_update_age: Duration::from_secs(2),
+        };
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["last_update_age_ms"], 2000);
+        assert_eq!(serde_json::from_value::<AggregatorStats>(json).unwrap(), stats);
+    }
+
+    #[test]
+    fn test_validate_rejects_dex_missing_from_chain() {
+        let config = |chain| AggregatorConfig {
+            chains: vec![ChainConfig {
+                chain,
+                rpc_http: String::new(),
+                rpc_ws: String::new(),
+                enabled_dexes: vec![DexProtocol::UniswapV3, DexProtocol::Aerodrome],
+                native_usd_pool: None,
+            }],
+            ..Default::default()
+        };
+
+        assert!(matches!(config(ChainId::Ethereum).validate(), Err(CoreError::InvalidConfig(_))));
+        assert!(config(ChainId::Base).validate().is_ok());
+
+        // Fee overrides are held to the same rule
+        let fees = AggregatorConfig {
+            dex_fees: DexFeeTable::new().with_fee(ChainId::Ethereum, DexProtocol::Aerodrome, 20),
+            ..config(ChainId::Base)
+        };
+        assert!(matches!(fees.validate(), Err(CoreError::InvalidConfig(_))));
+    }
+
     #[tokio::test]
     async fn test_aggregator_creation() {
         let config = AggregatorConfig::default();
@@ -189,5 +539,160 @@ mod tests {
 
         assert!(!aggregator.is_running().await);
         assert_eq!(aggregator.stats().feed_count, 0);
+        assert!(aggregator.feed_statuses().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_start_spawns_feed_per_supported_dex() {
+        let chain = |chain: ChainId, enabled_dexes: Vec<DexProtocol>| ChainConfig {
+            chain,
+            rpc_http: String::new(),
+            // Nothing listens here; feeds just sit in their reconnect loop
+            rpc_ws: "ws://127.0.0.1:1".to_string(),
+            enabled_dexes,
+            native_usd_pool: None,
+        };
+        let mut aggregator = PriceAggregator::new(AggregatorConfig {
+            chains: vec![
+                chain(ChainId::Base, vec![DexProtocol::Aerodrome, DexProtocol::UniswapV3]),
+                chain(ChainId::Polygon, vec![DexProtocol::QuickSwap, DexProtocol::Curve]),
+                chain(ChainId::Arbitrum, vec![DexProtocol::Camelot]),
+            ],
+            ..Default::default()
+        });
+
+        aggregator.start().await.unwrap();
+
+        // Curve has no feed yet and is skipped
+        let started: Vec<(ChainId, DexProtocol)> = aggregator
+            .feed_statuses()
+            .into_iter()
+            .map(|s| (s.chain, s.dex))
+            .collect();
+        assert_eq!(started, vec![
+            (ChainId::Base, DexProtocol::Aerodrome),
+            (ChainId::Base, DexProtocol::UniswapV3),
+            (ChainId::Polygon, DexProtocol::QuickSwap),
+            (ChainId::Arbitrum, DexProtocol::Camelot),
+        ]);
+        // The supervisor owns the feed tasks; the other handle is cleanup
+        assert_eq!(aggregator.handles.len(), 2);
+
+        aggregator.stop().await;
+        assert_eq!(aggregator.stats().feed_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_start_runs_configured_mempool_monitor() {
+        let aggregator = PriceAggregator::new(AggregatorConfig::default());
+        assert!(aggregator.mempool().is_none());
+
+        let mut aggregator = PriceAggregator::new(AggregatorConfig {
+            mempool: Some(MempoolConfig {
+                // Nothing listens here; the monitor keeps retrying
+                ws_url: "ws://127.0.0.1:1".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        aggregator.start().await.unwrap();
+
+        assert!(aggregator.mempool().is_some());
+        // Supervisor, cleanup and the mempool monitor
+        assert_eq!(aggregator.handles.len(), 3);
+        aggregator.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_start_refreshes_ticks_for_chains_with_rpc() {
+        let chain = |chain: ChainId, rpc_http: &str| ChainConfig {
+            chain,
+            rpc_http: rpc_http.to_string(),
+            rpc_ws: String::new(),
+            enabled_dexes: vec![],
+            native_usd_pool: None,
+        };
+        let config = AggregatorConfig {
+            chains: vec![chain(ChainId::Ethereum, "http://127.0.0.1:1"), chain(ChainId::Base, "")],
+            ..Default::default()
+        };
+
+        let mut aggregator = PriceAggregator::new(config.clone());
+        aggregator.start().await.unwrap();
+        // Supervisor, cleanup and one refresher for the chain with an RPC
+        assert_eq!(aggregator.handles.len(), 3);
+
+        // The refresher took its limiter from the shared set, at the configured rate
+        let limiter = aggregator.rate_limiters().get(&config.chains[0].rpc_config(1));
+        assert_eq!(limiter.requests_per_second(), config.rpc_requests_per_second);
+        aggregator.stop().await;
+
+        let mut aggregator = PriceAggregator::new(AggregatorConfig {
+            tick_refresh_interval: Duration::ZERO,
+            ..config
+        });
+        aggregator.start().await.unwrap();
+        assert_eq!(aggregator.handles.len(), 2);
+        aggregator.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_silent_feeds_are_restarted() {
+        let mut aggregator = PriceAggregator::new(AggregatorConfig {
+            chains: vec![ChainConfig {
+                chain: ChainId::Base,
+                rpc_http: String::new(),
+                // Nothing listens here, so neither feed ever writes an update
+                rpc_ws: "ws://127.0.0.1:1".to_string(),
+                enabled_dexes: vec![DexProtocol::Aerodrome, DexProtocol::UniswapV3],
+                native_usd_pool: None,
+            }],
+            feed_timeout: Duration::from_millis(100),
+            ..Default::default()
+        });
+        aggregator.start().await.unwrap();
+
+        // Keep the Aerodrome heartbeat fresh as if its feed were live
+        let state = aggregator.state();
+        let heartbeat = tokio::spawn(async move {
+            loop {
+                state.update_price(defi_core::Price {
+                    value: 1.0,
+                    token: alloy_primitives::Address::repeat_byte(1),
+                    quote_token: alloy_primitives::Address::repeat_byte(2),
+                    dex: DexProtocol::Aerodrome,
+                    chain: ChainId::Base,
+                    block_number: 1,
+                    timestamp_ms: 0,
+                });
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        });
+
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        heartbeat.abort();
+
+        let restarts: HashMap<DexProtocol, u64> = aggregator
+            .feed_statuses()
+            .into_iter()
+            .map(|s| (s.dex, s.restarts))
+            .collect();
+        assert_eq!(restarts[&DexProtocol::Aerodrome], 0);
+        assert!(restarts[&DexProtocol::UniswapV3] >= 1);
+        assert_eq!(aggregator.stats().feed_restarts, restarts[&DexProtocol::UniswapV3]);
+
+        aggregator.stop().await;
+    }
+
+    #[test]
+    fn test_feed_kind_per_dex() {
+        assert_eq!(FeedKind::for_dex(DexProtocol::QuickSwap), Some(FeedKind::UniswapV2));
+        assert_eq!(FeedKind::for_dex(DexProtocol::Camelot), Some(FeedKind::UniswapV2));
+        assert_eq!(FeedKind::for_dex(DexProtocol::Aerodrome), Some(FeedKind::Solidly));
+        assert_eq!(FeedKind::for_dex(DexProtocol::Curve), None);
+
+        // Solidly reserves are uint256, so its Sync event hashes differently
+        assert_ne!(FeedKind::Solidly.topics(), FeedKind::UniswapV2.topics());
+        assert_eq!(FeedKind::UniswapV3.topics(), vec![crate::feeds::UNISWAP_V3_SWAP_TOPIC.to_string()]);
     }
 }
@@ -1,16 +1,25 @@
 //! WebSocket price feed implementations
 
 use alloy_primitives::{Address, U256};
+use futures_util::stream::{SplitSink, SplitStream};
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::error::TrySendError;
 use tokio::sync::mpsc;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+use tokio_tungstenite::{connect_async_with_config, tungstenite::Message, MaybeTlsStream, WebSocketStream};
 use tracing::{debug, error, info, warn};
 
-use defi_core::{ChainId, DexProtocol, Pool, Price, UniswapV2Pool, UniswapV3Pool};
+use defi_core::{ChainId, DexProtocol, Pool, Price, TickInfo, UniswapV2Pool, UniswapV3Pool};
+use crate::ratelimit::RateLimiter;
 use crate::state::PriceState;
+use crate::writer::{BackgroundWriter, RecordWriter};
 
 /// Price update message
 #[derive(Debug, Clone)]
@@ -28,10 +37,270 @@ pub struct FeedConfig {
     pub dex: DexProtocol,
     pub ws_url: String,
     pub reconnect_delay: Duration,
+    pub max_reconnect_delay: Duration,
+    pub stable_connection_threshold: Duration,
     pub max_reconnects: u32,
+    /// Append every raw message to this JSONL file for later replay
+    pub record_path: Option<PathBuf>,
+    /// Event topic0 hashes to subscribe to (any of them matches)
+    pub topics: Vec<String>,
+    /// Only receive logs from these contracts (empty = every address)
+    pub addresses: Vec<Address>,
+    /// Largest WebSocket message accepted; bigger ones drop the connection
+    pub max_message_size: usize,
+    /// Largest single WebSocket frame accepted
+    pub max_frame_size: usize,
+    /// Updates held for a slow consumer before the oldest are dropped
+    pub update_buffer: usize,
+    /// Reconnect if the node hasn't confirmed the subscription this long
+    /// after it was sent. Zero waits forever.
+    pub subscribe_timeout: Duration,
+}
+
+/// Default `FeedConfig::max_message_size`, enough for large log batches
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 64 << 20;
+
+/// Default `FeedConfig::max_frame_size`
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 16 << 20;
+
+/// Default `FeedConfig::update_buffer`
+pub const DEFAULT_UPDATE_BUFFER: usize = 1_024;
+
+/// Default `FeedConfig::subscribe_timeout`
+pub const DEFAULT_SUBSCRIBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// tungstenite limits for a feed's connection
+fn websocket_config(config: &FeedConfig) -> WebSocketConfig {
+    WebSocketConfig {
+        max_message_size: Some(config.max_message_size),
+        max_frame_size: Some(config.max_frame_size),
+        ..Default::default()
+    }
+}
+
+/// Uniswap V3 `Swap(address,address,int256,int256,uint160,uint128,int24)`
+pub const UNISWAP_V3_SWAP_TOPIC: &str =
+    "0xc42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67";
+
+/// Uniswap V2 `Sync(uint112,uint112)`, shared by Sushi, QuickSwap and Camelot
+pub const UNISWAP_V2_SYNC_TOPIC: &str =
+    "0x1c411e9a96e071241c2f21f7726b17ae89e3cab4c78be50e062b03a9fffbbad1";
+
+/// Solidly `Sync(uint256,uint256)` as emitted by Aerodrome pools
+pub const SOLIDLY_SYNC_TOPIC: &str =
+    "0xcf2aa50876cdfbb541206f89af0ee78d44a2abf8d328e37fa4917f982149848a";
+
+/// Pool event family a feed listens for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedKind {
+    /// Concentrated liquidity swaps
+    UniswapV3,
+    /// Constant-product reserve syncs
+    UniswapV2,
+    /// Solidly stable/volatile reserve syncs
+    Solidly,
+}
+
+impl FeedKind {
+    /// Feed kind serving a DEX, or `None` if no feed exists for it yet
+    pub fn for_dex(dex: DexProtocol) -> Option<Self> {
+        match dex {
+            DexProtocol::UniswapV3 => Some(FeedKind::UniswapV3),
+            DexProtocol::UniswapV2
+            | DexProtocol::SushiSwap
+            | DexProtocol::QuickSwap
+            | DexProtocol::Camelot => Some(FeedKind::UniswapV2),
+            DexProtocol::Aerodrome => Some(FeedKind::Solidly),
+            DexProtocol::Curve | DexProtocol::Balancer | DexProtocol::AaveV3 => None,
+        }
+    }
+
+    /// Event topics to subscribe to
+    pub fn topics(&self) -> Vec<String> {
+        let topic = match self {
+            FeedKind::UniswapV3 => UNISWAP_V3_SWAP_TOPIC,
+            FeedKind::UniswapV2 => UNISWAP_V2_SYNC_TOPIC,
+            FeedKind::Solidly => SOLIDLY_SYNC_TOPIC,
+        };
+        vec![topic.to_string()]
+    }
+}
+
+/// `eth_subscribe` request for the feed's configured topics and addresses
+pub fn subscribe_request(config: &FeedConfig) -> serde_json::Value {
+    let mut filter = serde_json::Map::new();
+
+    if !config.addresses.is_empty() {
+        let addresses: Vec<String> = config.addresses.iter().map(|a| format!("{:#x}", a)).collect();
+        filter.insert("address".to_string(), serde_json::json!(addresses));
+    }
+
+    // A nested array in topic position 0 matches any of the listed events
+    let topics = match config.topics.as_slice() {
+        [] => serde_json::json!([]),
+        [topic] => serde_json::json!([topic]),
+        topics => serde_json::json!([topics]),
+    };
+    filter.insert("topics".to_string(), topics);
+
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_subscribe",
+        "params": ["logs", filter]
+    })
+}
+
+/// Exponential reconnect backoff with ±20% jitter
+///
+/// The delay doubles from `base` on every failed attempt up to `max`.
+/// Jitter is drawn from an injectable source returning values in `[-1.0, 1.0]`
+/// so tests can drive it deterministically.
+pub struct ReconnectBackoff {
+    base: Duration,
+    max: Duration,
+    attempt: u32,
+    jitter: Box<dyn FnMut() -> f64 + Send + Sync>,
+}
+
+impl ReconnectBackoff {
+    /// Maximum jitter as a fraction of the delay
+    pub const JITTER_FRACTION: f64 = 0.2;
+
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self::with_jitter(base, max, || rand::random::<f64>() * 2.0 - 1.0)
+    }
+
+    pub fn with_jitter(
+        base: Duration,
+        max: Duration,
+        jitter: impl FnMut() -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            base,
+            max,
+            attempt: 0,
+            jitter: Box::new(jitter),
+        }
+    }
+
+    /// Delay before the next attempt, advancing the backoff
+    pub fn next_delay(&mut self) -> Duration {
+        let factor = 2u32.saturating_pow(self.attempt.min(31));
+        let delay = self.base.saturating_mul(factor).min(self.max);
+        self.attempt = self.attempt.saturating_add(1);
+
+        let sample = (self.jitter)().clamp(-1.0, 1.0);
+        delay.mul_f64(1.0 + sample * Self::JITTER_FRACTION)
+    }
+
+    /// Reset to the base delay (after a stable connection)
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+}
+
+/// Raw WebSocket message as written to a recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedMessage {
+    pub timestamp_ms: u64,
+    pub text: String,
+}
+
+/// Messages a `FeedRecorder` buffers for its writer before dropping new ones
+const RECORDER_BUFFER: usize = 10_000;
+
+/// Appends raw feed messages to a JSONL recording
+///
+/// `record` only queues the message. A writer task on the blocking pool
+/// appends it, so a slow disk never stalls the feed's read loop. Messages
+/// arriving while the buffer is full are dropped, counted and warned about.
+pub struct FeedRecorder {
+    writer: BackgroundWriter<RecordedMessage>,
+}
+
+impl FeedRecorder {
+    /// Open `path` for appending, creating it if needed, and start the
+    /// writer task. Must be called from within a tokio runtime.
+    pub fn create(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        let writer = BackgroundWriter::spawn("Feed recording", RECORDER_BUFFER, RecordingWriter(BufWriter::new(file)));
+
+        Ok(Self { writer })
+    }
+
+    /// Queue one message stamped with the current wall-clock time
+    pub fn record(&self, text: &str) {
+        self.record_at(chrono::Utc::now().timestamp_millis() as u64, text)
+    }
+
+    pub fn record_at(&self, timestamp_ms: u64, text: &str) {
+        self.writer.send(RecordedMessage {
+            timestamp_ms,
+            text: text.to_string(),
+        });
+    }
+
+    /// Wait until everything recorded so far is on disk
+    pub async fn flush(&self) {
+        self.writer.flush().await;
+    }
+
+    /// Messages dropped because the writer fell behind
+    pub fn dropped(&self) -> u64 {
+        self.writer.dropped()
+    }
+}
+
+/// JSONL recording file behind a `FeedRecorder`
+struct RecordingWriter(BufWriter<std::fs::File>);
+
+impl RecordWriter for RecordingWriter {
+    type Record = RecordedMessage;
+
+    fn write(&mut self, message: RecordedMessage) {
+        let written = serde_json::to_writer(&mut self.0, &message)
+            .map_err(std::io::Error::from)
+            .and_then(|_| self.0.write_all(b"\n"));
+        if let Err(e) = written {
+            warn!("Failed to record feed message: {}", e);
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Err(e) = self.0.flush() {
+            warn!("Failed to flush feed recording: {}", e);
+        }
+    }
+}
+
+/// Read every message from a JSONL recording
+pub fn load_recording(path: impl AsRef<Path>) -> anyhow::Result<Vec<RecordedMessage>> {
+    let reader = BufReader::new(std::fs::File::open(path)?);
+    let mut messages = Vec::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        messages.push(serde_json::from_str(&line)?);
+    }
+
+    Ok(messages)
 }
 
 /// Base trait for price feeds
+///
+/// `connect` establishes the upstream connection and `is_connected` reports
+/// it until `disconnect` closes it or the upstream drops.
 #[async_trait::async_trait]
 pub trait PriceFeed: Send + Sync {
     async fn connect(&mut self) -> anyhow::Result<()>;
@@ -41,35 +310,153 @@ pub trait PriceFeed: Send + Sync {
     fn dex(&self) -> DexProtocol;
 }
 
-/// Uniswap V3 WebSocket feed
-pub struct UniswapV3Feed {
+/// WebSocket stream returned by `connect_async_with_config`
+type WsStream = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Open WebSocket with its subscription already sent
+struct Connection {
+    write: SplitSink<WsStream, Message>,
+    read: SplitStream<WsStream>,
+}
+
+/// Updates parsed from the socket but not yet taken by the consumer.
+///
+/// Holds at most `capacity`; past that the oldest update is dropped, so a
+/// slow consumer never stalls reads from the socket.
+struct PendingUpdates {
+    queue: VecDeque<PriceUpdate>,
+    capacity: usize,
+    dropped: u64,
+}
+
+impl PendingUpdates {
+    fn new(capacity: usize) -> Self {
+        Self {
+            queue: VecDeque::new(),
+            capacity: capacity.max(1),
+            dropped: 0,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    fn push(&mut self, update: PriceUpdate) {
+        if self.queue.len() >= self.capacity {
+            self.queue.pop_front();
+            self.dropped += 1;
+        }
+        self.queue.push_back(update);
+    }
+
+    fn pop(&mut self) -> Option<PriceUpdate> {
+        self.queue.pop_front()
+    }
+
+    /// Hand over as many updates as the channel has room for, oldest
+    /// first. Returns false once the channel is closed.
+    fn drain(&mut self, updates_tx: &mpsc::Sender<PriceUpdate>) -> bool {
+        while let Some(update) = self.queue.pop_front() {
+            match updates_tx.try_send(update) {
+                Ok(()) => {}
+                Err(TrySendError::Full(update)) => {
+                    self.queue.push_front(update);
+                    return true;
+                }
+                Err(TrySendError::Closed(_)) => return false,
+            }
+        }
+        true
+    }
+}
+
+/// Log-subscription WebSocket feed
+///
+/// Serves every [`FeedKind`]: the kind only decides which topics go into
+/// `FeedConfig::topics`.
+///
+/// `connect` opens the socket and subscribes, `run` consumes updates and
+/// reconnects on failure, and `disconnect` closes the socket. A subscription
+/// the node doesn't confirm within `FeedConfig::subscribe_timeout` counts as
+/// a failed connection.
+///
+/// Updates the consumer channel can't take yet wait in a buffer of
+/// `FeedConfig::update_buffer`, dropping the oldest when it fills.
+pub struct LogFeed {
     config: FeedConfig,
     state: Arc<PriceState>,
-    connected: bool,
-    shutdown: Option<tokio::sync::oneshot::Sender<()>>,
+    connected: Arc<AtomicBool>,
+    connected_at: Option<Instant>,
+    /// When an unconfirmed subscription gives up
+    confirm_deadline: Option<tokio::time::Instant>,
+    backoff: ReconnectBackoff,
+    connection: Option<Connection>,
+    recorder: Option<FeedRecorder>,
+    pending: PendingUpdates,
 }
 
-impl UniswapV3Feed {
+impl LogFeed {
     pub fn new(config: FeedConfig, state: Arc<PriceState>) -> Self {
+        let backoff = ReconnectBackoff::new(config.reconnect_delay, config.max_reconnect_delay);
+        let pending = PendingUpdates::new(config.update_buffer);
+
         Self {
             config,
             state,
-            connected: false,
-            shutdown: None,
+            connected: Arc::new(AtomicBool::new(false)),
+            connected_at: None,
+            confirm_deadline: None,
+            backoff,
+            connection: None,
+            recorder: None,
+            pending,
         }
     }
 
+    /// Updates dropped because the consumer fell behind
+    pub fn dropped_updates(&self) -> u64 {
+        self.pending.dropped
+    }
+
+    /// Shared connection flag, readable after the feed is moved into its task
+    pub fn connection_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.connected)
+    }
+
+    /// Report connection state through an existing flag, e.g. the one a
+    /// restarted feed's predecessor used
+    pub fn with_connection_flag(mut self, connected: Arc<AtomicBool>) -> Self {
+        self.connected = connected;
+        self
+    }
+
+    /// Replace the reconnect backoff (e.g. with deterministic jitter)
+    pub fn with_backoff(mut self, backoff: ReconnectBackoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Consume updates until the server closes the socket or reconnects are
+    /// exhausted. Connects first if `connect` hasn't been called.
     pub async fn run(&mut self, mut updates_tx: mpsc::Sender<PriceUpdate>) {
         let mut reconnect_count = 0;
 
         loop {
-            match self.connect_and_listen(&mut updates_tx).await {
+            let result = match self.connection {
+                Some(_) => self.listen(&mut updates_tx).await,
+                None => self.connect().await,
+            };
+
+            match result {
+                Ok(_) if self.connection.is_some() => continue,
                 Ok(_) => {
                     info!("Feed {} disconnected normally", self.config.dex.name());
                     break;
                 }
                 Err(e) => {
                     error!("Feed {} error: {}", self.config.dex.name(), e);
+                    self.drop_connection();
                     reconnect_count += 1;
 
                     if reconnect_count >= self.config.max_reconnects {
@@ -77,70 +464,108 @@ impl UniswapV3Feed {
                         break;
                     }
 
+                    let delay = self.next_reconnect_delay();
+
                     warn!(
                         "Reconnecting {} in {:?} (attempt {}/{})",
                         self.config.dex.name(),
-                        self.config.reconnect_delay,
+                        delay,
                         reconnect_count,
                         self.config.max_reconnects
                     );
 
-                    tokio::time::sleep(self.config.reconnect_delay).await;
+                    tokio::time::sleep(delay).await;
                 }
             }
         }
     }
 
-    async fn connect_and_listen(
-        &mut self,
-        updates_tx: &mut mpsc::Sender<PriceUpdate>,
-    ) -> anyhow::Result<()> {
-        info!("Connecting to {} at {}", self.config.dex.name(), self.config.ws_url);
+    /// Compute the next reconnect delay, resetting the backoff first if the
+    /// last connection stayed up longer than the stability threshold
+    fn next_reconnect_delay(&mut self) -> Duration {
+        let uptime = self.connected_at.take().map(|t| t.elapsed());
+        self.backoff_after(uptime)
+    }
 
-        let (ws_stream, _) = connect_async(&self.config.ws_url).await?;
-        let (mut write, mut read) = ws_stream.split();
+    fn backoff_after(&mut self, uptime: Option<Duration>) -> Duration {
+        if uptime.is_some_and(|u| u >= self.config.stable_connection_threshold) {
+            debug!("Connection to {} was stable, resetting backoff", self.config.dex.name());
+            self.backoff.reset();
+        }
+        self.backoff.next_delay()
+    }
 
-        self.connected = true;
-        info!("Connected to {}", self.config.dex.name());
+    /// Forget the socket without a close handshake
+    fn drop_connection(&mut self) {
+        self.connection = None;
+        self.connected.store(false, Ordering::Relaxed);
+    }
 
-        // Subscribe to pool updates
-        let subscribe_msg = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "eth_subscribe",
-            "params": ["logs", {
-                "topics": [
-                    // Uniswap V3 Swap event
-                    "0xc42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67"
-                ]
-            }]
-        });
+    /// Read messages from the open connection until it closes.
+    /// A clean close drops the connection and returns `Ok`; a subscription
+    /// left unconfirmed past its deadline returns an error.
+    async fn listen(&mut self, updates_tx: &mut mpsc::Sender<PriceUpdate>) -> anyhow::Result<()> {
+        let Some(connection) = self.connection.as_mut() else {
+            return Err(anyhow::anyhow!("Feed {} is not connected", self.config.dex.name()));
+        };
 
-        write.send(Message::Text(subscribe_msg.to_string())).await?;
+        loop {
+            // Keep reading while buffered updates wait for channel space
+            let msg = tokio::select! {
+                permit = updates_tx.reserve(), if !self.pending.is_empty() => {
+                    match permit {
+                        Ok(permit) => {
+                            if let Some(update) = self.pending.pop() {
+                                permit.send(update);
+                            }
+                            continue;
+                        }
+                        Err(_) => {
+                            debug!("Updates channel closed");
+                            break;
+                        }
+                    }
+                }
+                _ = tokio::time::sleep_until(self.confirm_deadline.unwrap_or_else(tokio::time::Instant::now)),
+                    if self.confirm_deadline.is_some() =>
+                {
+                    return Err(anyhow::anyhow!(
+                        "{} did not confirm the subscription within {:?}",
+                        self.config.dex.name(),
+                        self.config.subscribe_timeout
+                    ));
+                }
+                msg = connection.read.next() => msg,
+            };
+            let Some(msg) = msg else {
+                break;
+            };
 
-        while let Some(msg) = read.next().await {
             match msg {
                 Ok(Message::Text(text)) => {
-                    if let Ok(update) = self.parse_message(&text) {
+                    if let Some(recorder) = &self.recorder {
+                        recorder.record(&text);
+                    }
+
+                    if self.confirm_deadline.is_some() && is_subscription_confirmation(&text) {
+                        debug!("{} subscription confirmed", self.config.dex.name());
+                        self.confirm_deadline = None;
+                    }
+
+                    if let Ok(update) = parse_message(&self.config, &text) {
                         // Update local state immediately
-                        match &update {
-                            PriceUpdate::Price(p) => self.state.update_price(p.clone()),
-                            PriceUpdate::Pool(p) => self.state.update_pool(p.clone()),
-                            PriceUpdate::Block { chain, number } => {
-                                self.state.update_block(*chain, *number)
-                            }
-                            _ => {}
-                        }
+                        apply_update(&self.state, &update);
 
-                        // Send to channel for external consumers
-                        if updates_tx.send(update).await.is_err() {
+                        // Queue for external consumers without waiting on them
+                        self.pending.push(update);
+                        if !self.pending.drain(updates_tx) {
                             debug!("Updates channel closed");
                             break;
                         }
                     }
                 }
                 Ok(Message::Ping(data)) => {
-                    write.send(Message::Pong(data)).await?;
+                    connection.write.send(Message::Pong(data)).await?;
                 }
                 Ok(Message::Close(_)) => {
                     info!("WebSocket closed by server");
@@ -154,61 +579,115 @@ impl UniswapV3Feed {
             }
         }
 
-        self.connected = false;
+        self.drop_connection();
         Ok(())
     }
+}
 
-    fn parse_message(&self, text: &str) -> anyhow::Result<PriceUpdate> {
-        // Parse the WebSocket message and extract price/pool updates
-        // This is a simplified implementation - real version would decode logs properly
+/// Apply a parsed update to the shared price state
+fn apply_update(state: &PriceState, update: &PriceUpdate) {
+    match update {
+        PriceUpdate::Price(p) => {
+            state.update_price(p.clone());
+        }
+        PriceUpdate::Pool(p) => {
+            state.update_pool(p.clone());
+        }
+        PriceUpdate::Block { chain, number } => state.update_block(*chain, *number),
+        _ => {}
+    }
+}
 
-        let json: serde_json::Value = serde_json::from_str(text)?;
+/// Whether a raw message is the node's reply to `eth_subscribe`
+fn is_subscription_confirmation(text: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(text)
+        .map(|json| json.get("id").is_some() && json.get("result").is_some())
+        .unwrap_or(false)
+}
 
-        // Handle subscription confirmation
-        if json.get("result").is_some() {
-            debug!("Subscription confirmed");
-            return Err(anyhow::anyhow!("Not a price update"));
-        }
+/// Parse a raw WebSocket message into a price update
+fn parse_message(config: &FeedConfig, text: &str) -> anyhow::Result<PriceUpdate> {
+    // Parse the WebSocket message and extract price/pool updates
+    // This is a simplified implementation - real version would decode logs properly
 
-        // Handle log events
-        if let Some(params) = json.get("params") {
-            if let Some(result) = params.get("result") {
-                // Parse Swap event log
-                // In production, decode the actual log data
-                let price = Price {
-                    value: 0.0,  // Would be calculated from log data
-                    token: Address::ZERO,
-                    quote_token: Address::ZERO,
-                    dex: self.config.dex,
-                    chain: self.config.chain,
-                    block_number: 0,
-                    timestamp_ms: chrono::Utc::now().timestamp_millis() as u64,
-                };
-
-                return Ok(PriceUpdate::Price(price));
-            }
-        }
+    let json: serde_json::Value = serde_json::from_str(text)?;
+
+    // Handle subscription confirmation
+    if json.get("result").is_some() {
+        debug!("Subscription confirmed");
+        return Err(anyhow::anyhow!("Not a price update"));
+    }
+
+    // Handle log events
+    if let Some(params) = json.get("params") {
+        if let Some(result) = params.get("result") {
+            // Parse Swap event log
+            // In production, decode the actual log data
+            let price = Price {
+                value: 0.0,  // Would be calculated from log data
+                token: Address::ZERO,
+                quote_token: Address::ZERO,
+                dex: config.dex,
+                chain: config.chain,
+                block_number: 0,
+                timestamp_ms: chrono::Utc::now().timestamp_millis() as u64,
+            };
 
-        Err(anyhow::anyhow!("Unknown message format"))
+            return Ok(PriceUpdate::Price(price));
+        }
     }
+
+    Err(anyhow::anyhow!("Unknown message format"))
 }
 
 #[async_trait::async_trait]
-impl PriceFeed for UniswapV3Feed {
+impl PriceFeed for LogFeed {
+    /// Open the WebSocket and send the log subscription. A no-op when
+    /// already connected.
     async fn connect(&mut self) -> anyhow::Result<()> {
-        // Connection is handled in run()
+        if self.connection.is_some() {
+            return Ok(());
+        }
+
+        info!("Connecting to {} at {}", self.config.dex.name(), self.config.ws_url);
+
+        if self.recorder.is_none() {
+            if let Some(path) = &self.config.record_path {
+                info!("Recording {} feed to {}", self.config.dex.name(), path.display());
+                self.recorder = Some(FeedRecorder::create(path)?);
+            }
+        }
+
+        let (ws_stream, _) =
+            connect_async_with_config(&self.config.ws_url, Some(websocket_config(&self.config)), false).await?;
+        let (mut write, read) = ws_stream.split();
+
+        // Subscribe to pool updates
+        let subscribe_msg = subscribe_request(&self.config);
+        write.send(Message::Text(subscribe_msg.to_string())).await?;
+
+        self.connection = Some(Connection { write, read });
+        self.connected.store(true, Ordering::Relaxed);
+        self.connected_at = Some(Instant::now());
+        self.confirm_deadline = (!self.config.subscribe_timeout.is_zero())
+            .then(|| tokio::time::Instant::now() + self.config.subscribe_timeout);
+        info!("Connected to {}", self.config.dex.name());
+
         Ok(())
     }
 
+    /// Close the WebSocket with a close frame
     async fn disconnect(&mut self) {
-        if let Some(shutdown) = self.shutdown.take() {
-            let _ = shutdown.send(());
+        if let Some(mut connection) = self.connection.take() {
+            if let Err(e) = connection.write.close().await {
+                debug!("Error closing {} feed: {}", self.config.dex.name(), e);
+            }
         }
-        self.connected = false;
+        self.connected.store(false, Ordering::Relaxed);
     }
 
     fn is_connected(&self) -> bool {
-        self.connected
+        self.connected.load(Ordering::Relaxed)
     }
 
     fn chain(&self) -> ChainId {
@@ -220,15 +699,115 @@ impl PriceFeed for UniswapV3Feed {
     }
 }
 
+/// Replays a recorded feed into `PriceState` as a live feed would
+pub struct ReplayFeed {
+    config: FeedConfig,
+    state: Arc<PriceState>,
+    messages: Vec<RecordedMessage>,
+    /// Playback rate relative to the recording; 0 replays without delays
+    speed: f64,
+}
+
+impl ReplayFeed {
+    pub fn new(config: FeedConfig, state: Arc<PriceState>, messages: Vec<RecordedMessage>) -> Self {
+        Self {
+            config,
+            state,
+            messages,
+            speed: 1.0,
+        }
+    }
+
+    /// Replay a JSONL recording written by `FeedRecorder`
+    pub fn from_file(
+        config: FeedConfig,
+        state: Arc<PriceState>,
+        path: impl AsRef<Path>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self::new(config, state, load_recording(path)?))
+    }
+
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = speed.max(0.0);
+        self
+    }
+
+    /// Replay every message, preserving the recorded spacing scaled by
+    /// `speed`. Returns the number of updates applied.
+    pub async fn run(&self, updates_tx: mpsc::Sender<PriceUpdate>) -> usize {
+        let mut applied = 0;
+        let mut last_ts: Option<u64> = None;
+
+        for message in &self.messages {
+            if let Some(last) = last_ts {
+                let gap = message.timestamp_ms.saturating_sub(last);
+                if self.speed > 0.0 && gap > 0 {
+                    tokio::time::sleep(Duration::from_millis(gap).div_f64(self.speed)).await;
+                }
+            }
+            last_ts = Some(message.timestamp_ms);
+
+            let Ok(update) = parse_message(&self.config, &message.text) else {
+                continue;
+            };
+
+            apply_update(&self.state, &update);
+            applied += 1;
+
+            if updates_tx.send(update).await.is_err() {
+                debug!("Updates channel closed");
+                break;
+            }
+        }
+
+        applied
+    }
+}
+
 /// RPC-based pool state fetcher (for initial sync and fallback)
 pub struct PoolFetcher {
     chain: ChainId,
     rpc_url: String,
+    limiter: Arc<RateLimiter>,
+    client: reqwest::Client,
 }
 
 impl PoolFetcher {
-    pub fn new(chain: ChainId, rpc_url: String) -> Self {
-        Self { chain, rpc_url }
+    /// `limiter` should be shared with every other caller of `rpc_url`
+    pub fn new(chain: ChainId, rpc_url: String, limiter: Arc<RateLimiter>) -> Self {
+        Self { chain, rpc_url, limiter, client: reqwest::Client::new() }
+    }
+
+    /// `eth_call` against the latest block, returning the raw return data
+    async fn eth_call(&self, to: Address, data: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        self.limiter.acquire().await;
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_call",
+            "params": [
+                { "to": format!("{:#x}", to), "data": format!("0x{}", alloy_primitives::hex::encode(&data)) },
+                "latest"
+            ],
+        });
+        let response: serde_json::Value = self.client
+            .post(&self.rpc_url)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        if let Some(error) = response.get("error") {
+            anyhow::bail!("eth_call to {} on {} failed: {}", to, self.chain, error);
+        }
+        let result = response
+            .get("result")
+            .and_then(|r| r.as_str())
+            .ok_or_else(|| anyhow::anyhow!("eth_call to {} on {} returned no result", to, self.chain))?;
+        Ok(alloy_primitives::hex::decode(result.trim_start_matches("0x"))?)
     }
 
     /// Fetch V2 pool reserves
@@ -239,6 +818,8 @@ impl PoolFetcher {
     ) -> anyhow::Result<UniswapV2Pool> {
         // In production, use alloy to make the RPC call
         // getReserves() -> (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast)
+        // decimals0/decimals1 from get_decimals(chain, token), falling back to decimals()
+        self.limiter.acquire().await;
 
         todo!("Implement V2 reserves fetch with alloy")
     }
@@ -250,16 +831,660 @@ impl PoolFetcher {
     ) -> anyhow::Result<UniswapV3Pool> {
         // In production, use alloy to make the RPC call
         // slot0() -> (sqrtPriceX96, tick, observationIndex, ...)
+        // decimals0/decimals1 from get_decimals(chain, token), falling back to decimals()
+        self.limiter.acquire().await;
 
         todo!("Implement V3 slot0 fetch with alloy")
     }
 
+    /// Fetch initialized ticks within `word_range` bitmap words of the current tick
+    ///
+    /// Reads `tickBitmap(int16)` for each nearby word, then `ticks(int24)`
+    /// for every set bit. Ticks come back sorted by index.
+    pub async fn fetch_v3_ticks(
+        &self,
+        pool: &UniswapV3Pool,
+        word_range: i16,
+    ) -> anyhow::Result<Vec<TickInfo>> {
+        let spacing = pool.tick_spacing.max(1);
+        let center = tick_word(pool.tick, spacing);
+
+        let mut ticks = Vec::new();
+        for word in center.saturating_sub(word_range)..=center.saturating_add(word_range) {
+            let bitmap = self.eth_call(pool.address, encode_int_call("tickBitmap(int16)", word as i32)).await?;
+            for index in bitmap_ticks(word, decode_word(&bitmap)?, spacing) {
+                let info = self.eth_call(pool.address, encode_int_call("ticks(int24)", index)).await?;
+                ticks.push(TickInfo { index, liquidity_net: decode_liquidity_net(&info)? });
+            }
+        }
+        ticks.sort_by_key(|t| t.index);
+        Ok(ticks)
+    }
+
+    /// Re-fetch the ticks of every V3 pool tracked for this chain.
+    /// Returns how many pools were refreshed; failures are logged and skipped.
+    pub async fn refresh_v3_ticks(&self, state: &PriceState, word_range: i16) -> usize {
+        let pools: Vec<UniswapV3Pool> = state
+            .get_chain_pools(self.chain, Duration::MAX)
+            .into_iter()
+            .filter_map(|entry| match entry.pool {
+                Pool::UniswapV3(pool) => Some(pool),
+                _ => None,
+            })
+            .collect();
+
+        let mut refreshed = 0;
+        for pool in pools {
+            match self.fetch_v3_ticks(&pool, word_range).await {
+                Ok(ticks) => {
+                    if state.set_v3_ticks(self.chain, pool.address, ticks) {
+                        refreshed += 1;
+                    }
+                }
+                Err(e) => warn!("Tick fetch for V3 pool {} on {} failed: {}", pool.address, self.chain, e),
+            }
+        }
+        refreshed
+    }
+
     /// Batch fetch multiple pools
     pub async fn fetch_pools_batch(
         &self,
         addresses: &[Address],
     ) -> anyhow::Result<Vec<Pool>> {
         // Use multicall for efficiency
+        self.limiter.acquire().await;
         todo!("Implement batch pool fetch")
     }
 }
+
+/// Bitmap word holding `tick` (one word covers 256 spacings)
+fn tick_word(tick: i32, spacing: i32) -> i16 {
+    (tick.div_euclid(spacing) >> 8) as i16
+}
+
+/// Initialized ticks flagged in one `tickBitmap` word
+fn bitmap_ticks(word: i16, bitmap: U256, spacing: i32) -> Vec<i32> {
+    (0..256)
+        .filter(|bit| bitmap.bit(*bit))
+        .map(|bit| (word as i32 * 256 + bit as i32) * spacing)
+        .collect()
+}
+
+/// Calldata for a view taking one signed integer argument
+fn encode_int_call(signature: &str, value: i32) -> Vec<u8> {
+    let mut data = alloy_primitives::keccak256(signature.as_bytes())[..4].to_vec();
+    // ABI words are sign-extended to 32 bytes
+    let fill = if value < 0 { 0xff } else { 0x00 };
+    data.extend_from_slice(&[fill; 28]);
+    data.extend_from_slice(&value.to_be_bytes());
+    data
+}
+
+fn decode_word(data: &[u8]) -> anyhow::Result<U256> {
+    anyhow::ensure!(data.len() >= 32, "expected a 32-byte word, got {} bytes", data.len());
+    Ok(U256::from_be_slice(&data[..32]))
+}
+
+/// `liquidityNet` from a `ticks(int24)` return: the int128 second word
+fn decode_liquidity_net(data: &[u8]) -> anyhow::Result<i128> {
+    anyhow::ensure!(data.len() >= 64, "expected at least 64 bytes from ticks(), got {}", data.len());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&data[48..64]);
+    Ok(i128::from_be_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> FeedConfig {
+        FeedConfig {
+            chain: ChainId::Ethereum,
+            dex: DexProtocol::UniswapV3,
+            ws_url: "ws://localhost:8546".to_string(),
+            reconnect_delay: Duration::from_secs(1),
+            max_reconnect_delay: Duration::from_secs(30),
+            stable_connection_threshold: Duration::from_secs(60),
+            max_reconnects: 10,
+            record_path: None,
+            topics: vec![UNISWAP_V3_SWAP_TOPIC.to_string()],
+            addresses: vec![],
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            update_buffer: DEFAULT_UPDATE_BUFFER,
+            subscribe_timeout: DEFAULT_SUBSCRIBE_TIMEOUT,
+        }
+    }
+
+    #[test]
+    fn test_subscribe_request_uses_configured_topics() {
+        let request = subscribe_request(&test_config());
+        assert_eq!(request["method"], "eth_subscribe");
+        assert_eq!(request["params"][0], "logs");
+        assert_eq!(request["params"][1]["topics"], serde_json::json!([UNISWAP_V3_SWAP_TOPIC]));
+        assert!(request["params"][1].get("address").is_none());
+
+        let mint = "0x7a53080ba414158be7ec69b987b5fb7d07dee101fe85488f0853ae16239d0bde";
+        let pool = Address::repeat_byte(0xAB);
+        let config = FeedConfig {
+            topics: vec![UNISWAP_V3_SWAP_TOPIC.to_string(), mint.to_string()],
+            addresses: vec![pool],
+            ..test_config()
+        };
+
+        let filter = &subscribe_request(&config)["params"][1];
+        assert_eq!(filter["topics"], serde_json::json!([[UNISWAP_V3_SWAP_TOPIC, mint]]));
+        assert_eq!(filter["address"], serde_json::json!([format!("{:#x}", pool)]));
+    }
+
+    fn recording_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("defi-feed-{}-{}.jsonl", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    const MESSAGES: [&str; 3] = [
+        r#"{"jsonrpc":"2.0","id":1,"result":"0xsub"}"#,
+        r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"result":{"blockNumber":"0x1"}}}"#,
+        r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"result":{"blockNumber":"0x2"}}}"#,
+    ];
+
+    #[tokio::test]
+    async fn test_recording_round_trip() {
+        let path = recording_path("round-trip");
+        let recorder = FeedRecorder::create(&path).unwrap();
+        for (i, text) in MESSAGES.iter().enumerate() {
+            recorder.record_at(1_000 + i as u64 * 100, text);
+        }
+        recorder.flush().await;
+        assert_eq!(recorder.dropped(), 0);
+
+        let messages = load_recording(&path).unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1], RecordedMessage {
+            timestamp_ms: 1_100,
+            text: MESSAGES[1].to_string(),
+        });
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replay_matches_live_state() {
+        let config = test_config();
+
+        // Apply messages as the live feed does, recording as we go
+        let path = recording_path("replay");
+        let live = PriceState::new();
+        let recorder = FeedRecorder::create(&path).unwrap();
+        for (i, text) in MESSAGES.iter().enumerate() {
+            recorder.record_at(i as u64 * 10, text);
+            if let Ok(update) = parse_message(&config, text) {
+                apply_update(&live, &update);
+            }
+        }
+        recorder.flush().await;
+
+        let replayed = Arc::new(PriceState::new());
+        let feed = ReplayFeed::from_file(config, Arc::clone(&replayed), &path)
+            .unwrap()
+            .with_speed(0.0);
+        let (tx, mut rx) = mpsc::channel(16);
+
+        assert_eq!(feed.run(tx).await, 2);
+        assert!(matches!(rx.recv().await, Some(PriceUpdate::Price(_))));
+
+        let stats = replayed.stats();
+        assert_eq!(stats.price_count, live.stats().price_count);
+        assert_eq!(stats.update_count, live.stats().update_count);
+        let key = crate::state::PriceKey::new(ChainId::Ethereum, Address::ZERO, Address::ZERO, DexProtocol::UniswapV3);
+        assert_eq!(replayed.get_price(&key).unwrap().price.value, live.get_price(&key).unwrap().price.value);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replay_speed_scales_gaps() {
+        let messages = vec![
+            RecordedMessage { timestamp_ms: 0, text: MESSAGES[1].to_string() },
+            RecordedMessage { timestamp_ms: 4_000, text: MESSAGES[2].to_string() },
+        ];
+        // 4s of recording at 100x
+        let feed = ReplayFeed::new(test_config(), Arc::new(PriceState::new()), messages).with_speed(100.0);
+        let (tx, _rx) = mpsc::channel(16);
+
+        let start = Instant::now();
+        assert_eq!(feed.run(tx).await, 2);
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(40) && elapsed < Duration::from_secs(2), "{:?}", elapsed);
+    }
+
+    /// Local WebSocket server for a single client. Returns the client's
+    /// first message and whether the client sent a close frame.
+    async fn mock_ws_server(
+        replies: Vec<String>,
+        close_after_replies: bool,
+    ) -> (String, tokio::task::JoinHandle<(String, bool)>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+
+            let subscription = match ws.next().await {
+                Some(Ok(Message::Text(text))) => text,
+                other => panic!("expected a subscription, got {:?}", other),
+            };
+            for reply in replies {
+                ws.send(Message::Text(reply)).await.unwrap();
+            }
+            if close_after_replies {
+                let _ = ws.close(None).await;
+            }
+
+            let closed_by_client = loop {
+                match ws.next().await {
+                    Some(Ok(Message::Close(_))) => break !close_after_replies,
+                    None | Some(Err(_)) => break false,
+                    Some(Ok(_)) => {}
+                }
+            };
+            (subscription, closed_by_client)
+        });
+
+        (url, server)
+    }
+
+    #[tokio::test]
+    async fn test_connect_and_disconnect_transitions() {
+        let (url, server) = mock_ws_server(vec![], false).await;
+        let mut feed = LogFeed::new(FeedConfig { ws_url: url, ..test_config() }, Arc::new(PriceState::new()));
+        let flag = feed.connection_flag();
+        assert!(!feed.is_connected());
+
+        feed.connect().await.unwrap();
+        assert!(feed.is_connected());
+        assert!(flag.load(Ordering::Relaxed));
+
+        // Connecting again keeps the open socket
+        feed.connect().await.unwrap();
+        assert!(feed.is_connected());
+
+        feed.disconnect().await;
+        assert!(!feed.is_connected());
+        assert!(!flag.load(Ordering::Relaxed));
+
+        let (subscription, closed_by_client) = server.await.unwrap();
+        let subscription: serde_json::Value = serde_json::from_str(&subscription).unwrap();
+        assert_eq!(subscription, subscribe_request(&test_config()));
+        assert!(closed_by_client);
+    }
+
+    #[tokio::test]
+    async fn test_run_reads_until_server_closes() {
+        let (url, server) = mock_ws_server(vec![MESSAGES[0].to_string(), MESSAGES[1].to_string()], true).await;
+        let state = Arc::new(PriceState::new());
+        let mut feed = LogFeed::new(FeedConfig { ws_url: url, ..test_config() }, Arc::clone(&state));
+        feed.connect().await.unwrap();
+
+        let (tx, mut rx) = mpsc::channel(16);
+        feed.run(tx).await;
+
+        // The subscription confirmation isn't an update; the log is
+        assert!(!feed.is_connected());
+        assert!(matches!(rx.recv().await, Some(PriceUpdate::Price(_))));
+        assert!(rx.recv().await.is_none());
+        assert_eq!(state.stats().price_count, 1);
+        server.await.unwrap();
+    }
+
+    /// Local WebSocket server that takes subscriptions and never answers.
+    /// Counts the connections it accepts.
+    async fn silent_ws_server() -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let accepted = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        let counter = Arc::clone(&accepted);
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::Relaxed);
+                tokio::spawn(async move {
+                    let Ok(mut ws) = tokio_tungstenite::accept_async(socket).await else {
+                        return;
+                    };
+                    while let Some(Ok(_)) = ws.next().await {}
+                });
+            }
+        });
+
+        (url, accepted)
+    }
+
+    #[tokio::test]
+    async fn test_unconfirmed_subscription_reconnects() {
+        let (url, accepted) = silent_ws_server().await;
+        let config = FeedConfig {
+            ws_url: url,
+            reconnect_delay: Duration::from_millis(10),
+            max_reconnect_delay: Duration::from_millis(10),
+            max_reconnects: 3,
+            subscribe_timeout: Duration::from_millis(50),
+            ..test_config()
+        };
+        let mut feed = LogFeed::new(config, Arc::new(PriceState::new()));
+        let (tx, _rx) = mpsc::channel(16);
+
+        // Every connection times out, so run gives up after the last reconnect
+        tokio::time::timeout(Duration::from_secs(5), feed.run(tx)).await.unwrap();
+        assert!(!feed.is_connected());
+        assert_eq!(accepted.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn test_confirmed_subscription_outlives_timeout() {
+        let (url, _server) = mock_ws_server(vec![MESSAGES[0].to_string()], false).await;
+        let config = FeedConfig {
+            ws_url: url,
+            subscribe_timeout: Duration::from_millis(50),
+            ..test_config()
+        };
+        let mut feed = LogFeed::new(config, Arc::new(PriceState::new()));
+        let (tx, _rx) = mpsc::channel(16);
+
+        // Still listening well past the timeout
+        assert!(tokio::time::timeout(Duration::from_millis(300), feed.run(tx)).await.is_err());
+        assert!(feed.is_connected());
+    }
+
+    #[test]
+    fn test_subscription_confirmation_detection() {
+        assert!(is_subscription_confirmation(MESSAGES[0]));
+        assert!(!is_subscription_confirmation(MESSAGES[1]));
+        assert!(!is_subscription_confirmation("not json"));
+    }
+
+    /// A log notification padded past `len` bytes
+    fn oversized_message(len: usize) -> String {
+        format!(
+            r#"{{"jsonrpc":"2.0","method":"eth_subscription","params":{{"result":{{"blockNumber":"0x1","data":"0x{}"}}}}}}"#,
+            "ab".repeat(len / 2)
+        )
+    }
+
+    #[tokio::test]
+    async fn test_message_size_limit_is_honored() {
+        // Within the default limits the large log is delivered
+        let (url, server) = mock_ws_server(vec![oversized_message(64 * 1024)], true).await;
+        let mut feed = LogFeed::new(FeedConfig { ws_url: url, ..test_config() }, Arc::new(PriceState::new()));
+        let (tx, mut rx) = mpsc::channel(16);
+        feed.run(tx).await;
+        assert!(matches!(rx.recv().await, Some(PriceUpdate::Price(_))));
+        server.await.unwrap();
+
+        // With a 16 KiB cap the same message is rejected and the connection dropped
+        let (url, server) = mock_ws_server(vec![oversized_message(64 * 1024)], false).await;
+        let config = FeedConfig {
+            ws_url: url,
+            max_message_size: 16 * 1024,
+            max_frame_size: 16 * 1024,
+            max_reconnects: 1,
+            ..test_config()
+        };
+        let state = Arc::new(PriceState::new());
+        let mut feed = LogFeed::new(config, Arc::clone(&state));
+        let (tx, mut rx) = mpsc::channel(16);
+        feed.run(tx).await;
+
+        assert!(!feed.is_connected());
+        assert!(rx.recv().await.is_none());
+        assert_eq!(state.stats().price_count, 0);
+        let (_, closed_by_client) = server.await.unwrap();
+        assert!(!closed_by_client);
+    }
+
+    #[test]
+    fn test_websocket_config_uses_feed_limits() {
+        let config = FeedConfig { max_message_size: 1_000, max_frame_size: 500, ..test_config() };
+        let ws_config = websocket_config(&config);
+        assert_eq!(ws_config.max_message_size, Some(1_000));
+        assert_eq!(ws_config.max_frame_size, Some(500));
+    }
+
+    #[test]
+    fn test_pending_updates_drop_oldest() {
+        let block = |number| PriceUpdate::Block { chain: ChainId::Ethereum, number };
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut pending = PendingUpdates::new(2);
+
+        // The channel takes one, the buffer two more, then the oldest buffered goes
+        for number in 0..4 {
+            pending.push(block(number));
+            assert!(pending.drain(&tx));
+        }
+        assert_eq!(pending.dropped, 1);
+
+        let mut received = Vec::new();
+        while let Ok(PriceUpdate::Block { number, .. }) = rx.try_recv() {
+            received.push(number);
+            pending.drain(&tx);
+        }
+        assert_eq!(received, vec![0, 2, 3]);
+        assert!(pending.is_empty());
+
+        drop(rx);
+        pending.push(block(4));
+        assert!(!pending.drain(&tx));
+    }
+
+    #[tokio::test]
+    async fn test_connect_failure_stays_disconnected() {
+        // Nothing listens on port 1
+        let config = FeedConfig { ws_url: "ws://127.0.0.1:1".to_string(), ..test_config() };
+        let mut feed = LogFeed::new(config, Arc::new(PriceState::new()));
+
+        assert!(feed.connect().await.is_err());
+        assert!(!feed.is_connected());
+
+        // Disconnecting a feed that never connected is harmless
+        feed.disconnect().await;
+        assert!(!feed.is_connected());
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_cap() {
+        let mut backoff = ReconnectBackoff::with_jitter(
+            Duration::from_secs(1),
+            Duration::from_secs(10),
+            || 0.0,
+        );
+
+        let delays: Vec<u64> = (0..6).map(|_| backoff.next_delay().as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 8, 10, 10]);
+    }
+
+    #[test]
+    fn test_backoff_jitter_bounds() {
+        let mut high = ReconnectBackoff::with_jitter(Duration::from_secs(10), Duration::from_secs(60), || 1.0);
+        let mut low = ReconnectBackoff::with_jitter(Duration::from_secs(10), Duration::from_secs(60), || -1.0);
+
+        assert_eq!(high.next_delay(), Duration::from_secs(12));
+        assert_eq!(low.next_delay(), Duration::from_secs(8));
+
+        // Random jitter stays within ±20%
+        let mut random = ReconnectBackoff::new(Duration::from_secs(10), Duration::from_secs(60));
+        let delay = random.next_delay();
+        assert!(delay >= Duration::from_secs(8) && delay <= Duration::from_secs(12));
+    }
+
+    #[test]
+    fn test_backoff_resets_after_stable_connection() {
+        let state = Arc::new(PriceState::new());
+        let mut feed = LogFeed::new(test_config(), state).with_backoff(
+            ReconnectBackoff::with_jitter(Duration::from_secs(1), Duration::from_secs(30), || 0.0),
+        );
+
+        // Repeated quick failures grow the delay
+        assert_eq!(feed.backoff_after(None), Duration::from_secs(1));
+        assert_eq!(feed.backoff_after(Some(Duration::from_secs(5))), Duration::from_secs(2));
+        assert_eq!(feed.backoff_after(Some(Duration::from_secs(5))), Duration::from_secs(4));
+
+        // A connection that outlived the threshold starts over from the base delay
+        assert_eq!(feed.backoff_after(Some(Duration::from_secs(120))), Duration::from_secs(1));
+        assert_eq!(feed.backoff.attempt(), 1);
+    }
+
+    fn v3_pool(tick: i32, tick_spacing: i32) -> UniswapV3Pool {
+        UniswapV3Pool {
+            address: Address::repeat_byte(0x33),
+            token0: Address::repeat_byte(1),
+            token1: Address::repeat_byte(2),
+            decimals0: 18,
+            decimals1: 18,
+            fee: UniswapV3Pool::FEE_MEDIUM,
+            tick_spacing,
+            liquidity: 1_000_000,
+            sqrt_price_x96: U256::from(1u128 << 96),
+            tick,
+            ticks: vec![],
+            chain: ChainId::Ethereum,
+            block_number: 1,
+        }
+    }
+
+    #[test]
+    fn test_tick_call_encoding() {
+        let call = encode_int_call("ticks(int24)", -60);
+        assert_eq!(&call[..4], &[0xf3, 0x0d, 0xba, 0x93]);
+        assert_eq!(call.len(), 36);
+        assert!(call[4..32].iter().all(|b| *b == 0xff));
+        assert_eq!(&call[32..], &(-60i32).to_be_bytes());
+
+        let call = encode_int_call("tickBitmap(int16)", 3);
+        assert_eq!(&call[..4], &[0x53, 0x39, 0xc2, 0x96]);
+        assert!(call[4..35].iter().all(|b| *b == 0));
+        assert_eq!(call[35], 3);
+    }
+
+    #[test]
+    fn test_bitmap_word_positions() {
+        assert_eq!(tick_word(0, 60), 0);
+        assert_eq!(tick_word(256 * 60 - 1, 60), 0);
+        assert_eq!(tick_word(256 * 60, 60), 1);
+        assert_eq!(tick_word(-1, 60), -1);
+        assert_eq!(tick_word(-887_272, 1), -3466);
+
+        let bitmap = (U256::from(1) << 1) | (U256::from(1) << 3);
+        assert_eq!(bitmap_ticks(0, bitmap, 60), vec![60, 180]);
+        assert_eq!(bitmap_ticks(-1, U256::from(1) << 255, 60), vec![-60]);
+        assert!(bitmap_ticks(5, U256::ZERO, 60).is_empty());
+    }
+
+    #[test]
+    fn test_liquidity_net_decoding() {
+        let mut data = vec![0u8; 256];
+        data[32..48].fill(0xff);
+        data[48..64].copy_from_slice(&(-500i128).to_be_bytes());
+        assert_eq!(decode_liquidity_net(&data).unwrap(), -500);
+        assert!(decode_liquidity_net(&data[..40]).is_err());
+        assert!(decode_word(&data[..31]).is_err());
+    }
+
+    /// Local JSON-RPC endpoint answering `eth_call` from `results`, keyed by
+    /// calldata, with a zero word for anything else
+    async fn mock_rpc_server(results: std::collections::HashMap<Vec<u8>, Vec<u8>>) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let results = Arc::new(results);
+
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let results = results.clone();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    let body = loop {
+                        let n = socket.read(&mut buf).await.unwrap();
+                        assert!(n > 0, "client closed mid-request");
+                        request.extend_from_slice(&buf[..n]);
+                        let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") else {
+                            continue;
+                        };
+                        let headers = String::from_utf8_lossy(&request[..end]).to_ascii_lowercase();
+                        let length: usize = headers
+                            .lines()
+                            .find_map(|l| l.strip_prefix("content-length:"))
+                            .map(|v| v.trim().parse().unwrap())
+                            .unwrap_or(0);
+                        if request.len() >= end + 4 + length {
+                            break request[end + 4..end + 4 + length].to_vec();
+                        }
+                    };
+
+                    let call: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                    let data = call["params"][0]["data"].as_str().unwrap();
+                    let data = alloy_primitives::hex::decode(data.trim_start_matches("0x")).unwrap();
+                    let result = results.get(&data).cloned().unwrap_or_else(|| vec![0u8; 32]);
+                    let reply = serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": call["id"],
+                        "result": format!("0x{}", alloy_primitives::hex::encode(result)),
+                    })
+                    .to_string();
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        reply.len(),
+                        reply
+                    );
+                    socket.write_all(response.as_bytes()).await.unwrap();
+                });
+            }
+        });
+        url
+    }
+
+    fn tick_result(liquidity_net: i128) -> Vec<u8> {
+        let mut data = vec![0u8; 256];
+        if liquidity_net < 0 {
+            data[32..48].fill(0xff);
+        }
+        data[48..64].copy_from_slice(&liquidity_net.to_be_bytes());
+        data
+    }
+
+    #[tokio::test]
+    async fn test_fetch_v3_ticks_reads_bitmap_and_ticks() {
+        let word = |bitmap: U256| bitmap.to_be_bytes::<32>().to_vec();
+        let results = std::collections::HashMap::from([
+            (encode_int_call("tickBitmap(int16)", -1), word(U256::from(1) << 255)),
+            (encode_int_call("tickBitmap(int16)", 0), word((U256::from(1) << 1) | (U256::from(1) << 3))),
+            (encode_int_call("ticks(int24)", -60), tick_result(700)),
+            (encode_int_call("ticks(int24)", 60), tick_result(-500)),
+            (encode_int_call("ticks(int24)", 180), tick_result(-200)),
+        ]);
+        let url = mock_rpc_server(results).await;
+        let fetcher = PoolFetcher::new(ChainId::Ethereum, url, Arc::new(RateLimiter::new(0)));
+
+        let pool = v3_pool(10, 60);
+        let ticks = fetcher.fetch_v3_ticks(&pool, 1).await.unwrap();
+        assert_eq!(
+            ticks,
+            vec![
+                TickInfo { index: -60, liquidity_net: 700 },
+                TickInfo { index: 60, liquidity_net: -500 },
+                TickInfo { index: 180, liquidity_net: -200 },
+            ]
+        );
+
+        // Refreshing fills the tracked pool in place
+        let state = PriceState::new();
+        state.update_pool(Pool::UniswapV3(pool.clone()));
+        assert_eq!(fetcher.refresh_v3_ticks(&state, 1).await, 1);
+        match state.get_pool(ChainId::Ethereum, pool.address).unwrap().pool {
+            Pool::UniswapV3(stored) => assert_eq!(stored.ticks, ticks),
+            other => panic!("expected a V3 pool, got {:?}", other),
+        }
+    }
+}
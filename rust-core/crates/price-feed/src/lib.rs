@@ -9,7 +9,16 @@
 
 pub mod aggregator;
 pub mod feeds;
+pub mod mempool;
+pub mod ratelimit;
 pub mod state;
+pub mod writer;
+#[cfg(any(test, feature = "testing"))]
+pub mod test_support;
 
-pub use aggregator::PriceAggregator;
-pub use state::PriceState;
+pub use aggregator::{AggregatorConfig, FeedStatus, PriceAggregator};
+pub use feeds::{FeedKind, FeedRecorder, LogFeed, RecordedMessage, ReplayFeed};
+pub use mempool::{MempoolConfig, MempoolMonitor};
+pub use ratelimit::{RateLimiter, RateLimiters};
+pub use state::{PoolEntry, PoolLiquidity, PriceDeviationConfig, PriceSnapshot, PriceState};
+pub use writer::{BackgroundWriter, RecordWriter};
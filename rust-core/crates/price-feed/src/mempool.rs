@@ -0,0 +1,524 @@
+//! Mempool monitoring for competition estimates
+//!
+//! Decodes pending swaps to the pools and token pairs they trade, so
+//! detected opportunities can be annotated with `competing_txs`. The
+//! aggregator runs one when `AggregatorConfig::mempool` is set, and
+//! scanners started by the service annotate with it.
+
+use alloy_primitives::{Address, Bytes};
+use dashmap::DashMap;
+use futures_util::{SinkExt, StreamExt};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, info};
+
+use defi_core::ArbitrageOpportunity;
+
+/// Mempool monitor configuration
+#[derive(Debug, Clone)]
+pub struct MempoolConfig {
+    pub ws_url: String,
+    /// Pending txs older than this are assumed mined or dropped
+    pub pending_ttl: Duration,
+}
+
+impl Default for MempoolConfig {
+    fn default() -> Self {
+        Self {
+            ws_url: String::new(),
+            pending_ttl: Duration::from_secs(12),
+        }
+    }
+}
+
+/// Pending transactions by hash, with when each was first seen
+type PendingTxs = HashMap<String, Instant>;
+
+/// `UniswapV2Pair.swap(uint256,uint256,address,bytes)`
+const V2_PAIR_SWAP: [u8; 4] = [0x02, 0x2c, 0x0d, 0x9f];
+/// `UniswapV3Pool.swap(address,bool,int256,uint160,bytes)`
+const V3_POOL_SWAP: [u8; 4] = [0x12, 0x8a, 0xcb, 0x08];
+
+/// Uniswap V2 router swaps, with the argument index of their `address[] path`
+const V2_ROUTER_SWAPS: [([u8; 4], usize); 9] = [
+    ([0x38, 0xed, 0x17, 0x39], 2), // swapExactTokensForTokens
+    ([0x88, 0x03, 0xdb, 0xee], 2), // swapTokensForExactTokens
+    ([0x7f, 0xf3, 0x6a, 0xb5], 1), // swapExactETHForTokens
+    ([0xfb, 0x3b, 0xdb, 0x41], 1), // swapETHForExactTokens
+    ([0x18, 0xcb, 0xaf, 0xe5], 2), // swapExactTokensForETH
+    ([0x4a, 0x25, 0xd9, 0x4a], 2), // swapTokensForExactETH
+    ([0x5c, 0x11, 0xd7, 0x95], 2), // swapExactTokensForTokensSupportingFeeOnTransferTokens
+    ([0xb6, 0xf9, 0xde, 0x95], 1), // swapExactETHForTokensSupportingFeeOnTransferTokens
+    ([0x79, 0x1a, 0xc9, 0x47], 2), // swapExactTokensForETHSupportingFeeOnTransferTokens
+];
+
+/// Uniswap V3 router single-pool swaps, whose params start with
+/// `tokenIn, tokenOut` (SwapRouter and SwapRouter02)
+const V3_SINGLE_SWAPS: [[u8; 4]; 4] = [
+    [0x41, 0x4b, 0xf3, 0x89], // exactInputSingle
+    [0xdb, 0x3e, 0x21, 0x98], // exactOutputSingle
+    [0x04, 0xe4, 0x5a, 0xaf], // exactInputSingle (02)
+    [0x50, 0x23, 0xb4, 0xdf], // exactOutputSingle (02)
+];
+
+/// Uniswap V3 router multi-hop swaps, whose params start with a packed
+/// `token, fee, token, ...` path (SwapRouter and SwapRouter02)
+const V3_PATH_SWAPS: [[u8; 4]; 4] = [
+    [0xc0, 0x4b, 0x8d, 0x59], // exactInput
+    [0xf2, 0x8c, 0x04, 0x98], // exactOutput
+    [0xb8, 0x58, 0x18, 0x3f], // exactInput (02)
+    [0x09, 0xb8, 0x13, 0x46], // exactOutput (02)
+];
+
+/// What a pending transaction's calldata swaps through
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SwapTarget {
+    /// The pool the transaction calls `swap` on directly
+    Pool(Address),
+    /// The token path a router swaps along
+    Path(Vec<Address>),
+}
+
+/// Decode the pool or token path a transaction to `to` swaps through.
+/// Anything that isn't a recognised swap, including other calls to a
+/// router, decodes to None.
+fn decode_swap(to: Address, input: &[u8]) -> Option<SwapTarget> {
+    let selector: [u8; 4] = input.get(..4)?.try_into().ok()?;
+    let args = &input[4..];
+
+    if selector == V2_PAIR_SWAP || selector == V3_POOL_SWAP {
+        return Some(SwapTarget::Pool(to));
+    }
+
+    if let Some(&(_, path_arg)) = V2_ROUTER_SWAPS.iter().find(|(s, _)| *s == selector) {
+        let offset = abi_usize(args, path_arg * 32)?;
+        let len = abi_usize(args, offset)?;
+        let path = (0..len)
+            .map(|i| abi_address(args, offset + 32 + i * 32))
+            .collect::<Option<Vec<_>>>()?;
+        return (path.len() >= 2).then_some(SwapTarget::Path(path));
+    }
+
+    if V3_SINGLE_SWAPS.contains(&selector) {
+        return Some(SwapTarget::Path(vec![abi_address(args, 0)?, abi_address(args, 32)?]));
+    }
+
+    if V3_PATH_SWAPS.contains(&selector) {
+        // A struct holding `bytes` is passed by offset, as is the path within it
+        let params = abi_usize(args, 0)?;
+        let path = params.checked_add(abi_usize(args, params)?)?;
+        let len = abi_usize(args, path)?;
+        let packed = args.get(path + 32..path.checked_add(32 + len)?)?;
+        if len < 43 || (len - 20) % 23 != 0 {
+            return None;
+        }
+        let path = packed.chunks(23).map(|hop| Address::from_slice(&hop[..20])).collect();
+        return Some(SwapTarget::Path(path));
+    }
+
+    None
+}
+
+/// ABI word at byte `offset` read as an offset or length
+fn abi_usize(args: &[u8], offset: usize) -> Option<usize> {
+    let word = args.get(offset..offset.checked_add(32)?)?;
+    if word[..24].iter().any(|&b| b != 0) {
+        return None;
+    }
+    usize::try_from(u64::from_be_bytes(word[24..].try_into().ok()?)).ok()
+}
+
+/// ABI word at byte `offset` read as an address
+fn abi_address(args: &[u8], offset: usize) -> Option<Address> {
+    let word = args.get(offset..offset.checked_add(32)?)?;
+    Some(Address::from_slice(&word[12..]))
+}
+
+/// Order-independent key for a token pair
+fn pair_key(a: Address, b: Address) -> (Address, Address) {
+    if a <= b { (a, b) } else { (b, a) }
+}
+
+/// Tracks pending swaps per pool and per token pair
+#[derive(Debug)]
+pub struct MempoolMonitor {
+    config: MempoolConfig,
+    /// Pool called directly -> pending txs
+    pending: DashMap<Address, PendingTxs>,
+    /// Token pair swapped through a router -> pending txs
+    pending_pairs: DashMap<(Address, Address), PendingTxs>,
+    /// Only transactions sent to these addresses are tracked (empty =
+    /// track everything)
+    watched: DashMap<Address, ()>,
+}
+
+impl MempoolMonitor {
+    pub fn new(config: MempoolConfig) -> Self {
+        Self {
+            config,
+            pending: DashMap::new(),
+            pending_pairs: DashMap::new(),
+            watched: DashMap::new(),
+        }
+    }
+
+    /// Restrict tracking to transactions sent to the given address (pool
+    /// or router)
+    pub fn watch(&self, address: Address) {
+        self.watched.insert(address, ());
+    }
+
+    fn is_watched(&self, address: &Address) -> bool {
+        self.watched.is_empty() || self.watched.contains_key(address)
+    }
+
+    /// Record a pending swap called directly on `pool`
+    pub fn record_pending(&self, pool: Address, tx_hash: impl Into<String>) {
+        if !self.is_watched(&pool) {
+            return;
+        }
+        self.pending
+            .entry(pool)
+            .or_default()
+            .entry(tx_hash.into())
+            .or_insert_with(Instant::now);
+    }
+
+    /// Record a pending router swap between `token_a` and `token_b`, in
+    /// either direction
+    pub fn record_pending_pair(&self, token_a: Address, token_b: Address, tx_hash: impl Into<String>) {
+        self.pending_pairs
+            .entry(pair_key(token_a, token_b))
+            .or_default()
+            .entry(tx_hash.into())
+            .or_insert_with(Instant::now);
+    }
+
+    /// Forget a transaction (mined, replaced or dropped)
+    pub fn remove(&self, tx_hash: &str) {
+        for mut entry in self.pending.iter_mut() {
+            entry.value_mut().remove(tx_hash);
+        }
+        for mut entry in self.pending_pairs.iter_mut() {
+            entry.value_mut().remove(tx_hash);
+        }
+    }
+
+    /// Live pending tx count for a pool
+    pub fn pending_count(&self, pool: Address) -> u32 {
+        self.pending
+            .get(&pool)
+            .map(|txs| self.live(&txs).count() as u32)
+            .unwrap_or(0)
+    }
+
+    /// Live pending router swaps between two tokens
+    pub fn pending_pair_count(&self, token_a: Address, token_b: Address) -> u32 {
+        self.pending_pairs
+            .get(&pair_key(token_a, token_b))
+            .map(|txs| self.live(&txs).count() as u32)
+            .unwrap_or(0)
+    }
+
+    fn live<'a>(&self, txs: &'a PendingTxs) -> impl Iterator<Item = &'a String> {
+        let ttl = self.config.pending_ttl;
+        txs.iter().filter(move |(_, seen)| seen.elapsed() < ttl).map(|(hash, _)| hash)
+    }
+
+    /// Distinct pending txs swapping on any pool, or any token pair, on the
+    /// opportunity's routes
+    pub fn competing_txs(&self, opp: &ArbitrageOpportunity) -> u32 {
+        let mut competing = HashSet::new();
+
+        for step in opp.buy_route.steps.iter().chain(opp.sell_route.steps.iter()) {
+            if let Some(txs) = self.pending.get(&step.pool) {
+                competing.extend(self.live(&txs).cloned());
+            }
+            if let Some(txs) = self.pending_pairs.get(&pair_key(step.token_in, step.token_out)) {
+                competing.extend(self.live(&txs).cloned());
+            }
+        }
+
+        competing.len() as u32
+    }
+
+    /// Set `competing_txs` on an opportunity from current mempool state,
+    /// shortening its expiry to match
+    pub fn annotate(&self, opp: &mut ArbitrageOpportunity) {
+        opp.set_competing_txs(self.competing_txs(opp));
+    }
+
+    /// Drop expired entries
+    pub fn prune(&self) {
+        let ttl = self.config.pending_ttl;
+        self.pending.retain(|_, txs| {
+            txs.retain(|_, seen| seen.elapsed() < ttl);
+            !txs.is_empty()
+        });
+        self.pending_pairs.retain(|_, txs| {
+            txs.retain(|_, seen| seen.elapsed() < ttl);
+            !txs.is_empty()
+        });
+    }
+
+    /// Subscribe to full pending transactions and track them until the
+    /// connection closes
+    pub async fn run(&self) -> anyhow::Result<()> {
+        info!("Connecting mempool monitor to {}", self.config.ws_url);
+
+        let (ws_stream, _) = connect_async(&self.config.ws_url).await?;
+        let (mut write, mut read) = ws_stream.split();
+
+        let subscribe_msg = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_subscribe",
+            "params": ["newPendingTransactions", true]
+        });
+        write.send(Message::Text(subscribe_msg.to_string())).await?;
+
+        let mut last_prune = Instant::now();
+
+        while let Some(msg) = read.next().await {
+            match msg? {
+                Message::Text(text) => self.handle_message(&text),
+                Message::Ping(data) => write.send(Message::Pong(data)).await?,
+                Message::Close(_) => break,
+                _ => {}
+            }
+
+            if last_prune.elapsed() >= self.config.pending_ttl {
+                self.prune();
+                last_prune = Instant::now();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_message(&self, text: &str) {
+        let Ok(json) = serde_json::from_str::<serde_json::Value>(text) else {
+            debug!("Ignoring malformed mempool message");
+            return;
+        };
+
+        let Some(tx) = json.get("params").and_then(|p| p.get("result")) else {
+            return;
+        };
+
+        let to = tx.get("to").and_then(|v| v.as_str()).and_then(|s| s.parse::<Address>().ok());
+        let hash = tx.get("hash").and_then(|v| v.as_str());
+        let input = tx.get("input").and_then(|v| v.as_str()).and_then(|s| s.parse::<Bytes>().ok());
+
+        let (Some(to), Some(hash), Some(input)) = (to, hash, input) else {
+            return;
+        };
+        if !self.is_watched(&to) {
+            return;
+        }
+
+        match decode_swap(to, &input) {
+            Some(SwapTarget::Pool(pool)) => self.record_pending(pool, hash),
+            Some(SwapTarget::Path(path)) => {
+                for hop in path.windows(2) {
+                    self.record_pending_pair(hop[0], hop[1], hash);
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+    use defi_core::{ChainId, DexProtocol, OpportunityBuilder, SwapRoute, SwapStep};
+
+    fn route(pool: Address, token_in: u8, token_out: u8) -> SwapRoute {
+        SwapRoute {
+            steps: vec![SwapStep {
+                pool,
+                dex: DexProtocol::UniswapV2,
+                token_in: Address::repeat_byte(token_in),
+                token_out: Address::repeat_byte(token_out),
+                amount_in: U256::from(100u64),
+                amount_out: U256::from(101u64),
+                fee_bps: 30,
+                min_amount_out: U256::ZERO,
+            }],
+            chain: ChainId::Ethereum,
+            total_amount_in: U256::from(100u64),
+            total_amount_out: U256::from(101u64),
+            gas_estimate: 0,
+            price_impact_bps: 0,
+        }
+    }
+
+    #[test]
+    fn test_competing_txs_lower_success_probability() {
+        let (pool_a, pool_b) = (Address::repeat_byte(0xA), Address::repeat_byte(0xB));
+        let monitor = MempoolMonitor::new(MempoolConfig::default());
+
+        let mut opp = OpportunityBuilder::new()
+            .routes(route(pool_a, 1, 2), route(pool_b, 2, 1))
+            .build()
+            .unwrap();
+
+        monitor.annotate(&mut opp);
+        assert_eq!(opp.competing_txs, 0);
+        let uncontested = opp.success_probability();
+
+        monitor.record_pending(pool_a, "0x01");
+        monitor.record_pending(pool_a, "0x02");
+        monitor.record_pending(pool_b, "0x03");
+        monitor.record_pending(Address::repeat_byte(0xC), "0x04");
+
+        monitor.annotate(&mut opp);
+        assert_eq!(opp.competing_txs, 3);
+        assert!(opp.success_probability() < uncontested);
+        assert!(opp.ttl_ms(opp.detected_at_ms) < ChainId::Ethereum.block_time_ms() as i64);
+    }
+
+    #[test]
+    fn test_pending_expiry_and_removal() {
+        let pool = Address::repeat_byte(0xA);
+        let monitor = MempoolMonitor::new(MempoolConfig {
+            pending_ttl: Duration::ZERO,
+            ..Default::default()
+        });
+        monitor.record_pending(pool, "0x01");
+        assert_eq!(monitor.pending_count(pool), 0);
+
+        let monitor = MempoolMonitor::new(MempoolConfig::default());
+        monitor.record_pending(pool, "0x01");
+        monitor.record_pending(pool, "0x01");
+        assert_eq!(monitor.pending_count(pool), 1);
+        monitor.remove("0x01");
+        assert_eq!(monitor.pending_count(pool), 0);
+    }
+
+    /// `newPendingTransactions` notification for a tx to `to` with `input`
+    fn pending_tx(hash: &str, to: Address, input: &[u8]) -> String {
+        serde_json::json!({
+            "params": {
+                "result": { "hash": hash, "to": to, "input": Bytes::copy_from_slice(input) }
+            }
+        })
+        .to_string()
+    }
+
+    fn word(value: usize) -> [u8; 32] {
+        U256::from(value).to_be_bytes()
+    }
+
+    fn address_word(address: Address) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        word[12..].copy_from_slice(address.as_slice());
+        word
+    }
+
+    /// `swapExactTokensForTokens(amountIn, amountOutMin, path, to, deadline)`
+    fn v2_router_swap(path: &[Address]) -> Vec<u8> {
+        let mut input = vec![0x38, 0xed, 0x17, 0x39];
+        input.extend(word(100));
+        input.extend(word(99));
+        input.extend(word(5 * 32));
+        input.extend(address_word(Address::repeat_byte(0xEE)));
+        input.extend(word(0));
+        input.extend(word(path.len()));
+        for token in path {
+            input.extend(address_word(*token));
+        }
+        input
+    }
+
+    /// `exactInput((path, recipient, deadline, amountIn, amountOutMinimum))`
+    fn v3_router_swap(path: &[Address]) -> Vec<u8> {
+        let mut packed = Vec::new();
+        for (i, token) in path.iter().enumerate() {
+            if i > 0 {
+                packed.extend([0x00, 0x0b, 0xb8]); // 3000 fee
+            }
+            packed.extend_from_slice(token.as_slice());
+        }
+
+        let mut input = vec![0xc0, 0x4b, 0x8d, 0x59];
+        input.extend(word(32));
+        input.extend(word(5 * 32));
+        input.extend(address_word(Address::repeat_byte(0xEE)));
+        input.extend(word(0));
+        input.extend(word(100));
+        input.extend(word(99));
+        input.extend(word(packed.len()));
+        input.extend(&packed);
+        input.resize(4 + (input.len() - 4).div_ceil(32) * 32, 0);
+        input
+    }
+
+    #[test]
+    fn test_decodes_router_paths() {
+        let (a, b, c) = (Address::repeat_byte(1), Address::repeat_byte(2), Address::repeat_byte(3));
+        let router = Address::repeat_byte(0xF0);
+
+        assert_eq!(decode_swap(router, &v2_router_swap(&[a, b, c])), Some(SwapTarget::Path(vec![a, b, c])));
+        assert_eq!(decode_swap(router, &v3_router_swap(&[c, a])), Some(SwapTarget::Path(vec![c, a])));
+
+        let mut single = vec![0x04, 0xe4, 0x5a, 0xaf];
+        single.extend(address_word(a));
+        single.extend(address_word(b));
+        single.extend(word(500));
+        assert_eq!(decode_swap(router, &single), Some(SwapTarget::Path(vec![a, b])));
+
+        // Truncated calldata and calls that aren't swaps
+        let truncated = v2_router_swap(&[a, b]);
+        assert_eq!(decode_swap(router, &truncated[..truncated.len() - 1]), None);
+        assert_eq!(decode_swap(router, &[0xa9, 0x05, 0x9c, 0xbb]), None);
+        assert_eq!(decode_swap(router, &[]), None);
+    }
+
+    #[test]
+    fn test_router_swaps_compete_on_their_token_pair() {
+        let (pool_a, pool_b) = (Address::repeat_byte(0xA), Address::repeat_byte(0xB));
+        let router = Address::repeat_byte(0xF0);
+        let monitor = MempoolMonitor::new(MempoolConfig::default());
+        let mut opp = OpportunityBuilder::new()
+            .routes(route(pool_a, 1, 2), route(pool_b, 2, 1))
+            .build()
+            .unwrap();
+
+        let (token_1, token_2, token_3) = (Address::repeat_byte(1), Address::repeat_byte(2), Address::repeat_byte(3));
+        // Swaps 2 -> 1 on the way to 3: one competitor, counted once
+        monitor.handle_message(&pending_tx("0x01", router, &v2_router_swap(&[token_3, token_2, token_1])));
+        // Other pairs, and other calls to the same router, don't compete
+        monitor.handle_message(&pending_tx("0x02", router, &v3_router_swap(&[token_1, token_3])));
+        monitor.handle_message(&pending_tx("0x03", router, &[0xa9, 0x05, 0x9c, 0xbb]));
+        // A direct swap on one of the route's pools does
+        monitor.handle_message(&pending_tx("0x04", pool_b, &[0x02, 0x2c, 0x0d, 0x9f]));
+
+        assert_eq!(monitor.pending_pair_count(token_1, token_2), 1);
+        assert_eq!(monitor.pending_pair_count(token_3, token_1), 1);
+        monitor.annotate(&mut opp);
+        assert_eq!(opp.competing_txs, 2);
+
+        monitor.remove("0x01");
+        monitor.annotate(&mut opp);
+        assert_eq!(opp.competing_txs, 1);
+    }
+
+    #[test]
+    fn test_watchlist_filters_targets() {
+        let monitor = MempoolMonitor::new(MempoolConfig::default());
+        monitor.watch(Address::repeat_byte(0xA));
+        let swap = [0x02, 0x2c, 0x0d, 0x9f];
+
+        monitor.handle_message(&pending_tx("0x01", Address::repeat_byte(0xB), &swap));
+        monitor.handle_message(&pending_tx("0x02", Address::repeat_byte(0xA), &swap));
+        // Not a swap, so not counted even on a watched pool
+        monitor.handle_message(&pending_tx("0x03", Address::repeat_byte(0xA), &[0xa9, 0x05, 0x9c, 0xbb]));
+
+        assert_eq!(monitor.pending_count(Address::repeat_byte(0xA)), 1);
+        assert_eq!(monitor.pending_count(Address::repeat_byte(0xB)), 0);
+    }
+}
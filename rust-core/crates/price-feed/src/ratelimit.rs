@@ -0,0 +1,163 @@
+//! Token-bucket rate limiting for RPC calls
+//!
+//! Every caller talking to the same endpoint shares one `RateLimiter`, so
+//! the combined request rate stays within `RpcConfig::requests_per_second`.
+
+use dashmap::DashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use defi_core::{ChainId, RpcConfig};
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Async token bucket. Waiters are served in arrival order.
+#[derive(Debug)]
+pub struct RateLimiter {
+    requests_per_second: u32,
+    burst: u32,
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    /// Allow `requests_per_second` with a burst of one second's worth.
+    /// A rate of 0 disables limiting.
+    pub fn new(requests_per_second: u32) -> Self {
+        Self::with_burst(requests_per_second, requests_per_second)
+    }
+
+    pub fn with_burst(requests_per_second: u32, burst: u32) -> Self {
+        let burst = burst.max(1);
+        Self {
+            requests_per_second,
+            burst,
+            bucket: Mutex::new(Bucket {
+                tokens: burst as f64,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn from_config(config: &RpcConfig) -> Self {
+        Self::new(config.requests_per_second)
+    }
+
+    pub fn requests_per_second(&self) -> u32 {
+        self.requests_per_second
+    }
+
+    /// Wait until a request may be sent
+    pub async fn acquire(&self) {
+        if self.requests_per_second == 0 {
+            return;
+        }
+
+        // Holding the lock while sleeping keeps waiters in FIFO order
+        let mut bucket = self.bucket.lock().await;
+        let rate = self.requests_per_second as f64;
+
+        loop {
+            let now = Instant::now();
+            let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * rate).min(self.burst as f64);
+            bucket.refilled_at = now;
+
+            if bucket.tokens >= 1.0 {
+                bucket.tokens -= 1.0;
+                return;
+            }
+
+            let wait = (1.0 - bucket.tokens) / rate;
+            tokio::time::sleep(Duration::from_secs_f64(wait)).await;
+        }
+    }
+}
+
+/// Shared limiters keyed by chain and endpoint
+#[derive(Debug, Default)]
+pub struct RateLimiters {
+    limiters: DashMap<(ChainId, String), Arc<RateLimiter>>,
+}
+
+impl RateLimiters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limiter for an endpoint, created from its config on first use
+    pub fn get(&self, config: &RpcConfig) -> Arc<RateLimiter> {
+        let key = (config.chain, config.http_url.clone());
+        Arc::clone(
+            &self.limiters
+                .entry(key)
+                .or_insert_with(|| Arc::new(RateLimiter::from_config(config))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_burst_is_throttled_to_rate() {
+        let limiter = Arc::new(RateLimiter::with_burst(100, 1));
+        let start = Instant::now();
+
+        let handles: Vec<_> = (0..26)
+            .map(|_| {
+                let limiter = Arc::clone(&limiter);
+                tokio::spawn(async move { limiter.acquire().await })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        // First request is free, the other 25 arrive 10ms apart
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(240), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(600), "{:?}", elapsed);
+    }
+
+    #[tokio::test]
+    async fn test_burst_capacity_and_unlimited() {
+        let start = Instant::now();
+
+        let limiter = RateLimiter::new(10);
+        for _ in 0..10 {
+            limiter.acquire().await;
+        }
+
+        let unlimited = RateLimiter::new(0);
+        for _ in 0..1_000 {
+            unlimited.acquire().await;
+        }
+
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_limiters_shared_per_endpoint() {
+        let config = |chain: ChainId, url: &str| RpcConfig {
+            http_url: url.to_string(),
+            ws_url: None,
+            chain,
+            requests_per_second: 25,
+        };
+        let limiters = RateLimiters::new();
+
+        let a = limiters.get(&config(ChainId::Ethereum, "https://eth.example"));
+        let b = limiters.get(&config(ChainId::Ethereum, "https://eth.example"));
+        let c = limiters.get(&config(ChainId::Arbitrum, "https://arb.example"));
+
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &c));
+        assert_eq!(c.requests_per_second(), 25);
+    }
+}
@@ -2,13 +2,21 @@
 //!
 //! Uses DashMap for concurrent reads/writes with minimal contention
 
-use alloy_primitives::Address;
+use alloy_primitives::{Address, U256};
+use dashmap::mapref::entry::Entry;
 use dashmap::DashMap;
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
 
-use defi_core::{ChainId, DexProtocol, Pool, Price, UniswapV2Pool, UniswapV3Pool};
+use defi_core::serde_helpers::duration_ms;
+use defi_core::{
+    get_token, is_stablecoin_address, stablecoin_addresses, wrapped_native,
+    ChainId, DexFeeTable, DexProtocol, Pool, Price, TickInfo, UniswapV2Pool, UniswapV3Pool,
+};
 
 /// Key for price lookups
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -38,6 +46,68 @@ pub struct PoolKey {
     pub address: Address,
 }
 
+/// Normalized token pair on a chain, for pool lookups by pair
+type PairKey = (ChainId, Address, Address);
+
+fn pair_key(chain: ChainId, token0: Address, token1: Address) -> PairKey {
+    if token0 < token1 {
+        (chain, token0, token1)
+    } else {
+        (chain, token1, token0)
+    }
+}
+
+/// Every pair a pool trades; multi-token Curve pools trade each combination
+fn pool_pairs(pool: &Pool) -> Vec<PairKey> {
+    let tokens: Vec<Address> = pool.reserves().into_iter().map(|(token, _)| token).collect();
+    let mut pairs = Vec::new();
+    for (i, a) in tokens.iter().enumerate() {
+        for b in &tokens[i + 1..] {
+            pairs.push(pair_key(pool.chain(), *a, *b));
+        }
+    }
+    pairs
+}
+
+/// Circuit breaker for pools whose price breaks away from the rest of the
+/// market, as a bad decode or a manipulated pool would.
+///
+/// Each DEX's price for a pair is the median over its pools, and the
+/// market price is the median across DEXes. A pool further than
+/// `max_deviation_bps` from the market price is quarantined: it stays
+/// tracked but is left out of scanning and pricing until it comes back
+/// within the threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PriceDeviationConfig {
+    pub max_deviation_bps: u32,
+    /// DEXes that must price a pair before any of its pools is judged;
+    /// with two there's no telling which one is off
+    pub min_dexes: usize,
+}
+
+impl Default for PriceDeviationConfig {
+    fn default() -> Self {
+        Self {
+            max_deviation_bps: 2_000,
+            min_dexes: 3,
+        }
+    }
+}
+
+/// Middle value, averaging the two middle ones for an even count
+fn median(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        Some((values[mid - 1] + values[mid]) / 2.0)
+    } else {
+        Some(values[mid])
+    }
+}
+
 /// Timestamped price entry
 #[derive(Debug, Clone)]
 pub struct PriceEntry {
@@ -75,6 +145,34 @@ pub struct PriceState {
     /// Latest block number per chain
     block_numbers: DashMap<ChainId, u64>,
 
+    /// Last time any price, pool or block arrived per chain
+    chain_updates: DashMap<ChainId, Instant>,
+
+    /// Last time a price or pool arrived per chain and DEX, the heartbeat
+    /// of the feed writing them
+    feed_updates: DashMap<(ChainId, DexProtocol), Instant>,
+
+    /// Pools updated since the last `get_dirty_pools` call per chain
+    dirty_pools: DashMap<ChainId, HashSet<Address>>,
+
+    /// Pool addresses by normalized token pair
+    pair_pools: DashMap<PairKey, HashSet<Address>>,
+
+    /// Pool addresses by chain and DEX
+    dex_pools: DashMap<(ChainId, DexProtocol), HashSet<Address>>,
+
+    /// Authoritative wrapped native / stablecoin pool per chain
+    native_usd_pools: DashMap<ChainId, Address>,
+
+    /// Fees applied to incoming pools in place of the one they report
+    dex_fees: RwLock<DexFeeTable>,
+
+    /// Deviation circuit breaker, off when None
+    price_deviation: RwLock<Option<PriceDeviationConfig>>,
+
+    /// Pools held out by the breaker, with their deviation in bps
+    quarantined: DashMap<PoolKey, u32>,
+
     /// Stats
     update_count: std::sync::atomic::AtomicU64,
     last_update: RwLock<Instant>,
@@ -86,13 +184,41 @@ impl PriceState {
             prices: DashMap::new(),
             pools: DashMap::new(),
             block_numbers: DashMap::new(),
+            chain_updates: DashMap::new(),
+            feed_updates: DashMap::new(),
+            dirty_pools: DashMap::new(),
+            pair_pools: DashMap::new(),
+            dex_pools: DashMap::new(),
+            native_usd_pools: DashMap::new(),
+            dex_fees: RwLock::new(DexFeeTable::new()),
+            price_deviation: RwLock::new(None),
+            quarantined: DashMap::new(),
             update_count: std::sync::atomic::AtomicU64::new(0),
             last_update: RwLock::new(Instant::now()),
         }
     }
 
+    /// Pre-size the price and pool maps for `capacity` entries each, split
+    /// across `shards` locks (rounded up to a power of two, at least 2).
+    ///
+    /// A tracked pool costs roughly 300 bytes of map storage (key, entry
+    /// and hash overhead; Curve pools add their token and balance vectors),
+    /// so 100k pools is on the order of 30 MB. Prices are about half that.
+    pub fn new_with_capacity(shards: usize, capacity: usize) -> Self {
+        let shards = shards.max(2).next_power_of_two();
+        Self {
+            prices: DashMap::with_capacity_and_shard_amount(capacity, shards),
+            pools: DashMap::with_capacity_and_shard_amount(capacity, shards),
+            ..Self::new()
+        }
+    }
+
     /// Update a price
-    pub fn update_price(&self, price: Price) {
+    ///
+    /// A price from an earlier block than the stored one is dropped, so a
+    /// message delivered out of order can't overwrite newer data. It still
+    /// counts as feed activity. Returns whether the price was stored.
+    pub fn update_price(&self, price: Price) -> bool {
         let key = PriceKey::new(
             price.chain,
             price.token,
@@ -100,15 +226,29 @@ impl PriceState {
             price.dex,
         );
 
+        self.chain_updates.insert(key.chain, Instant::now());
+        self.feed_updates.insert((key.chain, key.dex), Instant::now());
+        self.update_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        *self.last_update.write() = Instant::now();
+
         let entry = PriceEntry {
             block_number: price.block_number,
             price,
             updated_at: Instant::now(),
         };
 
-        self.prices.insert(key, entry);
-        self.update_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-        *self.last_update.write() = Instant::now();
+        let slot = self.prices.entry(key);
+        if let Entry::Occupied(ref stored) = slot {
+            if stored.get().block_number > entry.block_number {
+                debug!(
+                    "Dropping block {} price for {:?}, have block {}",
+                    entry.block_number, stored.key(), stored.get().block_number
+                );
+                return false;
+            }
+        }
+        slot.insert(entry);
+        true
     }
 
     /// Get a price
@@ -116,6 +256,30 @@ impl PriceState {
         self.prices.get(key).map(|r| r.value().clone())
     }
 
+    /// Price of `base` in `quote` as the DEX's feed last reported it, if no
+    /// older than `max_age`. A direct lookup, unlike the pair-wide queries.
+    pub fn get_dex_price(
+        &self,
+        chain: ChainId,
+        base: Address,
+        quote: Address,
+        dex: DexProtocol,
+        max_age: Duration,
+    ) -> Option<f64> {
+        let entry = self.prices.get(&PriceKey::new(chain, base, quote, dex))?;
+        let value = entry.price.value;
+        if entry.is_stale(max_age) || value <= 0.0 || !value.is_finite() {
+            return None;
+        }
+
+        // Stored as reported, which may quote the pair the other way round
+        if entry.price.token == base {
+            Some(value)
+        } else {
+            Some(1.0 / value)
+        }
+    }
+
     /// Get best price across all DEXes for a pair
     pub fn get_best_price(
         &self,
@@ -124,7 +288,40 @@ impl PriceState {
         token1: Address,
         max_age: Duration,
     ) -> Option<PriceEntry> {
-        let now = Instant::now();
+        self.pair_prices(chain, token0, token1, max_age)
+            .into_iter()
+            .max_by(|a, b| {
+                a.price.value.partial_cmp(&b.price.value)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+    }
+
+    /// Most recent price for a pair among those within `tolerance_bps` of
+    /// the best one.
+    ///
+    /// `get_best_price` takes the highest value however old it is, so a
+    /// slightly stale outlier beats a fresh price just below it. Here every
+    /// price in the band counts as equally good and the freshest wins.
+    pub fn get_freshest_price(
+        &self,
+        chain: ChainId,
+        token0: Address,
+        token1: Address,
+        max_age: Duration,
+        tolerance_bps: u32,
+    ) -> Option<PriceEntry> {
+        let prices = self.pair_prices(chain, token0, token1, max_age);
+        let best = prices.iter().map(|e| e.price.value).fold(f64::NEG_INFINITY, f64::max);
+        let floor = best * (1.0 - tolerance_bps as f64 / 10_000.0);
+
+        prices
+            .into_iter()
+            .filter(|e| e.price.value >= floor)
+            .max_by_key(|e| e.updated_at)
+    }
+
+    /// Prices for a pair on every DEX, no older than `max_age`
+    fn pair_prices(&self, chain: ChainId, token0: Address, token1: Address, max_age: Duration) -> Vec<PriceEntry> {
         let (t0, t1) = if token0 < token1 {
             (token0, token1)
         } else {
@@ -140,26 +337,157 @@ impl PriceState {
                     && key.token1 == t1
                     && !entry.value().is_stale(max_age)
             })
-            .max_by(|a, b| {
-                a.value().price.value.partial_cmp(&b.value().price.value)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            })
             .map(|r| r.value().clone())
+            .collect()
     }
 
-    /// Update a pool
-    pub fn update_pool(&self, pool: Pool) {
-        let key = PoolKey {
-            chain: pool.chain(),
-            address: pool.address(),
-        };
+    /// Update a pool, correcting its fee from the DEX fee table
+    ///
+    /// Like prices, a pool from an earlier block than the stored one is
+    /// dropped rather than overwriting newer reserves. Returns whether the
+    /// pool was stored.
+    pub fn update_pool(&self, mut pool: Pool) -> bool {
+        self.dex_fees.read().apply(&mut pool);
+
+        let (chain, address) = (pool.chain(), pool.address());
+        self.chain_updates.insert(chain, Instant::now());
+        self.feed_updates.insert((chain, pool.dex()), Instant::now());
 
         let entry = PoolEntry {
             pool,
             updated_at: Instant::now(),
         };
 
-        self.pools.insert(key, entry);
+        let slot = self.pools.entry(PoolKey { chain, address });
+        if let Entry::Occupied(ref stored) = slot {
+            let stored_block = stored.get().pool.block_number();
+            if stored_block > entry.pool.block_number() {
+                debug!(
+                    "Dropping block {} reserves for pool {} on {}, have block {}",
+                    entry.pool.block_number(), address, chain, stored_block
+                );
+                return false;
+            }
+        }
+
+        self.dirty_pools.entry(chain).or_default().insert(address);
+        self.index_pool(&entry.pool);
+        let pairs = pool_pairs(&entry.pool);
+        slot.insert(entry);
+
+        for pair in pairs {
+            self.check_price_deviation(pair);
+        }
+        true
+    }
+
+    /// Replace the initialized ticks of a tracked V3 pool
+    ///
+    /// Tick refreshes come from RPC rather than the feed, so unlike
+    /// `update_pool` this leaves the feed heartbeat alone. Returns whether
+    /// a V3 pool was found at `address`.
+    pub fn set_v3_ticks(&self, chain: ChainId, address: Address, ticks: Vec<TickInfo>) -> bool {
+        let Some(mut entry) = self.pools.get_mut(&PoolKey { chain, address }) else {
+            return false;
+        };
+        let Pool::UniswapV3(pool) = &mut entry.pool else {
+            return false;
+        };
+        pool.ticks = ticks;
+        drop(entry);
+
+        self.dirty_pools.entry(chain).or_default().insert(address);
+        true
+    }
+
+    /// Re-judge every pool of a pair against the cross-DEX median price,
+    /// quarantining the ones that broke away and releasing the ones that
+    /// reconverged. Pairs priced by too few DEXes are left as they are.
+    fn check_price_deviation(&self, (chain, token0, token1): PairKey) {
+        let Some(config) = *self.price_deviation.read() else {
+            return;
+        };
+        let Some(addresses) = self.pair_pools.get(&(chain, token0, token1)).map(|r| r.value().clone()) else {
+            return;
+        };
+
+        let priced: Vec<(Address, DexProtocol, f64)> = addresses
+            .iter()
+            .filter_map(|address| {
+                let entry = self.pools.get(&PoolKey { chain, address: *address })?;
+                let price = entry.pool.human_price(token0, token1)?;
+                Some((*address, entry.pool.dex(), price))
+            })
+            .collect();
+
+        let mut by_dex: HashMap<DexProtocol, Vec<f64>> = HashMap::new();
+        for (_, dex, price) in &priced {
+            by_dex.entry(*dex).or_default().push(*price);
+        }
+        if by_dex.len() < config.min_dexes.max(1) {
+            return;
+        }
+        let mut dex_prices: Vec<f64> = by_dex.into_values().filter_map(|mut prices| median(&mut prices)).collect();
+        let Some(market) = median(&mut dex_prices).filter(|m| *m > 0.0) else {
+            return;
+        };
+
+        for (address, dex, price) in priced {
+            let key = PoolKey { chain, address };
+            let deviation_bps = ((price - market).abs() / market * 10_000.0) as u32;
+
+            if deviation_bps > config.max_deviation_bps {
+                if self.quarantined.insert(key, deviation_bps).is_none() {
+                    warn!(
+                        "Quarantining {:?} pool {} on {}: price {} is {}bps from the cross-DEX median {}",
+                        dex, address, chain, price, deviation_bps, market
+                    );
+                }
+            } else if self.quarantined.remove(&key).is_some() {
+                info!(
+                    "Releasing {:?} pool {} on {}: price {} is back within {}bps of the median",
+                    dex, address, chain, price, config.max_deviation_bps
+                );
+                // Rescan it now it's trusted again
+                self.dirty_pools.entry(chain).or_default().insert(address);
+            }
+        }
+    }
+
+    fn index_pool(&self, pool: &Pool) {
+        for pair in pool_pairs(pool) {
+            self.pair_pools.entry(pair).or_default().insert(pool.address());
+        }
+        self.dex_pools.entry((pool.chain(), pool.dex())).or_default().insert(pool.address());
+    }
+
+    fn unindex_pool(&self, pool: &Pool) {
+        for pair in pool_pairs(pool) {
+            if let Some(mut pools) = self.pair_pools.get_mut(&pair) {
+                pools.remove(&pool.address());
+            }
+            self.pair_pools.remove_if(&pair, |_, pools| pools.is_empty());
+        }
+        let dex = (pool.chain(), pool.dex());
+        if let Some(mut pools) = self.dex_pools.get_mut(&dex) {
+            pools.remove(&pool.address());
+        }
+        self.dex_pools.remove_if(&dex, |_, pools| pools.is_empty());
+    }
+
+    /// Fresh pools among `addresses`
+    fn fresh_pools<'a>(
+        &self,
+        chain: ChainId,
+        addresses: impl IntoIterator<Item = &'a Address>,
+        max_age: Duration,
+    ) -> Vec<PoolEntry> {
+        addresses
+            .into_iter()
+            .filter_map(|address| self.pools.get(&PoolKey { chain, address: *address }))
+            .filter(|e| e.value().updated_at.elapsed() < max_age && !self.quarantined.contains_key(e.key()))
+            .map(|e| e.value().clone())
+            .collect()
     }
 
     /// Get a pool
@@ -168,9 +496,171 @@ impl PriceState {
         self.pools.get(&key).map(|r| r.value().clone())
     }
 
+    /// Human-unit price of `base` quoted in `quote` from the freshest pool holding both
+    pub fn get_pool_pair_price(&self, chain: ChainId, base: Address, quote: Address) -> Option<f64> {
+        self.pool_pair_price_with_age(chain, base, quote).map(|(price, _)| price)
+    }
+
+    /// Pair price from the freshest pool, with that pool's age. Only the
+    /// pair's own pools are visited, so pricing every pool of a chain stays
+    /// linear in its size.
+    fn pool_pair_price_with_age(&self, chain: ChainId, base: Address, quote: Address) -> Option<(f64, Duration)> {
+        let addresses = self.pair_pools.get(&pair_key(chain, base, quote))?.value().clone();
+        addresses
+            .iter()
+            .filter_map(|address| {
+                let key = PoolKey { chain, address: *address };
+                if self.quarantined.contains_key(&key) {
+                    return None;
+                }
+                let entry = self.pools.get(&key)?;
+                let price = entry.pool.human_price(base, quote)?;
+                Some((entry.updated_at, price))
+            })
+            .max_by_key(|(updated_at, _)| *updated_at)
+            .map(|(updated_at, price)| (price, updated_at.elapsed()))
+    }
+
+    /// USD price of a token, anchored on a stablecoin pool.
+    ///
+    /// Uses a direct token/stable pool when one exists, otherwise routes
+    /// token -> WETH -> stable.
+    pub fn get_usd_price(&self, chain: ChainId, token: Address) -> Option<f64> {
+        self.get_usd_price_with_age(chain, token).map(|(price, _)| price)
+    }
+
+    /// `get_usd_price` along with the age of the oldest pool the price was
+    /// derived from. Stablecoins are pegged and always have age zero.
+    pub fn get_usd_price_with_age(&self, chain: ChainId, token: Address) -> Option<(f64, Duration)> {
+        if is_stablecoin_address(chain, token) {
+            return Some((1.0, Duration::ZERO));
+        }
+
+        let stables = stablecoin_addresses(chain);
+
+        if let Some(priced) = stables
+            .iter()
+            .find_map(|s| self.pool_pair_price_with_age(chain, token, *s))
+        {
+            return Some(priced);
+        }
+
+        let weth = get_token(chain, "WETH")?.address;
+        if token == weth {
+            return None;
+        }
+
+        let (in_weth, weth_age) = self.pool_pair_price_with_age(chain, token, weth)?;
+        let (weth_usd, usd_age) = stables
+            .iter()
+            .find_map(|s| self.pool_pair_price_with_age(chain, weth, *s))?;
+
+        Some((in_weth * weth_usd, weth_age.max(usd_age)))
+    }
+
+    /// Human-unit price of `token` in `quote`.
+    ///
+    /// Uses a pool holding both when one exists, otherwise the ratio of
+    /// their USD prices.
+    pub fn get_price_in(&self, chain: ChainId, token: Address, quote: Address) -> Option<f64> {
+        if token == quote {
+            return Some(1.0);
+        }
+        if let Some(price) = self.get_pool_pair_price(chain, token, quote) {
+            return Some(price);
+        }
+
+        let quote_usd = self.get_usd_price(chain, quote).filter(|p| *p > 0.0)?;
+        Some(self.get_usd_price(chain, token)? / quote_usd)
+    }
+
+    /// Price the chain's native gas token from `pool`, which must pair its
+    /// wrapped form with a stablecoin
+    pub fn set_native_usd_pool(&self, chain: ChainId, pool: Address) {
+        self.native_usd_pools.insert(chain, pool);
+    }
+
+    /// Override the fee of every pool updated from now on whose chain and
+    /// DEX are in `table`. Pools already tracked keep their fee until
+    /// their next update.
+    pub fn set_dex_fees(&self, table: DexFeeTable) {
+        *self.dex_fees.write() = table;
+    }
+
+    pub fn dex_fees(&self) -> DexFeeTable {
+        self.dex_fees.read().clone()
+    }
+
+    /// Turn the price deviation breaker on, or off with None. Pairs are
+    /// judged on their next pool update; turning it off releases every
+    /// quarantined pool.
+    pub fn set_price_deviation(&self, config: Option<PriceDeviationConfig>) {
+        *self.price_deviation.write() = config;
+        if config.is_none() {
+            for key in self.quarantined.iter().map(|r| r.key().clone()).collect::<Vec<_>>() {
+                self.quarantined.remove(&key);
+                self.dirty_pools.entry(key.chain).or_default().insert(key.address);
+            }
+        }
+    }
+
+    /// Whether the deviation breaker is holding a pool out of scanning
+    pub fn is_quarantined(&self, chain: ChainId, address: Address) -> bool {
+        self.quarantined.contains_key(&PoolKey { chain, address })
+    }
+
+    /// Quarantined pools on a chain with how far off they were, in bps
+    pub fn quarantined_pools(&self, chain: ChainId) -> Vec<(Address, u32)> {
+        let mut pools: Vec<(Address, u32)> = self.quarantined
+            .iter()
+            .filter(|r| r.key().chain == chain)
+            .map(|r| (r.key().address, *r.value()))
+            .collect();
+        pools.sort();
+        pools
+    }
+
+    /// Authoritative native/USD pool for a chain, if one is configured
+    pub fn native_usd_pool(&self, chain: ChainId) -> Option<Address> {
+        self.native_usd_pools.get(&chain).map(|r| *r.value())
+    }
+
+    /// USD price of the chain's native gas token (ETH, or MATIC on Polygon).
+    ///
+    /// With an authoritative pool configured only that pool is used, and
+    /// the price is None until it is tracked. Otherwise the wrapped token
+    /// is priced like any other through `get_usd_price`.
+    pub fn native_usd_price(&self, chain: ChainId) -> Option<f64> {
+        let native = wrapped_native(chain)?.address;
+
+        let Some(address) = self.native_usd_pool(chain) else {
+            return self.get_usd_price(chain, native);
+        };
+
+        let pool = self.get_pool(chain, address)?.pool;
+        let (token0, token1) = pool.tokens()?;
+        let stable = match (token0 == native, token1 == native) {
+            (true, false) => token1,
+            (false, true) => token0,
+            _ => return None,
+        };
+        if !is_stablecoin_address(chain, stable) {
+            return None;
+        }
+
+        pool.human_price(native, stable)
+    }
+
+    /// USD cost of `gas_cost_wei` of the chain's native token
+    pub fn gas_cost_usd(&self, chain: ChainId, gas_cost_wei: U256) -> Option<f64> {
+        let wei: f64 = gas_cost_wei.to_string().parse().ok()?;
+        Some(wei / 1e18 * self.native_usd_price(chain)?)
+    }
+
     /// Update block number
     pub fn update_block(&self, chain: ChainId, block: u64) {
         self.block_numbers.insert(chain, block);
+        self.chain_updates.insert(chain, Instant::now());
     }
 
     /// Get latest block
@@ -187,19 +677,204 @@ impl PriceState {
             .collect()
     }
 
-    /// Get all pools for a chain
+    /// Get all pools for a chain, leaving out quarantined ones
     pub fn get_chain_pools(&self, chain: ChainId, max_age: Duration) -> Vec<PoolEntry> {
         self.pools
             .iter()
-            .filter(|e| e.key().chain == chain && e.value().updated_at.elapsed() < max_age)
+            .filter(|e| {
+                e.key().chain == chain
+                    && e.value().updated_at.elapsed() < max_age
+                    && !self.quarantined.contains_key(e.key())
+            })
             .map(|e| e.value().clone())
             .collect()
     }
 
-    /// Clean up stale entries
-    pub fn cleanup(&self, max_age: Duration) {
-        self.prices.retain(|_, v| !v.is_stale(max_age));
-        self.pools.retain(|_, v| v.updated_at.elapsed() < max_age);
+    /// Pools trading `token0` against `token1` in either order, without
+    /// scanning the rest of the chain
+    pub fn get_pools_for_pair(
+        &self,
+        chain: ChainId,
+        token0: Address,
+        token1: Address,
+        max_age: Duration,
+    ) -> Vec<PoolEntry> {
+        let Some(addresses) = self.pair_pools.get(&pair_key(chain, token0, token1)).map(|r| r.value().clone()) else {
+            return vec![];
+        };
+        self.fresh_pools(chain, &addresses, max_age)
+    }
+
+    /// Pools of one DEX on a chain
+    pub fn get_pools_for_dex(&self, chain: ChainId, dex: DexProtocol, max_age: Duration) -> Vec<PoolEntry> {
+        let Some(addresses) = self.dex_pools.get(&(chain, dex)).map(|r| r.value().clone()) else {
+            return vec![];
+        };
+        self.fresh_pools(chain, &addresses, max_age)
+    }
+
+    /// Addresses of pools updated since the previous call for this chain.
+    ///
+    /// Draining the set here means every update is handed out exactly once,
+    /// even if it lands while the caller is scanning.
+    pub fn get_dirty_pools(&self, chain: ChainId) -> Vec<Address> {
+        let mut dirty: Vec<Address> = self.dirty_pools
+            .remove(&chain)
+            .map(|(_, pools)| pools.into_iter().collect())
+            .unwrap_or_default();
+        dirty.sort();
+        dirty
+    }
+
+    /// Time since the last update of any kind for a chain
+    pub fn chain_last_update_age(&self, chain: ChainId) -> Option<Duration> {
+        self.chain_updates.get(&chain).map(|r| r.value().elapsed())
+    }
+
+    /// Time since the last price or pool update from a DEX on a chain
+    pub fn feed_last_update_age(&self, chain: ChainId, dex: DexProtocol) -> Option<Duration> {
+        self.feed_updates.get(&(chain, dex)).map(|r| r.value().elapsed())
+    }
+
+    /// Chains that have received at least one update
+    pub fn chains(&self) -> Vec<ChainId> {
+        self.chain_updates.iter().map(|r| *r.key()).collect()
+    }
+
+    /// Number of pools tracked for a chain, regardless of age
+    pub fn chain_pool_count(&self, chain: ChainId) -> usize {
+        self.pools.iter().filter(|e| e.key().chain == chain).count()
+    }
+
+    /// Number of pools for a chain updated within `max_age`
+    pub fn fresh_pool_count(&self, chain: ChainId, max_age: Duration) -> usize {
+        self.pools
+            .iter()
+            .filter(|e| e.key().chain == chain && e.value().updated_at.elapsed() < max_age)
+            .count()
+    }
+
+    /// Drop prices older than `max_price_age` and pools not updated within
+    /// `max_pool_age`. Reserves stay valid until the pool's next event, so
+    /// quiet pools usually warrant a longer limit than spot prices.
+    pub fn cleanup(&self, max_price_age: Duration, max_pool_age: Duration) {
+        self.prices.retain(|_, v| !v.is_stale(max_price_age));
+
+        // Un-index while the shard is still locked, so an update racing to
+        // re-insert the pool can't have its index entries removed after it
+        self.pools.retain(|key, v| {
+            let keep = v.updated_at.elapsed() < max_pool_age;
+            if !keep {
+                self.unindex_pool(&v.pool);
+                self.quarantined.remove(key);
+            }
+            keep
+        });
+    }
+
+    /// Release backing storage left over after `cleanup`. `retain` keeps
+    /// the maps at their high-water capacity, so call this periodically.
+    pub fn compact(&self) {
+        self.prices.shrink_to_fit();
+        self.pools.shrink_to_fit();
+        self.dirty_pools.shrink_to_fit();
+        self.pair_pools.shrink_to_fit();
+        self.dex_pools.shrink_to_fit();
+    }
+
+    /// Allocated slots in the price and pool maps
+    pub fn capacity(&self) -> (usize, usize) {
+        (self.prices.capacity(), self.pools.capacity())
+    }
+
+    /// Point-in-time copy of every price, pool and block number.
+    ///
+    /// Entry timestamps are stored as ages relative to the snapshot, so a
+    /// restored state has the same staleness profile as the original.
+    pub fn snapshot(&self) -> PriceSnapshot {
+        let taken_at_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        let mut prices: Vec<PriceSnapshotEntry> = self.prices
+            .iter()
+            .map(|e| PriceSnapshotEntry {
+                price: e.value().price.clone(),
+                block_number: e.value().block_number,
+                age: e.value().age(),
+            })
+            .collect();
+        prices.sort_by_key(|e| (e.price.chain.chain_id(), e.price.token, e.price.quote_token, e.price.dex.name()));
+
+        let mut pools: Vec<PoolSnapshotEntry> = self.pools
+            .iter()
+            .map(|e| PoolSnapshotEntry {
+                pool: e.value().pool.clone(),
+                age: e.value().updated_at.elapsed(),
+            })
+            .collect();
+        pools.sort_by_key(|e| (e.pool.chain().chain_id(), e.pool.address()));
+
+        let mut chains: Vec<ChainSnapshotEntry> = self.chain_updates
+            .iter()
+            .map(|e| ChainSnapshotEntry {
+                chain: *e.key(),
+                block_number: self.get_block(*e.key()),
+                last_update_age: e.value().elapsed(),
+            })
+            .collect();
+        chains.sort_by_key(|e| e.chain.chain_id());
+
+        PriceSnapshot {
+            taken_at_ms,
+            prices,
+            pools,
+            chains,
+        }
+    }
+
+    /// Rebuild a state from a snapshot, preserving entry ages
+    pub fn from_snapshot(snapshot: PriceSnapshot) -> Self {
+        let state = Self::new();
+        let now = Instant::now();
+        let since = |age: Duration| now.checked_sub(age).unwrap_or(now);
+
+        for entry in snapshot.prices {
+            let key = PriceKey::new(
+                entry.price.chain,
+                entry.price.token,
+                entry.price.quote_token,
+                entry.price.dex,
+            );
+            state.prices.insert(key, PriceEntry {
+                price: entry.price,
+                updated_at: since(entry.age),
+                block_number: entry.block_number,
+            });
+        }
+
+        for entry in snapshot.pools {
+            let key = PoolKey {
+                chain: entry.pool.chain(),
+                address: entry.pool.address(),
+            };
+            state.dirty_pools.entry(key.chain).or_default().insert(key.address);
+            state.index_pool(&entry.pool);
+            state.pools.insert(key, PoolEntry {
+                pool: entry.pool,
+                updated_at: since(entry.age),
+            });
+        }
+
+        for entry in snapshot.chains {
+            if let Some(block) = entry.block_number {
+                state.block_numbers.insert(entry.chain, block);
+            }
+            state.chain_updates.insert(entry.chain, since(entry.last_update_age));
+        }
+
+        state
     }
 
     /// Stats
@@ -213,24 +888,109 @@ impl PriceState {
     }
 }
 
+/// USD valuation of pool liquidity from tracked prices
+pub trait PoolLiquidity {
+    /// Total value of the pool's reserves in USD.
+    ///
+    /// Tokens that can't be priced are assumed to match the average value
+    /// of the priced ones, as they would in a balanced pool. None when no
+    /// token in the pool can be priced.
+    fn liquidity_usd(&self, state: &PriceState) -> Option<f64>;
+}
+
+impl PoolLiquidity for Pool {
+    fn liquidity_usd(&self, state: &PriceState) -> Option<f64> {
+        let chain = self.chain();
+        let reserves = self.reserves();
+
+        let priced: Vec<f64> = reserves
+            .iter()
+            .filter_map(|(token, reserve)| {
+                let price = state.get_usd_price(chain, *token)?;
+                let raw: f64 = reserve.to_string().parse().ok()?;
+                Some(raw / 10f64.powi(self.decimals(*token)? as i32) * price)
+            })
+            .collect();
+
+        if priced.is_empty() {
+            return None;
+        }
+
+        let total: f64 = priced.iter().sum();
+        Some(total * reserves.len() as f64 / priced.len() as f64)
+    }
+}
+
 impl Default for PriceState {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Serializable point-in-time copy of a `PriceState`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceSnapshot {
+    /// Wall-clock time the snapshot was taken
+    pub taken_at_ms: u64,
+    pub prices: Vec<PriceSnapshotEntry>,
+    pub pools: Vec<PoolSnapshotEntry>,
+    pub chains: Vec<ChainSnapshotEntry>,
+}
+
+/// Price with its age at snapshot time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceSnapshotEntry {
+    pub price: Price,
+    pub block_number: u64,
+    #[serde(rename = "age_ms", with = "duration_ms")]
+    pub age: Duration,
+}
+
+/// Pool with its age at snapshot time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolSnapshotEntry {
+    pub pool: Pool,
+    #[serde(rename = "age_ms", with = "duration_ms")]
+    pub age: Duration,
+}
+
+/// Per-chain block number and update age at snapshot time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainSnapshotEntry {
+    pub chain: ChainId,
+    pub block_number: Option<u64>,
+    #[serde(rename = "last_update_age_ms", with = "duration_ms")]
+    pub last_update_age: Duration,
+}
+
 /// Statistics about price state
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PriceStateStats {
     pub price_count: usize,
     pub pool_count: usize,
     pub update_count: u64,
+    #[serde(rename = "last_update_age_ms", with = "defi_core::serde_helpers::duration_ms")]
     pub last_update_age: Duration,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use defi_core::get_decimals;
+
+    #[test]
+    fn test_stats_round_trip() {
+        let stats = PriceStateStats {
+            price_count: 4,
+            pool_count: 2,
+            update_count: 100,
+            last_update_age: Duration::from_millis(250),
+        };
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["last_update_age_ms"], 250);
+        assert_eq!(serde_json::from_value::<PriceStateStats>(json).unwrap(), stats);
+    }
 
     #[test]
     fn test_price_key_normalization() {
@@ -275,4 +1035,620 @@ mod tests {
 
         assert_eq!(state.stats().update_count, 400);
     }
+
+    fn v2_pool(chain: ChainId, address: Address, token0: Address, token1: Address, r0: u128, r1: u128) -> Pool {
+        Pool::UniswapV2(UniswapV2Pool {
+            address,
+            token0,
+            token1,
+            reserve0: alloy_primitives::U256::from(r0),
+            reserve1: alloy_primitives::U256::from(r1),
+            decimals0: get_decimals(chain, token0),
+            decimals1: get_decimals(chain, token1),
+            fee_bps: 30,
+            chain,
+            dex: DexProtocol::UniswapV2,
+            block_number: 1,
+        })
+    }
+
+    #[test]
+    fn test_dex_fee_table_corrects_pool_fees() {
+        use crate::test_support::seed_v2_pool;
+        use alloy_primitives::U256;
+
+        let chain = ChainId::Ethereum;
+        let weth = get_token(chain, "WETH").unwrap().address;
+        let usdc = get_token(chain, "USDC").unwrap().address;
+        let (e18, e6) = (10u128.pow(18), 10u128.pow(6));
+
+        let state = PriceState::new();
+        state.set_dex_fees(DexFeeTable::new().with_fee(chain, DexProtocol::SushiSwap, 25));
+
+        // Both arrive claiming the 0.3% default
+        let reserves = (1_000 * e18, 2_000_000 * e6);
+        let uni = seed_v2_pool(&state, chain, DexProtocol::UniswapV2, weth, usdc, reserves.0, reserves.1);
+        let sushi = seed_v2_pool(&state, chain, DexProtocol::SushiSwap, weth, usdc, reserves.0, reserves.1);
+        let pool = |address: Address| state.get_pool(chain, address).unwrap().pool;
+        assert_eq!(pool(uni).fee_bps(), 30);
+        assert_eq!(pool(sushi).fee_bps(), 25);
+
+        // out = in * (10000 - fee) * R1 / (R0 * 10000 + in * (10000 - fee))
+        let amount_in = U256::from(e18);
+        let expected = |fee_bps: u64| {
+            let in_with_fee = amount_in * U256::from(10_000 - fee_bps);
+            in_with_fee * U256::from(reserves.1) / (U256::from(reserves.0) * U256::from(10_000u64) + in_with_fee)
+        };
+        let uni_out = pool(uni).get_amount_out(amount_in, weth);
+        let sushi_out = pool(sushi).get_amount_out(amount_in, weth);
+        assert_eq!(uni_out, expected(30));
+        assert_eq!(sushi_out, expected(25));
+        assert!(sushi_out > uni_out);
+
+        // The override is per chain
+        let base_sushi = seed_v2_pool(&state, ChainId::Base, DexProtocol::SushiSwap, weth, usdc, e18, e18);
+        assert_eq!(state.get_pool(ChainId::Base, base_sushi).unwrap().pool.fee_bps(), 30);
+    }
+
+    #[test]
+    fn test_usd_price_via_weth() {
+        let chain = ChainId::Arbitrum;
+        let arb = get_token(chain, "ARB").unwrap().address;
+        let weth = get_token(chain, "WETH").unwrap().address;
+        let usdc = get_token(chain, "USDC").unwrap().address;
+        let e18 = 10u128.pow(18);
+
+        let state = PriceState::new();
+        // 2M ARB : 1000 WETH  => 1 ARB = 0.0005 WETH
+        state.update_pool(v2_pool(chain, Address::repeat_byte(0x10), arb, weth, 2_000_000 * e18, 1_000 * e18));
+        // 1000 WETH : 2M USDC => 1 WETH = 2000 USDC
+        state.update_pool(v2_pool(chain, Address::repeat_byte(0x11), weth, usdc, 1_000 * e18, 2_000_000 * 10u128.pow(6)));
+
+        let weth_usd = state.get_usd_price(chain, weth).unwrap();
+        assert!((weth_usd - 2000.0).abs() < 1e-6);
+
+        let arb_usd = state.get_usd_price(chain, arb).unwrap();
+        assert!((arb_usd - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_usd_price_direct_and_stable() {
+        let chain = ChainId::Ethereum;
+        let weth = get_token(chain, "WETH").unwrap().address;
+        let usdc = get_token(chain, "USDC").unwrap().address;
+        let e18 = 10u128.pow(18);
+
+        let state = PriceState::new();
+        assert!(state.get_usd_price(chain, weth).is_none());
+        assert_eq!(state.get_usd_price(chain, usdc), Some(1.0));
+
+        // USDC as token0: 3M USDC : 1000 WETH => 1 WETH = 3000 USDC
+        state.update_pool(v2_pool(chain, Address::repeat_byte(0x20), usdc, weth, 3_000_000 * 10u128.pow(6), 1_000 * e18));
+        let weth_usd = state.get_usd_price(chain, weth).unwrap();
+        assert!((weth_usd - 3000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_usd_price_via_registered_stablecoin() {
+        let chain = ChainId::Base;
+        let weth = get_token(chain, "WETH").unwrap().address;
+        let usdbc: Address = "0xd9aAEc86B65D86f6A7B5B1b0c42FFA531710b6CA".parse().unwrap();
+        let e18 = 10u128.pow(18);
+
+        let state = PriceState::new();
+        // 1000 WETH : 2.5M USDbC => 1 WETH = 2500 USD, but only once USDbC counts as a stable
+        let pool = || v2_pool(chain, Address::repeat_byte(0x30), weth, usdbc, 1_000 * e18, 2_500_000 * 10u128.pow(6));
+        state.update_pool(pool());
+        assert!(state.get_usd_price(chain, weth).is_none());
+
+        // The next pool update picks up the registered decimals
+        defi_core::register_stablecoin(chain, usdbc, 6);
+        state.update_pool(pool());
+        assert_eq!(state.get_usd_price(chain, usdbc), Some(1.0));
+        let weth_usd = state.get_usd_price(chain, weth).unwrap();
+        assert!((weth_usd - 2500.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_authoritative_native_pool_prices_gas() {
+        let chain = ChainId::Arbitrum;
+        let arb = get_token(chain, "ARB").unwrap().address;
+        let weth = get_token(chain, "WETH").unwrap().address;
+        let usdc = get_token(chain, "USDC").unwrap().address;
+        let e18 = 10u128.pow(18);
+        let e6 = 10u128.pow(6);
+        let designated = Address::repeat_byte(0x41);
+        let gas_wei = alloy_primitives::U256::from(10u64.pow(15));
+
+        let state = PriceState::new();
+        // Designated pool: 1 WETH = 2500 USDC. A fresher pool says 2000.
+        state.update_pool(v2_pool(chain, designated, weth, usdc, 100 * e18, 250_000 * e6));
+        state.update_pool(v2_pool(chain, Address::repeat_byte(0x42), weth, usdc, 100 * e18, 200_000 * e6));
+
+        // Without a designated pool the freshest one prices gas
+        let unconfigured = state.gas_cost_usd(chain, gas_wei).unwrap();
+        assert!((unconfigured - 2.0).abs() < 1e-9, "{}", unconfigured);
+
+        state.set_native_usd_pool(chain, designated);
+        assert_eq!(state.native_usd_pool(chain), Some(designated));
+        let native = state.native_usd_price(chain).unwrap();
+        assert!((native - 2500.0).abs() < 1e-6, "{}", native);
+        let configured = state.gas_cost_usd(chain, gas_wei).unwrap();
+        assert!((configured - 2.5).abs() < 1e-9, "{}", configured);
+
+        // A designated pool that isn't tracked, or doesn't pair WETH with a
+        // stablecoin, gives no price rather than falling back
+        state.set_native_usd_pool(chain, Address::repeat_byte(0x43));
+        assert!(state.native_usd_price(chain).is_none());
+        let arb_usdc = Address::repeat_byte(0x44);
+        state.update_pool(v2_pool(chain, arb_usdc, arb, usdc, 1_000 * e18, 1_000 * e6));
+        state.set_native_usd_pool(chain, arb_usdc);
+        assert!(state.gas_cost_usd(chain, gas_wei).is_none());
+    }
+
+    #[test]
+    fn test_price_in_quote_asset() {
+        let chain = ChainId::Arbitrum;
+        let arb = get_token(chain, "ARB").unwrap().address;
+        let weth = get_token(chain, "WETH").unwrap().address;
+        let usdc = get_token(chain, "USDC").unwrap().address;
+        let e18 = 10u128.pow(18);
+
+        let state = PriceState::new();
+        assert_eq!(state.get_price_in(chain, arb, arb), Some(1.0));
+        assert!(state.get_price_in(chain, arb, usdc).is_none());
+
+        // 1 WETH = 2000 USDC, 1 ARB = 1 USDC, no ARB/WETH pool
+        state.update_pool(v2_pool(chain, Address::repeat_byte(0x30), weth, usdc, 1_000 * e18, 2_000_000 * 10u128.pow(6)));
+        state.update_pool(v2_pool(chain, Address::repeat_byte(0x31), arb, usdc, 1_000_000 * e18, 1_000_000 * 10u128.pow(6)));
+
+        assert!((state.get_price_in(chain, weth, usdc).unwrap() - 2000.0).abs() < 1e-6);
+        // Routed through USD
+        assert!((state.get_price_in(chain, arb, weth).unwrap() - 0.0005).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_snapshot_restore_round_trip() {
+        let chain = ChainId::Ethereum;
+        let weth = get_token(chain, "WETH").unwrap().address;
+        let usdc = get_token(chain, "USDC").unwrap().address;
+        let e18 = 10u128.pow(18);
+
+        let state = PriceState::new();
+        state.update_pool(v2_pool(chain, Address::repeat_byte(0x20), weth, usdc, 1_000 * e18, 3_000_000 * 10u128.pow(6)));
+        state.update_pool(v2_pool(ChainId::Base, Address::repeat_byte(0x21), weth, usdc, e18, e18));
+        state.update_price(Price {
+            value: 3000.0,
+            token: weth,
+            quote_token: usdc,
+            dex: DexProtocol::UniswapV2,
+            chain,
+            block_number: 17,
+            timestamp_ms: 0,
+        });
+        state.update_block(chain, 18);
+
+        let json = serde_json::to_string(&state.snapshot()).unwrap();
+        let restored = PriceState::from_snapshot(serde_json::from_str(&json).unwrap());
+
+        assert_eq!(restored.stats().pool_count, 2);
+        assert_eq!(restored.stats().price_count, 1);
+        assert_eq!(restored.get_block(chain), Some(18));
+        assert_eq!(restored.get_block(ChainId::Base), None);
+        assert_eq!(restored.get_usd_price(chain, weth), state.get_usd_price(chain, weth));
+
+        let key = PriceKey::new(chain, weth, usdc, DexProtocol::UniswapV2);
+        let price = restored.get_price(&key).unwrap();
+        assert_eq!(price.block_number, 17);
+        assert!(!price.is_stale(Duration::from_secs(1)));
+
+        let mut chains = restored.chains();
+        chains.sort_by_key(|c| c.chain_id());
+        assert_eq!(chains, vec![ChainId::Ethereum, ChainId::Base]);
+    }
+
+    #[test]
+    fn test_freshest_price_beats_stale_outlier() {
+        let chain = ChainId::Arbitrum;
+        let (token, quote) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let price = |value: f64, dex: DexProtocol| Price {
+            value,
+            token,
+            quote_token: quote,
+            dex,
+            chain,
+            block_number: 1,
+            timestamp_ms: 0,
+        };
+        let entry = |value: f64, dex: DexProtocol, age_secs: u64| PriceSnapshotEntry {
+            price: price(value, dex),
+            block_number: 1,
+            age: Duration::from_secs(age_secs),
+        };
+
+        // 20s old at 2010 against 1s old at 2000, and a fresher low outlier
+        let state = PriceState::from_snapshot(PriceSnapshot {
+            taken_at_ms: 0,
+            prices: vec![
+                entry(2010.0, DexProtocol::UniswapV2, 20),
+                entry(2000.0, DexProtocol::UniswapV3, 1),
+                entry(1900.0, DexProtocol::SushiSwap, 0),
+            ],
+            pools: vec![],
+            chains: vec![],
+        });
+        let max_age = Duration::from_secs(30);
+
+        let best = state.get_best_price(chain, token, quote, max_age).unwrap();
+        assert_eq!(best.price.dex, DexProtocol::UniswapV2);
+
+        // Within 1% of the best, the fresh price wins; the 1900 is outside the band
+        let freshest = state.get_freshest_price(chain, token, quote, max_age, 100).unwrap();
+        assert_eq!(freshest.price.dex, DexProtocol::UniswapV3);
+        assert_eq!(freshest.price.value, 2000.0);
+
+        // A zero band is the plain maximum
+        let strict = state.get_freshest_price(chain, token, quote, max_age, 0).unwrap();
+        assert_eq!(strict.price.dex, DexProtocol::UniswapV2);
+
+        assert!(state.get_freshest_price(chain, token, Address::repeat_byte(3), max_age, 100).is_none());
+    }
+
+    #[test]
+    fn test_snapshot_preserves_age() {
+        let state = PriceState::from_snapshot(PriceSnapshot {
+            taken_at_ms: 0,
+            prices: vec![],
+            pools: vec![PoolSnapshotEntry {
+                pool: v2_pool(ChainId::Base, Address::repeat_byte(0x30), Address::repeat_byte(1), Address::repeat_byte(2), 1, 1),
+                age: Duration::from_secs(60),
+            }],
+            chains: vec![],
+        });
+
+        assert_eq!(state.chain_pool_count(ChainId::Base), 1);
+        assert_eq!(state.fresh_pool_count(ChainId::Base, Duration::from_secs(30)), 0);
+    }
+
+    #[test]
+    fn test_pool_liquidity_usd() {
+        let chain = ChainId::Ethereum;
+        let weth = get_token(chain, "WETH").unwrap().address;
+        let usdc = get_token(chain, "USDC").unwrap().address;
+        let e18 = 10u128.pow(18);
+
+        let state = PriceState::new();
+        // 250 WETH : 500k USDC => $1M
+        let pool = v2_pool(chain, Address::repeat_byte(0x40), weth, usdc, 250 * e18, 500_000 * 10u128.pow(6));
+        state.update_pool(pool.clone());
+
+        let liquidity = pool.liquidity_usd(&state).unwrap();
+        assert!((liquidity - 1_000_000.0).abs() < 1e-3, "{}", liquidity);
+
+        // Unknown tokens can't be valued
+        let unknown = v2_pool(chain, Address::repeat_byte(0x41), Address::repeat_byte(1), Address::repeat_byte(2), e18, e18);
+        assert!(unknown.liquidity_usd(&state).is_none());
+
+        // Only the USDC side is priced; the other side is assumed equal
+        let half = v2_pool(chain, Address::repeat_byte(0x42), Address::repeat_byte(1), usdc, e18, 250 * 10u128.pow(6));
+        assert!((half.liquidity_usd(&state).unwrap() - 500.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_compact_reclaims_capacity_after_cleanup() {
+        let state = PriceState::new_with_capacity(4, 1_000);
+        let (_, presized) = state.capacity();
+        assert!(presized >= 1_000);
+
+        let e18 = 10u128.pow(18);
+        for i in 0..5_000u32 {
+            let mut bytes = [0u8; 20];
+            bytes[16..].copy_from_slice(&i.to_be_bytes());
+            state.update_pool(v2_pool(
+                ChainId::Ethereum,
+                Address::from(bytes),
+                Address::repeat_byte(1),
+                Address::repeat_byte(2),
+                e18,
+                e18,
+            ));
+        }
+        let (_, grown) = state.capacity();
+        assert!(grown >= 5_000);
+
+        state.cleanup(Duration::ZERO, Duration::ZERO);
+        assert_eq!(state.chain_pool_count(ChainId::Ethereum), 0);
+
+        // Reported capacity after retain depends on the table's bucket
+        // bookkeeping, so only the compacted size is checked
+        state.compact();
+        assert!(state.capacity().1 < grown, "{} -> {}", grown, state.capacity().1);
+    }
+
+    #[test]
+    fn test_pools_by_pair_and_dex() {
+        let chain = ChainId::Ethereum;
+        let (weth, usdc, dai, usdt) = (
+            Address::repeat_byte(1),
+            Address::repeat_byte(2),
+            Address::repeat_byte(3),
+            Address::repeat_byte(4),
+        );
+        let e18 = 10u128.pow(18);
+        let addresses = |pools: Vec<PoolEntry>| {
+            let mut addresses: Vec<Address> = pools.iter().map(|e| e.pool.address()).collect();
+            addresses.sort();
+            addresses
+        };
+
+        let state = PriceState::new();
+        state.update_pool(v2_pool(chain, Address::repeat_byte(0x10), weth, usdc, e18, e18));
+        // Reversed token order is the same pair
+        state.update_pool(v2_pool(chain, Address::repeat_byte(0x11), usdc, weth, e18, e18));
+        state.update_pool(v2_pool(chain, Address::repeat_byte(0x12), weth, dai, e18, e18));
+        state.update_pool(v2_pool(ChainId::Base, Address::repeat_byte(0x13), weth, usdc, e18, e18));
+        if let Pool::UniswapV2(mut sushi) = v2_pool(chain, Address::repeat_byte(0x14), weth, usdc, e18, e18) {
+            sushi.dex = DexProtocol::SushiSwap;
+            state.update_pool(Pool::UniswapV2(sushi));
+        }
+        state.update_pool(Pool::Curve(defi_core::CurvePool {
+            address: Address::repeat_byte(0x15),
+            tokens: vec![dai, usdc, usdt],
+            balances: vec![alloy_primitives::U256::from(e18); 3],
+            decimals: vec![18, 6, 6],
+            a_parameter: alloy_primitives::U256::from(100),
+            fee: 4_000_000,
+            chain,
+            block_number: 1,
+        }));
+
+        let max_age = Duration::from_secs(60);
+        assert_eq!(
+            addresses(state.get_pools_for_pair(chain, usdc, weth, max_age)),
+            vec![Address::repeat_byte(0x10), Address::repeat_byte(0x11), Address::repeat_byte(0x14)]
+        );
+        // Any two tokens of a Curve pool
+        assert_eq!(addresses(state.get_pools_for_pair(chain, usdt, dai, max_age)), vec![Address::repeat_byte(0x15)]);
+        assert_eq!(
+            addresses(state.get_pools_for_pair(chain, usdc, dai, max_age)),
+            vec![Address::repeat_byte(0x15)]
+        );
+        assert!(state.get_pools_for_pair(chain, weth, usdt, max_age).is_empty());
+
+        assert_eq!(
+            addresses(state.get_pools_for_dex(chain, DexProtocol::UniswapV2, max_age)),
+            vec![Address::repeat_byte(0x10), Address::repeat_byte(0x11), Address::repeat_byte(0x12)]
+        );
+        assert_eq!(addresses(state.get_pools_for_dex(chain, DexProtocol::Curve, max_age)), vec![Address::repeat_byte(0x15)]);
+        assert_eq!(
+            addresses(state.get_pools_for_dex(ChainId::Base, DexProtocol::UniswapV2, max_age)),
+            vec![Address::repeat_byte(0x13)]
+        );
+
+        // Stale pools are skipped, and cleanup drops them from the indexes
+        assert!(state.get_pools_for_pair(chain, weth, usdc, Duration::ZERO).is_empty());
+        state.cleanup(Duration::ZERO, Duration::ZERO);
+        assert!(state.pair_pools.is_empty());
+        assert!(state.dex_pools.is_empty());
+    }
+
+    #[test]
+    fn test_deviating_dex_is_quarantined_until_it_reconverges() {
+        use crate::test_support::seed_v2_pool;
+
+        let chain = ChainId::Arbitrum;
+        let weth = get_token(chain, "WETH").unwrap().address;
+        let usdc = get_token(chain, "USDC").unwrap().address;
+        let (e18, e6) = (10u128.pow(18), 10u128.pow(6));
+        let max_age = Duration::from_secs(60);
+        let scanned = |state: &PriceState| {
+            let mut addresses: Vec<Address> = state.get_chain_pools(chain, max_age).iter().map(|e| e.pool.address()).collect();
+            addresses.sort();
+            addresses
+        };
+
+        let state = PriceState::new();
+        state.set_price_deviation(Some(PriceDeviationConfig::default()));
+
+        // 2000 USDC per WETH on two DEXes, half that on a third
+        let uni = seed_v2_pool(&state, chain, DexProtocol::UniswapV2, weth, usdc, 1_000 * e18, 2_000_000 * e6);
+        let sushi = seed_v2_pool(&state, chain, DexProtocol::SushiSwap, weth, usdc, 1_000 * e18, 1_000_000 * e6);
+        // Two DEXes can't say which one is off
+        assert!(!state.is_quarantined(chain, sushi));
+
+        let camelot = seed_v2_pool(&state, chain, DexProtocol::Camelot, weth, usdc, 500 * e18, 1_000_000 * e6);
+        let quarantined = state.quarantined_pools(chain);
+        assert_eq!(quarantined.len(), 1);
+        assert_eq!(quarantined[0].0, sushi);
+        assert!((4_990..=5_000).contains(&quarantined[0].1), "{}", quarantined[0].1);
+        let mut healthy = vec![uni, camelot];
+        healthy.sort();
+        assert_eq!(scanned(&state), healthy);
+        assert!(state.get_pools_for_pair(chain, weth, usdc, max_age).iter().all(|e| e.pool.address() != sushi));
+
+        // Still off on its next update, and ignored for pricing though freshest
+        seed_v2_pool(&state, chain, DexProtocol::SushiSwap, weth, usdc, 1_000 * e18, 1_000_000 * e6);
+        assert!(state.is_quarantined(chain, sushi));
+        assert!((state.get_usd_price(chain, weth).unwrap() - 2000.0).abs() < 1e-6);
+
+        // Back within 20% of the others and released
+        seed_v2_pool(&state, chain, DexProtocol::SushiSwap, weth, usdc, 1_000 * e18, 1_900_000 * e6);
+        assert!(state.quarantined_pools(chain).is_empty());
+        assert_eq!(scanned(&state).len(), 3);
+    }
+
+    #[test]
+    fn test_cleanup_ages_prices_and_pools_separately() {
+        let chain = ChainId::Ethereum;
+        let (weth, usdc) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let e18 = 10u128.pow(18);
+
+        let state = PriceState::new();
+        state.update_pool(v2_pool(chain, Address::repeat_byte(0x30), weth, usdc, e18, e18));
+        state.update_price(Price {
+            value: 3000.0,
+            token: weth,
+            quote_token: usdc,
+            dex: DexProtocol::UniswapV2,
+            chain,
+            block_number: 1,
+            timestamp_ms: 0,
+        });
+        std::thread::sleep(Duration::from_millis(20));
+
+        // Past the price limit but well within the pool limit
+        state.cleanup(Duration::from_millis(10), Duration::from_secs(60));
+        assert_eq!(state.stats().price_count, 0);
+        assert_eq!(state.stats().pool_count, 1);
+        assert_eq!(state.get_pools_for_pair(chain, weth, usdc, Duration::from_secs(60)).len(), 1);
+
+        state.cleanup(Duration::from_millis(10), Duration::from_millis(10));
+        assert_eq!(state.stats().pool_count, 0);
+        assert!(state.pair_pools.is_empty());
+    }
+
+    #[test]
+    fn test_cleanup_racing_updates_keeps_index_consistent() {
+        let state = Arc::new(PriceState::new());
+        let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let address = Address::repeat_byte(0x50);
+
+        let writer = {
+            let state = Arc::clone(&state);
+            std::thread::spawn(move || {
+                for _ in 0..5_000 {
+                    state.update_pool(v2_pool(ChainId::Ethereum, address, a, b, 1_000, 1_000));
+                }
+            })
+        };
+        for _ in 0..5_000 {
+            state.cleanup(Duration::from_secs(60), Duration::ZERO);
+        }
+        writer.join().unwrap();
+
+        // Whatever survived is still reachable through the pair index
+        let stored = state.get_pool(ChainId::Ethereum, address).is_some();
+        let indexed = !state.get_pools_for_pair(ChainId::Ethereum, a, b, Duration::MAX).is_empty();
+        assert_eq!(stored, indexed);
+    }
+
+    #[test]
+    fn test_dirty_pools_drain_per_chain() {
+        let state = PriceState::new();
+        let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let e18 = 10u128.pow(18);
+
+        state.update_pool(v2_pool(ChainId::Ethereum, Address::repeat_byte(0x20), a, b, e18, e18));
+        state.update_pool(v2_pool(ChainId::Ethereum, Address::repeat_byte(0x10), a, b, e18, e18));
+        state.update_pool(v2_pool(ChainId::Ethereum, Address::repeat_byte(0x10), a, b, e18, 2 * e18));
+        state.update_pool(v2_pool(ChainId::Base, Address::repeat_byte(0x30), a, b, e18, e18));
+
+        assert_eq!(
+            state.get_dirty_pools(ChainId::Ethereum),
+            vec![Address::repeat_byte(0x10), Address::repeat_byte(0x20)]
+        );
+        assert!(state.get_dirty_pools(ChainId::Ethereum).is_empty());
+        assert_eq!(state.get_dirty_pools(ChainId::Base), vec![Address::repeat_byte(0x30)]);
+    }
+
+    #[test]
+    fn test_set_v3_ticks_replaces_ticks_without_feed_heartbeat() {
+        let state = PriceState::new();
+        let chain = ChainId::Ethereum;
+        let (v2, v3) = (Address::repeat_byte(0x10), Address::repeat_byte(0x11));
+        state.update_pool(v2_pool(chain, v2, Address::repeat_byte(1), Address::repeat_byte(2), 1_000, 1_000));
+        state.update_pool(Pool::UniswapV3(UniswapV3Pool {
+            address: v3,
+            token0: Address::repeat_byte(1),
+            token1: Address::repeat_byte(2),
+            decimals0: 18,
+            decimals1: 18,
+            fee: UniswapV3Pool::FEE_MEDIUM,
+            tick_spacing: 60,
+            liquidity: 1_000,
+            sqrt_price_x96: U256::from(1u128 << 96),
+            tick: 0,
+            ticks: vec![],
+            chain,
+            block_number: 1,
+        }));
+        state.get_dirty_pools(chain);
+        let heartbeat = state.feed_updates.get(&(chain, DexProtocol::UniswapV3)).map(|r| *r);
+
+        let ticks = vec![
+            TickInfo { index: -60, liquidity_net: 500 },
+            TickInfo { index: 60, liquidity_net: -500 },
+        ];
+        assert!(state.set_v3_ticks(chain, v3, ticks.clone()));
+        assert!(!state.set_v3_ticks(chain, v2, ticks.clone()));
+        assert!(!state.set_v3_ticks(chain, Address::repeat_byte(0x12), ticks.clone()));
+
+        match state.get_pool(chain, v3).unwrap().pool {
+            Pool::UniswapV3(pool) => assert_eq!(pool.ticks, ticks),
+            other => panic!("expected a V3 pool, got {:?}", other),
+        }
+        assert_eq!(state.get_dirty_pools(chain), vec![v3]);
+        assert_eq!(state.feed_updates.get(&(chain, DexProtocol::UniswapV3)).map(|r| *r), heartbeat);
+    }
+
+    #[test]
+    fn test_older_block_updates_are_dropped() {
+        let chain = ChainId::Ethereum;
+        let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let address = Address::repeat_byte(0x40);
+        let at_block = |block_number: u64, reserve1: u128| match v2_pool(chain, address, a, b, 1_000, reserve1) {
+            Pool::UniswapV2(pool) => Pool::UniswapV2(UniswapV2Pool { block_number, ..pool }),
+            _ => unreachable!(),
+        };
+        let reserve1 = |state: &PriceState| state.get_pool(chain, address).unwrap().pool.reserves()[1].1;
+
+        let state = PriceState::new();
+        assert!(state.update_pool(at_block(11, 2_000)));
+        state.get_dirty_pools(chain);
+
+        // A late message from block 10 leaves block 11's reserves in place
+        assert!(!state.update_pool(at_block(10, 3_000)));
+        assert_eq!(reserve1(&state), U256::from(2_000u64));
+        assert_eq!(state.get_pool(chain, address).unwrap().pool.block_number(), 11);
+        assert!(state.get_dirty_pools(chain).is_empty());
+
+        // The same block or a later one replaces it
+        assert!(state.update_pool(at_block(11, 2_500)));
+        assert_eq!(reserve1(&state), U256::from(2_500u64));
+        assert!(state.update_pool(at_block(12, 4_000)));
+        assert_eq!(reserve1(&state), U256::from(4_000u64));
+
+        let price = |value: f64, block_number: u64| Price {
+            value,
+            token: a,
+            quote_token: b,
+            dex: DexProtocol::UniswapV2,
+            chain,
+            block_number,
+            timestamp_ms: 0,
+        };
+        let key = PriceKey::new(chain, a, b, DexProtocol::UniswapV2);
+        assert!(state.update_price(price(2000.0, 11)));
+        assert!(!state.update_price(price(1900.0, 10)));
+        let stored = state.get_price(&key).unwrap();
+        assert_eq!((stored.price.value, stored.block_number), (2000.0, 11));
+
+        assert!(state.update_price(price(2100.0, 12)));
+        assert_eq!(state.get_price(&key).unwrap().price.value, 2100.0);
+        // Dropped updates still count as feed activity
+        assert_eq!(state.stats().update_count, 3);
+    }
+
+    #[test]
+    fn test_chain_last_update_age() {
+        let state = PriceState::new();
+        assert!(state.chain_last_update_age(ChainId::Base).is_none());
+
+        state.update_block(ChainId::Base, 100);
+
+        let age = state.chain_last_update_age(ChainId::Base).unwrap();
+        assert!(age < Duration::from_secs(1));
+        assert_eq!(state.chains(), vec![ChainId::Base]);
+        assert!(state.chain_last_update_age(ChainId::Ethereum).is_none());
+    }
 }
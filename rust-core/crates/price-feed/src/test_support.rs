@@ -0,0 +1,87 @@
+//! Builders for populating a `PriceState` in tests
+//!
+//! Available to this crate's tests and, through the `testing` feature, to
+//! downstream crates. Pool addresses are derived from the chain, DEX and
+//! tokens, so the same seed always produces the same state.
+
+use alloy_primitives::{keccak256, Address, U256};
+
+use defi_core::{get_decimals, ChainId, DexProtocol, Pool, UniswapV2Pool};
+
+use crate::state::PriceState;
+
+/// Swap fee given to seeded V2 pools, in bps
+pub const DEFAULT_V2_FEE_BPS: u16 = 30;
+
+/// Deterministic address for a seeded pool
+pub fn pool_address(chain: ChainId, dex: DexProtocol, token0: Address, token1: Address) -> Address {
+    let mut data = Vec::with_capacity(72);
+    data.extend_from_slice(&chain.chain_id().to_be_bytes());
+    data.extend_from_slice(format!("{:?}", dex).as_bytes());
+    data.extend_from_slice(token0.as_slice());
+    data.extend_from_slice(token1.as_slice());
+    Address::from_slice(&keccak256(&data)[12..])
+}
+
+/// V2 pool holding `reserve0` and `reserve1` raw units, with decimals from
+/// the token registry and the default fee
+pub fn v2_pool(
+    chain: ChainId,
+    dex: DexProtocol,
+    token0: Address,
+    token1: Address,
+    reserve0: u128,
+    reserve1: u128,
+) -> Pool {
+    Pool::UniswapV2(UniswapV2Pool {
+        address: pool_address(chain, dex, token0, token1),
+        token0,
+        token1,
+        reserve0: U256::from(reserve0),
+        reserve1: U256::from(reserve1),
+        decimals0: get_decimals(chain, token0),
+        decimals1: get_decimals(chain, token1),
+        fee_bps: DEFAULT_V2_FEE_BPS,
+        chain,
+        dex,
+        block_number: 1,
+    })
+}
+
+/// Insert a V2 pool into `state` and return its address
+pub fn seed_v2_pool(
+    state: &PriceState,
+    chain: ChainId,
+    dex: DexProtocol,
+    token0: Address,
+    token1: Address,
+    reserve0: u128,
+    reserve1: u128,
+) -> Address {
+    let pool = v2_pool(chain, dex, token0, token1, reserve0, reserve1);
+    let address = pool.address();
+    state.update_pool(pool);
+    address
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_pools_are_deterministic() {
+        let chain = ChainId::Ethereum;
+        let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+
+        let state = PriceState::new();
+        let uni = seed_v2_pool(&state, chain, DexProtocol::UniswapV2, a, b, 1_000, 2_000);
+        let sushi = seed_v2_pool(&state, chain, DexProtocol::SushiSwap, a, b, 1_000, 2_000);
+
+        assert_ne!(uni, sushi);
+        assert_eq!(uni, pool_address(chain, DexProtocol::UniswapV2, a, b));
+
+        let entry = state.get_pool(chain, uni).unwrap();
+        assert_eq!(entry.pool.tokens(), Some((a, b)));
+        assert_eq!(entry.pool.dex(), DexProtocol::UniswapV2);
+    }
+}
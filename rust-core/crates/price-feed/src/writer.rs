@@ -0,0 +1,169 @@
+//! Bounded hand-off to a writer on the blocking pool
+//!
+//! `BackgroundWriter::send` only queues the record, so a slow disk never
+//! holds up the caller. A task on the blocking pool hands each record to a
+//! `RecordWriter`, draining whatever else is queued before paying for a
+//! flush. Records arriving while the buffer is full are dropped and
+//! counted, with a warning at most every `DROP_WARN_INTERVAL`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{mpsc, oneshot};
+use tracing::warn;
+
+/// Least time between two warnings about dropped records
+pub const DROP_WARN_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Destination a `BackgroundWriter` feeds. Failures are the writer's to
+/// log, since no caller is waiting on the result.
+pub trait RecordWriter: Send + 'static {
+    type Record: Send + 'static;
+
+    fn write(&mut self, record: Self::Record);
+
+    fn flush(&mut self);
+}
+
+enum WriterMessage<T> {
+    Record(T),
+    Flush(oneshot::Sender<()>),
+}
+
+#[derive(Debug, Default)]
+struct DropCounter {
+    dropped: AtomicU64,
+    /// Wall-clock time of the last drop warning (0 = never)
+    warned_at_ms: AtomicU64,
+}
+
+/// Non-blocking front of a `RecordWriter` running on the blocking pool
+pub struct BackgroundWriter<T> {
+    /// What the records are, for the drop warning
+    name: &'static str,
+    sender: mpsc::Sender<WriterMessage<T>>,
+    drops: Arc<DropCounter>,
+}
+
+impl<T: Send + 'static> BackgroundWriter<T> {
+    /// Start `writer` on the blocking pool behind a channel of `buffer`
+    /// records. Must be called from within a tokio runtime.
+    pub fn spawn<W>(name: &'static str, buffer: usize, writer: W) -> Self
+    where
+        W: RecordWriter<Record = T>,
+    {
+        let (sender, receiver) = mpsc::channel(buffer.max(1));
+        tokio::task::spawn_blocking(move || write_loop(writer, receiver));
+
+        Self {
+            name,
+            sender,
+            drops: Arc::new(DropCounter::default()),
+        }
+    }
+
+    /// Queue a record without waiting. Returns false if it was dropped.
+    pub fn send(&self, record: T) -> bool {
+        if self.sender.try_send(WriterMessage::Record(record)).is_ok() {
+            return true;
+        }
+
+        let dropped = self.drops.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+        let now = now_ms();
+        let warned_at = self.drops.warned_at_ms.load(Ordering::Relaxed);
+        if now.saturating_sub(warned_at) >= DROP_WARN_INTERVAL.as_millis() as u64
+            && self.drops.warned_at_ms
+                .compare_exchange(warned_at, now, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            warn!("{} writer is falling behind, {} records dropped so far", self.name, dropped);
+        }
+        false
+    }
+
+    /// Wait until everything sent so far has been written out
+    pub async fn flush(&self) {
+        let (ack, done) = oneshot::channel();
+        if self.sender.send(WriterMessage::Flush(ack)).await.is_ok() {
+            let _ = done.await;
+        }
+    }
+
+    /// Records dropped because the buffer was full
+    pub fn dropped(&self) -> u64 {
+        self.drops.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// Writer task body. Exits once the `BackgroundWriter` is dropped.
+fn write_loop<W: RecordWriter>(mut writer: W, mut receiver: mpsc::Receiver<WriterMessage<W::Record>>) {
+    while let Some(message) = receiver.blocking_recv() {
+        handle_message(&mut writer, message);
+        // Drain whatever else is queued before paying for a flush
+        while let Ok(message) = receiver.try_recv() {
+            handle_message(&mut writer, message);
+        }
+        writer.flush();
+    }
+}
+
+fn handle_message<W: RecordWriter>(writer: &mut W, message: WriterMessage<W::Record>) {
+    match message {
+        WriterMessage::Record(record) => writer.write(record),
+        WriterMessage::Flush(ack) => {
+            writer.flush();
+            let _ = ack.send(());
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Collects records once `gate` opens
+    struct Gated {
+        gate: std::sync::mpsc::Receiver<()>,
+        written: Arc<parking_lot::Mutex<Vec<u32>>>,
+    }
+
+    impl RecordWriter for Gated {
+        type Record = u32;
+
+        fn write(&mut self, record: u32) {
+            let _ = self.gate.recv();
+            self.written.lock().push(record);
+        }
+
+        fn flush(&mut self) {}
+    }
+
+    #[tokio::test]
+    async fn test_full_buffer_drops_and_counts() {
+        let (open, gate) = std::sync::mpsc::channel();
+        let written = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let writer = BackgroundWriter::spawn("test", 2, Gated { gate, written: Arc::clone(&written) });
+
+        // The writer holds the first record at the gate, the buffer takes
+        // two more and the rest are dropped
+        assert!(writer.send(0));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let sent: Vec<u32> = (1..6).filter(|&i| writer.send(i)).collect();
+        assert_eq!(sent, vec![1, 2]);
+        assert_eq!(writer.dropped(), 3);
+
+        for _ in 0..3 {
+            open.send(()).unwrap();
+        }
+        writer.flush().await;
+        assert_eq!(*written.lock(), vec![0, 1, 2]);
+    }
+}
@@ -62,6 +62,7 @@ export enum ExecutionStatus {
   CONFIRMED = 'EXECUTION_CONFIRMED',
   FAILED = 'EXECUTION_FAILED',
   REVERTED = 'EXECUTION_REVERTED',
+  SIMULATED = 'EXECUTION_SIMULATED',
 }
 
 export interface Token {
@@ -280,13 +281,20 @@ export class RustCoreClient extends EventEmitter {
   }
 
   /**
-   * Get price for a token
+   * Get price for a token, or null if the core has no price for it
    */
   async getPrice(tokenAddress: string, chain: Chain): Promise<PriceUpdate | null> {
-    return this.callUnary('getPrice', {
-      token_address: tokenAddress,
-      chain: this.chainToProto(chain),
-    });
+    try {
+      return await this.callUnary('getPrice', {
+        token_address: tokenAddress,
+        chain: this.chainToProto(chain),
+      });
+    } catch (err) {
+      if ((err as grpc.ServiceError).code === grpc.status.NOT_FOUND) {
+        return null;
+      }
+      throw err;
+    }
   }
 
   /**
@@ -452,13 +460,22 @@ export class RustCoreClient extends EventEmitter {
   }
 
   /**
-   * Start the arbitrage scanner
+   * Start the arbitrage scanner. A scanner that is already running counts
+   * as started.
    */
   async startScanner(chains: Chain[] = []): Promise<boolean> {
-    const response = await this.callUnary('startScanner', {
-      chains: chains.map(c => this.chainToProto(c)),
-    });
-    return response.success;
+    try {
+      const response = await this.callUnary('startScanner', {
+        chains: chains.map(c => this.chainToProto(c)),
+      });
+      return response.success;
+    } catch (err) {
+      if (this.errorReason(err as grpc.ServiceError) === 'SCANNER_ALREADY_RUNNING') {
+        structuredLogger.info('grpc', 'Scanner already running');
+        return true;
+      }
+      throw err;
+    }
   }
 
   /**
@@ -504,6 +521,22 @@ export class RustCoreClient extends EventEmitter {
     });
   }
 
+  /**
+   * Reason code from the JSON error detail the Rust core attaches to
+   * failed calls, e.g. "SCANNER_ALREADY_RUNNING"
+   */
+  private errorReason(err: grpc.ServiceError): string | undefined {
+    const [details] = err.metadata?.get('grpc-status-details-bin') ?? [];
+    if (!details) {
+      return undefined;
+    }
+    try {
+      return JSON.parse(details.toString()).reason;
+    } catch {
+      return undefined;
+    }
+  }
+
   private chainToProto(chain: Chain): number {
     const mapping: Record<Chain, number> = {
       [Chain.UNKNOWN]: 0,
@@ -547,6 +580,7 @@ export class RustCoreClient extends EventEmitter {
       3: ExecutionStatus.CONFIRMED,
       4: ExecutionStatus.FAILED,
       5: ExecutionStatus.REVERTED,
+      6: ExecutionStatus.SIMULATED,
     };
     return mapping[value] || ExecutionStatus.UNKNOWN;
   }